@@ -76,6 +76,30 @@ pub fn expand_lru_cache(attr: TokenStream, item: TokenStream) -> TokenStream {
 
     let cap = args.cap;
     let ttl_ms = args.ttl_ms;
+
+    if args.concurrent {
+        // Sharded cache: no global Mutex, concurrent callers only contend on one shard.
+        // A miss computes outside any lock, so two threads may compute the same key.
+        let wrapped = quote! {
+            #(#attrs)*
+            #vis #sig {
+                use rust_tools::cw::concurrent_lru_cache::ConcurrentLruCache;
+
+                static #cache_name: std::sync::LazyLock<ConcurrentLruCache<#key_ref_type, #ret_type>> =
+                    std::sync::LazyLock::new(|| ConcurrentLruCache::with_ttl(#cap, #ttl_ms));
+
+                if let Some(result) = #cache_name.get_cloned(&(#(#key_fields),*)) {
+                    return result;
+                }
+
+                let result = (|| #fn_body)();
+                #cache_name.put((#(#key_fields),*), result.clone());
+                result
+            }
+        };
+        return TokenStream::from(wrapped);
+    }
+
     let cache_init = if ttl_ms >= 0 {
         quote! { std::sync::Mutex::new(LruCache::with_ttl(#cap, #ttl_ms)) }
     } else {
@@ -111,15 +135,25 @@ pub fn expand_lru_cache(attr: TokenStream, item: TokenStream) -> TokenStream {
 struct CacheArgs {
     cap: usize,
     ttl_ms: i64,
+    /// Use `cw::ConcurrentLruCache` instead of `Mutex<LruCache>`.
+    concurrent: bool,
 }
 
 impl syn::parse::Parse for CacheArgs {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let mut cap = 100usize;
         let mut ttl_ms = -1i64;
+        let mut concurrent = false;
 
         while !input.is_empty() {
             let ident: Ident = input.parse()?;
+            if ident == "concurrent" {
+                concurrent = true;
+                if !input.is_empty() {
+                    let _: syn::Token![,] = input.parse()?;
+                }
+                continue;
+            }
             let _: syn::Token![=] = input.parse()?;
 
             match ident.to_string().as_str() {
@@ -131,7 +165,12 @@ impl syn::parse::Parse for CacheArgs {
                     let lit: LitInt = input.parse()?;
                     ttl_ms = lit.base10_parse()?;
                 }
-                _ => return Err(syn::Error::new(ident.span(), "expected `cap` or `ttl_ms`")),
+                _ => {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "expected `cap`, `ttl_ms` or `concurrent`",
+                    ));
+                }
             }
 
             if !input.is_empty() {
@@ -139,7 +178,11 @@ impl syn::parse::Parse for CacheArgs {
             }
         }
 
-        Ok(CacheArgs { cap, ttl_ms })
+        Ok(CacheArgs {
+            cap,
+            ttl_ms,
+            concurrent,
+        })
     }
 }
 
@@ -148,6 +191,7 @@ impl Default for CacheArgs {
         CacheArgs {
             cap: 100,
             ttl_ms: -1,
+            concurrent: false,
         }
     }
 }
@@ -1,8 +1,8 @@
 use std::path::PathBuf;
-use std::sync::{Arc, LazyLock, Mutex};
+use std::sync::{Arc, LazyLock};
 
 use dirs::config_dir;
-use rust_tools::cw::LruCache;
+use rust_tools::cw::ConcurrentLruCache;
 use rust_tools::cw::{SkipMap, SkipSet};
 
 use crate::ai::knowledge::storage::vector_store::{VectorEntry, VectorStore};
@@ -13,11 +13,11 @@ const SKILL_ROUTING_CATEGORY: &str = "skill-routing";
 const QUERY_EMBEDDING_CACHE_LIMIT: usize = 32;
 
 /// 短词条 query embedding 缓存：skill_match / 本地 skill 排序会频繁复用。
-/// 用项目自带的 cw::LruCache（FxHashMap O(1) 查询 + 双向链表 O(1) 淘汰），
-/// 替代之前 Vec 线性扫描 + remove(0) 双 O(n) 实现。
+/// 用项目自带的 cw::ConcurrentLruCache（O(1) 查询 + O(1) 淘汰，只需 &self），
+/// 替代之前 Vec 线性扫描 + remove(0) 双 O(n) 实现，并发调用也不再争用全局锁。
 /// Value 用 Arc 避免命中时 clone 整个 f32 向量。
-static QUERY_EMBEDDING_CACHE: LazyLock<Mutex<LruCache<String, Arc<Vec<f32>>>>> =
-    LazyLock::new(|| Mutex::new(LruCache::new(QUERY_EMBEDDING_CACHE_LIMIT)));
+static QUERY_EMBEDDING_CACHE: LazyLock<ConcurrentLruCache<String, Arc<Vec<f32>>>> =
+    LazyLock::new(|| ConcurrentLruCache::new(QUERY_EMBEDDING_CACHE_LIMIT));
 
 /// 同时缓存最近若干 skills_hash 对应的 VectorStore + snapshot。
 /// 单槽缓存在 user 来回切 agent（每个 agent 暴露不同 skill 子集）时会
@@ -32,8 +32,8 @@ struct CachedIndex {
     snapshot: Arc<Vec<(VectorEntry, SkillEmbeddingDocumentSection, String)>>,
 }
 
-static CACHED_INDEX: LazyLock<Mutex<LruCache<String, CachedIndex>>> =
-    LazyLock::new(|| Mutex::new(LruCache::new(CACHED_INDEX_CAPACITY)));
+static CACHED_INDEX: LazyLock<ConcurrentLruCache<String, CachedIndex>> =
+    LazyLock::new(|| ConcurrentLruCache::new(CACHED_INDEX_CAPACITY));
 
 #[derive(Debug, Clone)]
 pub struct SkillEmbeddingHit {
@@ -55,9 +55,7 @@ impl SkillEmbeddingIndex {
         let skills_hash = compute_skills_hash(documents);
         let section_count = documents.len() * 3;
 
-        if let Ok(mut cache) = CACHED_INDEX.lock()
-            && let Some(cached) = cache.get_ref(&skills_hash)
-        {
+        if let Some(cached) = CACHED_INDEX.get(&skills_hash) {
             return Ok(Self {
                 store: Arc::clone(&cached.store),
                 section_count: cached.section_count,
@@ -71,16 +69,14 @@ impl SkillEmbeddingIndex {
         sync_documents(&store, documents)?;
         let snapshot = Arc::new(load_snapshot(&store)?);
 
-        if let Ok(mut cache) = CACHED_INDEX.lock() {
-            cache.put(
-                skills_hash,
-                CachedIndex {
-                    store: Arc::clone(&store),
-                    section_count,
-                    snapshot: Arc::clone(&snapshot),
-                },
-            );
-        }
+        CACHED_INDEX.put(
+            skills_hash,
+            CachedIndex {
+                store: Arc::clone(&store),
+                section_count,
+                snapshot: Arc::clone(&snapshot),
+            },
+        );
 
         Ok(Self {
             store,
@@ -301,18 +297,14 @@ fn section_name(section: SkillEmbeddingDocumentSection) -> &'static str {
 
 fn cached_embed(store: &VectorStore, text: &str) -> Result<Vec<f32>, String> {
     let cache_key = normalize_cache_key(text);
-    if let Ok(mut cache) = QUERY_EMBEDDING_CACHE.lock()
-        && let Some(arc) = cache.get_ref(&cache_key)
-    {
+    if let Some(arc) = QUERY_EMBEDDING_CACHE.get(&cache_key) {
         return Ok((*arc).clone());
     }
 
     let embedding = store.embed_text(&cache_key)?;
     let arc = Arc::new(embedding);
 
-    if let Ok(mut cache) = QUERY_EMBEDDING_CACHE.lock() {
-        cache.put(cache_key, Arc::clone(&arc));
-    }
+    QUERY_EMBEDDING_CACHE.put(cache_key, Arc::clone(&arc));
 
     Ok((*arc).clone())
 }
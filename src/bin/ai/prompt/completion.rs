@@ -222,6 +222,17 @@ impl CommandCompleter {
             .collect()
     }
 
+    /// 前缀匹配（排序）；无命中时退回到编辑距离匹配（按距离从近到远），
+    /// 例如 `/hsitory` → `/history`。过短的 token 不做容错，避免噪声候选。
+    fn trie_words_or_fuzzy(trie: &Trie, token: &str) -> Vec<String> {
+        let mut words = trie.words_with_prefix(token);
+        if !words.is_empty() {
+            words.sort();
+            return words;
+        }
+        fuzzy_matches(trie, token)
+    }
+
     fn model_handle(model: &crate::ai::model_names::ModelDef) -> String {
         crate::ai::model_names::model_handle(model)
     }
@@ -486,15 +497,11 @@ impl CommandCompleter {
 
         let candidates = if token_start == 0 {
             // 用 Trie 做前缀匹配："/" / ":" 走命令 Trie，"--" / "-" 走选项 Trie；
-            // 排序结果以保证确定性（HashMap 迭代无序）。
+            // 排序结果以保证确定性（HashMap 迭代无序）。前缀无命中时按编辑距离容错。
             if token.starts_with('/') || token.starts_with(':') {
-                let mut words = COMMANDS_TRIE.words_with_prefix(token);
-                words.sort();
-                Self::plain_candidates(words)
+                Self::plain_candidates(Self::trie_words_or_fuzzy(&COMMANDS_TRIE, token))
             } else if token.starts_with('-') {
-                let mut words = FLAGS_TRIE.words_with_prefix(token);
-                words.sort();
-                Self::plain_candidates(words)
+                Self::plain_candidates(Self::trie_words_or_fuzzy(&FLAGS_TRIE, token))
             } else {
                 Vec::new()
             }
@@ -629,6 +636,12 @@ fn complete_skill_reference(before: &str) -> Option<(usize, Vec<CompletionCandid
                 set.insert(word);
             }
         }
+        // 前缀全部落空时按编辑距离容错（`@skill:reveiw` → `review`）。
+        if set.is_empty() {
+            for filter in &filters {
+                set.extend(fuzzy_matches(&trie, filter));
+            }
+        }
         Some(set)
     };
 
@@ -647,6 +660,20 @@ fn complete_skill_reference(before: &str) -> Option<(usize, Vec<CompletionCandid
     Some((token_start, candidates))
 }
 
+/// 编辑距离容错匹配：少于 4 个字符不容错，6 个字符以内允许 1 处编辑，更长允许 2 处
+/// （相邻字母对调算 2 处）。
+fn fuzzy_matches(trie: &Trie, token: &str) -> Vec<String> {
+    let max_distance = match token.chars().count() {
+        0..=3 => return Vec::new(),
+        4..=6 => 1,
+        _ => 2,
+    };
+    trie.fuzzy_search(token, max_distance)
+        .into_iter()
+        .map(|(word, _)| word)
+        .collect()
+}
+
 /// 解析 `@` 之后的内容，判断是否为技能引用并返回所有可能的过滤前缀（小写）。
 /// 返回 `None` 表示不是技能引用 token；返回的 Vec 中含空串表示"列出全部"。
 ///
@@ -1188,6 +1215,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn trie_command_completion_tolerates_typos() {
+        // /hsitory → /history（前缀无命中时按编辑距离容错）
+        let (_, candidates) = CommandCompleter::complete_for_line("/hsitory", 8);
+        let replacements: Vec<_> = candidates.iter().map(|c| c.replacement.as_str()).collect();
+        assert_eq!(replacements.first(), Some(&"/history"), "{replacements:?}");
+        // --modle → --model
+        let (_, candidates) = CommandCompleter::complete_for_line("--modle", 7);
+        assert!(candidates.iter().any(|c| c.replacement == "--model"));
+        // 过短的 token 不容错
+        let (_, candidates) = CommandCompleter::complete_for_line("/x", 2);
+        assert!(candidates.is_empty());
+    }
+
     #[test]
     fn trie_flag_completion_expands_model_prefix() {
         // --mod → --model（选项 Trie 前缀匹配）
@@ -1,14 +1,34 @@
+use std::cmp::Ordering;
 use std::io::{self, IsTerminal, Read};
+use std::path::{Path, PathBuf};
 
 use clap::{ArgAction, Parser as ClapParser};
 use rust_decimal::Decimal;
-use rust_decimal::RoundingStrategy;
-use rust_decimal::prelude::*;
+use rust_tools::commonw::utils::expanduser;
+use rust_tools::strw::calc::precise;
+use rustyline::{DefaultEditor, error::ReadlineError};
+
+#[path = "c/bits.rs"]
+mod bits;
+#[path = "c/session.rs"]
+mod session;
+#[path = "c/units.rs"]
+mod units;
+
+use bits::IntMode;
+use session::{Session, Statement, parse_statement, split_statements};
+use units::Dim;
 
 const DEFAULT_PREC: usize = 16;
 
-const PI_DIGITS: &str = "3.14159265358979323846264338327950288419716939937510";
-const E_DIGITS: &str = "2.71828182845904523536028747135266249775724709369995";
+const HISTORY_FILE: &str = "~/.c_history";
+
+/// 带单位的中间结果、常数和角度换算比 `--prec` 多算的位数，只在显示时舍入，
+/// 避免 `km/h` 这类换算系数或 `pi` 先被截断
+const GUARD_DIGITS: usize = 10;
+
+/// 用户函数的最大嵌套调用深度，防止递归定义耗尽栈
+const MAX_CALL_DEPTH: usize = 64;
 
 #[derive(ClapParser, Debug)]
 #[command(about = "Command-line calculator (c)", long_about = None)]
@@ -25,20 +45,79 @@ struct Cli {
         short = 'f',
         long = "file",
         default_value = "",
-        help = "read expression from file"
+        help = "read statements from file (one per line or separated by ';')"
     )]
     file: String,
 
-    #[arg(long = "prec", default_value_t = DEFAULT_PREC, help = "decimal digits after division/float functions (default: 16)")]
+    #[arg(
+        short = 's',
+        long = "session",
+        value_name = "FILE",
+        help = "load variables/functions from FILE and save them back after running"
+    )]
+    session: Option<PathBuf>,
+
+    #[arg(long = "prec", default_value_t = DEFAULT_PREC, help = "decimal digits for division, roots, logarithms and trig, at any precision (default: 16)")]
     prec: usize,
 
     #[arg(long = "deg", action = ArgAction::SetTrue, help = "use degrees for sin/cos/tan and inverse trig")]
     degree: bool,
 
+    #[arg(
+        short = 'p',
+        long = "programmer",
+        action = ArgAction::SetTrue,
+        help = "integer mode: '^' is xor, '**' is power, '/' truncates"
+    )]
+    programmer: bool,
+
+    #[arg(
+        long = "bits",
+        value_name = "N",
+        value_parser = clap::value_parser!(u32).range(1..=65536),
+        help = "wrap every result to N bits (implies -p)"
+    )]
+    bits: Option<u32>,
+
+    #[arg(long = "signed", action = ArgAction::SetTrue, requires = "bits", help = "treat --bits values as two's complement")]
+    signed: bool,
+
+    #[arg(
+        long = "base",
+        value_name = "BASES",
+        value_delimiter = ',',
+        value_parser = clap::value_parser!(u32).range(2..=36),
+        help = "print integer results in each base, e.g. 16,2 (implies -p)"
+    )]
+    base: Vec<u32>,
+
     #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
     args: Vec<String>,
 }
 
+/// 求值选项
+#[derive(Debug, Clone, Copy)]
+struct Settings {
+    prec: usize,
+    degree: bool,
+    /// 程序员模式：只做整数运算，按位宽回绕
+    int_mode: Option<IntMode>,
+}
+
+impl Settings {
+    fn from_cli(cli: &Cli) -> Self {
+        let programmer = cli.programmer || cli.bits.is_some() || !cli.base.is_empty();
+        Settings {
+            prec: cli.prec,
+            degree: cli.degree,
+            int_mode: programmer.then_some(IntMode {
+                bits: cli.bits,
+                signed: cli.signed,
+            }),
+        }
+    }
+}
+
 fn main() {
     if let Err(err) = run() {
         eprintln!("{err}");
@@ -61,38 +140,160 @@ fn normalize_args(args: impl Iterator<Item = String>) -> Vec<String> {
 
 fn run() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse_from(normalize_args(std::env::args()));
+    let settings = Settings::from_cli(&cli);
+    let mut session = match &cli.session {
+        Some(path) => Session::load(path)?,
+        None => Session::default(),
+    };
 
-    let expr = if !cli.expr.trim().is_empty() {
-        normalize_expression(&cli.expr)
+    let input = if !cli.expr.trim().is_empty() {
+        cli.expr.clone()
     } else if !cli.file.trim().is_empty() {
-        let contents = std::fs::read_to_string(&cli.file)?;
-        normalize_expression(&contents)
+        std::fs::read_to_string(&cli.file)?
     } else if !cli.args.is_empty() {
-        normalize_expression(&cli.args.join(" "))
+        cli.args.join(" ")
     } else {
         let stdin = io::stdin();
         if stdin.is_terminal() {
-            return Err("missing expression\nusage: c [options] <expr>\n  -prec N   decimal precision (default 16)\n  -deg      use degrees for trig".into());
+            run_repl(&mut session, &cli, &settings);
+            return save_session(&session, cli.session.as_deref());
         }
         let mut input = String::new();
         io::stdin().read_to_string(&mut input)?;
-        normalize_expression(&input)
+        join_lines(&input)
     };
 
-    if expr.is_empty() {
+    let statements = split_statements(&input);
+    if statements.is_empty() {
         return Err("empty expression".into());
     }
+    for stmt in &statements {
+        if let Some(result) = execute_statement(&mut session, stmt, &settings)? {
+            println!("{}", format_result(&result, &cli.base, &settings)?);
+        }
+    }
+    save_session(&session, cli.session.as_deref())
+}
+
+/// `--base` 指定进制时按每个进制各输出一行
+fn format_result(result: &str, bases: &[u32], settings: &Settings) -> Result<String, String> {
+    match settings.int_mode {
+        Some(mode) if !bases.is_empty() => bits::format_bases(result, bases, mode),
+        _ => Ok(result.to_string()),
+    }
+}
 
-    let result = evaluate_expression(&expr, cli.prec, cli.degree)?;
-    println!("{result}");
+fn save_session(session: &Session, path: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(path) = path {
+        session.save(path)?;
+    }
+    Ok(())
+}
+
+/// 交互模式：逐行执行语句，出错只打印不退出；`:vars` 列出变量和函数，`:q` 退出
+fn run_repl(session: &mut Session, cli: &Cli, settings: &Settings) {
+    let Ok(mut editor) = DefaultEditor::new() else {
+        eprintln!("failed to start line editor");
+        return;
+    };
+    let history = PathBuf::from(expanduser(HISTORY_FILE).as_ref());
+    let _ = editor.load_history(&history);
+
+    loop {
+        let line = match editor.readline("> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(_) => break,
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line);
+        match line {
+            ":q" | ":quit" | "exit" | "quit" => break,
+            ":vars" => {
+                print!("{}", session.to_text().trim_start_matches("# c session\n"));
+                continue;
+            }
+            _ => {}
+        }
+        for stmt in split_statements(line) {
+            match execute_statement(session, &stmt, settings).and_then(|result| {
+                result
+                    .map(|r| format_result(&r, &cli.base, settings))
+                    .transpose()
+            }) {
+                Ok(Some(result)) => println!("{result}"),
+                Ok(None) => {}
+                Err(err) => eprintln!("error: {err}"),
+            }
+        }
+    }
+    let _ = editor.save_history(&history);
+}
+
+/// 执行一条语句：赋值和函数定义写入会话，表达式的结果同时记为 `ans`
+fn execute_statement(
+    session: &mut Session,
+    stmt: &str,
+    settings: &Settings,
+) -> Result<Option<String>, String> {
+    match parse_statement(stmt)? {
+        Statement::Assign { name, expr } => {
+            check_name_available(&name)?;
+            let value =
+                evaluate_expression(&normalize_expression(&expr), &Env::new(session, settings))?;
+            session.assign(name, value);
+            Ok(None)
+        }
+        Statement::Define { name, mut fun } => {
+            check_name_available(&name)?;
+            for p in &fun.params {
+                check_name_available(p)?;
+            }
+            fun.body = normalize_expression(&fun.body);
+            tokenize(&fun.body, &|_| false)?;
+            session.define(name, fun);
+            Ok(None)
+        }
+        Statement::Expr(expr) => {
+            let expr = normalize_expression(&expr);
+            if expr.is_empty() {
+                return Err("empty expression".into());
+            }
+            let result = evaluate_expression(&expr, &Env::new(session, settings))?;
+            session.ans = Some(result.clone());
+            Ok(Some(result))
+        }
+    }
+}
+
+/// 内置函数、常量、`ans` 和单位换算关键字不能被重新定义
+fn check_name_available(name: &str) -> Result<(), String> {
+    if is_function_name(name)
+        || resolve_constant(name, DEFAULT_PREC).is_some()
+        || matches!(name, "ans" | "to" | "in")
+    {
+        return Err(format!("{name} is a reserved name"));
+    }
     Ok(())
 }
 
 // ─── Normalise ────────────────────────────────────────────────────────────────
 
+/// 管道输入与以前一样把多行拼成一个表达式（`printf '1+\n2' | c` 得到 3），
+/// 语句只按 `;` 分隔；`#` 注释仍按行去掉
+fn join_lines(input: &str) -> String {
+    let lines: Vec<&str> = input
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or(""))
+        .collect();
+    normalize_expression(&lines.join(" "))
+}
+
 fn normalize_expression(input: &str) -> String {
     let s = input.trim();
-    let s = s.replace("**", "^");
     let s = s.replace(['\r', '\n', '\t'], " ");
     s.trim().to_string()
 }
@@ -107,6 +308,8 @@ enum TokenKind {
     LParen,
     RParen,
     Comma,
+    /// 单位换算关键字 `to` / `in`
+    Convert,
     Eof,
 }
 
@@ -114,20 +317,32 @@ enum TokenKind {
 struct Token {
     kind: TokenKind,
     value: String,
+    /// 原始文本：单位区分大小写（`MB` 与 `mb`）；隐式插入的乘号为空
+    raw: String,
 }
 
 impl Token {
-    fn eof() -> Self {
+    fn new(kind: TokenKind, value: impl Into<String>) -> Self {
+        let value = value.into();
         Token {
-            kind: TokenKind::Eof,
-            value: String::new(),
+            kind,
+            raw: value.clone(),
+            value,
         }
     }
+
+    fn eof() -> Self {
+        Token::new(TokenKind::Eof, "")
+    }
+
+    fn is_implicit_mul(&self) -> bool {
+        self.kind == TokenKind::Operator && self.raw.is_empty()
+    }
 }
 
 // ─── Tokenize ─────────────────────────────────────────────────────────────────
 
-fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+fn tokenize(input: &str, is_func: &dyn Fn(&str) -> bool) -> Result<Vec<Token>, String> {
     let bytes = input.as_bytes();
     let n = bytes.len();
     let mut tokens = Vec::new();
@@ -139,6 +354,25 @@ fn tokenize(input: &str) -> Result<Vec<Token>, String> {
             b' ' => {
                 i += 1;
             }
+            b'0' if i + 2 < n
+                && matches!(bytes[i + 1], b'x' | b'X' | b'o' | b'O' | b'b' | b'B')
+                && (bytes[i + 2] as char).is_digit(radix_of(bytes[i + 1])) =>
+            {
+                let start = i;
+                let radix = radix_of(bytes[i + 1]);
+                i += 2;
+                while i < n && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                let raw = &input[start..i];
+                let value = bits::parse_radix_literal(&raw[2..], radix)
+                    .ok_or_else(|| format!("invalid number {raw:?}"))?;
+                tokens.push(Token {
+                    kind: TokenKind::Number,
+                    value,
+                    raw: raw.to_string(),
+                });
+            }
             b'0'..=b'9' | b'.' => {
                 let start = i;
                 let mut dot_seen = ch == b'.';
@@ -166,10 +400,7 @@ fn tokenize(input: &str) -> Result<Vec<Token>, String> {
                 if lit.ends_with('.') {
                     lit.push('0');
                 }
-                tokens.push(Token {
-                    kind: TokenKind::Number,
-                    value: clean_number(&lit),
-                });
+                tokens.push(Token::new(TokenKind::Number, clean_number(&lit)));
             }
             b'a'..=b'z' | b'A'..=b'Z' | b'_' => {
                 let start = i;
@@ -177,37 +408,37 @@ fn tokenize(input: &str) -> Result<Vec<Token>, String> {
                 while i < n && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
                     i += 1;
                 }
+                let raw = &input[start..i];
+                let value = raw.to_lowercase();
+                let kind = if value == "to" || value == "in" {
+                    TokenKind::Convert
+                } else {
+                    TokenKind::Identifier
+                };
                 tokens.push(Token {
-                    kind: TokenKind::Identifier,
-                    value: input[start..i].to_lowercase(),
+                    kind,
+                    value,
+                    raw: raw.to_string(),
                 });
             }
-            b'+' | b'-' | b'*' | b'/' | b'%' | b'^' => {
-                tokens.push(Token {
-                    kind: TokenKind::Operator,
-                    value: (ch as char).to_string(),
-                });
+            b'*' | b'<' | b'>' if bytes.get(i + 1) == Some(&ch) => {
+                tokens.push(Token::new(TokenKind::Operator, &input[i..i + 2]));
+                i += 2;
+            }
+            b'+' | b'-' | b'*' | b'/' | b'%' | b'^' | b'&' | b'|' | b'~' => {
+                tokens.push(Token::new(TokenKind::Operator, (ch as char).to_string()));
                 i += 1;
             }
             b'(' => {
-                tokens.push(Token {
-                    kind: TokenKind::LParen,
-                    value: "(".into(),
-                });
+                tokens.push(Token::new(TokenKind::LParen, "("));
                 i += 1;
             }
             b')' => {
-                tokens.push(Token {
-                    kind: TokenKind::RParen,
-                    value: ")".into(),
-                });
+                tokens.push(Token::new(TokenKind::RParen, ")"));
                 i += 1;
             }
             b',' => {
-                tokens.push(Token {
-                    kind: TokenKind::Comma,
-                    value: ",".into(),
-                });
+                tokens.push(Token::new(TokenKind::Comma, ","));
                 i += 1;
             }
             _ => return Err(format!("unexpected character {:?}", ch as char)),
@@ -215,7 +446,15 @@ fn tokenize(input: &str) -> Result<Vec<Token>, String> {
     }
 
     tokens.push(Token::eof());
-    Ok(insert_implicit_mul(tokens))
+    Ok(insert_implicit_mul(tokens, is_func))
+}
+
+fn radix_of(prefix: u8) -> u32 {
+    match prefix.to_ascii_lowercase() {
+        b'x' => 16,
+        b'o' => 8,
+        _ => 2,
+    }
 }
 
 fn ends_primary(tok: &Token) -> bool {
@@ -232,7 +471,7 @@ fn starts_primary(tok: &Token) -> bool {
     )
 }
 
-fn insert_implicit_mul(tokens: Vec<Token>) -> Vec<Token> {
+fn insert_implicit_mul(tokens: Vec<Token>, is_func: &dyn Fn(&str) -> bool) -> Vec<Token> {
     if tokens.len() <= 1 {
         return tokens;
     }
@@ -245,13 +484,14 @@ fn insert_implicit_mul(tokens: Vec<Token>) -> Vec<Token> {
             // don't insert between function-name and its '('
             if cur.kind == TokenKind::Identifier
                 && nxt.kind == TokenKind::LParen
-                && is_function_name(&cur.value)
+                && is_func(&cur.value)
             {
                 continue;
             }
             res.push(Token {
                 kind: TokenKind::Operator,
                 value: "*".into(),
+                raw: String::new(),
             });
         }
     }
@@ -261,20 +501,145 @@ fn insert_implicit_mul(tokens: Vec<Token>) -> Vec<Token> {
 
 // ─── Parser ───────────────────────────────────────────────────────────────────
 
-struct ExprParser {
+/// 带量纲的数值：数值以国际单位制基本单位（信息量以字节）表示
+#[derive(Debug, Clone, PartialEq)]
+struct Quantity {
+    value: String,
+    dim: Dim,
+}
+
+impl Quantity {
+    fn number(value: impl Into<String>) -> Self {
+        Quantity {
+            value: value.into(),
+            dim: Dim::NONE,
+        }
+    }
+
+    fn unit(factor: Decimal, dim: Dim) -> Self {
+        Quantity {
+            value: clean_number(&factor.to_string()),
+            dim,
+        }
+    }
+
+    fn rounded(&self, prec: usize) -> Result<Quantity, String> {
+        let value = precise::round(&self.value, prec)
+            .ok_or_else(|| format!("invalid number {:?}", self.value))?;
+        Ok(Quantity {
+            value,
+            dim: self.dim,
+        })
+    }
+}
+
+impl std::fmt::Display for Quantity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.dim.is_none() {
+            f.write_str(&clean_number(&self.value))
+        } else {
+            write!(f, "{} {}", clean_number(&self.value), self.dim)
+        }
+    }
+}
+
+/// 求值环境：精度、角度单位、会话中的变量和函数，以及用户函数调用时绑定的参数
+struct Env<'a> {
+    session: &'a Session,
+    prec: usize,
+    degree: bool,
+    int_mode: Option<IntMode>,
+    locals: Vec<(String, Quantity)>,
+    depth: usize,
+}
+
+impl<'a> Env<'a> {
+    fn new(session: &'a Session, settings: &Settings) -> Self {
+        Env {
+            session,
+            prec: settings.prec,
+            degree: settings.degree,
+            int_mode: settings.int_mode,
+            locals: Vec::new(),
+            depth: 0,
+        }
+    }
+
+    fn is_function(&self, name: &str) -> bool {
+        is_function_name(name) || self.session.funcs.contains_key(name)
+    }
+
+    fn has_variable(&self, name: &str) -> bool {
+        self.locals.iter().any(|(k, _)| k == name)
+            || self.session.vars.contains_key(name)
+            || (name == "ans" && self.session.ans.is_some())
+    }
+
+    /// 依次查找函数参数、会话变量和 `ans`
+    ///
+    /// 会话中保存的是显示结果，如 `5000 m`，读取时重新解析为带量纲的值。
+    fn variable(&self, name: &str) -> Option<Result<Quantity, String>> {
+        if let Some((_, v)) = self.locals.iter().find(|(k, _)| k == name) {
+            return Some(Ok(v.clone()));
+        }
+        let text = match self.session.vars.get(name) {
+            Some(v) => v,
+            None if name == "ans" => self.session.ans.as_ref()?,
+            None => return None,
+        };
+        // 单位不能被会话中的同名变量遮住
+        let empty = Session::default();
+        let settings = Settings {
+            prec: self.prec,
+            degree: self.degree,
+            int_mode: None,
+        };
+        Some(evaluate_quantity(text, &Env::new(&empty, &settings)))
+    }
+
+    /// 函数体只能看到自己的参数和会话中的全局变量
+    fn call_user(&self, name: &str, args: &[Quantity]) -> Option<Result<Quantity, String>> {
+        let fun = self.session.funcs.get(name)?;
+        if args.len() != fun.params.len() {
+            return Some(Err(format!(
+                "{name} expects {} argument(s)",
+                fun.params.len()
+            )));
+        }
+        if self.depth >= MAX_CALL_DEPTH {
+            return Some(Err(format!("{name}: call depth exceeds {MAX_CALL_DEPTH}")));
+        }
+        let env = Env {
+            session: self.session,
+            prec: self.prec,
+            degree: self.degree,
+            int_mode: self.int_mode,
+            locals: fun
+                .params
+                .iter()
+                .cloned()
+                .zip(args.iter().cloned())
+                .collect(),
+            depth: self.depth + 1,
+        };
+        Some(evaluate_quantity(&fun.body, &env))
+    }
+}
+
+struct ExprParser<'a> {
     tokens: Vec<Token>,
     pos: usize,
+    env: &'a Env<'a>,
     prec: usize,
-    degree: bool,
 }
 
-impl ExprParser {
-    fn new(tokens: Vec<Token>, prec: usize, degree: bool) -> Self {
+impl<'a> ExprParser<'a> {
+    fn new(tokens: Vec<Token>, env: &'a Env<'a>) -> Self {
         ExprParser {
             tokens,
             pos: 0,
-            prec,
-            degree,
+            env,
+            prec: env.prec,
         }
     }
 
@@ -290,22 +655,85 @@ impl ExprParser {
         }
     }
 
-    fn parse(&mut self) -> Result<String, String> {
-        let result = self.parse_add_sub()?;
+    /// 解析整条表达式，返回结果和按 `to`/`in` 换算后的显示文本
+    fn parse(&mut self) -> Result<(Quantity, String), String> {
+        let result = self.parse_bitwise(0)?;
+        let display = if self.current().kind == TokenKind::Convert {
+            self.advance();
+            let start = self.pos;
+            let target = self.parse_mul_div()?;
+            let text: String = self.tokens[start..self.pos]
+                .iter()
+                .map(|t| if t.is_implicit_mul() { " " } else { &t.raw })
+                .collect();
+            if target.dim != result.dim {
+                return Err(format!("cannot convert {} to {text}", dim_name(result.dim)));
+            }
+            let value = apply_binary(&result.value, &target.value, b'/', self.prec + GUARD_DIGITS)?;
+            format!("{} {text}", Quantity::number(value).rounded(self.prec)?)
+        } else {
+            result.rounded(self.prec)?.to_string()
+        };
         if self.current().kind != TokenKind::Eof {
-            return Err(format!("unexpected token {:?}", self.current().value));
+            return Err(format!("unexpected token {:?}", self.current().raw));
         }
-        Ok(clean_number(&result))
+        Ok((result, display))
     }
 
-    fn parse_add_sub(&mut self) -> Result<String, String> {
+    fn at_operator(&self, ops: &[&str]) -> Option<String> {
+        let tok = self.current();
+        (tok.kind == TokenKind::Operator && ops.contains(&tok.value.as_str()))
+            .then(|| tok.value.clone())
+    }
+
+    /// 位运算优先级从低到高：`|`、`^`、`&`、移位，都低于加减；
+    /// `^` 只在程序员模式下是异或，否则在 [`Self::parse_power`] 中作为乘方
+    fn parse_bitwise(&mut self, level: usize) -> Result<Quantity, String> {
+        const LEVELS: [&[&str]; 4] = [&["|"], &["^"], &["&"], &["<<", ">>"]];
+        let Some(ops) = LEVELS.get(level) else {
+            return self.parse_add_sub();
+        };
+        let mut left = self.parse_bitwise(level + 1)?;
+        while let Some(op) = self.at_operator(ops) {
+            self.advance();
+            let right = self.parse_bitwise(level + 1)?;
+            if !left.dim.is_none() || !right.dim.is_none() {
+                return Err(format!("'{op}' requires plain integers"));
+            }
+            let value = bits::bit_op(&left.value, &right.value, &op)?;
+            left = self.finish(Quantity::number(value))?;
+        }
+        Ok(left)
+    }
+
+    /// 程序员模式下每个中间结果都必须是整数，并按位宽回绕
+    fn finish(&self, value: Quantity) -> Result<Quantity, String> {
+        let Some(mode) = self.env.int_mode else {
+            return Ok(value);
+        };
+        if !value.dim.is_none() {
+            return Err("programmer mode does not support units".into());
+        }
+        Ok(Quantity::number(mode.wrap(&value.value)?))
+    }
+
+    fn binary(&self, left: &Quantity, right: &Quantity, op: u8) -> Result<Quantity, String> {
+        let result = if op == b'/' && self.env.int_mode.is_some() {
+            Quantity::number(bits::trunc_div(&left.value, &right.value)?)
+        } else {
+            apply_quantity(left, right, op, self.prec)?
+        };
+        self.finish(result)
+    }
+
+    fn parse_add_sub(&mut self) -> Result<Quantity, String> {
         let mut left = self.parse_mul_div()?;
         loop {
             let tok = self.current().clone();
             if tok.kind == TokenKind::Operator && (tok.value == "+" || tok.value == "-") {
                 self.advance();
                 let right = self.parse_mul_div()?;
-                left = apply_binary(&left, &right, tok.value.as_bytes()[0], self.prec)?;
+                left = self.binary(&left, &right, tok.value.as_bytes()[0])?;
             } else {
                 break;
             }
@@ -313,7 +741,7 @@ impl ExprParser {
         Ok(left)
     }
 
-    fn parse_mul_div(&mut self) -> Result<String, String> {
+    fn parse_mul_div(&mut self) -> Result<Quantity, String> {
         let mut left = self.parse_unary()?;
         loop {
             let tok = self.current().clone();
@@ -322,10 +750,10 @@ impl ExprParser {
             {
                 self.advance();
                 let right = self.parse_unary()?;
-                left = apply_binary(&left, &right, tok.value.as_bytes()[0], self.prec)?;
+                left = self.binary(&left, &right, tok.value.as_bytes()[0])?;
             } else if starts_primary(&tok) {
                 let right = self.parse_unary()?;
-                left = apply_binary(&left, &right, b'*', self.prec)?;
+                left = self.binary(&left, &right, b'*')?;
             } else {
                 break;
             }
@@ -333,18 +761,22 @@ impl ExprParser {
         Ok(left)
     }
 
-    fn parse_power(&mut self) -> Result<String, String> {
+    fn parse_power(&mut self) -> Result<Quantity, String> {
         let left = self.parse_primary()?;
-        let tok = self.current().clone();
-        if tok.kind == TokenKind::Operator && tok.value == "^" {
+        let power_ops: &[&str] = if self.env.int_mode.is_some() {
+            &["**"]
+        } else {
+            &["**", "^"]
+        };
+        if self.at_operator(power_ops).is_some() {
             self.advance();
             let right = self.parse_unary()?;
-            return apply_binary(&left, &right, b'^', self.prec);
+            return self.finish(pow_quantity(&left, &right, self.prec)?);
         }
         Ok(left)
     }
 
-    fn parse_unary(&mut self) -> Result<String, String> {
+    fn parse_unary(&mut self) -> Result<Quantity, String> {
         let tok = self.current().clone();
         if tok.kind == TokenKind::Operator {
             match tok.value.as_str() {
@@ -355,7 +787,18 @@ impl ExprParser {
                 "-" => {
                     self.advance();
                     let v = self.parse_unary()?;
-                    return Ok(negate(&v));
+                    return self.finish(Quantity {
+                        value: negate(&v.value),
+                        dim: v.dim,
+                    });
+                }
+                "~" => {
+                    self.advance();
+                    let v = self.parse_unary()?;
+                    if !v.dim.is_none() {
+                        return Err("'~' requires a plain integer".into());
+                    }
+                    return self.finish(Quantity::number(bits::bit_not(&v.value)?));
                 }
                 _ => {}
             }
@@ -363,42 +806,117 @@ impl ExprParser {
         self.parse_power()
     }
 
-    fn parse_primary(&mut self) -> Result<String, String> {
+    fn parse_primary(&mut self) -> Result<Quantity, String> {
+        let value = self.parse_operand()?;
+        self.finish(value)
+    }
+
+    fn parse_operand(&mut self) -> Result<Quantity, String> {
         let tok = self.current().clone();
         match tok.kind {
             TokenKind::Number => {
                 self.advance();
-                Ok(tok.value.clone())
+                self.parse_unit_suffix(Quantity::number(tok.value))
             }
             TokenKind::Identifier => {
                 self.advance();
-                if self.current().kind == TokenKind::LParen && is_function_name(&tok.value) {
+                if self.current().kind == TokenKind::LParen && self.env.is_function(&tok.value) {
                     return self.parse_function_call(&tok.value.clone());
                 }
+                if let Some(v) = self.env.variable(&tok.value) {
+                    return v;
+                }
                 if let Some(c) = resolve_constant(&tok.value, self.prec) {
-                    return Ok(c);
+                    return Ok(Quantity::number(c));
                 }
-                Err(format!("unknown identifier: {}", tok.value))
+                if let Some((factor, dim)) = units::lookup(&tok.raw) {
+                    return Ok(Quantity::unit(factor, dim));
+                }
+                Err(format!("unknown identifier: {}", tok.raw))
             }
             TokenKind::LParen => {
                 self.advance();
-                let v = self.parse_add_sub()?;
+                let v = self.parse_bitwise(0)?;
                 if self.current().kind != TokenKind::RParen {
                     return Err("missing ')' in expression".into());
                 }
                 self.advance();
                 Ok(v)
             }
-            _ => Err(format!("unexpected token {:?}", tok.value)),
+            _ => Err(format!("unexpected token {:?}", tok.raw)),
         }
     }
 
-    fn parse_function_call(&mut self, name: &str) -> Result<String, String> {
+    /// 数字后面紧跟的单位与数字结合得比乘除更紧：`200 MB/s` 是一个整体，
+    /// 因此 `3 GiB / 200 MB/s` 得到秒
+    fn parse_unit_suffix(&mut self, mut value: Quantity) -> Result<Quantity, String> {
+        loop {
+            let tok = self.current();
+            let divide = match tok.value.as_str() {
+                "*" if tok.kind == TokenKind::Operator => false,
+                "/" if tok.kind == TokenKind::Operator => true,
+                _ => break,
+            };
+            let Some(unit) = self.unit_at(self.pos + 1) else {
+                break;
+            };
+            self.advance();
+            self.advance();
+            let unit = self.parse_unit_exponent(unit)?;
+            let op = if divide { b'/' } else { b'*' };
+            value = apply_quantity(&value, &unit, op, self.prec)?;
+        }
+        Ok(value)
+    }
+
+    /// 单位后的整数指数，如 `m/s^2`
+    fn parse_unit_exponent(&mut self, unit: Quantity) -> Result<Quantity, String> {
+        if self.at_operator(&["^", "**"]).is_none() {
+            return Ok(unit);
+        }
+        self.advance();
+        let negative = self.current().kind == TokenKind::Operator && self.current().value == "-";
+        if negative {
+            self.advance();
+        }
+        let tok = self.current().clone();
+        if tok.kind != TokenKind::Number || tok.value.parse::<i64>().is_err() {
+            return Err("unit exponent must be an integer".into());
+        }
+        self.advance();
+        let exp = if negative {
+            negate(&tok.value)
+        } else {
+            tok.value
+        };
+        pow_quantity(&unit, &Quantity::number(exp), self.prec)
+    }
+
+    /// 变量、常量和函数调用优先于同名单位
+    fn unit_at(&self, index: usize) -> Option<Quantity> {
+        let tok = self.tokens.get(index)?;
+        if tok.kind != TokenKind::Identifier
+            || self.env.has_variable(&tok.value)
+            || resolve_constant(&tok.value, self.prec).is_some()
+        {
+            return None;
+        }
+        let calls = self
+            .tokens
+            .get(index + 1)
+            .is_some_and(|t| t.kind == TokenKind::LParen);
+        if calls && self.env.is_function(&tok.value) {
+            return None;
+        }
+        units::lookup(&tok.raw).map(|(factor, dim)| Quantity::unit(factor, dim))
+    }
+
+    fn parse_function_call(&mut self, name: &str) -> Result<Quantity, String> {
         self.advance(); // skip '('
         let mut args = Vec::new();
         if self.current().kind != TokenKind::RParen {
             loop {
-                let arg = self.parse_add_sub()?;
+                let arg = self.parse_bitwise(0)?;
                 args.push(arg);
                 if self.current().kind == TokenKind::Comma {
                     self.advance();
@@ -411,81 +929,172 @@ impl ExprParser {
             return Err(format!("missing ')' after {name}"));
         }
         self.advance();
-        call_function(name, &args, self.prec, self.degree)
+        if let Some(result) = self.env.call_user(name, &args) {
+            return result;
+        }
+        call_quantity_function(name, &args, self.prec, self.env.degree)
+    }
+}
+
+// ─── Units ────────────────────────────────────────────────────────────────────
+
+fn dim_name(dim: Dim) -> String {
+    if dim.is_none() {
+        "a plain number".into()
+    } else {
+        dim.to_string()
+    }
+}
+
+/// 带量纲的四则运算：加减和取模要求量纲相同，乘除合并量纲
+fn apply_quantity(
+    left: &Quantity,
+    right: &Quantity,
+    op: u8,
+    prec: usize,
+) -> Result<Quantity, String> {
+    let dim = match op {
+        b'+' | b'-' | b'%' => {
+            if left.dim != right.dim {
+                let verb = match op {
+                    b'+' => "add",
+                    b'-' => "subtract",
+                    _ => "take modulo of",
+                };
+                return Err(format!(
+                    "cannot {verb} {} and {}",
+                    dim_name(left.dim),
+                    dim_name(right.dim)
+                ));
+            }
+            left.dim
+        }
+        b'*' => left.dim.mul(right.dim),
+        b'/' => left.dim.div(right.dim),
+        b'^' => return pow_quantity(left, right, prec),
+        _ => return Err(format!("unsupported operator: {}", op as char)),
+    };
+    let prec = if left.dim.is_none() && right.dim.is_none() {
+        prec
+    } else {
+        prec + GUARD_DIGITS
+    };
+    Ok(Quantity {
+        value: apply_binary(&left.value, &right.value, op, prec)?,
+        dim,
+    })
+}
+
+/// 指数必须是纯数；底数带单位时指数必须是整数
+fn pow_quantity(base: &Quantity, exp: &Quantity, prec: usize) -> Result<Quantity, String> {
+    if !exp.dim.is_none() {
+        return Err(format!("exponent must be a plain number, got {}", exp.dim));
     }
+    let dim = if base.dim.is_none() {
+        Dim::NONE
+    } else {
+        let n: i64 = exp
+            .value
+            .parse()
+            .map_err(|_| format!("cannot raise {} to a fractional power", base.dim))?;
+        base.dim.pow(n).ok_or("unit exponent overflow")?
+    };
+    Ok(Quantity {
+        value: pow_number(&base.value, &exp.value, prec)?,
+        dim,
+    })
+}
+
+/// 内置函数的量纲规则：取整、绝对值和最值保留单位，`sqrt` 开方单位，其余只接受纯数
+fn call_quantity_function(
+    name: &str,
+    args: &[Quantity],
+    prec: usize,
+    degree: bool,
+) -> Result<Quantity, String> {
+    let first = args.first().map_or(Dim::NONE, |q| q.dim);
+    let dim = match name {
+        "pow" if args.len() == 2 => return pow_quantity(&args[0], &args[1], prec),
+        "abs" | "floor" | "ceil" | "round" => {
+            if args.iter().skip(1).any(|q| !q.dim.is_none()) {
+                return Err(format!("{name} precision must be a plain number"));
+            }
+            first
+        }
+        "min" | "max" => {
+            if let Some(q) = args.iter().find(|q| q.dim != first) {
+                return Err(format!(
+                    "{name}: cannot compare {} and {}",
+                    dim_name(first),
+                    dim_name(q.dim)
+                ));
+            }
+            first
+        }
+        "sqrt" => first
+            .root(2)
+            .ok_or_else(|| format!("cannot take the square root of {first}"))?,
+        _ => {
+            if let Some(q) = args.iter().find(|q| !q.dim.is_none()) {
+                return Err(format!("{name} expects a plain number, got {}", q.dim));
+            }
+            Dim::NONE
+        }
+    };
+    let values: Vec<String> = args.iter().map(|q| q.value.clone()).collect();
+    Ok(Quantity {
+        value: call_function(name, &values, prec, degree)?,
+        dim,
+    })
 }
 
 // ─── Arithmetic ───────────────────────────────────────────────────────────────
 
+/// 加、减、乘和取模精确计算，除法按 `prec` 位小数舍入
 fn apply_binary(left: &str, right: &str, op: u8, prec: usize) -> Result<String, String> {
-    let l = parse_decimal(left)?;
-    let r = parse_decimal(right)?;
     let result = match op {
-        b'+' => l + r,
-        b'-' => l - r,
-        b'*' => l * r,
+        b'+' => precise::add(left, right),
+        b'-' => precise::sub(left, right),
+        b'*' => precise::mul(left, right),
         b'/' => {
-            if r.is_zero() {
+            if is_zero(right) {
                 return Err("division by zero".into());
             }
-            let scale = prec as u32;
-            let result = l.checked_div(r).ok_or("division overflow")?;
-            round_to(result, scale)
+            precise::div(left, right, prec)
         }
         b'%' => {
-            if r.is_zero() {
+            if is_zero(right) {
                 return Err("modulo by zero".into());
             }
-            l % r
+            precise::rem(left, right)
         }
-        b'^' => return pow_decimal(left, right, prec),
+        b'^' => return pow_number(left, right, prec),
         _ => return Err(format!("unsupported operator: {}", op as char)),
     };
-    Ok(clean_number(&result.to_string()))
+    result.ok_or_else(|| format!("invalid number in {left} {} {right}", op as char))
 }
 
-fn pow_decimal(base: &str, exp: &str, prec: usize) -> Result<String, String> {
-    // Try integer exponent first (exact)
-    if let Ok(e) = exp.parse::<i64>() {
-        if e == 0 {
-            return Ok("1".into());
-        }
-        if e < 0 {
-            let pos = pow_decimal(base, &(-e).to_string(), prec)?;
-            return apply_binary("1", &pos, b'/', prec);
-        }
-        let b = parse_decimal(base)?;
-        let mut result = Decimal::ONE;
-        let mut factor = b;
-        let mut n = e as u64;
-        while n > 0 {
-            if n % 2 == 1 {
-                result = result.checked_mul(factor).ok_or("overflow in pow")?;
-            }
-            n /= 2;
-            if n > 0 {
-                factor = factor.checked_mul(factor).ok_or("overflow in pow")?;
-            }
-        }
-        return Ok(clean_number(&result.to_string()));
+/// 整数指数精确计算，分数指数按 `prec` 位小数正确舍入
+fn pow_number(base: &str, exp: &str, prec: usize) -> Result<String, String> {
+    if is_zero(base) && sign(exp)? == Ordering::Less {
+        return Err("cannot raise 0 to a negative power".into());
     }
-    // Fall back to f64
-    let bf = parse_f64(base)?;
-    let ef = parse_f64(exp)?;
-    format_float(bf.powf(ef), prec)
+    if sign(base)? == Ordering::Less && !is_integer(exp) {
+        return Err("cannot raise a negative number to a fractional power".into());
+    }
+    precise::pow(base, exp, prec).ok_or_else(|| "overflow in pow: result too large".into())
 }
 
-fn parse_decimal(s: &str) -> Result<Decimal, String> {
-    Decimal::from_str(s).map_err(|e| format!("invalid number {:?}: {e}", s))
+fn sign(s: &str) -> Result<Ordering, String> {
+    precise::cmp(s, "0").ok_or_else(|| format!("invalid number {s:?}"))
 }
 
-fn parse_f64(s: &str) -> Result<f64, String> {
-    s.parse::<f64>()
-        .map_err(|_| format!("invalid number {s:?}"))
+fn is_zero(s: &str) -> bool {
+    precise::cmp(s, "0") == Some(Ordering::Equal)
 }
 
-fn round_to(d: Decimal, scale: u32) -> Decimal {
-    d.round_dp(scale)
+fn is_integer(s: &str) -> bool {
+    precise::floor(s).and_then(|f| precise::cmp(&f, s)) == Some(Ordering::Equal)
 }
 
 fn negate(s: &str) -> String {
@@ -502,6 +1111,7 @@ fn negate(s: &str) -> String {
 // ─── Functions ────────────────────────────────────────────────────────────────
 
 fn call_function(name: &str, args: &[String], prec: usize, degree: bool) -> Result<String, String> {
+    let too_large = || format!("{name}: result too large");
     match name {
         "abs" => {
             require_args(name, args, 1)?;
@@ -509,136 +1119,143 @@ fn call_function(name: &str, args: &[String], prec: usize, degree: bool) -> Resu
         }
         "sqrt" => {
             require_args(name, args, 1)?;
-            let v = parse_f64(&args[0])?;
-            if v < 0.0 {
+            if sign(&args[0])? == Ordering::Less {
                 return Err("sqrt requires a non-negative argument".into());
             }
-            format_float(v.sqrt(), prec)
+            precise::sqrt(&args[0], prec).ok_or_else(too_large)
         }
         "sin" | "cos" | "tan" => {
             require_args(name, args, 1)?;
-            let mut v = parse_f64(&args[0])?;
-            if degree {
-                v = v.to_radians();
-            }
+            let x = if degree {
+                // 先按 360° 取余再换成弧度，`sin(180)` 这类整角度不会因为 pi 的截断误差偏离 0
+                let x = precise::rem(&args[0], "360").ok_or("invalid angle")?;
+                if name == "tan"
+                    && precise::rem(&x, "180").is_some_and(|r| matches!(r.as_str(), "90" | "-90"))
+                {
+                    return Err(format!("tan is undefined at {} degrees", args[0]));
+                }
+                degrees_to_radians(&x, prec)?
+            } else {
+                args[0].clone()
+            };
             let r = match name {
-                "sin" => v.sin(),
-                "cos" => v.cos(),
-                _ => v.tan(),
+                "sin" => precise::sin(&x, prec),
+                "cos" => precise::cos(&x, prec),
+                _ => precise::tan(&x, prec),
             };
-            format_float(r, prec)
+            r.ok_or_else(too_large)
         }
         "asin" | "acos" | "atan" => {
             require_args(name, args, 1)?;
-            let v = parse_f64(&args[0])?;
-            let mut r = match name {
-                "asin" => v.asin(),
-                "acos" => v.acos(),
-                _ => v.atan(),
-            };
-            if degree {
-                r = r.to_degrees();
+            if name != "atan" && precise::cmp(&abs_str(&args[0]), "1") == Some(Ordering::Greater) {
+                return Err(format!("{name} requires an argument between -1 and 1"));
             }
-            format_float(r, prec)
+            let digits = if degree { prec + GUARD_DIGITS } else { prec };
+            let r = match name {
+                "asin" => precise::asin(&args[0], digits),
+                "acos" => precise::acos(&args[0], digits),
+                _ => precise::atan(&args[0], digits),
+            }
+            .ok_or_else(too_large)?;
+            if !degree {
+                return Ok(r);
+            }
+            let scaled = apply_binary(&r, "180", b'*', digits)?;
+            let deg = apply_binary(&scaled, &precise::pi(digits), b'/', digits)?;
+            precise::round(&deg, prec).ok_or_else(too_large)
         }
         "ln" => {
             require_args(name, args, 1)?;
-            let v = parse_f64(&args[0])?;
-            if v <= 0.0 {
+            if sign(&args[0])? != Ordering::Greater {
                 return Err("ln requires a positive argument".into());
             }
-            format_float(v.ln(), prec)
+            precise::ln(&args[0], prec).ok_or_else(too_large)
         }
         "log" => {
             if args.len() != 1 && args.len() != 2 {
                 return Err("log expects 1 or 2 arguments".into());
             }
-            let v = parse_f64(&args[0])?;
-            if v <= 0.0 {
+            if sign(&args[0])? != Ordering::Greater {
                 return Err("log requires a positive argument".into());
             }
             if args.len() == 1 {
-                return format_float(v.log10(), prec);
+                return precise::log10(&args[0], prec).ok_or_else(too_large);
             }
-            let base = parse_f64(&args[1])?;
-            if base <= 0.0 || (base - 1.0).abs() < 1e-15 {
+            if sign(&args[1])? != Ordering::Greater
+                || precise::cmp(&args[1], "1") == Some(Ordering::Equal)
+            {
                 return Err("log base must be positive and not equal to 1".into());
             }
-            format_float(v.ln() / base.ln(), prec)
+            precise::log(&args[0], &args[1], prec).ok_or_else(too_large)
         }
         "exp" => {
             require_args(name, args, 1)?;
-            let v = parse_f64(&args[0])?;
-            format_float(v.exp(), prec)
+            precise::exp(&args[0], prec).ok_or_else(too_large)
         }
         "floor" => {
             require_args(name, args, 1)?;
-            let v = parse_f64(&args[0])?;
-            format_float(v.floor(), 0)
+            precise::floor(&args[0]).ok_or_else(|| format!("invalid number {:?}", args[0]))
         }
         "ceil" => {
             require_args(name, args, 1)?;
-            let v = parse_f64(&args[0])?;
-            format_float(v.ceil(), 0)
+            precise::ceil(&args[0]).ok_or_else(|| format!("invalid number {:?}", args[0]))
         }
         "round" => {
             if args.len() != 1 && args.len() != 2 {
                 return Err("round expects 1 or 2 arguments".into());
             }
-            let d = parse_decimal(&args[0])?;
-            if args.len() == 1 {
-                return Ok(clean_number(
-                    &d.round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero)
-                        .to_string(),
-                ));
-            }
-            let digits: i32 = args[1]
-                .parse()
-                .map_err(|_| "round precision must be an integer")?;
+            let digits: i64 = match args.get(1) {
+                Some(d) => d
+                    .parse()
+                    .map_err(|_| "round precision must be an integer")?,
+                None => 0,
+            };
             if digits >= 0 {
-                let rounded =
-                    d.round_dp_with_strategy(digits as u32, RoundingStrategy::MidpointAwayFromZero);
-                return Ok(clean_number(&rounded.to_string()));
+                return precise::round(&args[0], digits as usize)
+                    .ok_or_else(|| format!("invalid number {:?}", args[0]));
             }
-            // Negative digits: round to 10s, 100s, etc.
-            let v = parse_f64(&args[0])?;
-            let factor = 10f64.powi(-digits);
-            format_float((v / factor).round() * factor, 0)
-        }
-        "min" => {
-            if args.len() < 2 {
-                return Err("min expects at least 2 arguments".into());
-            }
-            let mut best = parse_decimal(&args[0])?;
-            for a in &args[1..] {
-                let v = parse_decimal(a)?;
-                if v < best {
-                    best = v;
-                }
+            // 负数位数按 10、100 … 取整；超过整数部分位数的结果都是 0
+            let zeros = digits.unsigned_abs() as usize;
+            if zeros > args[0].len() {
+                return Ok("0".into());
             }
-            Ok(clean_number(&best.to_string()))
+            let unit = format!("1{}", "0".repeat(zeros));
+            let q = apply_binary(&args[0], &unit, b'/', 0)?;
+            apply_binary(&q, &unit, b'*', 0)
         }
-        "max" => {
+        "min" | "max" => {
             if args.len() < 2 {
-                return Err("max expects at least 2 arguments".into());
+                return Err(format!("{name} expects at least 2 arguments"));
             }
-            let mut best = parse_decimal(&args[0])?;
+            let want = if name == "min" {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            };
+            let mut best = &args[0];
             for a in &args[1..] {
-                let v = parse_decimal(a)?;
-                if v > best {
-                    best = v;
+                let ord = precise::cmp(a, best).ok_or_else(|| format!("invalid number {a:?}"))?;
+                if ord == want {
+                    best = a;
                 }
             }
-            Ok(clean_number(&best.to_string()))
+            Ok(clean_number(best))
         }
         "pow" => {
             require_args(name, args, 2)?;
-            pow_decimal(&args[0], &args[1], prec)
+            pow_number(&args[0], &args[1], prec)
         }
         _ => Err(format!("unknown function: {name}")),
     }
 }
 
+/// 角度换算成弧度，多保留 [`GUARD_DIGITS`] 位
+fn degrees_to_radians(x: &str, prec: usize) -> Result<String, String> {
+    let digits = prec + GUARD_DIGITS;
+    let scaled = apply_binary(x, &precise::pi(digits + 3), b'*', digits + 3)?;
+    apply_binary(&scaled, "180", b'/', digits)
+}
+
 fn require_args(name: &str, args: &[String], want: usize) -> Result<(), String> {
     if args.len() != want {
         Err(format!("{name} expects {want} argument(s)"))
@@ -649,30 +1266,17 @@ fn require_args(name: &str, args: &[String], want: usize) -> Result<(), String>
 
 // ─── Constants ────────────────────────────────────────────────────────────────
 
+/// 常数多保留 [`GUARD_DIGITS`] 位，显示时再按 `--prec` 舍入
 fn resolve_constant(name: &str, prec: usize) -> Option<String> {
-    let digits = DEFAULT_PREC.max(prec);
+    let digits = prec + GUARD_DIGITS;
     match name {
-        "pi" => Some(trim_constant_digits(PI_DIGITS, digits)),
-        "e" => Some(trim_constant_digits(E_DIGITS, digits)),
-        "tau" => {
-            // 2 * pi
-            let pi = trim_constant_digits(PI_DIGITS, digits + 2);
-            let result = parse_decimal(&pi).ok()? * Decimal::TWO;
-            Some(trim_constant_digits(&result.to_string(), digits))
-        }
+        "pi" => Some(precise::pi(digits)),
+        "e" => Some(precise::e(digits)),
+        "tau" => precise::round(&precise::mul(&precise::pi(digits + 1), "2")?, digits),
         _ => None,
     }
 }
 
-fn trim_constant_digits(value: &str, digits: usize) -> String {
-    let dot = match value.find('.') {
-        Some(i) => i,
-        None => return value.to_string(),
-    };
-    let end = (dot + digits + 1).min(value.len());
-    clean_number(&value[..end])
-}
-
 // ─── Helpers ──────────────────────────────────────────────────────────────────
 
 fn is_function_name(name: &str) -> bool {
@@ -766,24 +1370,211 @@ fn clean_number(s: &str) -> String {
     }
 }
 
-fn format_float(v: f64, prec: usize) -> Result<String, String> {
-    if v.is_nan() || v.is_infinite() {
-        return Err("result is not a finite number".into());
-    }
-    let snapped = snap_tiny(v, prec);
-    let s = format!("{:.prec$}", snapped, prec = prec);
-    Ok(clean_number(&s))
+// ─── Evaluate ─────────────────────────────────────────────────────────────────
+
+/// 求值并返回显示文本，带单位的结果附上单位
+fn evaluate_expression(expr: &str, env: &Env) -> Result<String, String> {
+    let tokens = tokenize(expr, &|name| env.is_function(name))?;
+    let mut parser = ExprParser::new(tokens, env);
+    parser.parse().map(|(_, display)| display)
 }
 
-fn snap_tiny(v: f64, prec: usize) -> f64 {
-    let eps = 10f64.powi(-(prec.max(4) as i32 + 2));
-    if v.abs() < eps { 0.0 } else { v }
+fn evaluate_quantity(expr: &str, env: &Env) -> Result<Quantity, String> {
+    let tokens = tokenize(expr, &|name| env.is_function(name))?;
+    let mut parser = ExprParser::new(tokens, env);
+    parser.parse().map(|(value, _)| value)
 }
 
-// ─── Evaluate ─────────────────────────────────────────────────────────────────
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFAULTS: Settings = Settings {
+        prec: DEFAULT_PREC,
+        degree: false,
+        int_mode: None,
+    };
+
+    fn run_all(session: &mut Session, text: &str) -> Vec<String> {
+        split_statements(text)
+            .iter()
+            .filter_map(|stmt| execute_statement(session, stmt, &DEFAULTS).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn variables_functions_and_ans() {
+        let mut session = Session::default();
+        let out = run_all(
+            &mut session,
+            "x = 3.5\nf(a, b) = a^2 + b\nf(x, 1); ans * 2\nsq(t) = f(t, 0)\nsq(3) + 2x",
+        );
+        assert_eq!(out, vec!["13.25", "26.5", "16"]);
+        assert_eq!(session.ans.as_deref(), Some("16"));
+        assert_eq!(session.vars.get("x").map(String::as_str), Some("3.5"));
+    }
+
+    #[test]
+    fn piped_input_joins_lines_and_splits_on_semicolons() {
+        let mut session = Session::default();
+        assert_eq!(run_all(&mut session, &join_lines("1+\n2")), vec!["3"]);
+        assert_eq!(run_all(&mut session, &join_lines("1\n2")), vec!["2"]);
+        assert_eq!(
+            run_all(&mut session, &join_lines("x = 2 # two\n; x *\n3")),
+            vec!["6"]
+        );
+    }
+
+    #[test]
+    fn function_parameters_shadow_globals_only_inside_body() {
+        let mut session = Session::default();
+        let out = run_all(&mut session, "a = 10; g(a) = a + 1; g(1); a");
+        assert_eq!(out, vec!["2", "10"]);
+    }
+
+    #[test]
+    fn rejects_reserved_names_and_runaway_recursion() {
+        let mut session = Session::default();
+        assert!(execute_statement(&mut session, "pi = 3", &DEFAULTS).is_err());
+        assert!(execute_statement(&mut session, "sqrt(x) = x", &DEFAULTS).is_err());
+        run_all(&mut session, "loop(x) = loop(x) + 1");
+        let err = execute_statement(&mut session, "loop(1)", &DEFAULTS).unwrap_err();
+        assert!(err.contains("call depth"), "{err}");
+        let err = execute_statement(&mut session, "loop(1, 2)", &DEFAULTS).unwrap_err();
+        assert_eq!(err, "loop expects 1 argument(s)");
+    }
+
+    #[test]
+    fn unit_arithmetic_and_conversions() {
+        let mut session = Session::default();
+        let out = run_all(
+            &mut session,
+            "3 GiB / 200 MB/s in s\n5 km/h to m/s\n1700000000 ms to days\n60 mph to km/h\n\
+             d = 1.5 km; d + 300 m; ans in km\n9.81 m/s^2 * 2 s\nsqrt(16 m^2)\n5 min",
+        );
+        assert_eq!(
+            out,
+            vec![
+                "16.10612736 s",
+                "1.3888888888888889 m/s",
+                "19.6759259259259259 days",
+                "96.56064 km/h",
+                "1800 m",
+                "1.8 km",
+                "19.62 m/s",
+                "4 m",
+                "300 s",
+            ]
+        );
+        assert_eq!(session.vars.get("d").map(String::as_str), Some("1500 m"));
+    }
+
+    #[test]
+    fn transcendental_functions_at_high_precision() {
+        let at = |text: &str, prec: usize| eval(text, &Settings { prec, ..DEFAULTS }).unwrap();
+        assert_eq!(
+            at("sqrt(2)", 50),
+            "1.41421356237309504880168872420969807856967187537695"
+        );
+        assert_eq!(
+            at("pi", 60),
+            "3.141592653589793238462643383279502884197169399375105820974945"
+        );
+        assert_eq!(
+            at("2^(1/3)", 40),
+            "1.2599210498948731647672106072782283505702"
+        );
+        assert_eq!(
+            at("ln(10)", 40),
+            "2.3025850929940456840179914546843642076011"
+        );
+        assert_eq!(at("log(1000)", 30), "3");
+        assert_eq!(at("sin(pi)", 30), "0");
+        assert_eq!(at("4 atan(1)", 16), "3.1415926535897932");
+
+        let deg = Settings {
+            degree: true,
+            ..DEFAULTS
+        };
+        assert_eq!(eval("sin(30)", &deg).unwrap(), "0.5");
+        assert_eq!(eval("acos(-1)", &deg).unwrap(), "180");
+        assert_eq!(
+            eval("tan(90)", &deg).unwrap_err(),
+            "tan is undefined at 90 degrees"
+        );
+
+        assert_eq!(
+            eval("asin(2)", &DEFAULTS).unwrap_err(),
+            "asin requires an argument between -1 and 1"
+        );
+        assert_eq!(
+            eval("(-8)^(1/3)", &DEFAULTS).unwrap_err(),
+            "cannot raise a negative number to a fractional power"
+        );
+    }
+
+    #[test]
+    fn rejects_incompatible_units() {
+        let mut session = Session::default();
+        let mut err = |stmt: &str| execute_statement(&mut session, stmt, &DEFAULTS).unwrap_err();
+        assert_eq!(err("5 m + 2 s"), "cannot add m and s");
+        assert_eq!(err("1 + 1 kg"), "cannot add a plain number and kg");
+        assert_eq!(err("5 km to s"), "cannot convert m to s");
+        assert_eq!(err("sin(2 m)"), "sin expects a plain number, got m");
+        assert_eq!(err("(2 m)^0.5"), "cannot raise m to a fractional power");
+        assert_eq!(err("2 m^0.5"), "unit exponent must be an integer");
+        assert_eq!(err("to = 1"), "to is a reserved name");
+    }
 
-fn evaluate_expression(expr: &str, prec: usize, degree: bool) -> Result<String, String> {
-    let tokens = tokenize(expr)?;
-    let mut parser = ExprParser::new(tokens, prec, degree);
-    parser.parse()
+    fn programmer(bits: Option<u32>, signed: bool) -> Settings {
+        Settings {
+            int_mode: Some(IntMode { bits, signed }),
+            ..DEFAULTS
+        }
+    }
+
+    fn eval(text: &str, settings: &Settings) -> Result<String, String> {
+        execute_statement(&mut Session::default(), text, settings).map(Option::unwrap)
+    }
+
+    #[test]
+    fn integer_literals_bitwise_operators_and_big_integers() {
+        let ok = |text: &str| eval(text, &DEFAULTS).unwrap();
+        assert_eq!(ok("0xff + 0o17 + 0b101"), "275");
+        assert_eq!(ok("0xF0 | 0x0F & 0x3C"), "252");
+        assert_eq!(ok("1 << 4 + 1"), "32");
+        assert_eq!(ok("~0 & 0xff"), "255");
+        assert_eq!(ok("2^10"), "1024");
+        assert_eq!(ok("2**100"), "1267650600228229401496703205376");
+        assert_eq!(ok("2^100 / 2^98 - 2^96 * 0"), "4");
+        assert_eq!(
+            ok("99999999999999999999999999999 * 10 + 1"),
+            "999999999999999999999999999991"
+        );
+        assert_eq!(
+            eval("1.5 & 1", &DEFAULTS).unwrap_err(),
+            "'&' requires integer operands"
+        );
+    }
+
+    #[test]
+    fn programmer_mode_wraps_to_bit_width() {
+        let p = programmer(None, false);
+        assert_eq!(eval("0xf0 ^ 0xff", &p).unwrap(), "15");
+        assert_eq!(eval("2 ** 8", &p).unwrap(), "256");
+        assert_eq!(eval("7 / 2", &p).unwrap(), "3");
+        assert_eq!(eval("-7 / 2", &p).unwrap(), "-3");
+        assert!(eval("1.5 + 1", &p).is_err());
+
+        let u32 = programmer(Some(32), false);
+        assert_eq!(eval("0 - 1", &u32).unwrap(), "4294967295");
+        assert_eq!(eval("0xffffffff + 2", &u32).unwrap(), "1");
+        let i32 = programmer(Some(32), true);
+        assert_eq!(eval("0x7fffffff + 1", &i32).unwrap(), "-2147483648");
+        assert_eq!(eval("~0", &i32).unwrap(), "-1");
+        assert_eq!(
+            format_result("-1", &[16, 2], &i32).unwrap(),
+            format!("0xffffffff\n0b{}", "1".repeat(32))
+        );
+    }
 }
@@ -0,0 +1,195 @@
+use num_bigint::BigInt;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+/// 移位位数上限，避免 `1 << 1e9` 这类输入耗尽内存
+const MAX_SHIFT: usize = 1 << 20;
+
+/// 程序员模式的整数宽度：`bits` 为空时不截断
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntMode {
+    pub bits: Option<u32>,
+    pub signed: bool,
+}
+
+impl IntMode {
+    /// 按位宽回绕：无符号取 `[0, 2^bits)`，有符号按补码取 `[-2^(bits-1), 2^(bits-1))`
+    pub fn wrap(&self, value: &str) -> Result<String, String> {
+        let v = parse_int(value)
+            .ok_or_else(|| format!("programmer mode only supports integers, got {value}"))?;
+        let Some(bits) = self.bits else {
+            return Ok(value.to_string());
+        };
+        let modulus = BigInt::one() << bits;
+        let mut r = ((v % &modulus) + &modulus) % &modulus;
+        if self.signed && r >= (&modulus >> 1usize) {
+            r -= &modulus;
+        }
+        Ok(r.to_string())
+    }
+}
+
+/// 解析十进制整数字符串（可带负号）
+pub fn parse_int(s: &str) -> Option<BigInt> {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+/// 解析 `0x`/`0o`/`0b` 字面量的数字部分，允许 `_` 分隔，返回十进制字符串
+pub fn parse_radix_literal(digits: &str, radix: u32) -> Option<String> {
+    let digits: String = digits.chars().filter(|&c| c != '_').collect();
+    if digits.is_empty() {
+        return None;
+    }
+    BigInt::parse_bytes(digits.as_bytes(), radix).map(|v| v.to_string())
+}
+
+fn int_operands(left: &str, right: &str, op: &str) -> Result<(BigInt, BigInt), String> {
+    match (parse_int(left), parse_int(right)) {
+        (Some(l), Some(r)) => Ok((l, r)),
+        _ => Err(format!("'{op}' requires integer operands")),
+    }
+}
+
+/// 按位与、或、异或和移位；负数按无限位补码处理，`>>` 是算术右移
+pub fn bit_op(left: &str, right: &str, op: &str) -> Result<String, String> {
+    let (l, r) = int_operands(left, right, op)?;
+    let result = match op {
+        "&" => l & r,
+        "|" => l | r,
+        "^" => l ^ r,
+        "<<" | ">>" => {
+            let n = r
+                .to_usize()
+                .filter(|&n| n <= MAX_SHIFT)
+                .ok_or_else(|| format!("shift amount must be between 0 and {MAX_SHIFT}"))?;
+            if op == "<<" { l << n } else { l >> n }
+        }
+        _ => return Err(format!("unsupported operator: {op}")),
+    };
+    Ok(result.to_string())
+}
+
+pub fn bit_not(value: &str) -> Result<String, String> {
+    let v = parse_int(value).ok_or("'~' requires an integer operand")?;
+    Ok((!v).to_string())
+}
+
+/// 程序员模式的除法，向零取整
+pub fn trunc_div(left: &str, right: &str) -> Result<String, String> {
+    let (l, r) = int_operands(left, right, "/")?;
+    if r.is_zero() {
+        return Err("division by zero".into());
+    }
+    Ok((l / r).to_string())
+}
+
+/// 按多个进制输出，每个进制一行
+///
+/// 设置了位宽时负数显示为补码，二、八、十六进制按位宽补齐前导零。
+pub fn format_bases(value: &str, bases: &[u32], mode: IntMode) -> Result<String, String> {
+    let v =
+        parse_int(value).ok_or_else(|| format!("--base needs an integer result, got {value}"))?;
+    let lines: Vec<String> = bases
+        .iter()
+        .map(|&base| {
+            let (prefix, pad) = match base {
+                2 => ("0b", true),
+                8 => ("0o", true),
+                16 => ("0x", true),
+                _ => ("", false),
+            };
+            if base == 10 {
+                return v.to_string();
+            }
+            match mode.bits {
+                Some(bits) => {
+                    let pattern = if v.is_negative() {
+                        &v + (BigInt::one() << bits)
+                    } else {
+                        v.clone()
+                    };
+                    let digits = pattern.to_str_radix(base);
+                    let width = if pad {
+                        ((BigInt::one() << bits) - 1u32).to_str_radix(base).len()
+                    } else {
+                        0
+                    };
+                    format!("{prefix}{digits:0>width$}")
+                }
+                None => {
+                    let sign = if v.is_negative() { "-" } else { "" };
+                    format!("{sign}{prefix}{}", v.abs().to_str_radix(base))
+                }
+            }
+        })
+        .collect();
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_to_bit_width() {
+        let u8 = IntMode {
+            bits: Some(8),
+            signed: false,
+        };
+        let i8 = IntMode {
+            bits: Some(8),
+            signed: true,
+        };
+        assert_eq!(u8.wrap("256").unwrap(), "0");
+        assert_eq!(u8.wrap("-1").unwrap(), "255");
+        assert_eq!(i8.wrap("128").unwrap(), "-128");
+        assert_eq!(i8.wrap("-129").unwrap(), "127");
+        assert!(u8.wrap("1.5").is_err());
+    }
+
+    #[test]
+    fn bitwise_and_big_integers() {
+        assert_eq!(bit_op("12", "10", "&").unwrap(), "8");
+        assert_eq!(bit_op("12", "10", "^").unwrap(), "6");
+        assert_eq!(bit_op("-8", "1", ">>").unwrap(), "-4");
+        assert_eq!(
+            bit_op("1", "100", "<<").unwrap(),
+            "1267650600228229401496703205376"
+        );
+        assert_eq!(bit_not("5").unwrap(), "-6");
+        assert!(bit_op("1.5", "1", "&").is_err());
+        assert_eq!(
+            bit_op("99999999999999999999999999999", "1", "<<").unwrap(),
+            "199999999999999999999999999998"
+        );
+        assert_eq!(parse_radix_literal("dead_BEEF", 16).unwrap(), "3735928559");
+    }
+
+    #[test]
+    fn formats_multiple_bases() {
+        let free = IntMode {
+            bits: None,
+            signed: false,
+        };
+        assert_eq!(
+            format_bases("255", &[16, 2], free).unwrap(),
+            "0xff\n0b11111111"
+        );
+        assert_eq!(format_bases("-10", &[16, 10], free).unwrap(), "-0xa\n-10");
+        let i16 = IntMode {
+            bits: Some(16),
+            signed: true,
+        };
+        assert_eq!(
+            format_bases("-1", &[16, 8], i16).unwrap(),
+            "0xffff\n0o177777"
+        );
+        assert_eq!(
+            format_bases("5", &[2, 36], i16).unwrap(),
+            "0b0000000000000101\n5"
+        );
+    }
+}
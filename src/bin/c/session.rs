@@ -0,0 +1,284 @@
+use std::{collections::BTreeMap, fmt::Write as _, path::Path};
+
+/// 用户自定义函数 `name(params) = body`，函数体保存为表达式文本，调用时求值
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserFn {
+    pub params: Vec<String>,
+    pub body: String,
+}
+
+/// 变量、函数和上一次结果 `ans`，可以保存到会话文件后在下次调用时继续使用
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Session {
+    pub vars: BTreeMap<String, String>,
+    pub funcs: BTreeMap<String, UserFn>,
+    pub ans: Option<String>,
+}
+
+/// 一条语句
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    /// `x = expr`
+    Assign {
+        name: String,
+        expr: String,
+    },
+    /// `f(a, b) = expr`
+    Define {
+        name: String,
+        fun: UserFn,
+    },
+    Expr(String),
+}
+
+impl Session {
+    /// 读取会话文件，文件不存在时返回空会话
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let mut session = Self::default();
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(session),
+            Err(e) => return Err(format!("{}: {e}", path.display())),
+        };
+        for stmt in split_statements(&text) {
+            match parse_statement(&stmt).map_err(|e| format!("{}: {e}", path.display()))? {
+                // 会话文件中的变量都是求值后的结果，直接读回
+                Statement::Assign { name, expr } if name == "ans" => session.ans = Some(expr),
+                Statement::Assign { name, expr } => {
+                    session.vars.insert(name, expr);
+                }
+                Statement::Define { name, fun } => {
+                    session.funcs.insert(name, fun);
+                }
+                Statement::Expr(expr) => {
+                    return Err(format!(
+                        "{}: unexpected expression {expr:?}",
+                        path.display()
+                    ));
+                }
+            }
+        }
+        Ok(session)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent).map_err(|e| format!("{}: {e}", parent.display()))?;
+        }
+        std::fs::write(path, self.to_text()).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// 会话文件内容：每行一条可以重新执行的语句
+    pub fn to_text(&self) -> String {
+        let mut out = String::from("# c session\n");
+        if let Some(ans) = &self.ans {
+            let _ = writeln!(out, "ans = {ans}");
+        }
+        for (name, value) in &self.vars {
+            let _ = writeln!(out, "{name} = {value}");
+        }
+        for (name, fun) in &self.funcs {
+            let _ = writeln!(out, "{name}({}) = {}", fun.params.join(", "), fun.body);
+        }
+        out
+    }
+
+    pub fn assign(&mut self, name: String, value: String) {
+        self.funcs.remove(&name);
+        self.vars.insert(name, value);
+    }
+
+    pub fn define(&mut self, name: String, fun: UserFn) {
+        self.vars.remove(&name);
+        self.funcs.insert(name, fun);
+    }
+}
+
+/// 按 `;` 和换行拆分语句；括号未闭合时跨行拼接，`#` 之后是注释
+pub fn split_statements(text: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0i32;
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or("");
+        for ch in line.chars() {
+            match ch {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                ';' if depth <= 0 => {
+                    push_statement(&mut out, &mut current);
+                    continue;
+                }
+                _ => {}
+            }
+            current.push(ch);
+        }
+        if depth <= 0 {
+            push_statement(&mut out, &mut current);
+            depth = 0;
+        } else {
+            current.push(' ');
+        }
+    }
+    push_statement(&mut out, &mut current);
+    out
+}
+
+fn push_statement(out: &mut Vec<String>, current: &mut String) {
+    let stmt = current.trim();
+    if !stmt.is_empty() {
+        out.push(stmt.to_string());
+    }
+    current.clear();
+}
+
+/// 区分赋值、函数定义和普通表达式；名字统一转为小写，与表达式中的标识符一致
+///
+/// 只有左边是标识符或 `f(参数)` 且不是 `==`、`<=` 这类写法时才算赋值，其余带 `=` 的语句按比较运算报错。
+pub fn parse_statement(stmt: &str) -> Result<Statement, String> {
+    let Some((lhs, rhs)) = stmt.split_once('=') else {
+        return Ok(Statement::Expr(stmt.trim().to_string()));
+    };
+    let (lhs, rhs) = (lhs.trim(), rhs.trim());
+    let definition_shape = |lhs: &str| {
+        lhs.split_once('(')
+            .is_some_and(|(name, rest)| identifier(name).is_ok() && rest.ends_with(')'))
+    };
+    // `==`、`!=`、`<=`、`>=` 拆开后 `=` 紧挨着这些字符
+    if rhs.starts_with('=') || lhs.ends_with(['!', '<', '>']) {
+        return Err("comparison operators are not supported".to_string());
+    }
+    if identifier(lhs).is_err() && !definition_shape(lhs) {
+        return Err(format!("cannot assign to `{lhs}`"));
+    }
+    if rhs.is_empty() {
+        return Err(format!("missing value after '=' in {stmt:?}"));
+    }
+    match lhs.split_once('(') {
+        None => Ok(Statement::Assign {
+            name: identifier(lhs)?,
+            expr: rhs.to_string(),
+        }),
+        Some((name, rest)) => {
+            let params = rest
+                .strip_suffix(')')
+                .ok_or_else(|| format!("invalid function definition {lhs:?}"))?;
+            let params: Vec<String> = if params.trim().is_empty() {
+                Vec::new()
+            } else {
+                params
+                    .split(',')
+                    .map(identifier)
+                    .collect::<Result<_, _>>()?
+            };
+            for (i, p) in params.iter().enumerate() {
+                if params[..i].contains(p) {
+                    return Err(format!("duplicate parameter {p:?}"));
+                }
+            }
+            Ok(Statement::Define {
+                name: identifier(name)?,
+                fun: UserFn {
+                    params,
+                    body: rhs.to_string(),
+                },
+            })
+        }
+    }
+}
+
+fn identifier(s: &str) -> Result<String, String> {
+    let s = s.trim();
+    let mut chars = s.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(s.to_lowercase())
+    } else {
+        Err(format!("invalid name {s:?}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_statements_across_lines_and_semicolons() {
+        let text = "x = 1; y = 2  # comment\n\nf(a,\n  b) = a + b\nmax(x,\n y)\n";
+        assert_eq!(
+            split_statements(text),
+            vec!["x = 1", "y = 2", "f(a,   b) = a + b", "max(x,  y)"]
+        );
+    }
+
+    #[test]
+    fn parses_statements() {
+        assert_eq!(
+            parse_statement("X = 3.5").unwrap(),
+            Statement::Assign {
+                name: "x".into(),
+                expr: "3.5".into()
+            }
+        );
+        assert_eq!(
+            parse_statement("f(a, b) = a^2 + b").unwrap(),
+            Statement::Define {
+                name: "f".into(),
+                fun: UserFn {
+                    params: vec!["a".into(), "b".into()],
+                    body: "a^2 + b".into()
+                }
+            }
+        );
+        assert_eq!(
+            parse_statement("2 + 3").unwrap(),
+            Statement::Expr("2 + 3".into())
+        );
+        assert!(parse_statement("2x = 1").is_err());
+        assert!(parse_statement("f(a, a) = a").is_err());
+        assert!(parse_statement("x =").is_err());
+        for stmt in ["2 & 3 == 2", "x == 1", "x <= 1", "x >= 1", "x != 1"] {
+            assert_eq!(
+                parse_statement(stmt).unwrap_err(),
+                "comparison operators are not supported",
+                "{stmt}"
+            );
+        }
+        for (stmt, target) in [("2x = 1", "2x"), ("x + 1 = 3", "x + 1"), ("3 = x", "3")] {
+            assert_eq!(
+                parse_statement(stmt).unwrap_err(),
+                format!("cannot assign to `{target}`"),
+            );
+        }
+    }
+
+    #[test]
+    fn session_round_trips_through_text() {
+        let mut session = Session::default();
+        session.assign("x".into(), "3.5".into());
+        session.define(
+            "f".into(),
+            UserFn {
+                params: vec!["a".into(), "b".into()],
+                body: "a^2 + b".into(),
+            },
+        );
+        session.ans = Some("-2".into());
+
+        let dir = std::env::temp_dir().join(format!("c_session_test_{}", std::process::id()));
+        let path = dir.join("session.c");
+        session.save(&path).unwrap();
+        assert_eq!(Session::load(&path).unwrap(), session);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            Session::load(Path::new("/nonexistent/c_session")).unwrap(),
+            Session::default()
+        );
+    }
+}
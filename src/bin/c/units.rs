@@ -0,0 +1,361 @@
+use std::fmt;
+
+use rust_decimal::Decimal;
+use rust_decimal::prelude::*;
+
+/// 基本量纲：长度、质量、时间、电流、温度、物质的量、发光强度、信息量
+const BASE_UNITS: [&str; 8] = ["m", "kg", "s", "A", "K", "mol", "cd", "B"];
+
+/// 量纲，各基本量纲的指数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Dim([i8; 8]);
+
+const LENGTH: Dim = Dim([1, 0, 0, 0, 0, 0, 0, 0]);
+const MASS: Dim = Dim([0, 1, 0, 0, 0, 0, 0, 0]);
+const TIME: Dim = Dim([0, 0, 1, 0, 0, 0, 0, 0]);
+const CURRENT: Dim = Dim([0, 0, 0, 1, 0, 0, 0, 0]);
+const TEMPERATURE: Dim = Dim([0, 0, 0, 0, 1, 0, 0, 0]);
+const AMOUNT: Dim = Dim([0, 0, 0, 0, 0, 1, 0, 0]);
+const LUMINOSITY: Dim = Dim([0, 0, 0, 0, 0, 0, 1, 0]);
+const INFORMATION: Dim = Dim([0, 0, 0, 0, 0, 0, 0, 1]);
+const FREQUENCY: Dim = Dim([0, 0, -1, 0, 0, 0, 0, 0]);
+const SPEED: Dim = Dim([1, 0, -1, 0, 0, 0, 0, 0]);
+const VOLUME: Dim = Dim([3, 0, 0, 0, 0, 0, 0, 0]);
+const FORCE: Dim = Dim([1, 1, -2, 0, 0, 0, 0, 0]);
+const ENERGY: Dim = Dim([2, 1, -2, 0, 0, 0, 0, 0]);
+const POWER: Dim = Dim([2, 1, -3, 0, 0, 0, 0, 0]);
+const PRESSURE: Dim = Dim([-1, 1, -2, 0, 0, 0, 0, 0]);
+const CHARGE: Dim = Dim([0, 0, 1, 1, 0, 0, 0, 0]);
+const VOLTAGE: Dim = Dim([2, 1, -3, -1, 0, 0, 0, 0]);
+const RESISTANCE: Dim = Dim([2, 1, -3, -2, 0, 0, 0, 0]);
+
+/// 结果按国际单位制显示时优先使用的导出单位
+const DERIVED_NAMES: [(Dim, &str); 8] = [
+    (FREQUENCY, "Hz"),
+    (FORCE, "N"),
+    (ENERGY, "J"),
+    (POWER, "W"),
+    (PRESSURE, "Pa"),
+    (CHARGE, "C"),
+    (VOLTAGE, "V"),
+    (RESISTANCE, "ohm"),
+];
+
+impl Dim {
+    pub const NONE: Dim = Dim([0; 8]);
+
+    pub fn is_none(self) -> bool {
+        self == Self::NONE
+    }
+
+    pub fn mul(self, other: Dim) -> Dim {
+        Dim(std::array::from_fn(|i| self.0[i] + other.0[i]))
+    }
+
+    pub fn div(self, other: Dim) -> Dim {
+        Dim(std::array::from_fn(|i| self.0[i] - other.0[i]))
+    }
+
+    pub fn pow(self, n: i64) -> Option<Dim> {
+        let mut out = [0i8; 8];
+        for (o, &e) in out.iter_mut().zip(&self.0) {
+            *o = i8::try_from(i64::from(e).checked_mul(n)?).ok()?;
+        }
+        Some(Dim(out))
+    }
+
+    /// 开 `n` 次方，指数不能整除时返回 `None`
+    pub fn root(self, n: i8) -> Option<Dim> {
+        self.0
+            .iter()
+            .all(|e| e % n == 0)
+            .then(|| Dim(self.0.map(|e| e / n)))
+    }
+}
+
+impl fmt::Display for Dim {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some((_, name)) = DERIVED_NAMES.iter().find(|(d, _)| d == self) {
+            return f.write_str(name);
+        }
+        let term = |name: &str, e: i8| {
+            if e == 1 {
+                name.to_string()
+            } else {
+                format!("{name}^{e}")
+            }
+        };
+        let num: Vec<String> = BASE_UNITS
+            .iter()
+            .zip(self.0)
+            .filter(|(_, e)| *e > 0)
+            .map(|(name, e)| term(name, e))
+            .collect();
+        let den: Vec<String> = BASE_UNITS
+            .iter()
+            .zip(self.0)
+            .filter(|(_, e)| *e < 0)
+            .map(|(name, e)| term(name, if num.is_empty() { e } else { -e }))
+            .collect();
+        match (num.is_empty(), den.is_empty()) {
+            (_, true) => f.write_str(&num.join(" ")),
+            (true, false) => f.write_str(&den.join(" ")),
+            (false, false) if den.len() == 1 => write!(f, "{}/{}", num.join(" "), den[0]),
+            (false, false) => write!(f, "{}/({})", num.join(" "), den.join(" ")),
+        }
+    }
+}
+
+/// 可以加在单位符号前的前缀
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Prefixes {
+    None,
+    /// 全部 SI 前缀
+    Si,
+    /// 信息量：SI 放大前缀、`K` 和二进制前缀 `Ki`、`Mi`……
+    Data,
+}
+
+struct UnitDef {
+    names: &'static [&'static str],
+    /// 换算到基本单位的系数
+    factor: &'static str,
+    dim: Dim,
+    prefixes: Prefixes,
+}
+
+const fn unit(
+    names: &'static [&'static str],
+    factor: &'static str,
+    dim: Dim,
+    prefixes: Prefixes,
+) -> UnitDef {
+    UnitDef {
+        names,
+        factor,
+        dim,
+        prefixes,
+    }
+}
+
+/// 带前缀的只能是每组中的第一个名字（单位符号）
+const UNITS: &[UnitDef] = &[
+    // 长度
+    unit(
+        &["m", "meter", "meters", "metre", "metres"],
+        "1",
+        LENGTH,
+        Prefixes::Si,
+    ),
+    unit(&["inch", "inches"], "0.0254", LENGTH, Prefixes::None),
+    unit(&["ft", "foot", "feet"], "0.3048", LENGTH, Prefixes::None),
+    unit(&["yd", "yard", "yards"], "0.9144", LENGTH, Prefixes::None),
+    unit(&["mi", "mile", "miles"], "1609.344", LENGTH, Prefixes::None),
+    unit(&["nmi"], "1852", LENGTH, Prefixes::None),
+    unit(&["au"], "149597870700", LENGTH, Prefixes::None),
+    unit(
+        &["ly", "lightyear", "lightyears"],
+        "9460730472580800",
+        LENGTH,
+        Prefixes::None,
+    ),
+    // 质量
+    unit(&["g", "gram", "grams"], "0.001", MASS, Prefixes::Si),
+    unit(&["t", "tonne", "tonnes"], "1000", MASS, Prefixes::None),
+    unit(
+        &["lb", "lbs", "pound", "pounds"],
+        "0.45359237",
+        MASS,
+        Prefixes::None,
+    ),
+    unit(
+        &["oz", "ounce", "ounces"],
+        "0.028349523125",
+        MASS,
+        Prefixes::None,
+    ),
+    // 时间
+    unit(&["s", "sec", "second", "seconds"], "1", TIME, Prefixes::Si),
+    unit(&["min", "minute", "minutes"], "60", TIME, Prefixes::None),
+    unit(&["h", "hr", "hour", "hours"], "3600", TIME, Prefixes::None),
+    unit(&["d", "day", "days"], "86400", TIME, Prefixes::None),
+    unit(&["week", "weeks", "wk"], "604800", TIME, Prefixes::None),
+    // 儒略年
+    unit(&["yr", "year", "years"], "31557600", TIME, Prefixes::None),
+    // 其他基本量
+    unit(&["A", "ampere", "amperes"], "1", CURRENT, Prefixes::Si),
+    unit(&["K", "kelvin"], "1", TEMPERATURE, Prefixes::Si),
+    unit(&["mol"], "1", AMOUNT, Prefixes::Si),
+    unit(&["cd", "candela"], "1", LUMINOSITY, Prefixes::Si),
+    unit(&["B", "byte", "bytes"], "1", INFORMATION, Prefixes::Data),
+    unit(&["bit", "bits"], "0.125", INFORMATION, Prefixes::Data),
+    // 导出单位
+    unit(&["Hz", "hertz"], "1", FREQUENCY, Prefixes::Si),
+    unit(&["N", "newton", "newtons"], "1", FORCE, Prefixes::Si),
+    unit(&["J", "joule", "joules"], "1", ENERGY, Prefixes::Si),
+    unit(&["Wh"], "3600", ENERGY, Prefixes::Si),
+    unit(
+        &["eV"],
+        "0.0000000000000000001602176634",
+        ENERGY,
+        Prefixes::Si,
+    ),
+    unit(
+        &["cal", "calorie", "calories"],
+        "4.184",
+        ENERGY,
+        Prefixes::Si,
+    ),
+    unit(&["W", "watt", "watts"], "1", POWER, Prefixes::Si),
+    unit(&["Pa", "pascal"], "1", PRESSURE, Prefixes::Si),
+    unit(&["bar"], "100000", PRESSURE, Prefixes::Si),
+    unit(&["atm"], "101325", PRESSURE, Prefixes::None),
+    unit(
+        &["psi"],
+        "6894.757293168361336722673445",
+        PRESSURE,
+        Prefixes::None,
+    ),
+    unit(&["C", "coulomb", "coulombs"], "1", CHARGE, Prefixes::Si),
+    unit(&["V", "volt", "volts"], "1", VOLTAGE, Prefixes::Si),
+    unit(&["ohm", "ohms"], "1", RESISTANCE, Prefixes::Si),
+    unit(
+        &["L", "l", "liter", "liters", "litre", "litres"],
+        "0.001",
+        VOLUME,
+        Prefixes::Si,
+    ),
+    unit(&["mph"], "0.44704", SPEED, Prefixes::None),
+    unit(
+        &["kn", "knot", "knots"],
+        "0.5144444444444444444444444444",
+        SPEED,
+        Prefixes::None,
+    ),
+];
+
+const SI_PREFIXES: [(&str, i32); 21] = [
+    ("da", 1),
+    ("Y", 24),
+    ("Z", 21),
+    ("E", 18),
+    ("P", 15),
+    ("T", 12),
+    ("G", 9),
+    ("M", 6),
+    ("k", 3),
+    ("h", 2),
+    ("d", -1),
+    ("c", -2),
+    ("m", -3),
+    ("u", -6),
+    ("n", -9),
+    ("p", -12),
+    ("f", -15),
+    ("a", -18),
+    ("z", -21),
+    ("y", -24),
+    ("K", 3),
+];
+
+const BINARY_PREFIXES: [(&str, u32); 8] = [
+    ("Ki", 10),
+    ("Mi", 20),
+    ("Gi", 30),
+    ("Ti", 40),
+    ("Pi", 50),
+    ("Ei", 60),
+    ("Zi", 70),
+    ("Yi", 80),
+];
+
+/// 查找单位，返回换算到基本单位的系数和量纲；单位名区分大小写
+///
+/// 完整名字优先，例如 `min` 是分钟而不是毫英寸，`Pa` 是帕斯卡。
+pub fn lookup(name: &str) -> Option<(Decimal, Dim)> {
+    if let Some(def) = UNITS.iter().find(|u| u.names.contains(&name)) {
+        return Some((Decimal::from_str(def.factor).ok()?, def.dim));
+    }
+    for (prefix, shift) in BINARY_PREFIXES {
+        if let Some(rest) = name.strip_prefix(prefix)
+            && let Some(def) = prefixable(rest, Prefixes::Data)
+        {
+            let factor =
+                Decimal::from(1u128 << shift).checked_mul(Decimal::from_str(def.factor).ok()?)?;
+            return Some((factor, def.dim));
+        }
+    }
+    for (prefix, exp) in SI_PREFIXES {
+        let Some(rest) = name.strip_prefix(prefix) else {
+            continue;
+        };
+        let Some(def) = prefixable(rest, Prefixes::Si).or_else(|| prefixable(rest, Prefixes::Data))
+        else {
+            continue;
+        };
+        // 信息量只有 k、M、G…… 放大前缀（包括习惯写法 `K`）；`K` 不用于其他单位
+        let allowed = match def.prefixes {
+            Prefixes::Data => exp >= 3,
+            _ => prefix != "K",
+        };
+        if !allowed {
+            continue;
+        }
+        let scale = if exp >= 0 {
+            Decimal::from(10u128.pow(exp as u32))
+        } else {
+            Decimal::new(1, (-exp) as u32)
+        };
+        return Some((
+            scale.checked_mul(Decimal::from_str(def.factor).ok()?)?,
+            def.dim,
+        ));
+    }
+    None
+}
+
+fn prefixable(symbol: &str, prefixes: Prefixes) -> Option<&'static UnitDef> {
+    UNITS
+        .iter()
+        .find(|u| u.prefixes == prefixes && u.names[0] == symbol)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn factor(name: &str) -> String {
+        lookup(name).unwrap().0.normalize().to_string()
+    }
+
+    #[test]
+    fn resolves_prefixes() {
+        assert_eq!(factor("km"), "1000");
+        assert_eq!(factor("ms"), "0.001");
+        assert_eq!(factor("min"), "60");
+        assert_eq!(factor("GiB"), "1073741824");
+        assert_eq!(factor("MB"), "1000000");
+        assert_eq!(factor("KB"), "1000");
+        assert_eq!(factor("Mbit"), "125000");
+        assert_eq!(factor("kg"), "1");
+        assert_eq!(factor("kWh"), "3600000");
+        assert_eq!(factor("dam"), "10");
+        assert_eq!(lookup("mB"), None);
+        assert_eq!(lookup("Km"), None);
+        assert_eq!(lookup("kft"), None);
+        assert_eq!(lookup("foo"), None);
+    }
+
+    #[test]
+    fn displays_dimensions() {
+        assert_eq!(SPEED.to_string(), "m/s");
+        assert_eq!(ENERGY.to_string(), "J");
+        assert_eq!(Dim([0, 0, -1, 0, 0, 0, 0, 1]).to_string(), "B/s");
+        assert_eq!(Dim([1, 0, -2, 0, 0, 0, 0, 0]).to_string(), "m/s^2");
+        assert_eq!(Dim([0, 0, -2, 0, 0, 0, 0, 0]).to_string(), "s^-2");
+        assert_eq!(Dim([1, 0, -1, -1, 0, 0, 0, 0]).to_string(), "m/(s A)");
+        assert_eq!(SPEED.mul(TIME), LENGTH);
+        assert_eq!(VOLUME.root(3), Some(LENGTH));
+        assert_eq!(VOLUME.root(2), None);
+    }
+}
@@ -185,7 +185,7 @@ pub fn run(opts: &crate::cli::Options) -> Result<i64, String> {
         let match_mode = Arc::new(match_mode);
         let num_lines = opts.num_lines;
         let max_len = opts.max_len;
-        Arc::new(move |filename: String| {
+        move |filename: String| {
             check_file(
                 filename,
                 Arc::clone(&match_mode),
@@ -193,12 +193,10 @@ pub fn run(opts: &crate::cli::Options) -> Result<i64, String> {
                 num_lines,
                 max_len,
             );
-        })
+        }
     };
 
-    let wg = Arc::new(terminalw::WaitGroup::new());
-    terminalw::find(&opts.root_dir, task, Arc::clone(&wg), 0);
-    wg.wait();
+    terminalw::find_blocking(&opts.root_dir, task, 0);
 
     Ok(terminalw::COUNT.load(Ordering::Relaxed))
 }
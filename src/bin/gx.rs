@@ -1,5 +1,5 @@
 use std::fs;
-use std::io::{self, BufRead, Read};
+use std::io::{self, Read};
 use std::path::{Path, PathBuf};
 
 use clap::{CommandFactory, Parser};
@@ -9,10 +9,29 @@ use rust_tools::cw::{
 };
 use serde::Serialize;
 
+#[path = "gx/deps.rs"]
+mod deps;
+#[path = "gx/diff.rs"]
+mod diff;
+#[path = "gx/dot.rs"]
+mod dot;
+#[path = "gx/graphml.rs"]
+mod graphml;
+#[path = "gx/input.rs"]
+mod input;
+#[path = "gx/nodelink.rs"]
+mod nodelink;
+#[path = "gx/stats.rs"]
+mod stats;
+#[path = "gx/view.rs"]
+mod view;
+
+use input::{GraphData, InputFormat};
+
 #[derive(Parser)]
 #[command(
     about = "Graph analysis utilities (go_tools graphx/gx compatible subset)",
-    after_help = "modes:\n  cycle      detect cycle\n  scc        strongly connected components (directed)\n  topo       topological sort (directed DAG)\n  sp         shortest path: requires -from and -to\n  cc         connected components\n  mst        minimum spanning tree (undirected weighted)\n\ninput edge format:\n  unweighted: <from> <to>\n  weighted:   <from> <to> <weight>\n  comments:   lines starting with # or //\n\nextra output:\n  --fmt text|json|csv   result output format\n  --viz dot|mermaid     include graph visualization text\n  --out <file>          write output to file\n  --dir <path>          batch analyze all graph files in directory\n"
+    after_help = "modes:\n  cycle      detect cycle\n  scc        strongly connected components (directed)\n  topo       topological sort (directed DAG)\n  sp         shortest path: requires -from and -to\n  cc         connected components\n  mst        minimum spanning tree (undirected weighted)\n  deps       dependency graph of a Cargo workspace or Rust source tree:\n             gx deps [PATH] [--analyze cycle|scc|topo|...] [--level crate|module]\n             [--external] [--dev] [--mod-edges]\n  view       interactive terminal explorer: gx view FILE\n  diff       compare two snapshots: gx diff OLD NEW (files, or directories paired\n             by relative path); with --dir, consecutive files in name order\n  stats      degree distribution, density, diameter, average path length and\n             top central nodes (--top K)\n\ninput edge format:\n  unweighted: <from> <to>\n  weighted:   <from> <to> <weight>\n  comments:   lines starting with # or //\n\nother input formats (detected by extension or content, or set with --in-fmt):\n  dot        Graphviz DOT (.dot, .gv)\n  graphml    GraphML (.graphml)\n  json       node-link JSON as written by networkx/d3 (.json)\n  matrix     CSV adjacency matrix with a header row of node names\n  node/edge attributes are kept; edge weight comes from the weight attribute\n  (or a numeric label), matrix cells are weights\n\nextra output:\n  --fmt text|json|csv   result output format\n  --viz dot|mermaid     include graph visualization text\n  --out <file>          write output to file\n  --dir <path>          batch analyze all graph files in directory\n"
 )]
 struct Cli {
     #[arg(short = 'f', value_name = "FILE", num_args = 0..=1, default_missing_value = "")]
@@ -23,7 +42,7 @@ struct Cli {
 
     #[arg(
         long,
-        default_value = ".txt,.graph,.edgelist,.csv",
+        default_value = ".txt,.graph,.edgelist,.csv,.dot,.gv,.graphml,.json",
         value_name = "EXTS"
     )]
     ext: String,
@@ -59,6 +78,51 @@ struct Cli {
     )]
     sep: String,
 
+    #[arg(
+        long = "in-fmt",
+        default_value = "auto",
+        value_name = "FMT",
+        help = "input format: auto|edges|dot|graphml|json|matrix"
+    )]
+    input_format: String,
+
+    #[arg(
+        long,
+        default_value = "cycle",
+        value_name = "MODE",
+        help = "deps: analysis mode run on the extracted graph"
+    )]
+    analyze: String,
+
+    #[arg(
+        long,
+        default_value = "module",
+        value_name = "LEVEL",
+        help = "deps: graph level crate|module"
+    )]
+    level: String,
+
+    #[arg(
+        long,
+        default_value_t = false,
+        help = "deps: include crates outside the workspace"
+    )]
+    external: bool,
+
+    #[arg(
+        long,
+        default_value_t = false,
+        help = "deps: include dev-dependencies (crate level)"
+    )]
+    dev: bool,
+
+    #[arg(
+        long = "mod-edges",
+        default_value_t = false,
+        help = "deps: add parent -> child module edges"
+    )]
+    mod_edges: bool,
+
     #[arg(long = "fmt", default_value = "text", value_name = "FMT")]
     format: String,
 
@@ -78,15 +142,16 @@ struct Cli {
     )]
     out: String,
 
-    #[arg(value_name = "MODE [FILE]", num_args = 0..=2)]
-    positional: Vec<String>,
-}
+    #[arg(
+        long,
+        default_value_t = 5,
+        value_name = "K",
+        help = "stats: number of central nodes to list"
+    )]
+    top: usize,
 
-#[derive(Clone, Debug)]
-struct GraphEdge {
-    from: String,
-    to: String,
-    weight: f64,
+    #[arg(value_name = "MODE [FILE] [FILE2]", num_args = 0..=3)]
+    positional: Vec<String>,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -98,9 +163,11 @@ struct AnalysisOptions {
     undirected: bool,
     viz: String,
     source: String,
+    input_format: Option<InputFormat>,
+    top: usize,
 }
 
-#[derive(Clone, Debug, Serialize, Default)]
+#[derive(Clone, Debug, Serialize, Default, PartialEq)]
 struct EdgeOut {
     from: String,
     to: String,
@@ -133,6 +200,10 @@ struct AnalysisResult {
     total_weight: Option<f64>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     mst_edges: Vec<EdgeOut>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stats: Option<stats::GraphStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    diff: Option<diff::GraphDiff>,
     #[serde(skip_serializing_if = "String::is_empty")]
     warning: String,
     #[serde(skip_serializing_if = "String::is_empty")]
@@ -152,7 +223,7 @@ fn main() {
     };
 
     let mode = mode.to_lowercase();
-    let (input_file, file_flag_set) = resolve_input_file(cli.file, &cli.positional);
+    let (input_file, file_flag_set) = resolve_input_file(cli.file.clone(), &cli.positional);
     if file_flag_set && input_file.is_empty() {
         eprintln!("flag -f requires a file path; remove -f to read from stdin");
         std::process::exit(1);
@@ -173,6 +244,14 @@ fn main() {
         std::process::exit(1);
     }
 
+    let input_format = match InputFormat::from_name(&cli.input_format) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+    };
+
     let weighted = cli.weighted || mode == "mst";
     let undirected = cli.undirected || mode == "cc" || mode == "components" || mode == "mst";
 
@@ -184,55 +263,85 @@ fn main() {
         undirected,
         viz,
         source: String::new(),
+        input_format,
+        top: cli.top,
     };
 
-    let files = match collect_input_files(&input_file, &cli.dir, &cli.ext, cli.recursive) {
-        Ok(v) => v,
-        Err(err) => {
-            eprintln!("failed to collect input files: {err}");
+    if mode == "view" {
+        if let Err(err) = view_graph(&input_file, &opts, &cli.sep) {
+            eprintln!("{err}");
             std::process::exit(1);
         }
-    };
+        return;
+    }
 
     let mut results = Vec::new();
-    if files.is_empty() {
-        let source = if input_file.is_empty() {
-            "<stdin>".to_string()
-        } else {
-            input_file.clone()
+    if mode == "deps" {
+        results.push(analyze_deps(&cli, &input_file, &opts));
+    } else if mode == "diff" {
+        let new_file = cli
+            .positional
+            .get(if file_flag_set { 1 } else { 2 })
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default();
+        let pairs = match diff_pairs(&cli, &input_file, &new_file) {
+            Ok(v) => v,
+            Err(err) => {
+                eprintln!("{err}");
+                std::process::exit(1);
+            }
         };
-
-        if input_file.is_empty() && stdin_is_tty() {
-            eprintln!("reading edge list from stdin, press Ctrl-D to finish");
+        for (old, new) in pairs {
+            results.push(diff_pair(&old, &new, &opts, &cli.sep));
         }
-
-        let input = match open_input(&input_file) {
-            Ok(r) => r,
+    } else {
+        let files = match collect_input_files(&input_file, &cli.dir, &cli.ext, cli.recursive) {
+            Ok(v) => v,
             Err(err) => {
-                eprintln!("failed to open input: {err}");
+                eprintln!("failed to collect input files: {err}");
                 std::process::exit(1);
             }
         };
-        let mut local_opts = opts.clone();
-        local_opts.source = source;
-        results.push(analyze_single_source(input, &local_opts, &cli.sep));
-    } else {
-        for path in files {
-            match fs::File::open(&path) {
-                Ok(f) => {
-                    let mut local_opts = opts.clone();
-                    local_opts.source = path.to_string_lossy().to_string();
-                    results.push(analyze_single_source(Box::new(f), &local_opts, &cli.sep));
-                }
+
+        if files.is_empty() {
+            let source = if input_file.is_empty() {
+                "<stdin>".to_string()
+            } else {
+                input_file.clone()
+            };
+
+            if input_file.is_empty() && stdin_is_tty() {
+                eprintln!("reading graph from stdin, press Ctrl-D to finish");
+            }
+
+            let input = match open_input(&input_file) {
+                Ok(r) => r,
                 Err(err) => {
-                    results.push(AnalysisResult {
-                        source: path.to_string_lossy().to_string(),
-                        mode: mode.clone(),
-                        weighted,
-                        undirected,
-                        error: err.to_string(),
-                        ..AnalysisResult::default()
-                    });
+                    eprintln!("failed to open input: {err}");
+                    std::process::exit(1);
+                }
+            };
+            let mut local_opts = opts.clone();
+            local_opts.source = source;
+            results.push(analyze_single_source(input, &local_opts, &cli.sep));
+        } else {
+            for path in files {
+                match fs::File::open(&path) {
+                    Ok(f) => {
+                        let mut local_opts = opts.clone();
+                        local_opts.source = path.to_string_lossy().to_string();
+                        results.push(analyze_single_source(Box::new(f), &local_opts, &cli.sep));
+                    }
+                    Err(err) => {
+                        results.push(AnalysisResult {
+                            source: path.to_string_lossy().to_string(),
+                            mode: mode.clone(),
+                            weighted,
+                            undirected,
+                            error: err.to_string(),
+                            ..AnalysisResult::default()
+                        });
+                    }
                 }
             }
         }
@@ -307,7 +416,10 @@ fn analyze_single_source(
         };
     }
 
-    let edges = match parse_graph_edges(buf.as_bytes(), opts.weighted, sep) {
+    let format = opts
+        .input_format
+        .unwrap_or_else(|| InputFormat::detect(&opts.source, &buf, sep));
+    let graph = match input::parse_graph(&buf, format, opts.weighted, sep) {
         Ok(v) => v,
         Err(err) => {
             return AnalysisResult {
@@ -321,7 +433,7 @@ fn analyze_single_source(
         }
     };
 
-    if edges.is_empty() {
+    if graph.nodes.is_empty() {
         return AnalysisResult {
             source: opts.source.clone(),
             mode: opts.mode.clone(),
@@ -332,102 +444,170 @@ fn analyze_single_source(
         };
     }
 
-    analyze_graph(&edges, opts)
+    // 输入自己声明为无向图（DOT `graph`、GraphML `edgedefault="undirected"` 等）时按无向分析
+    let mut opts = opts.clone();
+    opts.undirected |= graph.directed == Some(false);
+    analyze_graph(&graph, &opts)
 }
 
-fn parse_graph_edges<R: Read>(
-    reader: R,
-    weighted: bool,
-    sep: &str,
-) -> Result<Vec<GraphEdge>, String> {
-    let mut res = Vec::new();
-    let br = io::BufReader::new(reader);
-    for (idx, line) in br.lines().enumerate() {
-        let line_no = idx + 1;
-        let line = line.map_err(|e| e.to_string())?;
-        let line = line.trim();
-        if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
-            continue;
-        }
+/// 读取并解析整张图，`path` 为空时读标准输入
+fn read_graph(path: &str, opts: &AnalysisOptions, sep: &str) -> Result<GraphData, String> {
+    let mut buf = String::new();
+    open_input(path)
+        .and_then(|mut r| r.read_to_string(&mut buf))
+        .map_err(|e| format!("failed to read input: {e}"))?;
+    let format = opts
+        .input_format
+        .unwrap_or_else(|| InputFormat::detect(path, &buf, sep));
+    input::parse_graph(&buf, format, opts.weighted, sep)
+}
+
+/// `gx view`：读入整张图后进入交互浏览
+fn view_graph(path: &str, opts: &AnalysisOptions, sep: &str) -> Result<(), String> {
+    let graph = read_graph(path, opts, sep)?;
+    let model = view::Model::new(&graph, opts.undirected || graph.directed == Some(false));
+    let source = if path.is_empty() { "<stdin>" } else { path };
+    view::run(&model, source).map_err(|e| format!("view: {e}"))
+}
 
-        let parts = split_fields(line, sep);
-        if parts.len() < 2 {
+/// `gx diff` 要比较的文件对：两个文件；两个目录按相对路径配对，只在一边存在的文件和空图比较；
+/// `--dir` 时按文件名排序后相邻的两个快照依次比较
+fn diff_pairs(cli: &Cli, old: &str, new: &str) -> Result<Vec<(String, String)>, String> {
+    let display = |p: &PathBuf| p.to_string_lossy().to_string();
+    if !cli.dir.trim().is_empty() {
+        let files = collect_input_files("", &cli.dir, &cli.ext, cli.recursive)
+            .map_err(|e| format!("failed to collect input files: {e}"))?;
+        if files.len() < 2 {
             return Err(format!(
-                "line {line_no}: expected at least 2 columns, got {}",
-                parts.len()
+                "diff --dir needs at least 2 graph files in {:?}",
+                cli.dir
             ));
         }
-
-        let mut edge = GraphEdge {
-            from: parts[0].clone(),
-            to: parts[1].clone(),
-            weight: 1.0,
-        };
-
-        if weighted {
-            if parts.len() < 3 {
-                return Err(format!(
-                    "line {line_no}: expected 3 columns for weighted graph"
-                ));
+        return Ok(files
+            .windows(2)
+            .map(|w| (display(&w[0]), display(&w[1])))
+            .collect());
+    }
+    if old.is_empty() || new.is_empty() {
+        return Err("diff requires two inputs: gx diff OLD NEW".to_string());
+    }
+    match (Path::new(old).is_dir(), Path::new(new).is_dir()) {
+        (false, false) => Ok(vec![(old.to_string(), new.to_string())]),
+        (true, true) => {
+            let mut pairs: std::collections::BTreeMap<PathBuf, (String, String)> =
+                std::collections::BTreeMap::new();
+            for (root, is_old) in [(old, true), (new, false)] {
+                let files = collect_input_files("", root, &cli.ext, cli.recursive)
+                    .map_err(|e| format!("failed to collect input files: {e}"))?;
+                for f in files {
+                    let rel = f.strip_prefix(root).unwrap_or(&f).to_path_buf();
+                    let entry = pairs.entry(rel).or_default();
+                    if is_old {
+                        entry.0 = display(&f);
+                    } else {
+                        entry.1 = display(&f);
+                    }
+                }
+            }
+            if pairs.is_empty() {
+                return Err(format!("no graph files found in {old:?} or {new:?}"));
             }
-            edge.weight = parts[2]
-                .parse::<f64>()
-                .map_err(|e| format!("line {line_no}: invalid weight {:?}: {e}", parts[2]))?;
+            Ok(pairs.into_values().collect())
         }
-
-        res.push(edge);
+        _ => Err("diff expects two files or two directories".to_string()),
     }
-    Ok(res)
-}
-
-fn split_fields(line: &str, sep: &str) -> Vec<String> {
-    if sep.is_empty() {
-        return line.split_whitespace().map(|s| s.to_string()).collect();
-    }
-    line.split(sep)
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .map(|s| s.to_string())
-        .collect()
 }
 
-fn count_nodes(edges: &[GraphEdge]) -> usize {
-    let mut set = SkipSet::new(16);
-    for e in edges {
-        set.insert(e.from.clone());
-        set.insert(e.to.clone());
+/// 比较两个快照；路径为空表示那一边不存在，按空图处理
+fn diff_pair(old: &str, new: &str, opts: &AnalysisOptions, sep: &str) -> AnalysisResult {
+    let name = |p: &str| if p.is_empty() { "(none)" } else { p }.to_string();
+    let mut result = AnalysisResult {
+        source: format!("{} -> {}", name(old), name(new)),
+        mode: opts.mode.clone(),
+        weighted: opts.weighted,
+        undirected: opts.undirected,
+        ..AnalysisResult::default()
+    };
+    let load = |path: &str| {
+        if path.is_empty() {
+            Ok(GraphData::default())
+        } else {
+            read_graph(path, opts, sep).map_err(|e| format!("{path}: {e}"))
+        }
+    };
+    match (load(old), load(new)) {
+        (Ok(a), Ok(b)) => {
+            result.undirected |= a.directed == Some(false) || b.directed == Some(false);
+            result.nodes = b.nodes.len();
+            result.edges = b.edges.len();
+            result.diff = Some(diff::compare(&a, &b, result.undirected, opts.weighted));
+            if !opts.viz.is_empty() {
+                result.viz = build_viz(&b, &opts.viz, result.undirected, opts.weighted);
+            }
+        }
+        (Err(err), _) | (_, Err(err)) => result.error = err,
     }
-    set.len()
+    result
 }
 
-fn sorted_nodes(edges: &[GraphEdge]) -> Vec<String> {
-    let mut set = SkipSet::new(16);
-    for e in edges {
-        set.insert(e.from.clone());
-        set.insert(e.to.clone());
+/// `gx deps`：从工作区提取依赖图，再按 `--analyze` 指定的模式分析
+fn analyze_deps(cli: &Cli, path: &str, base: &AnalysisOptions) -> AnalysisResult {
+    let path = if path.is_empty() { "." } else { path };
+    let mode = cli.analyze.to_lowercase();
+    let opts = AnalysisOptions {
+        weighted: cli.weighted || mode == "mst",
+        undirected: cli.undirected || mode == "cc" || mode == "components" || mode == "mst",
+        mode,
+        source: path.to_string(),
+        ..base.clone()
+    };
+    let graph = deps::Level::from_name(&cli.level).and_then(|level| {
+        let deps_opts = deps::DepsOptions {
+            level,
+            mod_edges: cli.mod_edges,
+            dev: cli.dev,
+            external: cli.external,
+        };
+        deps::build(Path::new(path), &deps_opts)
+    });
+    match graph {
+        Ok(graph) => analyze_graph(&graph, &opts),
+        Err(err) => AnalysisResult {
+            source: opts.source.clone(),
+            mode: opts.mode.clone(),
+            weighted: opts.weighted,
+            undirected: opts.undirected,
+            error: err,
+            ..AnalysisResult::default()
+        },
     }
-    set.to_vec()
 }
 
-fn build_directed(edges: &[GraphEdge]) -> DirectedGraph<String> {
+fn build_directed(graph: &GraphData) -> DirectedGraph<String> {
     let mut g = DirectedGraph::new();
-    for e in edges {
+    for n in &graph.nodes {
+        g.add_node(n.id.clone());
+    }
+    for e in &graph.edges {
         g.add_edge(e.from.clone(), e.to.clone());
     }
     g
 }
 
-fn build_undirected(edges: &[GraphEdge]) -> UndirectedGraph<String> {
+fn build_undirected(graph: &GraphData) -> UndirectedGraph<String> {
     let mut g = UndirectedGraph::new();
-    for e in edges {
+    for n in &graph.nodes {
+        g.add_node(n.id.clone());
+    }
+    for e in &graph.edges {
         g.add_edge(e.from.clone(), e.to.clone());
     }
     g
 }
 
-fn build_weighted_directed(edges: &[GraphEdge], undirected: bool) -> WeightedDirectedGraph<String> {
+fn build_weighted_directed(graph: &GraphData, undirected: bool) -> WeightedDirectedGraph<String> {
     let mut g = WeightedDirectedGraph::new();
-    for e in edges {
+    for e in &graph.edges {
         g.add_edge(e.from.clone(), e.to.clone(), e.weight);
         if undirected {
             g.add_edge(e.to.clone(), e.from.clone(), e.weight);
@@ -436,9 +616,9 @@ fn build_weighted_directed(edges: &[GraphEdge], undirected: bool) -> WeightedDir
     g
 }
 
-fn build_weighted_undirected(edges: &[GraphEdge]) -> WeightedUndirectedGraph<String> {
+fn build_weighted_undirected(graph: &GraphData) -> WeightedUndirectedGraph<String> {
     let mut g = WeightedUndirectedGraph::new();
-    for e in edges {
+    for e in &graph.edges {
         g.add_edge(e.from.clone(), e.to.clone(), e.weight);
     }
     g
@@ -453,12 +633,12 @@ fn normalize_components(mut groups: Vec<Vec<String>>) -> Vec<Vec<String>> {
     groups
 }
 
-fn analyze_graph(edges: &[GraphEdge], opts: &AnalysisOptions) -> AnalysisResult {
+fn analyze_graph(graph: &GraphData, opts: &AnalysisOptions) -> AnalysisResult {
     let mut result = AnalysisResult {
         source: opts.source.clone(),
         mode: opts.mode.clone(),
-        nodes: count_nodes(edges),
-        edges: edges.len(),
+        nodes: graph.nodes.len(),
+        edges: graph.edges.len(),
         weighted: opts.weighted,
         undirected: opts.undirected,
         ..AnalysisResult::default()
@@ -467,10 +647,10 @@ fn analyze_graph(edges: &[GraphEdge], opts: &AnalysisOptions) -> AnalysisResult
     match opts.mode.as_str() {
         "cycle" | "cy" => {
             if opts.undirected {
-                let g = build_undirected(edges);
+                let g = build_undirected(graph);
                 result.has_cycle = Some(g.has_cycle());
             } else {
-                let g = build_directed(edges);
+                let g = build_directed(graph);
                 let has = g.has_cycle();
                 result.has_cycle = Some(has);
                 if has {
@@ -482,7 +662,7 @@ fn analyze_graph(edges: &[GraphEdge], opts: &AnalysisOptions) -> AnalysisResult
             if opts.undirected {
                 result.error = "scc is for directed graph only".to_string();
             } else {
-                let g = build_directed(edges);
+                let g = build_directed(graph);
                 let comps = g.strong_components();
                 result.components = normalize_components(
                     comps
@@ -496,7 +676,7 @@ fn analyze_graph(edges: &[GraphEdge], opts: &AnalysisOptions) -> AnalysisResult
             }
         }
         "cc" | "components" => {
-            let g = build_undirected(edges);
+            let g = build_undirected(graph);
             let comps = g.groups();
             result.components = normalize_components(comps);
         }
@@ -504,7 +684,7 @@ fn analyze_graph(edges: &[GraphEdge], opts: &AnalysisOptions) -> AnalysisResult
             if opts.undirected {
                 result.error = "topo is for directed graph only".to_string();
             } else {
-                let g = build_directed(edges);
+                let g = build_directed(graph);
                 match g.sorted() {
                     Some(order) => result.order = order,
                     None => {
@@ -528,9 +708,9 @@ fn analyze_graph(edges: &[GraphEdge], opts: &AnalysisOptions) -> AnalysisResult
                 result.total_weight = Some(0.0);
             } else if !opts.weighted {
                 let path = if opts.undirected {
-                    build_undirected(edges).path(&opts.from, &opts.to)
+                    build_undirected(graph).path(&opts.from, &opts.to)
                 } else {
-                    build_directed(edges).path(&opts.from, &opts.to)
+                    build_directed(graph).path(&opts.from, &opts.to)
                 };
                 match path {
                     Some(p) => {
@@ -540,7 +720,7 @@ fn analyze_graph(edges: &[GraphEdge], opts: &AnalysisOptions) -> AnalysisResult
                     None => result.error = format!("no path from {:?} to {:?}", opts.from, opts.to),
                 }
             } else if opts.undirected {
-                let mut g = build_weighted_undirected(edges);
+                let mut g = build_weighted_undirected(graph);
                 let path_edges = g.shortest_path(&opts.from, &opts.to);
                 if path_edges.is_empty() {
                     result.error = format!("no path from {:?} to {:?}", opts.from, opts.to);
@@ -555,7 +735,7 @@ fn analyze_graph(edges: &[GraphEdge], opts: &AnalysisOptions) -> AnalysisResult
                     }
                 }
             } else {
-                let mut g = build_weighted_directed(edges, false);
+                let mut g = build_weighted_directed(graph, false);
                 let path_edges = g.shortest_path(&opts.from, &opts.to);
                 if path_edges.is_empty() {
                     result.error = format!("no path from {:?} to {:?}", opts.from, opts.to);
@@ -572,7 +752,7 @@ fn analyze_graph(edges: &[GraphEdge], opts: &AnalysisOptions) -> AnalysisResult
             }
         }
         "mst" => {
-            let g = build_weighted_undirected(edges);
+            let g = build_weighted_undirected(graph);
             let mst = g.mst();
             result.mst_edges = mst_edges_out(&mst);
             result.total_weight = Some(mst.total_weight());
@@ -581,11 +761,12 @@ fn analyze_graph(edges: &[GraphEdge], opts: &AnalysisOptions) -> AnalysisResult
                     "graph is disconnected, result is a minimum spanning forest".to_string();
             }
         }
+        "stats" => result.stats = Some(stats::compute(graph, opts.undirected, opts.top)),
         other => result.error = format!("unknown mode {:?}", other),
     }
 
     if !opts.viz.is_empty() {
-        result.viz = build_viz(edges, &opts.viz, opts.undirected, opts.weighted);
+        result.viz = build_viz(graph, &opts.viz, opts.undirected, opts.weighted);
     }
 
     result
@@ -627,10 +808,10 @@ fn normalize_pair<'a>(a: &'a String, b: &'a String) -> (&'a String, &'a String)
     if a <= b { (a, b) } else { (b, a) }
 }
 
-fn build_viz(edges: &[GraphEdge], viz: &str, undirected: bool, weighted: bool) -> String {
+fn build_viz(graph: &GraphData, viz: &str, undirected: bool, weighted: bool) -> String {
     match viz {
-        "dot" => build_dot(edges, undirected, weighted),
-        "mermaid" => build_mermaid(edges, undirected, weighted),
+        "dot" => build_dot(graph, undirected, weighted),
+        "mermaid" => build_mermaid(graph, undirected, weighted),
         _ => String::new(),
     }
 }
@@ -655,30 +836,55 @@ fn format_weight(weight: f64) -> String {
     }
 }
 
-fn build_dot(edges: &[GraphEdge], undirected: bool, weighted: bool) -> String {
-    let mut lines = Vec::with_capacity(edges.len().saturating_add(8));
+/// `[k="v", ...]`，没有属性时为空
+fn dot_attr_list(attrs: &input::Attrs) -> String {
+    if attrs.is_empty() {
+        return String::new();
+    }
+    let items: Vec<String> = attrs
+        .iter()
+        .map(|(k, v)| format!("{}=\"{}\"", k, escape_dot_label(v)))
+        .collect();
+    format!(" [{}]", items.join(", "))
+}
+
+/// 输入中的图、节点和边属性原样写回；带权时权重写到边的 `label`
+fn build_dot(graph: &GraphData, undirected: bool, weighted: bool) -> String {
+    let mut lines = Vec::with_capacity(graph.edges.len().saturating_add(8));
     let (header, op) = if undirected {
         ("graph G {", "--")
     } else {
         ("digraph G {", "->")
     };
     lines.push(header.to_string());
-    for node in sorted_nodes(edges) {
-        lines.push(format!("  \"{}\";", escape_dot_label(&node)));
+    for (k, v) in &graph.attrs {
+        lines.push(format!("  {}=\"{}\";", k, escape_dot_label(v)));
+    }
+    for id in graph.sorted_node_ids() {
+        let attrs = graph.node(&id).map(|n| dot_attr_list(&n.attrs));
+        lines.push(format!(
+            "  \"{}\"{};",
+            escape_dot_label(&id),
+            attrs.unwrap_or_default()
+        ));
     }
-    for e in edges {
+    for e in &graph.edges {
         let from = escape_dot_label(&e.from);
         let to = escape_dot_label(&e.to);
+        let mut attrs = e.attrs.clone();
         if weighted {
+            input::set_attr(&mut attrs, "label", &format_weight(e.weight));
+        }
+        if attrs.is_empty() {
+            lines.push(format!("  \"{}\" {} \"{}\";", from, op, to));
+        } else {
             lines.push(format!(
-                "  \"{}\" {} \"{}\" [label=\"{}\"] ;",
+                "  \"{}\" {} \"{}\"{} ;",
                 from,
                 op,
                 to,
-                format_weight(e.weight)
+                dot_attr_list(&attrs)
             ));
-        } else {
-            lines.push(format!("  \"{}\" {} \"{}\";", from, op, to));
         }
     }
     lines.push("}".to_string());
@@ -693,8 +899,8 @@ fn escape_mermaid_label(s: &str) -> String {
     s.replace('"', "\\\"")
 }
 
-fn build_mermaid(edges: &[GraphEdge], undirected: bool, weighted: bool) -> String {
-    let nodes = sorted_nodes(edges);
+fn build_mermaid(graph: &GraphData, undirected: bool, weighted: bool) -> String {
+    let nodes = graph.sorted_node_ids();
     let mut id_map: Box<SkipMap<String, String>> =
         SkipMap::new(16, |a: &String, b: &String| a.cmp(b) as i32);
     let mut lines = Vec::new();
@@ -702,10 +908,14 @@ fn build_mermaid(edges: &[GraphEdge], undirected: bool, weighted: bool) -> Strin
     for (idx, node) in nodes.iter().enumerate() {
         let id = mermaid_id(idx);
         id_map.insert(node.clone(), id.clone());
-        lines.push(format!("    {id}[\"{}\"]", escape_mermaid_label(node)));
+        let label = graph
+            .node(node)
+            .and_then(|n| input::attr(&n.attrs, "label"))
+            .unwrap_or(node);
+        lines.push(format!("    {id}[\"{}\"]", escape_mermaid_label(label)));
     }
     let op = if undirected { "---" } else { "-->" };
-    for e in edges {
+    for e in &graph.edges {
         let from = id_map
             .get_ref(&e.from)
             .cloned()
@@ -808,6 +1018,12 @@ fn format_text_result(r: &AnalysisResult) -> String {
             out.push_str(&format!("{}. ({}-{}) {:.6}\n", idx + 1, e.from, e.to, w));
         }
     }
+    if let Some(d) = &r.diff {
+        out.push_str(&format_diff_text(d, r.undirected));
+    }
+    if let Some(s) = &r.stats {
+        out.push_str(&format_stats_text(s));
+    }
     if !r.warning.is_empty() {
         out.push_str(&format!("warning: {}\n", r.warning));
     }
@@ -819,7 +1035,310 @@ fn format_text_result(r: &AnalysisResult) -> String {
     out
 }
 
+fn format_edge(e: &EdgeOut, undirected: bool) -> String {
+    let arrow = if undirected { "--" } else { "->" };
+    match e.weight {
+        Some(w) => format!("{} {arrow} {} ({})", e.from, e.to, format_weight(w)),
+        None => format!("{} {arrow} {}", e.from, e.to),
+    }
+}
+
+fn format_diff_text(d: &diff::GraphDiff, undirected: bool) -> String {
+    if d.is_empty() {
+        return "changes: none\n".to_string();
+    }
+    let mut out = String::new();
+    for (label, items) in [
+        ("added_nodes", &d.added_nodes),
+        ("removed_nodes", &d.removed_nodes),
+    ] {
+        if !items.is_empty() {
+            out.push_str(&format!(
+                "{label} ({}): {}\n",
+                items.len(),
+                items.join(", ")
+            ));
+        }
+    }
+    for (label, edges) in [
+        ("added_edges", &d.added_edges),
+        ("removed_edges", &d.removed_edges),
+    ] {
+        if !edges.is_empty() {
+            out.push_str(&format!("{label} ({}):\n", edges.len()));
+            for e in edges {
+                out.push_str(&format!("  {}\n", format_edge(e, undirected)));
+            }
+        }
+    }
+    if !d.weight_changes.is_empty() {
+        out.push_str(&format!("weight_changes ({}):\n", d.weight_changes.len()));
+        let arrow = if undirected { "--" } else { "->" };
+        for c in &d.weight_changes {
+            out.push_str(&format!(
+                "  {} {arrow} {}: {} => {}\n",
+                c.from,
+                c.to,
+                format_weight(c.old),
+                format_weight(c.new)
+            ));
+        }
+    }
+    if !d.new_cycles.is_empty() {
+        out.push_str(&format!("new_cycles ({}):\n", d.new_cycles.len()));
+        for (idx, cycle) in d.new_cycles.iter().enumerate() {
+            out.push_str(&format!("{}. {}\n", idx + 1, cycle.join(" -> ")));
+        }
+    }
+    if !d.scc_merges.is_empty() {
+        out.push_str(&format!("scc_merges ({}):\n", d.scc_merges.len()));
+        for (idx, m) in d.scc_merges.iter().enumerate() {
+            let parts: Vec<String> = m
+                .merged
+                .iter()
+                .map(|p| format!("[{}]", p.join(", ")))
+                .collect();
+            out.push_str(&format!(
+                "{}. {} <= {}\n",
+                idx + 1,
+                m.component.join(", "),
+                parts.join(" + ")
+            ));
+        }
+    }
+    out
+}
+
+fn format_stats_text(s: &stats::GraphStats) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("density: {:.6}\n", s.density));
+    out.push_str(&format!(
+        "components: {} isolated: {} self_loops: {}\n",
+        s.components, s.isolated, s.self_loops
+    ));
+    let mut degree = format!(
+        "degree: min={} max={} mean={:.3}",
+        s.min_degree, s.max_degree, s.mean_degree
+    );
+    if let (Some(i), Some(o)) = (s.max_in_degree, s.max_out_degree) {
+        degree.push_str(&format!(" max_in={i} max_out={o}"));
+    }
+    out.push_str(&degree);
+    out.push('\n');
+    out.push_str(&format!(
+        "degree_distribution: {}\n",
+        flatten_degree_distribution(&s.degree_distribution, " ")
+    ));
+    out.push_str(&format!("diameter: {} (hops)\n", s.diameter));
+    out.push_str(&format!(
+        "average_path_length: {:.6} over {} reachable pairs\n",
+        s.average_path_length, s.reachable_pairs
+    ));
+    if !s.central.is_empty() {
+        out.push_str("central (betweenness, closeness, degree):\n");
+        for (idx, c) in s.central.iter().enumerate() {
+            out.push_str(&format!(
+                "{}. {} {:.6} {:.6} {}\n",
+                idx + 1,
+                c.node,
+                c.betweenness,
+                c.closeness,
+                c.degree
+            ));
+        }
+    }
+    out
+}
+
+fn flatten_degree_distribution(dist: &[stats::DegreeCount], sep: &str) -> String {
+    dist.iter()
+        .map(|d| format!("{}:{}", d.degree, d.count))
+        .collect::<Vec<_>>()
+        .join(sep)
+}
+
+/// diff 的 CSV 每行一项变化
+fn render_diff_csv(results: &[AnalysisResult]) -> String {
+    let mut out = String::from("source,change,item,old,new\n");
+    let mut push = |row: [String; 5]| {
+        let escaped: Vec<String> = row.into_iter().map(csv_escape).collect();
+        out.push_str(&escaped.join(","));
+        out.push('\n');
+    };
+    let weight = |w: Option<f64>| w.map(format_weight).unwrap_or_default();
+    for r in results {
+        let src = || r.source.clone();
+        let Some(d) = r.diff.as_ref().filter(|_| r.error.is_empty()) else {
+            push([
+                src(),
+                "error".into(),
+                r.error.clone(),
+                String::new(),
+                String::new(),
+            ]);
+            continue;
+        };
+        if d.is_empty() {
+            push([
+                src(),
+                "none".into(),
+                String::new(),
+                String::new(),
+                String::new(),
+            ]);
+        }
+        for n in &d.added_nodes {
+            push([
+                src(),
+                "added_node".into(),
+                n.clone(),
+                String::new(),
+                String::new(),
+            ]);
+        }
+        for n in &d.removed_nodes {
+            push([
+                src(),
+                "removed_node".into(),
+                n.clone(),
+                String::new(),
+                String::new(),
+            ]);
+        }
+        for e in &d.added_edges {
+            let item = format_edge(
+                &EdgeOut {
+                    weight: None,
+                    ..e.clone()
+                },
+                r.undirected,
+            );
+            push([
+                src(),
+                "added_edge".into(),
+                item,
+                String::new(),
+                weight(e.weight),
+            ]);
+        }
+        for e in &d.removed_edges {
+            let item = format_edge(
+                &EdgeOut {
+                    weight: None,
+                    ..e.clone()
+                },
+                r.undirected,
+            );
+            push([
+                src(),
+                "removed_edge".into(),
+                item,
+                weight(e.weight),
+                String::new(),
+            ]);
+        }
+        for c in &d.weight_changes {
+            let item = format_edge(
+                &EdgeOut {
+                    from: c.from.clone(),
+                    to: c.to.clone(),
+                    weight: None,
+                },
+                r.undirected,
+            );
+            push([
+                src(),
+                "weight_change".into(),
+                item,
+                format_weight(c.old),
+                format_weight(c.new),
+            ]);
+        }
+        for c in &d.new_cycles {
+            push([
+                src(),
+                "new_cycle".into(),
+                csv_join(c),
+                String::new(),
+                String::new(),
+            ]);
+        }
+        for m in &d.scc_merges {
+            push([
+                src(),
+                "scc_merge".into(),
+                csv_join(&m.component),
+                flatten_components(&m.merged),
+                String::new(),
+            ]);
+        }
+    }
+    out
+}
+
+fn render_stats_csv(results: &[AnalysisResult]) -> String {
+    let headers = [
+        "source",
+        "nodes",
+        "edges",
+        "undirected",
+        "density",
+        "components",
+        "isolated",
+        "self_loops",
+        "min_degree",
+        "max_degree",
+        "mean_degree",
+        "degree_distribution",
+        "diameter",
+        "average_path_length",
+        "reachable_pairs",
+        "central",
+        "error",
+    ];
+    let mut out = headers.join(",");
+    out.push('\n');
+    for r in results {
+        let s = r.stats.clone().unwrap_or_default();
+        let central = s
+            .central
+            .iter()
+            .map(|c| format!("{}:{:.6}", c.node, c.betweenness))
+            .collect::<Vec<_>>();
+        let row = [
+            r.source.clone(),
+            r.nodes.to_string(),
+            r.edges.to_string(),
+            r.undirected.to_string(),
+            format!("{:.6}", s.density),
+            s.components.to_string(),
+            s.isolated.to_string(),
+            s.self_loops.to_string(),
+            s.min_degree.to_string(),
+            s.max_degree.to_string(),
+            format!("{:.3}", s.mean_degree),
+            flatten_degree_distribution(&s.degree_distribution, ";"),
+            s.diameter.to_string(),
+            format!("{:.6}", s.average_path_length),
+            s.reachable_pairs.to_string(),
+            csv_join(&central),
+            r.error.clone(),
+        ];
+        let escaped: Vec<String> = row.into_iter().map(csv_escape).collect();
+        out.push_str(&escaped.join(","));
+        out.push('\n');
+    }
+    out
+}
+
 fn render_csv_results(results: &[AnalysisResult]) -> Result<String, String> {
+    // diff 和 stats 的结果形状不同，单独成表
+    if !results.is_empty() && results.iter().all(|r| r.mode == "diff") {
+        return Ok(render_diff_csv(results));
+    }
+    if !results.is_empty() && results.iter().all(|r| r.mode == "stats") {
+        return Ok(render_stats_csv(results));
+    }
     let headers = [
         "source",
         "mode",
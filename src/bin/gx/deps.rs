@@ -0,0 +1,1084 @@
+//! `gx deps`：从 Cargo 工作区和 Rust 源码提取依赖图
+//!
+//! - `crate` 级别：节点是工作区成员包，边是 `[dependencies]`、`[build-dependencies]`
+//!   （加 `--dev` 时还有 `[dev-dependencies]`）中指向其他成员的依赖
+//! - `module` 级别：从每个 target 的根文件沿 `mod` 声明展开模块树，`use` 声明和代码里
+//!   `crate::`、`super::`、`self::`、工作区 crate 名开头的路径按最长的已知模块前缀解析成依赖边。
+//!   `#[cfg(test)]` 下的模块和 `use` 不计入。
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::input::GraphData;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    Crate,
+    Module,
+}
+
+impl Level {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name.trim().to_lowercase().as_str() {
+            "crate" | "crates" | "package" => Ok(Self::Crate),
+            "" | "module" | "modules" | "mod" => Ok(Self::Module),
+            other => Err(format!(
+                "unsupported deps level {other:?}, use crate|module"
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct DepsOptions {
+    pub level: Level,
+    /// 模块级别额外加上父模块到子模块的 `mod` 边
+    pub mod_edges: bool,
+    /// 包含 `[dev-dependencies]`
+    pub dev: bool,
+    /// 包含工作区以外的 crate
+    pub external: bool,
+}
+
+pub fn build(path: &Path, opts: &DepsOptions) -> Result<GraphData, String> {
+    let ws = load_workspace(path)?;
+    let mut graph = match opts.level {
+        Level::Crate => crate_graph(&ws, opts),
+        Level::Module => module_graph(&ws, opts)?,
+    };
+    graph.directed = Some(true);
+    Ok(graph)
+}
+
+// ─── Cargo 清单 ───────────────────────────────────────────────────────────────
+
+struct Workspace {
+    root: PathBuf,
+    packages: Vec<Package>,
+}
+
+struct Package {
+    name: String,
+    version: String,
+    dir: PathBuf,
+    deps: Vec<Dep>,
+    lib: Option<Target>,
+    bins: Vec<Target>,
+}
+
+struct Dep {
+    /// 包名，`package = "..."` 重命名时取实际的包名
+    package: String,
+    /// 代码中引用它时用的 crate 名
+    crate_name: String,
+    kind: &'static str,
+}
+
+struct Target {
+    name: String,
+    root: PathBuf,
+}
+
+fn load_workspace(path: &Path) -> Result<Workspace, String> {
+    let manifest = if path.is_dir() {
+        path.join("Cargo.toml")
+    } else if path.extension().is_some_and(|e| e == "rs") {
+        // 单个源文件当作一个 crate 的根
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("main")
+            .to_string();
+        let dir = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+        return Ok(Workspace {
+            root: dir.clone(),
+            packages: vec![Package {
+                name: name.clone(),
+                version: String::new(),
+                dir,
+                deps: Vec::new(),
+                lib: None,
+                bins: vec![Target {
+                    name,
+                    root: path.to_path_buf(),
+                }],
+            }],
+        });
+    } else {
+        path.to_path_buf()
+    };
+    if !manifest.is_file() {
+        return Err(format!("no Cargo.toml found at {}", path.display()));
+    }
+    let root = manifest.parent().unwrap_or(Path::new(".")).to_path_buf();
+    let table = read_manifest(&manifest)?;
+
+    let ws = table.get("workspace").and_then(|v| v.as_table());
+    let ws_deps = ws
+        .and_then(|w| w.get("dependencies"))
+        .and_then(|v| v.as_table());
+    let mut dirs = Vec::new();
+    if table.contains_key("package") {
+        dirs.push(root.clone());
+    }
+    if let Some(ws) = ws {
+        let excluded: Vec<PathBuf> = string_list(ws.get("exclude"))
+            .iter()
+            .map(|p| normalize(&root.join(p)))
+            .collect();
+        for member in string_list(ws.get("members")) {
+            for dir in expand_member(&root, &member) {
+                let dir = normalize(&dir);
+                if !excluded.contains(&dir) && !dirs.iter().any(|d| normalize(d) == dir) {
+                    dirs.push(dir);
+                }
+            }
+        }
+    }
+
+    let mut packages = Vec::new();
+    for dir in dirs {
+        let table = if dir == root {
+            table.clone()
+        } else {
+            read_manifest(&dir.join("Cargo.toml"))?
+        };
+        if let Some(pkg) = load_package(&dir, &table, ws_deps) {
+            packages.push(pkg);
+        }
+    }
+    Ok(Workspace { root, packages })
+}
+
+fn read_manifest(path: &Path) -> Result<toml::Table, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    text.parse::<toml::Table>()
+        .map_err(|e| format!("{}: {e}", path.display()))
+}
+
+fn string_list(v: Option<&toml::Value>) -> Vec<String> {
+    v.and_then(|v| v.as_array())
+        .map(|a| {
+            a.iter()
+                .filter_map(|s| s.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 成员路径支持末尾的 `*`，如 `crates/*`
+fn expand_member(root: &Path, member: &str) -> Vec<PathBuf> {
+    let Some(parent) = member
+        .strip_suffix("/*")
+        .or_else(|| member.strip_suffix('*'))
+    else {
+        return vec![root.join(member)];
+    };
+    let mut dirs: Vec<PathBuf> = fs::read_dir(root.join(parent))
+        .map(|rd| {
+            rd.filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| p.join("Cargo.toml").is_file())
+                .collect()
+        })
+        .unwrap_or_default();
+    dirs.sort();
+    dirs
+}
+
+/// 去掉 `.` 和 `..`，用于比较成员路径
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for c in path.components() {
+        match c {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+fn load_package(dir: &Path, table: &toml::Table, ws_deps: Option<&toml::Table>) -> Option<Package> {
+    let pkg = table.get("package")?.as_table()?;
+    let name = pkg.get("name")?.as_str()?.to_string();
+    let version = pkg
+        .get("version")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string();
+
+    let lib_table = table.get("lib").and_then(|v| v.as_table());
+    let lib_path = lib_table
+        .and_then(|l| l.get("path"))
+        .and_then(|v| v.as_str())
+        .map_or_else(|| dir.join("src/lib.rs"), |p| dir.join(p));
+    let lib = lib_path.is_file().then(|| Target {
+        name: lib_table
+            .and_then(|l| l.get("name"))
+            .and_then(|v| v.as_str())
+            .map_or_else(|| name.replace('-', "_"), str::to_string),
+        root: lib_path,
+    });
+
+    let mut bins: Vec<Target> = Vec::new();
+    if let Some(list) = table.get("bin").and_then(|v| v.as_array()) {
+        for b in list {
+            let Some(bin_name) = b.get("name").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let root = b.get("path").and_then(|v| v.as_str()).map_or_else(
+                || dir.join(format!("src/bin/{bin_name}.rs")),
+                |p| dir.join(p),
+            );
+            bins.push(Target {
+                name: bin_name.to_string(),
+                root,
+            });
+        }
+    }
+    let mut auto = Vec::new();
+    if dir.join("src/main.rs").is_file() {
+        auto.push((name.clone(), dir.join("src/main.rs")));
+    }
+    if let Ok(rd) = fs::read_dir(dir.join("src/bin")) {
+        let mut entries: Vec<PathBuf> = rd.filter_map(|e| e.ok().map(|e| e.path())).collect();
+        entries.sort();
+        for p in entries {
+            let stem = p
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("")
+                .to_string();
+            if p.extension().is_some_and(|e| e == "rs") && p.is_file() {
+                auto.push((stem, p));
+            } else if p.join("main.rs").is_file() {
+                auto.push((stem, p.join("main.rs")));
+            }
+        }
+    }
+    for (bin_name, root) in auto {
+        if !bins.iter().any(|b| b.name == bin_name || b.root == root) {
+            bins.push(Target {
+                name: bin_name,
+                root,
+            });
+        }
+    }
+    bins.retain(|b| b.root.is_file());
+
+    let mut deps = Vec::new();
+    let mut sections: Vec<&toml::Table> = vec![table];
+    if let Some(targets) = table.get("target").and_then(|v| v.as_table()) {
+        sections.extend(targets.values().filter_map(|v| v.as_table()));
+    }
+    for section in sections {
+        for (key, kind) in [
+            ("dependencies", "normal"),
+            ("build-dependencies", "build"),
+            ("dev-dependencies", "dev"),
+        ] {
+            let Some(list) = section.get(key).and_then(|v| v.as_table()) else {
+                continue;
+            };
+            for (dep_key, spec) in list {
+                let inherited = spec
+                    .get("workspace")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let package = spec
+                    .get("package")
+                    .or_else(|| {
+                        inherited
+                            .then(|| ws_deps?.get(dep_key)?.get("package"))
+                            .flatten()
+                    })
+                    .and_then(|v| v.as_str())
+                    .unwrap_or(dep_key)
+                    .to_string();
+                deps.push(Dep {
+                    package,
+                    crate_name: dep_key.replace('-', "_"),
+                    kind,
+                });
+            }
+        }
+    }
+
+    Some(Package {
+        name,
+        version,
+        dir: dir.to_path_buf(),
+        deps,
+        lib,
+        bins,
+    })
+}
+
+fn relative(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string()
+}
+
+// ─── crate 级别 ───────────────────────────────────────────────────────────────
+
+fn crate_graph(ws: &Workspace, opts: &DepsOptions) -> GraphData {
+    let members: HashSet<&str> = ws.packages.iter().map(|p| p.name.as_str()).collect();
+    let mut graph = GraphData::default();
+    for p in &ws.packages {
+        let (node, _) = graph.add_node(&p.name);
+        node.attrs.push(("version".into(), p.version.clone()));
+        let dir = relative(&ws.root, &p.dir);
+        node.attrs
+            .push(("path".into(), if dir.is_empty() { ".".into() } else { dir }));
+    }
+    let mut seen = HashSet::new();
+    for p in &ws.packages {
+        for dep in &p.deps {
+            if dep.kind == "dev" && !opts.dev {
+                continue;
+            }
+            let internal = members.contains(dep.package.as_str());
+            if (!internal && !opts.external)
+                || dep.package == p.name
+                || !seen.insert((p.name.clone(), dep.package.clone()))
+            {
+                continue;
+            }
+            if !internal {
+                let (node, created) = graph.add_node(&dep.package);
+                if created {
+                    node.attrs.push(("external".into(), "true".into()));
+                }
+            }
+            let attrs = vec![("kind".to_string(), dep.kind.to_string())];
+            let _ = graph.add_edge(&p.name, &dep.package, attrs, false);
+        }
+    }
+    graph
+}
+
+// ─── 模块级别 ─────────────────────────────────────────────────────────────────
+
+struct Module {
+    /// 不含 crate 根的路径段
+    segs: Vec<String>,
+    file: PathBuf,
+    children: Vec<String>,
+    refs: Vec<Vec<String>>,
+}
+
+/// 一个 target（lib 或 bin）的模块树
+struct CrateModules {
+    root: String,
+    modules: Vec<Module>,
+    /// 代码中的 crate 名 → 工作区内对应 lib 的根节点名
+    extern_libs: HashMap<String, String>,
+    /// 工作区以外的依赖 crate 名
+    external: HashSet<String>,
+}
+
+impl CrateModules {
+    fn full_name(&self, segs: &[String]) -> String {
+        std::iter::once(self.root.as_str())
+            .chain(segs.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join("::")
+    }
+}
+
+enum Resolved {
+    Module(String),
+    External(String),
+}
+
+fn module_graph(ws: &Workspace, opts: &DepsOptions) -> Result<GraphData, String> {
+    let libs: HashMap<&str, &str> = ws
+        .packages
+        .iter()
+        .filter_map(|p| Some((p.name.as_str(), p.lib.as_ref()?.name.as_str())))
+        .collect();
+    let lib_names: HashSet<&str> = libs.values().copied().collect();
+
+    let mut crates = Vec::new();
+    for p in &ws.packages {
+        let mut extern_libs = HashMap::new();
+        let mut external: HashSet<String> = ["std", "core", "alloc"]
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        for dep in &p.deps {
+            if dep.kind == "dev" {
+                continue;
+            }
+            match libs.get(dep.package.as_str()) {
+                Some(lib) => {
+                    extern_libs.insert(dep.crate_name.clone(), lib.to_string());
+                }
+                None => {
+                    external.insert(dep.crate_name.clone());
+                }
+            }
+        }
+        let mut targets: Vec<(String, &Path)> = Vec::new();
+        if let Some(lib) = &p.lib {
+            targets.push((lib.name.clone(), &lib.root));
+        }
+        for bin in &p.bins {
+            // bin 名和某个 lib 重名时加后缀区分
+            let name = if lib_names.contains(bin.name.as_str()) {
+                format!("{}-bin", bin.name)
+            } else {
+                bin.name.clone()
+            };
+            targets.push((name, &bin.root));
+        }
+        for (root, file) in targets {
+            let mut extern_libs = extern_libs.clone();
+            if let Some(lib) = &p.lib
+                && lib.name != root
+            {
+                extern_libs.insert(lib.name.clone(), lib.name.clone());
+            }
+            let mut walker = Walker {
+                modules: Vec::new(),
+                visited: HashSet::new(),
+            };
+            walker.walk_file(file, crate_dir(file), Vec::new())?;
+            crates.push(CrateModules {
+                root,
+                modules: walker.modules,
+                extern_libs,
+                external: external.clone(),
+            });
+        }
+    }
+
+    let known: HashSet<String> = crates
+        .iter()
+        .flat_map(|c| c.modules.iter().map(|m| c.full_name(&m.segs)))
+        .collect();
+    let mut graph = GraphData::default();
+    for c in &crates {
+        for m in &c.modules {
+            let (node, _) = graph.add_node(&c.full_name(&m.segs));
+            node.attrs = vec![
+                ("crate".into(), c.root.clone()),
+                ("file".into(), relative(&ws.root, &m.file)),
+            ];
+        }
+    }
+    let mut seen = HashSet::new();
+    let mut add = |graph: &mut GraphData, from: &str, to: &str, kind: &str| {
+        if from != to && seen.insert((from.to_string(), to.to_string())) {
+            let _ = graph.add_edge(from, to, vec![("kind".into(), kind.into())], false);
+        }
+    };
+    for c in &crates {
+        for m in &c.modules {
+            let from = c.full_name(&m.segs);
+            if opts.mod_edges {
+                for child in &m.children {
+                    let mut segs = m.segs.clone();
+                    segs.push(child.clone());
+                    add(&mut graph, &from, &c.full_name(&segs), "mod");
+                }
+            }
+            for r in &m.refs {
+                match resolve(c, m, r, &known) {
+                    Some(Resolved::Module(to)) => add(&mut graph, &from, &to, "use"),
+                    Some(Resolved::External(name)) if opts.external => {
+                        let (node, created) = graph.add_node(&name);
+                        if created {
+                            node.attrs.push(("external".into(), "true".into()));
+                        }
+                        add(&mut graph, &from, &name, "use");
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    Ok(graph)
+}
+
+/// 把路径解析成最长的已知模块前缀
+fn resolve(
+    c: &CrateModules,
+    m: &Module,
+    path: &[String],
+    known: &HashSet<String>,
+) -> Option<Resolved> {
+    let first = path.first()?.as_str();
+    let (base, rest): (Vec<String>, &[String]) = match first {
+        "crate" => (vec![c.root.clone()], &path[1..]),
+        "self" => (full_segs(c, &m.segs), &path[1..]),
+        "super" => {
+            let ups = path.iter().take_while(|s| *s == "super").count();
+            if ups > m.segs.len() {
+                return None;
+            }
+            (full_segs(c, &m.segs[..m.segs.len() - ups]), &path[ups..])
+        }
+        s if m.children.iter().any(|ch| ch == s) => (full_segs(c, &m.segs), path),
+        s if c.extern_libs.contains_key(s) => (vec![c.extern_libs[s].clone()], &path[1..]),
+        s if c.external.contains(s) => return Some(Resolved::External(s.to_string())),
+        _ => return None,
+    };
+    let mut candidate = base.clone();
+    candidate.extend(rest.iter().cloned());
+    (base.len()..=candidate.len())
+        .rev()
+        .map(|k| candidate[..k].join("::"))
+        .find(|name| known.contains(name))
+        .map(Resolved::Module)
+}
+
+fn full_segs(c: &CrateModules, segs: &[String]) -> Vec<String> {
+    std::iter::once(c.root.clone())
+        .chain(segs.iter().cloned())
+        .collect()
+}
+
+/// crate 根文件的子模块和它在同一目录
+fn crate_dir(file: &Path) -> PathBuf {
+    file.parent().unwrap_or(Path::new(".")).to_path_buf()
+}
+
+struct Walker {
+    modules: Vec<Module>,
+    visited: HashSet<PathBuf>,
+}
+
+impl Walker {
+    /// `dir` 是该文件中 `mod x;` 查找 `x.rs`/`x/mod.rs` 的目录
+    fn walk_file(&mut self, file: &Path, dir: PathBuf, segs: Vec<String>) -> Result<(), String> {
+        if !self.visited.insert(normalize(file)) {
+            return Ok(());
+        }
+        let src = fs::read_to_string(file).map_err(|e| format!("{}: {e}", file.display()))?;
+        let toks = lex(&src);
+        let idx = self.modules.len();
+        self.modules.push(Module {
+            segs,
+            file: file.to_path_buf(),
+            children: Vec::new(),
+            refs: Vec::new(),
+        });
+        self.items(&toks, idx, file, &dir)
+    }
+
+    fn items(&mut self, toks: &[Tok], idx: usize, file: &Path, dir: &Path) -> Result<(), String> {
+        let mut i = 0;
+        let mut cfg_test = false;
+        let mut path_attr: Option<String> = None;
+        while i < toks.len() {
+            match &toks[i] {
+                Tok::Sym('#') => {
+                    let open = if toks.get(i + 1) == Some(&Tok::Sym('!')) {
+                        i + 2
+                    } else {
+                        i + 1
+                    };
+                    if toks.get(open) != Some(&Tok::Sym('[')) {
+                        i += 1;
+                        continue;
+                    }
+                    let close = matching(toks, open, '[', ']');
+                    let attr = &toks[open + 1..close];
+                    match attr.first() {
+                        Some(Tok::Ident(name)) if name == "cfg" => {
+                            let has = |s: &str| attr.iter().any(|t| *t == Tok::Ident(s.into()));
+                            cfg_test |= has("test") && !has("not");
+                        }
+                        Some(Tok::Ident(name)) if name == "path" => {
+                            path_attr = attr.iter().find_map(|t| match t {
+                                Tok::Str(s) => Some(s.clone()),
+                                _ => None,
+                            });
+                        }
+                        _ => {}
+                    }
+                    i = close + 1;
+                    continue;
+                }
+                Tok::Ident(kw) if kw == "mod" => {
+                    let Some(Tok::Ident(name)) = toks.get(i + 1) else {
+                        i += 1;
+                        continue;
+                    };
+                    let name = name.trim_start_matches("r#").to_string();
+                    let after = i + 2;
+                    let inline = toks.get(after) == Some(&Tok::Sym('{'));
+                    let end = if inline {
+                        matching(toks, after, '{', '}')
+                    } else {
+                        after
+                    };
+                    if !cfg_test {
+                        let mut segs = self.modules[idx].segs.clone();
+                        segs.push(name.clone());
+                        self.modules[idx].children.push(name.clone());
+                        if inline {
+                            let child = self.modules.len();
+                            self.modules.push(Module {
+                                segs,
+                                file: file.to_path_buf(),
+                                children: Vec::new(),
+                                refs: Vec::new(),
+                            });
+                            self.items(&toks[after + 1..end], child, file, &dir.join(&name))?;
+                        } else if let Some(child_file) =
+                            module_file(file, dir, &name, path_attr.take())
+                        {
+                            let child_dir = child_module_dir(&child_file);
+                            self.walk_file(&child_file, child_dir, segs)?;
+                        } else {
+                            self.modules[idx].children.pop();
+                        }
+                    }
+                    cfg_test = false;
+                    path_attr = None;
+                    i = end + 1;
+                    continue;
+                }
+                Tok::Ident(kw) if kw == "use" => {
+                    let end = (i + 1..toks.len())
+                        .find(|&j| toks[j] == Tok::Sym(';'))
+                        .unwrap_or(toks.len());
+                    if !cfg_test {
+                        let mut paths = Vec::new();
+                        use_tree(&toks[i + 1..end], &mut 0, Vec::new(), &mut paths);
+                        self.modules[idx].refs.extend(paths);
+                    }
+                    cfg_test = false;
+                    path_attr = None;
+                    i = end + 1;
+                    continue;
+                }
+                Tok::Ident(first) if toks.get(i + 1) == Some(&Tok::PathSep) => {
+                    let mut path = vec![first.clone()];
+                    let mut j = i + 1;
+                    while toks.get(j) == Some(&Tok::PathSep) {
+                        match toks.get(j + 1) {
+                            Some(Tok::Ident(seg)) => {
+                                path.push(seg.clone());
+                                j += 2;
+                            }
+                            _ => break,
+                        }
+                    }
+                    if !cfg_test {
+                        self.modules[idx].refs.push(path);
+                    }
+                    i = j;
+                    continue;
+                }
+                Tok::Sym('{') if cfg_test => {
+                    // `#[cfg(test)]` 修饰的条目整块跳过
+                    i = matching(toks, i, '{', '}') + 1;
+                    cfg_test = false;
+                    continue;
+                }
+                Tok::Sym(';' | '{' | '}') => {
+                    cfg_test = false;
+                    path_attr = None;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        Ok(())
+    }
+}
+
+/// `mod name;` 对应的文件：`#[path]` 相对声明所在文件的目录，否则找 `name.rs` 和 `name/mod.rs`
+fn module_file(file: &Path, dir: &Path, name: &str, path_attr: Option<String>) -> Option<PathBuf> {
+    if let Some(p) = path_attr {
+        let f = file.parent().unwrap_or(Path::new(".")).join(p);
+        return f.is_file().then_some(f);
+    }
+    [
+        dir.join(format!("{name}.rs")),
+        dir.join(name).join("mod.rs"),
+    ]
+    .into_iter()
+    .find(|f| f.is_file())
+}
+
+/// 模块文件中子模块所在的目录：`mod.rs` 是同目录，`x.rs` 是 `x/`
+fn child_module_dir(file: &Path) -> PathBuf {
+    let dir = crate_dir(file);
+    match file.file_stem().and_then(|s| s.to_str()) {
+        Some("mod") | None => dir,
+        Some(stem) => dir.join(stem),
+    }
+}
+
+/// 从 `open` 处的开括号找到匹配的闭括号，找不到时返回末尾
+fn matching(toks: &[Tok], open: usize, l: char, r: char) -> usize {
+    let mut depth = 0usize;
+    for (j, t) in toks.iter().enumerate().skip(open) {
+        if *t == Tok::Sym(l) {
+            depth += 1;
+        } else if *t == Tok::Sym(r) {
+            depth -= 1;
+            if depth == 0 {
+                return j;
+            }
+        }
+    }
+    toks.len()
+}
+
+/// 展开 `use a::{b, c::{d, e}, f::*}`，每个叶子得到一条完整路径；`self` 表示前缀本身
+fn use_tree(toks: &[Tok], pos: &mut usize, mut prefix: Vec<String>, out: &mut Vec<Vec<String>>) {
+    while *pos < toks.len() {
+        match &toks[*pos] {
+            Tok::PathSep => *pos += 1,
+            Tok::Sym('{') => {
+                *pos += 1;
+                while *pos < toks.len() && toks[*pos] != Tok::Sym('}') {
+                    use_tree(toks, pos, prefix.clone(), out);
+                    if toks.get(*pos) == Some(&Tok::Sym(',')) {
+                        *pos += 1;
+                    }
+                }
+                *pos += 1;
+                return;
+            }
+            Tok::Sym('*') => {
+                *pos += 1;
+                out.push(prefix);
+                return;
+            }
+            Tok::Ident(seg) => {
+                if seg != "self" || prefix.is_empty() {
+                    prefix.push(seg.clone());
+                }
+                *pos += 1;
+                if toks.get(*pos) == Some(&Tok::PathSep) {
+                    continue;
+                }
+                if toks.get(*pos) == Some(&Tok::Ident("as".into())) {
+                    *pos += 2;
+                }
+                out.push(prefix);
+                return;
+            }
+            _ => {
+                *pos += 1;
+                return;
+            }
+        }
+    }
+}
+
+// ─── 词法 ─────────────────────────────────────────────────────────────────────
+
+/// 只保留识别模块结构需要的词法单元：标识符、字符串、`::` 和单字符符号；跳过注释、字符和数字
+#[derive(Clone, Debug, PartialEq)]
+enum Tok {
+    Ident(String),
+    Str(String),
+    PathSep,
+    Sym(char),
+}
+
+fn lex(src: &str) -> Vec<Tok> {
+    let cs: Vec<char> = src.chars().collect();
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < cs.len() {
+        let c = cs[i];
+        let next = cs.get(i + 1).copied();
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && next == Some('/') {
+            while i < cs.len() && cs[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && next == Some('*') {
+            let mut depth = 0;
+            while i < cs.len() {
+                if cs[i] == '/' && cs.get(i + 1) == Some(&'*') {
+                    depth += 1;
+                    i += 2;
+                } else if cs[i] == '*' && cs.get(i + 1) == Some(&'/') {
+                    depth -= 1;
+                    i += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    i += 1;
+                }
+            }
+        } else if c == '"' {
+            let (s, end) = quoted(&cs, i + 1);
+            out.push(Tok::Str(s));
+            i = end;
+        } else if c == '\'' {
+            // 字符字面量 'a'、'\n'，否则是生命周期 'a
+            if next == Some('\\') {
+                i += 2;
+                while i < cs.len() && cs[i] != '\'' {
+                    i += 1;
+                }
+                i += 1;
+            } else if cs.get(i + 2) == Some(&'\'') {
+                i += 3;
+            } else {
+                i += 1;
+                while i < cs.len() && (cs[i].is_alphanumeric() || cs[i] == '_') {
+                    i += 1;
+                }
+            }
+        } else if c.is_ascii_digit() {
+            while i < cs.len() && (cs[i].is_alphanumeric() || cs[i] == '_') {
+                i += 1;
+            }
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < cs.len() && (cs[i].is_alphanumeric() || cs[i] == '_') {
+                i += 1;
+            }
+            let ident: String = cs[start..i].iter().collect();
+            let at = cs.get(i).copied();
+            match (ident.as_str(), at) {
+                ("r" | "br" | "cr", Some('"' | '#')) if raw_string_start(&cs, i) => {
+                    let (s, end) = raw_string(&cs, i);
+                    out.push(Tok::Str(s));
+                    i = end;
+                }
+                ("b" | "c", Some('"')) => {
+                    let (s, end) = quoted(&cs, i + 1);
+                    out.push(Tok::Str(s));
+                    i = end;
+                }
+                ("b", Some('\'')) => {
+                    i += 1;
+                    if cs.get(i) == Some(&'\\') {
+                        i += 1;
+                    }
+                    while i < cs.len() && cs[i] != '\'' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                ("r", Some('#')) => {
+                    // 原始标识符 r#type
+                    let start = i + 1;
+                    i = start;
+                    while i < cs.len() && (cs[i].is_alphanumeric() || cs[i] == '_') {
+                        i += 1;
+                    }
+                    out.push(Tok::Ident(cs[start..i].iter().collect()));
+                }
+                _ => out.push(Tok::Ident(ident)),
+            }
+        } else if c == ':' && next == Some(':') {
+            out.push(Tok::PathSep);
+            i += 2;
+        } else {
+            out.push(Tok::Sym(c));
+            i += 1;
+        }
+    }
+    out
+}
+
+/// 从开引号后开始读到闭引号，返回内容和闭引号之后的位置
+fn quoted(cs: &[char], mut i: usize) -> (String, usize) {
+    let mut s = String::new();
+    while i < cs.len() {
+        match cs[i] {
+            '\\' => {
+                if let Some(&c) = cs.get(i + 1) {
+                    s.push(c);
+                }
+                i += 2;
+            }
+            '"' => return (s, i + 1),
+            c => {
+                s.push(c);
+                i += 1;
+            }
+        }
+    }
+    (s, i)
+}
+
+fn raw_string_start(cs: &[char], i: usize) -> bool {
+    let hashes = cs[i..].iter().take_while(|&&c| c == '#').count();
+    cs.get(i + hashes) == Some(&'"')
+}
+
+/// `r#"..."#`，`i` 指向第一个 `#` 或引号
+fn raw_string(cs: &[char], i: usize) -> (String, usize) {
+    let hashes = cs[i..].iter().take_while(|&&c| c == '#').count();
+    let start = i + hashes + 1;
+    let mut j = start;
+    while j < cs.len() {
+        if cs[j] == '"'
+            && cs[j + 1..]
+                .iter()
+                .take(hashes)
+                .filter(|&&c| c == '#')
+                .count()
+                == hashes
+        {
+            return (cs[start..j].iter().collect(), j + 1 + hashes);
+        }
+        j += 1;
+    }
+    (cs[start..].iter().collect(), cs.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(src: &str) -> Vec<String> {
+        let toks = lex(src);
+        let mut out = Vec::new();
+        use_tree(&toks, &mut 0, Vec::new(), &mut out);
+        out.iter().map(|p| p.join("::")).collect()
+    }
+
+    #[test]
+    fn expands_use_trees() {
+        assert_eq!(
+            paths("crate::{a::{self, b as c}, d::*, e}"),
+            vec!["crate::a", "crate::a::b", "crate::d", "crate::e"]
+        );
+        assert_eq!(paths("super::super::x::Y"), vec!["super::super::x::Y"]);
+    }
+
+    #[test]
+    fn lexer_skips_comments_strings_and_lifetimes() {
+        let toks = lex(
+            "// crate::a\nfn f<'a>(x: &'a str) -> char { let _ = r#\"crate::b\"#; /* crate::c */ 'x' }",
+        );
+        assert!(!toks.contains(&Tok::Ident("crate".into())));
+        assert!(toks.contains(&Tok::Str("crate::b".into())));
+        assert!(toks.contains(&Tok::Ident("str".into())));
+    }
+
+    fn write(root: &Path, rel: &str, content: &str) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn builds_crate_and_module_graphs() {
+        let root = std::env::temp_dir().join(format!("gx_deps_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        write(
+            &root,
+            "Cargo.toml",
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[workspace]\nmembers = [\"crates/*\"]\n\n\
+             [dependencies]\nutil-lib = { path = \"crates/util\" }\nserde = \"1\"\n",
+        );
+        write(
+            &root,
+            "crates/util/Cargo.toml",
+            "[package]\nname = \"util-lib\"\nversion = \"0.2.0\"\n\n[dev-dependencies]\napp = { path = \"../..\" }\n",
+        );
+        write(&root, "crates/util/src/lib.rs", "pub mod text;\n");
+        write(&root, "crates/util/src/text.rs", "pub fn f() {}\n");
+        write(
+            &root,
+            "src/lib.rs",
+            "pub mod a;\npub mod b;\nmod inline { use super::a::A; }\n#[cfg(test)]\nmod tests { use crate::b; }\n",
+        );
+        write(
+            &root,
+            "src/a.rs",
+            "pub struct A;\nuse crate::b::helper;\nmod nested;\n",
+        );
+        write(
+            &root,
+            "src/a/nested.rs",
+            "fn g() { util_lib::text::f(); }\n",
+        );
+        write(&root, "src/b.rs", "use crate::a::A;\npub fn helper() {}\n");
+        write(
+            &root,
+            "src/bin/tool.rs",
+            "#[path = \"tool/cli.rs\"]\nmod cli;\nuse app::a;\n",
+        );
+        write(&root, "src/bin/tool/cli.rs", "use super::*;\n");
+
+        let opts = DepsOptions {
+            level: Level::Crate,
+            mod_edges: false,
+            dev: false,
+            external: false,
+        };
+        let g = build(&root, &opts).unwrap();
+        let edges: Vec<String> = g
+            .edges
+            .iter()
+            .map(|e| format!("{}>{}", e.from, e.to))
+            .collect();
+        assert_eq!(edges, vec!["app>util-lib"]);
+        let g = build(&root, &DepsOptions { dev: true, ..opts }).unwrap();
+        assert_eq!(g.edges.len(), 2);
+
+        let opts = DepsOptions {
+            level: Level::Module,
+            ..opts
+        };
+        let g = build(&root, &opts).unwrap();
+        let mut nodes = g.sorted_node_ids();
+        nodes.sort();
+        assert_eq!(
+            nodes,
+            vec![
+                "app",
+                "app::a",
+                "app::a::nested",
+                "app::b",
+                "app::inline",
+                "tool",
+                "tool::cli",
+                "util_lib",
+                "util_lib::text"
+            ]
+        );
+        let edges: Vec<String> = g
+            .edges
+            .iter()
+            .map(|e| format!("{}>{}", e.from, e.to))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                "app::a>app::b",
+                "app::a::nested>util_lib::text",
+                "app::b>app::a",
+                "app::inline>app::a",
+                "tool>app::a",
+                "tool::cli>tool"
+            ]
+        );
+
+        let g = build(
+            &root,
+            &DepsOptions {
+                mod_edges: true,
+                external: true,
+                ..opts
+            },
+        )
+        .unwrap();
+        assert!(g.edges.iter().any(|e| e.from == "app" && e.to == "app::a"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,264 @@
+//! `gx diff`：比较同一张图的两个版本
+//!
+//! 报告增删的节点和边、权重变化，以及新版本里的环：一个非平凡强连通分量（多于一个节点或有自环）
+//! 如果在旧版本中对应的部分都没有环，算作新引入的环；如果由旧版本的多个分量合并而成，且其中
+//! 至少有一个原来就有环，算作分量合并。无向图按连通分量只报告合并。
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use rust_tools::cw::{DirectedGraph, UndirectedGraph};
+use serde::Serialize;
+
+use crate::EdgeOut;
+use crate::input::GraphData;
+
+#[derive(Clone, Debug, Serialize, Default, PartialEq)]
+pub struct GraphDiff {
+    pub added_nodes: Vec<String>,
+    pub removed_nodes: Vec<String>,
+    pub added_edges: Vec<EdgeOut>,
+    pub removed_edges: Vec<EdgeOut>,
+    pub weight_changes: Vec<WeightChange>,
+    /// 新引入的环，首尾是同一个节点
+    pub new_cycles: Vec<Vec<String>>,
+    pub scc_merges: Vec<SccMerge>,
+}
+
+impl GraphDiff {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct WeightChange {
+    pub from: String,
+    pub to: String,
+    pub old: f64,
+    pub new: f64,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct SccMerge {
+    pub component: Vec<String>,
+    /// 合并前各自所在的分量（只列出仍在新分量里的节点），新增的节点单独成组
+    pub merged: Vec<Vec<String>>,
+}
+
+pub fn compare(old: &GraphData, new: &GraphData, undirected: bool, weighted: bool) -> GraphDiff {
+    let old_nodes: HashSet<&str> = old.nodes.iter().map(|n| n.id.as_str()).collect();
+    let new_nodes: HashSet<&str> = new.nodes.iter().map(|n| n.id.as_str()).collect();
+    let mut diff = GraphDiff {
+        added_nodes: sorted_difference(&new_nodes, &old_nodes),
+        removed_nodes: sorted_difference(&old_nodes, &new_nodes),
+        ..GraphDiff::default()
+    };
+
+    let (old_edges, new_edges) = (edge_map(old, undirected), edge_map(new, undirected));
+    let edge_out = |(from, to): &(String, String), weight: f64| EdgeOut {
+        from: from.clone(),
+        to: to.clone(),
+        weight: weighted.then_some(weight),
+    };
+    for (key, &w) in &new_edges {
+        match old_edges.get(key) {
+            None => diff.added_edges.push(edge_out(key, w)),
+            Some(&old_w) if old_w != w => diff.weight_changes.push(WeightChange {
+                from: key.0.clone(),
+                to: key.1.clone(),
+                old: old_w,
+                new: w,
+            }),
+            Some(_) => {}
+        }
+    }
+    for (key, &w) in &old_edges {
+        if !new_edges.contains_key(key) {
+            diff.removed_edges.push(edge_out(key, w));
+        }
+    }
+
+    let old_groups = components(old, undirected);
+    let old_group_of: HashMap<&str, usize> = old_groups
+        .iter()
+        .enumerate()
+        .flat_map(|(i, g)| g.iter().map(move |id| (id.as_str(), i)))
+        .collect();
+    let old_cyclic: Vec<bool> = old_groups
+        .iter()
+        .map(|g| is_cyclic(g, &old_edges, undirected))
+        .collect();
+    for group in components(new, undirected) {
+        if !is_cyclic(&group, &new_edges, undirected) {
+            continue;
+        }
+        // 按旧分量分组，保持首次出现的顺序
+        let mut parts: Vec<(Option<usize>, Vec<String>)> = Vec::new();
+        for id in &group {
+            let key = old_group_of.get(id.as_str()).copied();
+            match parts.iter_mut().find(|(k, _)| k.is_some() && *k == key) {
+                Some((_, members)) => members.push(id.clone()),
+                None => parts.push((key, vec![id.clone()])),
+            }
+        }
+        let had_cycle = parts.iter().any(|(k, _)| k.is_some_and(|k| old_cyclic[k]));
+        if !had_cycle && !undirected {
+            diff.new_cycles.push(cycle_in(&group, &new_edges));
+        } else if parts.len() > 1 {
+            diff.scc_merges.push(SccMerge {
+                component: group,
+                merged: parts.into_iter().map(|(_, members)| members).collect(),
+            });
+        }
+    }
+    diff
+}
+
+fn sorted_difference(a: &HashSet<&str>, b: &HashSet<&str>) -> Vec<String> {
+    let mut out: Vec<String> = a.difference(b).map(|s| s.to_string()).collect();
+    out.sort();
+    out
+}
+
+/// 边 → 权重，重复边取第一次出现的权重；无向图的端点按字典序排列
+fn edge_map(graph: &GraphData, undirected: bool) -> BTreeMap<(String, String), f64> {
+    let mut map = BTreeMap::new();
+    for e in &graph.edges {
+        let key = if undirected && e.to < e.from {
+            (e.to.clone(), e.from.clone())
+        } else {
+            (e.from.clone(), e.to.clone())
+        };
+        map.entry(key).or_insert(e.weight);
+    }
+    map
+}
+
+/// 有向图的强连通分量或无向图的连通分量，分量内和分量间都排好序
+fn components(graph: &GraphData, undirected: bool) -> Vec<Vec<String>> {
+    let mut groups = if undirected {
+        let mut g = UndirectedGraph::new();
+        for n in &graph.nodes {
+            g.add_node(n.id.clone());
+        }
+        for e in &graph.edges {
+            g.add_edge(e.from.clone(), e.to.clone());
+        }
+        g.groups()
+    } else {
+        crate::build_directed(graph).strong_components()
+    };
+    for g in &mut groups {
+        g.sort();
+    }
+    groups.sort();
+    groups
+}
+
+fn is_cyclic(group: &[String], edges: &BTreeMap<(String, String), f64>, undirected: bool) -> bool {
+    match group {
+        [] => false,
+        [only] => !undirected && edges.contains_key(&(only.clone(), only.clone())),
+        _ => true,
+    }
+}
+
+/// 在强连通分量内找一个环，从字典序最小的节点开始
+fn cycle_in(group: &[String], edges: &BTreeMap<(String, String), f64>) -> Vec<String> {
+    let members: HashSet<&String> = group.iter().collect();
+    let mut g = DirectedGraph::new();
+    for (from, to) in edges.keys() {
+        if members.contains(from) && members.contains(to) {
+            g.add_edge(from.clone(), to.clone());
+        }
+    }
+    let mut cycle = g.cycle().unwrap_or_default();
+    cycle.pop();
+    if let Some(start) = (0..cycle.len()).min_by_key(|&i| &cycle[i]) {
+        cycle.rotate_left(start);
+        cycle.push(cycle[0].clone());
+    }
+    cycle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weighted(edges: &[(&str, &str, &str)]) -> GraphData {
+        let mut g = GraphData::default();
+        for (from, to, w) in edges {
+            g.add_edge(from, to, vec![("weight".into(), w.to_string())], true)
+                .unwrap();
+        }
+        g
+    }
+
+    fn edges(list: &[EdgeOut]) -> Vec<String> {
+        list.iter()
+            .map(|e| format!("{}>{}", e.from, e.to))
+            .collect()
+    }
+
+    #[test]
+    fn reports_node_edge_and_weight_changes() {
+        let old = weighted(&[("a", "b", "1"), ("b", "c", "2"), ("c", "gone", "1")]);
+        let new = weighted(&[("a", "b", "1"), ("b", "c", "5"), ("c", "d", "1")]);
+        let d = compare(&old, &new, false, true);
+        assert_eq!(d.added_nodes, vec!["d"]);
+        assert_eq!(d.removed_nodes, vec!["gone"]);
+        assert_eq!(edges(&d.added_edges), vec!["c>d"]);
+        assert_eq!(edges(&d.removed_edges), vec!["c>gone"]);
+        assert_eq!(d.removed_edges[0].weight, Some(1.0));
+        assert_eq!(
+            d.weight_changes,
+            vec![WeightChange {
+                from: "b".into(),
+                to: "c".into(),
+                old: 2.0,
+                new: 5.0
+            }]
+        );
+        assert!(d.new_cycles.is_empty() && d.scc_merges.is_empty());
+        assert!(compare(&old, &old, false, true).is_empty());
+
+        // 无向图里方向相反的同一条边不算变化
+        let flipped = weighted(&[("b", "a", "1"), ("c", "b", "2"), ("gone", "c", "1")]);
+        assert!(compare(&old, &flipped, true, true).is_empty());
+    }
+
+    #[test]
+    fn detects_new_cycles_and_scc_merges() {
+        let old = weighted(&[
+            ("a", "b", "1"),
+            ("b", "a", "1"),
+            ("c", "d", "1"),
+            ("x", "y", "1"),
+        ]);
+        let new = weighted(&[
+            ("a", "b", "1"),
+            ("b", "a", "1"),
+            ("b", "c", "1"),
+            ("c", "d", "1"),
+            ("d", "a", "1"),
+            ("x", "y", "1"),
+            ("y", "z", "1"),
+            ("z", "x", "1"),
+            ("s", "s", "1"),
+        ]);
+        let d = compare(&old, &new, false, false);
+        assert_eq!(d.new_cycles, vec![vec!["s", "s"], vec!["x", "y", "z", "x"]]);
+        assert_eq!(
+            d.scc_merges,
+            vec![SccMerge {
+                component: vec!["a".into(), "b".into(), "c".into(), "d".into()],
+                merged: vec![
+                    vec!["a".into(), "b".into()],
+                    vec!["c".into()],
+                    vec!["d".into()]
+                ],
+            }]
+        );
+        assert_eq!(d.added_edges[0].weight, None);
+    }
+}
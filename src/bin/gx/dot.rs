@@ -0,0 +1,520 @@
+//! Graphviz DOT 读取：支持 `strict`、子图、`node`/`edge` 默认属性、边链 `a -> b -> c`、
+//! 以子图为端点的边 `a -> {b c}`、端口 `a:p:n`、注释和 `"a" + "b"` 字符串拼接。
+
+use crate::input::{Attrs, GraphData, merge_attrs, set_attr};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Tok {
+    Id(String),
+    Sym(char),
+    /// `->` 为真，`--` 为假
+    EdgeOp(bool),
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    /// 当前是否在行首，`#` 开头的预处理行要跳过
+    line_start: bool,
+}
+
+fn tokenize(src: &str) -> Result<Vec<(Tok, usize)>, String> {
+    let mut lx = Lexer {
+        chars: src.chars().peekable(),
+        line: 1,
+        line_start: true,
+    };
+    let mut out: Vec<(Tok, usize)> = Vec::new();
+    while let Some(&c) = lx.chars.peek() {
+        let line = lx.line;
+        match c {
+            '\n' => {
+                lx.bump();
+                lx.line_start = true;
+            }
+            c if c.is_whitespace() => {
+                lx.chars.next();
+            }
+            '#' if lx.line_start => lx.skip_line(),
+            '/' => {
+                lx.chars.next();
+                match lx.chars.peek() {
+                    Some('/') => lx.skip_line(),
+                    Some('*') => {
+                        lx.chars.next();
+                        lx.skip_block_comment()?;
+                    }
+                    _ => return Err(format!("dot: line {line}: unexpected '/'")),
+                }
+            }
+            '"' => {
+                lx.chars.next();
+                let s = lx.quoted()?;
+                // "a" + "b" 拼接成一个字符串
+                if let Some((Tok::Sym('+'), _)) = out.last() {
+                    out.pop();
+                    match out.last_mut() {
+                        Some((Tok::Id(prev), _)) => prev.push_str(&s),
+                        _ => return Err(format!("dot: line {line}: unexpected '+'")),
+                    }
+                } else {
+                    out.push((Tok::Id(s), line));
+                }
+            }
+            '<' => {
+                lx.chars.next();
+                out.push((Tok::Id(lx.html()?), line));
+            }
+            '-' => {
+                lx.chars.next();
+                match lx.chars.peek() {
+                    Some('>') => {
+                        lx.chars.next();
+                        out.push((Tok::EdgeOp(true), line));
+                    }
+                    Some('-') => {
+                        lx.chars.next();
+                        out.push((Tok::EdgeOp(false), line));
+                    }
+                    Some(d) if d.is_ascii_digit() || *d == '.' => {
+                        out.push((Tok::Id(format!("-{}", lx.numeral())), line));
+                    }
+                    _ => return Err(format!("dot: line {line}: unexpected '-'")),
+                }
+            }
+            '{' | '}' | '[' | ']' | ';' | ',' | '=' | ':' | '+' => {
+                lx.chars.next();
+                out.push((Tok::Sym(c), line));
+            }
+            c if c.is_ascii_digit() || c == '.' => out.push((Tok::Id(lx.numeral()), line)),
+            c if c.is_alphanumeric() || c == '_' || !c.is_ascii() => {
+                let mut id = String::new();
+                while let Some(&c) = lx.chars.peek() {
+                    if c.is_alphanumeric() || c == '_' || !c.is_ascii() {
+                        id.push(c);
+                        lx.chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                out.push((Tok::Id(id), line));
+            }
+            other => return Err(format!("dot: line {line}: unexpected character {other:?}")),
+        }
+        if !c.is_whitespace() {
+            lx.line_start = false;
+        }
+    }
+    Ok(out)
+}
+
+impl Lexer<'_> {
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_line(&mut self) {
+        while let Some(&c) = self.chars.peek() {
+            if c == '\n' {
+                break;
+            }
+            self.chars.next();
+        }
+    }
+
+    fn skip_block_comment(&mut self) -> Result<(), String> {
+        let start = self.line;
+        let mut prev = '\0';
+        while let Some(c) = self.bump() {
+            if prev == '*' && c == '/' {
+                return Ok(());
+            }
+            prev = c;
+        }
+        Err(format!("dot: line {start}: unterminated comment"))
+    }
+
+    /// 引号字符串：`\"` 转义为 `"`，行尾的 `\` 是续行，其余转义（如 `\n`）原样保留
+    fn quoted(&mut self) -> Result<String, String> {
+        let start = self.line;
+        let mut s = String::new();
+        while let Some(c) = self.bump() {
+            match c {
+                '"' => return Ok(s),
+                '\\' => match self.bump() {
+                    Some('"') => s.push('"'),
+                    Some('\n') => {}
+                    Some(other) => {
+                        s.push('\\');
+                        s.push(other);
+                    }
+                    None => break,
+                },
+                _ => s.push(c),
+            }
+        }
+        Err(format!("dot: line {start}: unterminated string"))
+    }
+
+    /// HTML 字符串 `<...>`，尖括号可以嵌套，返回去掉最外层尖括号的内容
+    fn html(&mut self) -> Result<String, String> {
+        let start = self.line;
+        let mut depth = 1;
+        let mut s = String::new();
+        while let Some(c) = self.bump() {
+            match c {
+                '<' => depth += 1,
+                '>' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(s);
+                    }
+                }
+                _ => {}
+            }
+            s.push(c);
+        }
+        Err(format!("dot: line {start}: unterminated HTML string"))
+    }
+
+    fn numeral(&mut self) -> String {
+        let mut s = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if c.is_ascii_digit() || c == '.' {
+                s.push(c);
+                self.chars.next();
+            } else {
+                break;
+            }
+        }
+        s
+    }
+}
+
+/// 当前作用域的 `node [...]`、`edge [...]` 默认属性；子图继承外层的默认值
+#[derive(Clone, Default)]
+struct Scope {
+    node: Attrs,
+    edge: Attrs,
+}
+
+struct Parser {
+    toks: Vec<(Tok, usize)>,
+    pos: usize,
+    directed: bool,
+    weighted: bool,
+    graph: GraphData,
+}
+
+pub fn parse(content: &str, weighted: bool) -> Result<GraphData, String> {
+    let mut p = Parser {
+        toks: tokenize(content)?,
+        pos: 0,
+        directed: true,
+        weighted,
+        graph: GraphData::default(),
+    };
+    if p.keyword("strict") {
+        p.pos += 1;
+    }
+    p.directed = if p.keyword("digraph") {
+        true
+    } else if p.keyword("graph") {
+        false
+    } else {
+        return Err(p.error("expected 'graph' or 'digraph'"));
+    };
+    p.pos += 1;
+    if let Some(Tok::Id(_)) = p.peek() {
+        p.pos += 1;
+    }
+    p.expect('{')?;
+    p.stmt_list(&mut Scope::default(), 0)?;
+    if p.pos < p.toks.len() {
+        return Err(p.error("unexpected content after the closing '}'"));
+    }
+    p.graph.directed = Some(p.directed);
+    Ok(p.graph)
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Tok> {
+        self.toks.get(self.pos).map(|(t, _)| t)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Tok> {
+        self.toks.get(self.pos + offset).map(|(t, _)| t)
+    }
+
+    fn keyword(&self, kw: &str) -> bool {
+        matches!(self.peek(), Some(Tok::Id(id)) if id.eq_ignore_ascii_case(kw))
+    }
+
+    fn error(&self, msg: &str) -> String {
+        match self.toks.get(self.pos).or(self.toks.last()) {
+            Some((_, line)) => format!("dot: line {line}: {msg}"),
+            None => format!("dot: {msg}"),
+        }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(&Tok::Sym(c)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{c}'")))
+        }
+    }
+
+    fn id(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some(Tok::Id(id)) => {
+                let id = id.clone();
+                self.pos += 1;
+                Ok(id)
+            }
+            _ => Err(self.error("expected an identifier")),
+        }
+    }
+
+    /// 解析到对应的 `}` 为止，返回语句中出现过的节点，供 `a -> {b c}` 使用
+    fn stmt_list(&mut self, scope: &mut Scope, depth: usize) -> Result<Vec<String>, String> {
+        let mut nodes = Vec::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("missing '}'")),
+                Some(Tok::Sym('}')) => {
+                    self.pos += 1;
+                    return Ok(nodes);
+                }
+                Some(Tok::Sym(';')) => self.pos += 1,
+                _ => nodes.extend(self.stmt(scope, depth)?),
+            }
+        }
+    }
+
+    fn stmt(&mut self, scope: &mut Scope, depth: usize) -> Result<Vec<String>, String> {
+        let attr_target = ["graph", "node", "edge"]
+            .into_iter()
+            .find(|kw| self.keyword(kw) && self.peek_at(1) == Some(&Tok::Sym('[')));
+        if let Some(kw) = attr_target {
+            self.pos += 1;
+            let attrs = self.attr_lists()?;
+            match kw {
+                "node" => merge_attrs(&mut scope.node, &attrs),
+                "edge" => merge_attrs(&mut scope.edge, &attrs),
+                _ if depth == 0 => merge_attrs(&mut self.graph.attrs, &attrs),
+                _ => {}
+            }
+            return Ok(Vec::new());
+        }
+        if let (Some(Tok::Id(_)), Some(Tok::Sym('='))) = (self.peek(), self.peek_at(1)) {
+            let key = self.id()?;
+            self.pos += 1;
+            let value = self.id()?;
+            if depth == 0 {
+                set_attr(&mut self.graph.attrs, &key, &value);
+            }
+            return Ok(Vec::new());
+        }
+
+        let first = self.operand(scope, depth)?;
+        if !matches!(self.peek(), Some(Tok::EdgeOp(_))) {
+            if let Operand::Node(id) = &first {
+                let attrs = self.attr_lists()?;
+                let (node, _) = self.graph.add_node(id);
+                merge_attrs(&mut node.attrs, &attrs);
+            }
+            return Ok(first.into_nodes());
+        }
+
+        let mut operands = vec![first];
+        while let Some(&Tok::EdgeOp(directed)) = self.peek() {
+            if directed != self.directed {
+                let op = if directed { "->" } else { "--" };
+                let kind = if self.directed { "digraph" } else { "graph" };
+                return Err(self.error(&format!("'{op}' is not allowed in a {kind}")));
+            }
+            self.pos += 1;
+            operands.push(self.operand(scope, depth)?);
+        }
+        let mut attrs = scope.edge.clone();
+        merge_attrs(&mut attrs, &self.attr_lists()?);
+        let line = self.toks.get(self.pos.saturating_sub(1)).map_or(0, |t| t.1);
+        let mut touched = Vec::new();
+        for pair in operands.windows(2) {
+            for from in pair[0].nodes() {
+                for to in pair[1].nodes() {
+                    self.graph
+                        .add_edge(from, to, attrs.clone(), self.weighted)
+                        .map_err(|e| format!("dot: line {line}: {e}"))?;
+                }
+            }
+        }
+        for op in operands {
+            touched.extend(op.into_nodes());
+        }
+        Ok(touched)
+    }
+
+    /// 节点 ID（忽略端口）或子图
+    fn operand(&mut self, scope: &Scope, depth: usize) -> Result<Operand, String> {
+        if self.keyword("subgraph") || self.peek() == Some(&Tok::Sym('{')) {
+            if self.keyword("subgraph") {
+                self.pos += 1;
+                if let Some(Tok::Id(_)) = self.peek() {
+                    self.pos += 1;
+                }
+            }
+            self.expect('{')?;
+            let mut inner = scope.clone();
+            let nodes = self.stmt_list(&mut inner, depth + 1)?;
+            return Ok(Operand::Subgraph(nodes));
+        }
+        let id = self.id()?;
+        // 端口 `a:port` 或 `a:port:compass`
+        while self.eat(':') {
+            self.id()?;
+        }
+        let (node, created) = self.graph.add_node(&id);
+        if created {
+            merge_attrs(&mut node.attrs, &scope.node);
+        }
+        Ok(Operand::Node(id))
+    }
+
+    /// 一个或多个 `[k=v, k=v; ...]`，没有时返回空
+    fn attr_lists(&mut self) -> Result<Attrs, String> {
+        let mut attrs = Attrs::new();
+        while self.eat('[') {
+            while !self.eat(']') {
+                let key = self.id()?;
+                let value = if self.eat('=') {
+                    self.id()?
+                } else {
+                    "true".to_string()
+                };
+                set_attr(&mut attrs, &key, &value);
+                if !self.eat(',') {
+                    self.eat(';');
+                }
+            }
+        }
+        Ok(attrs)
+    }
+}
+
+enum Operand {
+    Node(String),
+    Subgraph(Vec<String>),
+}
+
+impl Operand {
+    fn nodes(&self) -> Vec<&str> {
+        match self {
+            Operand::Node(id) => vec![id.as_str()],
+            Operand::Subgraph(nodes) => {
+                let mut seen: Vec<&str> = Vec::new();
+                for n in nodes {
+                    if !seen.contains(&n.as_str()) {
+                        seen.push(n);
+                    }
+                }
+                seen
+            }
+        }
+    }
+
+    fn into_nodes(self) -> Vec<String> {
+        match self {
+            Operand::Node(id) => vec![id],
+            Operand::Subgraph(nodes) => nodes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::attr;
+
+    fn edge_list(g: &GraphData) -> Vec<String> {
+        g.edges
+            .iter()
+            .map(|e| format!("{}>{}", e.from, e.to))
+            .collect()
+    }
+
+    #[test]
+    fn parses_statements_defaults_and_subgraphs() {
+        let g = parse(
+            r#"
+            /* generated */
+            strict digraph "deps" {
+              rankdir = LR;
+              node [shape=box];
+              "core lib" [color="red", label="Core \"lib\""];
+              a -> b -> "core lib" [weight=3];
+              subgraph cluster_x { edge [style=dashed] c; d -> c }
+              a -> {c d};
+              e:p1:n -> "f" + "g"
+              // trailing comment
+              # preprocessor line
+              lonely;
+            }
+            "#,
+            true,
+        )
+        .unwrap();
+        assert_eq!(g.directed, Some(true));
+        assert_eq!(attr(&g.attrs, "rankdir"), Some("LR"));
+        assert_eq!(
+            edge_list(&g),
+            vec!["a>b", "b>core lib", "d>c", "a>c", "a>d", "e>fg"]
+        );
+        assert_eq!(g.edges[0].weight, 3.0);
+        assert_eq!(attr(&g.edges[2].attrs, "style"), Some("dashed"));
+        assert_eq!(attr(&g.edges[3].attrs, "style"), None);
+        let core = g.node("core lib").unwrap();
+        assert_eq!(attr(&core.attrs, "label"), Some("Core \"lib\""));
+        assert_eq!(attr(&core.attrs, "shape"), Some("box"));
+        assert!(g.node("lonely").is_some());
+    }
+
+    #[test]
+    fn reads_back_gx_viz_output() {
+        let g = parse(
+            "graph G {\n  \"a\";\n  \"b\";\n  \"a\" -- \"b\" [label=\"2.5\"] ;\n}",
+            true,
+        )
+        .unwrap();
+        assert_eq!(g.directed, Some(false));
+        assert_eq!(g.edges[0].weight, 2.5);
+    }
+
+    #[test]
+    fn reports_errors_with_line_numbers() {
+        assert_eq!(
+            parse("digraph {\n a -- b\n}", false).unwrap_err(),
+            "dot: line 2: '--' is not allowed in a digraph"
+        );
+        assert_eq!(
+            parse("graph {\n a -- b", false).unwrap_err(),
+            "dot: line 2: missing '}'"
+        );
+        assert!(parse("digraph { a -> b [weight=x] }", true).is_err());
+        assert!(parse("a b", false).is_err());
+    }
+}
@@ -0,0 +1,163 @@
+//! GraphML 读取：`<key>` 声明的属性名和默认值、`edgedefault`、节点和边上的 `<data>`。
+//! 嵌套图的节点和边展开到同一张图里。
+
+use std::collections::HashMap;
+
+use roxmltree::{Document, Node};
+
+use crate::input::{Attrs, GraphData, merge_attrs, set_attr};
+
+struct Key {
+    name: String,
+    domain: String,
+    default: Option<String>,
+}
+
+pub fn parse(content: &str, weighted: bool) -> Result<GraphData, String> {
+    let doc = Document::parse(content).map_err(|e| format!("graphml: {e}"))?;
+    let root = doc.root_element();
+    if root.tag_name().name() != "graphml" {
+        return Err(format!(
+            "graphml: expected <graphml> root element, got <{}>",
+            root.tag_name().name()
+        ));
+    }
+
+    let mut keys: HashMap<String, Key> = HashMap::new();
+    for k in root.children().filter(|n| is_element(n, "key")) {
+        let Some(id) = k.attribute("id") else {
+            continue;
+        };
+        let default = k
+            .children()
+            .find(|n| is_element(n, "default"))
+            .map(|d| d.text().unwrap_or("").trim().to_string());
+        keys.insert(
+            id.to_string(),
+            Key {
+                name: k.attribute("attr.name").unwrap_or(id).to_string(),
+                domain: k.attribute("for").unwrap_or("all").to_string(),
+                default,
+            },
+        );
+    }
+    // 按 id 排序，保证默认属性的顺序稳定
+    let mut key_ids: Vec<&String> = keys.keys().collect();
+    key_ids.sort();
+    let defaults = |domain: &str| -> Attrs {
+        key_ids
+            .iter()
+            .filter_map(|id| {
+                let k = &keys[*id];
+                let value = k.default.as_ref()?;
+                (k.domain == domain || k.domain == "all").then(|| (k.name.clone(), value.clone()))
+            })
+            .collect()
+    };
+    let data = |node: Node| -> Attrs {
+        let mut attrs = Attrs::new();
+        for d in node.children().filter(|n| is_element(n, "data")) {
+            let key = d.attribute("key").unwrap_or("");
+            let name = keys.get(key).map_or(key, |k| k.name.as_str());
+            set_attr(&mut attrs, name, d.text().unwrap_or("").trim());
+        }
+        attrs
+    };
+
+    let top = root
+        .children()
+        .find(|n| is_element(n, "graph"))
+        .ok_or("graphml: no <graph> element")?;
+    let mut graph = GraphData::default();
+    graph.directed = Some(top.attribute("edgedefault") != Some("undirected"));
+    graph.attrs = data(top);
+    let node_defaults = defaults("node");
+    let edge_defaults = defaults("edge");
+    for n in top.descendants() {
+        if is_element(&n, "node") {
+            let id = n
+                .attribute("id")
+                .ok_or_else(|| format!("graphml: line {}: <node> without id", line_of(&doc, n)))?;
+            let mut attrs = node_defaults.clone();
+            merge_attrs(&mut attrs, &data(n));
+            let (node, _) = graph.add_node(id);
+            merge_attrs(&mut node.attrs, &attrs);
+        } else if is_element(&n, "edge") {
+            let line = line_of(&doc, n);
+            let (Some(from), Some(to)) = (n.attribute("source"), n.attribute("target")) else {
+                return Err(format!(
+                    "graphml: line {line}: <edge> needs source and target"
+                ));
+            };
+            let mut attrs = edge_defaults.clone();
+            merge_attrs(&mut attrs, &data(n));
+            if let Some(id) = n.attribute("id") {
+                attrs.insert(0, ("id".to_string(), id.to_string()));
+            }
+            graph
+                .add_edge(from, to, attrs, weighted)
+                .map_err(|e| format!("graphml: line {line}: {e}"))?;
+        }
+    }
+    Ok(graph)
+}
+
+fn line_of(doc: &Document, node: Node) -> u32 {
+    doc.text_pos_at(node.range().start).row
+}
+
+/// 只比较本地名，GraphML 通常带默认命名空间
+fn is_element(node: &Node, name: &str) -> bool {
+    node.is_element() && node.tag_name().name() == name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::attr;
+
+    const SAMPLE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="d0" for="node" attr.name="color" attr.type="string"><default>gray</default></key>
+  <key id="d1" for="edge" attr.name="weight" attr.type="double"/>
+  <key id="d2" for="graph" attr.name="name" attr.type="string"/>
+  <graph id="G" edgedefault="undirected">
+    <data key="d2">roads</data>
+    <node id="a"><data key="d0">red</data></node>
+    <node id="b"/>
+    <node id="lonely"/>
+    <edge id="e0" source="a" target="b"><data key="d1">2.5</data></edge>
+    <edge source="b" target="c"/>
+  </graph>
+</graphml>"#;
+
+    #[test]
+    fn parses_keys_defaults_and_data() {
+        let g = parse(SAMPLE, true).unwrap();
+        assert_eq!(g.directed, Some(false));
+        assert_eq!(attr(&g.attrs, "name"), Some("roads"));
+        assert_eq!(g.nodes.len(), 4);
+        assert_eq!(attr(&g.node("a").unwrap().attrs, "color"), Some("red"));
+        assert_eq!(attr(&g.node("b").unwrap().attrs, "color"), Some("gray"));
+        assert_eq!(g.edges[0].weight, 2.5);
+        assert_eq!(attr(&g.edges[0].attrs, "id"), Some("e0"));
+        assert_eq!(g.edges[1].weight, 1.0);
+    }
+
+    #[test]
+    fn rejects_malformed_documents() {
+        assert!(parse("<graphml><graph>", false).is_err());
+        assert_eq!(
+            parse("<graphml/>", false).unwrap_err(),
+            "graphml: no <graph> element"
+        );
+        assert_eq!(
+            parse(
+                "<graphml>\n<graph>\n<edge source=\"a\"/></graph></graphml>",
+                false
+            )
+            .unwrap_err(),
+            "graphml: line 3: <edge> needs source and target"
+        );
+    }
+}
@@ -0,0 +1,460 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+/// 节点、边和图的属性，保持输入中的顺序
+pub type Attrs = Vec<(String, String)>;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GraphNode {
+    pub id: String,
+    pub attrs: Attrs,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    pub weight: f64,
+    pub attrs: Attrs,
+}
+
+/// 解析后的图，`nodes` 包括只声明、没有边的孤立节点
+#[derive(Clone, Debug, Default)]
+pub struct GraphData {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    /// 输入自己声明的方向：DOT 的 `graph`/`digraph`、GraphML 的 `edgedefault`、
+    /// node-link 的 `directed`，以及对称的邻接矩阵
+    pub directed: Option<bool>,
+    pub attrs: Attrs,
+    index: HashMap<String, usize>,
+}
+
+impl GraphData {
+    /// 取节点，不存在时创建；返回值表示是否新建
+    pub fn add_node(&mut self, id: &str) -> (&mut GraphNode, bool) {
+        let created = !self.index.contains_key(id);
+        if created {
+            self.index.insert(id.to_string(), self.nodes.len());
+            self.nodes.push(GraphNode {
+                id: id.to_string(),
+                attrs: Vec::new(),
+            });
+        }
+        let idx = self.index[id];
+        (&mut self.nodes[idx], created)
+    }
+
+    /// 添加边并补齐两端节点
+    ///
+    /// 权重取 `weight` 属性，没有时取数字形式的 `label`（`--viz dot` 就是这样输出权重的），
+    /// 都没有时为 1。只有 `weighted` 时才检查权重能否解析。
+    pub fn add_edge(
+        &mut self,
+        from: &str,
+        to: &str,
+        attrs: Attrs,
+        weighted: bool,
+    ) -> Result<(), String> {
+        let weight = match attr(&attrs, "weight") {
+            Some(w) => match w.trim().parse::<f64>() {
+                Ok(v) => v,
+                Err(e) if weighted => {
+                    return Err(format!("edge {from} -> {to}: invalid weight {w:?}: {e}"));
+                }
+                Err(_) => 1.0,
+            },
+            None => attr(&attrs, "label")
+                .and_then(|l| l.trim().parse::<f64>().ok())
+                .unwrap_or(1.0),
+        };
+        self.add_node(from);
+        self.add_node(to);
+        self.edges.push(GraphEdge {
+            from: from.to_string(),
+            to: to.to_string(),
+            weight,
+            attrs,
+        });
+        Ok(())
+    }
+
+    pub fn node(&self, id: &str) -> Option<&GraphNode> {
+        self.index.get(id).map(|&idx| &self.nodes[idx])
+    }
+
+    pub fn sorted_node_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.nodes.iter().map(|n| n.id.clone()).collect();
+        ids.sort();
+        ids
+    }
+}
+
+pub fn attr<'a>(attrs: &'a Attrs, key: &str) -> Option<&'a str> {
+    attrs
+        .iter()
+        .rev()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.as_str())
+}
+
+/// 设置属性，已存在时覆盖原值并保持原来的位置
+pub fn set_attr(attrs: &mut Attrs, key: &str, value: &str) {
+    match attrs.iter_mut().find(|(k, _)| k == key) {
+        Some((_, v)) => *v = value.to_string(),
+        None => attrs.push((key.to_string(), value.to_string())),
+    }
+}
+
+pub fn merge_attrs(attrs: &mut Attrs, other: &Attrs) {
+    for (k, v) in other {
+        set_attr(attrs, k, v);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputFormat {
+    EdgeList,
+    Dot,
+    GraphMl,
+    NodeLink,
+    Matrix,
+}
+
+impl InputFormat {
+    /// 解析 `--in-fmt`，`auto` 返回 `None`
+    pub fn from_name(name: &str) -> Result<Option<Self>, String> {
+        match name.trim().to_lowercase().as_str() {
+            "" | "auto" => Ok(None),
+            "edges" | "edgelist" | "txt" => Ok(Some(Self::EdgeList)),
+            "dot" | "gv" => Ok(Some(Self::Dot)),
+            "graphml" | "xml" => Ok(Some(Self::GraphMl)),
+            "json" | "nodelink" | "node-link" => Ok(Some(Self::NodeLink)),
+            "matrix" | "adj" | "adjacency" => Ok(Some(Self::Matrix)),
+            other => Err(format!(
+                "unsupported input format {other:?}, use auto|edges|dot|graphml|json|matrix"
+            )),
+        }
+    }
+
+    /// 按扩展名判断；`.csv`、`.txt` 这类可能是边列表也可能是矩阵的返回 `None`，交给内容判断
+    pub fn from_extension(path: &str) -> Option<Self> {
+        let ext = Path::new(path).extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "dot" | "gv" => Some(Self::Dot),
+            "graphml" => Some(Self::GraphMl),
+            "json" => Some(Self::NodeLink),
+            _ => None,
+        }
+    }
+
+    /// 按内容判断：`<` 开头是 GraphML，`{`/`[` 开头是 JSON，`graph`/`digraph` 加 `{` 是 DOT，
+    /// 带表头的方阵是邻接矩阵，其余按边列表处理
+    pub fn sniff(content: &str, sep: &str) -> Self {
+        let first = content
+            .trim_start_matches('\u{feff}')
+            .lines()
+            .map(str::trim)
+            .find(|l| !l.is_empty() && !l.starts_with('#') && !l.starts_with("//"))
+            .unwrap_or("");
+        if first.starts_with('<') {
+            return Self::GraphMl;
+        }
+        if first.starts_with('{') || first.starts_with('[') {
+            return Self::NodeLink;
+        }
+        let mut words = first
+            .split(|c: char| c.is_whitespace() || c == '{')
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase);
+        let mut keyword = words.next().unwrap_or_default();
+        if keyword == "strict" {
+            keyword = words.next().unwrap_or_default();
+        }
+        if (keyword == "graph" || keyword == "digraph") && content.contains('{') {
+            return Self::Dot;
+        }
+        if parse_matrix_table(content, sep, false).is_some() {
+            return Self::Matrix;
+        }
+        Self::EdgeList
+    }
+
+    pub fn detect(path: &str, content: &str, sep: &str) -> Self {
+        Self::from_extension(path).unwrap_or_else(|| Self::sniff(content, sep))
+    }
+}
+
+pub fn parse_graph(
+    content: &str,
+    format: InputFormat,
+    weighted: bool,
+    sep: &str,
+) -> Result<GraphData, String> {
+    match format {
+        InputFormat::EdgeList => parse_edge_list(content, weighted, sep),
+        InputFormat::Dot => crate::dot::parse(content, weighted),
+        InputFormat::GraphMl => crate::graphml::parse(content, weighted),
+        InputFormat::NodeLink => crate::nodelink::parse(content, weighted),
+        InputFormat::Matrix => parse_matrix(content, sep),
+    }
+}
+
+// ─── 边列表 ───────────────────────────────────────────────────────────────────
+
+fn parse_edge_list(content: &str, weighted: bool, sep: &str) -> Result<GraphData, String> {
+    let mut graph = GraphData::default();
+    for (idx, line) in content.lines().enumerate() {
+        let line_no = idx + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
+            continue;
+        }
+
+        let parts = split_fields(line, sep);
+        if parts.len() < 2 {
+            return Err(format!(
+                "line {line_no}: expected at least 2 columns, got {}",
+                parts.len()
+            ));
+        }
+
+        let mut weight = 1.0;
+        if weighted {
+            if parts.len() < 3 {
+                return Err(format!(
+                    "line {line_no}: expected 3 columns for weighted graph"
+                ));
+            }
+            weight = parts[2]
+                .parse::<f64>()
+                .map_err(|e| format!("line {line_no}: invalid weight {:?}: {e}", parts[2]))?;
+        }
+
+        graph.add_node(&parts[0]);
+        graph.add_node(&parts[1]);
+        graph.edges.push(GraphEdge {
+            from: parts[0].clone(),
+            to: parts[1].clone(),
+            weight,
+            attrs: Vec::new(),
+        });
+    }
+    Ok(graph)
+}
+
+fn split_fields(line: &str, sep: &str) -> Vec<String> {
+    if sep.is_empty() {
+        return line.split_whitespace().map(|s| s.to_string()).collect();
+    }
+    line.split(sep)
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+// ─── 邻接矩阵 ─────────────────────────────────────────────────────────────────
+
+/// 解析后的邻接矩阵：行标签、列标签和数值
+struct MatrixTable {
+    rows: Vec<String>,
+    cols: Vec<String>,
+    values: Vec<Vec<f64>>,
+}
+
+/// 邻接矩阵：首行是列名（左上角可以留空），之后每行是行名加数值，非 0 的格子是一条边，
+/// 数值就是权重。也接受没有表头的纯数字方阵，节点按 0 开始编号。
+///
+/// 矩阵对称时视为无向图，只取上三角。
+fn parse_matrix(content: &str, sep: &str) -> Result<GraphData, String> {
+    let table = parse_matrix_table(content, sep, true).ok_or(
+        "matrix: expected a header row of node names followed by one row per node, \
+         or a square numeric matrix",
+    )?;
+    let n = table.cols.len();
+    let symmetric = table.rows == table.cols
+        && (0..n).all(|i| (0..i).all(|j| table.values[i][j] == table.values[j][i]));
+
+    let mut graph = GraphData {
+        directed: symmetric.then_some(false),
+        ..GraphData::default()
+    };
+    for name in table.rows.iter().chain(&table.cols) {
+        graph.add_node(name);
+    }
+    for (i, row) in table.values.iter().enumerate() {
+        for (j, &w) in row.iter().enumerate() {
+            if w == 0.0 || (symmetric && j < i) {
+                continue;
+            }
+            graph.edges.push(GraphEdge {
+                from: table.rows[i].clone(),
+                to: table.cols[j].clone(),
+                weight: w,
+                attrs: Vec::new(),
+            });
+        }
+    }
+    Ok(graph)
+}
+
+/// `allow_headerless` 为假时只认带表头的矩阵，用于内容判断，避免把全是数字的带权边列表当成矩阵
+fn parse_matrix_table(content: &str, sep: &str, allow_headerless: bool) -> Option<MatrixTable> {
+    let lines: Vec<&str> = content
+        .trim_start_matches('\u{feff}')
+        .lines()
+        .filter(|l| {
+            let t = l.trim();
+            !t.is_empty() && !t.starts_with('#') && !t.starts_with("//")
+        })
+        .collect();
+    let first = *lines.first()?;
+    let sep = if !sep.is_empty() {
+        sep
+    } else if first.contains(',') {
+        ","
+    } else if first.contains('\t') {
+        "\t"
+    } else if first.contains(';') {
+        ";"
+    } else {
+        ""
+    };
+    let cells = |line: &str| -> Vec<String> {
+        if sep.is_empty() {
+            line.split_whitespace().map(str::to_string).collect()
+        } else {
+            line.split(sep).map(|c| c.trim().to_string()).collect()
+        }
+    };
+    let number = |c: &str| -> Option<f64> {
+        if c.is_empty() {
+            Some(0.0)
+        } else {
+            c.parse::<f64>().ok().filter(|v| v.is_finite())
+        }
+    };
+    let rows: Vec<Vec<String>> = lines.iter().map(|l| cells(l)).collect();
+
+    let header = &rows[0];
+    let header_numeric = header.iter().all(|c| !c.is_empty() && number(c).is_some());
+    if header_numeric {
+        // 没有表头的纯数字方阵
+        let n = header.len();
+        if !allow_headerless || n == 0 || rows.len() != n || rows.iter().any(|r| r.len() != n) {
+            return None;
+        }
+        let values = rows
+            .iter()
+            .map(|r| r.iter().map(|c| number(c)).collect::<Option<Vec<f64>>>())
+            .collect::<Option<Vec<_>>>()?;
+        let names: Vec<String> = (0..n).map(|i| i.to_string()).collect();
+        return Some(MatrixTable {
+            rows: names.clone(),
+            cols: names,
+            values,
+        });
+    }
+
+    let cols: Vec<String> = match header.first() {
+        Some(c) if c.is_empty() => header[1..].to_vec(),
+        _ => header.clone(),
+    };
+    let data = &rows[1..];
+    if cols.is_empty()
+        || cols.iter().any(|c| c.is_empty())
+        || data.len() != cols.len()
+        || data
+            .iter()
+            .any(|r| r.len() != cols.len() + 1 || r[0].is_empty())
+    {
+        return None;
+    }
+    let values = data
+        .iter()
+        .map(|r| {
+            r[1..]
+                .iter()
+                .map(|c| number(c))
+                .collect::<Option<Vec<f64>>>()
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(MatrixTable {
+        rows: data.iter().map(|r| r[0].clone()).collect(),
+        cols,
+        values,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_formats_by_extension_and_content() {
+        let detect = |path: &str, content: &str| InputFormat::detect(path, content, "");
+        assert_eq!(detect("g.gv", "a b"), InputFormat::Dot);
+        assert_eq!(detect("g.graphml", ""), InputFormat::GraphMl);
+        assert_eq!(
+            detect("<stdin>", "// deps\ndigraph {\n a -> b\n}"),
+            InputFormat::Dot
+        );
+        assert_eq!(
+            detect("g.txt", "strict graph G { a -- b }"),
+            InputFormat::Dot
+        );
+        assert_eq!(
+            detect("g.xml", "<?xml version=\"1.0\"?>"),
+            InputFormat::GraphMl
+        );
+        assert_eq!(detect("<stdin>", "{\"nodes\": []}"), InputFormat::NodeLink);
+        assert_eq!(detect("g.csv", ",a,b\na,0,1\nb,1,0\n"), InputFormat::Matrix);
+        assert_eq!(detect("g.txt", "a b\nb c\n"), InputFormat::EdgeList);
+        assert_eq!(
+            detect("g.txt", "1 2 3\n2 3 4\n3 1 5\n"),
+            InputFormat::EdgeList
+        );
+        assert_eq!(detect("g.txt", "graph a\ngraph b\n"), InputFormat::EdgeList);
+    }
+
+    #[test]
+    fn parses_adjacency_matrices() {
+        let g = parse_matrix(",a,b,c\na,0,2,0\nb,2,0,1.5\nc,0,1.5,0\n", "").unwrap();
+        assert_eq!(g.directed, Some(false));
+        let edges: Vec<(&str, &str, f64)> = g
+            .edges
+            .iter()
+            .map(|e| (e.from.as_str(), e.to.as_str(), e.weight))
+            .collect();
+        assert_eq!(edges, vec![("a", "b", 2.0), ("b", "c", 1.5)]);
+
+        let g = parse_matrix("x y z\nx 0 1 0\ny 0 0 1\nz 0 0 0\n", "").unwrap();
+        assert_eq!(g.directed, None);
+        assert_eq!(g.edges.len(), 2);
+        assert_eq!(g.nodes.len(), 3);
+
+        let g = parse_matrix("0 1\n0 0\n", "").unwrap();
+        assert_eq!(
+            (g.edges[0].from.as_str(), g.edges[0].to.as_str()),
+            ("0", "1")
+        );
+        assert!(parse_matrix("a,b\na,0\n", "").is_err());
+    }
+
+    #[test]
+    fn edge_weight_falls_back_to_numeric_label() {
+        let mut g = GraphData::default();
+        g.add_edge("a", "b", vec![("label".into(), "2.5".into())], true)
+            .unwrap();
+        g.add_edge("b", "c", vec![("color".into(), "red".into())], true)
+            .unwrap();
+        assert_eq!(g.edges[0].weight, 2.5);
+        assert_eq!(g.edges[1].weight, 1.0);
+        assert!(
+            g.add_edge("c", "a", vec![("weight".into(), "heavy".into())], true)
+                .is_err()
+        );
+        assert_eq!(g.nodes.len(), 3);
+    }
+}
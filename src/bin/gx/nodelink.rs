@@ -0,0 +1,173 @@
+//! JSON node-link 读取，兼容 networkx `node_link_data` 和 d3 force 布局的数据：
+//! `{"directed": bool, "graph": {...}, "nodes": [{"id": ...}], "links"|"edges": [{"source", "target"}]}`。
+//!
+//! 节点没有 `id` 时依次用 `name` 和数组下标；d3 的边端点可以是节点下标，也可以是节点对象。
+//! 数字端点优先匹配节点 id，匹配不上再按下标解析。
+
+use serde_json::{Map, Value};
+
+use crate::input::{Attrs, GraphData, merge_attrs};
+
+pub fn parse(content: &str, weighted: bool) -> Result<GraphData, String> {
+    let root: Value = serde_json::from_str(content).map_err(|e| format!("json: {e}"))?;
+    let obj = root
+        .as_object()
+        .ok_or("json: expected a node-link object with \"nodes\" and \"links\"")?;
+    let mut graph = GraphData::default();
+    graph.directed = obj.get("directed").and_then(Value::as_bool);
+    if let Some(g) = obj.get("graph").and_then(Value::as_object) {
+        graph.attrs = attrs_of(g, &[]);
+    }
+
+    let nodes = match obj.get("nodes") {
+        None | Some(Value::Null) => &Vec::new(),
+        Some(Value::Array(nodes)) => nodes,
+        Some(_) => return Err("json: \"nodes\" must be an array".into()),
+    };
+    let mut ids = Vec::with_capacity(nodes.len());
+    for (idx, n) in nodes.iter().enumerate() {
+        let obj = n
+            .as_object()
+            .ok_or_else(|| format!("json: nodes[{idx}] must be an object"))?;
+        let id = match obj.get("id").or_else(|| obj.get("name")) {
+            Some(v) => {
+                scalar_id(v).ok_or_else(|| format!("json: nodes[{idx}] has an invalid id"))?
+            }
+            None => idx.to_string(),
+        };
+        let (node, _) = graph.add_node(&id);
+        merge_attrs(&mut node.attrs, &attrs_of(obj, &["id"]));
+        ids.push(id);
+    }
+
+    let links = match obj.get("links").or_else(|| obj.get("edges")) {
+        None | Some(Value::Null) => &Vec::new(),
+        Some(Value::Array(links)) => links,
+        Some(_) => return Err("json: \"links\" must be an array".into()),
+    };
+    for (idx, l) in links.iter().enumerate() {
+        let obj = l
+            .as_object()
+            .ok_or_else(|| format!("json: links[{idx}] must be an object"))?;
+        let by_index = |v: &Value| ids.get(usize::try_from(v.as_u64()?).ok()?).cloned();
+        let endpoint = |key: &str| -> Result<String, String> {
+            let v = obj
+                .get(key)
+                .ok_or_else(|| format!("json: links[{idx}] has no {key:?}"))?;
+            let id = match v {
+                // d3 force 布局会把端点替换成节点对象
+                Value::Object(o) => o
+                    .get("id")
+                    .or_else(|| o.get("name"))
+                    .and_then(scalar_id)
+                    .or_else(|| by_index(o.get("index")?)),
+                // 数字端点不是已有节点的 id 时当作节点下标（d3 的 miserables.json）
+                Value::Number(_) => scalar_id(v)
+                    .filter(|id| graph.node(id).is_some())
+                    .or_else(|| by_index(v))
+                    .or_else(|| scalar_id(v)),
+                other => scalar_id(other),
+            };
+            id.ok_or_else(|| format!("json: links[{idx}] has an invalid {key:?}"))
+        };
+        let (from, to) = (endpoint("source")?, endpoint("target")?);
+        graph
+            .add_edge(&from, &to, attrs_of(obj, &["source", "target"]), weighted)
+            .map_err(|e| format!("json: links[{idx}]: {e}"))?;
+    }
+    Ok(graph)
+}
+
+fn scalar_id(v: &Value) -> Option<String> {
+    match v {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// 除 `skip` 以外的字段都作为属性，字符串取原值，其余保留 JSON 文本
+fn attrs_of(obj: &Map<String, Value>, skip: &[&str]) -> Attrs {
+    obj.iter()
+        .filter(|(k, _)| !skip.contains(&k.as_str()))
+        .map(|(k, v)| {
+            let v = match v {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            (k.clone(), v)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::attr;
+
+    #[test]
+    fn parses_networkx_node_link_data() {
+        let g = parse(
+            r#"{"directed": false, "multigraph": false, "graph": {"name": "g"},
+                "nodes": [{"id": "a", "color": "red"}, {"id": 2}, {"id": "lonely"}],
+                "links": [{"source": "a", "target": 2, "weight": 1.5, "tags": ["x"]}]}"#,
+            true,
+        )
+        .unwrap();
+        assert_eq!(g.directed, Some(false));
+        assert_eq!(attr(&g.attrs, "name"), Some("g"));
+        assert_eq!(g.nodes.len(), 3);
+        assert_eq!(attr(&g.node("a").unwrap().attrs, "color"), Some("red"));
+        let e = &g.edges[0];
+        assert_eq!((e.from.as_str(), e.to.as_str(), e.weight), ("a", "2", 1.5));
+        assert_eq!(attr(&e.attrs, "tags"), Some(r#"["x"]"#));
+    }
+
+    #[test]
+    fn resolves_d3_index_and_object_endpoints() {
+        let g = parse(
+            r#"{"nodes": [{"group": 1}, {"group": 2}],
+                "edges": [{"source": 0, "target": 1}, {"source": {"index": 1}, "target": 0}]}"#,
+            false,
+        )
+        .unwrap();
+        let ends: Vec<(&str, &str)> = g
+            .edges
+            .iter()
+            .map(|e| (e.from.as_str(), e.to.as_str()))
+            .collect();
+        assert_eq!(ends, vec![("0", "1"), ("1", "0")]);
+        assert_eq!(attr(&g.node("1").unwrap().attrs, "group"), Some("2"));
+        assert_eq!(g.directed, None);
+
+        let g = parse(
+            r#"{"nodes": [{"name": "x"}, {"name": "y"}],
+                "links": [{"source": {"name": "x", "index": 0}, "target": "y", "value": 3}]}"#,
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            (g.edges[0].from.as_str(), g.edges[0].to.as_str()),
+            ("x", "y")
+        );
+        assert!(parse("[1, 2]", false).is_err());
+    }
+
+    #[test]
+    fn numeric_endpoints_index_named_nodes() {
+        let g = parse(
+            r#"{"nodes":[{"name":"Myriel"},{"name":"Napoleon"},{"name":"Mlle"}],
+                "links":[{"source":1,"target":0},{"source":2,"target":0}]}"#,
+            false,
+        )
+        .unwrap();
+        assert_eq!(g.nodes.len(), 3);
+        let ends: Vec<(&str, &str)> = g
+            .edges
+            .iter()
+            .map(|e| (e.from.as_str(), e.to.as_str()))
+            .collect();
+        assert_eq!(ends, vec![("Napoleon", "Myriel"), ("Mlle", "Myriel")]);
+    }
+}
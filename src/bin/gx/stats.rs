@@ -0,0 +1,314 @@
+//! `gx stats`：度分布、密度、直径、平均路径长度和中心性排名
+//!
+//! 距离按边数计，不看权重，只统计互相可达的有序节点对。介数中心性用 Brandes 算法，
+//! 接近中心性取调和平均，这样不连通的图也有定义。自环和重复边不计入度数和密度。
+
+use std::collections::{HashMap, VecDeque};
+
+use serde::Serialize;
+
+use crate::input::GraphData;
+
+#[derive(Clone, Debug, Serialize, Default, PartialEq)]
+pub struct GraphStats {
+    pub density: f64,
+    /// 弱连通分量数
+    pub components: usize,
+    pub isolated: usize,
+    pub self_loops: usize,
+    pub min_degree: usize,
+    pub max_degree: usize,
+    pub mean_degree: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_in_degree: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_out_degree: Option<usize>,
+    pub degree_distribution: Vec<DegreeCount>,
+    pub diameter: usize,
+    pub average_path_length: f64,
+    pub reachable_pairs: usize,
+    pub central: Vec<Centrality>,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct DegreeCount {
+    pub degree: usize,
+    pub count: usize,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct Centrality {
+    pub node: String,
+    pub degree: usize,
+    /// 归一化到 0..1 的介数中心性
+    pub betweenness: f64,
+    /// 调和接近中心性，归一化到 0..1
+    pub closeness: f64,
+}
+
+pub fn compute(graph: &GraphData, undirected: bool, top: usize) -> GraphStats {
+    let n = graph.nodes.len();
+    let index: HashMap<&str, usize> = graph
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| (node.id.as_str(), i))
+        .collect();
+    let mut out = vec![Vec::new(); n];
+    let mut inc = vec![Vec::new(); n];
+    let mut self_loops = 0;
+    for e in &graph.edges {
+        let (f, t) = (index[e.from.as_str()], index[e.to.as_str()]);
+        if f == t {
+            self_loops += 1;
+            continue;
+        }
+        out[f].push(t);
+        inc[t].push(f);
+        if undirected {
+            out[t].push(f);
+            inc[f].push(t);
+        }
+    }
+    for list in out.iter_mut().chain(inc.iter_mut()) {
+        list.sort_unstable();
+        list.dedup();
+    }
+
+    let degree: Vec<usize> = if undirected {
+        out.iter().map(Vec::len).collect()
+    } else {
+        (0..n).map(|i| out[i].len() + inc[i].len()).collect()
+    };
+    let edges: usize = out.iter().map(Vec::len).sum::<usize>() / if undirected { 2 } else { 1 };
+    let pairs = n.saturating_mul(n.saturating_sub(1));
+    let density = match (pairs, undirected) {
+        (0, _) => 0.0,
+        (p, true) => 2.0 * edges as f64 / p as f64,
+        (p, false) => edges as f64 / p as f64,
+    };
+
+    let mut distribution: Vec<DegreeCount> = Vec::new();
+    let mut sorted = degree.clone();
+    sorted.sort_unstable();
+    for d in sorted {
+        match distribution.last_mut() {
+            Some(last) if last.degree == d => last.count += 1,
+            _ => distribution.push(DegreeCount {
+                degree: d,
+                count: 1,
+            }),
+        }
+    }
+
+    let paths = shortest_paths(&out);
+    let scale = if n > 2 {
+        1.0 / ((n - 1) * (n - 2)) as f64
+    } else {
+        0.0
+    };
+    let mut central: Vec<Centrality> = (0..n)
+        .map(|i| Centrality {
+            node: graph.nodes[i].id.clone(),
+            degree: degree[i],
+            betweenness: paths.betweenness[i] * scale,
+            closeness: if n > 1 {
+                paths.harmonic[i] / (n - 1) as f64
+            } else {
+                0.0
+            },
+        })
+        .collect();
+    central.sort_by(|a, b| {
+        b.betweenness
+            .total_cmp(&a.betweenness)
+            .then(b.closeness.total_cmp(&a.closeness))
+            .then(b.degree.cmp(&a.degree))
+            .then(a.node.cmp(&b.node))
+    });
+    central.truncate(top);
+
+    GraphStats {
+        density,
+        components: weak_components(&out, &inc),
+        isolated: degree.iter().filter(|&&d| d == 0).count(),
+        self_loops,
+        min_degree: degree.iter().copied().min().unwrap_or(0),
+        max_degree: degree.iter().copied().max().unwrap_or(0),
+        mean_degree: if n == 0 {
+            0.0
+        } else {
+            degree.iter().sum::<usize>() as f64 / n as f64
+        },
+        max_in_degree: (!undirected).then(|| inc.iter().map(Vec::len).max().unwrap_or(0)),
+        max_out_degree: (!undirected).then(|| out.iter().map(Vec::len).max().unwrap_or(0)),
+        degree_distribution: distribution,
+        diameter: paths.diameter,
+        average_path_length: if paths.pairs == 0 {
+            0.0
+        } else {
+            paths.total as f64 / paths.pairs as f64
+        },
+        reachable_pairs: paths.pairs,
+        central,
+    }
+}
+
+struct Paths {
+    diameter: usize,
+    total: usize,
+    pairs: usize,
+    /// 未归一化的介数，按有序节点对累计
+    betweenness: Vec<f64>,
+    harmonic: Vec<f64>,
+}
+
+/// 从每个节点 BFS 一次，顺带用 Brandes 算法累计介数
+fn shortest_paths(adj: &[Vec<usize>]) -> Paths {
+    let n = adj.len();
+    let mut paths = Paths {
+        diameter: 0,
+        total: 0,
+        pairs: 0,
+        betweenness: vec![0.0; n],
+        harmonic: vec![0.0; n],
+    };
+    let mut dist = vec![usize::MAX; n];
+    let mut sigma = vec![0f64; n];
+    let mut delta = vec![0f64; n];
+    let mut preds: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut order = Vec::with_capacity(n);
+    for s in 0..n {
+        dist.fill(usize::MAX);
+        sigma.fill(0.0);
+        delta.fill(0.0);
+        preds.iter_mut().for_each(Vec::clear);
+        order.clear();
+        dist[s] = 0;
+        sigma[s] = 1.0;
+        let mut queue = VecDeque::from([s]);
+        while let Some(u) = queue.pop_front() {
+            order.push(u);
+            for &v in &adj[u] {
+                if dist[v] == usize::MAX {
+                    dist[v] = dist[u] + 1;
+                    queue.push_back(v);
+                }
+                if dist[v] == dist[u] + 1 {
+                    sigma[v] += sigma[u];
+                    preds[v].push(u);
+                }
+            }
+        }
+        for &v in &order[1..] {
+            paths.diameter = paths.diameter.max(dist[v]);
+            paths.total += dist[v];
+            paths.pairs += 1;
+            paths.harmonic[s] += 1.0 / dist[v] as f64;
+        }
+        for &w in order.iter().rev() {
+            for &v in &preds[w] {
+                delta[v] += sigma[v] / sigma[w] * (1.0 + delta[w]);
+            }
+            if w != s {
+                paths.betweenness[w] += delta[w];
+            }
+        }
+    }
+    paths
+}
+
+fn weak_components(out: &[Vec<usize>], inc: &[Vec<usize>]) -> usize {
+    let mut seen = vec![false; out.len()];
+    let mut count = 0;
+    for start in 0..out.len() {
+        if seen[start] {
+            continue;
+        }
+        count += 1;
+        seen[start] = true;
+        let mut stack = vec![start];
+        while let Some(u) = stack.pop() {
+            for &v in out[u].iter().chain(&inc[u]) {
+                if !seen[v] {
+                    seen[v] = true;
+                    stack.push(v);
+                }
+            }
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(&str, &str)], isolated: &[&str]) -> GraphData {
+        let mut g = GraphData::default();
+        for (from, to) in edges {
+            g.add_edge(from, to, Vec::new(), false).unwrap();
+        }
+        for id in isolated {
+            g.add_node(id);
+        }
+        g
+    }
+
+    #[test]
+    fn path_graph_metrics() {
+        // a - b - c - d，无向
+        let g = graph(&[("a", "b"), ("b", "c"), ("c", "d")], &[]);
+        let s = compute(&g, true, 2);
+        assert_eq!(s.density, 0.5);
+        assert_eq!(s.components, 1);
+        assert_eq!((s.min_degree, s.max_degree, s.mean_degree), (1, 2, 1.5));
+        assert_eq!(
+            s.degree_distribution,
+            vec![
+                DegreeCount {
+                    degree: 1,
+                    count: 2
+                },
+                DegreeCount {
+                    degree: 2,
+                    count: 2
+                }
+            ]
+        );
+        assert_eq!(s.diameter, 3);
+        assert_eq!(s.reachable_pairs, 12);
+        assert!((s.average_path_length - 20.0 / 12.0).abs() < 1e-12);
+        // b 和 c 各在 2 对节点的最短路径上：2 / C(3, 2)
+        let names: Vec<&str> = s.central.iter().map(|c| c.node.as_str()).collect();
+        assert_eq!(names, vec!["b", "c"]);
+        assert!((s.central[0].betweenness - 2.0 / 3.0).abs() < 1e-12);
+        assert_eq!(s.max_in_degree, None);
+    }
+
+    #[test]
+    fn directed_star_with_loops_and_isolated_nodes() {
+        let g = graph(
+            &[
+                ("hub", "x"),
+                ("hub", "y"),
+                ("y", "hub"),
+                ("x", "x"),
+                ("x", "y"),
+            ],
+            &["lonely"],
+        );
+        let s = compute(&g, false, 5);
+        assert_eq!(s.self_loops, 1);
+        assert_eq!(s.isolated, 1);
+        assert_eq!(s.components, 2);
+        assert_eq!((s.max_in_degree, s.max_out_degree), (Some(2), Some(2)));
+        // 4 条不同的非自环边，4 个节点共 12 个有序对
+        assert!((s.density - 4.0 / 12.0).abs() < 1e-12);
+        assert_eq!(s.reachable_pairs, 6);
+        assert_eq!(s.diameter, 2);
+        assert_eq!(s.central.len(), 4);
+        assert_eq!(s.central[3].node, "lonely");
+        assert_eq!(s.central[3].closeness, 0.0);
+    }
+}
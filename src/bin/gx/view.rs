@@ -0,0 +1,890 @@
+//! `gx view`：在终端里交互浏览图
+//!
+//! 左边是节点列表（入度/出度、所在强连通分量），右边是选中节点的出边、入边和
+//! 以它为中心的分层邻域图。可以沿边跳转、回退、按名字过滤，用 `p` 选两个节点求最短路径。
+
+use std::collections::{HashMap, VecDeque};
+use std::io;
+
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use ratatui::{
+    Frame, Terminal,
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+};
+use rust_tools::cw::{DirectedGraph, UndirectedGraph};
+use unicode_width::UnicodeWidthStr;
+
+use crate::input::GraphData;
+
+/// 分层布局向前、向后各展开的层数
+const LAYOUT_DEPTH: usize = 2;
+/// 分层布局每层最多显示的节点数
+const LAYOUT_MAX_PER_LAYER: usize = 8;
+/// 分层布局里节点名的最大显示宽度
+const LAYOUT_LABEL_WIDTH: usize = 18;
+/// 强连通分量轮流使用的颜色，红色留给 `cycle` 找到的环
+const SCC_COLORS: [Color; 5] = [
+    Color::Yellow,
+    Color::Cyan,
+    Color::Magenta,
+    Color::Blue,
+    Color::LightYellow,
+];
+
+/// 浏览用的图：节点按名字排序后用下标表示
+pub struct Model {
+    pub ids: Vec<String>,
+    out: Vec<Vec<usize>>,
+    inc: Vec<Vec<usize>>,
+    pub edges: usize,
+    pub undirected: bool,
+    /// 节点所在的非平凡强连通分量（多于一个节点或有自环）编号，无向图是连通分量
+    scc: Vec<Option<usize>>,
+    pub scc_count: usize,
+    /// 和 `cycle` 模式一样找到的一个环，首尾是同一个节点
+    pub cycle: Vec<usize>,
+}
+
+impl Model {
+    pub fn new(graph: &GraphData, undirected: bool) -> Self {
+        let ids = graph.sorted_node_ids();
+        let index: HashMap<&str, usize> = ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (id.as_str(), i))
+            .collect();
+        let mut out = vec![Vec::new(); ids.len()];
+        let mut inc = vec![Vec::new(); ids.len()];
+        for e in &graph.edges {
+            let (f, t) = (index[e.from.as_str()], index[e.to.as_str()]);
+            out[f].push(t);
+            inc[t].push(f);
+            if undirected {
+                out[t].push(f);
+                inc[f].push(t);
+            }
+        }
+        for list in out.iter_mut().chain(inc.iter_mut()) {
+            list.sort_unstable();
+            list.dedup();
+        }
+
+        let (mut groups, cycle) = if undirected {
+            let mut g = UndirectedGraph::new();
+            for (i, targets) in out.iter().enumerate() {
+                g.add_node(i);
+                for &t in targets {
+                    g.add_edge(i, t);
+                }
+            }
+            (g.groups(), Vec::new())
+        } else {
+            let mut g = DirectedGraph::new();
+            for (i, targets) in out.iter().enumerate() {
+                g.add_node(i);
+                for &t in targets {
+                    g.add_edge(i, t);
+                }
+            }
+            (g.strong_components(), g.cycle().unwrap_or_default())
+        };
+        groups.retain(|c| c.len() > 1 || out[c[0]].contains(&c[0]));
+        for c in &mut groups {
+            c.sort_unstable();
+        }
+        groups.sort();
+        let mut scc = vec![None; ids.len()];
+        for (n, c) in groups.iter().enumerate() {
+            for &i in c {
+                scc[i] = Some(n);
+            }
+        }
+
+        Self {
+            ids,
+            out,
+            inc,
+            edges: graph.edges.len(),
+            undirected,
+            scc,
+            scc_count: groups.len(),
+            cycle,
+        }
+    }
+
+    /// 按边数最少求路径（BFS），包含两端
+    pub fn path(&self, from: usize, to: usize) -> Option<Vec<usize>> {
+        let mut prev = vec![usize::MAX; self.ids.len()];
+        prev[from] = from;
+        let mut queue = VecDeque::from([from]);
+        while let Some(u) = queue.pop_front() {
+            if u == to {
+                let mut path = vec![to];
+                let mut cur = to;
+                while cur != from {
+                    cur = prev[cur];
+                    path.push(cur);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for &v in &self.out[u] {
+                if prev[v] == usize::MAX {
+                    prev[v] = u;
+                    queue.push_back(v);
+                }
+            }
+        }
+        None
+    }
+
+    fn on_cycle(&self, node: usize) -> bool {
+        self.cycle.contains(&node)
+    }
+
+    /// 以 `center` 为中心的分层布局：左边是前驱（按距离分层），右边是后继，
+    /// 相邻两列之间有边的节点行用 `──▶` 连接。同一节点只出现一次，后继优先。
+    pub fn layered_layout(&self, center: usize, depth: usize, max_per_layer: usize) -> Vec<String> {
+        let mut placed = vec![false; self.ids.len()];
+        placed[center] = true;
+        let forward = self.bfs_layers(center, depth, &self.out, &mut placed);
+        let backward = self.bfs_layers(center, depth, &self.inc, &mut placed);
+
+        // 列从左到右：最远的前驱 … 中心 … 最远的后继
+        let mut columns: Vec<(String, Vec<usize>)> = Vec::new();
+        for (d, layer) in backward.iter().enumerate().rev() {
+            columns.push((format!("←{}", d + 1), layer.clone()));
+        }
+        columns.push(("•".to_string(), vec![center]));
+        for (d, layer) in forward.iter().enumerate() {
+            columns.push((format!("→{}", d + 1), layer.clone()));
+        }
+
+        let cells: Vec<Vec<String>> = columns
+            .iter()
+            .map(|(_, nodes)| {
+                let mut cells: Vec<String> = nodes
+                    .iter()
+                    .take(max_per_layer)
+                    .map(|&n| {
+                        let name = truncate(&self.ids[n], LAYOUT_LABEL_WIDTH);
+                        if n == center {
+                            format!("[[{name}]]")
+                        } else {
+                            format!("[{name}]")
+                        }
+                    })
+                    .collect();
+                if nodes.len() > max_per_layer {
+                    cells.push(format!("+{} more", nodes.len() - max_per_layer));
+                }
+                cells
+            })
+            .collect();
+        let widths: Vec<usize> = columns
+            .iter()
+            .zip(&cells)
+            .map(|((title, _), cells)| {
+                cells
+                    .iter()
+                    .map(|c| c.width())
+                    .chain([title.width()])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let rows = cells.iter().map(Vec::len).max().unwrap_or(0);
+
+        let mut lines = Vec::with_capacity(rows + 1);
+        let header: Vec<String> = columns
+            .iter()
+            .zip(&widths)
+            .map(|((title, _), &w)| pad(title, w))
+            .collect();
+        lines.push(header.join("     ").trim_end().to_string());
+        for r in 0..rows {
+            let mut line = String::new();
+            for (c, col) in cells.iter().enumerate() {
+                line.push_str(&pad(col.get(r).map_or("", String::as_str), widths[c]));
+                if c + 1 < cells.len() {
+                    let linked = columns[c]
+                        .1
+                        .get(r)
+                        .filter(|_| r < max_per_layer)
+                        .is_some_and(|&n| {
+                            columns[c + 1]
+                                .1
+                                .iter()
+                                .take(max_per_layer)
+                                .any(|m| self.out[n].contains(m))
+                        });
+                    line.push_str(if linked { " ──▶ " } else { "     " });
+                }
+            }
+            lines.push(line.trim_end().to_string());
+        }
+        lines
+    }
+
+    /// 沿 `adj` 按距离分层，跳过已放置的节点；去掉末尾的空层
+    fn bfs_layers(
+        &self,
+        start: usize,
+        depth: usize,
+        adj: &[Vec<usize>],
+        placed: &mut [bool],
+    ) -> Vec<Vec<usize>> {
+        let mut layers: Vec<Vec<usize>> = Vec::new();
+        let mut frontier = vec![start];
+        for _ in 0..depth {
+            let mut next = Vec::new();
+            for &u in &frontier {
+                for &v in &adj[u] {
+                    if !placed[v] {
+                        placed[v] = true;
+                        next.push(v);
+                    }
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            next.sort_unstable();
+            layers.push(next.clone());
+            frontier = next;
+        }
+        layers
+    }
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    if s.width() <= max {
+        return s.to_string();
+    }
+    let mut out = String::new();
+    for c in s.chars() {
+        if out.width() + unicode_width::UnicodeWidthChar::width(c).unwrap_or(0) >= max {
+            break;
+        }
+        out.push(c);
+    }
+    out.push('…');
+    out
+}
+
+fn pad(s: &str, width: usize) -> String {
+    format!("{s}{}", " ".repeat(width.saturating_sub(s.width())))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Pane {
+    Nodes,
+    Out,
+    In,
+}
+
+struct App<'a> {
+    model: &'a Model,
+    source: String,
+    /// 列表中显示的节点（按过滤条件）
+    visible: Vec<usize>,
+    nodes_state: ListState,
+    out_state: ListState,
+    in_state: ListState,
+    selected: usize,
+    pane: Pane,
+    /// 沿边跳转前选中的节点，用于回退
+    history: Vec<usize>,
+    filter: String,
+    filtering: bool,
+    cycles_only: bool,
+    /// 用 `p` 选中的路径起点
+    mark: Option<usize>,
+    path: Vec<usize>,
+    message: String,
+}
+
+impl<'a> App<'a> {
+    fn new(model: &'a Model, source: &str) -> Self {
+        let mut app = Self {
+            model,
+            source: source.to_string(),
+            visible: Vec::new(),
+            nodes_state: ListState::default(),
+            out_state: ListState::default(),
+            in_state: ListState::default(),
+            selected: 0,
+            pane: Pane::Nodes,
+            history: Vec::new(),
+            filter: String::new(),
+            filtering: false,
+            cycles_only: false,
+            mark: None,
+            path: Vec::new(),
+            message: String::new(),
+        };
+        app.refresh_visible();
+        if let Some(&first) = app.visible.first() {
+            app.select(first);
+        }
+        app
+    }
+
+    fn refresh_visible(&mut self) {
+        let filter = self.filter.to_lowercase();
+        self.visible = (0..self.model.ids.len())
+            .filter(|&i| !self.cycles_only || self.model.scc[i].is_some())
+            .filter(|&i| filter.is_empty() || self.model.ids[i].to_lowercase().contains(&filter))
+            .collect();
+        match self.visible.iter().position(|&i| i == self.selected) {
+            Some(pos) => self.nodes_state.select(Some(pos)),
+            None if self.visible.is_empty() => self.nodes_state.select(None),
+            None => {
+                self.nodes_state.select(Some(0));
+                self.select(self.visible[0]);
+            }
+        }
+    }
+
+    fn select(&mut self, node: usize) {
+        self.selected = node;
+        self.out_state
+            .select((!self.model.out[node].is_empty()).then_some(0));
+        self.in_state
+            .select((!self.model.inc[node].is_empty()).then_some(0));
+        if let Some(pos) = self.visible.iter().position(|&i| i == node) {
+            self.nodes_state.select(Some(pos));
+        }
+    }
+
+    /// 跳到另一个节点；它被过滤掉时清除过滤条件
+    fn jump(&mut self, node: usize) {
+        self.history.push(self.selected);
+        if !self.visible.contains(&node) {
+            self.filter.clear();
+            self.cycles_only = false;
+            self.selected = node;
+            self.refresh_visible();
+        }
+        self.select(node);
+    }
+
+    fn neighbors(&self, pane: Pane) -> &[usize] {
+        match pane {
+            Pane::Out => &self.model.out[self.selected],
+            Pane::In => &self.model.inc[self.selected],
+            Pane::Nodes => &self.visible,
+        }
+    }
+
+    fn move_cursor(&mut self, delta: isize) {
+        let len = self.neighbors(self.pane).len();
+        if len == 0 {
+            return;
+        }
+        let state = match self.pane {
+            Pane::Nodes => &mut self.nodes_state,
+            Pane::Out => &mut self.out_state,
+            Pane::In => &mut self.in_state,
+        };
+        let cur = state.selected().unwrap_or(0) as isize;
+        let next = (cur + delta).clamp(0, len as isize - 1) as usize;
+        state.select(Some(next));
+        if self.pane == Pane::Nodes {
+            self.select(self.visible[next]);
+        }
+    }
+
+    /// 处理一次按键，返回 true 表示退出
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return true;
+        }
+        if self.filtering {
+            match key.code {
+                KeyCode::Enter => self.filtering = false,
+                KeyCode::Esc => {
+                    self.filtering = false;
+                    self.filter.clear();
+                }
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                }
+                KeyCode::Char(c) => self.filter.push(c),
+                _ => return false,
+            }
+            self.refresh_visible();
+            return false;
+        }
+        self.message.clear();
+        match key.code {
+            KeyCode::Char('q') => return true,
+            KeyCode::Esc if self.filter.is_empty() && self.mark.is_none() => return true,
+            KeyCode::Esc => {
+                self.mark = None;
+                self.filter.clear();
+                self.refresh_visible();
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.move_cursor(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_cursor(1),
+            KeyCode::PageUp => self.move_cursor(-10),
+            KeyCode::PageDown => self.move_cursor(10),
+            KeyCode::Home | KeyCode::Char('g') => self.move_cursor(isize::MIN / 2),
+            KeyCode::End | KeyCode::Char('G') => self.move_cursor(isize::MAX / 2),
+            KeyCode::Tab => {
+                self.pane = match self.pane {
+                    Pane::Nodes => Pane::Out,
+                    Pane::Out => Pane::In,
+                    Pane::In => Pane::Nodes,
+                }
+            }
+            KeyCode::BackTab => {
+                self.pane = match self.pane {
+                    Pane::Nodes => Pane::In,
+                    Pane::Out => Pane::Nodes,
+                    Pane::In => Pane::Out,
+                }
+            }
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => {
+                let state = match self.pane {
+                    Pane::Nodes => {
+                        self.pane = Pane::Out;
+                        return false;
+                    }
+                    Pane::Out => &self.out_state,
+                    Pane::In => &self.in_state,
+                };
+                if let Some(&target) = state
+                    .selected()
+                    .and_then(|i| self.neighbors(self.pane).get(i))
+                {
+                    self.jump(target);
+                }
+            }
+            KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => {
+                if let Some(prev) = self.history.pop() {
+                    if !self.visible.contains(&prev) {
+                        self.filter.clear();
+                        self.cycles_only = false;
+                        self.selected = prev;
+                        self.refresh_visible();
+                    }
+                    self.select(prev);
+                }
+            }
+            KeyCode::Char('/') => {
+                self.filtering = true;
+                self.pane = Pane::Nodes;
+            }
+            KeyCode::Char('c') => {
+                self.cycles_only = !self.cycles_only;
+                self.refresh_visible();
+                if self.cycles_only && self.visible.is_empty() {
+                    self.message = "no cycles in this graph".to_string();
+                }
+            }
+            KeyCode::Char('s') => match self.model.scc[self.selected] {
+                Some(id) => {
+                    let members: Vec<usize> = (0..self.model.ids.len())
+                        .filter(|&i| self.model.scc[i] == Some(id))
+                        .collect();
+                    let pos = members
+                        .iter()
+                        .position(|&i| i == self.selected)
+                        .unwrap_or(0);
+                    self.jump(members[(pos + 1) % members.len()]);
+                }
+                None => self.message = "node is not in a strongly connected component".to_string(),
+            },
+            KeyCode::Char('p') => match self.mark.take() {
+                None => {
+                    self.mark = Some(self.selected);
+                    self.path.clear();
+                    self.message = format!(
+                        "path from {}: select the target and press p",
+                        self.model.ids[self.selected]
+                    );
+                }
+                Some(from) => match self.model.path(from, self.selected) {
+                    Some(path) => self.path = path,
+                    None => {
+                        self.path.clear();
+                        self.message = format!(
+                            "no path from {} to {}",
+                            self.model.ids[from], self.model.ids[self.selected]
+                        );
+                    }
+                },
+            },
+            KeyCode::Char('x') => {
+                self.mark = None;
+                self.path.clear();
+            }
+            _ => {}
+        }
+        false
+    }
+
+    fn node_style(&self, node: usize) -> Style {
+        if self.path.contains(&node) {
+            Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD)
+        } else if self.model.on_cycle(node) {
+            Style::default().fg(Color::Red)
+        } else if let Some(id) = self.model.scc[node] {
+            Style::default().fg(SCC_COLORS[id % SCC_COLORS.len()])
+        } else {
+            Style::default()
+        }
+    }
+
+    fn node_line(&self, node: usize, name_width: usize) -> Line<'static> {
+        let m = self.model;
+        let marker = if self.mark == Some(node) {
+            "▸ "
+        } else if m.on_cycle(node) {
+            "↻ "
+        } else if m.scc[node].is_some() {
+            "● "
+        } else {
+            "  "
+        };
+        let mut spans = vec![
+            Span::styled(marker, self.node_style(node)),
+            Span::styled(pad(&m.ids[node], name_width), self.node_style(node)),
+            Span::styled(
+                format!(" {:>4} {:>4}", m.inc[node].len(), m.out[node].len()),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ];
+        if let Some(id) = m.scc[node] {
+            spans.push(Span::styled(
+                format!("  {}#{}", if m.undirected { "cc" } else { "scc" }, id + 1),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        Line::from(spans)
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Min(5),
+                Constraint::Length(2),
+            ])
+            .split(frame.area());
+        let body = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+            .split(rows[1]);
+        let right = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+            .split(body[1]);
+        let neighbors = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(right[0]);
+
+        let m = self.model;
+        let cycle = if m.cycle.is_empty() {
+            "none".to_string()
+        } else {
+            m.cycle
+                .iter()
+                .map(|&i| m.ids[i].as_str())
+                .collect::<Vec<_>>()
+                .join(" -> ")
+        };
+        let header = format!(
+            " {}  nodes={} edges={} {}  {}={}  cycle: {}",
+            self.source,
+            m.ids.len(),
+            m.edges,
+            if m.undirected {
+                "undirected"
+            } else {
+                "directed"
+            },
+            if m.undirected { "components" } else { "sccs" },
+            m.scc_count,
+            cycle
+        );
+        frame.render_widget(
+            Paragraph::new(header).style(Style::default().add_modifier(Modifier::BOLD)),
+            rows[0],
+        );
+
+        self.draw_nodes(frame, body[0]);
+        self.draw_neighbors(frame, neighbors[0], Pane::Out);
+        self.draw_neighbors(frame, neighbors[1], Pane::In);
+
+        let layout: Vec<Line> = m
+            .layered_layout(self.selected, LAYOUT_DEPTH, LAYOUT_MAX_PER_LAYER)
+            .into_iter()
+            .map(Line::from)
+            .collect();
+        frame.render_widget(
+            Paragraph::new(layout).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" neighborhood "),
+            ),
+            right[1],
+        );
+
+        let status = if self.filtering {
+            format!(" filter: {}▏", self.filter)
+        } else if !self.message.is_empty() {
+            format!(" {}", self.message)
+        } else if !self.path.is_empty() {
+            format!(
+                " path ({} hops): {}",
+                self.path.len() - 1,
+                self.path
+                    .iter()
+                    .map(|&i| m.ids[i].as_str())
+                    .collect::<Vec<_>>()
+                    .join(" -> ")
+            )
+        } else if !self.filter.is_empty() {
+            format!(" filter: {}", self.filter)
+        } else {
+            String::new()
+        };
+        let help = " ↑↓ move  Tab pane  Enter follow edge  ← back  / filter  c cycles only  s next in scc  p path  x clear  q quit";
+        frame.render_widget(
+            Paragraph::new(vec![
+                Line::from(Span::styled(status, Style::default().fg(Color::Green))),
+                Line::from(Span::styled(help, Style::default().fg(Color::DarkGray))),
+            ]),
+            rows[2],
+        );
+    }
+
+    fn pane_block(&self, pane: Pane, title: String) -> Block<'static> {
+        let style = if self.pane == pane {
+            Style::default().fg(Color::Cyan)
+        } else {
+            Style::default()
+        };
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(style)
+            .title(title)
+    }
+
+    fn draw_nodes(&mut self, frame: &mut Frame, area: Rect) {
+        let name_width = self
+            .visible
+            .iter()
+            .map(|&i| self.model.ids[i].width())
+            .max()
+            .unwrap_or(0)
+            .min(area.width.saturating_sub(24) as usize);
+        let items: Vec<ListItem> = self
+            .visible
+            .iter()
+            .map(|&i| ListItem::new(self.node_line(i, name_width)))
+            .collect();
+        let title = format!(
+            " nodes {}/{}  (in out) ",
+            self.visible.len(),
+            self.model.ids.len()
+        );
+        let list = List::new(items)
+            .block(self.pane_block(Pane::Nodes, title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, area, &mut self.nodes_state);
+    }
+
+    fn draw_neighbors(&mut self, frame: &mut Frame, area: Rect, pane: Pane) {
+        let nodes = self.neighbors(pane).to_vec();
+        let items: Vec<ListItem> = nodes
+            .iter()
+            .map(|&i| {
+                let arrow = if pane == Pane::Out { "→ " } else { "← " };
+                ListItem::new(Line::from(vec![
+                    Span::raw(arrow),
+                    Span::styled(self.model.ids[i].clone(), self.node_style(i)),
+                ]))
+            })
+            .collect();
+        let title = match pane {
+            Pane::Out => format!(" out {} ", nodes.len()),
+            _ => format!(" in {} ", nodes.len()),
+        };
+        let list = List::new(items)
+            .block(self.pane_block(pane, title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let state = if pane == Pane::Out {
+            &mut self.out_state
+        } else {
+            &mut self.in_state
+        };
+        frame.render_stateful_widget(list, area, state);
+    }
+}
+
+/// 进入备用屏幕运行浏览器，退出时恢复终端
+pub fn run(model: &Model, source: &str) -> io::Result<()> {
+    if model.ids.is_empty() {
+        return Err(io::Error::other("graph has no nodes"));
+    }
+    let mut app = App::new(model, source);
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    let result = event_loop(&mut app);
+    let _ = disable_raw_mode();
+    let _ = execute!(io::stdout(), LeaveAlternateScreen);
+    result
+}
+
+fn event_loop(app: &mut App) -> io::Result<()> {
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+    loop {
+        terminal.draw(|frame| app.draw(frame))?;
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+            && app.handle_key(key)
+        {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(&str, &str)]) -> GraphData {
+        let mut g = GraphData::default();
+        for (from, to) in edges {
+            g.add_edge(from, to, Vec::new(), false).unwrap();
+        }
+        g
+    }
+
+    fn idx(m: &Model, id: &str) -> usize {
+        m.ids.iter().position(|i| i == id).unwrap()
+    }
+
+    #[test]
+    fn model_tracks_degrees_components_and_paths() {
+        let g = graph(&[
+            ("a", "b"),
+            ("b", "c"),
+            ("c", "a"),
+            ("c", "d"),
+            ("d", "d"),
+            ("e", "d"),
+        ]);
+        let m = Model::new(&g, false);
+        assert_eq!(m.ids, vec!["a", "b", "c", "d", "e"]);
+        let (c, d) = (idx(&m, "c"), idx(&m, "d"));
+        assert_eq!((m.inc[c].len(), m.out[c].len()), (1, 2));
+        assert_eq!(m.scc_count, 2);
+        assert_eq!(m.scc[0], m.scc[1]);
+        assert_ne!(m.scc[d], None);
+        assert_eq!(m.scc[idx(&m, "e")], None);
+        assert_eq!(m.cycle.first(), m.cycle.last());
+        let names = |p: Vec<usize>| p.iter().map(|&i| m.ids[i].clone()).collect::<Vec<_>>();
+        assert_eq!(names(m.path(idx(&m, "b"), d).unwrap()), vec!["b", "c", "d"]);
+        assert_eq!(m.path(d, idx(&m, "a")), None);
+
+        let m = Model::new(&g, true);
+        assert_eq!(m.scc_count, 1);
+        assert!(m.path(d, idx(&m, "a")).is_some());
+    }
+
+    #[test]
+    fn renders_layered_neighborhood() {
+        let g = graph(&[
+            ("x", "a"),
+            ("a", "b"),
+            ("y", "b"),
+            ("b", "c"),
+            ("c", "d"),
+            ("b", "e"),
+        ]);
+        let m = Model::new(&g, false);
+        let lines = m.layered_layout(idx(&m, "b"), 2, 8);
+        assert_eq!(
+            lines,
+            vec![
+                "←2      ←1      •         →1      →2",
+                "[x] ──▶ [a] ──▶ [[b]] ──▶ [c] ──▶ [d]",
+                "        [y] ──▶           [e]",
+            ]
+        );
+        let lines = m.layered_layout(idx(&m, "b"), 1, 1);
+        assert_eq!(lines[2], "+1 more               +1 more");
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn keys_follow_edges_filter_and_pick_paths() {
+        let g = graph(&[("a", "b"), ("b", "c"), ("c", "a"), ("c", "d")]);
+        let m = Model::new(&g, false);
+        let mut app = App::new(&m, "test");
+        assert_eq!(app.selected, 0);
+
+        // a → b → c，然后回退到 b
+        app.handle_key(key(KeyCode::Enter));
+        assert_eq!(app.pane, Pane::Out);
+        app.handle_key(key(KeyCode::Enter));
+        app.handle_key(key(KeyCode::Enter));
+        assert_eq!(m.ids[app.selected], "c");
+        app.handle_key(key(KeyCode::Left));
+        assert_eq!(m.ids[app.selected], "b");
+
+        // 过滤后只剩 d，路径 b → d
+        app.handle_key(key(KeyCode::Char('p')));
+        app.handle_key(key(KeyCode::Char('/')));
+        app.handle_key(key(KeyCode::Char('d')));
+        app.handle_key(key(KeyCode::Enter));
+        assert_eq!(app.visible, vec![idx(&m, "d")]);
+        assert_eq!(m.ids[app.selected], "d");
+        app.handle_key(key(KeyCode::Char('p')));
+        assert_eq!(app.path.len(), 3);
+
+        app.handle_key(key(KeyCode::Esc));
+        assert_eq!(app.visible.len(), 4);
+        app.handle_key(key(KeyCode::Char('c')));
+        assert_eq!(app.visible.len(), 3);
+        assert!(app.handle_key(key(KeyCode::Char('q'))));
+    }
+
+    #[test]
+    fn draws_into_small_terminals() {
+        let g = graph(&[("a", "b"), ("b", "a"), ("b", "c")]);
+        let m = Model::new(&g, false);
+        let mut app = App::new(&m, "g.txt");
+        for (w, h) in [(100, 30), (20, 6)] {
+            let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(w, h)).unwrap();
+            terminal.draw(|frame| app.draw(frame)).unwrap();
+            let text: String = terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(|c| c.symbol())
+                .collect();
+            assert!(text.contains("g.txt"));
+        }
+    }
+}
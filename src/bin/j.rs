@@ -1,8 +1,14 @@
-use std::path::Path;
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, IsTerminal, Write},
+    path::Path,
+};
 
 use clap::Parser;
+use colored::Colorize;
 use rust_tools::{clipboardw::string_content, jsonw};
 use serde_json::Value;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 #[derive(Parser)]
 #[command(about = "JSON diff/format utilities (go_tools jsondiff compatible subset)")]
@@ -10,12 +16,46 @@ struct Cli {
     #[arg(short = 'f', value_name = "FILE", num_args = 0..=1, default_missing_value = "")]
     format: Option<String>,
 
+    #[arg(
+        short = 'q',
+        value_name = "EXPR",
+        help = "run a JSONPath / jq-style query on FILE, stdin or clipboard"
+    )]
+    query: Option<String>,
+
     #[arg(short = 'o', default_value = "", value_name = "FILE")]
     output: String,
 
-    #[arg(long, default_value_t = false, help = "sort arrays before diff")]
+    #[arg(
+        long = "format",
+        value_name = "FORMAT",
+        default_value = "entries",
+        value_parser = ["entries", "patch", "merge"],
+        help = "diff output: entries (key/old/new), patch (RFC 6902) or merge (RFC 7386)"
+    )]
+    diff_format: String,
+
+    #[arg(
+        long,
+        default_value_t = false,
+        help = "sort arrays before diff (entries only)"
+    )]
     sort: bool,
 
+    #[arg(
+        long = "key",
+        value_name = "PATH",
+        help = "match array elements by identity, e.g. id or items[].sku (repeatable, entries only)"
+    )]
+    keys: Vec<String>,
+
+    #[arg(
+        long,
+        default_value_t = false,
+        help = "print a colored side-by-side diff (implied by --key)"
+    )]
+    side: bool,
+
     #[arg(
         long,
         default_value_t = false,
@@ -40,10 +80,68 @@ struct Cli {
     )]
     oneline: bool,
 
+    #[arg(
+        long,
+        default_value_t = false,
+        help = "pretty-print FILE or stdin in constant memory (compact with --oneline)"
+    )]
+    stream: bool,
+
+    #[arg(
+        long,
+        default_value_t = false,
+        help = "check JSON/NDJSON syntax of FILE or stdin in constant memory"
+    )]
+    check: bool,
+
+    #[arg(
+        long,
+        value_name = "REGEX",
+        help = "search scalar values of FILE or stdin in constant memory"
+    )]
+    grep: Option<String>,
+
+    #[arg(
+        long,
+        default_value_t = false,
+        help = "treat input as NDJSON: -q filters line by line, OLD NEW diffs record by record"
+    )]
+    ndjson: bool,
+
+    #[arg(
+        long,
+        default_value_t = false,
+        help = "infer a draft 2020-12 JSON Schema from sample FILEs or stdin (each record with --ndjson)"
+    )]
+    infer_schema: bool,
+
+    #[arg(
+        long,
+        value_name = "SCHEMA",
+        help = "validate FILE or stdin against SCHEMA, reporting line:column of each violation"
+    )]
+    validate: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        value_parser = ["json", "yaml", "toml"],
+        help = "convert FILE, stdin or clipboard; json keeps comments of JSONC/JSON5 input"
+    )]
+    to: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        value_parser = ["json", "json5", "jsonc", "yaml", "toml"],
+        help = "input format for --to (default: by extension, else detected)"
+    )]
+    from: Option<String>,
+
     #[arg(long, default_value_t = false, help = "print JSON length in clipboard")]
     len: bool,
 
-    #[arg(value_name = "OLD NEW", num_args = 0..=2)]
+    #[arg(value_name = "FILE", num_args = 0..)]
     files: Vec<String>,
 }
 
@@ -63,6 +161,40 @@ fn main() {
         return;
     }
 
+    if let Some(expr) = cli.query.as_deref() {
+        if cli.ndjson {
+            run_ndjson_query(expr, &cli);
+        } else {
+            run_query(expr, &cli);
+        }
+        return;
+    }
+
+    if let Some(to) = cli.to.as_deref() {
+        run_convert(to, &cli);
+        return;
+    }
+
+    if cli.infer_schema {
+        run_infer_schema(&cli);
+        return;
+    }
+
+    if let Some(schema) = cli.validate.as_deref() {
+        run_validate(schema, &cli);
+        return;
+    }
+
+    if cli.stream || cli.check || cli.grep.is_some() {
+        run_stream(&cli);
+        return;
+    }
+
+    if cli.ndjson && cli.files.len() == 2 {
+        run_ndjson_diff(&cli);
+        return;
+    }
+
     if cli.oneline && cli.format.is_none() && !cli.len && cli.files.is_empty() {
         let content = string_content::get_clipboard_content().replace(['\n', '\r'], "");
         println!("{content}");
@@ -89,10 +221,31 @@ fn main() {
         let j = if fname.is_empty() {
             parse_clipboard_json(options)
         } else {
-            expand_nested_json_strings_in_json(
-                jsonw::Json::from_file(fname, options).unwrap(),
-                options,
-            )
+            let text = std::fs::read_to_string(fname).unwrap_or_else(|e| {
+                eprintln!("{fname}: {e}");
+                std::process::exit(1);
+            });
+            match format_commented_file(fname, &text) {
+                Some(Ok((formatted, preview))) => {
+                    let preview = if cli.oneline {
+                        &preview
+                    } else {
+                        formatted.trim_end()
+                    };
+                    println!("{}", preview.chars().take(1024).collect::<String>());
+                    println!("write formatted json to {fname}");
+                    std::fs::write(fname, formatted).unwrap();
+                    return;
+                }
+                Some(Err(e)) => {
+                    eprintln!("{fname}: {e}");
+                    std::process::exit(1);
+                }
+                None => expand_nested_json_strings_in_json(
+                    jsonw::Json::from_str(&text, options).unwrap(),
+                    options,
+                ),
+            }
         };
 
         let mut formatted = j.to_pretty_string();
@@ -116,8 +269,20 @@ fn main() {
         let old = jsonw::Json::from_file(&cli.files[0], options).unwrap();
         let new = jsonw::Json::from_file(&cli.files[1], options).unwrap();
 
-        let diff = jsonw::diff_json(old.value(), new.value(), cli.sort);
-        let diff_value = serde_json::to_value(diff).unwrap_or(Value::Null);
+        let diff_value = match cli.diff_format.as_str() {
+            "patch" => serde_json::to_value(jsonw::diff_patch(old.value(), new.value())),
+            "merge" => Ok(jsonw::diff_merge_patch(old.value(), new.value())),
+            _ => {
+                let keys = parse_keys(&cli.keys);
+                let diff = jsonw::diff_json_by_key(old.value(), new.value(), cli.sort, &keys);
+                if cli.side || !keys.is_empty() {
+                    let width = crossterm::terminal::size().map_or(120, |(w, _)| w as usize);
+                    print!("{}", render_side_by_side(&diff, width));
+                }
+                serde_json::to_value(diff)
+            }
+        }
+        .unwrap_or(Value::Null);
         let diff_json = jsonw::Json::new(diff_value);
 
         if cli.print {
@@ -138,7 +303,395 @@ fn main() {
         return;
     }
 
-    eprintln!("usage: j old.json new.json  |  j -f [file]  |  j --quote [--oneline]  |  j --len");
+    eprintln!(
+        "usage: j old.json new.json [--format entries|patch|merge] [--key id] [--ndjson]  |  j --stream|--check|--grep <re> [file]  |  j -f [file]  |  j -q <expr> [file]  |  j --infer-schema [files]  |  j --validate schema.json [file]  |  j --to json|yaml|toml [file]  |  j --quote [--oneline]  |  j --len"
+    );
+}
+
+fn parse_keys(specs: &[String]) -> Vec<jsonw::ArrayKey> {
+    specs
+        .iter()
+        .map(|spec| {
+            jsonw::ArrayKey::parse(spec).unwrap_or_else(|| {
+                eprintln!("invalid --key: {spec}");
+                std::process::exit(2);
+            })
+        })
+        .collect()
+}
+
+/// 大文件入口：位置参数中的文件或 stdin，不读剪贴板
+fn open_input(file: Option<&String>) -> (String, Box<dyn BufRead>) {
+    match file {
+        Some(fname) => {
+            let f = File::open(fname).unwrap_or_else(|e| {
+                eprintln!("{fname}: {e}");
+                std::process::exit(1);
+            });
+            (
+                fname.clone(),
+                Box::new(BufReader::with_capacity(1 << 20, f)),
+            )
+        }
+        None => ("<stdin>".to_string(), Box::new(io::stdin().lock())),
+    }
+}
+
+fn exit_on_stream_error<T>(name: &str, result: Result<T, jsonw::stream::StreamError>) -> T {
+    result.unwrap_or_else(|e| {
+        eprintln!("{name}: {e}");
+        std::process::exit(1);
+    })
+}
+
+/// `-f FILE` 对 JSONC / JSON5（按扩展名判断，或者不是严格 JSON 的 `.json`）保留注释重新排版，
+/// 返回 `(写回文件的内容, 压缩成一行的预览)`；严格 JSON 返回 None，仍按原来的方式展开嵌套字符串
+fn format_commented_file(fname: &str, text: &str) -> Option<Result<(String, String), String>> {
+    use jsonw::convert::{Format, format_jsonc, parse_json5};
+
+    let format = Format::from_path(fname);
+    if format == Format::Json && serde_json::from_str::<Value>(text).is_ok() {
+        return None;
+    }
+    if !format.is_json_family() {
+        return Some(Err(format!("cannot format {format} with -f, use --to")));
+    }
+    let result = format_jsonc(text, "  ").and_then(|formatted| {
+        let compact = parse_json5(text)?.to_string();
+        Ok((formatted, compact))
+    });
+    Some(result.map_err(|e| e.to_string()))
+}
+
+/// `--stream` / `--check` / `--grep`：基于事件流，内存占用与文件大小无关；
+/// `--check` 和 `--grep` 加 `--ndjson` 时逐行处理，每行必须是一个完整的值
+fn run_stream(cli: &Cli) {
+    let (name, input) = open_input(cli.files.first());
+    let mut out = BufWriter::new(io::stdout().lock());
+
+    if cli.check {
+        let stats = if cli.ndjson {
+            jsonw::ndjson::validate_ndjson(input)
+        } else {
+            jsonw::stream::validate_stream(input)
+        };
+        let stats = exit_on_stream_error(&name, stats);
+        println!(
+            "{name}: ok, {} value(s), {} bytes, max depth {}",
+            stats.values, stats.bytes, stats.max_depth
+        );
+    } else if let Some(pattern) = cli.grep.as_deref() {
+        let re = regex::Regex::new(pattern).unwrap_or_else(|e| {
+            eprintln!("invalid --grep: {e}");
+            std::process::exit(2);
+        });
+        let on_match = |m: jsonw::stream::GrepMatch| {
+            let _ = writeln!(out, "{}:{}: {}", m.line, m.path, m.value);
+        };
+        let found = if cli.ndjson {
+            jsonw::ndjson::grep_ndjson(input, &re, on_match)
+        } else {
+            jsonw::stream::grep_stream(input, &re, on_match)
+        };
+        let found = exit_on_stream_error(&name, found);
+        let _ = out.flush();
+        if found == 0 {
+            std::process::exit(1);
+        }
+    } else {
+        let indent = (!cli.oneline).then_some("  ");
+        exit_on_stream_error(&name, jsonw::stream::format_stream(input, &mut out, indent));
+    }
+}
+
+/// `--ndjson -q`：逐行查询，结果一行一个
+fn run_ndjson_query(expr: &str, cli: &Cli) {
+    let query = jsonw::Query::parse(expr).unwrap_or_else(|e| {
+        eprintln!("invalid query: {e}");
+        std::process::exit(2);
+    });
+    let (name, input) = open_input(cli.files.first());
+    let out = BufWriter::new(io::stdout().lock());
+    let written = exit_on_stream_error(&name, jsonw::ndjson::filter_ndjson(input, out, &query));
+    if written == 0 {
+        std::process::exit(1);
+    }
+}
+
+/// `--ndjson OLD NEW`：逐条记录比较，差异以 NDJSON 写到 `-o` 或 stdout
+fn run_ndjson_diff(cli: &Cli) {
+    let keys = parse_keys(&cli.keys);
+    let (old_name, old) = open_input(cli.files.first());
+    let (new_name, new) = open_input(cli.files.get(1));
+    let mut out: Box<dyn Write> = if cli.output.is_empty() {
+        Box::new(BufWriter::new(io::stdout().lock()))
+    } else {
+        Box::new(BufWriter::new(File::create(&cli.output).unwrap_or_else(
+            |e| {
+                eprintln!("{}: {e}", cli.output);
+                std::process::exit(1);
+            },
+        )))
+    };
+    let result = jsonw::ndjson::diff_ndjson(old, new, cli.sort, &keys, |record, entries| {
+        for d in entries {
+            let line =
+                serde_json::json!({"record": record, "key": d.key, "old": d.old, "new": d.new});
+            let _ = writeln!(out, "{line}");
+        }
+    });
+    let changed = exit_on_stream_error(&format!("{old_name} / {new_name}"), result);
+    let _ = out.flush();
+    eprintln!("{changed} record(s) differ");
+}
+
+/// `--to`：输入依次取位置参数中的文件、管道 stdin、剪贴板，结果写到 `-o` 或 stdout
+///
+/// JSON 系列输入转 JSON 时按原文重新排版，注释保留。
+fn run_convert(to: &str, cli: &Cli) {
+    use jsonw::convert::{Format, format_jsonc, parse_as, to_string_as};
+
+    let (name, text) = if let Some(fname) = cli.files.first() {
+        let text = std::fs::read_to_string(fname).unwrap_or_else(|e| {
+            eprintln!("{fname}: {e}");
+            std::process::exit(1);
+        });
+        (fname.clone(), text)
+    } else if !std::io::stdin().is_terminal() {
+        ("<stdin>".to_string(), read_stdin_all())
+    } else {
+        (
+            "<clipboard>".to_string(),
+            string_content::get_clipboard_content(),
+        )
+    };
+    let from = match (cli.from.as_deref(), cli.files.first()) {
+        (Some(from), _) => from.parse().ok(),
+        (None, Some(fname)) => Some(Format::from_path(fname)),
+        (None, None) => None,
+    };
+    // 没有扩展名可依据时依次尝试 JSON5、TOML、YAML
+    let from = from.unwrap_or_else(|| {
+        [Format::Json5, Format::Toml, Format::Yaml]
+            .into_iter()
+            .find(|f| parse_as(&text, *f).is_ok())
+            .unwrap_or(Format::Json5)
+    });
+    let to: Format = to.parse().unwrap_or(Format::Json);
+
+    let result = if to == Format::Json && from.is_json_family() {
+        format_jsonc(&text, "  ")
+    } else {
+        parse_as(&text, from).and_then(|value| to_string_as(&value, to))
+    };
+    let out = result.unwrap_or_else(|e| {
+        eprintln!("{name}: {e}");
+        std::process::exit(1);
+    });
+
+    if cli.output.is_empty() {
+        print!("{out}");
+    } else {
+        std::fs::write(&cli.output, out).unwrap();
+        eprintln!("write to {}", cli.output);
+    }
+}
+
+/// `--infer-schema`：每个文件是一个样本，没有文件时读 stdin；`--ndjson` 时每条记录是一个样本
+fn run_infer_schema(cli: &Cli) {
+    let options = jsonw::ParseOptions::default();
+    let mut samples = Vec::new();
+    if cli.ndjson {
+        let inputs: Vec<Option<&String>> = if cli.files.is_empty() {
+            vec![None]
+        } else {
+            cli.files.iter().map(Some).collect()
+        };
+        for file in inputs {
+            let (name, input) = open_input(file);
+            for record in jsonw::ndjson::NdjsonReader::new(input) {
+                samples.push(exit_on_stream_error(&name, record).value);
+            }
+        }
+    } else if cli.files.is_empty() {
+        let j = jsonw::Json::from_str(&read_stdin_all(), options).unwrap_or_else(|e| {
+            eprintln!("Failed to parse stdin as JSON: {e}");
+            std::process::exit(1);
+        });
+        samples.push(j.value().clone());
+    } else {
+        for fname in &cli.files {
+            let j = jsonw::Json::from_file(fname, options).unwrap_or_else(|e| {
+                eprintln!("Failed to parse {fname} as JSON: {e}");
+                std::process::exit(1);
+            });
+            samples.push(j.value().clone());
+        }
+    }
+
+    let schema = jsonw::Json::new(jsonw::infer_schema(&samples));
+    if cli.output.is_empty() {
+        print!("{}", schema.to_pretty_string());
+    } else {
+        schema.to_file(&cli.output, true).unwrap();
+        eprintln!("write to {}", cli.output);
+    }
+}
+
+/// `--validate SCHEMA`：逐条输出 `文件:行:列: 指针: 信息`，有违规时退出码为 1
+fn run_validate(schema_file: &str, cli: &Cli) {
+    let options = jsonw::ParseOptions::default();
+    let schema = jsonw::Json::from_file(schema_file, options).unwrap_or_else(|e| {
+        eprintln!("Failed to parse {schema_file} as JSON: {e}");
+        std::process::exit(2);
+    });
+    let validator = jsonw::SchemaValidator::new(schema.value()).unwrap_or_else(|e| {
+        eprintln!("{schema_file}: {e}");
+        std::process::exit(2);
+    });
+
+    let (name, text) = match cli.files.first() {
+        Some(fname) => (
+            fname.clone(),
+            std::fs::read_to_string(fname).unwrap_or_else(|e| {
+                eprintln!("{fname}: {e}");
+                std::process::exit(2);
+            }),
+        ),
+        None => ("<stdin>".to_string(), read_stdin_all()),
+    };
+    let violations = validator.validate_str(&text, options).unwrap_or_else(|e| {
+        eprintln!("{name}: {e}");
+        std::process::exit(2);
+    });
+
+    if violations.is_empty() {
+        println!("{name}: ok");
+        return;
+    }
+    for v in &violations {
+        let pointer = if v.pointer.is_empty() {
+            "(root)"
+        } else {
+            &v.pointer
+        };
+        println!("{name}:{}:{}: {pointer}: {}", v.line, v.column, v.message);
+    }
+    eprintln!("{} violation(s)", violations.len());
+    std::process::exit(1);
+}
+
+/// `-q`：输入依次取位置参数中的文件、管道 stdin、剪贴板，每个结果单独输出
+fn run_query(expr: &str, cli: &Cli) {
+    let query = jsonw::Query::parse(expr).unwrap_or_else(|e| {
+        eprintln!("invalid query: {e}");
+        std::process::exit(2);
+    });
+
+    let options = jsonw::ParseOptions::default();
+    let j = if let Some(fname) = cli.files.first() {
+        let j = jsonw::Json::from_file(fname, options).unwrap_or_else(|e| {
+            eprintln!("Failed to parse {fname} as JSON: {e}");
+            std::process::exit(1);
+        });
+        expand_nested_json_strings_in_json(j, options)
+    } else if !std::io::stdin().is_terminal() {
+        let j = jsonw::Json::from_str(&read_stdin_all(), options).unwrap_or_else(|e| {
+            eprintln!("Failed to parse stdin as JSON: {e}");
+            std::process::exit(1);
+        });
+        expand_nested_json_strings_in_json(j, options)
+    } else {
+        parse_clipboard_json(options)
+    };
+
+    let results = query.evaluate(j.raw_value());
+    if results.is_empty() {
+        eprintln!("no match");
+        std::process::exit(1);
+    }
+    for value in &results {
+        let result = jsonw::Json::new(value.clone());
+        if cli.oneline {
+            println!("{}", result.to_compact_string());
+        } else {
+            print!("{}", result.to_pretty_string());
+        }
+    }
+
+    if !cli.output.is_empty() {
+        let out = match <[Value; 1]>::try_from(results) {
+            Ok([single]) => single,
+            Err(results) => Value::Array(results),
+        };
+        jsonw::Json::new(out).to_file(&cli.output, true).unwrap();
+        eprintln!("write to {}", cli.output);
+    }
+}
+
+/// 左右对照渲染差异：左列旧值、右列新值，标题按新增/删除/修改着色，末尾附统计
+fn render_side_by_side(diff: &[jsonw::DiffEntry], width: usize) -> String {
+    let col = (width.saturating_sub(3) / 2).max(10);
+    let value_lines = |v: &Value, absent: bool| -> Vec<String> {
+        if absent {
+            return Vec::new();
+        }
+        serde_json::to_string_pretty(v)
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    };
+
+    let mut out = String::new();
+    let (mut added, mut removed, mut modified) = (0, 0, 0);
+    for d in diff {
+        let kind = d.kind();
+        let header = match kind {
+            jsonw::DiffKind::Added => {
+                added += 1;
+                format!("+ {}", d.key).green()
+            }
+            jsonw::DiffKind::Removed => {
+                removed += 1;
+                format!("- {}", d.key).red()
+            }
+            jsonw::DiffKind::Modified => {
+                modified += 1;
+                format!("~ {}", d.key).yellow()
+            }
+        };
+        out.push_str(&format!("{}\n", header.bold()));
+        let left = value_lines(&d.old, kind == jsonw::DiffKind::Added);
+        let right = value_lines(&d.new, kind == jsonw::DiffKind::Removed);
+        for i in 0..left.len().max(right.len()) {
+            let l = fit_width(left.get(i).map_or("", String::as_str), col);
+            let r = fit_width(right.get(i).map_or("", String::as_str), col);
+            out.push_str(&format!("{} │ {}\n", l.red(), r.green()));
+        }
+    }
+    out.push_str(&format!(
+        "{added} added, {removed} removed, {modified} modified\n"
+    ));
+    out
+}
+
+/// 按显示宽度截断（超出部分以 `…` 结尾）并补齐空格
+fn fit_width(s: &str, width: usize) -> String {
+    let mut out = String::new();
+    let mut used = 0;
+    for ch in s.chars() {
+        let w = UnicodeWidthChar::width(ch).unwrap_or(0);
+        if used + w > width.saturating_sub(1) && s.width() > width {
+            out.push('…');
+            used += 1;
+            break;
+        }
+        out.push(ch);
+        used += w;
+    }
+    out.push_str(&" ".repeat(width.saturating_sub(used)));
+    out
 }
 
 fn base_no_ext(path: &str) -> String {
@@ -386,6 +939,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn renders_side_by_side_columns() {
+        colored::control::set_override(false);
+        let old = serde_json::json!({"items": [{"id": 1, "name": "苹果"}, {"id": 2}]});
+        let new = serde_json::json!({"items": [{"id": 1, "name": "香蕉"}, {"id": 3}]});
+        let keys = [jsonw::ArrayKey::parse("items[].id").unwrap()];
+        let diff = jsonw::diff_json_by_key(&old, &new, false, &keys);
+
+        let out = render_side_by_side(&diff, 43);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "~ items[id=1].name");
+        assert_eq!(lines[1], format!("{:<18} │ {:<18}", "\"苹果\"", "\"香蕉\""));
+        assert_eq!(lines[2], "- items[id=2]");
+        assert!(lines[3].starts_with("{                    │ "));
+        assert_eq!(lines.last(), Some(&"1 added, 1 removed, 1 modified"));
+        assert!(
+            lines
+                .iter()
+                .filter(|l| l.contains('│'))
+                .all(|l| l.width() == 43)
+        );
+    }
+
+    #[test]
+    fn format_keeps_comments_of_jsonc_and_json5_files() {
+        let text = "{\n  // 端口\n  \"port\": 80, /* 默认 */\n  \"hosts\": [\"a\",],\n}\n";
+        let (formatted, preview) = format_commented_file("config.jsonc", text)
+            .unwrap()
+            .unwrap();
+        assert!(formatted.contains("// 端口") && formatted.contains("/* 默认 */"));
+        assert_eq!(preview, r#"{"port":80,"hosts":["a"]}"#);
+        // 带尾逗号的 .json 不再 panic，严格 JSON 仍走原来的路径
+        assert!(
+            format_commented_file("a.json", r#"{"a": 1,}"#)
+                .unwrap()
+                .is_ok()
+        );
+        assert!(format_commented_file("a.json", r#"{"a": 1}"#).is_none());
+        assert!(format_commented_file("a.json", "{").unwrap().is_err());
+    }
+
+    #[test]
+    fn fit_width_truncates_by_display_width() {
+        assert_eq!(fit_width("abc", 5), "abc  ");
+        assert_eq!(fit_width("abcdef", 5), "abcd…");
+        assert_eq!(fit_width("中文字符", 5), "中文…");
+        assert_eq!(fit_width("中文", 4), "中文");
+    }
+
     #[test]
     fn keeps_non_json_strings_unchanged() {
         let value = serde_json::json!({
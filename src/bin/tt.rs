@@ -1,12 +1,31 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use chrono::{DateTime, Local, LocalResult, NaiveDateTime, TimeZone};
-use clap::{ArgAction, Parser};
+use chrono::{DateTime, Local, LocalResult, NaiveDateTime, TimeZone, Utc};
+use clap::{ArgAction, CommandFactory, Parser};
+
+#[path = "tt/parse.rs"]
+mod parse;
+
+use parse::{Ctx, Kind, Value, Zone};
 
 #[derive(Parser, Debug)]
 #[command(
     about = "Convert unix timestamps and datetimes (go_tools tt compatible)",
-    after_help = "usage: tt 1603372219690"
+    after_help = "usage: tt 1603372219690
+
+inputs:
+  unix seconds/millis/micros/nanos, 1603372219.69
+  2020-10-22T13:10:19Z, 2020-10-22 21:10:19, 2020-10-22 (RFC 3339 / ISO 8601)
+  Thu, 22 Oct 2020 13:10:19 GMT (RFC 2822 / HTTP-date, RFC 850 and asctime too)
+  [22/Oct/2020:21:10:19 +0800] (common log format)
+  now, yesterday 9am, next monday 14:30, 3d ago, in 2 hours
+
+arithmetic:
+  tt now + 3d4h
+  tt 2026-01-01 - 2025-10-01
+  tt --tz Asia/Shanghai,UTC yesterday 9am
+
+times without a zone are read in the first --tz zone (local time by default)"
 )]
 struct Cli {
     #[arg(short = 'd', action = ArgAction::SetTrue, help = "show current date in date format")]
@@ -15,8 +34,19 @@ struct Cli {
     #[arg(long = "dt", action = ArgAction::SetTrue, help = "show current date in datetime format")]
     dt: bool,
 
-    #[arg(value_name = "INPUT")]
-    input: Option<String>,
+    #[arg(
+        long,
+        value_name = "ZONES",
+        value_delimiter = ',',
+        help = "print the result in these zones, e.g. Asia/Shanghai,UTC,+05:30,local"
+    )]
+    tz: Vec<String>,
+
+    #[arg(long, value_name = "TIME", help = "use TIME as the current time")]
+    now: Option<String>,
+
+    #[arg(value_name = "INPUT", allow_hyphen_values = true)]
+    input: Vec<String>,
 }
 
 fn normalize_args(args: impl Iterator<Item = String>) -> Vec<String> {
@@ -32,6 +62,50 @@ fn normalize_args(args: impl Iterator<Item = String>) -> Vec<String> {
     .collect()
 }
 
+/// 把已知选项（及其值）移到前面，其余参数放在 `--` 之后作为输入
+///
+/// 输入里有 `-`、`-90m` 这样以连字符开头的词，clap 一旦开始收集输入就不再识别选项，
+/// 所以先在这里分开，选项写在输入前后都可以。
+fn hoist_options(argv: Vec<String>) -> Vec<String> {
+    let mut cmd = Cli::command();
+    cmd.build();
+    let takes_value = |arg: &str| -> Option<bool> {
+        let found = if let Some(long) = arg.strip_prefix("--") {
+            let name = long.split_once('=').map_or(long, |(name, _)| name);
+            cmd.get_arguments().find(|a| a.get_long() == Some(name))
+        } else {
+            let short = arg.strip_prefix('-')?.chars().next()?;
+            cmd.get_arguments().find(|a| a.get_short() == Some(short))
+        }?;
+        let inline = arg.contains('=') || (!arg.starts_with("--") && arg.len() > 2);
+        Some(found.get_action().takes_values() && !inline)
+    };
+
+    let mut argv = argv.into_iter();
+    let mut options: Vec<String> = argv.next().into_iter().collect();
+    let mut input = Vec::new();
+    while let Some(arg) = argv.next() {
+        if arg == "--" {
+            input.extend(argv.by_ref());
+            break;
+        }
+        match takes_value(&arg) {
+            Some(needs_value) => {
+                options.push(arg);
+                if needs_value {
+                    options.extend(argv.next());
+                }
+            }
+            None => input.push(arg),
+        }
+    }
+    if !input.is_empty() {
+        options.push("--".to_string());
+        options.extend(input);
+    }
+    options
+}
+
 fn now_millis() -> u128 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -40,7 +114,7 @@ fn now_millis() -> u128 {
 }
 
 fn format_go_like(dt: DateTime<Local>) -> String {
-    dt.format("%Y-%m-%d %H:%M:%S %z %Z").to_string()
+    dt.format(parse::GO_LIKE).to_string()
 }
 
 fn parse_datetime_local(input: &str) -> Result<DateTime<Local>, String> {
@@ -53,61 +127,94 @@ fn parse_datetime_local(input: &str) -> Result<DateTime<Local>, String> {
     }
 }
 
-fn parse_unix_like(input: i64) -> DateTime<Local> {
-    let threshold = Local
+/// 按数量级依次当作秒、毫秒、微秒、纳秒，取第一个不晚于 2500 年的解释
+fn parse_unix_like(input: i64) -> DateTime<Utc> {
+    let threshold = Utc
         .with_ymd_and_hms(2500, 1, 1, 0, 0, 0)
         .single()
-        .unwrap_or_else(|| Local.timestamp_opt(0, 0).single().unwrap());
+        .unwrap_or(DateTime::UNIX_EPOCH);
 
-    let as_secs = Local.timestamp_opt(input, 0).single();
-    if let Some(dt) = as_secs
-        && dt <= threshold
-    {
-        return dt;
+    for per_sec in [1i64, 1_000, 1_000_000, 1_000_000_000] {
+        let secs = input.div_euclid(per_sec);
+        let nanos = (input.rem_euclid(per_sec) * (1_000_000_000 / per_sec)) as u32;
+        if let Some(dt) = Utc.timestamp_opt(secs, nanos).single()
+            && dt <= threshold
+        {
+            return dt;
+        }
     }
+    DateTime::UNIX_EPOCH
+}
 
-    let secs = input / 1000;
-    Local
-        .timestamp_opt(secs, 0)
-        .single()
-        .unwrap_or_else(|| Local.timestamp_opt(0, 0).single().unwrap())
+fn fail(message: impl std::fmt::Display) -> ! {
+    eprintln!("{message}");
+    std::process::exit(1);
+}
+
+/// 每个时区一行，时区名左对齐
+fn print_zones(zones: &[Zone], t: DateTime<Utc>, fmt: &str) {
+    let names: Vec<String> = zones.iter().map(Zone::name).collect();
+    let width = names.iter().map(String::len).max().unwrap_or(0);
+    for (zone, name) in zones.iter().zip(&names) {
+        println!("{name:<width$}  {}", zone.format(t, fmt));
+    }
 }
 
 fn main() {
-    let cli = Cli::parse_from(normalize_args(std::env::args()));
+    let cli = Cli::parse_from(hoist_options(normalize_args(std::env::args())));
 
-    if cli.d {
-        println!("{}", Local::now().format("%Y-%m-%d"));
-        return;
+    let zones: Vec<Zone> = cli
+        .tz
+        .iter()
+        .filter(|name| !name.trim().is_empty())
+        .map(|name| Zone::from_name(name).unwrap_or_else(|e| fail(e)))
+        .collect();
+    let mut ctx = Ctx {
+        now: Utc::now(),
+        zone: zones.first().copied().unwrap_or(Zone::Local),
+    };
+    if let Some(now) = &cli.now {
+        match parse::evaluate(now, &ctx) {
+            Ok((Value::Time(t), _)) => ctx.now = t,
+            Ok((Value::Span(_), _)) => fail(format!("--now needs a time, got {now:?}")),
+            Err(e) => fail(e),
+        }
     }
+    let real_now = cli.now.is_none();
 
-    if cli.dt {
-        println!("{}", Local::now().format("%Y-%m-%d %H:%M:%S"));
+    if cli.d || cli.dt {
+        let fmt = if cli.d {
+            "%Y-%m-%d"
+        } else {
+            "%Y-%m-%d %H:%M:%S"
+        };
+        if zones.is_empty() {
+            println!("{}", ctx.now.with_timezone(&Local).format(fmt));
+        } else {
+            print_zones(&zones, ctx.now, fmt);
+        }
         return;
     }
 
-    let Some(input) = cli
-        .input
-        .as_deref()
-        .map(str::trim)
-        .filter(|s| !s.is_empty())
-    else {
-        println!("{} ms", now_millis());
+    let input = cli.input.join(" ");
+    let input = input.trim();
+    if input.is_empty() {
+        if !zones.is_empty() {
+            print_zones(&zones, ctx.now, parse::GO_LIKE);
+        } else if real_now {
+            println!("{} ms", now_millis());
+        } else {
+            println!("{} ms", ctx.now.timestamp_millis());
+        }
         return;
-    };
+    }
 
-    match input.parse::<i64>() {
-        Ok(unix) => {
-            let dt = parse_unix_like(unix);
-            println!("{}", format_go_like(dt));
-        }
-        Err(_) => match parse_datetime_local(input) {
-            Ok(dt) => println!("{} s", dt.timestamp_millis() / 1000),
-            Err(e) => {
-                eprintln!("{e}");
-                std::process::exit(1);
-            }
-        },
+    match parse::evaluate(input, &ctx) {
+        Ok((Value::Span(span), _)) => println!("{}", parse::format_span(span)),
+        Ok((Value::Time(t), _)) if !zones.is_empty() => print_zones(&zones, t, parse::GO_LIKE),
+        Ok((Value::Time(t), Kind::Absolute)) => println!("{} s", t.timestamp_millis() / 1000),
+        Ok((Value::Time(t), _)) => println!("{}", format_go_like(t.with_timezone(&Local))),
+        Err(e) => fail(e),
     }
 }
 
@@ -123,6 +230,20 @@ mod tests {
         assert!(argv.iter().any(|a| a == "-d"));
     }
 
+    #[test]
+    fn hoist_options_moves_flags_out_of_the_input() {
+        let argv = |s: &str| s.split(' ').map(str::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            hoist_options(argv("tt 2026-03-08 02:30 --tz America/New_York -d")),
+            argv("tt --tz America/New_York -d -- 2026-03-08 02:30")
+        );
+        assert_eq!(
+            hoist_options(argv("tt now -90m --now=2026-01-01 - 1d")),
+            argv("tt --now=2026-01-01 -- now -90m - 1d")
+        );
+        assert_eq!(hoist_options(argv("tt -d")), argv("tt -d"));
+    }
+
     #[test]
     fn parse_unix_like_treats_large_value_as_millis() {
         let dt = parse_unix_like(1_603_372_219_690);
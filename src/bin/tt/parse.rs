@@ -0,0 +1,791 @@
+//! 时间表达式：多种输入格式、相对时间短语和加减运算
+//!
+//! - 绝对时间：unix 秒/毫秒/微秒/纳秒、RFC 3339 和常见的 ISO 8601 写法、RFC 2822、
+//!   HTTP-date（IMF-fixdate、RFC 850、asctime）、common log format
+//! - 相对时间：`now`、`today`/`yesterday`/`tomorrow`、`[last|next] <weekday>`，可以再跟
+//!   `9am`、`14:30`、`noon` 这样的时刻；`3d ago`、`in 2 hours`
+//! - 运算：`TIME ± SPAN`、`TIME - TIME`、`SPAN ± SPAN`，SPAN 写成 `1y2mo3w4d5h6m7s8ms`
+//!
+//! 没有时区的输入按 `Ctx::zone` 理解，“现在”取 `Ctx::now`，测试用固定的时钟。
+
+use std::str::FromStr;
+
+use chrono::{
+    DateTime, Datelike, Days, FixedOffset, Local, LocalResult, Months, NaiveDate, NaiveDateTime,
+    NaiveTime, TimeDelta, TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+
+const NANOS_PER_SEC: i128 = 1_000_000_000;
+const DAY_NANOS: i128 = 86_400 * NANOS_PER_SEC;
+
+/// 和 go_tools 一致的输出格式
+pub const GO_LIKE: &str = "%Y-%m-%d %H:%M:%S %z %Z";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Zone {
+    Local,
+    Tz(Tz),
+    Fixed(FixedOffset),
+}
+
+impl Zone {
+    /// `local`、`UTC`、IANA 名字（`Asia/Shanghai`）或固定偏移（`+08:00`）
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        let name = name.trim();
+        match name.to_ascii_lowercase().as_str() {
+            "local" => return Ok(Self::Local),
+            "utc" | "z" | "gmt" => return Ok(Self::Tz(Tz::UTC)),
+            _ => {}
+        }
+        if let Some(offset) = parse_offset(name) {
+            return Ok(Self::Fixed(offset));
+        }
+        Tz::from_str(name)
+            .map(Self::Tz)
+            .map_err(|_| format!("unknown time zone {name:?}"))
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Self::Local => "Local".to_string(),
+            Self::Tz(tz) => tz.name().to_string(),
+            Self::Fixed(offset) => offset.to_string(),
+        }
+    }
+
+    /// 把墙上时间放到这个时区；夏令时重叠时取较早的一个
+    pub fn localize(&self, naive: NaiveDateTime) -> Result<DateTime<Utc>, String> {
+        fn pick<T: TimeZone>(r: LocalResult<DateTime<T>>) -> Result<DateTime<Utc>, String> {
+            match r {
+                LocalResult::Single(dt) | LocalResult::Ambiguous(dt, _) => {
+                    Ok(dt.with_timezone(&Utc))
+                }
+                LocalResult::None => Err("invalid local datetime".to_string()),
+            }
+        }
+        match self {
+            Self::Local => pick(Local.from_local_datetime(&naive)),
+            Self::Tz(tz) => pick(tz.from_local_datetime(&naive)),
+            Self::Fixed(offset) => pick(offset.from_local_datetime(&naive)),
+        }
+    }
+
+    /// 同 [`Zone::localize`]，但落在夏令时跳过的时段里时按跳变前的偏移换算（顺延到跳变之后），
+    /// 用于日期运算的结果
+    pub fn localize_lenient(&self, naive: NaiveDateTime) -> Result<DateTime<Utc>, String> {
+        self.localize(naive).or_else(|e| {
+            let before = naive
+                .checked_sub_days(Days::new(1))
+                .ok_or_else(|| e.clone())?;
+            let before = self.localize(before).map_err(|_| e)?;
+            let offset = self.wall(before) - before.naive_utc();
+            Ok((naive - offset).and_utc())
+        })
+    }
+
+    /// 这个时区的墙上时间
+    pub fn wall(&self, t: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            Self::Local => t.with_timezone(&Local).naive_local(),
+            Self::Tz(tz) => t.with_timezone(tz).naive_local(),
+            Self::Fixed(offset) => t.with_timezone(offset).naive_local(),
+        }
+    }
+
+    pub fn format(&self, t: DateTime<Utc>, fmt: &str) -> String {
+        match self {
+            Self::Local => t.with_timezone(&Local).format(fmt).to_string(),
+            Self::Tz(tz) => t.with_timezone(tz).format(fmt).to_string(),
+            Self::Fixed(offset) => t.with_timezone(offset).format(fmt).to_string(),
+        }
+    }
+}
+
+/// `+08:00`、`+0800`、`-05`
+fn parse_offset(s: &str) -> Option<FixedOffset> {
+    let sign = match s.as_bytes().first()? {
+        b'+' => 1,
+        b'-' => -1,
+        _ => return None,
+    };
+    let digits: String = s[1..].chars().filter(|c| *c != ':').collect();
+    if !digits.chars().all(|c| c.is_ascii_digit()) || !matches!(digits.len(), 2 | 4) {
+        return None;
+    }
+    let hours: i32 = digits[..2].parse().ok()?;
+    let minutes: i32 = digits.get(2..).map_or(Some(0), |m| m.parse().ok())?;
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// 求值环境：当前时间和没有时区的输入所在的时区
+pub struct Ctx {
+    pub now: DateTime<Utc>,
+    pub zone: Zone,
+}
+
+/// 时间段：日历月份、日历天数和固定长度的纳秒分开记，月和天在墙上时间上加减，
+/// 所以跨夏令时切换时 `+ 1d` 仍然落在同一个钟点
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Span {
+    pub months: i64,
+    pub days: i64,
+    pub nanos: i128,
+}
+
+impl Span {
+    fn negate(self) -> Self {
+        Self {
+            months: -self.months,
+            days: -self.days,
+            nanos: -self.nanos,
+        }
+    }
+
+    fn plus(self, other: Self) -> Self {
+        Self {
+            months: self.months + other.months,
+            days: self.days + other.days,
+            nanos: self.nanos + other.nanos,
+        }
+    }
+
+    /// 天数按 24 小时折算后的总纳秒数
+    fn nominal_nanos(self) -> i128 {
+        self.nanos + i128::from(self.days) * DAY_NANOS
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Value {
+    Time(DateTime<Utc>),
+    Span(Span),
+}
+
+/// 输入的来源，决定默认的输出方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// 单个 unix 时间戳
+    Epoch,
+    /// 单个绝对时间字符串
+    Absolute,
+    /// 含 `now`、相对短语或运算
+    Relative,
+}
+
+pub fn evaluate(input: &str, ctx: &Ctx) -> Result<(Value, Kind), String> {
+    let (first, ops) = split_operators(input);
+    let (mut value, mut kind) = match parse_time(&first, ctx) {
+        Ok(v) => v,
+        Err(err) => match parse_span(&first) {
+            Some(span) => (Value::Span(span), Kind::Relative),
+            None => return Err(err),
+        },
+    };
+    for (op, operand) in ops {
+        kind = Kind::Relative;
+        let span = parse_span(&operand);
+        value = match (value, span) {
+            (Value::Time(t), Some(span)) => {
+                let span = if op == '-' { span.negate() } else { span };
+                Value::Time(add_span(t, span, ctx.zone)?)
+            }
+            (Value::Span(a), Some(b)) => Value::Span(if op == '-' {
+                a.plus(b.negate())
+            } else {
+                a.plus(b)
+            }),
+            (Value::Time(a), None) if op == '-' => {
+                let (b, _) = parse_time(&operand, ctx)?;
+                let Value::Time(b) = b else {
+                    return Err(format!("cannot subtract {operand:?}"));
+                };
+                Value::Span(difference(a, b, ctx.zone)?)
+            }
+            (Value::Span(a), None) if op == '+' => match parse_time(&operand, ctx)?.0 {
+                Value::Time(t) => Value::Time(add_span(t, a, ctx.zone)?),
+                Value::Span(_) => unreachable!("parse_time only returns times"),
+            },
+            _ => return Err(format!("invalid operand {operand:?} after '{op}'")),
+        };
+    }
+    Ok((value, kind))
+}
+
+/// 按空白切词，把独立的 `+`/`-`（或者紧贴时间段的 `+3d`、`-2h`）当作运算符。
+/// 第一个词不是运算符，时区偏移 `-0700` 这类后面跟的不是时间段的也不是。
+fn split_operators(input: &str) -> (String, Vec<(char, String)>) {
+    let mut first: Vec<&str> = Vec::new();
+    let mut ops: Vec<(char, Vec<&str>)> = Vec::new();
+    for (i, word) in input.split_whitespace().enumerate() {
+        let op = word.chars().next().filter(|c| matches!(c, '+' | '-'));
+        match op {
+            Some(op) if i > 0 && word.len() == 1 => ops.push((op, Vec::new())),
+            Some(op) if i > 0 && parse_span(&word[1..]).is_some() => {
+                ops.push((op, vec![&word[1..]]))
+            }
+            _ => match ops.last_mut() {
+                Some((_, words)) => words.push(word),
+                None => first.push(word),
+            },
+        }
+    }
+    (
+        first.join(" "),
+        ops.into_iter().map(|(op, w)| (op, w.join(" "))).collect(),
+    )
+}
+
+/// `a - b`：先按墙上日期数出整天数，余下的部分按实际经过的时间计，两部分同号
+fn difference(a: DateTime<Utc>, b: DateTime<Utc>, zone: Zone) -> Result<Span, String> {
+    let mut days = (zone.wall(a).date() - zone.wall(b).date()).num_days();
+    loop {
+        let base = add_span(
+            b,
+            Span {
+                days,
+                ..Span::default()
+            },
+            zone,
+        )?;
+        let rest = a - base;
+        let nanos = rest
+            .num_nanoseconds()
+            .map(i128::from)
+            .unwrap_or_else(|| i128::from(rest.num_seconds()) * NANOS_PER_SEC);
+        match (days.signum(), nanos.signum()) {
+            (1, -1) => days -= 1,
+            (-1, 1) => days += 1,
+            _ => {
+                return Ok(Span {
+                    months: 0,
+                    days,
+                    nanos,
+                });
+            }
+        }
+    }
+}
+
+fn add_span(t: DateTime<Utc>, span: Span, zone: Zone) -> Result<DateTime<Utc>, String> {
+    let overflow = || "date out of range".to_string();
+    let mut t = t;
+    if span.months != 0 || span.days != 0 {
+        let wall = zone.wall(t);
+        let months =
+            Months::new(u32::try_from(span.months.unsigned_abs()).map_err(|_| overflow())?);
+        let wall = if span.months >= 0 {
+            wall.checked_add_months(months)
+        } else {
+            wall.checked_sub_months(months)
+        }
+        .ok_or_else(overflow)?;
+        let days = Days::new(span.days.unsigned_abs());
+        let wall = if span.days >= 0 {
+            wall.checked_add_days(days)
+        } else {
+            wall.checked_sub_days(days)
+        }
+        .ok_or_else(overflow)?;
+        t = zone.localize_lenient(wall)?;
+    }
+    let secs = i64::try_from(span.nanos.div_euclid(NANOS_PER_SEC)).map_err(|_| overflow())?;
+    let nanos = span.nanos.rem_euclid(NANOS_PER_SEC) as u32;
+    let delta = TimeDelta::new(secs, nanos).ok_or_else(overflow)?;
+    t.checked_add_signed(delta).ok_or_else(overflow)
+}
+
+/// 解析单个时间：先试相对短语，再试各种绝对格式
+fn parse_time(s: &str, ctx: &Ctx) -> Result<(Value, Kind), String> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Err("missing time".to_string());
+    }
+    if let Some(t) = parse_relative(s, ctx) {
+        return Ok((Value::Time(t?), Kind::Relative));
+    }
+    if let Some(t) = parse_epoch(s) {
+        return Ok((Value::Time(t), Kind::Epoch));
+    }
+    match parse_absolute(s, ctx.zone) {
+        Some(t) => Ok((Value::Time(t?), Kind::Absolute)),
+        None => Err(format!("unrecognized time {s:?}")),
+    }
+}
+
+/// 整数按数量级判断秒到纳秒，带小数点的按秒
+fn parse_epoch(s: &str) -> Option<DateTime<Utc>> {
+    if let Ok(n) = s.parse::<i64>() {
+        return Some(crate::parse_unix_like(n));
+    }
+    let (int, frac) = s.split_once('.')?;
+    if int.is_empty()
+        || frac.is_empty()
+        || frac.len() > 9
+        || !frac.chars().all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let secs: i64 = int.parse().ok()?;
+    let nanos: u32 = format!("{frac:0<9}").parse().ok()?;
+    if int.starts_with('-') {
+        let t = Utc.timestamp_opt(secs, 0).single()?;
+        return t.checked_sub_signed(TimeDelta::nanoseconds(i64::from(nanos)));
+    }
+    Utc.timestamp_opt(secs, nanos).single()
+}
+
+const OFFSET_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S%.f%:z",
+    "%Y-%m-%d %H:%M:%S%.f%:z",
+    "%Y-%m-%dT%H:%M:%S%.f%z",
+    "%Y-%m-%d %H:%M:%S%.f%z",
+    "%Y-%m-%d %H:%M:%S%.f %z",
+    "%Y-%m-%d %H:%M:%S%.f %:z",
+    "%Y-%m-%dT%H:%M%:z",
+    "%Y-%m-%d %H:%M%:z",
+    "%Y%m%dT%H%M%S%z",
+    // common log format
+    "%d/%b/%Y:%H:%M:%S %z",
+];
+
+const NAIVE_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+    "%Y%m%dT%H%M%S",
+    "%Y/%m/%d %H:%M:%S",
+    "%Y/%m/%d %H:%M",
+];
+
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d", "%Y-%j"];
+
+/// HTTP-date 的两种旧写法，总是 GMT
+const HTTP_FORMATS: &[&str] = &["%A, %d-%b-%y %H:%M:%S GMT", "%a %b %e %H:%M:%S %Y"];
+
+/// 不像任何已知格式时返回 None；格式对但时间不存在（夏令时跳过的时段）时返回错误
+fn parse_absolute(s: &str, zone: Zone) -> Option<Result<DateTime<Utc>, String>> {
+    if zone == Zone::Local
+        && let Ok(dt) = crate::parse_datetime_local(s)
+    {
+        return Some(Ok(dt.with_timezone(&Utc)));
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(Ok(dt.with_timezone(&Utc)));
+    }
+    // `Z` 后缀统一成 +00:00；common log format 常带方括号
+    let s = s.trim_start_matches('[').trim_end_matches(']');
+    let zulu = s
+        .strip_suffix(['Z', 'z'])
+        .filter(|rest| rest.ends_with(|c: char| c.is_ascii_digit()))
+        .map(|rest| format!("{rest}+00:00"));
+    let with_offset = zulu.as_deref().unwrap_or(s);
+    for fmt in OFFSET_FORMATS {
+        if let Ok(dt) = DateTime::parse_from_str(with_offset, fmt) {
+            return Some(Ok(dt.with_timezone(&Utc)));
+        }
+    }
+    for fmt in NAIVE_FORMATS {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(s, fmt) {
+            return Some(zone.localize(naive));
+        }
+    }
+    for fmt in DATE_FORMATS {
+        if let Ok(date) = NaiveDate::parse_from_str(s, fmt) {
+            return Some(zone.localize(date.and_time(NaiveTime::MIN)));
+        }
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc2822(s) {
+        return Some(Ok(dt.with_timezone(&Utc)));
+    }
+    let collapsed = s.split_whitespace().collect::<Vec<_>>().join(" ");
+    for fmt in HTTP_FORMATS {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(&collapsed, fmt) {
+            return Some(Ok(naive.and_utc()));
+        }
+    }
+    None
+}
+
+/// 相对短语；不像相对短语时返回 None，交给绝对格式解析
+fn parse_relative(s: &str, ctx: &Ctx) -> Option<Result<DateTime<Utc>, String>> {
+    let lower = s.to_lowercase();
+    let words: Vec<&str> = lower.split_whitespace().collect();
+    match words.as_slice() {
+        ["now"] => return Some(Ok(ctx.now)),
+        [rest @ .., "ago"] => {
+            let span = parse_span(&rest.join(" "))?;
+            return Some(add_span(ctx.now, span.negate(), ctx.zone));
+        }
+        ["in", rest @ ..] => {
+            let span = parse_span(&rest.join(" "))?;
+            return Some(add_span(ctx.now, span, ctx.zone));
+        }
+        [rest @ .., "from", "now"] => {
+            let span = parse_span(&rest.join(" "))?;
+            return Some(add_span(ctx.now, span, ctx.zone));
+        }
+        _ => {}
+    }
+
+    let today = ctx.zone.wall(ctx.now).date();
+    let mut date = None;
+    let mut rest = Vec::new();
+    let mut i = 0;
+    while i < words.len() {
+        let w = words[i];
+        let day = match w {
+            "today" => Some(today),
+            "yesterday" => today.checked_sub_days(Days::new(1)),
+            "tomorrow" => today.checked_add_days(Days::new(1)),
+            "last" | "next" | "this" => {
+                let weekday = words.get(i + 1).and_then(|d| Weekday::from_str(d).ok())?;
+                i += 1;
+                Some(weekday_date(today, weekday, w))
+            }
+            _ => Weekday::from_str(w)
+                .ok()
+                .map(|weekday| weekday_date(today, weekday, "this")),
+        };
+        match day {
+            Some(d) if date.is_none() => date = Some(d),
+            Some(_) => return None,
+            None if w == "at" => {}
+            None => rest.push(w),
+        }
+        i += 1;
+    }
+    let time = if rest.is_empty() {
+        NaiveTime::MIN
+    } else {
+        parse_time_of_day(&rest.concat())?
+    };
+    // 只有时刻（`9am`）时是今天的那个时刻
+    let date = date.unwrap_or(today);
+    Some(ctx.zone.localize(date.and_time(time)))
+}
+
+/// `last` 是今天之前最近的一个，`next` 是今天之后最近的一个，`this` 是从今天起的这一个
+fn weekday_date(today: NaiveDate, weekday: Weekday, which: &str) -> NaiveDate {
+    let ahead = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    match which {
+        "last" => {
+            let back = (7 - ahead) % 7;
+            today - Days::new(u64::from(if back == 0 { 7 } else { back }))
+        }
+        "next" => today + Days::new(u64::from(if ahead == 0 { 7 } else { ahead })),
+        _ => today + Days::new(u64::from(ahead)),
+    }
+}
+
+/// `9am`、`9:30pm`、`21:05`、`21:05:30`、`noon`、`midnight`（空白已去掉）
+fn parse_time_of_day(s: &str) -> Option<NaiveTime> {
+    match s {
+        "noon" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return Some(NaiveTime::MIN),
+        _ => {}
+    }
+    let (clock, meridiem) = match s.strip_suffix("am") {
+        Some(c) => (c, Some(false)),
+        None => match s.strip_suffix("pm") {
+            Some(c) => (c, Some(true)),
+            None => (s, None),
+        },
+    };
+    let mut parts = clock.split(':');
+    let mut next = |max: u32| -> Option<Option<u32>> {
+        match parts.next() {
+            None => Some(None),
+            Some(p) if !p.is_empty() && p.len() <= 2 && p.chars().all(|c| c.is_ascii_digit()) => {
+                p.parse().ok().filter(|v| *v <= max).map(Some)
+            }
+            Some(_) => None,
+        }
+    };
+    let hour = next(23)??;
+    let minute = next(59)?;
+    let second = next(59)?;
+    if parts.next().is_some() || (meridiem.is_none() && minute.is_none()) {
+        return None;
+    }
+    let hour = match meridiem {
+        Some(pm) if (1..=12).contains(&hour) => hour % 12 + if pm { 12 } else { 0 },
+        Some(_) => return None,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute.unwrap_or(0), second.unwrap_or(0))
+}
+
+/// `3d4h`、`1h 30m`、`2 hours`、`1.5s`、`1y2mo`；月和年只能是整数
+pub fn parse_span(s: &str) -> Option<Span> {
+    let chars: Vec<char> = s.trim().chars().collect();
+    let mut span = Span::default();
+    let mut i = 0;
+    let mut parts = 0;
+    while i < chars.len() {
+        if chars[i].is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+            i += 1;
+        }
+        let number: String = chars[start..i].iter().collect();
+        while i < chars.len() && chars[i].is_whitespace() {
+            i += 1;
+        }
+        let unit_start = i;
+        while i < chars.len() && (chars[i].is_alphabetic()) {
+            i += 1;
+        }
+        let unit: String = chars[unit_start..i]
+            .iter()
+            .collect::<String>()
+            .to_lowercase();
+        if number.is_empty() || unit.is_empty() {
+            return None;
+        }
+        let (int, frac) = number.split_once('.').unwrap_or((&number, ""));
+        if int.is_empty() && frac.is_empty() {
+            return None;
+        }
+        let int: i128 = if int.is_empty() { 0 } else { int.parse().ok()? };
+        let months = match unit.as_str() {
+            "mo" | "mon" | "month" | "months" => Some(1),
+            "y" | "yr" | "yrs" | "year" | "years" => Some(12),
+            _ => None,
+        };
+        if let Some(m) = months {
+            if !frac.is_empty() {
+                return None;
+            }
+            span.months = span.months.checked_add(i64::try_from(int * m).ok()?)?;
+        } else {
+            let unit_nanos: i128 = match unit.as_str() {
+                "ns" | "nanosecond" | "nanoseconds" => 1,
+                "us" | "µs" | "microsecond" | "microseconds" => 1_000,
+                "ms" | "millisecond" | "milliseconds" => 1_000_000,
+                "s" | "sec" | "secs" | "second" | "seconds" => NANOS_PER_SEC,
+                "m" | "min" | "mins" | "minute" | "minutes" => 60 * NANOS_PER_SEC,
+                "h" | "hr" | "hrs" | "hour" | "hours" => 3_600 * NANOS_PER_SEC,
+                "d" | "day" | "days" => DAY_NANOS,
+                "w" | "wk" | "wks" | "week" | "weeks" => 7 * DAY_NANOS,
+                _ => return None,
+            };
+            let frac_nanos = if frac.is_empty() {
+                0
+            } else {
+                let digits: i128 = frac.parse().ok()?;
+                digits * unit_nanos / 10i128.checked_pow(u32::try_from(frac.len()).ok()?)?
+            };
+            if unit_nanos % DAY_NANOS == 0 {
+                // 整天记作日历天，小数部分按固定长度
+                let days = i64::try_from(int.checked_mul(unit_nanos / DAY_NANOS)?).ok()?;
+                span.days = span.days.checked_add(days)?;
+                span.nanos = span.nanos.checked_add(frac_nanos)?;
+            } else {
+                span.nanos = span
+                    .nanos
+                    .checked_add(int.checked_mul(unit_nanos)?.checked_add(frac_nanos)?)?;
+            }
+        }
+        parts += 1;
+    }
+    (parts > 0).then_some(span)
+}
+
+/// `92d`、`3d4h`、`1h30m15.5s`、`1y2mo`，后面附上总秒数（一天按 24 小时算，有月份时不附）
+pub fn format_span(span: Span) -> String {
+    let total = span.nominal_nanos();
+    let negative = span.months < 0 || (span.months == 0 && total < 0);
+    let months = span.months.unsigned_abs();
+    let nanos = total.unsigned_abs();
+    let mut out = String::new();
+    if negative {
+        out.push('-');
+    }
+    if months >= 12 {
+        out.push_str(&format!("{}y", months / 12));
+    }
+    if !months.is_multiple_of(12) {
+        out.push_str(&format!("{}mo", months % 12));
+    }
+    let secs = nanos / NANOS_PER_SEC as u128;
+    let frac = nanos % NANOS_PER_SEC as u128;
+    let days = secs / 86_400;
+    let hours = secs % 86_400 / 3_600;
+    let minutes = secs % 3_600 / 60;
+    let seconds = secs % 60;
+    for (n, unit) in [(days, "d"), (hours, "h"), (minutes, "m")] {
+        if n > 0 {
+            out.push_str(&format!("{n}{unit}"));
+        }
+    }
+    if seconds > 0 || frac > 0 {
+        out.push_str(&format!("{seconds}{}s", fraction(frac)));
+    }
+    if out.is_empty() || out == "-" {
+        out = "0s".to_string();
+    }
+    if months == 0 {
+        let sign = if negative { "-" } else { "" };
+        out.push_str(&format!(" ({sign}{secs}{} s)", fraction(frac)));
+    }
+    out
+}
+
+/// 纳秒小数部分，去掉末尾的 0
+fn fraction(nanos: u128) -> String {
+    if nanos == 0 {
+        return String::new();
+    }
+    format!(".{nanos:09}").trim_end_matches('0').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx() -> Ctx {
+        // 2026-03-15 是星期日，上海时间 18:20:30
+        Ctx {
+            now: Utc.with_ymd_and_hms(2026, 3, 15, 10, 20, 30).unwrap(),
+            zone: Zone::from_name("Asia/Shanghai").unwrap(),
+        }
+    }
+
+    fn time(s: &str) -> String {
+        match evaluate(s, &ctx()).unwrap().0 {
+            Value::Time(t) => t.to_rfc3339(),
+            Value::Span(span) => panic!("expected a time, got {span:?}"),
+        }
+    }
+
+    fn span(s: &str) -> String {
+        match evaluate(s, &ctx()).unwrap().0 {
+            Value::Span(span) => format_span(span),
+            Value::Time(t) => panic!("expected a span, got {t}"),
+        }
+    }
+
+    #[test]
+    fn parses_absolute_formats() {
+        let new_year = "2026-01-01T00:00:00+00:00";
+        for input in [
+            "2026-01-01T00:00:00Z",
+            "2026-01-01T08:00:00+08:00",
+            "2026-01-01 08:00:00.000+0800",
+            "2026-01-01T08:00",
+            "2026-01-01 08:00:00",
+            "20260101T000000Z",
+            "Thu, 01 Jan 2026 00:00:00 +0000",
+            "Thu, 01 Jan 2026 00:00:00 GMT",
+            "Thursday, 01-Jan-26 00:00:00 GMT",
+            "Thu Jan  1 00:00:00 2026",
+            "[01/Jan/2026:03:00:00 +0300]",
+            "1767225600",
+            "1767225600000",
+            "1767225600000000",
+            "1767225600000000000",
+        ] {
+            assert_eq!(time(input), new_year, "{input}");
+        }
+        assert_eq!(time("2026-01-01"), "2025-12-31T16:00:00+00:00");
+        assert_eq!(time("1767225600.25"), "2026-01-01T00:00:00.250+00:00");
+        assert_eq!(evaluate("1767225600", &ctx()).unwrap().1, Kind::Epoch);
+        assert_eq!(evaluate("2026-01-01", &ctx()).unwrap().1, Kind::Absolute);
+        assert!(evaluate("not a time", &ctx()).is_err());
+    }
+
+    #[test]
+    fn parses_relative_phrases() {
+        assert_eq!(time("now"), "2026-03-15T10:20:30+00:00");
+        assert_eq!(time("yesterday 9am"), "2026-03-14T01:00:00+00:00");
+        assert_eq!(time("tomorrow at 9:30pm"), "2026-03-16T13:30:00+00:00");
+        assert_eq!(time("today noon"), "2026-03-15T04:00:00+00:00");
+        assert_eq!(time("14:05"), "2026-03-15T06:05:00+00:00");
+        assert_eq!(time("last friday"), "2026-03-12T16:00:00+00:00");
+        assert_eq!(time("next sunday 08:00"), "2026-03-22T00:00:00+00:00");
+        assert_eq!(time("monday"), "2026-03-15T16:00:00+00:00");
+        assert_eq!(time("3h ago"), "2026-03-15T07:20:30+00:00");
+        assert_eq!(time("in 2 days"), "2026-03-17T10:20:30+00:00");
+        assert_eq!(evaluate("yesterday", &ctx()).unwrap().1, Kind::Relative);
+        assert!(evaluate("13pm", &ctx()).is_err());
+    }
+
+    #[test]
+    fn evaluates_arithmetic() {
+        assert_eq!(time("now + 3d4h"), "2026-03-18T14:20:30+00:00");
+        assert_eq!(time("now -90m"), "2026-03-15T08:50:30+00:00");
+        assert_eq!(time("2026-01-31 + 1mo"), "2026-02-27T16:00:00+00:00");
+        assert_eq!(
+            time("2026-01-01T00:00:00Z + 1y - 1ms"),
+            "2026-12-31T23:59:59.999+00:00"
+        );
+        assert_eq!(time("1h30m + now"), "2026-03-15T11:50:30+00:00");
+        assert_eq!(span("2026-01-01 - 2025-10-01"), "92d (7948800 s)");
+        assert_eq!(span("2025-10-01 - 2026-01-01"), "-92d (-7948800 s)");
+        assert_eq!(span("now - yesterday 9am"), "1d9h20m30s (120030 s)");
+        assert_eq!(span("1h30m + 1.5s"), "1h30m1.5s (5401.5 s)");
+        assert_eq!(span("1y2mo + 3d"), "1y2mo3d");
+        assert_eq!(span("now - now"), "0s (0 s)");
+        // 时区偏移不是运算符
+        assert_eq!(
+            time("[10/Oct/2000:13:55:36 -0700]"),
+            "2000-10-10T20:55:36+00:00"
+        );
+        assert!(evaluate("now + tomorrow", &ctx()).is_err());
+    }
+
+    #[test]
+    fn day_arithmetic_follows_wall_clock_across_dst() {
+        let ny = Ctx {
+            now: Utc.with_ymd_and_hms(2026, 3, 15, 10, 20, 30).unwrap(),
+            zone: Zone::from_name("America/New_York").unwrap(),
+        };
+        let eval = |s: &str| evaluate(s, &ny).map(|(v, _)| v);
+        let time = |s: &str| match eval(s).unwrap() {
+            Value::Time(t) => t.to_rfc3339(),
+            Value::Span(span) => panic!("expected a time, got {span:?}"),
+        };
+        let span = |s: &str| match eval(s).unwrap() {
+            Value::Span(span) => format_span(span),
+            Value::Time(t) => panic!("expected a span, got {t}"),
+        };
+        assert_eq!(span("2026-01-01 - 2025-10-01"), "92d (7948800 s)");
+        // 2026-03-08 02:00 起夏令时，这一天只有 23 小时
+        assert_eq!(time("2026-03-07 12:00 + 1d"), "2026-03-08T16:00:00+00:00");
+        assert_eq!(time("2026-03-07 12:00 + 24h"), "2026-03-08T17:00:00+00:00");
+        assert_eq!(time("2026-03-14 12:00 - 1w"), "2026-03-07T17:00:00+00:00");
+        assert_eq!(
+            span("2026-03-08 23:00 - 2026-03-07 01:00"),
+            "1d21h (162000 s)"
+        );
+        // 运算结果落在跳过的时段里时顺延，直接写出这样的时间则报错
+        assert_eq!(time("2026-03-07 02:30 + 1d"), "2026-03-08T07:30:00+00:00");
+        assert_eq!(
+            eval("2026-03-08 02:30").unwrap_err(),
+            "invalid local datetime"
+        );
+    }
+
+    #[test]
+    fn zones_from_names() {
+        assert_eq!(Zone::from_name("utc").unwrap(), Zone::Tz(Tz::UTC));
+        assert_eq!(
+            Zone::from_name("Asia/Shanghai").unwrap().name(),
+            "Asia/Shanghai"
+        );
+        assert_eq!(Zone::from_name("+05:30").unwrap().name(), "+05:30");
+        assert_eq!(Zone::from_name("local").unwrap(), Zone::Local);
+        assert!(Zone::from_name("Mars/Olympus").is_err());
+        let t = Utc.with_ymd_and_hms(2026, 7, 1, 0, 0, 0).unwrap();
+        let ny = Zone::from_name("America/New_York").unwrap();
+        assert_eq!(
+            ny.format(t, "%Y-%m-%d %H:%M:%S %z %Z"),
+            "2026-06-30 20:00:00 -0400 EDT"
+        );
+    }
+}
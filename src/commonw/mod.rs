@@ -73,6 +73,7 @@ pub use rust_tools_macros::debug_measure_time;
 /// 为函数添加 LRU（最近最少使用）缓存机制，自动缓存给定参数的返回结果。
 ///
 /// 需要指定 `cap`（容量），还可以可选地指定 `ttl_ms`（缓存过期时间，毫秒）。
+/// 加上 `concurrent` 后改用分片的 [`crate::cw::ConcurrentLruCache`]，多线程调用不再争用同一把锁。
 /// 函数的参数必须实现 `Clone`, `Hash`, 和 `Eq`，返回值必须实现 `Clone`。
 ///
 /// # Example
@@ -85,6 +86,12 @@ pub use rust_tools_macros::debug_measure_time;
 ///     // ... 耗时计算
 ///     a + b
 /// }
+///
+/// // 多线程场景
+/// #[lru_cache(cap = 1024, concurrent)]
+/// fn shared_computation(key: String) -> usize {
+///     key.len()
+/// }
 /// ```
 pub use rust_tools_macros::lru_cache;
 
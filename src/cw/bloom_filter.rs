@@ -2,10 +2,14 @@
 //!
 //! 布隆过滤器是一种空间效率很高的概率型数据结构，用于测试一个元素是否在一个集合中。
 //! 它可能会产生假阳性（false positive），但不会产生假阴性（false negative）。
+//!
+//! 另见可删除的 [`CountingBloomFilter`](super::CountingBloomFilter) 和可自动扩容的
+//! [`ScalableBloomFilter`](super::ScalableBloomFilter)。三者共用同一套哈希和二进制格式头，
+//! 可以通过 `to_bytes` / `from_bytes`（或 `save` / `load`）跨进程持久化。
 
 use std::hash::{Hash, Hasher};
-
-use rustc_hash::FxHasher;
+use std::io;
+use std::path::Path;
 
 /// 布隆过滤器（Bloom Filter）
 ///
@@ -57,9 +61,10 @@ use rustc_hash::FxHasher;
 ///
 /// # 注意事项
 ///
-/// - 布隆过滤器不支持删除操作（除非使用计数布隆过滤器）
+/// - 布隆过滤器不支持删除操作（需要删除时使用 [`CountingBloomFilter`](super::CountingBloomFilter)）
 /// - 假阳性率随着插入元素数量的增加而增加
 /// - 选择合适的参数很重要：位数越多、哈希函数数量越合适，假阳性率越低
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BloomFilter {
     /// 位数组，每 u64 存储 64 位
     bits: Vec<u64>,
@@ -123,11 +128,7 @@ impl BloomFilter {
     ///
     /// 其中 n 为元素数量，p 为假阳性率
     pub fn with_rate(expected_items: usize, false_positive_rate: f64) -> Self {
-        let n = expected_items.max(1) as f64;
-        let p = false_positive_rate.clamp(1e-12, 0.999_999_999_999);
-        let ln2 = std::f64::consts::LN_2;
-        let m = (-(n * p.ln()) / (ln2 * ln2)).ceil().max(1.0) as usize;
-        let k = ((m as f64 / n) * ln2).round().max(1.0) as u32;
+        let (m, k) = optimal_params(expected_items, false_positive_rate);
         Self::new(m, k)
     }
 
@@ -194,9 +195,9 @@ impl BloomFilter {
     /// bf.insert(&"world".to_string());
     /// ```
     pub fn insert<T: Hash>(&mut self, item: &T) {
-        let (h1, h2) = self.hash_pair(item);
+        let (h1, h2) = hash_pair(item);
         for i in 0..self.hash_count {
-            let idx = self.index(h1, h2, i);
+            let idx = bit_index(h1, h2, i, self.bit_count);
             self.set_bit(idx);
         }
     }
@@ -224,9 +225,9 @@ impl BloomFilter {
     /// assert!(!bf.contains(&"world".to_string())); // 可能为 false 或 true（假阳性）
     /// ```
     pub fn contains<T: Hash>(&self, item: &T) -> bool {
-        let (h1, h2) = self.hash_pair(item);
+        let (h1, h2) = hash_pair(item);
         for i in 0..self.hash_count {
-            let idx = self.index(h1, h2, i);
+            let idx = bit_index(h1, h2, i, self.bit_count);
             if !self.get_bit(idx) {
                 return false;
             }
@@ -234,12 +235,128 @@ impl BloomFilter {
         true
     }
 
-    /// 计算第 i 个哈希函数对应的位索引
+    /// 已置 1 的位数
+    pub fn count_ones(&self) -> usize {
+        self.bits.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// 根据已置位比例估算已插入的元素个数
+    ///
+    /// 公式：n ≈ -(m / k) * ln(1 - X / m)，X 为已置位数
+    pub fn estimated_len(&self) -> usize {
+        let m = self.bit_count as f64;
+        let x = self.count_ones() as f64;
+        if x >= m {
+            return usize::MAX;
+        }
+        (-(m / self.hash_count as f64) * (1.0 - x / m).ln()).round() as usize
+    }
+
+    /// 两个过滤器的位数和哈希函数数量都相同时才能做并集 / 交集
+    pub fn is_compatible(&self, other: &BloomFilter) -> bool {
+        self.bit_count == other.bit_count && self.hash_count == other.hash_count
+    }
+
+    /// 并集：结果包含任一过滤器中的元素
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rust_tools::cw::BloomFilter;
+    ///
+    /// let mut a = BloomFilter::new(1024, 3);
+    /// let mut b = BloomFilter::new(1024, 3);
+    /// a.insert(&"a");
+    /// b.insert(&"b");
+    /// a.union(&b).unwrap();
+    /// assert!(a.contains(&"a") && a.contains(&"b"));
+    /// assert!(a.union(&BloomFilter::new(512, 3)).is_err());
+    /// ```
+    pub fn union(&mut self, other: &BloomFilter) -> Result<(), &'static str> {
+        if !self.is_compatible(other) {
+            return Err("bloom filters have different bit_count or hash_count");
+        }
+        for (a, b) in self.bits.iter_mut().zip(&other.bits) {
+            *a |= *b;
+        }
+        Ok(())
+    }
+
+    /// 交集：结果只保证包含两个过滤器中都存在的元素（假阳性率可能高于直接构建的过滤器）
+    pub fn intersect(&mut self, other: &BloomFilter) -> Result<(), &'static str> {
+        if !self.is_compatible(other) {
+            return Err("bloom filters have different bit_count or hash_count");
+        }
+        for (a, b) in self.bits.iter_mut().zip(&other.bits) {
+            *a &= *b;
+        }
+        Ok(())
+    }
+
+    /// 序列化为稳定的二进制格式（带版本号的格式头 + 小端字段）
     ///
-    /// 使用双重哈希技术：h(i) = h1 + i * h2
-    fn index(&self, h1: u64, h2: u64, i: u32) -> usize {
-        let mixed = h1.wrapping_add((i as u64).wrapping_mul(h2));
-        (mixed % (self.bit_count as u64)) as usize
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rust_tools::cw::BloomFilter;
+    ///
+    /// let mut bf = BloomFilter::with_rate(100, 0.01);
+    /// bf.insert(&"d41d8cd98f00b204e9800998ecf8427e");
+    /// let restored = BloomFilter::from_bytes(&bf.to_bytes()).unwrap();
+    /// assert!(restored.contains(&"d41d8cd98f00b204e9800998ecf8427e"));
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(HEADER_LEN + 12 + self.bits.len() * 8);
+        write_header(&mut buf, FilterKind::Standard);
+        self.write_body(&mut buf);
+        buf
+    }
+
+    /// 从 [`to_bytes`](Self::to_bytes) 的输出恢复
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let mut r = ByteReader::new(read_header(bytes, FilterKind::Standard)?);
+        let bf = Self::read_body(&mut r)?;
+        r.finish()?;
+        Ok(bf)
+    }
+
+    /// 保存到文件
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_bytes())
+    }
+
+    /// 从文件加载
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    pub(super) fn write_body(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&(self.bit_count as u64).to_le_bytes());
+        buf.extend_from_slice(&self.hash_count.to_le_bytes());
+        for w in &self.bits {
+            buf.extend_from_slice(&w.to_le_bytes());
+        }
+    }
+
+    pub(super) fn read_body(r: &mut ByteReader<'_>) -> io::Result<Self> {
+        let bit_count = r.read_len()?;
+        let hash_count = r.read_u32()?;
+        if bit_count == 0 || hash_count == 0 {
+            return Err(invalid_data("bloom filter with zero bits or hashes"));
+        }
+        let words = bit_count.div_ceil(64);
+        if r.remaining() < words.saturating_mul(8) {
+            return Err(invalid_data("truncated bloom filter bits"));
+        }
+        let mut bits = Vec::with_capacity(words);
+        for _ in 0..words {
+            bits.push(r.read_u64()?);
+        }
+        Ok(Self {
+            bits,
+            bit_count,
+            hash_count,
+        })
     }
 
     /// 设置指定位为 1
@@ -255,25 +372,183 @@ impl BloomFilter {
         let bit = idx % 64;
         (self.bits[word] & (1u64 << bit)) != 0
     }
+}
 
-    /// 计算两个哈希值用于双重哈希技术
-    ///
-    /// 使用两个不同的哈希种子生成两个独立的哈希值，
-    /// 然后通过线性组合生成多个哈希值。
-    fn hash_pair<T: Hash>(&self, item: &T) -> (u64, u64) {
-        let mut a = FxHasher::default();
-        item.hash(&mut a);
-        0x9e37_79b9_7f4a_7c15u64.hash(&mut a);
-        let h1 = a.finish();
+/// 根据期望元素数量和假阳性率计算最优 (位数, 哈希函数数量)
+pub(super) fn optimal_params(expected_items: usize, false_positive_rate: f64) -> (usize, u32) {
+    let n = expected_items.max(1) as f64;
+    let p = false_positive_rate.clamp(1e-12, 0.999_999_999_999);
+    let ln2 = std::f64::consts::LN_2;
+    let m = (-(n * p.ln()) / (ln2 * ln2)).ceil().max(1.0) as usize;
+    let k = ((m as f64 / n) * ln2).round().max(1.0) as u32;
+    (m, k)
+}
+
+/// 计算第 i 个哈希函数对应的位索引
+///
+/// 使用双重哈希技术：h(i) = h1 + i * h2
+pub(super) fn bit_index(h1: u64, h2: u64, i: u32, bit_count: usize) -> usize {
+    let mixed = h1.wrapping_add((i as u64).wrapping_mul(h2));
+    (mixed % (bit_count as u64)) as usize
+}
+
+/// 计算两个哈希值用于双重哈希技术
+///
+/// 使用两个不同种子的 [`StableHasher`] 生成两个独立的哈希值，
+/// 然后通过线性组合生成多个哈希值。哈希结果不依赖 Rust / 依赖库版本，保存到磁盘的过滤器才能跨版本复用。
+pub(super) fn hash_pair<T: Hash>(item: &T) -> (u64, u64) {
+    let mut a = StableHasher::with_seed(0x9e37_79b9_7f4a_7c15);
+    item.hash(&mut a);
+    let h1 = a.finish();
 
-        let mut b = std::collections::hash_map::DefaultHasher::new();
-        item.hash(&mut b);
-        0x243f_6a88_85a3_08d3u64.hash(&mut b);
-        let mut h2 = b.finish();
-        if h2 == 0 {
-            h2 = 0x27d4_eb2d;
+    let mut b = StableHasher::with_seed(0x243f_6a88_85a3_08d3);
+    item.hash(&mut b);
+    let mut h2 = b.finish();
+    if h2 == 0 {
+        h2 = 0x27d4_eb2d;
+    }
+    (h1, h2)
+}
+
+/// 带种子的 FNV-1a 64 位哈希，`finish` 时再做一次 splitmix64 混淆以打散低位
+struct StableHasher {
+    state: u64,
+}
+
+impl StableHasher {
+    fn with_seed(seed: u64) -> Self {
+        Self {
+            state: 0xcbf2_9ce4_8422_2325 ^ seed,
+        }
+    }
+}
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.state ^= b as u64;
+            self.state = self.state.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    // 整数统一按小端写入，保证不同平台结果一致
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write(&(i as u64).to_le_bytes());
+    }
+
+    fn finish(&self) -> u64 {
+        let mut z = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+// ============== 二进制格式 ==============
+//
+// header: magic "RTBLOOM\0"(8) | version(u16 LE) | kind(u8) | reserved(u8)
+// 之后的字段全部为小端，具体布局由各过滤器的 write_body 决定。
+
+const MAGIC: &[u8; 8] = b"RTBLOOM\0";
+const FORMAT_VERSION: u16 = 1;
+pub(super) const HEADER_LEN: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum FilterKind {
+    Standard = 1,
+    Counting = 2,
+    Scalable = 3,
+}
+
+pub(super) fn write_header(buf: &mut Vec<u8>, kind: FilterKind) {
+    buf.extend_from_slice(MAGIC);
+    buf.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    buf.push(kind as u8);
+    buf.push(0);
+}
+
+/// 校验格式头，返回格式头之后的字节
+pub(super) fn read_header(bytes: &[u8], kind: FilterKind) -> io::Result<&[u8]> {
+    if bytes.len() < HEADER_LEN || &bytes[..8] != MAGIC {
+        return Err(invalid_data("not a bloom filter file"));
+    }
+    let version = u16::from_le_bytes([bytes[8], bytes[9]]);
+    if version != FORMAT_VERSION {
+        return Err(invalid_data(format!(
+            "unsupported bloom filter format version {version}"
+        )));
+    }
+    if bytes[10] != kind as u8 {
+        return Err(invalid_data(format!(
+            "bloom filter kind mismatch: expected {kind:?}, found {}",
+            bytes[10]
+        )));
+    }
+    Ok(&bytes[HEADER_LEN..])
+}
+
+pub(super) fn invalid_data(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+/// 小端字段读取器
+pub(super) struct ByteReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    pub(super) fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub(super) fn remaining(&self) -> usize {
+        self.data.len()
+    }
+
+    pub(super) fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        if self.data.len() < n {
+            return Err(invalid_data("unexpected end of bloom filter data"));
+        }
+        let (head, tail) = self.data.split_at(n);
+        self.data = tail;
+        Ok(head)
+    }
+
+    pub(super) fn read_u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub(super) fn read_u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub(super) fn read_f64(&mut self) -> io::Result<f64> {
+        Ok(f64::from_bits(self.read_u64()?))
+    }
+
+    pub(super) fn read_len(&mut self) -> io::Result<usize> {
+        usize::try_from(self.read_u64()?).map_err(|_| invalid_data("length overflows usize"))
+    }
+
+    /// 确认所有数据都已读完
+    pub(super) fn finish(&self) -> io::Result<()> {
+        if self.data.is_empty() {
+            Ok(())
+        } else {
+            Err(invalid_data("trailing bytes after bloom filter data"))
         }
-        (h1, h2)
     }
 }
 
@@ -318,6 +593,64 @@ mod tests {
         assert!(bf.hash_count() >= 1);
     }
 
+    #[test]
+    fn test_bloom_filter_union_intersect() {
+        let mut a = BloomFilter::with_rate(100, 0.01);
+        let mut b = BloomFilter::with_rate(100, 0.01);
+        for i in 0..50 {
+            a.insert(&i);
+        }
+        for i in 25..75 {
+            b.insert(&i);
+        }
+        let mut i = a.clone();
+        i.intersect(&b).unwrap();
+        for x in 25..50 {
+            assert!(i.contains(&x));
+        }
+        a.union(&b).unwrap();
+        for x in 0..75 {
+            assert!(a.contains(&x));
+        }
+        assert!(a.union(&BloomFilter::new(64, 2)).is_err());
+        assert!(a.intersect(&BloomFilter::new(64, 2)).is_err());
+    }
+
+    #[test]
+    fn test_bloom_filter_bytes_round_trip() {
+        let mut bf = BloomFilter::with_rate(500, 0.01);
+        for i in 0..500 {
+            bf.insert(&format!("item-{i}"));
+        }
+        let bytes = bf.to_bytes();
+        let restored = BloomFilter::from_bytes(&bytes).unwrap();
+        assert_eq!(restored.bit_count(), bf.bit_count());
+        assert_eq!(restored.hash_count(), bf.hash_count());
+        for i in 0..500 {
+            assert!(restored.contains(&format!("item-{i}")));
+        }
+        let est = restored.estimated_len();
+        assert!((450..=550).contains(&est), "estimated {est}");
+
+        assert!(BloomFilter::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut bad_version = bytes.clone();
+        bad_version[8] = 99;
+        assert!(BloomFilter::from_bytes(&bad_version).is_err());
+        assert!(BloomFilter::from_bytes(b"garbage").is_err());
+    }
+
+    /// 格式与哈希都必须稳定：固定输入得到固定的字节
+    #[test]
+    fn test_bloom_filter_format_is_stable() {
+        let mut bf = BloomFilter::new(64, 2);
+        bf.insert(&"stable");
+        let bytes = bf.to_bytes();
+        assert_eq!(&bytes[..12], b"RTBLOOM\0\x01\x00\x01\x00");
+        assert_eq!(bytes.len(), 12 + 8 + 4 + 8);
+        assert_eq!(bf.count_ones(), 2);
+        assert_eq!(super::hash_pair(&"stable"), super::hash_pair(&"stable"));
+    }
+
     #[test]
     fn test_bloom_filter_default() {
         let bf = BloomFilter::default();
@@ -0,0 +1,732 @@
+//! 线程安全的分片 LRU 缓存
+//!
+//! [`ConcurrentLruCache`] 按 key 的哈希把数据分到多个分片（与 [`ConcurrentHashMap`](super::ConcurrentHashMap)
+//! 相同的分片方式），每个分片各自维护一条 LRU 链表和一把锁，所有操作只需要 `&self`，
+//! 多线程共享时不必再套一层全局 `Mutex`。
+//!
+//! - TTL 语义与 [`LruCache::with_ttl`](super::LruCache::with_ttl) 一致：`ttl_ms < 0` 表示永不过期；
+//! - 可选的权重函数（weigher）+ 总权重预算，用于按字节数而不是条目数限制内存；
+//! - 可选的淘汰监听器，在条目因容量 / 权重 / 过期被移除时回调（回调时不持有分片锁）；
+//! - 命中 / 未命中 / 淘汰计数。
+//!
+//! 条目数容量按分片均分、各分片独立淘汰，因此按容量淘汰时 LRU 顺序只在分片内部严格成立；
+//! 权重预算则是全局的：总权重超出后，每次从所有分片的链表尾中挑最久未访问的一条淘汰，
+//! 单个条目只要不超过总预算就能留在缓存里，与它落在哪个分片无关。
+//! 容量较小时（< 32）只使用一个分片，行为与 [`LruCache`](super::LruCache) 完全一致。
+
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use rustc_hash::{FxHashMap, FxHasher};
+
+const NIL: usize = usize::MAX;
+
+type Weigher<K, V> = Box<dyn Fn(&K, &V) -> u64 + Send + Sync>;
+type EvictionListener<K, V> = Box<dyn Fn(&K, &V, EvictionCause) + Send + Sync>;
+
+/// 条目被淘汰的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvictionCause {
+    /// 超出条目数容量
+    Capacity,
+    /// 超出权重预算
+    Weight,
+    /// TTL 过期
+    Expired,
+}
+
+/// 缓存统计信息的快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+impl CacheStats {
+    /// 命中率，没有任何查询时返回 0.0
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+struct Entry<K, V> {
+    key: Arc<K>,
+    val: V,
+    weight: u64,
+    expires_at: Option<Instant>,
+    /// 最近一次访问的逻辑时钟，用于跨分片比较新旧（未设置 weigher 时恒为 0）
+    stamp: u64,
+    prev: usize,
+    next: usize,
+}
+
+/// 单个分片：slab 存储的双向链表 + 哈希索引，head 为最近使用
+struct Shard<K, V> {
+    map: FxHashMap<Arc<K>, usize>,
+    slots: Vec<Option<Entry<K, V>>>,
+    free: Vec<usize>,
+    head: usize,
+    tail: usize,
+    cap: usize,
+    weight: u64,
+}
+
+type Evicted<K, V> = Vec<(Arc<K>, V, EvictionCause)>;
+
+impl<K, V> Shard<K, V>
+where
+    K: Eq + Hash,
+{
+    fn new(cap: usize) -> Self {
+        Self {
+            map: FxHashMap::default(),
+            slots: Vec::new(),
+            free: Vec::new(),
+            head: NIL,
+            tail: NIL,
+            cap,
+            weight: 0,
+        }
+    }
+
+    fn entry(&self, idx: usize) -> &Entry<K, V> {
+        self.slots[idx].as_ref().unwrap()
+    }
+
+    fn entry_mut(&mut self, idx: usize) -> &mut Entry<K, V> {
+        self.slots[idx].as_mut().unwrap()
+    }
+
+    fn unlink(&mut self, idx: usize) {
+        let (prev, next) = {
+            let e = self.entry(idx);
+            (e.prev, e.next)
+        };
+        if prev == NIL {
+            self.head = next;
+        } else {
+            self.entry_mut(prev).next = next;
+        }
+        if next == NIL {
+            self.tail = prev;
+        } else {
+            self.entry_mut(next).prev = prev;
+        }
+    }
+
+    fn push_front(&mut self, idx: usize) {
+        let old_head = self.head;
+        {
+            let e = self.entry_mut(idx);
+            e.prev = NIL;
+            e.next = old_head;
+        }
+        if old_head == NIL {
+            self.tail = idx;
+        } else {
+            self.entry_mut(old_head).prev = idx;
+        }
+        self.head = idx;
+    }
+
+    fn remove_idx(&mut self, idx: usize) -> Entry<K, V> {
+        self.unlink(idx);
+        let entry = self.slots[idx].take().unwrap();
+        self.free.push(idx);
+        self.map.remove(&entry.key);
+        self.weight -= entry.weight;
+        entry
+    }
+
+    /// 查找未过期的条目并移到链表头；过期条目会被顺手移除
+    fn lookup(
+        &mut self,
+        k: &K,
+        now: Instant,
+        stamp: u64,
+        evicted: &mut Evicted<K, V>,
+    ) -> Option<usize> {
+        let idx = *self.map.get(k)?;
+        if matches!(self.entry(idx).expires_at, Some(t) if t <= now) {
+            let e = self.remove_idx(idx);
+            evicted.push((e.key, e.val, EvictionCause::Expired));
+            return None;
+        }
+        self.entry_mut(idx).stamp = stamp;
+        if self.head != idx {
+            self.unlink(idx);
+            self.push_front(idx);
+        }
+        Some(idx)
+    }
+
+    fn insert(
+        &mut self,
+        k: K,
+        v: V,
+        weight: u64,
+        expires_at: Option<Instant>,
+        stamp: u64,
+        evicted: &mut Evicted<K, V>,
+    ) -> Option<V> {
+        let old = if let Some(&idx) = self.map.get(&k) {
+            let old_weight = self.entry(idx).weight;
+            self.weight = self.weight - old_weight + weight;
+            let e = self.entry_mut(idx);
+            e.weight = weight;
+            e.expires_at = expires_at;
+            e.stamp = stamp;
+            let old = std::mem::replace(&mut e.val, v);
+            if self.head != idx {
+                self.unlink(idx);
+                self.push_front(idx);
+            }
+            Some(old)
+        } else {
+            let key = Arc::new(k);
+            let entry = Entry {
+                key: key.clone(),
+                val: v,
+                weight,
+                expires_at,
+                stamp,
+                prev: NIL,
+                next: NIL,
+            };
+            let idx = match self.free.pop() {
+                Some(idx) => {
+                    self.slots[idx] = Some(entry);
+                    idx
+                }
+                None => {
+                    self.slots.push(Some(entry));
+                    self.slots.len() - 1
+                }
+            };
+            self.map.insert(key, idx);
+            self.weight += weight;
+            self.push_front(idx);
+            None
+        };
+        self.evict_overflow(evicted);
+        old
+    }
+
+    fn evict_overflow(&mut self, evicted: &mut Evicted<K, V>) {
+        while self.tail != NIL && self.map.len() > self.cap {
+            let e = self.remove_idx(self.tail);
+            evicted.push((e.key, e.val, EvictionCause::Capacity));
+        }
+    }
+
+    /// 链表尾（本分片最久未使用条目）的访问时钟
+    fn tail_stamp(&self) -> Option<u64> {
+        (self.tail != NIL).then(|| self.entry(self.tail).stamp)
+    }
+
+    fn purge_expired(&mut self, now: Instant, evicted: &mut Evicted<K, V>) {
+        let mut idx = self.tail;
+        while idx != NIL {
+            let (prev, expired) = {
+                let e = self.entry(idx);
+                (e.prev, matches!(e.expires_at, Some(t) if t <= now))
+            };
+            if expired {
+                let e = self.remove_idx(idx);
+                evicted.push((e.key, e.val, EvictionCause::Expired));
+            }
+            idx = prev;
+        }
+    }
+
+    fn live_len(&self, now: Instant) -> usize {
+        self.slots
+            .iter()
+            .flatten()
+            .filter(|e| !matches!(e.expires_at, Some(t) if t <= now))
+            .count()
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+        self.slots.clear();
+        self.free.clear();
+        self.head = NIL;
+        self.tail = NIL;
+        self.weight = 0;
+    }
+}
+
+/// 线程安全的分片 LRU 缓存
+///
+/// # 示例
+///
+/// ```rust
+/// use rust_tools::cw::ConcurrentLruCache;
+///
+/// // 最多 1000 条，且总字节数不超过 1 MiB
+/// let cache: ConcurrentLruCache<String, Vec<u8>> = ConcurrentLruCache::new(1000)
+///     .with_weigher(1 << 20, |_k, v: &Vec<u8>| v.len() as u64);
+///
+/// cache.put("a".to_string(), vec![0; 16]);
+/// assert_eq!(cache.get(&"a".to_string()).map(|v| v.len()), Some(16));
+/// assert!(cache.get(&"b".to_string()).is_none());
+///
+/// let stats = cache.stats();
+/// assert_eq!((stats.hits, stats.misses), (1, 1));
+/// ```
+pub struct ConcurrentLruCache<K, V> {
+    shards: Vec<Mutex<Shard<K, V>>>,
+    shard_mask: usize,
+    cap: usize,
+    max_weight: u64,
+    ttl_ms: i64,
+    weigher: Option<Weigher<K, V>>,
+    listener: Option<EvictionListener<K, V>>,
+    /// 所有分片的总权重，在分片锁内随分片权重一起更新
+    total_weight: AtomicU64,
+    clock: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl<K, V> ConcurrentLruCache<K, V>
+where
+    K: Eq + Hash,
+{
+    /// 创建容量为 `cap` 的缓存，分片数根据容量自动选择（最多 64）
+    pub fn new(cap: usize) -> Self {
+        let shard_count = (cap / 32).clamp(1, 64);
+        Self::with_shard_count(cap, shard_count)
+    }
+
+    /// 创建带 TTL 的缓存，`ttl_ms < 0` 表示永不过期
+    pub fn with_ttl(cap: usize, ttl_ms: i64) -> Self {
+        let mut c = Self::new(cap);
+        c.ttl_ms = ttl_ms;
+        c
+    }
+
+    /// 指定分片数（向上取整为 2 的幂）；容量按分片均分
+    pub fn with_shard_count(cap: usize, shard_count: usize) -> Self {
+        let n = shard_count.max(1).next_power_of_two();
+        let per_shard = cap.div_ceil(n);
+        let shards = (0..n).map(|_| Mutex::new(Shard::new(per_shard))).collect();
+        Self {
+            shards,
+            shard_mask: n - 1,
+            cap,
+            max_weight: u64::MAX,
+            ttl_ms: -1,
+            weigher: None,
+            listener: None,
+            total_weight: AtomicU64::new(0),
+            clock: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    /// 设置权重函数和总权重预算
+    ///
+    /// 预算对整个缓存生效而不是按分片均分：超出后从所有分片里最久未访问的条目开始淘汰。
+    /// 并发写入时可能短暂超出预算，或多淘汰一两条。单个条目超过整个预算时自身也会被淘汰。
+    pub fn with_weigher(
+        mut self,
+        max_weight: u64,
+        weigher: impl Fn(&K, &V) -> u64 + Send + Sync + 'static,
+    ) -> Self {
+        self.max_weight = max_weight;
+        self.weigher = Some(Box::new(weigher));
+        self
+    }
+
+    /// 设置淘汰监听器；显式 `remove` / `clear` 不会触发回调
+    pub fn with_eviction_listener(
+        mut self,
+        listener: impl Fn(&K, &V, EvictionCause) + Send + Sync + 'static,
+    ) -> Self {
+        self.listener = Some(Box::new(listener));
+        self
+    }
+
+    pub fn set_ttl_ms(&mut self, ttl_ms: i64) {
+        self.ttl_ms = ttl_ms;
+        if self.ttl_ms >= 0 {
+            self.purge_expired();
+        }
+    }
+
+    pub fn ttl_ms(&self) -> i64 {
+        self.ttl_ms
+    }
+
+    pub fn cap(&self) -> usize {
+        self.cap
+    }
+
+    pub fn max_weight(&self) -> u64 {
+        self.max_weight
+    }
+
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// 插入或覆盖，返回旧值
+    pub fn put(&self, k: K, v: V) -> Option<V> {
+        let now = Instant::now();
+        let weight = self.weigher.as_ref().map_or(0, |w| w(&k, &v));
+        let expires_at = self.calc_expires_at(now);
+        let stamp = self.tick();
+        let mut evicted = Vec::new();
+        let old = self.with_shard(self.shard_for_key(&k), |s| {
+            s.insert(k, v, weight, expires_at, stamp, &mut evicted)
+        });
+        self.evict_over_budget(&mut evicted);
+        self.notify(evicted);
+        old
+    }
+
+    /// 查找并克隆 value，同时把条目标记为最近使用
+    pub fn get(&self, k: &K) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.get_with(k, V::clone)
+    }
+
+    /// 与 [`get`](Self::get) 相同；分片锁不能跨调用持有，所以这里只能返回克隆而不是引用
+    pub fn get_cloned(&self, k: &K) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.get(k)
+    }
+
+    /// 在分片锁内对 value 调用 `f`，避免克隆大对象
+    pub fn get_with<R>(&self, k: &K, f: impl FnOnce(&V) -> R) -> Option<R> {
+        let now = Instant::now();
+        let stamp = self.tick();
+        let mut evicted = Vec::new();
+        let out = self.with_shard(self.shard_for_key(k), |s| {
+            s.lookup(k, now, stamp, &mut evicted)
+                .map(|idx| f(&s.entry(idx).val))
+        });
+        if out.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        self.notify(evicted);
+        out
+    }
+
+    /// 命中则返回缓存值，否则调用 `f` 计算并写入
+    ///
+    /// `f` 在锁外执行，并发调用同一个 key 时可能重复计算。
+    pub fn get_or_insert_with(&self, k: K, f: impl FnOnce() -> V) -> V
+    where
+        V: Clone,
+    {
+        if let Some(v) = self.get(&k) {
+            return v;
+        }
+        let v = f();
+        self.put(k, v.clone());
+        v
+    }
+
+    pub fn remove(&self, k: &K) -> Option<V> {
+        self.with_shard(self.shard_for_key(k), |s| {
+            let idx = *s.map.get(k)?;
+            Some(s.remove_idx(idx).val)
+        })
+    }
+
+    pub fn contains_key(&self, k: &K) -> bool {
+        let now = Instant::now();
+        let shard = self.shard_for_key(k).lock().unwrap();
+        match shard.map.get(k) {
+            Some(&idx) => !matches!(shard.entry(idx).expires_at, Some(t) if t <= now),
+            None => false,
+        }
+    }
+
+    /// 未过期的条目数
+    pub fn len(&self) -> usize {
+        let now = Instant::now();
+        self.shards
+            .iter()
+            .map(|s| {
+                let s = s.lock().unwrap();
+                if self.ttl_ms < 0 {
+                    s.map.len()
+                } else {
+                    s.live_len(now)
+                }
+            })
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 当前所有条目的总权重（未设置 weigher 时为 0）
+    pub fn weight(&self) -> u64 {
+        self.total_weight.load(Ordering::Relaxed)
+    }
+
+    pub fn clear(&self) {
+        for shard in &self.shards {
+            self.with_shard(shard, Shard::clear);
+        }
+    }
+
+    /// 主动清理所有过期条目（过期条目平时在访问时惰性清理）
+    pub fn purge_expired(&self) {
+        let now = Instant::now();
+        for shard in &self.shards {
+            let mut evicted = Vec::new();
+            self.with_shard(shard, |s| s.purge_expired(now, &mut evicted));
+            self.notify(evicted);
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn reset_stats(&self) {
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+        self.evictions.store(0, Ordering::Relaxed);
+    }
+
+    /// 持有分片锁执行 `f`，并把分片权重的变化同步到总权重
+    fn with_shard<R>(
+        &self,
+        shard: &Mutex<Shard<K, V>>,
+        f: impl FnOnce(&mut Shard<K, V>) -> R,
+    ) -> R {
+        let mut shard = shard.lock().unwrap();
+        let before = shard.weight;
+        let out = f(&mut shard);
+        if shard.weight >= before {
+            self.total_weight
+                .fetch_add(shard.weight - before, Ordering::Relaxed);
+        } else {
+            self.total_weight
+                .fetch_sub(before - shard.weight, Ordering::Relaxed);
+        }
+        out
+    }
+
+    /// 总权重超出预算时，反复淘汰所有分片中最久未访问的条目
+    ///
+    /// 一次只持有一把分片锁：先逐个读取链表尾的访问时钟，再锁住选中的分片淘汰它的尾部。
+    fn evict_over_budget(&self, evicted: &mut Evicted<K, V>) {
+        while self.total_weight.load(Ordering::Relaxed) > self.max_weight {
+            let oldest = self
+                .shards
+                .iter()
+                .filter_map(|s| Some((s.lock().unwrap().tail_stamp()?, s)))
+                .min_by_key(|(stamp, _)| *stamp);
+            let Some((_, shard)) = oldest else {
+                break;
+            };
+            self.with_shard(shard, |s| {
+                if s.tail != NIL {
+                    let e = s.remove_idx(s.tail);
+                    evicted.push((e.key, e.val, EvictionCause::Weight));
+                }
+            });
+        }
+    }
+
+    /// 设置了 weigher 时才推进逻辑时钟，避免无权重的缓存在每次访问时争用同一个原子变量
+    fn tick(&self) -> u64 {
+        if self.weigher.is_some() {
+            self.clock.fetch_add(1, Ordering::Relaxed)
+        } else {
+            0
+        }
+    }
+
+    fn notify(&self, evicted: Evicted<K, V>) {
+        if evicted.is_empty() {
+            return;
+        }
+        self.evictions
+            .fetch_add(evicted.len() as u64, Ordering::Relaxed);
+        if let Some(listener) = &self.listener {
+            for (k, v, cause) in &evicted {
+                listener(k, v, *cause);
+            }
+        }
+    }
+
+    fn calc_expires_at(&self, now: Instant) -> Option<Instant> {
+        if self.ttl_ms < 0 {
+            return None;
+        }
+        now.checked_add(Duration::from_millis(self.ttl_ms as u64))
+    }
+
+    fn shard_for_key(&self, key: &K) -> &Mutex<Shard<K, V>> {
+        let mut h = FxHasher::default();
+        key.hash(&mut h);
+        &self.shards[(h.finish() as usize) & self.shard_mask]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CacheStats, ConcurrentLruCache, EvictionCause};
+    use std::sync::{Arc, Mutex};
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    #[test]
+    fn test_basic_lru_order() {
+        let c: ConcurrentLruCache<i32, i32> = ConcurrentLruCache::new(2);
+        assert!(c.is_empty());
+        c.put(1, 10);
+        c.put(2, 20);
+        assert_eq!(c.get(&1), Some(10));
+        c.put(3, 30);
+        assert!(!c.contains_key(&2));
+        assert!(c.contains_key(&1));
+        assert!(c.contains_key(&3));
+        assert_eq!(c.put(3, 31), Some(30));
+        assert_eq!(c.len(), 2);
+        assert_eq!(c.remove(&3), Some(31));
+        c.clear();
+        assert!(c.is_empty());
+    }
+
+    #[test]
+    fn test_ttl_expire() {
+        let c: ConcurrentLruCache<i32, i32> = ConcurrentLruCache::with_ttl(4, 20);
+        c.put(1, 10);
+        assert!(c.contains_key(&1));
+        sleep(Duration::from_millis(40));
+        assert!(!c.contains_key(&1));
+        assert_eq!(c.len(), 0);
+        assert_eq!(c.get(&1), None);
+        assert_eq!(c.stats().evictions, 1);
+    }
+
+    #[test]
+    fn test_weight_budget_and_listener() {
+        let evicted = Arc::new(Mutex::new(Vec::new()));
+        let sink = evicted.clone();
+        let c: ConcurrentLruCache<&str, String> = ConcurrentLruCache::with_shard_count(100, 1)
+            .with_weigher(10, |_k, v: &String| v.len() as u64)
+            .with_eviction_listener(move |k, _v, cause| sink.lock().unwrap().push((*k, cause)));
+
+        c.put("a", "xxxx".to_string());
+        c.put("b", "xxxx".to_string());
+        assert_eq!(c.weight(), 8);
+        c.get(&"a");
+        c.put("c", "xxxx".to_string());
+        assert!(!c.contains_key(&"b"));
+        assert_eq!(c.weight(), 8);
+
+        // 单个条目超过预算时自身也会被淘汰
+        c.put("huge", "x".repeat(11));
+        assert!(!c.contains_key(&"huge"));
+
+        let evicted = evicted.lock().unwrap();
+        assert_eq!(evicted[0], ("b", EvictionCause::Weight));
+        assert!(evicted.contains(&("huge", EvictionCause::Weight)));
+        assert_eq!(c.stats().evictions, evicted.len() as u64);
+    }
+
+    #[test]
+    fn test_weight_budget_is_global_across_shards() {
+        let c: ConcurrentLruCache<u32, Vec<u8>> = ConcurrentLruCache::with_shard_count(1000, 32)
+            .with_weigher(1000, |_k, v: &Vec<u8>| v.len() as u64);
+        assert!(c.max_weight() / c.shard_count() as u64 <= 100);
+
+        // 远大于每个分片平均份额的条目也能留在缓存里
+        c.put(0, vec![0; 600]);
+        assert!(c.contains_key(&0));
+        assert_eq!(c.weight(), 600);
+
+        // 总预算超出时按全局访问顺序淘汰：0 刚被访问过，最先淘汰的是 1
+        for k in 1..=4 {
+            c.put(k, vec![0; 100]);
+        }
+        c.get(&0);
+        c.put(5, vec![0; 100]);
+        assert!(c.contains_key(&0));
+        assert!(!c.contains_key(&1));
+        assert_eq!(c.weight(), 1000);
+
+        c.put(6, vec![0; 700]);
+        assert!(!c.contains_key(&0));
+        assert!(c.weight() <= 1000);
+        c.clear();
+        assert_eq!(c.weight(), 0);
+    }
+
+    #[test]
+    fn test_stats() {
+        let c: ConcurrentLruCache<i32, i32> = ConcurrentLruCache::new(1);
+        c.put(1, 1);
+        c.get(&1);
+        c.get(&2);
+        c.put(2, 2);
+        assert_eq!(
+            c.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 1,
+                evictions: 1
+            }
+        );
+        assert!((c.stats().hit_rate() - 0.5).abs() < f64::EPSILON);
+        c.reset_stats();
+        assert_eq!(c.stats(), CacheStats::default());
+    }
+
+    #[test]
+    fn test_concurrent_access() {
+        let c: Arc<ConcurrentLruCache<u64, u64>> = Arc::new(ConcurrentLruCache::new(8192));
+        assert!(c.shard_count() > 1);
+        let handles: Vec<_> = (0..8)
+            .map(|t| {
+                let c = c.clone();
+                std::thread::spawn(move || {
+                    for i in 0..2000 {
+                        let k = (t * 2000 + i) % 3000;
+                        let v = c.get_or_insert_with(k, || k * 2);
+                        assert_eq!(v, k * 2);
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+        assert_eq!(c.len(), 3000);
+        let stats = c.stats();
+        assert_eq!(stats.hits + stats.misses, 16000);
+    }
+}
@@ -0,0 +1,264 @@
+//! 计数布隆过滤器（Counting Bloom Filter）
+//!
+//! 把 [`BloomFilter`](super::BloomFilter) 的每一位换成一个 8 位饱和计数器，
+//! 从而支持删除操作。代价是内存占用为普通布隆过滤器的 8 倍。
+
+use std::hash::Hash;
+use std::io;
+use std::path::Path;
+
+use super::bloom_filter::{
+    ByteReader, FilterKind, HEADER_LEN, bit_index, hash_pair, invalid_data, optimal_params,
+    read_header, write_header,
+};
+
+/// 计数布隆过滤器
+///
+/// # 特点
+///
+/// - 支持 [`remove`](Self::remove)：只要删除的元素确实插入过，就不会引入假阴性
+/// - 计数器达到 255 后饱和，不再增加也不会再被减少，避免删除时下溢产生假阴性
+/// - 与 [`BloomFilter`](super::BloomFilter) 使用相同的哈希方式和格式头
+///
+/// # 示例
+///
+/// ```rust
+/// use rust_tools::cw::CountingBloomFilter;
+///
+/// let mut cbf = CountingBloomFilter::with_rate(1000, 0.01);
+/// cbf.insert(&"apple");
+/// assert!(cbf.contains(&"apple"));
+/// assert!(cbf.remove(&"apple"));
+/// assert!(!cbf.contains(&"apple"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CountingBloomFilter {
+    /// 计数器数组
+    counters: Vec<u8>,
+    /// 哈希函数数量
+    hash_count: u32,
+}
+
+impl CountingBloomFilter {
+    /// 创建指定计数器数量和哈希函数数量的计数布隆过滤器
+    pub fn new(counter_count: usize, hash_count: u32) -> Self {
+        Self {
+            counters: vec![0; counter_count.max(1)],
+            hash_count: hash_count.max(1),
+        }
+    }
+
+    /// 根据期望元素数量和假阳性率创建（参数计算与 [`BloomFilter::with_rate`](super::BloomFilter::with_rate) 相同）
+    pub fn with_rate(expected_items: usize, false_positive_rate: f64) -> Self {
+        let (m, k) = optimal_params(expected_items, false_positive_rate);
+        Self::new(m, k)
+    }
+
+    /// 清空所有计数器
+    pub fn clear(&mut self) {
+        self.counters.fill(0);
+    }
+
+    /// 计数器数量
+    pub fn counter_count(&self) -> usize {
+        self.counters.len()
+    }
+
+    /// 哈希函数数量
+    pub fn hash_count(&self) -> u32 {
+        self.hash_count
+    }
+
+    /// 插入一个元素
+    pub fn insert<T: Hash>(&mut self, item: &T) {
+        for idx in self.indexes(item) {
+            let c = &mut self.counters[idx];
+            *c = c.saturating_add(1);
+        }
+    }
+
+    /// 删除一个元素
+    ///
+    /// 元素可能不存在（任一计数器为 0）时不做修改并返回 `false`。
+    /// 注意：删除从未插入过、但因假阳性而 "存在" 的元素会破坏其他元素，调用方需自行保证。
+    pub fn remove<T: Hash>(&mut self, item: &T) -> bool {
+        if !self.contains(item) {
+            return false;
+        }
+        for idx in self.indexes(item) {
+            let c = &mut self.counters[idx];
+            // 同一元素的多个哈希可能落到同一个计数器，这里要再次检查
+            if *c != u8::MAX && *c > 0 {
+                *c -= 1;
+            }
+        }
+        true
+    }
+
+    /// 检查元素是否可能存在
+    pub fn contains<T: Hash>(&self, item: &T) -> bool {
+        self.indexes(item).all(|idx| self.counters[idx] > 0)
+    }
+
+    /// 元素出现次数的上界（所有对应计数器中的最小值）
+    pub fn estimate_count<T: Hash>(&self, item: &T) -> u8 {
+        self.indexes(item)
+            .map(|idx| self.counters[idx])
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// 两个过滤器的计数器数量和哈希函数数量都相同时才能做并集 / 交集
+    pub fn is_compatible(&self, other: &CountingBloomFilter) -> bool {
+        self.counters.len() == other.counters.len() && self.hash_count == other.hash_count
+    }
+
+    /// 并集：计数器饱和相加
+    pub fn union(&mut self, other: &CountingBloomFilter) -> Result<(), &'static str> {
+        if !self.is_compatible(other) {
+            return Err("counting bloom filters have different counter_count or hash_count");
+        }
+        for (a, b) in self.counters.iter_mut().zip(&other.counters) {
+            *a = a.saturating_add(*b);
+        }
+        Ok(())
+    }
+
+    /// 交集：计数器取较小值
+    pub fn intersect(&mut self, other: &CountingBloomFilter) -> Result<(), &'static str> {
+        if !self.is_compatible(other) {
+            return Err("counting bloom filters have different counter_count or hash_count");
+        }
+        for (a, b) in self.counters.iter_mut().zip(&other.counters) {
+            *a = (*a).min(*b);
+        }
+        Ok(())
+    }
+
+    /// 序列化为稳定的二进制格式
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(HEADER_LEN + 12 + self.counters.len());
+        write_header(&mut buf, FilterKind::Counting);
+        buf.extend_from_slice(&(self.counters.len() as u64).to_le_bytes());
+        buf.extend_from_slice(&self.hash_count.to_le_bytes());
+        buf.extend_from_slice(&self.counters);
+        buf
+    }
+
+    /// 从 [`to_bytes`](Self::to_bytes) 的输出恢复
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let mut r = ByteReader::new(read_header(bytes, FilterKind::Counting)?);
+        let counter_count = r.read_len()?;
+        let hash_count = r.read_u32()?;
+        if counter_count == 0 || hash_count == 0 {
+            return Err(invalid_data(
+                "counting bloom filter with zero counters or hashes",
+            ));
+        }
+        let counters = r.take(counter_count)?.to_vec();
+        r.finish()?;
+        Ok(Self {
+            counters,
+            hash_count,
+        })
+    }
+
+    /// 保存到文件
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_bytes())
+    }
+
+    /// 从文件加载
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    fn indexes<T: Hash>(&self, item: &T) -> impl Iterator<Item = usize> + use<T> {
+        let (h1, h2) = hash_pair(item);
+        let m = self.counters.len();
+        (0..self.hash_count).map(move |i| bit_index(h1, h2, i, m))
+    }
+}
+
+impl Default for CountingBloomFilter {
+    fn default() -> Self {
+        Self::with_rate(1024, 0.01)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CountingBloomFilter;
+
+    #[test]
+    fn test_insert_remove() {
+        let mut cbf = CountingBloomFilter::with_rate(1000, 0.01);
+        for i in 0..1000 {
+            cbf.insert(&i);
+        }
+        for i in 0..500 {
+            assert!(cbf.remove(&i));
+        }
+        // 未删除的元素不能出现假阴性
+        for i in 500..1000 {
+            assert!(cbf.contains(&i));
+        }
+        let still_present = (0..500).filter(|i| cbf.contains(i)).count();
+        assert!(still_present < 50, "still present {still_present}");
+    }
+
+    #[test]
+    fn test_remove_absent_is_noop() {
+        let mut cbf = CountingBloomFilter::new(1024, 3);
+        cbf.insert(&"a");
+        let before = cbf.clone();
+        assert!(!cbf.remove(&"definitely-not-there"));
+        assert_eq!(cbf, before);
+    }
+
+    #[test]
+    fn test_saturation_never_underflows() {
+        let mut cbf = CountingBloomFilter::new(16, 2);
+        for _ in 0..300 {
+            cbf.insert(&"hot");
+        }
+        assert_eq!(cbf.estimate_count(&"hot"), u8::MAX);
+        for _ in 0..300 {
+            cbf.remove(&"hot");
+        }
+        assert!(cbf.contains(&"hot"));
+    }
+
+    #[test]
+    fn test_union_intersect() {
+        let mut a = CountingBloomFilter::new(2048, 4);
+        let mut b = CountingBloomFilter::new(2048, 4);
+        a.insert(&"x");
+        a.insert(&"shared");
+        b.insert(&"y");
+        b.insert(&"shared");
+
+        let mut i = a.clone();
+        i.intersect(&b).unwrap();
+        assert!(i.contains(&"shared"));
+
+        a.union(&b).unwrap();
+        assert!(a.contains(&"x") && a.contains(&"y"));
+        assert_eq!(a.estimate_count(&"shared"), 2);
+        assert!(a.union(&CountingBloomFilter::new(10, 4)).is_err());
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let mut cbf = CountingBloomFilter::with_rate(200, 0.01);
+        for i in 0..200 {
+            cbf.insert(&i);
+        }
+        let bytes = cbf.to_bytes();
+        assert_eq!(CountingBloomFilter::from_bytes(&bytes).unwrap(), cbf);
+        // 格式头中的类型不匹配
+        let bf = crate::cw::BloomFilter::new(64, 2);
+        assert!(CountingBloomFilter::from_bytes(&bf.to_bytes()).is_err());
+        assert!(CountingBloomFilter::from_bytes(&bytes[..bytes.len() - 3]).is_err());
+    }
+}
@@ -0,0 +1,869 @@
+//! 基于磁盘的有序映射（Disk-backed SkipMap）
+//!
+//! [`DiskSkipMap`] 与 [`SkipMap`] 共享同一套有序映射 API（`insert` / `get_ref` / `range` / `iter`，
+//! 以及 [`OrderedQuery`] 的 floor / ceiling / select / rank），
+//! 但数据持久化在一个追加写日志（append-only log）文件中：
+//!
+//! - 打开时只解析 key 建立内存索引，value 通过 `mmap` 映射的日志按需反序列化；
+//!   因此读取 value 的方法都返回 `io::Result`，日志里的 value 与 `V` 的类型对不上时
+//!   得到 `InvalidData` 错误；
+//! - 每次写入追加一条带 CRC32 校验的记录，进程在写入中途被杀死时，
+//!   重新打开会截掉尾部不完整 / 校验失败的记录，恢复到最后一次完整写入的状态；
+//! - 失效记录占比超过阈值时自动压缩（compaction）：先写临时文件再原子 `rename` 覆盖。
+//!
+//! ## 文件格式
+//!
+//! ```text
+//! header : magic(8) | version(u32 LE)
+//! record : payload_len(u32 LE) | crc32(payload)(u32 LE) | payload
+//! payload: op(u8) | key_len(u32 LE) | key(json) | value(json, 仅 put)
+//! ```
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::ops::RangeBounds;
+use std::os::unix::fs::FileExt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use super::ordered_query::OrderedQuery;
+use super::skip_list::{SkipMap, SkipRange};
+
+const MAGIC: &[u8; 8] = b"RTSKMAP\0";
+const VERSION: u32 = 1;
+const HEADER_LEN: u64 = 12;
+const RECORD_HEADER_LEN: u64 = 8;
+const OP_PUT: u8 = 1;
+const OP_DEL: u8 = 2;
+
+/// [`DiskSkipMap`] 的打开参数
+#[derive(Debug, Clone)]
+pub struct DiskSkipMapOptions {
+    /// 每次写入后是否 `fsync`（默认 `false`，只保证进程崩溃安全，不保证掉电安全）
+    pub sync_writes: bool,
+    /// 失效字节数达到该值后才会考虑自动压缩
+    pub compact_min_bytes: u64,
+    /// 失效字节数占日志总大小的比例达到该值时自动压缩（0.0 ~ 1.0）
+    pub compact_ratio: f64,
+}
+
+impl Default for DiskSkipMapOptions {
+    fn default() -> Self {
+        Self {
+            sync_writes: false,
+            compact_min_bytes: 1 << 20,
+            compact_ratio: 0.5,
+        }
+    }
+}
+
+struct Slot<V> {
+    /// value 字节在日志文件中的偏移
+    offset: u64,
+    value_len: u32,
+    /// 整条记录（含记录头）的长度，用于统计有效字节数
+    record_len: u64,
+    value: OnceLock<V>,
+}
+
+/// 只读的文件内存映射
+struct Mmap {
+    ptr: *mut libc::c_void,
+    len: usize,
+}
+
+// 映射为只读，且只通过 &self 访问
+unsafe impl Send for Mmap {}
+unsafe impl Sync for Mmap {}
+
+impl Mmap {
+    fn map(file: &File, len: u64) -> io::Result<Self> {
+        use std::os::fd::AsRawFd;
+        if len == 0 {
+            return Ok(Self {
+                ptr: std::ptr::null_mut(),
+                len: 0,
+            });
+        }
+        let len = usize::try_from(len)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "log file too large"))?;
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self { ptr, len })
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        if self.len == 0 {
+            return &[];
+        }
+        unsafe { std::slice::from_raw_parts(self.ptr as *const u8, self.len) }
+    }
+}
+
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        if self.len > 0 {
+            unsafe {
+                libc::munmap(self.ptr, self.len);
+            }
+        }
+    }
+}
+
+/// 持久化到磁盘的有序映射
+///
+/// # 示例
+///
+/// ```rust
+/// use rust_tools::cw::DiskSkipMap;
+///
+/// let path = std::env::temp_dir().join(format!("disk_skip_map_doc_{}.log", std::process::id()));
+/// {
+///     let mut map: DiskSkipMap<String, i32> = DiskSkipMap::open(&path).unwrap();
+///     map.insert("b".to_string(), 2).unwrap();
+///     map.insert("a".to_string(), 1).unwrap();
+/// }
+/// let map: DiskSkipMap<String, i32> = DiskSkipMap::open(&path).unwrap();
+/// assert_eq!(map.get_ref(&"a".to_string()).unwrap(), Some(&1));
+/// let keys: Vec<_> = map.keys().cloned().collect();
+/// assert_eq!(keys, ["a", "b"]);
+/// # std::fs::remove_file(&path).ok();
+/// ```
+pub struct DiskSkipMap<K, V> {
+    path: PathBuf,
+    file: File,
+    mmap: Mmap,
+    index: SkipMap<K, Slot<V>>,
+    /// 日志当前末尾（下一条记录写入位置）
+    end: u64,
+    /// 仍然有效的记录字节数
+    live_bytes: u64,
+    options: DiskSkipMapOptions,
+}
+
+impl<K, V> DiskSkipMap<K, V>
+where
+    K: Ord + Serialize + DeserializeOwned,
+    V: Serialize + DeserializeOwned,
+{
+    /// 使用默认参数打开（不存在则创建）日志文件
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::open_with(path, DiskSkipMapOptions::default())
+    }
+
+    /// 使用指定参数打开（不存在则创建）日志文件
+    ///
+    /// 尾部不完整或校验失败的记录会被截断。
+    pub fn open_with(path: impl AsRef<Path>, options: DiskSkipMapOptions) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        // 上次压缩中途崩溃留下的临时文件，原日志仍然完整，直接丢弃
+        let _ = fs::remove_file(compact_tmp_path(&path));
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        let mut len = file.metadata()?.len();
+        if len < HEADER_LEN {
+            // 新文件，或创建时连 header 都没写完
+            file.set_len(0)?;
+            file.write_all_at(&header_bytes(), 0)?;
+            file.sync_data()?;
+            len = HEADER_LEN;
+        }
+
+        let mmap = Mmap::map(&file, len)?;
+        let (index, valid_end, live_bytes) = {
+            let data = mmap.as_slice();
+            if &data[..MAGIC.len()] != MAGIC {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "not a DiskSkipMap log file",
+                ));
+            }
+            let version = u32::from_le_bytes(data[8..12].try_into().unwrap());
+            if version != VERSION {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unsupported DiskSkipMap log version {version}"),
+                ));
+            }
+            Self::replay(data)
+        };
+
+        let mmap = if valid_end < len {
+            drop(mmap);
+            file.set_len(valid_end)?;
+            file.sync_data()?;
+            Mmap::map(&file, valid_end)?
+        } else {
+            mmap
+        };
+
+        Ok(Self {
+            path,
+            file,
+            mmap,
+            index,
+            end: valid_end,
+            live_bytes,
+            options,
+        })
+    }
+
+    /// 重放日志，返回索引、最后一条完整记录的末尾位置以及有效字节数
+    fn replay(data: &[u8]) -> (SkipMap<K, Slot<V>>, u64, u64) {
+        let mut index: SkipMap<K, Slot<V>> = SkipMap::default();
+        let mut live_bytes = 0_u64;
+        let mut pos = HEADER_LEN;
+        while let Some(record) = parse_record(data, pos) {
+            let Ok(key) = serde_json::from_slice::<K>(record.key) else {
+                break;
+            };
+            let record_len = record.end - pos;
+            if let Some(old) = index.take(&key) {
+                live_bytes -= old.record_len;
+            }
+            if record.op == OP_PUT {
+                index.insert(
+                    key,
+                    Slot {
+                        offset: record.value_offset,
+                        value_len: record.value_len,
+                        record_len,
+                        value: OnceLock::new(),
+                    },
+                );
+                live_bytes += record_len;
+            }
+            pos = record.end;
+        }
+        (index, pos, live_bytes)
+    }
+
+    /// 插入键值对（已存在则覆盖），写入日志后才更新内存索引
+    pub fn insert(&mut self, k: K, v: V) -> io::Result<()> {
+        let key = serde_json::to_vec(&k)?;
+        let value = serde_json::to_vec(&v)?;
+        let (offset, record_len) = self.append(OP_PUT, &key, &value)?;
+        if let Some(old) = self.index.take(&k) {
+            self.live_bytes -= old.record_len;
+        }
+        self.index.insert(
+            k,
+            Slot {
+                offset,
+                value_len: value.len() as u32,
+                record_len,
+                value: OnceLock::from(v),
+            },
+        );
+        self.live_bytes += record_len;
+        self.maybe_compact()
+    }
+
+    /// 删除 key，返回 key 之前是否存在
+    pub fn remove(&mut self, k: &K) -> io::Result<bool> {
+        if !self.index.contains_key(k) {
+            return Ok(false);
+        }
+        let key = serde_json::to_vec(k)?;
+        self.append(OP_DEL, &key, &[])?;
+        if let Some(old) = self.index.take(k) {
+            self.live_bytes -= old.record_len;
+        }
+        self.maybe_compact()?;
+        Ok(true)
+    }
+
+    /// 查找 value；日志中的 value 无法反序列化为 `V` 时返回 `InvalidData`
+    pub fn get_ref(&self, k: &K) -> io::Result<Option<&V>> {
+        self.index
+            .get_ref(k)
+            .map(|slot| self.value(slot))
+            .transpose()
+    }
+
+    pub fn get(&self, k: &K) -> io::Result<Option<V>>
+    where
+        V: Clone,
+    {
+        Ok(self.get_ref(k)?.cloned())
+    }
+
+    pub fn contains_key(&self, k: &K) -> bool {
+        self.index.contains_key(k)
+    }
+
+    /// 返回 key 落在 `r` 内的双端迭代器，value 在迭代到时才反序列化；起点大于终点时为空
+    ///
+    /// 与 [`SkipMap`] 的 [`OrderedQuery::range`] 一致，`..=k`、`k..`、`..` 都可以。
+    pub fn range<R: RangeBounds<K>>(&self, r: R) -> DiskRange<'_, K, V> {
+        DiskRange {
+            inner: self.index.range(r),
+            map: self,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = io::Result<(&K, &V)>> {
+        self.index
+            .iter()
+            .map(|(k, slot)| Ok((k, self.value(slot)?)))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.index.keys()
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = io::Result<&V>> {
+        self.index.iter().map(|(_, slot)| self.value(slot))
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 日志文件当前大小（字节）
+    pub fn log_size(&self) -> u64 {
+        self.end
+    }
+
+    /// 将已写入的记录刷到磁盘
+    pub fn sync(&self) -> io::Result<()> {
+        self.file.sync_data()
+    }
+
+    /// 立即压缩日志：只保留每个 key 的最新记录
+    ///
+    /// 新日志逐条流式写入同目录下的临时文件并 `fsync`，再原子替换原文件，
+    /// 因此压缩过程中崩溃不会丢失数据，内存中也只保留一条记录。
+    pub fn compact(&mut self) -> io::Result<()> {
+        let tmp_path = compact_tmp_path(&self.path);
+        let mut offsets = Vec::with_capacity(self.index.len());
+        let mut end = HEADER_LEN;
+        {
+            let tmp = File::create(&tmp_path)?;
+            let mut out = BufWriter::new(&tmp);
+            out.write_all(&header_bytes())?;
+            let mut record = Vec::new();
+            for (k, slot) in self.index.iter() {
+                let key = serde_json::to_vec(k)?;
+                let value = self.value_bytes(slot)?;
+                record.clear();
+                encode_record(&mut record, OP_PUT, &key, &value);
+                out.write_all(&record)?;
+                end += record.len() as u64;
+                offsets.push((end - value.len() as u64, record.len() as u64));
+            }
+            out.flush()?;
+            drop(out);
+            tmp.sync_all()?;
+        }
+        fs::rename(&tmp_path, &self.path)?;
+        if let Some(dir) = self.path.parent() {
+            // 目录 fsync 失败（例如部分文件系统不支持）不影响数据正确性
+            if let Ok(dir) = File::open(if dir.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                dir
+            }) {
+                let _ = dir.sync_all();
+            }
+        }
+
+        let file = OpenOptions::new().read(true).write(true).open(&self.path)?;
+        self.mmap = Mmap::map(&file, end)?;
+        self.file = file;
+        self.end = end;
+        self.live_bytes = end - HEADER_LEN;
+        for ((_, slot), (offset, record_len)) in self.index.iter_mut().zip(offsets) {
+            slot.offset = offset;
+            slot.record_len = record_len;
+        }
+        Ok(())
+    }
+
+    fn maybe_compact(&mut self) -> io::Result<()> {
+        let total = self.end - HEADER_LEN;
+        let dead = total - self.live_bytes;
+        if dead >= self.options.compact_min_bytes
+            && dead as f64 >= total as f64 * self.options.compact_ratio
+        {
+            self.compact()?;
+        }
+        Ok(())
+    }
+
+    /// 追加一条记录，返回 (value 偏移, 记录长度)
+    fn append(&mut self, op: u8, key: &[u8], value: &[u8]) -> io::Result<(u64, u64)> {
+        let mut buf = Vec::with_capacity(RECORD_HEADER_LEN as usize + 5 + key.len() + value.len());
+        encode_record(&mut buf, op, key, value);
+        // 单次写入整条记录；被中途杀死时只会留下一条残缺的尾记录，重新打开时被截断
+        self.file.write_all_at(&buf, self.end)?;
+        if self.options.sync_writes {
+            self.file.sync_data()?;
+        }
+        let record_len = buf.len() as u64;
+        self.end += record_len;
+        Ok((self.end - value.len() as u64, record_len))
+    }
+
+    fn value_bytes(&self, slot: &Slot<V>) -> io::Result<Vec<u8>> {
+        let start = slot.offset as usize;
+        let end = start + slot.value_len as usize;
+        if let Some(bytes) = self.mmap.as_slice().get(start..end) {
+            return Ok(bytes.to_vec());
+        }
+        let mut bytes = vec![0; slot.value_len as usize];
+        self.file.read_exact_at(&mut bytes, slot.offset)?;
+        Ok(bytes)
+    }
+
+    fn value<'a>(&'a self, slot: &'a Slot<V>) -> io::Result<&'a V> {
+        // 本次会话写入的 value 已经缓存；只有打开时映射区域内的记录需要反序列化，
+        // 这些记录已通过 CRC 校验，反序列化失败只可能是 V 的类型与写入时不一致。
+        if let Some(v) = slot.value.get() {
+            return Ok(v);
+        }
+        let start = slot.offset as usize;
+        let parsed = match self
+            .mmap
+            .as_slice()
+            .get(start..start + slot.value_len as usize)
+        {
+            Some(bytes) => serde_json::from_slice(bytes),
+            None => serde_json::from_slice(&self.value_bytes(slot)?),
+        };
+        let v = parsed.map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("DiskSkipMap: value does not match the stored type: {e}"),
+            )
+        })?;
+        Ok(slot.value.get_or_init(|| v))
+    }
+}
+
+/// [`DiskSkipMap::range`] 返回的双端迭代器
+pub struct DiskRange<'a, K, V> {
+    inner: SkipRange<'a, K, Slot<V>>,
+    map: &'a DiskSkipMap<K, V>,
+}
+
+impl<'a, K, V> Iterator for DiskRange<'a, K, V>
+where
+    K: Ord + Serialize + DeserializeOwned,
+    V: Serialize + DeserializeOwned,
+{
+    type Item = io::Result<(&'a K, &'a V)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (k, slot) = self.inner.next()?;
+        Some(self.map.value(slot).map(|v| (k, v)))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for DiskRange<'_, K, V>
+where
+    K: Ord + Serialize + DeserializeOwned,
+    V: Serialize + DeserializeOwned,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (k, slot) = self.inner.next_back()?;
+        Some(self.map.value(slot).map(|v| (k, v)))
+    }
+}
+
+impl<K, V> OrderedQuery<K> for DiskSkipMap<K, V>
+where
+    K: Ord + Serialize + DeserializeOwned,
+    V: Serialize + DeserializeOwned,
+{
+    type Item<'a>
+        = io::Result<(&'a K, &'a V)>
+    where
+        Self: 'a;
+    type Iter<'a>
+        = DiskRange<'a, K, V>
+    where
+        Self: 'a;
+
+    fn range<R: RangeBounds<K>>(&self, range: R) -> Self::Iter<'_> {
+        DiskSkipMap::range(self, range)
+    }
+
+    fn select(&self, k: usize) -> Option<Self::Item<'_>> {
+        let (k, slot) = self.index.select(k)?;
+        Some(self.value(slot).map(|v| (k, v)))
+    }
+
+    fn rank(&self, key: &K) -> usize {
+        self.index.rank(key)
+    }
+}
+
+impl<K: std::fmt::Debug, V: std::fmt::Debug> std::fmt::Debug for DiskSkipMap<K, V>
+where
+    K: Ord + Serialize + DeserializeOwned,
+    V: Serialize + DeserializeOwned,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut map = f.debug_map();
+        for (k, slot) in self.index.iter() {
+            match self.value(slot) {
+                Ok(v) => map.entry(k, v),
+                Err(e) => map.entry(k, &e),
+            };
+        }
+        map.finish()
+    }
+}
+
+struct RawRecord<'a> {
+    op: u8,
+    key: &'a [u8],
+    value_offset: u64,
+    value_len: u32,
+    end: u64,
+}
+
+/// 解析 `pos` 处的一条记录；记录残缺、校验失败或格式非法时返回 `None`
+fn parse_record(data: &[u8], pos: u64) -> Option<RawRecord<'_>> {
+    let start = usize::try_from(pos).ok()?;
+    let head = data.get(start..start + RECORD_HEADER_LEN as usize)?;
+    let payload_len = u32::from_le_bytes(head[..4].try_into().unwrap()) as usize;
+    let checksum = u32::from_le_bytes(head[4..].try_into().unwrap());
+    let payload_start = start + RECORD_HEADER_LEN as usize;
+    let payload = data.get(payload_start..payload_start.checked_add(payload_len)?)?;
+    if crc32(payload) != checksum || payload.len() < 5 {
+        return None;
+    }
+    let op = payload[0];
+    let key_len = u32::from_le_bytes(payload[1..5].try_into().unwrap()) as usize;
+    let key = payload.get(5..5usize.checked_add(key_len)?)?;
+    let value_len = payload_len - 5 - key_len;
+    match op {
+        OP_PUT => {}
+        OP_DEL if value_len == 0 => {}
+        _ => return None,
+    }
+    let end = (payload_start + payload_len) as u64;
+    Some(RawRecord {
+        op,
+        key,
+        value_offset: end - value_len as u64,
+        value_len: value_len as u32,
+        end,
+    })
+}
+
+fn encode_record(buf: &mut Vec<u8>, op: u8, key: &[u8], value: &[u8]) {
+    let mut payload = Vec::with_capacity(5 + key.len() + value.len());
+    payload.push(op);
+    payload.extend_from_slice(&(key.len() as u32).to_le_bytes());
+    payload.extend_from_slice(key);
+    payload.extend_from_slice(value);
+    buf.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    buf.extend_from_slice(&crc32(&payload).to_le_bytes());
+    buf.extend_from_slice(&payload);
+}
+
+fn header_bytes() -> [u8; HEADER_LEN as usize] {
+    let mut header = [0; HEADER_LEN as usize];
+    header[..8].copy_from_slice(MAGIC);
+    header[8..].copy_from_slice(&VERSION.to_le_bytes());
+    header
+}
+
+fn compact_tmp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".compact");
+    path.with_file_name(name)
+}
+
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0_u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut c = i as u32;
+        let mut j = 0;
+        while j < 8 {
+            c = if c & 1 != 0 {
+                0xEDB8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            j += 1;
+        }
+        table[i] = c;
+        i += 1;
+    }
+    table
+};
+
+/// CRC-32（IEEE 802.3）
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+    for &b in data {
+        crc = CRC32_TABLE[((crc ^ b as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::{Command, Stdio};
+    use std::time::{Duration, Instant};
+
+    const CRASH_ENV: &str = "RUST_TOOLS_DISK_SKIP_MAP_CRASH_PATH";
+    const CRASH_KEYS: u64 = 64;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "rust_tools_disk_skip_map_{}_{}",
+            std::process::id(),
+            name
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("map.log")
+    }
+
+    fn small_compaction() -> DiskSkipMapOptions {
+        DiskSkipMapOptions {
+            sync_writes: false,
+            compact_min_bytes: 256,
+            compact_ratio: 0.5,
+        }
+    }
+
+    #[test]
+    fn crc32_known_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn insert_get_and_reopen() {
+        let path = temp_path("reopen");
+        {
+            let mut map: DiskSkipMap<i32, String> = DiskSkipMap::open(&path).unwrap();
+            for i in (0..20).rev() {
+                map.insert(i, format!("v{i}")).unwrap();
+            }
+            map.insert(3, "three".to_string()).unwrap();
+            assert!(map.remove(&5).unwrap());
+            assert!(!map.remove(&5).unwrap());
+            assert_eq!(map.len(), 19);
+        }
+        let map: DiskSkipMap<i32, String> = DiskSkipMap::open(&path).unwrap();
+        assert_eq!(map.len(), 19);
+        assert_eq!(map.get_ref(&3).unwrap().map(String::as_str), Some("three"));
+        assert_eq!(map.get(&4).unwrap(), Some("v4".to_string()));
+        assert!(!map.contains_key(&5));
+        let keys: Vec<_> = map.keys().copied().collect();
+        let expected: Vec<_> = (0..20).filter(|&i| i != 5).collect();
+        assert_eq!(keys, expected);
+    }
+
+    #[test]
+    fn range_query() {
+        let path = temp_path("range");
+        let mut map: DiskSkipMap<i32, i32> = DiskSkipMap::open(&path).unwrap();
+        for i in 1..=10 {
+            map.insert(i, i * 100).unwrap();
+        }
+        drop(map);
+        let map: DiskSkipMap<i32, i32> = DiskSkipMap::open(&path).unwrap();
+        let range: Vec<_> = map.range(3..7).collect::<io::Result<_>>().unwrap();
+        assert_eq!(range.len(), 4);
+        assert_eq!(range[0], (&3, &300));
+        assert_eq!(range[3], (&6, &600));
+        let tail: Vec<i32> = map.range(8..).rev().map(|e| *e.unwrap().0).collect();
+        assert_eq!(tail, [10, 9, 8]);
+        assert_eq!(map.range(..=2).count(), 2);
+        assert_eq!(map.range(..).count(), 10);
+        assert!(map.floor(&0).is_none());
+        assert_eq!(map.ceiling(&5).unwrap().unwrap(), (&5, &500));
+        assert_eq!(map.select(2).unwrap().unwrap(), (&3, &300));
+        assert_eq!(map.rank(&4), 3);
+    }
+
+    #[test]
+    fn value_type_mismatch_is_an_error() {
+        let path = temp_path("mismatch");
+        let mut map: DiskSkipMap<i32, String> = DiskSkipMap::open(&path).unwrap();
+        map.insert(1, "one".to_string()).unwrap();
+        drop(map);
+        let map: DiskSkipMap<i32, u64> = DiskSkipMap::open(&path).unwrap();
+        assert_eq!(map.len(), 1);
+        let err = map.get(&1).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(map.iter().next().unwrap().is_err());
+        assert!(map.get(&2).unwrap().is_none());
+    }
+
+    #[test]
+    fn compaction_shrinks_log_and_keeps_data() {
+        let path = temp_path("compact");
+        let mut map: DiskSkipMap<String, u64> =
+            DiskSkipMap::open_with(&path, small_compaction()).unwrap();
+        for round in 0..50_u64 {
+            for k in 0..8 {
+                map.insert(format!("key{k}"), round * 10 + k).unwrap();
+            }
+        }
+        map.remove(&"key0".to_string()).unwrap();
+        // 400 次写入、只剩 7 个有效 key，自动压缩后日志应远小于全部记录
+        assert!(map.log_size() < 2048, "log size {}", map.log_size());
+        assert_eq!(map.get(&"key7".to_string()).unwrap(), Some(497));
+
+        map.compact().unwrap();
+        drop(map);
+        let map: DiskSkipMap<String, u64> = DiskSkipMap::open(&path).unwrap();
+        assert_eq!(map.len(), 7);
+        assert_eq!(map.get(&"key0".to_string()).unwrap(), None);
+        assert_eq!(map.get(&"key3".to_string()).unwrap(), Some(493));
+        assert!(!compact_tmp_path(&path).exists());
+    }
+
+    #[test]
+    fn torn_tail_is_truncated_on_reopen() {
+        let path = temp_path("torn");
+        {
+            let mut map: DiskSkipMap<i32, String> = DiskSkipMap::open(&path).unwrap();
+            for i in 0..5 {
+                map.insert(i, "x".repeat(i as usize + 1)).unwrap();
+            }
+        }
+        let full = fs::read(&path).unwrap();
+        let record_ends: Vec<u64> = {
+            let mut ends = vec![HEADER_LEN];
+            let mut pos = HEADER_LEN;
+            while let Some(r) = parse_record(&full, pos) {
+                pos = r.end;
+                ends.push(pos);
+            }
+            ends
+        };
+        assert_eq!(record_ends.len(), 6);
+
+        // 在每个字节处截断，模拟写入中途崩溃
+        for cut in HEADER_LEN..full.len() as u64 {
+            fs::write(&path, &full[..cut as usize]).unwrap();
+            let map: DiskSkipMap<i32, String> = DiskSkipMap::open(&path).unwrap();
+            let complete = record_ends.iter().filter(|&&e| e <= cut).count() - 1;
+            assert_eq!(map.len(), complete, "cut at {cut}");
+            assert_eq!(map.log_size(), record_ends[complete]);
+            drop(map);
+            assert_eq!(fs::metadata(&path).unwrap().len(), record_ends[complete]);
+        }
+
+        // 损坏的尾记录（校验失败）同样被丢弃
+        let mut corrupted = full.clone();
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 0xFF;
+        fs::write(&path, &corrupted).unwrap();
+        let mut map: DiskSkipMap<i32, String> = DiskSkipMap::open(&path).unwrap();
+        assert_eq!(map.len(), 4);
+        map.insert(9, "after".to_string()).unwrap();
+        drop(map);
+        let map: DiskSkipMap<i32, String> = DiskSkipMap::open(&path).unwrap();
+        assert_eq!(map.len(), 5);
+        assert_eq!(map.get(&9).unwrap(), Some("after".to_string()));
+    }
+
+    #[test]
+    fn rejects_foreign_file() {
+        let path = temp_path("foreign");
+        fs::write(&path, b"definitely not a log file").unwrap();
+        let err = DiskSkipMap::<i32, i32>::open(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    /// 子进程写入端：只有设置了环境变量时才会运行（由 `writer_killed_mid_write` 启动）
+    #[test]
+    fn crash_writer_child() {
+        let Ok(path) = std::env::var(CRASH_ENV) else {
+            return;
+        };
+        let mut map: DiskSkipMap<u64, u64> =
+            DiskSkipMap::open_with(&path, small_compaction()).unwrap();
+        for i in 0.. {
+            map.insert(i % CRASH_KEYS, i).unwrap();
+        }
+    }
+
+    #[test]
+    fn writer_killed_mid_write() {
+        if std::env::var(CRASH_ENV).is_ok() {
+            return;
+        }
+        let path = temp_path("killed");
+        for attempt in 0..3 {
+            let _ = fs::remove_file(&path);
+            let mut child = Command::new(std::env::current_exe().unwrap())
+                .args([
+                    "cw::disk_skip_map::tests::crash_writer_child",
+                    "--exact",
+                    "--test-threads=1",
+                ])
+                .env(CRASH_ENV, &path)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .unwrap();
+            let deadline = Instant::now() + Duration::from_secs(10);
+            while Instant::now() < deadline
+                && fs::metadata(&path).map(|m| m.len()).unwrap_or(0) <= HEADER_LEN
+            {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            // 让子进程跑一段时间（期间会多次触发自动压缩）后再杀掉
+            std::thread::sleep(Duration::from_millis(30 + 40 * attempt));
+            child.kill().unwrap();
+            child.wait().unwrap();
+
+            // 恢复后的状态必须恰好等于前 N 次写入的结果
+            let map: DiskSkipMap<u64, u64> = DiskSkipMap::open(&path).unwrap();
+            let last = map
+                .values()
+                .map(|v| *v.unwrap())
+                .max()
+                .expect("writer made progress");
+            let expected: Vec<(u64, u64)> = (0..CRASH_KEYS.min(last + 1))
+                .map(|k| (k, last - (last + CRASH_KEYS - k) % CRASH_KEYS))
+                .collect();
+            let actual: Vec<(u64, u64)> = map
+                .iter()
+                .map(|e| e.map(|(k, v)| (*k, *v)))
+                .collect::<io::Result<_>>()
+                .unwrap();
+            assert_eq!(actual, expected, "attempt {attempt}");
+        }
+    }
+}
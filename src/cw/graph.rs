@@ -4,6 +4,15 @@ use std::hash::Hash;
 
 use crate::commonw::types::{FastMap, FastSet};
 
+mod centrality;
+mod connectivity;
+mod flow;
+mod indexed;
+mod matching;
+mod paths;
+
+pub use flow::MaxFlow;
+
 const INF: f64 = f64::INFINITY;
 
 #[derive(Clone, Debug, PartialEq)]
@@ -0,0 +1,245 @@
+use std::collections::{BinaryHeap, VecDeque};
+use std::hash::Hash;
+
+use crate::commonw::types::FastMap;
+
+use super::indexed::{Indexed, MinScored};
+use super::{DirectedGraph, UndirectedGraph, WeightedDirectedGraph};
+
+const EPS: f64 = 1e-12;
+
+impl<T> Indexed<T>
+where
+    T: Eq + Hash + Clone,
+{
+    /// Brandes 算法。`weighted` 为假时用 BFS，否则用 Dijkstra（边权须为正）。
+    /// 无向图的每条边存了两次，结果需要除以 2。
+    fn betweenness(&self, weighted: bool, normalized: bool) -> FastMap<T, f64> {
+        let n = self.len();
+        let mut centrality = vec![0.0; n];
+        for s in 0..n {
+            let mut order = Vec::with_capacity(n);
+            let mut preds: Vec<Vec<usize>> = vec![Vec::new(); n];
+            let mut sigma = vec![0.0f64; n];
+            let mut dist = vec![f64::INFINITY; n];
+            sigma[s] = 1.0;
+            dist[s] = 0.0;
+
+            if weighted {
+                let mut done = vec![false; n];
+                let mut heap = BinaryHeap::from([MinScored(0.0, s)]);
+                while let Some(MinScored(d, u)) = heap.pop() {
+                    if done[u] || d > dist[u] {
+                        continue;
+                    }
+                    done[u] = true;
+                    order.push(u);
+                    for &(v, w) in &self.adj[u] {
+                        let candidate = dist[u] + w;
+                        if candidate < dist[v] - EPS {
+                            dist[v] = candidate;
+                            sigma[v] = sigma[u];
+                            preds[v] = vec![u];
+                            heap.push(MinScored(candidate, v));
+                        } else if (candidate - dist[v]).abs() <= EPS && !done[v] {
+                            sigma[v] += sigma[u];
+                            preds[v].push(u);
+                        }
+                    }
+                }
+            } else {
+                let mut q = VecDeque::from([s]);
+                while let Some(u) = q.pop_front() {
+                    order.push(u);
+                    for &(v, _) in &self.adj[u] {
+                        if dist[v].is_infinite() {
+                            dist[v] = dist[u] + 1.0;
+                            q.push_back(v);
+                        }
+                        if dist[v] == dist[u] + 1.0 {
+                            sigma[v] += sigma[u];
+                            preds[v].push(u);
+                        }
+                    }
+                }
+            }
+
+            // 逆拓扑序累加依赖值
+            let mut delta = vec![0.0; n];
+            for &w in order.iter().rev() {
+                for &v in &preds[w] {
+                    delta[v] += sigma[v] / sigma[w] * (1.0 + delta[w]);
+                }
+                if w != s {
+                    centrality[w] += delta[w];
+                }
+            }
+        }
+
+        let mut scale = if self.directed { 1.0 } else { 0.5 };
+        if normalized && n > 2 {
+            let pairs = ((n - 1) * (n - 2)) as f64;
+            scale *= if self.directed {
+                1.0 / pairs
+            } else {
+                2.0 / pairs
+            };
+        }
+        self.nodes
+            .iter()
+            .cloned()
+            .zip(centrality.into_iter().map(|c| c * scale))
+            .collect()
+    }
+}
+
+impl<T> DirectedGraph<T>
+where
+    T: Eq + Hash + Clone,
+{
+    /// PageRank（幂迭代）。`damping` 通常取 0.85；两轮之间 L1 变化小于 `tolerance`
+    /// 或达到 `max_iter` 时停止。出度为 0 的节点把分数平均分给所有节点，结果之和为 1。
+    pub fn page_rank(&self, damping: f64, max_iter: usize, tolerance: f64) -> FastMap<T, f64> {
+        let g = Indexed::from_directed(self);
+        let n = g.len();
+        if n == 0 {
+            return FastMap::default();
+        }
+        let nf = n as f64;
+        let mut rank = vec![1.0 / nf; n];
+        for _ in 0..max_iter {
+            let dangling: f64 = (0..n)
+                .filter(|&u| g.adj[u].is_empty())
+                .map(|u| rank[u])
+                .sum();
+            let base = (1.0 - damping) / nf + damping * dangling / nf;
+            let mut next = vec![base; n];
+            for (u, edges) in g.adj.iter().enumerate() {
+                if edges.is_empty() {
+                    continue;
+                }
+                let share = damping * rank[u] / edges.len() as f64;
+                for &(v, _) in edges {
+                    next[v] += share;
+                }
+            }
+            let diff: f64 = rank.iter().zip(&next).map(|(a, b)| (a - b).abs()).sum();
+            rank = next;
+            if diff < tolerance {
+                break;
+            }
+        }
+        g.nodes.into_iter().zip(rank).collect()
+    }
+
+    /// 介数中心性（Brandes 算法，O(VE)）：经过该节点的最短路径比例之和。
+    /// `normalized` 为真时除以 `(n-1)(n-2)`。
+    pub fn betweenness_centrality(&self, normalized: bool) -> FastMap<T, f64> {
+        Indexed::from_directed(self).betweenness(false, normalized)
+    }
+}
+
+impl<T> UndirectedGraph<T>
+where
+    T: Eq + Hash + Clone,
+{
+    /// 介数中心性（Brandes 算法）；`normalized` 为真时除以 `(n-1)(n-2)/2`
+    pub fn betweenness_centrality(&self, normalized: bool) -> FastMap<T, f64> {
+        Indexed::from_undirected(self).betweenness(false, normalized)
+    }
+}
+
+impl<T> WeightedDirectedGraph<T>
+where
+    T: Eq + Hash + Clone,
+{
+    /// 按边权计算最短路的介数中心性（边权须为正）
+    pub fn betweenness_centrality(&self, normalized: bool) -> FastMap<T, f64> {
+        Indexed::from_weighted_directed(self).betweenness(true, normalized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cw::{DirectedGraph, UndirectedGraph, WeightedDirectedGraph};
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn test_page_rank() {
+        // 对称的环：每个节点分数相同
+        let mut ring = DirectedGraph::new();
+        for i in 0..4 {
+            ring.add_edge(i, (i + 1) % 4);
+        }
+        let pr = ring.page_rank(0.85, 100, 1e-12);
+        assert!(pr.values().all(|&v| close(v, 0.25)));
+
+        // A、B、C 都指向 D，D 指回 A：D 最高，A 次之
+        let mut g = DirectedGraph::new();
+        g.add_edge("A", "D");
+        g.add_edge("B", "D");
+        g.add_edge("C", "D");
+        g.add_edge("D", "A");
+        g.add_node("E"); // 孤立（悬挂）节点
+        let pr = g.page_rank(0.85, 200, 1e-12);
+        assert!(close(pr.values().sum::<f64>(), 1.0));
+        assert!(pr[&"D"] > pr[&"A"]);
+        assert!(pr[&"A"] > pr[&"B"]);
+        assert!(close(pr[&"B"], pr[&"C"]));
+        assert!(close(pr[&"B"], pr[&"E"]));
+
+        assert!(
+            DirectedGraph::<i32>::new()
+                .page_rank(0.85, 10, 1e-9)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_betweenness_path_and_star() {
+        // 路径 0-1-2-3-4：中心度 0, 3, 4, 3, 0
+        let mut path = UndirectedGraph::new();
+        for i in 0..4 {
+            path.add_edge(i, i + 1);
+        }
+        let bc = path.betweenness_centrality(false);
+        for (node, expected) in [(0, 0.0), (1, 3.0), (2, 4.0), (3, 3.0), (4, 0.0)] {
+            assert!(close(bc[&node], expected), "{node}: {}", bc[&node]);
+        }
+
+        // 星形：中心节点在所有叶子对之间，归一化后为 1
+        let mut star = UndirectedGraph::new();
+        for leaf in 1..=5 {
+            star.add_edge(0, leaf);
+        }
+        let bc = star.betweenness_centrality(true);
+        assert!(close(bc[&0], 1.0));
+        assert!(close(bc[&3], 0.0));
+    }
+
+    #[test]
+    fn test_betweenness_directed_and_weighted() {
+        // 菱形 s→a→t, s→b→t：a、b 各承担一半
+        let mut g = DirectedGraph::new();
+        for (u, v) in [("s", "a"), ("s", "b"), ("a", "t"), ("b", "t")] {
+            g.add_edge(u, v);
+        }
+        let bc = g.betweenness_centrality(false);
+        assert!(close(bc[&"a"], 0.5));
+        assert!(close(bc[&"b"], 0.5));
+        assert!(close(bc[&"s"], 0.0));
+
+        // 加权后 s→b→t 更短，b 承担全部
+        let mut w = WeightedDirectedGraph::new();
+        w.add_edge("s", "a", 5.0);
+        w.add_edge("a", "t", 5.0);
+        w.add_edge("s", "b", 1.0);
+        w.add_edge("b", "t", 1.0);
+        let bc = w.betweenness_centrality(false);
+        assert!(close(bc[&"b"], 1.0));
+        assert!(close(bc[&"a"], 0.0));
+    }
+}
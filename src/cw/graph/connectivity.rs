@@ -0,0 +1,207 @@
+use std::hash::Hash;
+
+use super::UndirectedGraph;
+use super::indexed::Indexed;
+
+/// Tarjan 低链接值，迭代实现以免深图爆栈；返回 (割点标记, 桥)
+fn low_link(adj: &[Vec<(usize, f64)>]) -> (Vec<bool>, Vec<(usize, usize)>) {
+    let n = adj.len();
+    let mut disc = vec![usize::MAX; n];
+    let mut low = vec![0; n];
+    let mut is_cut = vec![false; n];
+    let mut bridges = Vec::new();
+    let mut timer = 0;
+
+    for root in 0..n {
+        if disc[root] != usize::MAX {
+            continue;
+        }
+        disc[root] = timer;
+        low[root] = timer;
+        timer += 1;
+        let mut root_children = 0;
+        // (节点, 父节点, 下一个要看的邻接下标)
+        let mut stack = vec![(root, usize::MAX, 0usize)];
+        while let Some(top) = stack.last_mut() {
+            let (u, parent) = (top.0, top.1);
+            if let Some(&(v, _)) = adj[u].get(top.2) {
+                top.2 += 1;
+                if v == u || v == parent {
+                    continue;
+                }
+                if disc[v] == usize::MAX {
+                    disc[v] = timer;
+                    low[v] = timer;
+                    timer += 1;
+                    if u == root {
+                        root_children += 1;
+                    }
+                    stack.push((v, u, 0));
+                } else {
+                    low[u] = low[u].min(disc[v]);
+                }
+            } else {
+                stack.pop();
+                if parent != usize::MAX {
+                    low[parent] = low[parent].min(low[u]);
+                    if low[u] > disc[parent] {
+                        bridges.push((parent, u));
+                    }
+                    if parent != root && low[u] >= disc[parent] {
+                        is_cut[parent] = true;
+                    }
+                }
+            }
+        }
+        is_cut[root] = root_children > 1;
+    }
+    (is_cut, bridges)
+}
+
+impl<T> UndirectedGraph<T>
+where
+    T: Eq + Hash + Clone,
+{
+    /// 割点：删除后会让所在连通分量断开的节点
+    pub fn articulation_points(&self) -> Vec<T> {
+        let g = Indexed::from_undirected(self);
+        let (is_cut, _) = low_link(&g.adj);
+        g.nodes
+            .into_iter()
+            .zip(is_cut)
+            .filter_map(|(node, cut)| cut.then_some(node))
+            .collect()
+    }
+
+    /// 桥：删除后会让所在连通分量断开的边
+    pub fn bridges(&self) -> Vec<(T, T)> {
+        let g = Indexed::from_undirected(self);
+        let (_, bridges) = low_link(&g.adj);
+        bridges
+            .into_iter()
+            .map(|(u, v)| (g.nodes[u].clone(), g.nodes[v].clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cw::UndirectedGraph;
+
+    fn normalized(mut bridges: Vec<(i32, i32)>) -> Vec<(i32, i32)> {
+        for b in &mut bridges {
+            if b.0 > b.1 {
+                *b = (b.1, b.0);
+            }
+        }
+        bridges.sort();
+        bridges
+    }
+
+    #[test]
+    fn test_articulation_points_and_bridges() {
+        // 两个三角形 0-1-2 和 3-4-5 由桥 2-3 相连，6 挂在 5 上
+        let mut g = UndirectedGraph::new();
+        for (u, v) in [
+            (0, 1),
+            (1, 2),
+            (2, 0),
+            (2, 3),
+            (3, 4),
+            (4, 5),
+            (5, 3),
+            (5, 6),
+        ] {
+            g.add_edge(u, v);
+        }
+        g.add_node(7);
+
+        let mut points = g.articulation_points();
+        points.sort();
+        assert_eq!(points, [2, 3, 5]);
+        assert_eq!(normalized(g.bridges()), [(2, 3), (5, 6)]);
+    }
+
+    #[test]
+    fn test_cycle_and_path() {
+        let mut cycle = UndirectedGraph::new();
+        for i in 0..5 {
+            cycle.add_edge(i, (i + 1) % 5);
+        }
+        assert!(cycle.articulation_points().is_empty());
+        assert!(cycle.bridges().is_empty());
+
+        // 长链：每条边都是桥，内部节点都是割点；顺便验证迭代实现不会爆栈
+        let mut path = UndirectedGraph::new();
+        for i in 0..100_000 {
+            path.add_edge(i, i + 1);
+        }
+        assert_eq!(path.bridges().len(), 100_000);
+        assert_eq!(path.articulation_points().len(), 99_999);
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        use rand::{RngExt, SeedableRng, rngs::StdRng};
+
+        let mut rng = StdRng::seed_from_u64(11);
+        for _ in 0..30 {
+            let n = rng.random_range(2..9);
+            let mut g = UndirectedGraph::new();
+            for i in 0..n {
+                g.add_node(i);
+                for j in i + 1..n {
+                    if rng.random_range(0..3) == 0 {
+                        g.add_edge(i, j);
+                    }
+                }
+            }
+            let groups = g.num_groups();
+
+            let mut expected_points = Vec::new();
+            for i in 0..n {
+                let mut h = UndirectedGraph::new();
+                for u in g.nodes() {
+                    if u != i {
+                        h.add_node(u);
+                        for v in g.adj(&u) {
+                            if v != i {
+                                h.add_edge(u, v);
+                            }
+                        }
+                    }
+                }
+                // 删除孤立点会让分量数减 1，其余情况分量数增加才算割点
+                let isolated = g.degree(&i) == 0;
+                if h.num_groups() > groups - usize::from(isolated) {
+                    expected_points.push(i);
+                }
+            }
+            let mut points = g.articulation_points();
+            points.sort();
+            assert_eq!(points, expected_points);
+
+            let mut expected_bridges = Vec::new();
+            for u in 0..n {
+                for v in g.adj(&u) {
+                    if u < v {
+                        let mut h = UndirectedGraph::new();
+                        for x in g.nodes() {
+                            h.add_node(x);
+                            for y in g.adj(&x) {
+                                if (x, y) != (u, v) && (x, y) != (v, u) {
+                                    h.add_edge(x, y);
+                                }
+                            }
+                        }
+                        if h.num_groups() > groups {
+                            expected_bridges.push((u, v));
+                        }
+                    }
+                }
+            }
+            expected_bridges.sort();
+            assert_eq!(normalized(g.bridges()), expected_bridges);
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::VecDeque;
+use std::hash::Hash;
+
+use super::indexed::Indexed;
+use super::{Edge, WeightedDirectedGraph};
+
+const EPS: f64 = 1e-12;
+
+/// [`WeightedDirectedGraph::max_flow`] 的结果
+#[derive(Clone, Debug)]
+pub struct MaxFlow<T>
+where
+    T: Clone,
+{
+    value: f64,
+    flows: Vec<Edge<T>>,
+    cut: Vec<Edge<T>>,
+    source_side: Vec<T>,
+}
+
+impl<T> MaxFlow<T>
+where
+    T: Clone,
+{
+    /// 最大流的流量（等于最小割的容量）
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// 流量为正的边，`weight` 为该边上的流量
+    pub fn flows(&self) -> &[Edge<T>] {
+        &self.flows
+    }
+
+    /// 最小割：从源点一侧指向汇点一侧的原始边，`weight` 为容量
+    pub fn min_cut(&self) -> &[Edge<T>] {
+        &self.cut
+    }
+
+    /// 残量网络中从源点可达的节点（最小割的源点一侧）
+    pub fn source_side(&self) -> &[T] {
+        &self.source_side
+    }
+}
+
+/// Dinic 算法的残量网络：第 `e` 条边的反向边是 `e ^ 1`
+struct Dinic {
+    head: Vec<Vec<usize>>,
+    to: Vec<usize>,
+    cap: Vec<f64>,
+    level: Vec<i32>,
+    next: Vec<usize>,
+}
+
+impl Dinic {
+    fn new(n: usize) -> Self {
+        Self {
+            head: vec![Vec::new(); n],
+            to: Vec::new(),
+            cap: Vec::new(),
+            level: vec![-1; n],
+            next: vec![0; n],
+        }
+    }
+
+    fn add_edge(&mut self, u: usize, v: usize, cap: f64) -> usize {
+        let e = self.to.len();
+        self.head[u].push(e);
+        self.to.push(v);
+        self.cap.push(cap);
+        self.head[v].push(e + 1);
+        self.to.push(u);
+        self.cap.push(0.0);
+        e
+    }
+
+    /// 分层图 BFS，返回汇点是否可达
+    fn bfs(&mut self, s: usize, t: usize) -> bool {
+        self.level.fill(-1);
+        self.level[s] = 0;
+        let mut q = VecDeque::from([s]);
+        while let Some(u) = q.pop_front() {
+            for &e in &self.head[u] {
+                let v = self.to[e];
+                if self.cap[e] > EPS && self.level[v] < 0 {
+                    self.level[v] = self.level[u] + 1;
+                    q.push_back(v);
+                }
+            }
+        }
+        self.level[t] >= 0
+    }
+
+    /// 在分层图上找阻塞流（当前弧优化）
+    fn dfs(&mut self, u: usize, t: usize, pushed: f64) -> f64 {
+        if u == t {
+            return pushed;
+        }
+        while self.next[u] < self.head[u].len() {
+            let e = self.head[u][self.next[u]];
+            let v = self.to[e];
+            if self.cap[e] > EPS && self.level[v] == self.level[u] + 1 {
+                let d = self.dfs(v, t, pushed.min(self.cap[e]));
+                if d > EPS {
+                    self.cap[e] -= d;
+                    self.cap[e ^ 1] += d;
+                    return d;
+                }
+            }
+            self.next[u] += 1;
+        }
+        0.0
+    }
+
+    fn run(&mut self, s: usize, t: usize) -> f64 {
+        let mut flow = 0.0;
+        while self.bfs(s, t) {
+            self.next.fill(0);
+            loop {
+                let f = self.dfs(s, t, f64::INFINITY);
+                if f <= EPS {
+                    break;
+                }
+                flow += f;
+            }
+        }
+        flow
+    }
+}
+
+impl<T> WeightedDirectedGraph<T>
+where
+    T: Eq + Hash + Clone,
+{
+    /// 以边权为容量求 `source` 到 `sink` 的最大流（Dinic 算法，O(V²E)），同时给出最小割。
+    ///
+    /// 负容量按 0 处理；`source` / `sink` 不存在或相同时返回 `None`。
+    pub fn max_flow(&self, source: &T, sink: &T) -> Option<MaxFlow<T>> {
+        let g = Indexed::from_weighted_directed(self);
+        let (&s, &t) = (g.index.get(source)?, g.index.get(sink)?);
+        if s == t {
+            return None;
+        }
+
+        let mut dinic = Dinic::new(g.len());
+        let mut originals = Vec::new();
+        for (u, edges) in g.adj.iter().enumerate() {
+            for &(v, w) in edges {
+                let cap = w.max(0.0);
+                originals.push((u, v, cap, dinic.add_edge(u, v, cap)));
+            }
+        }
+        let value = dinic.run(s, t);
+
+        // 最后一次 BFS 失败时，level >= 0 的节点就是残量网络中源点可达的节点
+        let reachable: Vec<bool> = dinic.level.iter().map(|&l| l >= 0).collect();
+        let mut flows = Vec::new();
+        let mut cut = Vec::new();
+        for (u, v, cap, e) in originals {
+            let used = cap - dinic.cap[e];
+            if used > EPS {
+                flows.push(Edge::new(
+                    g.nodes[u].clone(),
+                    g.nodes[v].clone(),
+                    used,
+                    true,
+                ));
+            }
+            if reachable[u] && !reachable[v] {
+                cut.push(Edge::new(g.nodes[u].clone(), g.nodes[v].clone(), cap, true));
+            }
+        }
+        let source_side = (0..g.len())
+            .filter(|&i| reachable[i])
+            .map(|i| g.nodes[i].clone())
+            .collect();
+
+        Some(MaxFlow {
+            value,
+            flows,
+            cut,
+            source_side,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cw::WeightedDirectedGraph;
+
+    /// CLRS 图 26.1 的流网络，最大流为 23
+    #[test]
+    fn test_max_flow_clrs() {
+        let mut g = WeightedDirectedGraph::new();
+        for (u, v, c) in [
+            ("s", "v1", 16.0),
+            ("s", "v2", 13.0),
+            ("v2", "v1", 4.0),
+            ("v1", "v3", 12.0),
+            ("v3", "v2", 9.0),
+            ("v2", "v4", 14.0),
+            ("v4", "v3", 7.0),
+            ("v3", "t", 20.0),
+            ("v4", "t", 4.0),
+        ] {
+            g.add_edge(u, v, c);
+        }
+        let flow = g.max_flow(&"s", &"t").unwrap();
+        assert_eq!(flow.value(), 23.0);
+
+        let cut_capacity: f64 = flow.min_cut().iter().map(|e| e.weight()).sum();
+        assert_eq!(cut_capacity, 23.0);
+        let mut side: Vec<_> = flow.source_side().to_vec();
+        side.sort();
+        assert_eq!(side, ["s", "v1", "v2", "v4"]);
+
+        // 流量守恒：除源汇外流入等于流出
+        for node in ["v1", "v2", "v3", "v4"] {
+            let inflow: f64 = flow
+                .flows()
+                .iter()
+                .filter(|e| *e.v2() == node)
+                .map(|e| e.weight())
+                .sum();
+            let outflow: f64 = flow
+                .flows()
+                .iter()
+                .filter(|e| *e.v1() == node)
+                .map(|e| e.weight())
+                .sum();
+            assert!((inflow - outflow).abs() < 1e-9, "{node}");
+        }
+    }
+
+    #[test]
+    fn test_max_flow_degenerate() {
+        let mut g = WeightedDirectedGraph::new();
+        g.add_edge(1, 2, 5.0);
+        g.add_edge(3, 4, 5.0);
+        assert_eq!(g.max_flow(&1, &4).unwrap().value(), 0.0);
+        assert!(g.max_flow(&1, &1).is_none());
+        assert!(g.max_flow(&1, &9).is_none());
+        assert_eq!(g.max_flow(&1, &2).unwrap().min_cut().len(), 1);
+    }
+}
@@ -0,0 +1,194 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::hash::Hash;
+
+use crate::commonw::types::{FastMap, FastSet};
+
+use super::{DirectedGraph, Edge, UndirectedGraph, WeightedDirectedGraph, WeightedUndirectedGraph};
+
+/// 把泛型节点映射到 `0..n` 的下标，算法都在下标上运行，最后再映射回 `T`。
+pub(super) struct Indexed<T> {
+    pub(super) nodes: Vec<T>,
+    pub(super) index: FastMap<T, usize>,
+    /// 出边：`(目标下标, 权重)`；无向图的每条边在两个方向各存一次
+    pub(super) adj: Vec<Vec<(usize, f64)>>,
+    pub(super) directed: bool,
+}
+
+impl<T> Indexed<T>
+where
+    T: Eq + Hash + Clone,
+{
+    fn build<'a, I>(nodes: Vec<T>, directed: bool, edges: impl Fn(&T) -> I) -> Self
+    where
+        I: IntoIterator<Item = (&'a T, f64)>,
+        T: 'a,
+    {
+        let index: FastMap<T, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.clone(), i))
+            .collect();
+        let adj = nodes
+            .iter()
+            .map(|u| {
+                edges(u)
+                    .into_iter()
+                    .filter_map(|(v, w)| index.get(v).map(|&j| (j, w)))
+                    .collect()
+            })
+            .collect();
+        Self {
+            nodes,
+            index,
+            adj,
+            directed,
+        }
+    }
+
+    pub(super) fn from_directed(g: &DirectedGraph<T>) -> Self {
+        Self::build(g.nodes(), true, |u| {
+            g.adj
+                .get(u)
+                .into_iter()
+                .flat_map(|set| set.iter().map(|v| (v, 1.0)))
+        })
+    }
+
+    pub(super) fn from_undirected(g: &UndirectedGraph<T>) -> Self {
+        Self::build(g.nodes(), false, |u| {
+            g.adj
+                .get(u)
+                .into_iter()
+                .flat_map(|set| set.iter().map(|v| (v, 1.0)))
+        })
+    }
+
+    pub(super) fn from_weighted_directed(g: &WeightedDirectedGraph<T>) -> Self {
+        Self::build(g.graph.nodes(), true, |u| {
+            g.weights
+                .get(u)
+                .into_iter()
+                .flat_map(|m| m.iter().map(|(v, w)| (v, *w)))
+        })
+    }
+
+    pub(super) fn from_weighted_undirected(g: &WeightedUndirectedGraph<T>) -> Self
+    where
+        T: Ord,
+    {
+        Self::build(g.graph.nodes(), false, |u| {
+            g.weights
+                .get(u)
+                .into_iter()
+                .flat_map(|m| m.iter().map(|(v, w)| (v, *w)))
+        })
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub(super) fn weight(&self, u: usize, v: usize) -> Option<f64> {
+        self.adj[u].iter().find(|(x, _)| *x == v).map(|(_, w)| *w)
+    }
+
+    pub(super) fn path_cost(&self, path: &[usize]) -> f64 {
+        path.windows(2)
+            .map(|p| self.weight(p[0], p[1]).unwrap_or(f64::INFINITY))
+            .sum()
+    }
+
+    /// 把下标路径转换成边序列（与 `shortest_path` 的返回值一致）
+    pub(super) fn to_edges(&self, path: &[usize]) -> Vec<Edge<T>> {
+        path.windows(2)
+            .map(|p| {
+                Edge::new(
+                    self.nodes[p[0]].clone(),
+                    self.nodes[p[1]].clone(),
+                    self.weight(p[0], p[1]).unwrap_or(f64::INFINITY),
+                    self.directed,
+                )
+            })
+            .collect()
+    }
+
+    /// 带启发函数的最短路（启发值恒为 0 时即 Dijkstra），要求边权非负。
+    /// 找到更短路径时会重新打开已关闭的节点，所以启发函数只需不高估。
+    ///
+    /// `banned_nodes` / `banned_edges` 供 Yen 算法屏蔽已用过的根路径。
+    pub(super) fn search(
+        &self,
+        src: usize,
+        dst: usize,
+        heuristic: &dyn Fn(usize) -> f64,
+        banned_nodes: &[bool],
+        banned_edges: &FastSet<(usize, usize)>,
+    ) -> Option<(f64, Vec<usize>)> {
+        let n = self.len();
+        let mut dist = vec![f64::INFINITY; n];
+        let mut prev = vec![usize::MAX; n];
+        let mut closed = vec![false; n];
+        let mut heap = BinaryHeap::new();
+        dist[src] = 0.0;
+        heap.push(MinScored(heuristic(src), src));
+
+        while let Some(MinScored(_, u)) = heap.pop() {
+            if closed[u] {
+                continue;
+            }
+            if u == dst {
+                let mut path = vec![dst];
+                let mut curr = dst;
+                while curr != src {
+                    curr = prev[curr];
+                    path.push(curr);
+                }
+                path.reverse();
+                return Some((dist[dst], path));
+            }
+            closed[u] = true;
+            for &(v, w) in &self.adj[u] {
+                if banned_nodes[v] || banned_edges.contains(&(u, v)) {
+                    continue;
+                }
+                let candidate = dist[u] + w;
+                if candidate < dist[v] {
+                    // 启发函数不一致时已关闭的节点也可能找到更短的路径，需要重新打开
+                    dist[v] = candidate;
+                    prev[v] = u;
+                    closed[v] = false;
+                    heap.push(MinScored(candidate + heuristic(v), v));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// `BinaryHeap` 是大顶堆，这里反转比较使分数最小的先出堆
+#[derive(Clone, Copy, Debug)]
+pub(super) struct MinScored(pub(super) f64, pub(super) usize);
+
+impl PartialEq for MinScored {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for MinScored {}
+
+impl PartialOrd for MinScored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MinScored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .0
+            .total_cmp(&self.0)
+            .then_with(|| other.1.cmp(&self.1))
+    }
+}
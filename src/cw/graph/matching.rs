@@ -0,0 +1,231 @@
+use std::collections::VecDeque;
+use std::hash::Hash;
+
+use super::UndirectedGraph;
+use super::indexed::Indexed;
+
+const NIL: usize = usize::MAX;
+
+/// Hopcroft-Karp：`left` 中的节点只和右侧节点相连，O(E√V)
+fn hopcroft_karp(adj: &[Vec<(usize, f64)>], left: &[usize]) -> Vec<usize> {
+    let n = adj.len();
+    let mut mate = vec![NIL; n];
+    let mut dist = vec![usize::MAX; n];
+
+    // BFS 从所有未匹配的左侧节点出发，按交错路径分层
+    let bfs = |mate: &[usize], dist: &mut [usize]| -> bool {
+        let mut q = VecDeque::new();
+        for &u in left {
+            if mate[u] == NIL {
+                dist[u] = 0;
+                q.push_back(u);
+            } else {
+                dist[u] = usize::MAX;
+            }
+        }
+        let mut found = false;
+        while let Some(u) = q.pop_front() {
+            for &(v, _) in &adj[u] {
+                match mate[v] {
+                    NIL => found = true,
+                    w if dist[w] == usize::MAX => {
+                        dist[w] = dist[u] + 1;
+                        q.push_back(w);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        found
+    };
+
+    fn dfs(u: usize, adj: &[Vec<(usize, f64)>], mate: &mut [usize], dist: &mut [usize]) -> bool {
+        for &(v, _) in &adj[u] {
+            let w = mate[v];
+            if w == NIL || (dist[w] == dist[u] + 1 && dfs(w, adj, mate, dist)) {
+                mate[u] = v;
+                mate[v] = u;
+                return true;
+            }
+        }
+        // 本轮不再经过 u
+        dist[u] = usize::MAX;
+        false
+    }
+
+    while bfs(&mate, &mut dist) {
+        for &u in left {
+            if mate[u] == NIL {
+                dfs(u, adj, &mut mate, &mut dist);
+            }
+        }
+    }
+    mate
+}
+
+/// 二染色，返回每个节点的颜色；存在奇环时返回 `None`
+fn two_color(adj: &[Vec<(usize, f64)>]) -> Option<Vec<bool>> {
+    let n = adj.len();
+    let mut color: Vec<Option<bool>> = vec![None; n];
+    for start in 0..n {
+        if color[start].is_some() {
+            continue;
+        }
+        color[start] = Some(false);
+        let mut q = VecDeque::from([start]);
+        while let Some(u) = q.pop_front() {
+            let cu = color[u].unwrap();
+            for &(v, _) in &adj[u] {
+                match color[v] {
+                    None => {
+                        color[v] = Some(!cu);
+                        q.push_back(v);
+                    }
+                    Some(cv) if cv == cu => return None,
+                    _ => {}
+                }
+            }
+        }
+    }
+    Some(color.into_iter().map(|c| c.unwrap_or(false)).collect())
+}
+
+impl<T> UndirectedGraph<T>
+where
+    T: Eq + Hash + Clone,
+{
+    /// 是否为二分图（没有奇环）
+    pub fn is_bipartite(&self) -> bool {
+        two_color(&Indexed::from_undirected(self).adj).is_some()
+    }
+
+    /// 二分图的两侧节点；不是二分图时返回 `None`。
+    /// 每个连通分量中先被访问的节点放在左侧。
+    pub fn bipartition(&self) -> Option<(Vec<T>, Vec<T>)> {
+        let g = Indexed::from_undirected(self);
+        let color = two_color(&g.adj)?;
+        let (mut left, mut right) = (Vec::new(), Vec::new());
+        for (i, node) in g.nodes.into_iter().enumerate() {
+            if color[i] {
+                right.push(node);
+            } else {
+                left.push(node);
+            }
+        }
+        Some((left, right))
+    }
+
+    /// 二分图最大匹配（Hopcroft-Karp），返回 `(左侧节点, 右侧节点)` 对；
+    /// 不是二分图时返回 `None`。左右的划分与 [`bipartition`](Self::bipartition) 一致。
+    pub fn max_bipartite_matching(&self) -> Option<Vec<(T, T)>> {
+        let g = Indexed::from_undirected(self);
+        let color = two_color(&g.adj)?;
+        let left: Vec<usize> = (0..g.len()).filter(|&i| !color[i]).collect();
+        let mate = hopcroft_karp(&g.adj, &left);
+        Some(
+            left.into_iter()
+                .filter(|&u| mate[u] != NIL)
+                .map(|u| (g.nodes[u].clone(), g.nodes[mate[u]].clone()))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cw::UndirectedGraph;
+
+    #[test]
+    fn test_bipartite_detection() {
+        let mut g = UndirectedGraph::new();
+        for (u, v) in [(1, 2), (2, 3), (3, 4), (4, 1)] {
+            g.add_edge(u, v);
+        }
+        assert!(g.is_bipartite());
+        let (mut l, mut r) = g.bipartition().unwrap();
+        l.sort();
+        r.sort();
+        assert!(l == [1, 3] && r == [2, 4] || l == [2, 4] && r == [1, 3]);
+
+        g.add_edge(1, 3);
+        assert!(!g.is_bipartite());
+        assert!(g.max_bipartite_matching().is_none());
+    }
+
+    #[test]
+    fn test_hopcroft_karp_perfect_and_partial() {
+        // 工人 w* 与任务 j*
+        let mut g = UndirectedGraph::new();
+        for (u, v) in [
+            ("w1", "j1"),
+            ("w1", "j2"),
+            ("w2", "j1"),
+            ("w3", "j2"),
+            ("w3", "j3"),
+            ("w4", "j3"),
+            ("w4", "j4"),
+        ] {
+            g.add_edge(u, v);
+        }
+        let matching = g.max_bipartite_matching().unwrap();
+        assert_eq!(matching.len(), 4);
+        for (a, b) in &matching {
+            assert!(g.adj(a).contains(b));
+        }
+
+        // 三个工人都只能做 j1：最大匹配为 1
+        let mut g = UndirectedGraph::new();
+        for w in ["a", "b", "c"] {
+            g.add_edge(w, "j1");
+        }
+        g.add_node("isolated");
+        assert_eq!(g.max_bipartite_matching().unwrap().len(), 1);
+    }
+
+    /// 与暴力搜索对比随机二分图的最大匹配大小
+    #[test]
+    fn test_hopcroft_karp_matches_brute_force() {
+        use rand::{RngExt, SeedableRng, rngs::StdRng};
+
+        fn brute(
+            edges: &[(usize, usize)],
+            used_right: &mut Vec<bool>,
+            left: usize,
+            n: usize,
+        ) -> usize {
+            if left == n {
+                return 0;
+            }
+            let mut best = brute(edges, used_right, left + 1, n);
+            for &(l, r) in edges {
+                if l == left && !used_right[r] {
+                    used_right[r] = true;
+                    best = best.max(1 + brute(edges, used_right, left + 1, n));
+                    used_right[r] = false;
+                }
+            }
+            best
+        }
+
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..50 {
+            let n = rng.random_range(1..7);
+            let mut edges = Vec::new();
+            let mut g = UndirectedGraph::new();
+            for l in 0..n {
+                for r in 0..n {
+                    if rng.random_range(0..3) == 0 {
+                        edges.push((l, r));
+                        g.add_edge(l as i32, 100 + r as i32);
+                    }
+                }
+            }
+            let expected = brute(&edges, &mut vec![false; n], 0, n);
+            assert_eq!(
+                g.max_bipartite_matching().unwrap().len(),
+                expected,
+                "{edges:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use std::hash::Hash;
+
+use crate::commonw::types::FastSet;
+
+use super::indexed::Indexed;
+use super::{Edge, WeightedDirectedGraph, WeightedUndirectedGraph};
+
+impl<T> Indexed<T>
+where
+    T: Eq + Hash + Clone,
+{
+    fn astar(&self, from: &T, to: &T, heuristic: impl Fn(&T) -> f64) -> Vec<Edge<T>> {
+        let (Some(&src), Some(&dst)) = (self.index.get(from), self.index.get(to)) else {
+            return Vec::new();
+        };
+        let h = |i: usize| heuristic(&self.nodes[i]);
+        self.search(src, dst, &h, &vec![false; self.len()], &FastSet::default())
+            .map(|(_, path)| self.to_edges(&path))
+            .unwrap_or_default()
+    }
+
+    /// Yen 算法：按总权重从小到大给出最多 `k` 条无环路径
+    fn yen(&self, from: &T, to: &T, k: usize) -> Vec<Vec<Edge<T>>> {
+        let (Some(&src), Some(&dst)) = (self.index.get(from), self.index.get(to)) else {
+            return Vec::new();
+        };
+        if k == 0 || src == dst {
+            return Vec::new();
+        }
+        let zero = |_: usize| 0.0;
+        let Some((_, first)) = self.search(
+            src,
+            dst,
+            &zero,
+            &vec![false; self.len()],
+            &FastSet::default(),
+        ) else {
+            return Vec::new();
+        };
+
+        let mut found: Vec<Vec<usize>> = vec![first];
+        let mut candidates: Vec<(f64, Vec<usize>)> = Vec::new();
+        while found.len() < k {
+            let last = found.last().unwrap().clone();
+            for i in 0..last.len() - 1 {
+                let spur = last[i];
+                let root = &last[..=i];
+
+                let mut banned_edges = FastSet::default();
+                for path in &found {
+                    if path.len() > i + 1 && &path[..=i] == root {
+                        banned_edges.insert((path[i], path[i + 1]));
+                    }
+                }
+                let mut banned_nodes = vec![false; self.len()];
+                for &node in &root[..i] {
+                    banned_nodes[node] = true;
+                }
+
+                let Some((_, spur_path)) =
+                    self.search(spur, dst, &zero, &banned_nodes, &banned_edges)
+                else {
+                    continue;
+                };
+                let mut total = root[..i].to_vec();
+                total.extend(spur_path);
+                if found.contains(&total) || candidates.iter().any(|(_, p)| *p == total) {
+                    continue;
+                }
+                candidates.push((self.path_cost(&total), total));
+            }
+
+            // 取最短的候选；同权重时边数少的优先
+            let Some(best) = (0..candidates.len()).min_by(|&a, &b| {
+                candidates[a]
+                    .0
+                    .total_cmp(&candidates[b].0)
+                    .then_with(|| candidates[a].1.len().cmp(&candidates[b].1.len()))
+            }) else {
+                break;
+            };
+            found.push(candidates.swap_remove(best).1);
+        }
+
+        found.iter().map(|path| self.to_edges(path)).collect()
+    }
+}
+
+impl<T> WeightedDirectedGraph<T>
+where
+    T: Eq + Hash + Clone,
+{
+    /// A* 最短路：`heuristic(node)` 估计 `node` 到 `to` 的剩余距离。
+    /// 启发函数不高估（admissible）且边权非负时结果最优；不可达时返回空。
+    pub fn astar(&self, from: &T, to: &T, heuristic: impl Fn(&T) -> f64) -> Vec<Edge<T>> {
+        Indexed::from_weighted_directed(self).astar(from, to, heuristic)
+    }
+
+    /// 前 `k` 条最短的无环路径（Yen 算法），按总权重升序；要求边权非负
+    pub fn k_shortest_paths(&self, from: &T, to: &T, k: usize) -> Vec<Vec<Edge<T>>> {
+        Indexed::from_weighted_directed(self).yen(from, to, k)
+    }
+}
+
+impl<T> WeightedUndirectedGraph<T>
+where
+    T: Eq + Hash + Clone + Ord,
+{
+    /// 见 [`WeightedDirectedGraph::astar`]
+    pub fn astar(&self, from: &T, to: &T, heuristic: impl Fn(&T) -> f64) -> Vec<Edge<T>> {
+        Indexed::from_weighted_undirected(self).astar(from, to, heuristic)
+    }
+
+    /// 见 [`WeightedDirectedGraph::k_shortest_paths`]
+    pub fn k_shortest_paths(&self, from: &T, to: &T, k: usize) -> Vec<Vec<Edge<T>>> {
+        Indexed::from_weighted_undirected(self).yen(from, to, k)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cw::{Edge, WeightedDirectedGraph, WeightedUndirectedGraph};
+
+    fn nodes(path: &[Edge<&'static str>]) -> Vec<&'static str> {
+        let mut out = vec![*path[0].v1()];
+        out.extend(path.iter().map(|e| *e.v2()));
+        out
+    }
+
+    fn cost<T: Clone>(path: &[Edge<T>]) -> f64 {
+        path.iter().map(|e| e.weight()).sum()
+    }
+
+    #[test]
+    fn test_astar_grid() {
+        // 5x5 网格，中间一堵墙，曼哈顿距离为启发函数
+        let mut g = WeightedUndirectedGraph::new();
+        let wall = [(2, 0), (2, 1), (2, 2), (2, 3)];
+        for x in 0..5i32 {
+            for y in 0..5i32 {
+                if wall.contains(&(x, y)) {
+                    continue;
+                }
+                for (dx, dy) in [(1, 0), (0, 1)] {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if nx < 5 && ny < 5 && !wall.contains(&(nx, ny)) {
+                        g.add_edge((x, y), (nx, ny), 1.0);
+                    }
+                }
+            }
+        }
+        let goal = (4, 0);
+        let path = g.astar(&(0, 0), &goal, |&(x, y)| {
+            ((goal.0 - x).abs() + (goal.1 - y).abs()) as f64
+        });
+        // 必须绕过墙走到 y = 4 再回来
+        assert_eq!(cost(&path), 12.0);
+        assert_eq!(path.first().unwrap().v1(), &(0, 0));
+        assert_eq!(path.last().unwrap().v2(), &goal);
+        assert!(g.astar(&(0, 0), &(9, 9), |_| 0.0).is_empty());
+    }
+
+    #[test]
+    fn test_astar_matches_dijkstra() {
+        let mut g = WeightedDirectedGraph::new();
+        g.add_edge("A", "B", 1.0);
+        g.add_edge("B", "C", 2.0);
+        g.add_edge("A", "C", 10.0);
+        g.add_edge("C", "D", 1.0);
+        let path = g.astar(&"A", &"D", |_| 0.0);
+        assert_eq!(nodes(&path), ["A", "B", "C", "D"]);
+        assert_eq!(cost(&path), 4.0);
+    }
+
+    #[test]
+    fn test_astar_reopens_nodes_with_inconsistent_heuristic() {
+        // h(B) = 4 不高估，但 h(B) > w(B, C) + h(C)，C 会先经 A 以较长路径关闭
+        let mut g = WeightedDirectedGraph::new();
+        g.add_edge("S", "A", 1.0);
+        g.add_edge("A", "C", 2.0);
+        g.add_edge("S", "B", 1.0);
+        g.add_edge("B", "C", 1.0);
+        g.add_edge("C", "G", 3.0);
+        let path = g.astar(&"S", &"G", |&n| if n == "B" { 4.0 } else { 0.0 });
+        assert_eq!(nodes(&path), ["S", "B", "C", "G"]);
+        assert_eq!(cost(&path), 5.0);
+    }
+
+    /// 经典 Yen 示例（Wikipedia "Yen's algorithm"）
+    #[test]
+    fn test_k_shortest_paths_yen_example() {
+        let mut g = WeightedDirectedGraph::new();
+        for (u, v, w) in [
+            ("C", "D", 3.0),
+            ("C", "E", 2.0),
+            ("D", "F", 4.0),
+            ("E", "D", 1.0),
+            ("E", "F", 2.0),
+            ("E", "G", 3.0),
+            ("F", "G", 2.0),
+            ("F", "H", 1.0),
+            ("G", "H", 2.0),
+        ] {
+            g.add_edge(u, v, w);
+        }
+        let paths = g.k_shortest_paths(&"C", &"H", 3);
+        assert_eq!(paths.len(), 3);
+        assert_eq!(nodes(&paths[0]), ["C", "E", "F", "H"]);
+        assert_eq!(cost(&paths[0]), 5.0);
+        assert_eq!(nodes(&paths[1]), ["C", "E", "G", "H"]);
+        assert_eq!(cost(&paths[1]), 7.0);
+        assert_eq!(cost(&paths[2]), 8.0);
+
+        // 总共只有 7 条简单路径
+        let all = g.k_shortest_paths(&"C", &"H", 100);
+        assert_eq!(all.len(), 7);
+        for pair in all.windows(2) {
+            assert!(cost(&pair[0]) <= cost(&pair[1]));
+        }
+        assert!(g.k_shortest_paths(&"H", &"C", 3).is_empty());
+        assert!(g.k_shortest_paths(&"C", &"H", 0).is_empty());
+    }
+
+    #[test]
+    fn test_k_shortest_paths_undirected() {
+        let mut g = WeightedUndirectedGraph::new();
+        g.add_edge(1, 2, 1.0);
+        g.add_edge(2, 4, 1.0);
+        g.add_edge(1, 3, 2.0);
+        g.add_edge(3, 4, 2.0);
+        let paths = g.k_shortest_paths(&1, &4, 5);
+        let costs: Vec<f64> = paths.iter().map(|p| cost(p)).collect();
+        assert_eq!(costs, [2.0, 4.0]);
+        assert!(paths.iter().all(|p| p.iter().all(|e| !e.directed())));
+    }
+}
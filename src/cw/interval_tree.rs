@@ -0,0 +1,454 @@
+//! 区间树（Interval Tree）
+//!
+//! 以区间起点为键的 AVL 树，每个节点额外记录子树中最大的区间终点（`max_end`），
+//! 据此剪枝，能在 O(log n + k) 时间内找出与某点 / 某区间相交的全部 k 个区间。
+//!
+//! 区间统一使用半开区间 `start..end`：`1..3` 与 `3..5` 不相交，`start >= end` 的空区间
+//! 可以存入但不与任何东西相交。
+
+use std::cmp::Ordering;
+use std::ops::Range;
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+struct Node<K, V> {
+    range: Range<K>,
+    value: V,
+    /// 子树中最大的 `end`
+    max_end: K,
+    height: i32,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+/// 区间树
+///
+/// - 允许重复区间（同一个区间可以对应多个值）
+/// - 插入 / 删除 O(log n)，查询 O(log n + k)
+///
+/// # 示例
+///
+/// ```rust
+/// use rust_tools::cw::IntervalTree;
+///
+/// let mut hunks = IntervalTree::new();
+/// hunks.insert(10..20, "hunk-a");
+/// hunks.insert(15..30, "hunk-b");
+/// hunks.insert(40..50, "hunk-c");
+///
+/// // 哪些区间包含第 16 行
+/// let hits: Vec<_> = hunks.stab(&16).into_iter().map(|(_, v)| *v).collect();
+/// assert_eq!(hits, ["hunk-a", "hunk-b"]);
+///
+/// // 哪些区间与 25..45 相交
+/// let hits: Vec<_> = hunks.overlapping(&(25..45)).into_iter().map(|(_, v)| *v).collect();
+/// assert_eq!(hits, ["hunk-b", "hunk-c"]);
+///
+/// assert_eq!(hunks.remove(&(10..20)), Some("hunk-a"));
+/// assert!(!hunks.any_overlap(&(0..15)));
+/// ```
+pub struct IntervalTree<K, V> {
+    root: Link<K, V>,
+    len: usize,
+}
+
+impl<K: Ord + Clone, V> IntervalTree<K, V> {
+    /// 创建空的区间树
+    pub fn new() -> Self {
+        Self { root: None, len: 0 }
+    }
+
+    /// 区间个数
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 清空
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.len = 0;
+    }
+
+    /// 插入一个区间及其关联值（允许重复区间）
+    pub fn insert(&mut self, range: Range<K>, value: V) {
+        self.root = Some(insert(self.root.take(), range, value));
+        self.len += 1;
+    }
+
+    /// 删除一个与 `range` 完全相同的区间，返回其值；有多个相同区间时只删除其中一个
+    pub fn remove(&mut self, range: &Range<K>) -> Option<V> {
+        let (root, removed) = remove(self.root.take(), range);
+        self.root = root;
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    /// 是否存在与 `range` 完全相同的区间
+    pub fn contains(&self, range: &Range<K>) -> bool {
+        let mut curr = &self.root;
+        while let Some(node) = curr {
+            curr = match cmp_range(range, &node.range) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+                Ordering::Equal => return true,
+            };
+        }
+        false
+    }
+
+    /// 包含点 `point` 的所有区间（`start <= point < end`），按起点升序
+    pub fn stab(&self, point: &K) -> Vec<(&Range<K>, &V)> {
+        let mut out = Vec::new();
+        stab(&self.root, point, &mut out);
+        out
+    }
+
+    /// 与 `query` 相交的所有区间（`start < query.end && query.start < end`），按起点升序
+    pub fn overlapping(&self, query: &Range<K>) -> Vec<(&Range<K>, &V)> {
+        let mut out = Vec::new();
+        if query.start < query.end {
+            overlapping(&self.root, query, &mut out);
+        }
+        out
+    }
+
+    /// 是否存在与 `query` 相交的区间，找到第一个即返回，不收集结果
+    pub fn any_overlap(&self, query: &Range<K>) -> bool {
+        query.start < query.end && any_overlap(&self.root, query)
+    }
+
+    /// 按起点（再按终点）升序遍历所有区间
+    pub fn iter(&self) -> impl Iterator<Item = (&Range<K>, &V)> {
+        let mut out = Vec::with_capacity(self.len);
+        collect(&self.root, &mut out);
+        out.into_iter()
+    }
+}
+
+impl<K: Ord + Clone, V> Default for IntervalTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Clone, V> FromIterator<(Range<K>, V)> for IntervalTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (Range<K>, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        for (range, value) in iter {
+            tree.insert(range, value);
+        }
+        tree
+    }
+}
+
+fn cmp_range<K: Ord>(a: &Range<K>, b: &Range<K>) -> Ordering {
+    a.start.cmp(&b.start).then_with(|| a.end.cmp(&b.end))
+}
+
+fn overlaps<K: Ord>(a: &Range<K>, b: &Range<K>) -> bool {
+    a.start < a.end && a.start < b.end && b.start < a.end
+}
+
+fn height<K, V>(link: &Link<K, V>) -> i32 {
+    link.as_ref().map_or(0, |n| n.height)
+}
+
+/// 重新计算高度和 `max_end`
+fn update<K: Ord + Clone, V>(node: &mut Node<K, V>) {
+    node.height = 1 + height(&node.left).max(height(&node.right));
+    let mut max_end = node.range.end.clone();
+    for child in [&node.left, &node.right].into_iter().flatten() {
+        if child.max_end > max_end {
+            max_end = child.max_end.clone();
+        }
+    }
+    node.max_end = max_end;
+}
+
+fn rotate_right<K: Ord + Clone, V>(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    let mut left = node.left.take().expect("rotate_right needs a left child");
+    node.left = left.right.take();
+    update(&mut node);
+    left.right = Some(node);
+    update(&mut left);
+    left
+}
+
+fn rotate_left<K: Ord + Clone, V>(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    let mut right = node.right.take().expect("rotate_left needs a right child");
+    node.right = right.left.take();
+    update(&mut node);
+    right.left = Some(node);
+    update(&mut right);
+    right
+}
+
+fn rebalance<K: Ord + Clone, V>(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    update(&mut node);
+    let balance = height(&node.left) - height(&node.right);
+    if balance > 1 {
+        let left = node.left.take().unwrap();
+        node.left = Some(if height(&left.left) < height(&left.right) {
+            rotate_left(left)
+        } else {
+            left
+        });
+        return rotate_right(node);
+    }
+    if balance < -1 {
+        let right = node.right.take().unwrap();
+        node.right = Some(if height(&right.right) < height(&right.left) {
+            rotate_right(right)
+        } else {
+            right
+        });
+        return rotate_left(node);
+    }
+    node
+}
+
+fn insert<K: Ord + Clone, V>(link: Link<K, V>, range: Range<K>, value: V) -> Box<Node<K, V>> {
+    let Some(mut node) = link else {
+        return Box::new(Node {
+            max_end: range.end.clone(),
+            range,
+            value,
+            height: 1,
+            left: None,
+            right: None,
+        });
+    };
+    if cmp_range(&range, &node.range) == Ordering::Less {
+        node.left = Some(insert(node.left.take(), range, value));
+    } else {
+        node.right = Some(insert(node.right.take(), range, value));
+    }
+    rebalance(node)
+}
+
+/// 摘下子树中最小的节点，返回 (剩余子树, 最小节点)
+fn take_min<K: Ord + Clone, V>(mut node: Box<Node<K, V>>) -> (Link<K, V>, Box<Node<K, V>>) {
+    match node.left.take() {
+        None => (node.right.take(), node),
+        Some(left) => {
+            let (rest, min) = take_min(left);
+            node.left = rest;
+            (Some(rebalance(node)), min)
+        }
+    }
+}
+
+fn remove<K: Ord + Clone, V>(link: Link<K, V>, range: &Range<K>) -> (Link<K, V>, Option<V>) {
+    let Some(mut node) = link else {
+        return (None, None);
+    };
+    let removed = match cmp_range(range, &node.range) {
+        Ordering::Less => {
+            let (left, removed) = remove(node.left.take(), range);
+            node.left = left;
+            removed
+        }
+        Ordering::Greater => {
+            let (right, removed) = remove(node.right.take(), range);
+            node.right = right;
+            removed
+        }
+        Ordering::Equal => {
+            let Node {
+                value, left, right, ..
+            } = *node;
+            let replacement = match (left, right) {
+                (None, None) => None,
+                (Some(child), None) | (None, Some(child)) => Some(child),
+                (Some(left), Some(right)) => {
+                    let (rest, mut min) = take_min(right);
+                    min.left = Some(left);
+                    min.right = rest;
+                    Some(rebalance(min))
+                }
+            };
+            return (replacement, Some(value));
+        }
+    };
+    (Some(rebalance(node)), removed)
+}
+
+fn stab<'a, K: Ord, V>(link: &'a Link<K, V>, point: &K, out: &mut Vec<(&'a Range<K>, &'a V)>) {
+    let Some(node) = link else {
+        return;
+    };
+    if node.max_end <= *point {
+        return;
+    }
+    stab(&node.left, point, out);
+    if node.range.start <= *point && *point < node.range.end {
+        out.push((&node.range, &node.value));
+    }
+    if node.range.start <= *point {
+        stab(&node.right, point, out);
+    }
+}
+
+fn overlapping<'a, K: Ord, V>(
+    link: &'a Link<K, V>,
+    query: &Range<K>,
+    out: &mut Vec<(&'a Range<K>, &'a V)>,
+) {
+    let Some(node) = link else {
+        return;
+    };
+    if node.max_end <= query.start {
+        return;
+    }
+    overlapping(&node.left, query, out);
+    if overlaps(&node.range, query) {
+        out.push((&node.range, &node.value));
+    }
+    if node.range.start < query.end {
+        overlapping(&node.right, query, out);
+    }
+}
+
+// 空区间也参与 `max_end`，所以不能只沿一条路径下降，需与 `overlapping` 一样剪枝递归
+fn any_overlap<K: Ord, V>(link: &Link<K, V>, query: &Range<K>) -> bool {
+    let Some(node) = link else {
+        return false;
+    };
+    if node.max_end <= query.start {
+        return false;
+    }
+    overlaps(&node.range, query)
+        || any_overlap(&node.left, query)
+        || (node.range.start < query.end && any_overlap(&node.right, query))
+}
+
+fn collect<'a, K, V>(link: &'a Link<K, V>, out: &mut Vec<(&'a Range<K>, &'a V)>) {
+    if let Some(node) = link {
+        collect(&node.left, out);
+        out.push((&node.range, &node.value));
+        collect(&node.right, out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IntervalTree, Link};
+    use rand::{RngExt, SeedableRng, rngs::StdRng};
+    use std::ops::Range;
+
+    /// 检查 AVL 平衡、有序性和 `max_end`，返回子树高度
+    fn check<K: Ord + Clone + std::fmt::Debug, V>(link: &Link<K, V>) -> i32 {
+        let Some(node) = link else {
+            return 0;
+        };
+        let lh = check(&node.left);
+        let rh = check(&node.right);
+        assert!((lh - rh).abs() <= 1);
+        assert_eq!(node.height, 1 + lh.max(rh));
+        let mut max_end = node.range.end.clone();
+        for child in [&node.left, &node.right].into_iter().flatten() {
+            max_end = max_end.max(child.max_end.clone());
+        }
+        assert_eq!(node.max_end, max_end);
+        node.height
+    }
+
+    #[test]
+    fn test_adjacent_and_empty_intervals() {
+        let mut t = IntervalTree::new();
+        t.insert(1..3, 'a');
+        t.insert(3..5, 'b');
+        t.insert(4..4, 'e');
+        assert_eq!(t.len(), 3);
+        assert_eq!(t.stab(&3).len(), 1);
+        assert_eq!(t.stab(&4), vec![(&(3..5), &'b')]);
+        assert!(t.overlapping(&(2..2)).is_empty());
+        assert_eq!(t.overlapping(&(2..4)).len(), 2);
+        assert!(t.contains(&(4..4)));
+        assert!(!t.contains(&(4..5)));
+    }
+
+    #[test]
+    fn test_duplicates_and_remove() {
+        let mut t: IntervalTree<i32, i32> = (0..10).map(|i| (0..5, i)).collect();
+        assert_eq!(t.stab(&2).len(), 10);
+        for _ in 0..10 {
+            assert!(t.remove(&(0..5)).is_some());
+        }
+        assert!(t.remove(&(0..5)).is_none());
+        assert!(t.is_empty());
+    }
+
+    /// 随机插入 / 删除，与线性扫描对比所有查询结果
+    #[test]
+    fn property_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(0x1a7e);
+        for _ in 0..30 {
+            let mut tree = IntervalTree::new();
+            let mut reference: Vec<(Range<i32>, u32)> = Vec::new();
+            for step in 0..300u32 {
+                if !reference.is_empty() && rng.random_range(0..3) == 0 {
+                    let idx = rng.random_range(0..reference.len());
+                    let range = reference[idx].0.clone();
+                    assert!(tree.remove(&range).is_some());
+                    // 删除的可能是任意一个同区间的值，参照中也删掉同区间的某一项
+                    let pos = reference.iter().position(|(r, _)| *r == range).unwrap();
+                    reference.remove(pos);
+                } else {
+                    let start = rng.random_range(0..100);
+                    let end = start + rng.random_range(0..20);
+                    tree.insert(start..end, step);
+                    reference.push((start..end, step));
+                }
+                assert_eq!(tree.len(), reference.len());
+            }
+            check(&tree.root);
+
+            let sorted = |mut v: Vec<Range<i32>>| {
+                v.sort_by_key(|r| (r.start, r.end));
+                v
+            };
+            for _ in 0..50 {
+                let p = rng.random_range(-5..125);
+                let got: Vec<Range<i32>> =
+                    tree.stab(&p).into_iter().map(|(r, _)| r.clone()).collect();
+                let expected: Vec<Range<i32>> = reference
+                    .iter()
+                    .filter(|(r, _)| r.contains(&p))
+                    .map(|(r, _)| r.clone())
+                    .collect();
+                assert_eq!(got, sorted(expected), "stab {p}");
+
+                let qs = rng.random_range(-5..125);
+                let q = qs..qs + rng.random_range(0..30);
+                let got: Vec<Range<i32>> = tree
+                    .overlapping(&q)
+                    .into_iter()
+                    .map(|(r, _)| r.clone())
+                    .collect();
+                let expected: Vec<Range<i32>> = reference
+                    .iter()
+                    .filter(|(r, _)| {
+                        q.start < q.end && r.start < r.end && r.start < q.end && q.start < r.end
+                    })
+                    .map(|(r, _)| r.clone())
+                    .collect();
+                assert_eq!(tree.any_overlap(&q), !expected.is_empty(), "any {q:?}");
+                assert_eq!(got, sorted(expected), "overlap {q:?}");
+            }
+
+            let all: Vec<Range<i32>> = tree.iter().map(|(r, _)| r.clone()).collect();
+            assert_eq!(
+                all,
+                sorted(reference.iter().map(|(r, _)| r.clone()).collect())
+            );
+        }
+    }
+}
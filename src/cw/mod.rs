@@ -9,6 +9,9 @@
 //! - [`queue::Queue`] - 先进先出队列
 //! - [`stack::Stack`] - 后进先出栈
 //! - [`deque_list::DequeList`] - 双端队列
+//! - [`mpmc_queue::ArrayQueue`] - 无锁有界多生产者多消费者队列
+//! - [`mpmc_queue::BlockingQueue`] - 有界阻塞队列（超时 / 非阻塞 `try_*` / 关闭语义，可作 channel）
+//! - [`work_stealing::Worker`] / [`work_stealing::Stealer`] - 工作窃取双端队列（基于互斥锁，非无锁），[`work_stealing::run`] 为配套的作用域线程池
 //!
 //! ### 映射和集合
 //!
@@ -21,14 +24,24 @@
 //! ### 高级数据结构
 //!
 //! - [`lru_cache::LruCache`] - LRU（最近最少使用）缓存
+//! - [`concurrent_lru_cache::ConcurrentLruCache`] - 线程安全的分片 LRU 缓存（TTL / 权重预算 / 淘汰回调 / 统计）
 //! - [`priority_queue::MaxPriorityQueue`] / [`priority_queue::MinPriorityQueue`] - 优先队列
 //! - [`bloom_filter::BloomFilter`] - 布隆过滤器
+//! - [`counting_bloom_filter::CountingBloomFilter`] - 支持删除的计数布隆过滤器
+//! - [`scalable_bloom_filter::ScalableBloomFilter`] - 可自动扩容的布隆过滤器
 //! - [`counter::Counter`] - 计数器（元素频率统计）
-//! - [`zset::ZSet`] - 有序集合（带分数排序）
+//! - [`zset::ZSet`] - 有序集合（带分数排序，Redis 风格的按名次 / 分数 / 字典序范围查询与加权聚合）
 //! - [`uf::UF`] - 并查集（Union-Find）
-//! - [`trie::Trie`] - 前缀树（字典树）
+//! - [`trie::Trie`] - 前缀树（字典树），支持权重 top-k 补全、编辑距离模糊搜索
+//! - [`trie::RadixTrie`] - 紧凑可序列化的基数树（压缩前缀树）
 //! - [`skip_list::SkipMap`] / [`skip_list::SkipSet`] - 跳表
+//! - [`disk_skip_map::DiskSkipMap`] - 持久化到磁盘的有序映射（追加写日志 + 压缩）
 //! - [`rb_tree::RbTree`] - 红黑树（API 风格）
+//! - [`interval_tree::IntervalTree`] - 区间树（点 / 区间重叠查询）
+//! - [`segment_tree::SegmentTree`] - 懒标记线段树（自定义幺半群上的区间查询与区间修改）
+//! - [`persistent_map::PersistentMap`] - 持久化哈希映射（HAMT，结构共享，O(1) 克隆）
+//! - [`persistent_vector::PersistentVector`] - 持久化向量（RRB 树，O(log n) 拼接 / 切分）
+//! - [`ordered_query::OrderedQuery`] - 有序容器的范围 / floor / ceiling / rank 查询接口
 //!
 //! ### 图结构
 //!
@@ -36,6 +49,7 @@
 //! - [`graph::WeightedDirectedGraph`] / [`graph::WeightedUndirectedGraph`] - 带权图
 //! - [`graph::Edge`] - 边
 //! - [`graph::Mst`] - 最小生成树
+//! - [`graph::MaxFlow`] - 最大流 / 最小割（Dinic）；图类型上另有二分匹配、割点与桥、A*、k 短路、PageRank、介数中心性
 //!
 //! ## 使用示例
 //!
@@ -113,39 +127,65 @@
 
 pub mod bloom_filter;
 pub mod concurrent_hash_map;
+pub mod concurrent_lru_cache;
 pub mod counter;
+pub mod counting_bloom_filter;
 pub mod deque_list;
+pub mod disk_skip_map;
 pub mod graph;
+pub mod interval_tree;
 pub mod lru_cache;
+pub mod mpmc_queue;
+mod order_stat_tree;
 pub mod ordered_map;
+pub mod ordered_query;
 pub mod ordered_set;
+pub mod persistent_map;
+pub mod persistent_vector;
 pub mod priority_queue;
 pub mod queue;
 pub mod rb_tree;
+pub mod scalable_bloom_filter;
+pub mod segment_tree;
 pub mod skip_list;
 pub mod stack;
 pub mod tree_map;
 pub mod tree_set;
 pub mod trie;
 pub mod uf;
+pub mod work_stealing;
 pub mod zset;
 
 // 重新导出常用类型
 pub use bloom_filter::BloomFilter;
+pub use concurrent_lru_cache::{CacheStats, ConcurrentLruCache, EvictionCause};
 pub use counter::Counter;
+pub use counting_bloom_filter::CountingBloomFilter;
 pub use deque_list::DequeList;
+pub use disk_skip_map::{DiskSkipMap, DiskSkipMapOptions};
 pub use graph::{
-    DirectedGraph, Edge, Mst, UndirectedGraph, WeightedDirectedGraph, WeightedUndirectedGraph,
+    DirectedGraph, Edge, MaxFlow, Mst, UndirectedGraph, WeightedDirectedGraph,
+    WeightedUndirectedGraph,
 };
+pub use interval_tree::IntervalTree;
 pub use lru_cache::LruCache;
+pub use mpmc_queue::{ArrayQueue, BlockingQueue, PopError, PushError};
 pub use ordered_map::OrderedMap;
+pub use ordered_query::OrderedQuery;
 pub use ordered_set::OrderedSet;
+pub use persistent_map::PersistentMap;
+pub use persistent_vector::PersistentVector;
 pub use priority_queue::{MaxPriorityQueue, MinPriorityQueue};
 pub use queue::Queue;
+pub use scalable_bloom_filter::ScalableBloomFilter;
+pub use segment_tree::{
+    RangeAddMax, RangeAddMin, RangeAddSum, RangeAssignSum, SegmentMonoid, SegmentTree,
+};
 pub use skip_list::{Entry as SkipMapEntry, SkipMap, SkipSet};
 pub use stack::Stack;
 pub use tree_map::TreeMap;
 pub use tree_set::TreeSet;
-pub use trie::Trie;
+pub use trie::{RadixTrie, Trie};
 pub use uf::UF;
-pub use zset::ZSet;
+pub use work_stealing::{Stealer, Worker};
+pub use zset::{Aggregate, ZSet, ZSetEntry};
@@ -0,0 +1,648 @@
+//! 多生产者多消费者（MPMC）有界队列
+//!
+//! - [`ArrayQueue`]：无锁环形缓冲区（Dmitry Vyukov 的有界 MPMC 算法），只提供非阻塞操作
+//! - [`BlockingQueue`]：在 [`ArrayQueue`] 之上增加阻塞 / 超时的 `push` / `pop` 和关闭语义，
+//!   可当作有界 channel 使用
+//!
+//! 两者都通过 `&self` 操作，用 `Arc` 在线程间共享即可。
+
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::mem::MaybeUninit;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering, fence};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// 独占一条缓存行，避免 head / tail 伪共享
+#[repr(align(64))]
+struct CachePadded<T>(T);
+
+impl<T> Deref for CachePadded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+struct Slot<T> {
+    /// 槽位印记：等于 `2 * pos` 表示位置 `pos` 可写入，等于 `2 * pos + 1` 表示可读取。
+    /// 乘 2 是为了让容量为 1 时“已写入”和“下一轮可写入”也能区分开
+    seq: AtomicUsize,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+/// 无锁有界 MPMC 队列
+///
+/// 每个槽位带一个序号，生产者 / 消费者各自用 CAS 抢占 `tail` / `head` 位置，
+/// 不需要任何锁。队列满时 `push` 把元素原样退回，空时 `pop` 返回 `None`。
+///
+/// # 示例
+///
+/// ```rust
+/// use rust_tools::cw::ArrayQueue;
+/// use std::sync::Arc;
+///
+/// let q = Arc::new(ArrayQueue::new(2));
+/// assert!(q.push(1).is_ok());
+/// assert!(q.push(2).is_ok());
+/// assert_eq!(q.push(3), Err(3)); // 已满
+///
+/// let q2 = Arc::clone(&q);
+/// std::thread::spawn(move || assert_eq!(q2.pop(), Some(1)))
+///     .join()
+///     .unwrap();
+/// assert_eq!(q.pop(), Some(2));
+/// assert_eq!(q.pop(), None);
+/// ```
+pub struct ArrayQueue<T> {
+    buffer: Box<[Slot<T>]>,
+    cap: usize,
+    head: CachePadded<AtomicUsize>,
+    tail: CachePadded<AtomicUsize>,
+}
+
+unsafe impl<T: Send> Send for ArrayQueue<T> {}
+unsafe impl<T: Send> Sync for ArrayQueue<T> {}
+
+impl<T> ArrayQueue<T> {
+    /// 创建容量为 `cap` 的队列
+    ///
+    /// # Panics
+    ///
+    /// `cap` 为 0 时 panic
+    pub fn new(cap: usize) -> Self {
+        assert!(cap > 0, "capacity must be positive");
+        let buffer = (0..cap)
+            .map(|i| Slot {
+                seq: AtomicUsize::new(i.wrapping_mul(2)),
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+            })
+            .collect();
+        Self {
+            buffer,
+            cap,
+            head: CachePadded(AtomicUsize::new(0)),
+            tail: CachePadded(AtomicUsize::new(0)),
+        }
+    }
+
+    /// 入队；队列已满时返回 `Err(value)`
+    pub fn push(&self, value: T) -> Result<(), T> {
+        let mut pos = self.tail.load(Ordering::Relaxed);
+        loop {
+            let slot = &self.buffer[pos % self.cap];
+            let seq = slot.seq.load(Ordering::Acquire);
+            let diff = seq.wrapping_sub(pos.wrapping_mul(2)) as isize;
+            if diff == 0 {
+                match self.tail.compare_exchange_weak(
+                    pos,
+                    pos.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // CAS 成功后该槽位只属于当前线程，直到序号被发布
+                        unsafe { (*slot.value.get()).write(value) };
+                        slot.seq
+                            .store(pos.wrapping_mul(2).wrapping_add(1), Ordering::Release);
+                        return Ok(());
+                    }
+                    Err(actual) => pos = actual,
+                }
+            } else if diff < 0 {
+                // 槽位上一轮的元素还没被取走：队列已满
+                return Err(value);
+            } else {
+                pos = self.tail.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// 出队；队列为空时返回 `None`
+    pub fn pop(&self) -> Option<T> {
+        let mut pos = self.head.load(Ordering::Relaxed);
+        loop {
+            let slot = &self.buffer[pos % self.cap];
+            let seq = slot.seq.load(Ordering::Acquire);
+            let diff = seq.wrapping_sub(pos.wrapping_mul(2).wrapping_add(1)) as isize;
+            if diff == 0 {
+                match self.head.compare_exchange_weak(
+                    pos,
+                    pos.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        let value = unsafe { (*slot.value.get()).assume_init_read() };
+                        // 留给下一轮 pos + cap 的生产者
+                        slot.seq.store(
+                            pos.wrapping_add(self.cap).wrapping_mul(2),
+                            Ordering::Release,
+                        );
+                        return Some(value);
+                    }
+                    Err(actual) => pos = actual,
+                }
+            } else if diff < 0 {
+                return None;
+            } else {
+                pos = self.head.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// 容量
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    /// 当前元素个数（并发修改时只是一个近似值）
+    pub fn len(&self) -> usize {
+        loop {
+            let tail = self.tail.load(Ordering::SeqCst);
+            let head = self.head.load(Ordering::SeqCst);
+            // 两次读取之间 tail 没变，得到的是一致的快照
+            if self.tail.load(Ordering::SeqCst) == tail {
+                return tail.wrapping_sub(head).min(self.cap);
+            }
+        }
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 是否已满
+    pub fn is_full(&self) -> bool {
+        self.len() == self.cap
+    }
+}
+
+impl<T> Drop for ArrayQueue<T> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+impl<T> fmt::Debug for ArrayQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArrayQueue")
+            .field("len", &self.len())
+            .field("capacity", &self.cap)
+            .finish()
+    }
+}
+
+/// [`BlockingQueue`] 入队失败的原因，附带未能入队的元素
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushError<T> {
+    /// 队列已满（仅 `try_push`）
+    Full(T),
+    /// 等待超时（仅 `push_timeout`）
+    Timeout(T),
+    /// 队列已关闭
+    Closed(T),
+}
+
+impl<T> PushError<T> {
+    /// 取回未能入队的元素
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::Full(v) | Self::Timeout(v) | Self::Closed(v) => v,
+        }
+    }
+}
+
+impl<T> fmt::Display for PushError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Full(_) => "queue is full",
+            Self::Timeout(_) => "timed out waiting for free space",
+            Self::Closed(_) => "queue is closed",
+        })
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for PushError<T> {}
+
+/// [`BlockingQueue`] 出队失败的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PopError {
+    /// 队列为空（仅 `try_pop`）
+    Empty,
+    /// 等待超时（仅 `pop_timeout`）
+    Timeout,
+    /// 队列已关闭且已取空
+    Closed,
+}
+
+impl fmt::Display for PopError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Empty => "queue is empty",
+            Self::Timeout => "timed out waiting for an item",
+            Self::Closed => "queue is closed",
+        })
+    }
+}
+
+impl std::error::Error for PopError {}
+
+/// 有界阻塞 MPMC 队列
+///
+/// 快速路径直接走无锁的 [`ArrayQueue`]；只有需要等待时才进入互斥锁并在条件变量上休眠。
+///
+/// 关闭语义：[`close`](Self::close) 之后 `push` 一律返回 [`PushError::Closed`]，
+/// `pop` 仍能取出剩余元素，取空后返回 [`PopError::Closed`]；所有等待中的线程都会被唤醒。
+///
+/// # 示例
+///
+/// ```rust
+/// use rust_tools::cw::{BlockingQueue, PopError};
+/// use std::sync::Arc;
+///
+/// let q = Arc::new(BlockingQueue::new(4));
+/// let producer = {
+///     let q = Arc::clone(&q);
+///     std::thread::spawn(move || {
+///         for i in 0..10 {
+///             q.push(i).unwrap(); // 满了就等
+///         }
+///         q.close();
+///     })
+/// };
+///
+/// let mut got = Vec::new();
+/// while let Ok(v) = q.pop() {
+///     got.push(v);
+/// }
+/// producer.join().unwrap();
+/// assert_eq!(got, (0..10).collect::<Vec<_>>());
+/// assert_eq!(q.pop(), Err(PopError::Closed));
+/// ```
+pub struct BlockingQueue<T> {
+    queue: ArrayQueue<T>,
+    closed: AtomicBool,
+    lock: Mutex<()>,
+    not_empty: Condvar,
+    not_full: Condvar,
+    pop_waiters: AtomicUsize,
+    push_waiters: AtomicUsize,
+}
+
+impl<T> BlockingQueue<T> {
+    /// 创建容量为 `cap` 的队列
+    ///
+    /// # Panics
+    ///
+    /// `cap` 为 0 时 panic
+    pub fn new(cap: usize) -> Self {
+        Self {
+            queue: ArrayQueue::new(cap),
+            closed: AtomicBool::new(false),
+            lock: Mutex::new(()),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+            pop_waiters: AtomicUsize::new(0),
+            push_waiters: AtomicUsize::new(0),
+        }
+    }
+
+    /// 非阻塞入队
+    pub fn try_push(&self, value: T) -> Result<(), PushError<T>> {
+        if self.is_closed() {
+            return Err(PushError::Closed(value));
+        }
+        match self.queue.push(value) {
+            Ok(()) => {
+                self.wake(&self.pop_waiters, &self.not_empty);
+                Ok(())
+            }
+            Err(value) => Err(PushError::Full(value)),
+        }
+    }
+
+    /// 阻塞入队，直到有空位或队列被关闭
+    pub fn push(&self, value: T) -> Result<(), PushError<T>> {
+        self.push_until(value, None)
+    }
+
+    /// 最多等待 `timeout` 的入队
+    pub fn push_timeout(&self, value: T, timeout: Duration) -> Result<(), PushError<T>> {
+        self.push_until(value, Some(Instant::now() + timeout))
+    }
+
+    /// 非阻塞出队
+    pub fn try_pop(&self) -> Result<T, PopError> {
+        match self.queue.pop() {
+            Some(value) => {
+                self.wake(&self.push_waiters, &self.not_full);
+                Ok(value)
+            }
+            None if self.is_closed() => self.queue.pop().ok_or(PopError::Closed),
+            None => Err(PopError::Empty),
+        }
+    }
+
+    /// 阻塞出队，直到有元素或队列被关闭且取空
+    pub fn pop(&self) -> Result<T, PopError> {
+        self.pop_until(None)
+    }
+
+    /// 最多等待 `timeout` 的出队
+    pub fn pop_timeout(&self, timeout: Duration) -> Result<T, PopError> {
+        self.pop_until(Some(Instant::now() + timeout))
+    }
+
+    /// 关闭队列并唤醒所有等待者；重复关闭无副作用
+    pub fn close(&self) {
+        if !self.closed.swap(true, Ordering::SeqCst) {
+            let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+            self.not_empty.notify_all();
+            self.not_full.notify_all();
+        }
+    }
+
+    /// 是否已关闭
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// 容量
+    pub fn capacity(&self) -> usize {
+        self.queue.capacity()
+    }
+
+    /// 当前元素个数（近似值）
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// 是否已满
+    pub fn is_full(&self) -> bool {
+        self.queue.is_full()
+    }
+
+    /// 有线程在等待时才加锁通知。等待方先登记再重试，通知方先修改队列再检查登记，
+    /// 两边的 SeqCst 栅栏保证至少有一方看到对方，因此不会丢失唤醒。
+    fn wake(&self, waiters: &AtomicUsize, cv: &Condvar) {
+        fence(Ordering::SeqCst);
+        if waiters.load(Ordering::SeqCst) > 0 {
+            let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+            cv.notify_one();
+        }
+    }
+
+    fn push_until(&self, mut value: T, deadline: Option<Instant>) -> Result<(), PushError<T>> {
+        loop {
+            match self.try_push(value) {
+                Err(PushError::Full(v)) => value = v,
+                other => return other,
+            }
+            // 登记后再检查一次：消费者若在登记前腾出了空位，这里一定能看到
+            let blocked = || self.queue.is_full() && !self.is_closed();
+            if self.wait(&self.push_waiters, &self.not_full, blocked, deadline) {
+                return match self.try_push(value) {
+                    Err(PushError::Full(v)) => Err(PushError::Timeout(v)),
+                    other => other,
+                };
+            }
+        }
+    }
+
+    fn pop_until(&self, deadline: Option<Instant>) -> Result<T, PopError> {
+        loop {
+            match self.try_pop() {
+                Err(PopError::Empty) => {}
+                other => return other,
+            }
+            let blocked = || self.queue.is_empty() && !self.is_closed();
+            if self.wait(&self.pop_waiters, &self.not_empty, blocked, deadline) {
+                return match self.try_pop() {
+                    Err(PopError::Empty) => Err(PopError::Timeout),
+                    other => other,
+                };
+            }
+        }
+    }
+
+    /// 登记为等待者并在 `cv` 上休眠一次；`blocked` 在登记之后、休眠之前检查。
+    /// 返回是否已超过 `deadline`。
+    fn wait(
+        &self,
+        waiters: &AtomicUsize,
+        cv: &Condvar,
+        blocked: impl Fn() -> bool,
+        deadline: Option<Instant>,
+    ) -> bool {
+        let guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        waiters.fetch_add(1, Ordering::SeqCst);
+        fence(Ordering::SeqCst);
+        let timed_out = if !blocked() {
+            false
+        } else {
+            match deadline {
+                None => {
+                    drop(cv.wait(guard).unwrap_or_else(|e| e.into_inner()));
+                    false
+                }
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now < deadline {
+                        drop(cv.wait_timeout(guard, deadline - now));
+                    }
+                    Instant::now() >= deadline
+                }
+            }
+        };
+        waiters.fetch_sub(1, Ordering::SeqCst);
+        timed_out
+    }
+}
+
+impl<T> fmt::Debug for BlockingQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlockingQueue")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity())
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_array_queue_wraps_around() {
+        let q = ArrayQueue::new(3);
+        for round in 0..10 {
+            for i in 0..3 {
+                q.push(round * 10 + i).unwrap();
+            }
+            assert!(q.is_full());
+            assert_eq!(q.push(99), Err(99));
+            for i in 0..3 {
+                assert_eq!(q.pop(), Some(round * 10 + i));
+            }
+            assert!(q.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_array_queue_drops_remaining() {
+        let item = Arc::new(());
+        {
+            let q = ArrayQueue::new(4);
+            q.push(Arc::clone(&item)).unwrap();
+            q.push(Arc::clone(&item)).unwrap();
+            assert_eq!(Arc::strong_count(&item), 3);
+        }
+        assert_eq!(Arc::strong_count(&item), 1);
+    }
+
+    /// 多个生产者和消费者：每个元素恰好被取出一次，且同一生产者的元素保持顺序
+    #[test]
+    fn test_array_queue_concurrent() {
+        const PRODUCERS: usize = 4;
+        const PER: usize = 10_000;
+        let q = Arc::new(ArrayQueue::new(64));
+        let taken = Arc::new(AtomicUsize::new(0));
+        let mut handles = Vec::new();
+        for p in 0..PRODUCERS {
+            let q = Arc::clone(&q);
+            handles.push(thread::spawn(move || {
+                for i in 0..PER {
+                    let mut v = (p, i);
+                    while let Err(back) = q.push(v) {
+                        v = back;
+                        thread::yield_now();
+                    }
+                }
+                Vec::new()
+            }));
+        }
+        for _ in 0..PRODUCERS {
+            let q = Arc::clone(&q);
+            let taken = Arc::clone(&taken);
+            handles.push(thread::spawn(move || {
+                let mut got = Vec::new();
+                while taken.load(Ordering::SeqCst) < PRODUCERS * PER {
+                    if let Some(v) = q.pop() {
+                        taken.fetch_add(1, Ordering::SeqCst);
+                        got.push(v);
+                    } else {
+                        thread::yield_now();
+                    }
+                }
+                got
+            }));
+        }
+        let mut all = Vec::new();
+        for h in handles {
+            let got = h.join().unwrap();
+            for w in got.windows(2) {
+                if w[0].0 == w[1].0 {
+                    assert!(w[0].1 < w[1].1);
+                }
+            }
+            all.extend(got);
+        }
+        all.sort();
+        let expected: Vec<_> = (0..PRODUCERS)
+            .flat_map(|p| (0..PER).map(move |i| (p, i)))
+            .collect();
+        assert_eq!(all, expected);
+    }
+
+    #[test]
+    fn test_blocking_try_and_timeout() {
+        let q = BlockingQueue::new(1);
+        assert_eq!(q.try_pop(), Err(PopError::Empty));
+        assert_eq!(
+            q.pop_timeout(Duration::from_millis(20)),
+            Err(PopError::Timeout)
+        );
+        q.try_push(1).unwrap();
+        assert_eq!(q.try_push(2), Err(PushError::Full(2)));
+        let start = Instant::now();
+        assert_eq!(
+            q.push_timeout(3, Duration::from_millis(20)),
+            Err(PushError::Timeout(3))
+        );
+        assert!(start.elapsed() >= Duration::from_millis(20));
+        assert_eq!(q.pop(), Ok(1));
+    }
+
+    #[test]
+    fn test_blocking_close_wakes_waiters() {
+        let q = Arc::new(BlockingQueue::<i32>::new(1));
+        let waiters: Vec<_> = (0..3)
+            .map(|_| {
+                let q = Arc::clone(&q);
+                thread::spawn(move || q.pop())
+            })
+            .collect();
+        thread::sleep(Duration::from_millis(20));
+        q.close();
+        for w in waiters {
+            assert_eq!(w.join().unwrap(), Err(PopError::Closed));
+        }
+        assert_eq!(q.push(1), Err(PushError::Closed(1)));
+
+        // 关闭前已入队的元素仍可取出
+        let q = BlockingQueue::new(2);
+        q.push("a").unwrap();
+        q.close();
+        assert_eq!(q.pop(), Ok("a"));
+        assert_eq!(q.pop(), Err(PopError::Closed));
+    }
+
+    /// 容量很小时大量阻塞 push / pop，检查没有丢失唤醒导致的死锁或丢数据
+    #[test]
+    fn test_blocking_many_producers_consumers() {
+        let q = Arc::new(BlockingQueue::new(2));
+        let producers: Vec<_> = (0..4)
+            .map(|p| {
+                let q = Arc::clone(&q);
+                thread::spawn(move || {
+                    for i in 0..2_000 {
+                        q.push(p * 10_000 + i).unwrap();
+                    }
+                })
+            })
+            .collect();
+        let consumers: Vec<_> = (0..4)
+            .map(|_| {
+                let q = Arc::clone(&q);
+                thread::spawn(move || {
+                    let mut sum = 0u64;
+                    while let Ok(v) = q.pop() {
+                        sum += v as u64;
+                    }
+                    sum
+                })
+            })
+            .collect();
+        for p in producers {
+            p.join().unwrap();
+        }
+        q.close();
+        let total: u64 = consumers.into_iter().map(|c| c.join().unwrap()).sum();
+        let expected: u64 = (0..4u64)
+            .flat_map(|p| (0..2_000u64).map(move |i| p * 10_000 + i))
+            .sum();
+        assert_eq!(total, expected);
+    }
+}
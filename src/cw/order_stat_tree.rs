@@ -0,0 +1,470 @@
+//! 记录子树大小的 AVL 树，[`TreeMap`](super::TreeMap)、[`TreeSet`](super::TreeSet) 和
+//! [`RbTree`](super::rb_tree::RbTree) 的底层存储
+//!
+//! 标准库的 `BTreeMap` 不记录子树大小，求第 k 小或排名只能遍历；这里每个节点额外保存子树的
+//! 元素个数，查找、插入、删除、[`select`](OrderStatTree::select) 和 [`rank`](OrderStatTree::rank)
+//! 都是 O(log n)。范围迭代器从两端各维护一条路径栈，是惰性的双端迭代器。
+
+use std::cmp::Ordering;
+use std::ops::{Bound, RangeBounds};
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+#[derive(Clone)]
+struct Node<K, V> {
+    key: K,
+    val: V,
+    height: u32,
+    size: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+impl<K, V> Node<K, V> {
+    fn leaf(key: K, val: V) -> Box<Self> {
+        Box::new(Self {
+            key,
+            val,
+            height: 1,
+            size: 1,
+            left: None,
+            right: None,
+        })
+    }
+
+    fn update(&mut self) {
+        self.height = 1 + height(&self.left).max(height(&self.right));
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+}
+
+fn height<K, V>(link: &Link<K, V>) -> u32 {
+    link.as_ref().map_or(0, |n| n.height)
+}
+
+fn size<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |n| n.size)
+}
+
+fn rotate_right<K, V>(mut n: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    let mut l = n.left.take().expect("rotate_right needs a left child");
+    n.left = l.right.take();
+    n.update();
+    l.right = Some(n);
+    l.update();
+    l
+}
+
+fn rotate_left<K, V>(mut n: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    let mut r = n.right.take().expect("rotate_left needs a right child");
+    n.right = r.left.take();
+    n.update();
+    r.left = Some(n);
+    r.update();
+    r
+}
+
+/// 重新计算高度和大小，左右高度差超过 1 时旋转
+fn rebalance<K, V>(mut n: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    n.update();
+    let (lh, rh) = (height(&n.left), height(&n.right));
+    if lh > rh + 1 {
+        let l = n.left.take().expect("left-heavy node has a left child");
+        n.left = Some(if height(&l.left) < height(&l.right) {
+            rotate_left(l)
+        } else {
+            l
+        });
+        rotate_right(n)
+    } else if rh > lh + 1 {
+        let r = n.right.take().expect("right-heavy node has a right child");
+        n.right = Some(if height(&r.right) < height(&r.left) {
+            rotate_right(r)
+        } else {
+            r
+        });
+        rotate_left(n)
+    } else {
+        n
+    }
+}
+
+fn insert<K: Ord, V>(link: Link<K, V>, key: K, val: V) -> (Box<Node<K, V>>, Option<V>) {
+    let Some(mut n) = link else {
+        return (Node::leaf(key, val), None);
+    };
+    match key.cmp(&n.key) {
+        Ordering::Less => {
+            let (l, old) = insert(n.left.take(), key, val);
+            n.left = Some(l);
+            (rebalance(n), old)
+        }
+        Ordering::Greater => {
+            let (r, old) = insert(n.right.take(), key, val);
+            n.right = Some(r);
+            (rebalance(n), old)
+        }
+        Ordering::Equal => {
+            let old = std::mem::replace(&mut n.val, val);
+            (n, Some(old))
+        }
+    }
+}
+
+/// 摘下最小节点，返回剩余的子树和该节点
+fn take_min<K, V>(mut n: Box<Node<K, V>>) -> (Link<K, V>, Box<Node<K, V>>) {
+    match n.left.take() {
+        None => (n.right.take(), n),
+        Some(l) => {
+            let (rest, min) = take_min(l);
+            n.left = rest;
+            (Some(rebalance(n)), min)
+        }
+    }
+}
+
+fn take_max<K, V>(mut n: Box<Node<K, V>>) -> (Link<K, V>, Box<Node<K, V>>) {
+    match n.right.take() {
+        None => (n.left.take(), n),
+        Some(r) => {
+            let (rest, max) = take_max(r);
+            n.right = rest;
+            (Some(rebalance(n)), max)
+        }
+    }
+}
+
+fn remove<K: Ord, V>(link: Link<K, V>, key: &K) -> (Link<K, V>, Option<(K, V)>) {
+    let Some(mut n) = link else {
+        return (None, None);
+    };
+    match key.cmp(&n.key) {
+        Ordering::Less => {
+            let (l, removed) = remove(n.left.take(), key);
+            n.left = l;
+            (Some(rebalance(n)), removed)
+        }
+        Ordering::Greater => {
+            let (r, removed) = remove(n.right.take(), key);
+            n.right = r;
+            (Some(rebalance(n)), removed)
+        }
+        Ordering::Equal => {
+            let Node {
+                key,
+                val,
+                left,
+                right,
+                ..
+            } = *n;
+            let rest = match (left, right) {
+                (None, child) | (child, None) => child,
+                (Some(l), Some(r)) => {
+                    let (r, mut successor) = take_min(r);
+                    successor.left = Some(l);
+                    successor.right = r;
+                    Some(rebalance(successor))
+                }
+            };
+            (rest, Some((key, val)))
+        }
+    }
+}
+
+/// 有序映射，额外支持 O(log n) 的按名次查询
+#[derive(Clone)]
+pub(crate) struct OrderStatTree<K, V> {
+    root: Link<K, V>,
+}
+
+impl<K, V> Default for OrderStatTree<K, V> {
+    fn default() -> Self {
+        Self { root: None }
+    }
+}
+
+impl<K: Ord, V> OrderStatTree<K, V> {
+    pub(crate) fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.root = None;
+    }
+
+    pub(crate) fn get_key_value(&self, key: &K) -> Option<(&K, &V)> {
+        let mut cur = self.root.as_deref();
+        while let Some(n) = cur {
+            cur = match key.cmp(&n.key) {
+                Ordering::Less => n.left.as_deref(),
+                Ordering::Greater => n.right.as_deref(),
+                Ordering::Equal => return Some((&n.key, &n.val)),
+            };
+        }
+        None
+    }
+
+    pub(crate) fn get(&self, key: &K) -> Option<&V> {
+        self.get_key_value(key).map(|(_, v)| v)
+    }
+
+    pub(crate) fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut cur = self.root.as_deref_mut();
+        while let Some(n) = cur {
+            cur = match key.cmp(&n.key) {
+                Ordering::Less => n.left.as_deref_mut(),
+                Ordering::Greater => n.right.as_deref_mut(),
+                Ordering::Equal => return Some(&mut n.val),
+            };
+        }
+        None
+    }
+
+    pub(crate) fn contains_key(&self, key: &K) -> bool {
+        self.get_key_value(key).is_some()
+    }
+
+    /// 插入或覆盖 value，返回旧值；key 已存在时保留原来的 key
+    pub(crate) fn insert(&mut self, key: K, val: V) -> Option<V> {
+        let (root, old) = insert(self.root.take(), key, val);
+        self.root = Some(root);
+        old
+    }
+
+    pub(crate) fn remove(&mut self, key: &K) -> Option<V> {
+        let (root, removed) = remove(self.root.take(), key);
+        self.root = root;
+        removed.map(|(_, v)| v)
+    }
+
+    pub(crate) fn first(&self) -> Option<(&K, &V)> {
+        self.select(0)
+    }
+
+    pub(crate) fn last(&self) -> Option<(&K, &V)> {
+        self.select(self.len().checked_sub(1)?)
+    }
+
+    pub(crate) fn pop_first(&mut self) -> Option<(K, V)> {
+        let (root, min) = take_min(self.root.take()?);
+        self.root = root;
+        Some((min.key, min.val))
+    }
+
+    pub(crate) fn pop_last(&mut self) -> Option<(K, V)> {
+        let (root, max) = take_max(self.root.take()?);
+        self.root = root;
+        Some((max.key, max.val))
+    }
+
+    /// 第 `k` 小（从 0 开始）的键值对
+    pub(crate) fn select(&self, mut k: usize) -> Option<(&K, &V)> {
+        let mut cur = self.root.as_deref();
+        while let Some(n) = cur {
+            let left = size(&n.left);
+            cur = match k.cmp(&left) {
+                Ordering::Less => n.left.as_deref(),
+                Ordering::Equal => return Some((&n.key, &n.val)),
+                Ordering::Greater => {
+                    k -= left + 1;
+                    n.right.as_deref()
+                }
+            };
+        }
+        None
+    }
+
+    /// 严格小于 `key` 的 key 个数
+    pub(crate) fn rank(&self, key: &K) -> usize {
+        self.count_while(|k| k < key)
+    }
+
+    /// 从最小的 key 开始连续满足 `pred` 的个数；`pred` 必须随 key 单调（先真后假）
+    fn count_while(&self, pred: impl Fn(&K) -> bool) -> usize {
+        let mut count = 0;
+        let mut cur = self.root.as_deref();
+        while let Some(n) = cur {
+            cur = if pred(&n.key) {
+                count += size(&n.left) + 1;
+                n.right.as_deref()
+            } else {
+                n.left.as_deref()
+            };
+        }
+        count
+    }
+
+    pub(crate) fn iter(&self) -> Range<'_, K, V> {
+        self.range(..)
+    }
+
+    /// key 落在 `r` 内的双端迭代器；起点大于终点时为空
+    pub(crate) fn range<R: RangeBounds<K>>(&self, r: R) -> Range<'_, K, V> {
+        let after_start = |k: &K| match r.start_bound() {
+            Bound::Included(s) => k >= s,
+            Bound::Excluded(s) => k > s,
+            Bound::Unbounded => true,
+        };
+        let within_end = |k: &K| match r.end_bound() {
+            Bound::Included(e) => k <= e,
+            Bound::Excluded(e) => k < e,
+            Bound::Unbounded => true,
+        };
+        let remaining = self
+            .count_while(within_end)
+            .saturating_sub(self.count_while(|k| !after_start(k)));
+        let mut range = Range {
+            front: Vec::new(),
+            back: Vec::new(),
+            remaining,
+        };
+        if remaining == 0 {
+            return range;
+        }
+        let mut cur = self.root.as_deref();
+        while let Some(n) = cur {
+            cur = if after_start(&n.key) {
+                range.front.push(n);
+                n.left.as_deref()
+            } else {
+                n.right.as_deref()
+            };
+        }
+        let mut cur = self.root.as_deref();
+        while let Some(n) = cur {
+            cur = if within_end(&n.key) {
+                range.back.push(n);
+                n.right.as_deref()
+            } else {
+                n.left.as_deref()
+            };
+        }
+        range
+    }
+}
+
+/// [`OrderStatTree::range`] 返回的双端迭代器
+///
+/// `front` / `back` 是中序遍历两端待访问节点的栈，`remaining` 保证两端相遇后停止。
+pub struct Range<'a, K, V> {
+    front: Vec<&'a Node<K, V>>,
+    back: Vec<&'a Node<K, V>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let n = self.front.pop()?;
+        self.remaining -= 1;
+        let mut cur = n.right.as_deref();
+        while let Some(c) = cur {
+            self.front.push(c);
+            cur = c.left.as_deref();
+        }
+        Some((&n.key, &n.val))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Range<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let n = self.back.pop()?;
+        self.remaining -= 1;
+        let mut cur = n.left.as_deref();
+        while let Some(c) = cur {
+            self.back.push(c);
+            cur = c.right.as_deref();
+        }
+        Some((&n.key, &n.val))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Range<'_, K, V> {}
+
+/// 只取 key 的 [`Range`]，供集合类型使用
+pub struct Keys<'a, K>(Range<'a, K, ()>);
+
+impl<'a, K> From<Range<'a, K, ()>> for Keys<'a, K> {
+    fn from(range: Range<'a, K, ()>) -> Self {
+        Self(range)
+    }
+}
+
+impl<'a, K> Iterator for Keys<'a, K> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<&'a K> {
+        self.0.next().map(|(k, _)| k)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K> DoubleEndedIterator for Keys<'_, K> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(k, _)| k)
+    }
+}
+
+impl<K> ExactSizeIterator for Keys<'_, K> {}
+
+#[cfg(test)]
+mod tests {
+    use super::OrderStatTree;
+    use rand::{RngExt, SeedableRng, rngs::StdRng};
+    use std::collections::BTreeMap;
+
+    /// 检查 AVL 平衡条件和子树大小，返回高度
+    fn check<K: Ord, V>(link: &super::Link<K, V>) -> u32 {
+        let Some(n) = link else { return 0 };
+        let (lh, rh) = (check(&n.left), check(&n.right));
+        assert!(lh.abs_diff(rh) <= 1);
+        assert_eq!(n.size, 1 + super::size(&n.left) + super::size(&n.right));
+        assert_eq!(n.height, 1 + lh.max(rh));
+        n.height
+    }
+
+    #[test]
+    fn random_ops_stay_balanced_and_match_btreemap() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut tree = OrderStatTree::default();
+        let mut reference = BTreeMap::new();
+        for _ in 0..5000 {
+            let k = rng.random_range(0..500);
+            match rng.random_range(0..4) {
+                0 => assert_eq!(tree.remove(&k), reference.remove(&k)),
+                1 => assert_eq!(tree.pop_first(), reference.pop_first()),
+                _ => assert_eq!(tree.insert(k, k * 2), reference.insert(k, k * 2)),
+            }
+        }
+        check(&tree.root);
+        assert_eq!(tree.len(), reference.len());
+        assert!(tree.iter().eq(reference.iter()));
+        assert!(tree.iter().rev().eq(reference.iter().rev()));
+        for (i, (k, v)) in reference.iter().enumerate() {
+            assert_eq!(tree.select(i), Some((k, v)));
+            assert_eq!(tree.rank(k), i);
+        }
+        let mut mid = tree.range(100..400);
+        assert_eq!(mid.len(), reference.range(100..400).count());
+        assert_eq!(mid.next(), reference.range(100..400).next());
+        assert_eq!(mid.next_back(), reference.range(100..400).next_back());
+    }
+}
@@ -0,0 +1,268 @@
+//! 有序容器的公共查询接口
+//!
+//! [`OrderedQuery`] 为 [`TreeMap`](super::TreeMap)、[`TreeSet`](super::TreeSet)、
+//! [`SkipMap`](super::SkipMap)、[`SkipSet`](super::SkipSet) 和 [`RbTree`](super::rb_tree::RbTree)
+//! 提供统一的范围、前驱 / 后继以及排名查询，避免为 "a 到 b 之间的 key"、"不大于 x 的最大 key"
+//! 这类查询做全量扫描。
+//!
+//! 所有实现的范围、前驱 / 后继和排名查询（[`OrderedQuery::select`]、[`OrderedQuery::rank`]）
+//! 都是 O(log n)：`SkipMap` / `SkipSet` 借助跳表每层的跨度，`TreeMap`、`TreeSet` 和 `RbTree`
+//! 底层是记录子树大小的平衡树。
+//!
+//! # 示例
+//!
+//! ```rust
+//! use rust_tools::cw::{OrderedQuery, TreeMap};
+//!
+//! let mut m = TreeMap::new();
+//! for k in [10, 20, 30, 40] {
+//!     m.put(k, k * 10);
+//! }
+//! let keys: Vec<_> = m.range(15..=30).map(|(k, _)| *k).collect();
+//! assert_eq!(keys, [20, 30]);
+//! assert_eq!(m.floor(&25), Some((&20, &200)));
+//! assert_eq!(m.ceiling(&25), Some((&30, &300)));
+//! assert_eq!(m.select(1), Some((&20, &200)));
+//! assert_eq!(m.rank(&30), 2);
+//! ```
+
+use std::ops::{Bound, RangeBounds};
+
+/// 有序容器的范围 / 前驱后继 / 排名查询
+///
+/// - map 类型的元素为 `(&K, &V)`，set 类型的元素为 `&T`；
+/// - 所有范围迭代器都是双端迭代器，可以 `.rev()` 倒序遍历；
+/// - 与 `BTreeMap::range` 不同，起点大于终点的范围返回空迭代器而不是 panic。
+pub trait OrderedQuery<K: Ord> {
+    type Item<'a>
+    where
+        Self: 'a;
+    type Iter<'a>: DoubleEndedIterator<Item = Self::Item<'a>>
+    where
+        Self: 'a;
+
+    /// 返回 key 落在 `range` 内的所有元素（升序）
+    fn range<R: RangeBounds<K>>(&self, range: R) -> Self::Iter<'_>;
+
+    /// 不大于 `key` 的最大元素
+    fn floor(&self, key: &K) -> Option<Self::Item<'_>> {
+        self.range((Bound::Unbounded, Bound::Included(key)))
+            .next_back()
+    }
+
+    /// 不小于 `key` 的最小元素
+    fn ceiling(&self, key: &K) -> Option<Self::Item<'_>> {
+        self.range((Bound::Included(key), Bound::Unbounded)).next()
+    }
+
+    /// 从第一个不小于 `key` 的元素开始的迭代器（C++ `lower_bound` 语义）
+    fn lower_bound(&self, key: &K) -> Self::Iter<'_> {
+        self.range((Bound::Included(key), Bound::Unbounded))
+    }
+
+    /// 从第一个大于 `key` 的元素开始的迭代器（C++ `upper_bound` 语义）
+    fn upper_bound(&self, key: &K) -> Self::Iter<'_> {
+        self.range((Bound::Excluded(key), Bound::Unbounded))
+    }
+
+    /// 第 `k` 小的元素（从 0 开始）
+    ///
+    /// 默认实现遍历前 `k` 个元素，是 O(k)；本 crate 里的实现都覆盖为 O(log n)。
+    fn select(&self, k: usize) -> Option<Self::Item<'_>> {
+        self.range(..).nth(k)
+    }
+
+    /// 严格小于 `key` 的元素个数（`key` 存在时即为它的下标）
+    ///
+    /// 默认实现数出所有更小的元素，是 O(n)；本 crate 里的实现都覆盖为 O(log n)。
+    fn rank(&self, key: &K) -> usize {
+        self.range((Bound::Unbounded, Bound::Excluded(key))).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OrderedQuery;
+    use crate::cw::rb_tree::RbTree;
+    use crate::cw::{SkipMap, SkipSet, TreeMap, TreeSet};
+    use rand::{RngExt, SeedableRng, rngs::StdRng};
+    use std::collections::BTreeMap;
+    use std::ops::{Bound, RangeBounds};
+
+    /// 判断范围是否会让 `BTreeMap::range` panic（起点大于终点，或两端都排除同一个值）
+    fn is_valid_range<K: Ord, R: RangeBounds<K>>(range: &R) -> bool {
+        use Bound::*;
+        match (range.start_bound(), range.end_bound()) {
+            (Excluded(s), Excluded(e)) => s < e,
+            (Included(s) | Excluded(s), Included(e) | Excluded(e)) => s <= e,
+            _ => true,
+        }
+    }
+
+    fn random_bound(rng: &mut StdRng) -> Bound<i32> {
+        match rng.random_range(0..3) {
+            0 => Bound::Unbounded,
+            1 => Bound::Included(rng.random_range(-5..105)),
+            _ => Bound::Excluded(rng.random_range(-5..105)),
+        }
+    }
+
+    /// 以 BTreeMap 为参照，对四种容器做随机操作后比较所有查询结果
+    #[test]
+    fn property_matches_btreemap() {
+        let mut rng = StdRng::seed_from_u64(0x5eed);
+        for round in 0..40 {
+            let mut reference = BTreeMap::new();
+            let mut tree_map = TreeMap::new();
+            let mut tree_set = TreeSet::new();
+            let mut skip_map = SkipMap::default();
+            let mut skip_set = SkipSet::new(8);
+            let mut rb_tree = RbTree::new();
+
+            for _ in 0..rng.random_range(0..200) {
+                let k: i32 = rng.random_range(0..100);
+                if rng.random_range(0..4) == 0 {
+                    reference.remove(&k);
+                    tree_map.delete(&k);
+                    tree_set.delete(&k);
+                    skip_map.remove(&k);
+                    skip_set.remove(&k);
+                    rb_tree.delete(&k);
+                } else {
+                    reference.insert(k, k * 2);
+                    tree_map.put(k, k * 2);
+                    tree_set.add(k);
+                    skip_map.insert(k, k * 2);
+                    skip_set.insert(k);
+                    rb_tree.insert(k);
+                }
+            }
+
+            let keys: Vec<i32> = reference.keys().copied().collect();
+            for _ in 0..50 {
+                let range = (random_bound(&mut rng), random_bound(&mut rng));
+                let expected: Vec<i32> = if is_valid_range(&range) {
+                    reference.range(range).map(|(k, _)| *k).collect()
+                } else {
+                    Vec::new()
+                };
+                let mut expected_rev = expected.clone();
+                expected_rev.reverse();
+
+                let got: Vec<i32> = OrderedQuery::range(&tree_map, range)
+                    .map(|(k, _)| *k)
+                    .collect();
+                assert_eq!(got, expected, "round {round} TreeMap {range:?}");
+                let got: Vec<i32> = OrderedQuery::range(&tree_set, range).copied().collect();
+                assert_eq!(got, expected, "round {round} TreeSet {range:?}");
+                let got: Vec<i32> = OrderedQuery::range(&skip_map, range)
+                    .map(|(k, _)| *k)
+                    .collect();
+                assert_eq!(got, expected, "round {round} SkipMap {range:?}");
+                let got: Vec<i32> = OrderedQuery::range(&skip_set, range).copied().collect();
+                assert_eq!(got, expected, "round {round} SkipSet {range:?}");
+                let got: Vec<i32> = OrderedQuery::range(&rb_tree, range).copied().collect();
+                assert_eq!(got, expected, "round {round} RbTree {range:?}");
+
+                let got: Vec<i32> = OrderedQuery::range(&skip_map, range)
+                    .rev()
+                    .map(|(k, _)| *k)
+                    .collect();
+                assert_eq!(got, expected_rev, "round {round} SkipMap rev {range:?}");
+                let got: Vec<i32> = OrderedQuery::range(&tree_set, range)
+                    .rev()
+                    .copied()
+                    .collect();
+                assert_eq!(got, expected_rev, "round {round} TreeSet rev {range:?}");
+
+                // 双端交替消费
+                let mut it = OrderedQuery::range(&skip_map, range);
+                let mut front = Vec::new();
+                let mut back = Vec::new();
+                while let Some((k, _)) = it.next() {
+                    front.push(*k);
+                    match it.next_back() {
+                        Some((k, _)) => back.push(*k),
+                        None => break,
+                    }
+                }
+                back.reverse();
+                front.extend(back);
+                assert_eq!(front, expected, "round {round} SkipMap mixed {range:?}");
+            }
+
+            for probe in -2..103 {
+                let floor = reference.range(..=probe).next_back().map(|(k, _)| *k);
+                let ceiling = reference.range(probe..).next().map(|(k, _)| *k);
+                let rank = reference.range(..probe).count();
+                let upper: Vec<i32> = keys.iter().copied().filter(|k| *k > probe).collect();
+
+                assert_eq!(tree_map.floor(&probe).map(|(k, _)| *k), floor);
+                assert_eq!(tree_set.floor(&probe).copied(), floor);
+                assert_eq!(skip_map.floor(&probe).map(|(k, _)| *k), floor);
+                assert_eq!(skip_set.floor(&probe).copied(), floor);
+                assert_eq!(rb_tree.floor(&probe).copied(), floor);
+
+                assert_eq!(tree_map.ceiling(&probe).map(|(k, _)| *k), ceiling);
+                assert_eq!(tree_set.ceiling(&probe).copied(), ceiling);
+                assert_eq!(skip_map.ceiling(&probe).map(|(k, _)| *k), ceiling);
+                assert_eq!(skip_set.ceiling(&probe).copied(), ceiling);
+                assert_eq!(rb_tree.ceiling(&probe).copied(), ceiling);
+
+                assert_eq!(
+                    tree_map.lower_bound(&probe).next().map(|(k, _)| *k),
+                    ceiling
+                );
+                assert_eq!(skip_set.lower_bound(&probe).next().copied(), ceiling);
+                assert_eq!(
+                    skip_map
+                        .upper_bound(&probe)
+                        .map(|(k, _)| *k)
+                        .collect::<Vec<_>>(),
+                    upper
+                );
+                assert_eq!(
+                    rb_tree.upper_bound(&probe).copied().collect::<Vec<_>>(),
+                    upper
+                );
+
+                assert_eq!(tree_map.rank(&probe), rank);
+                assert_eq!(tree_set.rank(&probe), rank);
+                assert_eq!(skip_map.rank(&probe), rank);
+                assert_eq!(skip_set.rank(&probe), rank);
+                assert_eq!(rb_tree.rank(&probe), rank);
+            }
+
+            for k in 0..=keys.len() {
+                let expected = keys.get(k).copied();
+                assert_eq!(tree_map.select(k).map(|(k, _)| *k), expected);
+                assert_eq!(tree_set.select(k).copied(), expected);
+                assert_eq!(
+                    skip_map.select(k).map(|(k, v)| (*k, *v)),
+                    expected.map(|k| (k, k * 2))
+                );
+                assert_eq!(skip_set.select(k).copied(), expected);
+                assert_eq!(rb_tree.select(k).copied(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn skip_map_rank_select_survive_clear_and_drain() {
+        let mut m = SkipMap::default();
+        for i in 0..50 {
+            m.insert(i, ());
+        }
+        m.clear();
+        for i in (0..20).rev() {
+            m.insert(i * 3, ());
+        }
+        assert_eq!(m.select(4).map(|(k, _)| *k), Some(12));
+        assert_eq!(m.rank(&13), 5);
+        let _ = m.drain().count();
+        m.insert(7, ());
+        assert_eq!(m.select(0).map(|(k, _)| *k), Some(7));
+        assert_eq!(m.select(1), None);
+        assert_eq!(m.rank(&100), 1);
+    }
+}
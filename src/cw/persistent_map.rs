@@ -0,0 +1,550 @@
+//! 持久化哈希映射（HAMT，Hash Array Mapped Trie）
+//!
+//! 每层取哈希值的 5 位作为下标，用 32 位位图压缩稀疏的子节点数组。
+//! 节点放在 `Arc` 中：`clone` 是 O(1) 的，修改时只复制从根到目标的一条路径，
+//! 其余子树在新旧版本之间共享。
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use rustc_hash::FxHasher;
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+const BITS: u32 = 5;
+const MASK: u64 = (1 << BITS) - 1;
+
+fn hash_of<K: Hash + ?Sized>(key: &K) -> u64 {
+    let mut hasher = FxHasher::default();
+    key.hash(&mut hasher);
+    // FxHash 的高位更随机，再混合一次让每一层的 5 位都分布均匀
+    let mut z = hasher.finish();
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[derive(Clone)]
+enum Slot<K, V> {
+    Leaf {
+        hash: u64,
+        key: K,
+        value: V,
+    },
+    Branch(Arc<Branch<K, V>>),
+    /// 64 位哈希完全相同的键
+    Collision(Arc<Collision<K, V>>),
+}
+
+#[derive(Clone)]
+struct Branch<K, V> {
+    bitmap: u32,
+    slots: Vec<Slot<K, V>>,
+}
+
+#[derive(Clone)]
+struct Collision<K, V> {
+    hash: u64,
+    entries: Vec<(K, V)>,
+}
+
+impl<K, V> Branch<K, V> {
+    fn empty() -> Self {
+        Self {
+            bitmap: 0,
+            slots: Vec::new(),
+        }
+    }
+
+    /// (位图中的位, 在 `slots` 中的下标)
+    fn locate(&self, hash: u64, shift: u32) -> (u32, usize) {
+        let bit = 1u32 << ((hash >> shift) & MASK);
+        (bit, (self.bitmap & (bit - 1)).count_ones() as usize)
+    }
+}
+
+/// 持久化哈希映射
+///
+/// - `clone` O(1)，新旧版本共享未修改的子树
+/// - `get` / `insert` / `remove` O(log₃₂ n)
+/// - 修改既可以原地进行（`&mut self`，只复制与其他版本共享的路径），
+///   也可以用 [`update`](Self::update) / [`without`](Self::without) 返回新版本
+///
+/// # 示例
+///
+/// ```rust
+/// use rust_tools::cw::PersistentMap;
+///
+/// let mut v1 = PersistentMap::new();
+/// v1.insert("model", "gpt");
+/// v1.insert("temperature", "0.2");
+///
+/// // 分叉出一个新版本，原版本不受影响
+/// let v2 = v1.update("model", "claude");
+/// assert_eq!(v1.get(&"model"), Some(&"gpt"));
+/// assert_eq!(v2.get(&"model"), Some(&"claude"));
+///
+/// let v3 = v2.without(&"temperature");
+/// assert_eq!(v3.len(), 1);
+/// assert_eq!(v2.len(), 2);
+/// ```
+pub struct PersistentMap<K, V> {
+    root: Arc<Branch<K, V>>,
+    len: usize,
+}
+
+impl<K, V> Clone for PersistentMap<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            root: Arc::clone(&self.root),
+            len: self.len,
+        }
+    }
+}
+
+impl<K, V> PersistentMap<K, V> {
+    /// 创建空映射
+    pub fn new() -> Self {
+        Self {
+            root: Arc::new(Branch::empty()),
+            len: 0,
+        }
+    }
+
+    /// 键值对个数
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 两个映射是否共享同一个根（O(1)；为真则内容一定相同）
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.root, &other.root)
+    }
+
+    /// 遍历所有键值对（按哈希顺序）
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            stack: vec![self.root.slots.iter()],
+            collision: [].iter(),
+            remaining: self.len,
+        }
+    }
+
+    /// 遍历所有键
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    /// 遍历所有值
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, v)| v)
+    }
+}
+
+impl<K, V> PersistentMap<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    /// 查找键对应的值
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let hash = hash_of(key);
+        let mut branch = &*self.root;
+        let mut shift = 0;
+        loop {
+            let (bit, idx) = branch.locate(hash, shift);
+            if branch.bitmap & bit == 0 {
+                return None;
+            }
+            match &branch.slots[idx] {
+                Slot::Leaf { key: k, value, .. } => return (k == key).then_some(value),
+                Slot::Branch(child) => {
+                    branch = child;
+                    shift += BITS;
+                }
+                Slot::Collision(c) => {
+                    return c.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v);
+                }
+            }
+        }
+    }
+
+    /// 是否包含键
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// 插入或覆盖，返回旧值
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let hash = hash_of(&key);
+        let old = insert(Arc::make_mut(&mut self.root), hash, 0, key, value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// 删除键，返回旧值
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        if !self.contains_key(key) {
+            // 避免为不存在的键复制路径
+            return None;
+        }
+        let hash = hash_of(key);
+        let old = remove(Arc::make_mut(&mut self.root), hash, 0, key);
+        if old.is_some() {
+            self.len -= 1;
+        }
+        old
+    }
+
+    /// 返回插入 `key -> value` 后的新版本，自身不变
+    pub fn update(&self, key: K, value: V) -> Self {
+        let mut next = self.clone();
+        next.insert(key, value);
+        next
+    }
+
+    /// 返回删除 `key` 后的新版本，自身不变
+    pub fn without(&self, key: &K) -> Self {
+        let mut next = self.clone();
+        next.remove(key);
+        next
+    }
+}
+
+fn insert<K: Eq + Clone, V: Clone>(
+    branch: &mut Branch<K, V>,
+    hash: u64,
+    shift: u32,
+    key: K,
+    value: V,
+) -> Option<V> {
+    let (bit, idx) = branch.locate(hash, shift);
+    if branch.bitmap & bit == 0 {
+        branch.bitmap |= bit;
+        branch.slots.insert(idx, Slot::Leaf { hash, key, value });
+        return None;
+    }
+    let slot = &mut branch.slots[idx];
+    match slot {
+        Slot::Leaf {
+            key: k, value: v, ..
+        } if *k == key => Some(std::mem::replace(v, value)),
+        Slot::Leaf { .. } => {
+            let existing = std::mem::replace(slot, Slot::Branch(Arc::new(Branch::empty())));
+            *slot = merge(existing, Slot::Leaf { hash, key, value }, shift + BITS);
+            None
+        }
+        Slot::Branch(child) => insert(Arc::make_mut(child), hash, shift + BITS, key, value),
+        Slot::Collision(c) if c.hash == hash => {
+            let c = Arc::make_mut(c);
+            if let Some((_, v)) = c.entries.iter_mut().find(|(k, _)| *k == key) {
+                return Some(std::mem::replace(v, value));
+            }
+            c.entries.push((key, value));
+            None
+        }
+        Slot::Collision(_) => {
+            let existing = std::mem::replace(slot, Slot::Branch(Arc::new(Branch::empty())));
+            *slot = merge(existing, Slot::Leaf { hash, key, value }, shift + BITS);
+            None
+        }
+    }
+}
+
+fn slot_hash<K, V>(slot: &Slot<K, V>) -> u64 {
+    match slot {
+        Slot::Leaf { hash, .. } => *hash,
+        Slot::Collision(c) => c.hash,
+        Slot::Branch(_) => unreachable!("only leaves and collisions are merged"),
+    }
+}
+
+/// 把两个落在同一位置的叶子（或冲突节点）合并成子树
+fn merge<K: Clone, V: Clone>(a: Slot<K, V>, b: Slot<K, V>, shift: u32) -> Slot<K, V> {
+    let (ha, hb) = (slot_hash(&a), slot_hash(&b));
+    if ha == hb {
+        let mut entries = Vec::new();
+        for slot in [a, b] {
+            match slot {
+                Slot::Leaf { key, value, .. } => entries.push((key, value)),
+                Slot::Collision(c) => entries.extend(Arc::unwrap_or_clone(c).entries),
+                Slot::Branch(_) => unreachable!(),
+            }
+        }
+        return Slot::Collision(Arc::new(Collision { hash: ha, entries }));
+    }
+    let (ia, ib) = ((ha >> shift) & MASK, (hb >> shift) & MASK);
+    let mut branch = Branch::empty();
+    if ia == ib {
+        branch.bitmap = 1 << ia;
+        branch.slots.push(merge(a, b, shift + BITS));
+    } else {
+        branch.bitmap = (1 << ia) | (1 << ib);
+        branch.slots = if ia < ib { vec![a, b] } else { vec![b, a] };
+    }
+    Slot::Branch(Arc::new(branch))
+}
+
+fn remove<K: Eq + Clone, V: Clone>(
+    branch: &mut Branch<K, V>,
+    hash: u64,
+    shift: u32,
+    key: &K,
+) -> Option<V> {
+    let (bit, idx) = branch.locate(hash, shift);
+    if branch.bitmap & bit == 0 {
+        return None;
+    }
+    match &mut branch.slots[idx] {
+        Slot::Leaf { key: k, .. } if k == key => {
+            branch.bitmap &= !bit;
+            let Slot::Leaf { value, .. } = branch.slots.remove(idx) else {
+                unreachable!()
+            };
+            Some(value)
+        }
+        Slot::Leaf { .. } => None,
+        Slot::Branch(child) => {
+            let child = Arc::make_mut(child);
+            let removed = remove(child, hash, shift + BITS, key);
+            // 子树只剩一个叶子（或冲突节点）时上提，保持树的紧凑
+            if child.slots.len() == 1 && !matches!(child.slots[0], Slot::Branch(_)) {
+                let only = child.slots.pop().unwrap();
+                branch.slots[idx] = only;
+            }
+            removed
+        }
+        Slot::Collision(c) => {
+            let c = Arc::make_mut(c);
+            let pos = c.entries.iter().position(|(k, _)| k == key);
+            let removed = pos.map(|p| c.entries.remove(p).1);
+            if c.entries.len() == 1 {
+                let (key, value) = c.entries.pop().unwrap();
+                branch.slots[idx] = Slot::Leaf { hash, key, value };
+            }
+            removed
+        }
+    }
+}
+
+/// [`PersistentMap::iter`] 返回的迭代器
+pub struct Iter<'a, K, V> {
+    stack: Vec<std::slice::Iter<'a, Slot<K, V>>>,
+    collision: std::slice::Iter<'a, (K, V)>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((k, v)) = self.collision.next() {
+                self.remaining -= 1;
+                return Some((k, v));
+            }
+            match self.stack.last_mut()?.next() {
+                Some(Slot::Leaf { key, value, .. }) => {
+                    self.remaining -= 1;
+                    return Some((key, value));
+                }
+                Some(Slot::Branch(child)) => self.stack.push(child.slots.iter()),
+                Some(Slot::Collision(c)) => self.collision = c.entries.iter(),
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<'a, K, V> IntoIterator for &'a PersistentMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, V> Default for PersistentMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> FromIterator<(K, V)> for PersistentMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Extend<(K, V)> for PersistentMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone + PartialEq> PartialEq for PersistentMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len
+            && (self.ptr_eq(other) || self.iter().all(|(k, v)| other.get(k) == Some(v)))
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone + Eq> Eq for PersistentMap<K, V> {}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for PersistentMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Serialize, V: Serialize> Serialize for PersistentMap<K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+impl<'de, K, V> Deserialize<'de> for PersistentMap<K, V>
+where
+    K: Deserialize<'de> + Hash + Eq + Clone,
+    V: Deserialize<'de> + Clone,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct MapVisitor<K, V>(PhantomData<(K, V)>);
+
+        impl<'de, K, V> Visitor<'de> for MapVisitor<K, V>
+        where
+            K: Deserialize<'de> + Hash + Eq + Clone,
+            V: Deserialize<'de> + Clone,
+        {
+            type Value = PersistentMap<K, V>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a map")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Self::Value, A::Error> {
+                let mut map = PersistentMap::new();
+                while let Some((k, v)) = access.next_entry()? {
+                    map.insert(k, v);
+                }
+                Ok(map)
+            }
+        }
+
+        deserializer.deserialize_map(MapVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commonw::types::FastMap;
+    use rand::{RngExt, SeedableRng, rngs::StdRng};
+
+    /// 只用 `id % 4` 参与哈希，制造大量完全冲突的键
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    struct Colliding(u32);
+
+    impl Hash for Colliding {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            (self.0 % 4).hash(state);
+        }
+    }
+
+    #[test]
+    fn test_versions_are_independent() {
+        let base: PersistentMap<u32, u32> = (0..1000).map(|i| (i, i)).collect();
+        let mut fork = base.clone();
+        assert!(fork.ptr_eq(&base));
+        for i in 0..500 {
+            fork.insert(i, i * 10);
+        }
+        fork.remove(&999);
+        assert_eq!(base.len(), 1000);
+        assert_eq!(base.get(&10), Some(&10));
+        assert_eq!(base.get(&999), Some(&999));
+        assert_eq!(fork.len(), 999);
+        assert_eq!(fork.get(&10), Some(&100));
+        assert_eq!(fork.get(&999), None);
+        assert_ne!(base, fork);
+    }
+
+    #[test]
+    fn test_collisions() {
+        let mut m = PersistentMap::new();
+        for i in 0..40 {
+            assert_eq!(m.insert(Colliding(i), i), None);
+        }
+        assert_eq!(m.len(), 40);
+        let snapshot = m.clone();
+        for i in (0..40).step_by(2) {
+            assert_eq!(m.remove(&Colliding(i)), Some(i));
+        }
+        for i in 0..40 {
+            assert_eq!(m.get(&Colliding(i)).is_some(), i % 2 == 1);
+            assert_eq!(snapshot.get(&Colliding(i)), Some(&i));
+        }
+        assert_eq!(m.iter().count(), 20);
+    }
+
+    #[test]
+    fn test_serde_roundtrip() {
+        let m: PersistentMap<String, Vec<u32>> =
+            [("a".to_string(), vec![1]), ("b".to_string(), vec![2, 3])]
+                .into_iter()
+                .collect();
+        let json = serde_json::to_string(&m).unwrap();
+        let back: PersistentMap<String, Vec<u32>> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, m);
+    }
+
+    /// 随机操作与 `FastMap` 对比，同时保留历史版本检查其不被后续修改影响
+    #[test]
+    fn property_matches_hash_map() {
+        let mut rng = StdRng::seed_from_u64(0x4a57);
+        let mut map = PersistentMap::new();
+        let mut reference: FastMap<u32, u32> = FastMap::default();
+        let mut history = Vec::new();
+        for step in 0..5000u32 {
+            let key = rng.random_range(0..600);
+            if rng.random_range(0..3) == 0 {
+                assert_eq!(map.remove(&key), reference.remove(&key));
+            } else {
+                assert_eq!(map.insert(key, step), reference.insert(key, step));
+            }
+            assert_eq!(map.len(), reference.len());
+            if step % 500 == 0 {
+                history.push((map.clone(), reference.clone()));
+            }
+        }
+        for (snapshot, expected) in history.iter().chain([(map, reference)].iter()) {
+            assert_eq!(snapshot.len(), expected.len());
+            assert_eq!(snapshot.iter().len(), expected.len());
+            for (k, v) in snapshot {
+                assert_eq!(expected.get(k), Some(v));
+            }
+        }
+    }
+}
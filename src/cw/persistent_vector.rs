@@ -0,0 +1,748 @@
+//! 持久化向量（RRB-Tree，Relaxed Radix Balanced Tree）
+//!
+//! 32 叉树，元素都在叶子上。内部节点记录子树的累计长度（size table），
+//! 因此子节点不必是满的：拼接时只需重新平衡两棵树相接的边界，`concat` / `split`
+//! 都是 O(log n)。节点放在 `Arc` 中，`clone` 为 O(1)，修改只复制一条路径。
+
+use std::fmt;
+use std::ops::Index;
+use std::sync::Arc;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+const M: usize = 32;
+/// 拼接时允许比最优节点数多出的节点个数（RRB 的搜索步数不变量）
+const EXTRA: usize = 2;
+
+enum Node<T> {
+    Leaf(Arc<Vec<T>>),
+    Branch(Arc<Branch<T>>),
+}
+
+impl<T> Clone for Node<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Node::Leaf(items) => Node::Leaf(Arc::clone(items)),
+            Node::Branch(b) => Node::Branch(Arc::clone(b)),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Branch<T> {
+    children: Vec<Node<T>>,
+    /// `sizes[i]` 是前 `i + 1` 个子树的元素总数
+    sizes: Vec<usize>,
+}
+
+impl<T> Branch<T> {
+    fn new(children: Vec<Node<T>>) -> Self {
+        let mut total = 0;
+        let sizes = children
+            .iter()
+            .map(|c| {
+                total += c.len();
+                total
+            })
+            .collect();
+        Self { children, sizes }
+    }
+
+    fn len(&self) -> usize {
+        self.sizes.last().copied().unwrap_or(0)
+    }
+
+    /// 返回 (子节点下标, 子树内的偏移)
+    fn locate(&self, index: usize) -> (usize, usize) {
+        let i = self.sizes.partition_point(|&s| s <= index);
+        let before = if i == 0 { 0 } else { self.sizes[i - 1] };
+        (i, index - before)
+    }
+}
+
+impl<T> Node<T> {
+    fn len(&self) -> usize {
+        match self {
+            Node::Leaf(items) => items.len(),
+            Node::Branch(b) => b.len(),
+        }
+    }
+
+    /// 直接子元素个数（叶子为元素数，内部节点为子节点数）
+    fn slots(&self) -> usize {
+        match self {
+            Node::Leaf(items) => items.len(),
+            Node::Branch(b) => b.children.len(),
+        }
+    }
+
+    fn branch(children: Vec<Node<T>>) -> Self {
+        Node::Branch(Arc::new(Branch::new(children)))
+    }
+
+    fn children(&self) -> &[Node<T>] {
+        match self {
+            Node::Branch(b) => &b.children,
+            Node::Leaf(_) => unreachable!("leaf has no children"),
+        }
+    }
+}
+
+/// 持久化向量
+///
+/// - `clone` O(1)，各版本共享未修改的部分
+/// - `get` / `set` / `push_back` / `pop_back` / `split_off` / `append` 均为 O(log n)
+///
+/// # 示例
+///
+/// ```rust
+/// use rust_tools::cw::PersistentVector;
+///
+/// let transcript: PersistentVector<String> = (0..1000).map(|i| format!("msg {i}")).collect();
+///
+/// // 分叉：只复制被修改的路径
+/// let mut fork = transcript.clone();
+/// fork.set(0, "edited".to_string());
+/// fork.push_back("new".to_string());
+/// assert_eq!(transcript[0], "msg 0");
+/// assert_eq!(fork[0], "edited");
+/// assert_eq!(fork.len(), 1001);
+///
+/// // 切分和拼接
+/// let (head, tail) = transcript.split_at(10);
+/// assert_eq!(head.len(), 10);
+/// assert_eq!(tail[0], "msg 10");
+/// assert_eq!(head.concat(&tail), transcript);
+/// ```
+pub struct PersistentVector<T> {
+    root: Node<T>,
+    /// 根节点的高度，叶子为 0
+    height: usize,
+}
+
+impl<T> Clone for PersistentVector<T> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            height: self.height,
+        }
+    }
+}
+
+impl<T> PersistentVector<T> {
+    /// 创建空向量
+    pub fn new() -> Self {
+        Self {
+            root: Node::Leaf(Arc::new(Vec::new())),
+            height: 0,
+        }
+    }
+
+    /// 元素个数
+    pub fn len(&self) -> usize {
+        self.root.len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 第 `index` 个元素
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len() {
+            return None;
+        }
+        let mut node = &self.root;
+        let mut index = index;
+        loop {
+            match node {
+                Node::Leaf(items) => return items.get(index),
+                Node::Branch(b) => {
+                    let (i, offset) = b.locate(index);
+                    node = &b.children[i];
+                    index = offset;
+                }
+            }
+        }
+    }
+
+    /// 第一个元素
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// 最后一个元素
+    pub fn back(&self) -> Option<&T> {
+        self.len().checked_sub(1).and_then(|i| self.get(i))
+    }
+
+    /// 两个向量是否共享同一个根（O(1)；为真则内容一定相同）
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.root, &other.root) {
+            (Node::Leaf(a), Node::Leaf(b)) => Arc::ptr_eq(a, b),
+            (Node::Branch(a), Node::Branch(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    /// 按顺序遍历
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (stack, leaf) = match &self.root {
+            Node::Leaf(items) => (Vec::new(), items.iter()),
+            Node::Branch(b) => (vec![b.children.iter()], [].iter()),
+        };
+        Iter {
+            stack,
+            leaf,
+            remaining: self.len(),
+        }
+    }
+
+    /// 树高（仅用于测试平衡性）
+    #[cfg(test)]
+    fn height(&self) -> usize {
+        self.height
+    }
+}
+
+impl<T: Clone> PersistentVector<T> {
+    /// 替换第 `index` 个元素，返回旧值
+    ///
+    /// # Panics
+    ///
+    /// `index` 越界时 panic
+    pub fn set(&mut self, index: usize, value: T) -> T {
+        let len = self.len();
+        assert!(index < len, "index {index} out of range for length {len}");
+        let mut node = &mut self.root;
+        let mut index = index;
+        loop {
+            match node {
+                Node::Leaf(items) => {
+                    return std::mem::replace(&mut Arc::make_mut(items)[index], value);
+                }
+                Node::Branch(b) => {
+                    let b = Arc::make_mut(b);
+                    let (i, offset) = b.locate(index);
+                    node = &mut b.children[i];
+                    index = offset;
+                }
+            }
+        }
+    }
+
+    /// 返回替换第 `index` 个元素后的新版本，自身不变
+    pub fn update(&self, index: usize, value: T) -> Self {
+        let mut next = self.clone();
+        next.set(index, value);
+        next
+    }
+
+    /// 追加到末尾
+    pub fn push_back(&mut self, value: T) {
+        if let Some(sibling) = push_back(&mut self.root, value) {
+            let old = std::mem::replace(&mut self.root, Node::Leaf(Arc::new(Vec::new())));
+            self.root = Node::branch(vec![old, sibling]);
+            self.height += 1;
+        }
+    }
+
+    /// 移除并返回最后一个元素
+    pub fn pop_back(&mut self) -> Option<T> {
+        let len = self.len();
+        if len == 0 {
+            return None;
+        }
+        let last = self.split_off(len - 1);
+        last.get(0).cloned()
+    }
+
+    /// 把 `other` 拼接到末尾，O(log n)
+    pub fn append(&mut self, other: Self) {
+        if other.is_empty() {
+            return;
+        }
+        if self.is_empty() {
+            *self = other;
+            return;
+        }
+        let left = std::mem::take(self);
+        let height = left.height.max(other.height);
+        let mut merged = concat_nodes(left.root, left.height, other.root, other.height);
+        *self = if merged.len() == 1 {
+            Self {
+                root: merged.pop().unwrap(),
+                height,
+            }
+        } else {
+            Self {
+                root: Node::branch(merged),
+                height: height + 1,
+            }
+        };
+    }
+
+    /// 返回 `self` 与 `other` 拼接后的新版本
+    pub fn concat(&self, other: &Self) -> Self {
+        let mut next = self.clone();
+        next.append(other.clone());
+        next
+    }
+
+    /// 在 `at` 处切开：`self` 保留 `[0, at)`，返回 `[at, len)`，O(log n)
+    ///
+    /// # Panics
+    ///
+    /// `at > len` 时 panic
+    pub fn split_off(&mut self, at: usize) -> Self {
+        let len = self.len();
+        assert!(at <= len, "split index {at} out of range for length {len}");
+        if at == len {
+            return Self::new();
+        }
+        if at == 0 {
+            return std::mem::take(self);
+        }
+        let root = std::mem::replace(&mut self.root, Node::Leaf(Arc::new(Vec::new())));
+        let (left, right) = split_node(root, at);
+        let height = self.height;
+        *self = Self::trimmed(left, height);
+        Self::trimmed(right, height)
+    }
+
+    /// 返回 `([0, at), [at, len))` 两个新版本，自身不变
+    pub fn split_at(&self, at: usize) -> (Self, Self) {
+        let mut left = self.clone();
+        let right = left.split_off(at);
+        (left, right)
+    }
+
+    /// 去掉只有一个子节点的根
+    fn trimmed(node: Option<Node<T>>, mut height: usize) -> Self {
+        let Some(mut root) = node else {
+            return Self::new();
+        };
+        while let Node::Branch(b) = &root {
+            if b.children.len() != 1 {
+                break;
+            }
+            root = b.children[0].clone();
+            height -= 1;
+        }
+        Self { root, height }
+    }
+}
+
+/// 追加到子树末尾；子树已满时返回同高度的新兄弟节点
+fn push_back<T: Clone>(node: &mut Node<T>, value: T) -> Option<Node<T>> {
+    match node {
+        Node::Leaf(items) => {
+            if items.len() < M {
+                Arc::make_mut(items).push(value);
+                None
+            } else {
+                Some(Node::Leaf(Arc::new(vec![value])))
+            }
+        }
+        Node::Branch(b) => {
+            let b = Arc::make_mut(b);
+            let last = b.children.last_mut().expect("branch is never empty");
+            match push_back(last, value) {
+                None => {
+                    *b.sizes.last_mut().unwrap() += 1;
+                    None
+                }
+                Some(sibling) if b.children.len() < M => {
+                    b.sizes.push(b.len() + 1);
+                    b.children.push(sibling);
+                    None
+                }
+                Some(sibling) => Some(Node::branch(vec![sibling])),
+            }
+        }
+    }
+}
+
+/// 把子树在 `at` 处切开，两边高度不变；空的一边为 `None`
+fn split_node<T: Clone>(node: Node<T>, at: usize) -> (Option<Node<T>>, Option<Node<T>>) {
+    let wrap = |node: Node<T>| (node.len() > 0).then_some(node);
+    match node {
+        Node::Leaf(items) => {
+            let left = items[..at].to_vec();
+            let right = items[at..].to_vec();
+            (
+                wrap(Node::Leaf(Arc::new(left))),
+                wrap(Node::Leaf(Arc::new(right))),
+            )
+        }
+        Node::Branch(b) => {
+            let (i, offset) = b.locate(at);
+            let mut left: Vec<Node<T>> = b.children[..i].to_vec();
+            let mut right: Vec<Node<T>> = Vec::new();
+            if i < b.children.len() {
+                if offset == 0 {
+                    right.push(b.children[i].clone());
+                } else {
+                    let (l, r) = split_node(b.children[i].clone(), offset);
+                    left.extend(l);
+                    right.extend(r);
+                }
+                right.extend(b.children[i + 1..].iter().cloned());
+            }
+            let build =
+                |children: Vec<Node<T>>| (!children.is_empty()).then(|| Node::branch(children));
+            (build(left), build(right))
+        }
+    }
+}
+
+/// 拼接高度为 `lh` 和 `rh` 的两棵子树，返回 1 或 2 个高度为 `max(lh, rh)` 的节点
+fn concat_nodes<T: Clone>(left: Node<T>, lh: usize, right: Node<T>, rh: usize) -> Vec<Node<T>> {
+    if lh == 0 && rh == 0 {
+        // 两个叶子：能放进一个就合并
+        if left.len() + right.len() <= M {
+            let (Node::Leaf(a), Node::Leaf(b)) = (&left, &right) else {
+                unreachable!()
+            };
+            let mut items = a.as_ref().clone();
+            items.extend(b.iter().cloned());
+            return vec![Node::Leaf(Arc::new(items))];
+        }
+        return vec![left, right];
+    }
+
+    let mut children = Vec::new();
+    if lh > rh {
+        let lc = left.children();
+        let mid = concat_nodes(lc[lc.len() - 1].clone(), lh - 1, right, rh);
+        children.extend(lc[..lc.len() - 1].iter().cloned());
+        children.extend(mid);
+    } else if lh < rh {
+        let rc = right.children();
+        let mid = concat_nodes(left, lh, rc[0].clone(), rh - 1);
+        children.extend(mid);
+        children.extend(rc[1..].iter().cloned());
+    } else {
+        let (lc, rc) = (left.children(), right.children());
+        let mid = concat_nodes(lc[lc.len() - 1].clone(), lh - 1, rc[0].clone(), rh - 1);
+        children.extend(lc[..lc.len() - 1].iter().cloned());
+        children.extend(mid);
+        children.extend(rc[1..].iter().cloned());
+    }
+
+    let children = rebalance(children);
+    if children.len() <= M {
+        vec![Node::branch(children)]
+    } else {
+        let rest = children[M..].to_vec();
+        let mut first = children;
+        first.truncate(M);
+        vec![Node::branch(first), Node::branch(rest)]
+    }
+}
+
+/// 重新分配同一层相邻节点的内容，使节点个数不超过 `⌈总数 / M⌉ + EXTRA`；
+/// 已经足够紧凑时原样返回，最大限度保留共享
+fn rebalance<T: Clone>(nodes: Vec<Node<T>>) -> Vec<Node<T>> {
+    let mut plan: Vec<usize> = nodes.iter().map(Node::slots).collect();
+    let total: usize = plan.iter().sum();
+    let optimal = total.div_ceil(M);
+    if plan.len() <= optimal + EXTRA {
+        return nodes;
+    }
+
+    // 找到第一个不够满的节点，把它的内容依次挤进后面的节点
+    let mut first_changed = usize::MAX;
+    while plan.len() > optimal + EXTRA {
+        let mut i = 0;
+        while plan[i] >= M - EXTRA / 2 {
+            i += 1;
+        }
+        first_changed = first_changed.min(i);
+        let mut remaining = plan[i];
+        while remaining > 0 {
+            let merged = (remaining + plan[i + 1]).min(M);
+            plan[i] = merged;
+            remaining = remaining + plan[i + 1] - merged;
+            i += 1;
+        }
+        plan.remove(i);
+    }
+
+    // `first_changed` 之前的节点保持不变，之后的按计划重新切分
+    let mut out: Vec<Node<T>> = nodes[..first_changed].to_vec();
+    let is_leaf = matches!(nodes[0], Node::Leaf(_));
+    if is_leaf {
+        let mut items = nodes[first_changed..].iter().flat_map(|n| match n {
+            Node::Leaf(items) => items.iter().cloned(),
+            Node::Branch(_) => unreachable!("mixed node heights"),
+        });
+        for &size in &plan[first_changed..] {
+            out.push(Node::Leaf(Arc::new(items.by_ref().take(size).collect())));
+        }
+    } else {
+        let mut grandchildren = nodes[first_changed..]
+            .iter()
+            .flat_map(|n| n.children().iter().cloned());
+        for &size in &plan[first_changed..] {
+            out.push(Node::branch(grandchildren.by_ref().take(size).collect()));
+        }
+    }
+    out
+}
+
+/// [`PersistentVector::iter`] 返回的迭代器
+pub struct Iter<'a, T> {
+    stack: Vec<std::slice::Iter<'a, Node<T>>>,
+    leaf: std::slice::Iter<'a, T>,
+    remaining: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            if let Some(item) = self.leaf.next() {
+                self.remaining -= 1;
+                return Some(item);
+            }
+            match self.stack.last_mut()?.next() {
+                Some(Node::Leaf(items)) => self.leaf = items.iter(),
+                Some(Node::Branch(b)) => self.stack.push(b.children.iter()),
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<'a, T> IntoIterator for &'a PersistentVector<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> Default for PersistentVector<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<Vec<T>> for PersistentVector<T> {
+    /// 自底向上批量构建，所有节点都是满的，O(n)
+    fn from(items: Vec<T>) -> Self {
+        if items.len() <= M {
+            return Self {
+                root: Node::Leaf(Arc::new(items)),
+                height: 0,
+            };
+        }
+        let mut level: Vec<Node<T>> = Vec::with_capacity(items.len().div_ceil(M));
+        let mut items = items.into_iter().peekable();
+        while items.peek().is_some() {
+            level.push(Node::Leaf(Arc::new(items.by_ref().take(M).collect())));
+        }
+        let mut height = 0;
+        while level.len() > 1 {
+            let mut next = Vec::with_capacity(level.len().div_ceil(M));
+            let mut nodes = level.into_iter().peekable();
+            while nodes.peek().is_some() {
+                next.push(Node::branch(nodes.by_ref().take(M).collect()));
+            }
+            level = next;
+            height += 1;
+        }
+        Self {
+            root: level.pop().unwrap(),
+            height,
+        }
+    }
+}
+
+impl<T> FromIterator<T> for PersistentVector<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<T>>())
+    }
+}
+
+impl<T: Clone> Extend<T> for PersistentVector<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+impl<T> Index<usize> for PersistentVector<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        let len = self.len();
+        self.get(index)
+            .unwrap_or_else(|| panic!("index {index} out of range for length {len}"))
+    }
+}
+
+impl<T: PartialEq> PartialEq for PersistentVector<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && (self.ptr_eq(other) || self.iter().eq(other.iter()))
+    }
+}
+
+impl<T: Eq> Eq for PersistentVector<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for PersistentVector<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Serialize> Serialize for PersistentVector<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for PersistentVector<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::<T>::deserialize(deserializer).map(Self::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{RngExt, SeedableRng, rngs::StdRng};
+
+    /// 检查 size table 与实际长度一致、所有叶子在同一层
+    fn check<T>(node: &Node<T>, height: usize) {
+        match node {
+            Node::Leaf(items) => {
+                assert_eq!(height, 0);
+                assert!(items.len() <= M);
+            }
+            Node::Branch(b) => {
+                assert!(height > 0);
+                assert!(!b.children.is_empty() && b.children.len() <= M);
+                let mut total = 0;
+                for (child, &size) in b.children.iter().zip(&b.sizes) {
+                    check(child, height - 1);
+                    total += child.len();
+                    assert_eq!(total, size);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_push_pop_and_sharing() {
+        let mut v = PersistentVector::new();
+        for i in 0..5000 {
+            v.push_back(i);
+        }
+        check(&v.root, v.height());
+        let snapshot = v.clone();
+        for i in (0..5000).rev().take(100) {
+            assert_eq!(v.pop_back(), Some(i));
+        }
+        v.set(0, -1);
+        assert_eq!(snapshot.len(), 5000);
+        assert_eq!(snapshot[0], 0);
+        assert_eq!(snapshot.back(), Some(&4999));
+        assert_eq!(v.len(), 4900);
+        assert_eq!(v.front(), Some(&-1));
+        assert!(snapshot.iter().copied().eq(0..5000));
+    }
+
+    #[test]
+    fn test_serde_roundtrip() {
+        let v: PersistentVector<String> = (0..100).map(|i| i.to_string()).collect();
+        let json = serde_json::to_string(&v).unwrap();
+        assert!(json.starts_with("[\"0\",\"1\""));
+        let back: PersistentVector<String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, v);
+    }
+
+    /// 随机 push / pop / set / split / concat，与 `Vec` 对比，并检查树高保持对数级
+    #[test]
+    fn property_matches_vec() {
+        let mut rng = StdRng::seed_from_u64(0x5252);
+        let mut pieces: Vec<(PersistentVector<u32>, Vec<u32>)> =
+            vec![(PersistentVector::new(), Vec::new())];
+        let mut next = 0u32;
+        for _ in 0..3000 {
+            let i = rng.random_range(0..pieces.len());
+            match rng.random_range(0..6) {
+                0 | 1 => {
+                    let n = rng.random_range(1..80);
+                    let (v, r) = &mut pieces[i];
+                    for _ in 0..n {
+                        v.push_back(next);
+                        r.push(next);
+                        next += 1;
+                    }
+                }
+                2 => {
+                    let (v, r) = &mut pieces[i];
+                    assert_eq!(v.pop_back(), r.pop());
+                }
+                3 if !pieces[i].1.is_empty() => {
+                    let (v, r) = &mut pieces[i];
+                    let at = rng.random_range(0..r.len());
+                    assert_eq!(v.set(at, next), std::mem::replace(&mut r[at], next));
+                    next += 1;
+                }
+                4 => {
+                    let (v, r) = &mut pieces[i];
+                    let at = rng.random_range(0..=r.len());
+                    let rv = v.split_off(at);
+                    let rr = r.split_off(at);
+                    pieces.push((rv, rr));
+                }
+                _ if pieces.len() > 1 => {
+                    let j = rng.random_range(0..pieces.len());
+                    if i != j {
+                        let (v2, r2) = pieces.swap_remove(j.max(i));
+                        let (v, r) = &mut pieces[j.min(i)];
+                        v.append(v2);
+                        r.extend(r2);
+                    }
+                }
+                _ => {}
+            }
+        }
+        // 最后把所有片段拼起来，检查总体结构
+        let (mut all_v, mut all_r) = (PersistentVector::new(), Vec::new());
+        for (v, r) in pieces {
+            assert_eq!(v.len(), r.len());
+            assert!(v.iter().eq(r.iter()));
+            check(&v.root, v.height());
+            all_v.append(v);
+            all_r.extend(r);
+        }
+        check(&all_v.root, all_v.height());
+        assert!(all_v.iter().eq(all_r.iter()));
+        for (i, x) in all_r.iter().enumerate() {
+            assert_eq!(all_v.get(i), Some(x));
+        }
+        // 32 叉树：除去边界上的部分节点，高度应接近 log32(n)
+        let bound = (all_r.len().max(2) as f64).log(16.0).ceil() as usize + 2;
+        assert!(
+            all_v.height() <= bound,
+            "height {} > {bound}",
+            all_v.height()
+        );
+    }
+}
@@ -1,15 +1,18 @@
-use std::collections::BTreeSet;
 use std::ops::Bound::Included;
+use std::ops::RangeBounds;
+
+use super::order_stat_tree::{Keys, OrderStatTree};
+use super::ordered_query::OrderedQuery;
 
 /// Ordered set-like tree with a red-black-tree style API.
 ///
-/// Rust standard library uses a B-Tree internally for ordered sets.
+/// Backed by a size-augmented balanced tree, so `select` / `rank` are O(log n).
 /// This wrapper provides an API close to the original Go red-black tree usage.
 pub struct RbTree<T>
 where
     T: Ord,
 {
-    data: BTreeSet<T>,
+    data: OrderStatTree<T, ()>,
 }
 
 impl<T> RbTree<T>
@@ -18,24 +21,28 @@ where
 {
     pub fn new() -> Self {
         Self {
-            data: BTreeSet::new(),
+            data: OrderStatTree::default(),
         }
     }
 
     pub fn contains(&self, val: &T) -> bool {
-        self.data.contains(val)
+        self.data.contains_key(val)
     }
 
     pub fn search(&self, val: &T) -> Option<&T> {
-        self.data.get(val)
+        self.data.get_key_value(val).map(|(k, _)| k)
     }
 
     pub fn insert(&mut self, val: T) -> bool {
-        self.data.insert(val)
+        if self.data.contains_key(&val) {
+            return false;
+        }
+        self.data.insert(val, ());
+        true
     }
 
     pub fn delete(&mut self, val: &T) -> bool {
-        self.data.remove(val)
+        self.data.remove(val).is_some()
     }
 
     pub fn search_range<'a>(&'a self, lower: &'a T, upper: &'a T) -> Vec<&'a T> {
@@ -44,6 +51,7 @@ where
         }
         self.data
             .range((Included(lower), Included(upper)))
+            .map(|(k, _)| k)
             .collect()
     }
 
@@ -52,7 +60,7 @@ where
     }
 
     pub fn iter(&self) -> impl Iterator<Item = &T> {
-        self.data.iter()
+        self.data.iter().map(|(k, _)| k)
     }
 
     pub fn len(&self) -> usize {
@@ -64,11 +72,11 @@ where
     }
 
     pub fn min(&self) -> Option<&T> {
-        self.data.first()
+        self.data.first().map(|(k, _)| k)
     }
 
     pub fn max(&self) -> Option<&T> {
-        self.data.last()
+        self.data.last().map(|(k, _)| k)
     }
 }
 
@@ -87,14 +95,40 @@ where
 {
     /// Returns the stored value and whether insertion happened.
     pub fn search_or_insert(&mut self, val: T) -> (T, bool) {
-        if let Some(existing) = self.data.get(&val) {
+        if let Some(existing) = self.search(&val) {
             return (existing.clone(), false);
         }
-        self.data.insert(val.clone());
+        self.data.insert(val.clone(), ());
         (val, true)
     }
 }
 
+impl<T> OrderedQuery<T> for RbTree<T>
+where
+    T: Ord,
+{
+    type Item<'a>
+        = &'a T
+    where
+        Self: 'a;
+    type Iter<'a>
+        = Keys<'a, T>
+    where
+        Self: 'a;
+
+    fn range<R: RangeBounds<T>>(&self, range: R) -> Self::Iter<'_> {
+        self.data.range(range).into()
+    }
+
+    fn select(&self, k: usize) -> Option<Self::Item<'_>> {
+        self.data.select(k).map(|(k, _)| k)
+    }
+
+    fn rank(&self, key: &T) -> usize {
+        self.data.rank(key)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::RbTree;
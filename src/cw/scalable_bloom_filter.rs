@@ -0,0 +1,373 @@
+//! 可扩展布隆过滤器（Scalable Bloom Filter）
+//!
+//! 事先不知道元素数量时（例如 `ff -md5` 记录 "见过的文件哈希"、知识库去重），
+//! 固定大小的 [`BloomFilter`](super::BloomFilter) 一旦装满，假阳性率会迅速上升。
+//! 可扩展布隆过滤器由一串逐级变大的布隆过滤器组成：当前一级装满后新建一级，
+//! 容量按 `growth` 倍增长，假阳性率按 `tightening` 倍收紧，总体假阳性率始终不超过目标值。
+//!
+//! 参考：Almeida et al., "Scalable Bloom Filters", 2007。
+
+use std::hash::Hash;
+use std::io;
+use std::path::Path;
+
+use super::BloomFilter;
+use super::bloom_filter::{
+    ByteReader, FilterKind, HEADER_LEN, invalid_data, read_header, write_header,
+};
+
+/// 一级过滤器及其容量、已插入数量
+#[derive(Debug, Clone, PartialEq)]
+struct Stage {
+    filter: BloomFilter,
+    capacity: usize,
+    len: usize,
+}
+
+/// 可扩展布隆过滤器
+///
+/// # 示例
+///
+/// ```rust
+/// use rust_tools::cw::ScalableBloomFilter;
+///
+/// let mut sbf = ScalableBloomFilter::new(100, 0.01);
+/// for i in 0..10_000 {
+///     sbf.insert(&i);
+/// }
+/// assert!(sbf.contains(&42));
+/// assert!(sbf.stage_count() > 1);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScalableBloomFilter {
+    stages: Vec<Stage>,
+    initial_capacity: usize,
+    false_positive_rate: f64,
+    growth: u32,
+    tightening: f64,
+}
+
+impl ScalableBloomFilter {
+    /// 默认容量增长倍数
+    pub const DEFAULT_GROWTH: u32 = 2;
+    /// 默认假阳性率收紧比例
+    pub const DEFAULT_TIGHTENING: f64 = 0.5;
+
+    /// 创建可扩展布隆过滤器
+    ///
+    /// # 参数
+    ///
+    /// - `initial_capacity`: 第一级的容量
+    /// - `false_positive_rate`: 整体的目标假阳性率
+    pub fn new(initial_capacity: usize, false_positive_rate: f64) -> Self {
+        Self::with_params(
+            initial_capacity,
+            false_positive_rate,
+            Self::DEFAULT_GROWTH,
+            Self::DEFAULT_TIGHTENING,
+        )
+    }
+
+    /// 指定容量增长倍数（≥ 1）和假阳性率收紧比例（0 到 1 之间）创建
+    pub fn with_params(
+        initial_capacity: usize,
+        false_positive_rate: f64,
+        growth: u32,
+        tightening: f64,
+    ) -> Self {
+        let mut sbf = Self {
+            stages: Vec::new(),
+            initial_capacity: initial_capacity.max(1),
+            false_positive_rate: false_positive_rate.clamp(1e-12, 0.5),
+            growth: growth.max(1),
+            tightening: tightening.clamp(0.01, 0.99),
+        };
+        sbf.add_stage();
+        sbf
+    }
+
+    /// 插入一个元素，返回该元素之前是否 "不存在"
+    ///
+    /// 已经存在（或假阳性命中）的元素不会重复占用容量。
+    pub fn insert<T: Hash>(&mut self, item: &T) -> bool {
+        if self.contains(item) {
+            return false;
+        }
+        let last = self.stages.last().expect("at least one stage");
+        if last.len >= last.capacity {
+            self.add_stage();
+        }
+        let stage = self.stages.last_mut().expect("at least one stage");
+        stage.filter.insert(item);
+        stage.len += 1;
+        true
+    }
+
+    /// 检查元素是否可能存在
+    pub fn contains<T: Hash>(&self, item: &T) -> bool {
+        // 新插入的元素在后面的级里，倒序检查更快命中
+        self.stages.iter().rev().any(|s| s.filter.contains(item))
+    }
+
+    /// 已插入的（去重后）元素个数
+    pub fn len(&self) -> usize {
+        self.stages.iter().map(|s| s.len).sum()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 当前级数
+    pub fn stage_count(&self) -> usize {
+        self.stages.len()
+    }
+
+    /// 当前所有级的总容量
+    pub fn capacity(&self) -> usize {
+        self.stages.iter().map(|s| s.capacity).sum()
+    }
+
+    /// 目标假阳性率
+    pub fn false_positive_rate(&self) -> f64 {
+        self.false_positive_rate
+    }
+
+    /// 按当前各级的装载情况估算的假阳性率上界
+    pub fn estimated_false_positive_rate(&self) -> f64 {
+        let miss: f64 = self
+            .stages
+            .iter()
+            .map(|s| {
+                let m = s.filter.bit_count() as f64;
+                let k = s.filter.hash_count() as f64;
+                let fill = 1.0 - (-(k * s.len as f64) / m).exp();
+                1.0 - fill.powf(k)
+            })
+            .product();
+        1.0 - miss
+    }
+
+    /// 清空并回到只有一级的初始状态
+    pub fn clear(&mut self) {
+        self.stages.clear();
+        self.add_stage();
+    }
+
+    /// 两个过滤器的构建参数相同时才能做并集
+    pub fn is_compatible(&self, other: &ScalableBloomFilter) -> bool {
+        self.initial_capacity == other.initial_capacity
+            && self.false_positive_rate == other.false_positive_rate
+            && self.growth == other.growth
+            && self.tightening == other.tightening
+    }
+
+    /// 并集：逐级合并，`other` 多出的级直接追加
+    ///
+    /// 合并后每级的计数取两者之和（可能因重叠而偏大），偏大只会让之后更早扩容，不影响正确性。
+    /// 可扩展布隆过滤器不提供交集：两边各级的装载不同，按级求交会产生假阴性。
+    pub fn union(&mut self, other: &ScalableBloomFilter) -> Result<(), &'static str> {
+        if !self.is_compatible(other) {
+            return Err("scalable bloom filters have different parameters");
+        }
+        for (i, theirs) in other.stages.iter().enumerate() {
+            match self.stages.get_mut(i) {
+                Some(ours) => {
+                    ours.filter.union(&theirs.filter)?;
+                    ours.len = (ours.len + theirs.len).min(ours.capacity);
+                }
+                None => self.stages.push(theirs.clone()),
+            }
+        }
+        Ok(())
+    }
+
+    /// 序列化为稳定的二进制格式
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rust_tools::cw::ScalableBloomFilter;
+    ///
+    /// let mut seen = ScalableBloomFilter::new(1000, 0.001);
+    /// seen.insert(&"9e107d9d372bb6826bd81d3542a419d6");
+    /// let restored = ScalableBloomFilter::from_bytes(&seen.to_bytes()).unwrap();
+    /// assert!(restored.contains(&"9e107d9d372bb6826bd81d3542a419d6"));
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let bits: usize = self
+            .stages
+            .iter()
+            .map(|s| s.filter.bit_count().div_ceil(8))
+            .sum();
+        let mut buf = Vec::with_capacity(HEADER_LEN + 40 + self.stages.len() * 28 + bits);
+        write_header(&mut buf, FilterKind::Scalable);
+        buf.extend_from_slice(&(self.initial_capacity as u64).to_le_bytes());
+        buf.extend_from_slice(&self.false_positive_rate.to_bits().to_le_bytes());
+        buf.extend_from_slice(&self.growth.to_le_bytes());
+        buf.extend_from_slice(&self.tightening.to_bits().to_le_bytes());
+        buf.extend_from_slice(&(self.stages.len() as u64).to_le_bytes());
+        for s in &self.stages {
+            buf.extend_from_slice(&(s.capacity as u64).to_le_bytes());
+            buf.extend_from_slice(&(s.len as u64).to_le_bytes());
+            s.filter.write_body(&mut buf);
+        }
+        buf
+    }
+
+    /// 从 [`to_bytes`](Self::to_bytes) 的输出恢复
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let mut r = ByteReader::new(read_header(bytes, FilterKind::Scalable)?);
+        let initial_capacity = r.read_len()?;
+        let false_positive_rate = r.read_f64()?;
+        let growth = r.read_u32()?;
+        let tightening = r.read_f64()?;
+        let stage_count = r.read_len()?;
+        if initial_capacity == 0
+            || growth == 0
+            || stage_count == 0
+            || !(false_positive_rate > 0.0 && false_positive_rate < 1.0)
+            || !(tightening > 0.0 && tightening < 1.0)
+        {
+            return Err(invalid_data("invalid scalable bloom filter parameters"));
+        }
+        let mut stages = Vec::new();
+        for _ in 0..stage_count {
+            let capacity = r.read_len()?;
+            let len = r.read_len()?;
+            let filter = BloomFilter::read_body(&mut r)?;
+            stages.push(Stage {
+                filter,
+                capacity,
+                len,
+            });
+        }
+        r.finish()?;
+        Ok(Self {
+            stages,
+            initial_capacity,
+            false_positive_rate,
+            growth,
+            tightening,
+        })
+    }
+
+    /// 保存到文件
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_bytes())
+    }
+
+    /// 从文件加载
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// 追加一级：第 i 级容量为 `initial * growth^i`，假阳性率为 `p * (1 - r) * r^i`，
+    /// 各级之和收敛于 `p`
+    fn add_stage(&mut self) {
+        let i = self.stages.len() as i32;
+        let capacity = self
+            .initial_capacity
+            .saturating_mul((self.growth as usize).saturating_pow(i as u32));
+        let rate = self.false_positive_rate * (1.0 - self.tightening) * self.tightening.powi(i);
+        self.stages.push(Stage {
+            filter: BloomFilter::with_rate(capacity, rate),
+            capacity,
+            len: 0,
+        });
+    }
+}
+
+impl Default for ScalableBloomFilter {
+    fn default() -> Self {
+        Self::new(1024, 0.01)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScalableBloomFilter;
+
+    #[test]
+    fn test_grows_without_false_negatives() {
+        let mut sbf = ScalableBloomFilter::new(64, 0.01);
+        for i in 0..20_000u32 {
+            sbf.insert(&i);
+        }
+        assert!(sbf.stage_count() > 5);
+        assert!(sbf.capacity() >= 20_000);
+        for i in 0..20_000u32 {
+            assert!(sbf.contains(&i));
+        }
+    }
+
+    #[test]
+    fn test_false_positive_rate_stays_bounded() {
+        let target = 0.01;
+        let mut sbf = ScalableBloomFilter::new(100, target);
+        for i in 0..50_000u64 {
+            sbf.insert(&i);
+        }
+        let trials = 100_000u64;
+        let fp = (1_000_000..1_000_000 + trials)
+            .filter(|i| sbf.contains(i))
+            .count();
+        let rate = fp as f64 / trials as f64;
+        assert!(rate < target * 1.5, "observed fp rate {rate}");
+        assert!(sbf.estimated_false_positive_rate() < target * 1.5);
+    }
+
+    #[test]
+    fn test_insert_reports_novelty() {
+        let mut sbf = ScalableBloomFilter::default();
+        assert!(sbf.is_empty());
+        assert!(sbf.insert(&"a"));
+        assert!(!sbf.insert(&"a"));
+        assert_eq!(sbf.len(), 1);
+        sbf.clear();
+        assert!(!sbf.contains(&"a"));
+        assert_eq!(sbf.stage_count(), 1);
+    }
+
+    #[test]
+    fn test_union() {
+        let mut a = ScalableBloomFilter::new(16, 0.01);
+        let mut b = ScalableBloomFilter::new(16, 0.01);
+        for i in 0..100 {
+            a.insert(&i);
+        }
+        for i in 100..1000 {
+            b.insert(&i);
+        }
+        a.union(&b).unwrap();
+        for i in 0..1000 {
+            assert!(a.contains(&i));
+        }
+        assert!(a.union(&ScalableBloomFilter::new(32, 0.01)).is_err());
+    }
+
+    #[test]
+    fn test_bytes_round_trip_and_file() {
+        let mut sbf = ScalableBloomFilter::new(32, 0.001);
+        for i in 0..500 {
+            sbf.insert(&format!("hash-{i}"));
+        }
+        let bytes = sbf.to_bytes();
+        let restored = ScalableBloomFilter::from_bytes(&bytes).unwrap();
+        assert_eq!(restored, sbf);
+        assert!(ScalableBloomFilter::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+        let path = std::env::temp_dir().join(format!(
+            "rust_tools_sbf_{}_{}.bin",
+            std::process::id(),
+            line!()
+        ));
+        sbf.save(&path).unwrap();
+        let loaded = ScalableBloomFilter::load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        for i in 0..500 {
+            assert!(loaded.contains(&format!("hash-{i}")));
+        }
+    }
+}
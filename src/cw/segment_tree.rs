@@ -0,0 +1,452 @@
+//! 懒标记线段树（Lazy Segment Tree）
+//!
+//! 区间查询 + 区间修改，均为 O(log n)。查询的合并方式与修改的作用方式由
+//! [`SegmentMonoid`] 描述，常用的几种组合见 [`RangeAddSum`]、[`RangeAddMin`]、
+//! [`RangeAddMax`]、[`RangeAssignSum`]。
+
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+
+/// 线段树上的幺半群及其修改操作
+///
+/// 需要满足：
+/// - `combine` 满足结合律，`identity` 是它的单位元
+/// - `apply` 对 `combine` 满足分配律：
+///   `apply(f, combine(a, b), la + lb) == combine(apply(f, a, la), apply(f, b, lb))`
+/// - `compose(new, old)` 等价于先执行 `old` 再执行 `new`
+pub trait SegmentMonoid {
+    /// 区间聚合值
+    type Value: Clone;
+    /// 区间修改
+    type Update: Clone;
+
+    /// 单位元（空区间的聚合值）
+    fn identity() -> Self::Value;
+    /// 合并左右两段的聚合值
+    fn combine(left: &Self::Value, right: &Self::Value) -> Self::Value;
+    /// 把修改作用到长度为 `len` 的区间的聚合值上
+    fn apply(update: &Self::Update, value: &Self::Value, len: usize) -> Self::Value;
+    /// 合并两次修改：先 `old` 后 `new`
+    fn compose(new: &Self::Update, old: &Self::Update) -> Self::Update;
+}
+
+/// 区间加、区间求和
+pub struct RangeAddSum;
+
+impl SegmentMonoid for RangeAddSum {
+    type Value = i64;
+    type Update = i64;
+
+    fn identity() -> i64 {
+        0
+    }
+    fn combine(left: &i64, right: &i64) -> i64 {
+        left + right
+    }
+    fn apply(update: &i64, value: &i64, len: usize) -> i64 {
+        value + update * len as i64
+    }
+    fn compose(new: &i64, old: &i64) -> i64 {
+        new + old
+    }
+}
+
+/// 区间加、区间最小值
+pub struct RangeAddMin;
+
+impl SegmentMonoid for RangeAddMin {
+    type Value = i64;
+    type Update = i64;
+
+    fn identity() -> i64 {
+        i64::MAX
+    }
+    fn combine(left: &i64, right: &i64) -> i64 {
+        *left.min(right)
+    }
+    fn apply(update: &i64, value: &i64, _len: usize) -> i64 {
+        if *value == i64::MAX {
+            *value
+        } else {
+            value + update
+        }
+    }
+    fn compose(new: &i64, old: &i64) -> i64 {
+        new + old
+    }
+}
+
+/// 区间加、区间最大值
+pub struct RangeAddMax;
+
+impl SegmentMonoid for RangeAddMax {
+    type Value = i64;
+    type Update = i64;
+
+    fn identity() -> i64 {
+        i64::MIN
+    }
+    fn combine(left: &i64, right: &i64) -> i64 {
+        *left.max(right)
+    }
+    fn apply(update: &i64, value: &i64, _len: usize) -> i64 {
+        if *value == i64::MIN {
+            *value
+        } else {
+            value + update
+        }
+    }
+    fn compose(new: &i64, old: &i64) -> i64 {
+        new + old
+    }
+}
+
+/// 区间赋值、区间求和
+pub struct RangeAssignSum;
+
+impl SegmentMonoid for RangeAssignSum {
+    type Value = i64;
+    type Update = i64;
+
+    fn identity() -> i64 {
+        0
+    }
+    fn combine(left: &i64, right: &i64) -> i64 {
+        left + right
+    }
+    fn apply(update: &i64, _value: &i64, len: usize) -> i64 {
+        update * len as i64
+    }
+    fn compose(new: &i64, _old: &i64) -> i64 {
+        *new
+    }
+}
+
+/// 懒标记线段树
+///
+/// # 示例
+///
+/// ```rust
+/// use rust_tools::cw::{RangeAddSum, SegmentTree};
+///
+/// let mut tree = SegmentTree::<RangeAddSum>::from_vec(vec![1, 2, 3, 4, 5]);
+/// assert_eq!(tree.query(1..4), 9);
+///
+/// tree.update(0..3, 10); // [11, 12, 13, 4, 5]
+/// assert_eq!(tree.query(..), 45);
+/// assert_eq!(tree.get(2), 13);
+///
+/// tree.set(4, 0);
+/// assert_eq!(tree.query(3..), 4);
+/// ```
+pub struct SegmentTree<M: SegmentMonoid> {
+    len: usize,
+    values: Vec<M::Value>,
+    lazy: Vec<Option<M::Update>>,
+    _monoid: PhantomData<M>,
+}
+
+impl<M: SegmentMonoid> SegmentTree<M> {
+    /// 创建长度为 `len`、所有元素都是单位元的线段树
+    pub fn new(len: usize) -> Self {
+        Self::from_vec(vec![M::identity(); len])
+    }
+
+    /// 由初始元素构建，O(n)
+    pub fn from_vec(items: Vec<M::Value>) -> Self {
+        let len = items.len();
+        let size = 4 * len.max(1);
+        let mut tree = Self {
+            len,
+            values: vec![M::identity(); size],
+            lazy: vec![None; size],
+            _monoid: PhantomData,
+        };
+        if len > 0 {
+            tree.build(1, 0, len, &items);
+        }
+        tree
+    }
+
+    /// 元素个数
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 区间聚合值；空区间返回单位元
+    ///
+    /// # Panics
+    ///
+    /// 区间越界时 panic
+    pub fn query(&mut self, range: impl RangeBounds<usize>) -> M::Value {
+        let (lo, hi) = self.bounds(range);
+        if lo >= hi {
+            return M::identity();
+        }
+        self.query_rec(1, 0, self.len, lo, hi)
+    }
+
+    /// 对区间内每个元素执行修改
+    ///
+    /// # Panics
+    ///
+    /// 区间越界时 panic
+    pub fn update(&mut self, range: impl RangeBounds<usize>, update: M::Update) {
+        let (lo, hi) = self.bounds(range);
+        if lo < hi {
+            self.update_rec(1, 0, self.len, lo, hi, &update);
+        }
+    }
+
+    /// 第 `index` 个元素的当前值
+    pub fn get(&mut self, index: usize) -> M::Value {
+        self.query(index..=index)
+    }
+
+    /// 把第 `index` 个元素替换为 `value`
+    pub fn set(&mut self, index: usize, value: M::Value) {
+        assert!(index < self.len, "index {index} out of range {}", self.len);
+        self.set_rec(1, 0, self.len, index, value);
+    }
+
+    /// 下推懒标记后的全部元素
+    pub fn to_vec(&mut self) -> Vec<M::Value> {
+        (0..self.len).map(|i| self.get(i)).collect()
+    }
+
+    fn bounds(&self, range: impl RangeBounds<usize>) -> (usize, usize) {
+        let lo = match range.start_bound() {
+            Bound::Included(&s) => s,
+            Bound::Excluded(&s) => s + 1,
+            Bound::Unbounded => 0,
+        };
+        let hi = match range.end_bound() {
+            Bound::Included(&e) => e + 1,
+            Bound::Excluded(&e) => e,
+            Bound::Unbounded => self.len,
+        };
+        assert!(
+            hi <= self.len && lo <= hi,
+            "range {lo}..{hi} out of range {}",
+            self.len
+        );
+        (lo, hi)
+    }
+
+    fn build(&mut self, node: usize, lo: usize, hi: usize, items: &[M::Value]) {
+        if hi - lo == 1 {
+            self.values[node] = items[lo].clone();
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        self.build(2 * node, lo, mid, items);
+        self.build(2 * node + 1, mid, hi, items);
+        self.pull(node);
+    }
+
+    fn pull(&mut self, node: usize) {
+        self.values[node] = M::combine(&self.values[2 * node], &self.values[2 * node + 1]);
+    }
+
+    /// 把修改作用到整个节点上，并记录到懒标记
+    fn apply_node(&mut self, node: usize, len: usize, update: &M::Update) {
+        self.values[node] = M::apply(update, &self.values[node], len);
+        self.lazy[node] = Some(match &self.lazy[node] {
+            Some(old) => M::compose(update, old),
+            None => update.clone(),
+        });
+    }
+
+    fn push(&mut self, node: usize, lo: usize, hi: usize) {
+        if let Some(update) = self.lazy[node].take() {
+            let mid = (lo + hi) / 2;
+            self.apply_node(2 * node, mid - lo, &update);
+            self.apply_node(2 * node + 1, hi - mid, &update);
+        }
+    }
+
+    fn query_rec(&mut self, node: usize, lo: usize, hi: usize, ql: usize, qr: usize) -> M::Value {
+        if ql <= lo && hi <= qr {
+            return self.values[node].clone();
+        }
+        self.push(node, lo, hi);
+        let mid = (lo + hi) / 2;
+        if qr <= mid {
+            self.query_rec(2 * node, lo, mid, ql, qr)
+        } else if ql >= mid {
+            self.query_rec(2 * node + 1, mid, hi, ql, qr)
+        } else {
+            let left = self.query_rec(2 * node, lo, mid, ql, qr);
+            let right = self.query_rec(2 * node + 1, mid, hi, ql, qr);
+            M::combine(&left, &right)
+        }
+    }
+
+    fn update_rec(
+        &mut self,
+        node: usize,
+        lo: usize,
+        hi: usize,
+        ql: usize,
+        qr: usize,
+        update: &M::Update,
+    ) {
+        if ql <= lo && hi <= qr {
+            self.apply_node(node, hi - lo, update);
+            return;
+        }
+        self.push(node, lo, hi);
+        let mid = (lo + hi) / 2;
+        if ql < mid {
+            self.update_rec(2 * node, lo, mid, ql, qr, update);
+        }
+        if qr > mid {
+            self.update_rec(2 * node + 1, mid, hi, ql, qr, update);
+        }
+        self.pull(node);
+    }
+
+    fn set_rec(&mut self, node: usize, lo: usize, hi: usize, index: usize, value: M::Value) {
+        if hi - lo == 1 {
+            self.values[node] = value;
+            self.lazy[node] = None;
+            return;
+        }
+        self.push(node, lo, hi);
+        let mid = (lo + hi) / 2;
+        if index < mid {
+            self.set_rec(2 * node, lo, mid, index, value);
+        } else {
+            self.set_rec(2 * node + 1, mid, hi, index, value);
+        }
+        self.pull(node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{RngExt, SeedableRng, rngs::StdRng};
+
+    #[test]
+    fn test_empty_and_single() {
+        let mut empty = SegmentTree::<RangeAddSum>::new(0);
+        assert!(empty.is_empty());
+        assert_eq!(empty.query(..), 0);
+
+        let mut one = SegmentTree::<RangeAddMin>::from_vec(vec![7]);
+        one.update(.., -3);
+        assert_eq!(one.query(0..1), 4);
+        assert_eq!(one.query(1..1), i64::MAX);
+    }
+
+    /// 仿射变换 x -> a*x + b 作用于区间和，用来验证非交换的 `compose`
+    struct AffineSum;
+
+    impl SegmentMonoid for AffineSum {
+        type Value = i64;
+        type Update = (i64, i64);
+
+        fn identity() -> i64 {
+            0
+        }
+        fn combine(left: &i64, right: &i64) -> i64 {
+            left + right
+        }
+        fn apply(&(a, b): &(i64, i64), value: &i64, len: usize) -> i64 {
+            a * value + b * len as i64
+        }
+        fn compose(&(a2, b2): &(i64, i64), &(a1, b1): &(i64, i64)) -> (i64, i64) {
+            (a2 * a1, a2 * b1 + b2)
+        }
+    }
+
+    /// 随机区间修改 / 查询，与逐元素模拟对比
+    fn check_against_brute<M: SegmentMonoid>(
+        seed: u64,
+        gen_update: impl Fn(&mut StdRng) -> M::Update,
+        apply_one: impl Fn(&M::Update, i64) -> i64,
+        fold: impl Fn(&[i64]) -> M::Value,
+        set_value: impl Fn(i64) -> M::Value,
+    ) where
+        M::Value: PartialEq + std::fmt::Debug,
+    {
+        let mut rng = StdRng::seed_from_u64(seed);
+        for _ in 0..20 {
+            let n = rng.random_range(1..60);
+            let mut brute: Vec<i64> = (0..n).map(|_| rng.random_range(-9..10)).collect();
+            let mut tree =
+                SegmentTree::<M>::from_vec(brute.iter().map(|&x| set_value(x)).collect());
+            for _ in 0..300 {
+                let a = rng.random_range(0..=n);
+                let b = rng.random_range(0..=n);
+                let (lo, hi) = (a.min(b), a.max(b));
+                match rng.random_range(0..3) {
+                    0 => {
+                        let update = gen_update(&mut rng);
+                        tree.update(lo..hi, update.clone());
+                        for x in &mut brute[lo..hi] {
+                            *x = apply_one(&update, *x);
+                        }
+                    }
+                    1 if lo < n => {
+                        let x = rng.random_range(-9..10);
+                        tree.set(lo, set_value(x));
+                        brute[lo] = x;
+                    }
+                    _ => assert_eq!(tree.query(lo..hi), fold(&brute[lo..hi]), "{lo}..{hi}"),
+                }
+            }
+            let all: Vec<M::Value> = brute.iter().map(|&x| set_value(x)).collect();
+            assert_eq!(tree.to_vec(), all);
+        }
+    }
+
+    #[test]
+    fn property_builtin_monoids_match_brute_force() {
+        check_against_brute::<RangeAddSum>(
+            1,
+            |rng| rng.random_range(-5..6),
+            |u, x| x + u,
+            |s| s.iter().sum(),
+            |x| x,
+        );
+        check_against_brute::<RangeAddMin>(
+            2,
+            |rng| rng.random_range(-5..6),
+            |u, x| x + u,
+            |s| s.iter().copied().min().unwrap_or(i64::MAX),
+            |x| x,
+        );
+        check_against_brute::<RangeAddMax>(
+            3,
+            |rng| rng.random_range(-5..6),
+            |u, x| x + u,
+            |s| s.iter().copied().max().unwrap_or(i64::MIN),
+            |x| x,
+        );
+        check_against_brute::<RangeAssignSum>(
+            4,
+            |rng| rng.random_range(-5..6),
+            |u, _| *u,
+            |s| s.iter().sum(),
+            |x| x,
+        );
+    }
+
+    #[test]
+    fn property_custom_monoid_matches_brute_force() {
+        check_against_brute::<AffineSum>(
+            5,
+            |rng| (rng.random_range(-1..2), rng.random_range(-3..4)),
+            |&(a, b), x| a * x + b,
+            |s| s.iter().sum(),
+            |x| x,
+        );
+    }
+}
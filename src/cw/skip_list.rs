@@ -1,6 +1,8 @@
+use super::ordered_query::OrderedQuery;
 use rand::{RngExt, SeedableRng, rngs::StdRng};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
 use std::ptr;
 use std::sync::Mutex;
 
@@ -8,6 +10,9 @@ struct SkipNode<K, V> {
     k: std::mem::MaybeUninit<K>,
     v: std::mem::MaybeUninit<V>,
     forward: Vec<*mut SkipNode<K, V>>,
+    /// span[i]：沿第 i 层从本节点走到 forward[i] 跨过的底层节点数，用于 O(log n) 的 rank / select。
+    /// 只有 forward[i] 非空时才有意义。
+    span: Vec<usize>,
 }
 
 impl<K, V> SkipNode<K, V> {
@@ -16,11 +21,18 @@ impl<K, V> SkipNode<K, V> {
             k: std::mem::MaybeUninit::new(k),
             v: std::mem::MaybeUninit::new(v),
             forward: vec![ptr::null_mut(); max_height],
+            span: vec![0; max_height],
         });
         Box::into_raw(ret) as *mut Self
     }
 }
 
+/// `find_ranked` 的结果：每层的前驱节点及其排名（head 为 0）
+struct RankedPath<K, V> {
+    updates: Vec<*const SkipNode<K, V>>,
+    ranks: Vec<usize>,
+}
+
 pub struct SkipMap<K, V> {
     head: SkipNode<K, V>,
     max_height: usize,
@@ -121,6 +133,7 @@ impl<K, V> SkipMap<K, V> {
                 k: std::mem::MaybeUninit::uninit(),
                 v: std::mem::MaybeUninit::uninit(),
                 forward: vec![ptr::null_mut(); max_height],
+                span: vec![0; max_height],
             },
             max_height,
             len: 0,
@@ -137,26 +150,14 @@ impl<K, V> SkipMap<K, V> {
             // 跳跃前进，得到 O(log n) 的查找。若仅从新节点的随机层 `level` 开始
             // （`level` 有约 50% 概率为 0），查找会退化成在底层链表上的线性扫描，
             // 使每次 insert 变成 O(n)、整体建表 O(n²)，表现为单线程长时间 100% CPU。
-            let (updates, found) = self.find(&k, self.max_height - 1);
+            let (path, found) = self.find_ranked(&k);
             let found = found as *mut SkipNode<K, V>;
             if !found.is_null() {
                 (&mut *found).v.write(v);
                 return;
             }
             let new_node = SkipNode::new(k, v, self.max_height);
-
-            for i in (0..=level).rev() {
-                let prev = *updates.get_unchecked(i) as *mut SkipNode<K, V>;
-                if prev == &mut self.head as *mut SkipNode<K, V> {
-                    let tmp = self.head.forward[i];
-                    self.head.forward[i] = new_node;
-                    (&mut *new_node).forward[i] = tmp;
-                } else {
-                    let next = (&*prev).forward[i];
-                    (&mut *prev).forward[i] = new_node;
-                    (&mut *new_node).forward[i] = next;
-                }
-            }
+            self.link(&path, level, new_node);
         }
         self.len += 1;
     }
@@ -193,32 +194,104 @@ impl<K, V> SkipMap<K, V> {
         self.contains_key(k)
     }
 
-    pub fn range(&self, r: std::ops::Range<K>) -> Vec<(&K, &V)> {
-        let start = r.start;
-        let end = r.end;
-        let mut prev = &self.head as *const SkipNode<K, V>;
-        let mut ret = vec![];
+    /// 返回 key 落在 `r` 内的双端迭代器；起点大于终点时为空
+    pub fn range<R: RangeBounds<K>>(&self, r: R) -> SkipRange<'_, K, V> {
         let f = self.cmp;
+        self.range_by(
+            |k| match r.start_bound() {
+                Bound::Included(s) => f(k, s) < 0,
+                Bound::Excluded(s) => f(k, s) <= 0,
+                Bound::Unbounded => false,
+            },
+            |k| match r.end_bound() {
+                Bound::Included(e) => f(k, e) <= 0,
+                Bound::Excluded(e) => f(k, e) < 0,
+                Bound::Unbounded => true,
+            },
+        )
+    }
+
+    /// 用谓词描述的范围：`before_start(k)` 对起点之前的 key 为真，
+    /// `within_end(k)` 对终点及之前的 key 为真。两个谓词都必须随 key 单调（先真后假）。
+    ///
+    /// 供按 key 的某个字段（如 ZSet 的分数）查询，而不必构造完整的 key。
+    pub(crate) fn range_by(
+        &self,
+        before_start: impl Fn(&K) -> bool,
+        within_end: impl Fn(&K) -> bool,
+    ) -> SkipRange<'_, K, V> {
+        let head = &self.head as *const SkipNode<K, V>;
+        // 起点之前的最后一个节点
+        let before = self.descend(before_start);
+        // 终点范围内的最后一个节点
+        let last = self.descend(&within_end);
+        unsafe {
+            let first = *(&*before).forward.get_unchecked(0);
+            if first.is_null() || last == head || !within_end((*first).k.assume_init_ref()) {
+                return SkipRange {
+                    front: ptr::null(),
+                    back: ptr::null(),
+                    map: self,
+                };
+            }
+            SkipRange {
+                front: first,
+                back: last,
+                map: self,
+            }
+        }
+    }
+
+    /// 第 `k` 小（从 0 开始）的键值对，O(log n)
+    pub fn select(&self, k: usize) -> Option<(&K, &V)> {
+        if k >= self.len {
+            return None;
+        }
+        let target = k + 1;
+        let mut traversed = 0;
+        let mut x = &self.head as *const SkipNode<K, V>;
         unsafe {
             for i in (0..self.max_height).rev() {
-                let mut curr = *(&*prev).forward.get_unchecked(i);
-                while !curr.is_null() && f((*curr).k.assume_init_ref(), &start) < 0 {
-                    prev = curr;
-                    curr = (&*curr).forward[i];
+                loop {
+                    let next = *(&*x).forward.get_unchecked(i);
+                    let span = *(&*x).span.get_unchecked(i);
+                    if next.is_null() || traversed + span > target {
+                        break;
+                    }
+                    traversed += span;
+                    x = next;
+                }
+                if traversed == target {
+                    return Some(((*x).k.assume_init_ref(), (*x).v.assume_init_ref()));
                 }
             }
-            let mut curr = *(&*prev).forward.get_unchecked(0);
-            while !curr.is_null() {
-                if f((*curr).k.assume_init_ref(), &end) >= 0 {
-                    break;
+        }
+        None
+    }
+
+    /// 严格小于 `k` 的 key 个数，O(log n)
+    pub fn rank(&self, k: &K) -> usize {
+        let f = self.cmp;
+        self.count_while(|x| f(x, k) < 0)
+    }
+
+    /// 从头开始连续满足 `pred` 的 key 个数，O(log n)；`pred` 必须随 key 单调（先真后假）
+    pub(crate) fn count_while(&self, pred: impl Fn(&K) -> bool) -> usize {
+        let mut rank = 0;
+        let mut x = &self.head as *const SkipNode<K, V>;
+        unsafe {
+            for i in (0..self.max_height).rev() {
+                loop {
+                    let next = *(&*x).forward.get_unchecked(i);
+                    if next.is_null() || !pred((*next).k.assume_init_ref()) {
+                        break;
+                    }
+                    rank += *(&*x).span.get_unchecked(i);
+                    x = next;
                 }
-                let k = (*curr).k.assume_init_ref();
-                let v = (*curr).v.assume_init_ref();
-                ret.push((k, v));
-                curr = (&*curr).forward[0];
             }
         }
-        ret
+        rank
     }
 
     pub fn len(&self) -> usize {
@@ -258,10 +331,20 @@ impl<K, V> SkipMap<K, V> {
             for i in 0..self.max_height {
                 let prev = *updates.get_unchecked(i) as *mut SkipNode<K, V>;
                 let next = *(&*prev).forward.get_unchecked(i);
-                if next.is_null() || found != next {
+                if next.is_null() {
                     continue;
                 }
-                (&mut *prev).forward[i] = *(&*next).forward.get_unchecked(i);
+                if found != next {
+                    (&mut *prev).span[i] -= 1;
+                    continue;
+                }
+                let after = *(&*next).forward.get_unchecked(i);
+                (&mut *prev).span[i] = if after.is_null() {
+                    0
+                } else {
+                    (&*prev).span[i] + (&*next).span[i] - 1
+                };
+                (&mut *prev).forward[i] = after;
             }
             let boxed = Box::from_raw(found as *mut SkipNode<K, V>);
             // MaybeUninit 不会自动 drop 内容，需要手动读出以触发析构
@@ -429,6 +512,61 @@ impl<'a, K, V> Iterator for SkipListIterMut<'a, K, V> {
     }
 }
 
+/// [`SkipMap::range`] 返回的双端迭代器
+pub struct SkipRange<'a, K, V> {
+    front: *const SkipNode<K, V>,
+    back: *const SkipNode<K, V>,
+    map: &'a SkipMap<K, V>,
+}
+
+pub type SkipSetRange<'a, T> = std::iter::Map<SkipRange<'a, T, ()>, fn((&'a T, &'a ())) -> &'a T>;
+
+impl<'a, K, V> SkipRange<'a, K, V> {
+    fn take_front_back(&mut self) {
+        self.front = ptr::null();
+        self.back = ptr::null();
+    }
+}
+
+impl<'a, K, V> Iterator for SkipRange<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front.is_null() {
+            return None;
+        }
+        unsafe {
+            let node = &*self.front;
+            if self.front == self.back {
+                self.take_front_back();
+            } else {
+                self.front = *node.forward.get_unchecked(0);
+            }
+            Some((node.k.assume_init_ref(), node.v.assume_init_ref()))
+        }
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for SkipRange<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.back.is_null() {
+            return None;
+        }
+        unsafe {
+            let node = &*self.back;
+            if self.front == self.back {
+                self.take_front_back();
+            } else {
+                // 单向链表没有 prev 指针，重新下降一次找严格前驱，O(log n)
+                let f = self.map.cmp;
+                let key = node.k.assume_init_ref();
+                self.back = self.map.descend(|k| f(k, key) < 0);
+            }
+            Some((node.k.assume_init_ref(), node.v.assume_init_ref()))
+        }
+    }
+}
+
 // FromIterator for Box<SkipMap<K, V>>
 impl<K, V> FromIterator<(K, V)> for Box<SkipMap<K, V>>
 where
@@ -480,6 +618,81 @@ impl<K, V> SkipMap<K, V> {
         }
     }
 
+    /// 与 `find` 相同，额外返回每层前驱节点的排名（head 为 0）
+    fn find_ranked(&self, k: &K) -> (RankedPath<K, V>, *const SkipNode<K, V>) {
+        let f = self.cmp;
+        let level = self.max_height - 1;
+        unsafe {
+            let mut updates = vec![ptr::null(); level + 1];
+            let mut ranks = vec![0; level + 1];
+            let mut prev = &self.head as *const SkipNode<K, V>;
+            let mut rank = 0;
+            let mut found = ptr::null_mut();
+            for i in (0..=level).rev() {
+                let mut curr = *(&*prev).forward.get_unchecked(i);
+                while !curr.is_null() && f((&*curr).k.assume_init_ref(), k) < 0 {
+                    rank += *(&*prev).span.get_unchecked(i);
+                    prev = curr;
+                    curr = *((&*curr).forward).get_unchecked(i);
+                }
+                if !curr.is_null() && f((*curr).k.assume_init_ref(), k) == 0 {
+                    found = curr;
+                }
+                updates[i] = prev;
+                ranks[i] = rank;
+            }
+            (RankedPath { updates, ranks }, found)
+        }
+    }
+
+    /// 把新节点挂到 `updates` 之后：0..=level 层接入链表，更高层只需把跨度加一
+    unsafe fn link(
+        &mut self,
+        path: &RankedPath<K, V>,
+        level: usize,
+        new_node: *mut SkipNode<K, V>,
+    ) {
+        let RankedPath { updates, ranks } = path;
+        let head = &mut self.head as *mut SkipNode<K, V>;
+        unsafe {
+            for i in 0..self.max_height {
+                let prev = *updates.get_unchecked(i) as *mut SkipNode<K, V>;
+                let prev = if ptr::eq(prev, head) { head } else { prev };
+                let next = (&*prev).forward[i];
+                if i <= level {
+                    let dist = ranks[0] - ranks[i];
+                    (&mut *new_node).forward[i] = next;
+                    (&mut *new_node).span[i] = if next.is_null() {
+                        0
+                    } else {
+                        (&*prev).span[i] - dist
+                    };
+                    (&mut *prev).forward[i] = new_node;
+                    (&mut *prev).span[i] = dist + 1;
+                } else if !next.is_null() {
+                    (&mut *prev).span[i] += 1;
+                }
+            }
+        }
+    }
+
+    /// 从最高层往下走，只要下一个节点的 key 满足 `go_on` 就前进，返回停下的节点（可能是 head）
+    fn descend(&self, go_on: impl Fn(&K) -> bool) -> *const SkipNode<K, V> {
+        let mut x = &self.head as *const SkipNode<K, V>;
+        unsafe {
+            for i in (0..self.max_height).rev() {
+                loop {
+                    let next = *(&*x).forward.get_unchecked(i);
+                    if next.is_null() || !go_on((*next).k.assume_init_ref()) {
+                        break;
+                    }
+                    x = next;
+                }
+            }
+        }
+        x
+    }
+
     // private functions
     fn level(&self) -> usize {
         const PROB: f32 = 0.5;
@@ -578,8 +791,30 @@ where
         self.inner.remove(value)
     }
 
-    pub fn range(&self, r: std::ops::Range<T>) -> Vec<&T> {
-        self.inner.range(r).into_iter().map(|(k, _)| k).collect()
+    /// 返回落在 `r` 内的双端迭代器
+    pub fn range<R: RangeBounds<T>>(&self, r: R) -> SkipSetRange<'_, T> {
+        self.inner.range(r).map(|(k, _)| k)
+    }
+
+    /// 见 [`SkipMap::range_by`]
+    pub(crate) fn range_by(
+        &self,
+        before_start: impl Fn(&T) -> bool,
+        within_end: impl Fn(&T) -> bool,
+    ) -> SkipSetRange<'_, T> {
+        self.inner
+            .range_by(before_start, within_end)
+            .map(|(k, _)| k)
+    }
+
+    /// 见 [`SkipMap::count_while`]
+    pub(crate) fn count_while(&self, pred: impl Fn(&T) -> bool) -> usize {
+        self.inner.count_while(pred)
+    }
+
+    /// 第 `k` 小（从 0 开始）的元素，O(log n)
+    pub fn select(&self, k: usize) -> Option<&T> {
+        self.inner.select(k).map(|(k, _)| k)
     }
 
     pub fn clear(&mut self) {
@@ -879,7 +1114,7 @@ impl<K, V> SkipMap<K, V> {
         let level = self.level().min(self.max_height - 1);
         unsafe {
             // 同 insert：必须从最高层开始查找，否则退化为 O(n) 线性扫描。
-            let (updates, found) = self.find(&k, self.max_height - 1);
+            let (path, found) = self.find_ranked(&k);
             let found = found as *mut SkipNode<K, V>;
             if !found.is_null() {
                 let val_ptr = (&mut *found).v.as_mut_ptr();
@@ -887,18 +1122,7 @@ impl<K, V> SkipMap<K, V> {
                 return &mut *val_ptr;
             }
             let new_node = SkipNode::new(k, v, self.max_height);
-            for i in (0..=level).rev() {
-                let prev = *updates.get_unchecked(i) as *mut SkipNode<K, V>;
-                if prev == &mut self.head as *mut SkipNode<K, V> {
-                    let tmp = self.head.forward[i];
-                    self.head.forward[i] = new_node;
-                    (&mut *new_node).forward[i] = tmp;
-                } else {
-                    let next = (&*prev).forward[i];
-                    (&mut *prev).forward[i] = new_node;
-                    (&mut *new_node).forward[i] = next;
-                }
-            }
+            self.link(&path, level, new_node);
             self.len += 1;
             (&mut *new_node).v.assume_init_mut()
         }
@@ -1013,6 +1237,54 @@ impl<K: Ord, V> Extend<(K, V)> for Box<SkipMap<K, V>> {
     }
 }
 
+// ============== OrderedQuery ==============
+
+impl<K: Ord, V> OrderedQuery<K> for SkipMap<K, V> {
+    type Item<'a>
+        = (&'a K, &'a V)
+    where
+        Self: 'a;
+    type Iter<'a>
+        = SkipRange<'a, K, V>
+    where
+        Self: 'a;
+
+    fn range<R: RangeBounds<K>>(&self, range: R) -> Self::Iter<'_> {
+        SkipMap::range(self, range)
+    }
+
+    fn select(&self, k: usize) -> Option<Self::Item<'_>> {
+        SkipMap::select(self, k)
+    }
+
+    fn rank(&self, key: &K) -> usize {
+        SkipMap::rank(self, key)
+    }
+}
+
+impl<T: Ord> OrderedQuery<T> for SkipSet<T> {
+    type Item<'a>
+        = &'a T
+    where
+        Self: 'a;
+    type Iter<'a>
+        = SkipSetRange<'a, T>
+    where
+        Self: 'a;
+
+    fn range<R: RangeBounds<T>>(&self, range: R) -> Self::Iter<'_> {
+        SkipSet::range(self, range)
+    }
+
+    fn select(&self, k: usize) -> Option<Self::Item<'_>> {
+        self.inner.select(k).map(|(k, _)| k)
+    }
+
+    fn rank(&self, key: &T) -> usize {
+        self.inner.rank(key)
+    }
+}
+
 // ============== SkipSet: 补充方法 ==============
 
 impl<T: Ord> SkipSet<T> {
@@ -1119,7 +1391,7 @@ mod tests {
         for i in 1..=10 {
             list.insert(i, i * 100);
         }
-        let range = list.range(3..7);
+        let range: Vec<_> = list.range(3..7).collect();
         assert_eq!(range.len(), 4);
         assert_eq!(range[0], (&3, &300));
         assert_eq!(range[3], (&6, &600));
@@ -1188,8 +1460,10 @@ mod tests {
         for i in 1..=10 {
             set.insert(i);
         }
-        let range: Vec<_> = set.range(3..7);
+        let range: Vec<_> = set.range(3..7).collect();
         assert_eq!(range, vec![&3, &4, &5, &6]);
+        let rev: Vec<_> = set.range(3..7).rev().collect();
+        assert_eq!(rev, vec![&6, &5, &4, &3]);
     }
 
     #[test]
@@ -1,12 +1,17 @@
-use std::collections::BTreeMap;
 use std::ops::Bound::Included;
+use std::ops::RangeBounds;
+
+use super::order_stat_tree::{OrderStatTree, Range};
+use super::ordered_query::OrderedQuery;
 
 /// Ordered map backed by a tree structure.
+///
+/// The tree keeps subtree sizes, so `select` / `rank` are O(log n) like lookups.
 pub struct TreeMap<K, V>
 where
     K: Ord,
 {
-    data: BTreeMap<K, V>,
+    data: OrderStatTree<K, V>,
 }
 
 impl<K, V> TreeMap<K, V>
@@ -15,7 +20,7 @@ where
 {
     pub fn new() -> Self {
         Self {
-            data: BTreeMap::new(),
+            data: OrderStatTree::default(),
         }
     }
 
@@ -38,13 +43,11 @@ where
 
     /// Returns true when key did not exist and insertion happened.
     pub fn put_if_absent(&mut self, key: K, value: V) -> bool {
-        match self.data.entry(key) {
-            std::collections::btree_map::Entry::Vacant(v) => {
-                v.insert(value);
-                true
-            }
-            std::collections::btree_map::Entry::Occupied(_) => false,
+        if self.data.contains_key(&key) {
+            return false;
         }
+        self.data.insert(key, value);
+        true
     }
 
     pub fn size(&self) -> usize {
@@ -70,7 +73,7 @@ where
     }
 
     pub fn iter(&self) -> impl Iterator<Item = &K> {
-        self.data.keys()
+        self.data.iter().map(|(k, _)| k)
     }
 
     pub fn iter_entry(&self) -> impl Iterator<Item = (&K, &V)> {
@@ -81,7 +84,7 @@ where
     where
         F: FnMut(&K, &V),
     {
-        for (k, v) in &self.data {
+        for (k, v) in self.data.iter() {
             f(k, v);
         }
     }
@@ -90,7 +93,7 @@ where
     where
         F: FnMut(&K),
     {
-        for k in self.data.keys() {
+        for (k, _) in self.data.iter() {
             f(k);
         }
     }
@@ -104,11 +107,11 @@ where
     }
 
     pub fn first_key(&self) -> Option<&K> {
-        self.data.first_key_value().map(|(k, _)| k)
+        self.data.first().map(|(k, _)| k)
     }
 
     pub fn last_key(&self) -> Option<&K> {
-        self.data.last_key_value().map(|(k, _)| k)
+        self.data.last().map(|(k, _)| k)
     }
 
     pub fn pop_first(&mut self) -> Option<(K, V)> {
@@ -149,11 +152,37 @@ where
     }
 
     pub fn keys(&self) -> Vec<K> {
-        self.data.keys().cloned().collect()
+        self.data.iter().map(|(k, _)| k.clone()).collect()
     }
 
     pub fn values(&self) -> Vec<V> {
-        self.data.values().cloned().collect()
+        self.data.iter().map(|(_, v)| v.clone()).collect()
+    }
+}
+
+impl<K, V> OrderedQuery<K> for TreeMap<K, V>
+where
+    K: Ord,
+{
+    type Item<'a>
+        = (&'a K, &'a V)
+    where
+        Self: 'a;
+    type Iter<'a>
+        = Range<'a, K, V>
+    where
+        Self: 'a;
+
+    fn range<R: RangeBounds<K>>(&self, range: R) -> Self::Iter<'_> {
+        self.data.range(range)
+    }
+
+    fn select(&self, k: usize) -> Option<Self::Item<'_>> {
+        self.data.select(k)
+    }
+
+    fn rank(&self, key: &K) -> usize {
+        self.data.rank(key)
     }
 }
 
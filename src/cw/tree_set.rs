@@ -1,12 +1,17 @@
-use std::collections::BTreeSet;
 use std::ops::Bound::Included;
+use std::ops::RangeBounds;
+
+use super::order_stat_tree::{Keys, OrderStatTree};
+use super::ordered_query::OrderedQuery;
 
 /// Ordered set backed by a tree structure.
+///
+/// The tree keeps subtree sizes, so `select` / `rank` are O(log n) like lookups.
 pub struct TreeSet<T>
 where
     T: Ord,
 {
-    data: BTreeSet<T>,
+    data: OrderStatTree<T, ()>,
 }
 
 impl<T> TreeSet<T>
@@ -15,24 +20,28 @@ where
 {
     pub fn new() -> Self {
         Self {
-            data: BTreeSet::new(),
+            data: OrderStatTree::default(),
         }
     }
 
     pub fn add(&mut self, e: T) {
-        self.data.insert(e);
+        self.data.insert(e, ());
     }
 
     pub fn delete(&mut self, e: &T) -> bool {
-        self.data.remove(e)
+        self.data.remove(e).is_some()
     }
 
     pub fn contains(&self, e: &T) -> bool {
-        self.data.contains(e)
+        self.data.contains_key(e)
     }
 
     pub fn add_if_absent(&mut self, e: T) -> bool {
-        self.data.insert(e)
+        if self.data.contains_key(&e) {
+            return false;
+        }
+        self.data.insert(e, ());
+        true
     }
 
     pub fn size(&self) -> usize {
@@ -44,11 +53,11 @@ where
     }
 
     pub fn iterate(&self) -> impl Iterator<Item = &T> {
-        self.data.iter()
+        self.iter()
     }
 
     pub fn iter(&self) -> impl Iterator<Item = &T> {
-        self.data.iter()
+        self.data.iter().map(|(k, _)| k)
     }
 
     pub fn clear(&mut self) {
@@ -60,19 +69,19 @@ where
     }
 
     pub fn min(&self) -> Option<&T> {
-        self.data.first()
+        self.data.first().map(|(k, _)| k)
     }
 
     pub fn max(&self) -> Option<&T> {
-        self.data.last()
+        self.data.last().map(|(k, _)| k)
     }
 
     pub fn pop_min(&mut self) -> Option<T> {
-        self.data.pop_first()
+        self.data.pop_first().map(|(k, _)| k)
     }
 
     pub fn pop_max(&mut self) -> Option<T> {
-        self.data.pop_last()
+        self.data.pop_last().map(|(k, _)| k)
     }
 
     pub fn search_range<'a>(&'a self, lower: &'a T, upper: &'a T) -> Vec<&'a T> {
@@ -81,6 +90,7 @@ where
         }
         self.data
             .range((Included(lower), Included(upper)))
+            .map(|(k, _)| k)
             .collect()
     }
 }
@@ -118,33 +128,33 @@ where
     }
 
     pub fn mutual_exclude(&self, another: &TreeSet<T>) -> bool {
-        self.data.is_disjoint(&another.data)
+        !self.iter().any(|v| another.contains(v))
     }
 
     pub fn intersect(&self, another: &TreeSet<T>) -> TreeSet<T> {
-        TreeSet {
-            data: self.data.intersection(&another.data).cloned().collect(),
-        }
+        let mut out = TreeSet::new();
+        out.add_all(self.iter().filter(|v| another.contains(v)).cloned());
+        out
     }
 
     pub fn union(&self, another: &TreeSet<T>) -> TreeSet<T> {
-        TreeSet {
-            data: self.data.union(&another.data).cloned().collect(),
-        }
+        let mut out = self.shallow_copy();
+        out.union_inplace(another);
+        out
     }
 
     pub fn union_inplace(&mut self, another: &TreeSet<T>) {
-        for val in &another.data {
-            self.data.insert(val.clone());
+        for val in another.iter() {
+            self.add(val.clone());
         }
     }
 
     pub fn is_super_set(&self, another: &TreeSet<T>) -> bool {
-        self.data.is_superset(&another.data)
+        another.is_sub_set(self)
     }
 
     pub fn is_sub_set(&self, another: &TreeSet<T>) -> bool {
-        self.data.is_subset(&another.data)
+        self.len() <= another.len() && self.iter().all(|v| another.contains(v))
     }
 
     pub fn shallow_copy(&self) -> TreeSet<T> {
@@ -154,17 +164,43 @@ where
     }
 
     pub fn subtract(&mut self, another: &TreeSet<T>) {
-        for val in &another.data {
+        for val in another.iter() {
             self.data.remove(val);
         }
     }
 
     pub fn to_vec(&self) -> Vec<T> {
-        self.data.iter().cloned().collect()
+        self.iter().cloned().collect()
     }
 
     pub fn equals(&self, another: &TreeSet<T>) -> bool {
-        self.data == another.data
+        self.len() == another.len() && self.iter().eq(another.iter())
+    }
+}
+
+impl<T> OrderedQuery<T> for TreeSet<T>
+where
+    T: Ord,
+{
+    type Item<'a>
+        = &'a T
+    where
+        Self: 'a;
+    type Iter<'a>
+        = Keys<'a, T>
+    where
+        Self: 'a;
+
+    fn range<R: RangeBounds<T>>(&self, range: R) -> Self::Iter<'_> {
+        self.data.range(range).into()
+    }
+
+    fn select(&self, k: usize) -> Option<Self::Item<'_>> {
+        self.data.select(k).map(|(k, _)| k)
+    }
+
+    fn rank(&self, key: &T) -> usize {
+        self.data.rank(key)
     }
 }
 
@@ -1,4 +1,10 @@
-use std::{collections::hash_map::Entry, ptr::null_mut};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, hash_map::Entry},
+    ptr::null_mut,
+};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::commonw::types::FastMap;
 
@@ -8,6 +14,11 @@ use crate::commonw::types::FastMap;
 /// a map of characters to child nodes. It supports basic operations like insert,
 /// search, prefix matching, and deletion.
 ///
+/// Every word also carries a `u64` weight (e.g. usage frequency) that drives
+/// [`top_k_with_prefix`](Trie::top_k_with_prefix); [`fuzzy_search`](Trie::fuzzy_search)
+/// finds words within a Levenshtein distance, and [`to_radix`](Trie::to_radix)
+/// produces the compact [`RadixTrie`] form that is used for serialization.
+///
 /// # Examples
 ///
 /// ```
@@ -33,6 +44,9 @@ pub struct Trie {
 
     /// Total number of unique words in the entire trie
     size: usize,
+
+    /// Weight of the word ending at this node (0 for plain `insert`)
+    weight: u64,
 }
 
 impl Trie {
@@ -52,6 +66,7 @@ impl Trie {
             end_count: 0,
             size: 0,
             valid_count: 0,
+            weight: 0,
         }
     }
 }
@@ -293,6 +308,7 @@ impl Trie {
         curr.end_count -= 1;
         if curr.end_count == 0 {
             curr.valid_count -= 1;
+            curr.weight = 0;
         }
 
         // Clean up: remove child node from parent if parent is no longer a word endpoint
@@ -343,6 +359,392 @@ impl Trie {
     pub fn is_empty(&self) -> bool {
         self.size == 0
     }
+
+    /// Inserts a string with the given weight, or updates the weight of an
+    /// existing word.
+    ///
+    /// Returns `true` if the word was not present before.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_tools::cw::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// assert!(trie.insert_weighted("help", 3));
+    /// assert!(!trie.insert_weighted("help", 5));
+    /// assert_eq!(trie.weight("help"), Some(5));
+    /// ```
+    pub fn insert_weighted(&mut self, s: &str, weight: u64) -> bool {
+        let fresh = !self.contains(s);
+        self.insert(s);
+        if let Some(node) = self.find_node_mut(s) {
+            node.weight = weight;
+        }
+        fresh
+    }
+
+    /// Adds `delta` to the weight of `s` (inserting it with weight `delta` if
+    /// absent) and returns the new weight. Handy for counting selections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_tools::cw::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.add_weight("/usage", 1);
+    /// assert_eq!(trie.add_weight("/usage", 2), 3);
+    /// ```
+    pub fn add_weight(&mut self, s: &str, delta: u64) -> u64 {
+        self.insert(s);
+        let node = self.find_node_mut(s).expect("word was just inserted");
+        node.weight = node.weight.saturating_add(delta);
+        node.weight
+    }
+
+    /// Returns the weight of `s`, or `None` if it is not a complete word.
+    pub fn weight(&self, s: &str) -> Option<u64> {
+        self.find_node(s)
+            .filter(|node| node.end_count > 0)
+            .map(|node| node.weight)
+    }
+
+    /// Returns up to `k` words starting with `prefix`, ordered by weight
+    /// (heaviest first) and then alphabetically.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_tools::cw::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.insert_weighted("/help", 1);
+    /// trie.insert_weighted("/history", 9);
+    /// trie.insert_weighted("/h", 4);
+    /// trie.insert_weighted("/usage", 100);
+    ///
+    /// let top = trie.top_k_with_prefix("/h", 2);
+    /// assert_eq!(top, vec![("/history".to_string(), 9), ("/h".to_string(), 4)]);
+    /// ```
+    pub fn top_k_with_prefix(&self, prefix: &str, k: usize) -> Vec<(String, u64)> {
+        let Some(start) = self.find_node(prefix) else {
+            return Vec::new();
+        };
+        if k == 0 {
+            return Vec::new();
+        }
+
+        // Min-heap of the best `k` seen so far: the root is the entry to evict
+        // next (lowest weight, then alphabetically last).
+        let mut heap: BinaryHeap<Reverse<(u64, Reverse<String>)>> = BinaryHeap::new();
+        let mut buf = prefix.to_string();
+        start.visit_words(&mut buf, &mut |word, weight| {
+            if heap.len() == k {
+                let Reverse((min_weight, Reverse(min_word))) = heap.peek().unwrap();
+                if weight < *min_weight || (weight == *min_weight && word >= min_word.as_str()) {
+                    return;
+                }
+                heap.pop();
+            }
+            heap.push(Reverse((weight, Reverse(word.to_string()))));
+        });
+
+        let mut out: Vec<(String, u64)> = heap
+            .into_iter()
+            .map(|Reverse((weight, Reverse(word)))| (word, weight))
+            .collect();
+        out.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        out
+    }
+
+    /// Returns every word whose Levenshtein distance to `query` is at most
+    /// `max_distance`, sorted by distance, then by weight (heaviest first),
+    /// then alphabetically.
+    ///
+    /// The search walks the trie once, keeping one row of the edit-distance
+    /// table per depth and pruning subtrees whose row minimum already exceeds
+    /// `max_distance`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_tools::cw::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// for w in ["search", "seach", "starch", "research"] {
+    ///     trie.insert(w);
+    /// }
+    /// let hits = trie.fuzzy_search("serach", 2);
+    /// assert_eq!(hits, [("seach".to_string(), 1), ("search".to_string(), 2)]);
+    /// ```
+    pub fn fuzzy_search(&self, query: &str, max_distance: usize) -> Vec<(String, usize)> {
+        let query: Vec<char> = query.chars().collect();
+        let first_row: Vec<usize> = (0..=query.len()).collect();
+        let mut hits = Vec::new();
+        if self.end_count > 0 && query.len() <= max_distance {
+            hits.push((String::new(), query.len(), self.weight));
+        }
+        let mut buf = String::new();
+        for (ch, child) in &self.children {
+            child.fuzzy_walk(*ch, &query, &first_row, max_distance, &mut buf, &mut hits);
+        }
+        hits.sort_by(|a, b| {
+            a.1.cmp(&b.1)
+                .then_with(|| b.2.cmp(&a.2))
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        hits.into_iter()
+            .map(|(word, distance, _)| (word, distance))
+            .collect()
+    }
+
+    fn fuzzy_walk(
+        &self,
+        ch: char,
+        query: &[char],
+        prev_row: &[usize],
+        max_distance: usize,
+        buf: &mut String,
+        hits: &mut Vec<(String, usize, u64)>,
+    ) {
+        let mut row = Vec::with_capacity(prev_row.len());
+        row.push(prev_row[0] + 1);
+        for j in 1..prev_row.len() {
+            let substitute = prev_row[j - 1] + usize::from(query[j - 1] != ch);
+            row.push(substitute.min(prev_row[j] + 1).min(row[j - 1] + 1));
+        }
+
+        buf.push(ch);
+        let distance = row[query.len()];
+        if self.end_count > 0 && distance <= max_distance {
+            hits.push((buf.clone(), distance, self.weight));
+        }
+        if row.iter().copied().min().unwrap_or(usize::MAX) <= max_distance {
+            for (next, child) in &self.children {
+                child.fuzzy_walk(*next, query, &row, max_distance, buf, hits);
+            }
+        }
+        buf.pop();
+    }
+
+    /// Converts the trie into its compact radix-tree form, where chains of
+    /// single-child nodes collapse into one edge labelled with a string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_tools::cw::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.insert("romane");
+    /// trie.insert("romanus");
+    /// let radix = trie.to_radix();
+    /// assert_eq!(radix.len(), 2);
+    /// assert!(radix.node_count() < 10);
+    /// assert!(radix.to_trie().contains("romanus"));
+    /// ```
+    pub fn to_radix(&self) -> RadixTrie {
+        RadixTrie {
+            len: self.size,
+            root: self.radix_node(String::new()),
+        }
+    }
+
+    fn radix_node(&self, label: String) -> RadixNode {
+        let mut keys: Vec<&char> = self.children.keys().collect();
+        keys.sort();
+        let children = keys
+            .into_iter()
+            .map(|ch| {
+                let mut label = ch.to_string();
+                let mut node: &Trie = &self.children[ch];
+                while node.end_count == 0 && node.children.len() == 1 {
+                    let (next, child) = node.children.iter().next().unwrap();
+                    label.push(*next);
+                    node = child;
+                }
+                node.radix_node(label)
+            })
+            .collect();
+        RadixNode {
+            label,
+            weight: (self.end_count > 0).then_some(self.weight),
+            children,
+        }
+    }
+
+    fn find_node(&self, s: &str) -> Option<&Trie> {
+        let mut curr = self;
+        for ch in s.chars() {
+            curr = curr.children.get(&ch)?;
+        }
+        Some(curr)
+    }
+
+    fn find_node_mut(&mut self, s: &str) -> Option<&mut Trie> {
+        let mut curr = self;
+        for ch in s.chars() {
+            curr = curr.children.get_mut(&ch)?;
+        }
+        Some(curr)
+    }
+
+    /// Depth-first visit of every complete word in this subtree together with
+    /// its weight.
+    fn visit_words(&self, buf: &mut String, f: &mut impl FnMut(&str, u64)) {
+        if self.end_count > 0 {
+            f(buf, self.weight);
+        }
+        for (ch, child) in &self.children {
+            buf.push(*ch);
+            child.visit_words(buf, f);
+            buf.pop();
+        }
+    }
+}
+
+/// Serialized as its [`RadixTrie`] form.
+impl Serialize for Trie {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_radix().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Trie {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(RadixTrie::deserialize(deserializer)?.to_trie())
+    }
+}
+
+/// A compact, serializable radix tree (compressed trie).
+///
+/// Edges carry whole string fragments instead of single characters, and
+/// children are kept sorted, so the structure is both smaller than [`Trie`]
+/// and deterministic when serialized (e.g. to cache completion tables on disk).
+/// It is a read-only snapshot; convert back with [`to_trie`](RadixTrie::to_trie)
+/// to modify it.
+///
+/// # Examples
+///
+/// ```
+/// use rust_tools::cw::{RadixTrie, Trie};
+///
+/// let mut trie = Trie::new();
+/// trie.insert_weighted("/history", 2);
+/// trie.insert_weighted("/help", 1);
+///
+/// let json = serde_json::to_string(&trie.to_radix()).unwrap();
+/// let radix: RadixTrie = serde_json::from_str(&json).unwrap();
+/// assert_eq!(radix.weight("/history"), Some(2));
+/// assert_eq!(radix.words_with_prefix("/h"), ["/help", "/history"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RadixTrie {
+    len: usize,
+    root: RadixNode,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct RadixNode {
+    /// Edge label leading into this node (empty for the root)
+    #[serde(rename = "l", default, skip_serializing_if = "String::is_empty")]
+    label: String,
+
+    /// `Some(weight)` if a word ends here
+    #[serde(rename = "w", default, skip_serializing_if = "Option::is_none")]
+    weight: Option<u64>,
+
+    /// Children sorted by label
+    #[serde(rename = "c", default, skip_serializing_if = "Vec::is_empty")]
+    children: Vec<RadixNode>,
+}
+
+impl RadixTrie {
+    /// Returns the number of words.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if there are no words.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of nodes, including the root.
+    pub fn node_count(&self) -> usize {
+        fn count(node: &RadixNode) -> usize {
+            1 + node.children.iter().map(count).sum::<usize>()
+        }
+        count(&self.root)
+    }
+
+    /// Checks if `s` is a complete word.
+    pub fn contains(&self, s: &str) -> bool {
+        self.weight(s).is_some()
+    }
+
+    /// Returns the weight of `s`, or `None` if it is not a complete word.
+    pub fn weight(&self, s: &str) -> Option<u64> {
+        let mut node = &self.root;
+        let mut rest = s;
+        while !rest.is_empty() {
+            node = node
+                .children
+                .iter()
+                .find(|child| rest.starts_with(child.label.as_str()))?;
+            rest = &rest[node.label.len()..];
+        }
+        node.weight
+    }
+
+    /// Returns all words starting with `prefix`, in sorted order.
+    pub fn words_with_prefix(&self, prefix: &str) -> Vec<String> {
+        let mut node = &self.root;
+        let mut path = String::new();
+        let mut rest = prefix;
+        while !rest.is_empty() {
+            let Some(child) = node.children.iter().find(|child| {
+                rest.starts_with(child.label.as_str()) || child.label.starts_with(rest)
+            }) else {
+                return Vec::new();
+            };
+            path.push_str(&child.label);
+            rest = rest.get(child.label.len()..).unwrap_or("");
+            node = child;
+        }
+
+        fn collect(node: &RadixNode, path: &mut String, out: &mut Vec<String>) {
+            if node.weight.is_some() {
+                out.push(path.clone());
+            }
+            for child in &node.children {
+                path.push_str(&child.label);
+                collect(child, path, out);
+                path.truncate(path.len() - child.label.len());
+            }
+        }
+        let mut out = Vec::new();
+        collect(node, &mut path, &mut out);
+        out
+    }
+
+    /// Expands the radix tree back into a mutable [`Trie`].
+    pub fn to_trie(&self) -> Trie {
+        fn expand(node: &RadixNode, path: &mut String, trie: &mut Trie) {
+            if let Some(weight) = node.weight {
+                trie.insert_weighted(path, weight);
+            }
+            for child in &node.children {
+                path.push_str(&child.label);
+                expand(child, path, trie);
+                path.truncate(path.len() - child.label.len());
+            }
+        }
+        let mut trie = Trie::new();
+        expand(&self.root, &mut String::new(), &mut trie);
+        trie
+    }
 }
 
 #[cfg(test)]
@@ -427,4 +829,123 @@ mod tests {
         // No match.
         assert!(t.words_with_prefix("xyz").is_empty());
     }
+
+    /// Test weighted top-k completion and weight bookkeeping
+    #[test]
+    fn test_top_k_with_prefix() {
+        let mut t = Trie::new();
+        for (i, w) in ["apple", "apply", "ape", "apex", "banana"]
+            .iter()
+            .enumerate()
+        {
+            t.insert_weighted(w, i as u64);
+        }
+        t.insert("apt");
+
+        assert_eq!(
+            t.top_k_with_prefix("ap", 3),
+            vec![
+                ("apex".to_string(), 3),
+                ("ape".to_string(), 2),
+                ("apply".to_string(), 1),
+            ]
+        );
+        // Ties are broken alphabetically.
+        t.insert_weighted("apply", 3);
+        assert_eq!(
+            t.top_k_with_prefix("ap", 2),
+            vec![("apex".to_string(), 3), ("apply".to_string(), 3)]
+        );
+        assert_eq!(t.top_k_with_prefix("ap", 100).len(), 5);
+        assert!(t.top_k_with_prefix("ap", 0).is_empty());
+        assert!(t.top_k_with_prefix("zz", 3).is_empty());
+
+        assert_eq!(t.add_weight("apt", 7), 7);
+        assert_eq!(t.top_k_with_prefix("", 1), vec![("apt".to_string(), 7)]);
+        assert!(t.delete("apt"));
+        assert_eq!(t.weight("apt"), None);
+        t.insert("apt");
+        assert_eq!(t.weight("apt"), Some(0));
+    }
+
+    /// Compare fuzzy search against a brute-force Levenshtein distance
+    #[test]
+    fn test_fuzzy_search_matches_brute_force() {
+        fn levenshtein(a: &str, b: &str) -> usize {
+            let a: Vec<char> = a.chars().collect();
+            let b: Vec<char> = b.chars().collect();
+            let mut prev: Vec<usize> = (0..=b.len()).collect();
+            for i in 1..=a.len() {
+                let mut row = vec![i; b.len() + 1];
+                for j in 1..=b.len() {
+                    row[j] = (prev[j - 1] + usize::from(a[i - 1] != b[j - 1]))
+                        .min(prev[j] + 1)
+                        .min(row[j - 1] + 1);
+                }
+                prev = row;
+            }
+            prev[b.len()]
+        }
+
+        let words = [
+            "", "a", "search", "sear", "starch", "research", "serach", "seach", "搜索", "搜查",
+            "history", "help", "hello",
+        ];
+        let mut t = Trie::new();
+        for w in words {
+            t.insert(w);
+        }
+        for query in ["serach", "", "helo", "搜", "hstory", "xyz"] {
+            for max in 0..=3 {
+                let mut expected: Vec<(String, usize)> = words
+                    .iter()
+                    .map(|w| (w.to_string(), levenshtein(w, query)))
+                    .filter(|(_, d)| *d <= max)
+                    .collect();
+                expected.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+                assert_eq!(t.fuzzy_search(query, max), expected, "{query} {max}");
+            }
+        }
+    }
+
+    /// Radix conversion and serde round trips keep words and weights
+    #[test]
+    fn test_radix_round_trip() {
+        let mut t = Trie::new();
+        for (i, w) in [
+            "romane", "romanus", "romulus", "rubens", "ruber", "rubicon", "r",
+        ]
+        .iter()
+        .enumerate()
+        {
+            t.insert_weighted(w, i as u64 * 10);
+        }
+
+        let radix = t.to_radix();
+        assert_eq!(radix.len(), 7);
+        assert!(radix.node_count() < 15);
+        assert!(radix.contains("r"));
+        assert!(radix.contains("rubicon"));
+        assert!(!radix.contains("rub"));
+        assert_eq!(radix.weight("ruber"), Some(40));
+        assert_eq!(
+            radix.words_with_prefix("rom"),
+            ["romane", "romanus", "romulus"]
+        );
+        assert_eq!(radix.words_with_prefix("rubic"), ["rubicon"]);
+        assert!(radix.words_with_prefix("rx").is_empty());
+        assert_eq!(radix.words_with_prefix("").len(), 7);
+
+        let json = serde_json::to_string(&t).unwrap();
+        let back: Trie = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.len(), 7);
+        assert_eq!(back.to_radix(), radix);
+        let mut all = back.words_with_prefix("");
+        all.sort();
+        assert_eq!(all, radix.words_with_prefix(""));
+
+        let empty: RadixTrie =
+            serde_json::from_str(&serde_json::to_string(&Trie::new().to_radix()).unwrap()).unwrap();
+        assert!(empty.is_empty());
+    }
 }
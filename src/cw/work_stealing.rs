@@ -0,0 +1,314 @@
+//! 工作窃取（Work Stealing）
+//!
+//! 每个工作线程持有一个 [`Worker`] 双端队列：自己从尾部压入 / 弹出（LIFO，局部性好），
+//! 其他线程通过 [`Stealer`] 从头部窃取（FIFO，偷到的通常是较大的任务）。
+//!
+//! 与 [`ArrayQueue`](super::ArrayQueue) 不同，这里的双端队列不是无锁的（不是 Chase-Lev 算法）：
+//! 每个 [`Worker`] 是一个 `Mutex<VecDeque>`，压入、弹出和窃取都要短暂加锁。
+//! 锁只在所有者与窃取者之间竞争，对目录遍历这类单个任务较重的场景足够。
+//!
+//! [`run`] 在此之上实现了一个作用域线程池：任务执行时可以继续派生子任务，
+//! 所有任务完成（或调用 [`Context::stop`]）后返回。适合目录遍历这类任务树
+//! 大小事先未知、分布不均匀的场景。
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+type Shared<T> = Arc<Mutex<VecDeque<T>>>;
+
+fn lock<T>(deque: &Mutex<VecDeque<T>>) -> MutexGuard<'_, VecDeque<T>> {
+    deque.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 工作线程自己的双端队列
+///
+/// 内部是一把只在所有者和窃取者之间竞争的短锁；所有者操作尾部、窃取者操作头部。
+///
+/// # 示例
+///
+/// ```rust
+/// use rust_tools::cw::Worker;
+///
+/// let w = Worker::new();
+/// w.push(1);
+/// w.push(2);
+/// w.push(3);
+///
+/// let s = w.stealer();
+/// assert_eq!(s.steal(), Some(1)); // 窃取最早的
+/// assert_eq!(w.pop(), Some(3)); // 自己取最新的
+/// assert_eq!(w.len(), 1);
+/// ```
+pub struct Worker<T> {
+    deque: Shared<T>,
+}
+
+impl<T> Worker<T> {
+    /// 创建空队列
+    pub fn new() -> Self {
+        Self {
+            deque: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// 压入尾部
+    pub fn push(&self, task: T) {
+        lock(&self.deque).push_back(task);
+    }
+
+    /// 从尾部弹出（最近压入的任务）
+    pub fn pop(&self) -> Option<T> {
+        lock(&self.deque).pop_back()
+    }
+
+    /// 创建一个可以跨线程共享的窃取端
+    pub fn stealer(&self) -> Stealer<T> {
+        Stealer {
+            deque: Arc::clone(&self.deque),
+        }
+    }
+
+    /// 任务个数
+    pub fn len(&self) -> usize {
+        lock(&self.deque).len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Default for Worker<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// [`Worker`] 的窃取端，可克隆并发送到其他线程
+pub struct Stealer<T> {
+    deque: Shared<T>,
+}
+
+impl<T> Clone for Stealer<T> {
+    fn clone(&self) -> Self {
+        Self {
+            deque: Arc::clone(&self.deque),
+        }
+    }
+}
+
+impl<T> Stealer<T> {
+    /// 从头部窃取一个任务
+    pub fn steal(&self) -> Option<T> {
+        lock(&self.deque).pop_front()
+    }
+
+    /// 窃取约一半的任务放进 `dest`，并弹出其中一个直接返回，减少反复窃取
+    pub fn steal_half_and_pop(&self, dest: &Worker<T>) -> Option<T> {
+        if Arc::ptr_eq(&self.deque, &dest.deque) {
+            return dest.pop();
+        }
+        let batch: Vec<T> = {
+            let mut src = lock(&self.deque);
+            let n = src.len().div_ceil(2);
+            src.drain(..n).collect()
+        };
+        let mut batch = batch.into_iter();
+        let first = batch.next()?;
+        let mut dst = lock(&dest.deque);
+        // 保持窃取来的顺序：较早的任务留在 dest 的头部，继续供他人窃取
+        for task in batch.rev() {
+            dst.push_front(task);
+        }
+        Some(first)
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        lock(&self.deque).is_empty()
+    }
+}
+
+/// 任务执行时的上下文：派生子任务或提前终止整个 [`run`]
+pub struct Context<'a, T> {
+    worker: &'a Worker<T>,
+    pending: &'a AtomicUsize,
+    stopped: &'a AtomicBool,
+    index: usize,
+}
+
+impl<T> Context<'_, T> {
+    /// 派生一个子任务，放入当前线程的队列
+    pub fn spawn(&self, task: T) {
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        self.worker.push(task);
+    }
+
+    /// 让所有线程在完成手头任务后退出，未执行的任务被丢弃
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+
+    /// 是否已被要求停止
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+
+    /// 当前工作线程的编号（`0..threads`）
+    pub fn worker_index(&self) -> usize {
+        self.index
+    }
+}
+
+/// 用 `threads` 个线程执行 `seeds` 及其派生出的全部任务，全部完成后返回
+///
+/// `handler` 通过 [`Context::spawn`] 派生子任务。线程闲置时轮流从其他线程窃取一半任务，
+/// 用一个未完成任务计数器判断结束。`threads` 为 0 时按 1 处理。
+///
+/// # 示例
+///
+/// ```rust
+/// use rust_tools::cw::work_stealing;
+/// use std::sync::atomic::{AtomicU64, Ordering};
+///
+/// // 以 1 为根、每个 n 派生 2n 和 2n+1 的二叉树，统计不超过 1000 的节点之和
+/// let sum = AtomicU64::new(0);
+/// work_stealing::run(4, vec![1u64], |n, ctx| {
+///     sum.fetch_add(n, Ordering::Relaxed);
+///     for child in [2 * n, 2 * n + 1] {
+///         if child <= 1000 {
+///             ctx.spawn(child);
+///         }
+///     }
+/// });
+/// assert_eq!(sum.into_inner(), (1..=1000).sum::<u64>());
+/// ```
+pub fn run<T, F>(threads: usize, seeds: impl IntoIterator<Item = T>, handler: F)
+where
+    T: Send,
+    F: Fn(T, &Context<'_, T>) + Sync,
+{
+    let threads = threads.max(1);
+    let workers: Vec<Worker<T>> = (0..threads).map(|_| Worker::new()).collect();
+    let pending = AtomicUsize::new(0);
+    for (i, seed) in seeds.into_iter().enumerate() {
+        pending.fetch_add(1, Ordering::SeqCst);
+        workers[i % threads].push(seed);
+    }
+    let stealers: Vec<Stealer<T>> = workers.iter().map(Worker::stealer).collect();
+    let stopped = AtomicBool::new(false);
+
+    thread::scope(|scope| {
+        for (index, worker) in workers.into_iter().enumerate() {
+            let (stealers, pending, stopped, handler) = (&stealers, &pending, &stopped, &handler);
+            scope.spawn(move || {
+                let ctx = Context {
+                    worker: &worker,
+                    pending,
+                    stopped,
+                    index,
+                };
+                let mut idle_rounds = 0u32;
+                while !stopped.load(Ordering::Relaxed) {
+                    let task = worker.pop().or_else(|| {
+                        (1..threads)
+                            .map(|k| &stealers[(index + k) % threads])
+                            .find_map(|s| s.steal_half_and_pop(&worker))
+                    });
+                    match task {
+                        Some(task) => {
+                            idle_rounds = 0;
+                            handler(task, &ctx);
+                            pending.fetch_sub(1, Ordering::SeqCst);
+                        }
+                        None if pending.load(Ordering::SeqCst) == 0 => break,
+                        None => {
+                            // 还有任务在别的线程上执行，可能会派生新任务：先让出，再短暂休眠
+                            idle_rounds += 1;
+                            if idle_rounds < 64 {
+                                thread::yield_now();
+                            } else {
+                                thread::sleep(Duration::from_micros(100));
+                            }
+                        }
+                    }
+                }
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU64;
+
+    #[test]
+    fn test_steal_half_and_pop() {
+        let w = Worker::new();
+        for i in 0..5 {
+            w.push(i);
+        }
+        let thief = Worker::new();
+        assert_eq!(w.stealer().steal_half_and_pop(&thief), Some(0));
+        assert_eq!(w.len(), 2);
+        // 偷来的 1、2 留在 thief 中，顺序不变
+        assert_eq!(thief.stealer().steal(), Some(1));
+        assert_eq!(thief.pop(), Some(2));
+        assert_eq!(
+            Worker::<i32>::new().stealer().steal_half_and_pop(&thief),
+            None
+        );
+    }
+
+    /// 单个种子派生出不均匀的任务树，所有线程都应参与且每个任务恰好执行一次
+    #[test]
+    fn test_run_unbalanced_tree() {
+        let visited = AtomicU64::new(0);
+        let sum = AtomicU64::new(0);
+        let per_thread: Vec<AtomicUsize> = (0..4).map(|_| AtomicUsize::new(0)).collect();
+        run(4, [0u64], |depth, ctx| {
+            visited.fetch_add(1, Ordering::Relaxed);
+            sum.fetch_add(depth, Ordering::Relaxed);
+            per_thread[ctx.worker_index()].fetch_add(1, Ordering::Relaxed);
+            if depth < 14 {
+                ctx.spawn(depth + 1);
+                ctx.spawn(depth + 1);
+            }
+            thread::yield_now();
+        });
+        // 满二叉树：深度 d 有 2^d 个节点
+        assert_eq!(visited.into_inner(), (1 << 15) - 1);
+        assert_eq!(
+            sum.into_inner(),
+            (0..15u64).map(|d| d * (1 << d)).sum::<u64>()
+        );
+        assert!(
+            per_thread
+                .iter()
+                .filter(|c| c.load(Ordering::Relaxed) > 0)
+                .count()
+                > 1
+        );
+    }
+
+    #[test]
+    fn test_run_stop_and_empty() {
+        run(3, Vec::<u32>::new(), |_, _| unreachable!());
+
+        let executed = AtomicUsize::new(0);
+        run(2, [0u32], |n, ctx| {
+            if executed.fetch_add(1, Ordering::SeqCst) >= 100 {
+                ctx.stop();
+                return;
+            }
+            ctx.spawn(n + 1);
+            ctx.spawn(n + 1);
+        });
+        assert!(executed.into_inner() < 200);
+    }
+}
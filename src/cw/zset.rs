@@ -1,5 +1,6 @@
 use std::cmp::Ordering;
 use std::hash::Hash;
+use std::ops::{Bound, RangeBounds};
 
 use crate::commonw::types::FastMap;
 use crate::cw::SkipSet;
@@ -20,22 +21,78 @@ impl<K> ZSetEntry<'_, K> {
     }
 }
 
+/// Breaks score ties by comparing members; `None` keeps insertion order.
+type MemberCmp<K> = Option<fn(&K, &K) -> Ordering>;
+
 #[derive(Clone, Debug)]
 struct ZSetNode<K> {
     key: K,
     score: f64,
     order: u64,
+    member_cmp: MemberCmp<K>,
 }
 
 impl<K> ZSetNode<K> {
-    fn new(key: K, score: f64, order: u64) -> Self {
-        Self { key, score, order }
+    fn new(key: K, score: f64, order: u64, member_cmp: MemberCmp<K>) -> Self {
+        Self {
+            key,
+            score,
+            order,
+            member_cmp,
+        }
+    }
+
+    fn entry(&self) -> ZSetEntry<'_, K> {
+        ZSetEntry {
+            key: &self.key,
+            score: self.score,
+        }
+    }
+
+    /// Compares this node with the entry `(score, order, key)` without
+    /// building a node for it.
+    fn cmp_to(&self, score: f64, order: u64, key: &K) -> Ordering {
+        self.score
+            .total_cmp(&score)
+            .then_with(|| match self.member_cmp {
+                Some(cmp) => cmp(&self.key, key),
+                None => self.order.cmp(&order),
+            })
     }
 }
 
+/// How `union_with_weights` / `intersect_with_weights` combine the scores of
+/// a key present in several inputs (Redis `AGGREGATE SUM|MIN|MAX`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Aggregate {
+    #[default]
+    Sum,
+    Min,
+    Max,
+}
+
+impl Aggregate {
+    fn apply(self, acc: f64, score: f64) -> f64 {
+        match self {
+            // Like Redis, inf + -inf is 0 rather than NaN.
+            Aggregate::Sum => {
+                let sum = acc + score;
+                if sum.is_nan() { 0.0 } else { sum }
+            }
+            Aggregate::Min => acc.min(score),
+            Aggregate::Max => acc.max(score),
+        }
+    }
+}
+
+fn weighted(score: f64, weight: f64) -> f64 {
+    let s = score * weight;
+    if s.is_nan() { 0.0 } else { s }
+}
+
 impl<K> PartialEq for ZSetNode<K> {
     fn eq(&self, other: &Self) -> bool {
-        self.order == other.order
+        self.cmp(other).is_eq()
     }
 }
 
@@ -49,15 +106,36 @@ impl<K> PartialOrd for ZSetNode<K> {
 
 impl<K> Ord for ZSetNode<K> {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.score
-            .total_cmp(&other.score)
-            .then_with(|| self.order.cmp(&other.order))
+        self.cmp_to(other.score, other.order, &other.key)
     }
 }
 
 /// Ordered set with scores (similar to Redis ZSET).
 ///
-/// Elements are sorted by score. K only needs `Eq + Hash`.
+/// Elements are sorted by score. A set created with [`new`](Self::new)
+/// (which needs `K: Ord`) breaks ties by comparing members, as Redis does.
+/// Keys that are only `Eq + Hash` go through
+/// [`with_insertion_order`](Self::with_insertion_order) instead, where equal
+/// scores keep the order in which their scores were last set. Rank, index and
+/// score-range lookups are O(log n); lexicographic queries are one tree range
+/// per run of equal scores.
+///
+/// # Examples
+///
+/// ```
+/// use rust_tools::cw::ZSet;
+///
+/// let mut board = ZSet::new();
+/// board.add("alice", 30.0);
+/// board.add("bob", 10.0);
+/// board.add("carol", 20.0);
+/// board.incr_by("bob", 25.0);
+///
+/// let top: Vec<_> = board.range_by_rank(-2, -1).map(|e| *e.key()).collect();
+/// assert_eq!(top, ["alice", "bob"]);
+/// assert_eq!(board.rev_rank(&"bob"), 1);
+/// assert_eq!(board.count(20.0..=30.0), 2);
+/// ```
 pub struct ZSet<K>
 where
     K: Eq + Hash,
@@ -65,20 +143,37 @@ where
     tree: SkipSet<ZSetNode<K>>,
     map: FastMap<K, (f64, u64)>,
     next_order: u64,
+    member_cmp: MemberCmp<K>,
 }
 
 impl<K> ZSet<K>
 where
     K: Eq + Hash,
 {
-    pub fn new() -> Self {
+    /// Creates a set whose equal scores keep insertion order (last score
+    /// update first in, first out). Works for keys that are not `Ord`.
+    pub fn with_insertion_order() -> Self {
+        Self::with_member_cmp(None)
+    }
+
+    fn with_member_cmp(member_cmp: MemberCmp<K>) -> Self {
         Self {
             tree: SkipSet::new(12),
             map: FastMap::default(),
             next_order: 0,
+            member_cmp,
         }
     }
 
+    /// An empty set with the same tie-break as `self`.
+    fn empty_like(&self) -> Self {
+        Self::with_member_cmp(self.member_cmp)
+    }
+
+    fn node(&self, key: K, score: f64, order: u64) -> ZSetNode<K> {
+        ZSetNode::new(key, score, order, self.member_cmp)
+    }
+
     pub fn len(&self) -> usize {
         self.map.len()
     }
@@ -91,27 +186,121 @@ where
         if score_low > score_high {
             return Vec::new();
         }
+        self.range_by_score(score_low..=score_high).collect()
+    }
+
+    /// Entries whose score falls in `range` (Redis `ZRANGEBYSCORE`; use
+    /// `Bound::Excluded` for the `(` form). Reverse with `.rev()`.
+    pub fn range_by_score<R: RangeBounds<f64>>(
+        &self,
+        range: R,
+    ) -> impl DoubleEndedIterator<Item = ZSetEntry<'_, K>> + '_ {
+        let (before, within) = Self::score_predicates(&range);
+        self.tree.range_by(before, within).map(ZSetNode::entry)
+    }
+
+    /// Number of entries whose score falls in `range` (Redis `ZCOUNT`).
+    pub fn count<R: RangeBounds<f64>>(&self, range: R) -> usize {
+        let (before, within) = Self::score_predicates(&range);
         self.tree
-            .iter()
-            .filter(|entry| entry.score >= score_low && entry.score <= score_high)
-            .map(|entry| ZSetEntry {
-                key: &entry.key,
-                score: entry.score,
-            })
-            .collect()
+            .count_while(within)
+            .saturating_sub(self.tree.count_while(before))
+    }
+
+    fn score_predicates<R: RangeBounds<f64>>(
+        range: &R,
+    ) -> (
+        impl Fn(&ZSetNode<K>) -> bool + use<K, R>,
+        impl Fn(&ZSetNode<K>) -> bool + use<K, R>,
+    ) {
+        let start = range.start_bound().cloned();
+        let end = range.end_bound().cloned();
+        let before = move |n: &ZSetNode<K>| match start {
+            Bound::Included(s) => n.score.total_cmp(&s).is_lt(),
+            Bound::Excluded(s) => n.score.total_cmp(&s).is_le(),
+            Bound::Unbounded => false,
+        };
+        let within = move |n: &ZSetNode<K>| match end {
+            Bound::Included(e) => n.score.total_cmp(&e).is_le(),
+            Bound::Excluded(e) => n.score.total_cmp(&e).is_lt(),
+            Bound::Unbounded => true,
+        };
+        (before, within)
+    }
+
+    /// Entries with 0-based index in `start..=stop`, lowest score first
+    /// (Redis `ZRANGE`). Negative indices count from the end, so
+    /// `range_by_rank(0, -1)` is everything and `(-3, -1)` the top three.
+    pub fn range_by_rank(
+        &self,
+        start: isize,
+        stop: isize,
+    ) -> impl DoubleEndedIterator<Item = ZSetEntry<'_, K>> + '_ {
+        let bounds = self
+            .rank_bounds(start, stop)
+            .and_then(|(start, stop)| Some((self.tree.select(start)?, self.tree.select(stop)?)));
+        self.tree
+            .range_by(
+                move |n| bounds.is_none_or(|(first, _)| n < first),
+                move |n| bounds.is_some_and(|(_, last)| n <= last),
+            )
+            .map(ZSetNode::entry)
+    }
+
+    /// Like [`range_by_rank`](Self::range_by_rank) but indexed from the
+    /// highest score and yielded highest first (Redis `ZREVRANGE`).
+    pub fn rev_range_by_rank(
+        &self,
+        start: isize,
+        stop: isize,
+    ) -> impl DoubleEndedIterator<Item = ZSetEntry<'_, K>> + '_ {
+        let (start, stop) = match self.rank_bounds(start, stop) {
+            Some((start, stop)) => {
+                let last = self.len() as isize - 1;
+                (last - stop as isize, last - start as isize)
+            }
+            // An empty window: start after stop.
+            None => (1, 0),
+        };
+        self.range_by_rank(start, stop).rev()
+    }
+
+    /// Normalizes Redis-style inclusive, possibly negative indices.
+    fn rank_bounds(&self, start: isize, stop: isize) -> Option<(usize, usize)> {
+        let len = self.len() as isize;
+        let start = if start < 0 {
+            (start + len).max(0)
+        } else {
+            start
+        };
+        let stop = if stop < 0 {
+            stop + len
+        } else {
+            stop.min(len - 1)
+        };
+        if len == 0 || start > stop || start >= len {
+            return None;
+        }
+        Some((start as usize, stop as usize))
     }
 
     /// Returns -1 if key does not exist. Otherwise returns 1-based rank.
     pub fn rank(&self, key: &K) -> isize {
-        if !self.map.contains_key(key) {
+        let Some(&(score, order)) = self.map.get(key) else {
             return -1;
+        };
+        self.tree
+            .count_while(|n| n.cmp_to(score, order, key).is_lt()) as isize
+            + 1
+    }
+
+    /// Rank counted from the highest score: returns -1 if key does not exist,
+    /// otherwise the 1-based rank (the maximum has rank 1).
+    pub fn rev_rank(&self, key: &K) -> isize {
+        match self.rank(key) {
+            -1 => -1,
+            rank => self.len() as isize - rank + 1,
         }
-        for (idx, node) in self.tree.iter().enumerate() {
-            if node.key == *key {
-                return idx as isize + 1;
-            }
-        }
-        -1
     }
 
     pub fn min(&self) -> Option<ZSetEntry<'_, K>> {
@@ -136,14 +325,19 @@ where
         self.map.contains_key(key)
     }
 
-    pub fn iter(&self) -> Vec<ZSetEntry<'_, K>> {
-        self.tree
-            .iter()
-            .map(|entry| ZSetEntry {
-                key: &entry.key,
-                score: entry.score,
-            })
-            .collect()
+    /// Lazily iterates all entries, lowest score first.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = ZSetEntry<'_, K>> + '_ {
+        self.tree.range_by(|_| false, |_| true).map(ZSetNode::entry)
+    }
+
+    /// Distinct scores in ascending order; each step is one O(log n) lookup.
+    fn scores(&self) -> impl Iterator<Item = f64> + '_ {
+        std::iter::successors(self.tree.first().map(|n| n.score), |&s| {
+            self.tree
+                .range_by(|n| n.score.total_cmp(&s).is_le(), |_| true)
+                .next()
+                .map(|n| n.score)
+        })
     }
 
     pub fn clear(&mut self) {
@@ -153,6 +347,89 @@ where
     }
 }
 
+impl<K> ZSet<K>
+where
+    K: Eq + Hash + Ord,
+{
+    /// Creates a set whose equal scores are ordered by member, like Redis.
+    pub fn new() -> Self {
+        Self::with_member_cmp(Some(K::cmp as fn(&K, &K) -> Ordering))
+    }
+
+    /// Entries whose key falls in `range`, ordered by score and then by key
+    /// (Redis `ZRANGEBYLEX`). Redis only defines this when all scores are
+    /// equal, in which case the result is plain lexicographic order.
+    ///
+    /// Each run of equal scores is a tree range, so this costs
+    /// O(r log n + m) for r distinct scores and m results, O(log n + m) in the
+    /// Redis case. Runs of a set built with
+    /// [`with_insertion_order`](Self::with_insertion_order) are not sorted by
+    /// key, so they are filtered and sorted instead.
+    pub fn range_by_lex<R: RangeBounds<K>>(&self, range: R) -> Vec<ZSetEntry<'_, K>> {
+        let mut out = Vec::new();
+        for score in self.scores() {
+            if self.member_cmp.is_some() {
+                let (before, within) = Self::lex_predicates(score, &range);
+                out.extend(self.tree.range_by(before, within).map(ZSetNode::entry));
+            } else {
+                let start = out.len();
+                out.extend(
+                    self.range_by_score(score..=score)
+                        .filter(|e| range.contains(e.key)),
+                );
+                out[start..].sort_by(|a, b| a.key.cmp(b.key));
+            }
+        }
+        out
+    }
+
+    /// Number of keys that fall in `range` (Redis `ZLEXCOUNT`), counted per
+    /// run of equal scores like [`range_by_lex`](Self::range_by_lex).
+    pub fn lex_count<R: RangeBounds<K>>(&self, range: R) -> usize {
+        if self.member_cmp.is_none() {
+            return self.map.keys().filter(|k| range.contains(k)).count();
+        }
+        self.scores()
+            .map(|score| {
+                let (before, within) = Self::lex_predicates(score, &range);
+                self.tree
+                    .count_while(within)
+                    .saturating_sub(self.tree.count_while(before))
+            })
+            .sum()
+    }
+
+    /// Tree-range predicates for the keys in `range` among the entries
+    /// scored `score`; only valid when ties are ordered by member.
+    fn lex_predicates<R: RangeBounds<K>>(
+        score: f64,
+        range: &R,
+    ) -> (
+        impl Fn(&ZSetNode<K>) -> bool + '_,
+        impl Fn(&ZSetNode<K>) -> bool + '_,
+    ) {
+        let before = move |n: &ZSetNode<K>| match n.score.total_cmp(&score) {
+            Ordering::Less => true,
+            Ordering::Greater => false,
+            Ordering::Equal => match range.start_bound() {
+                Bound::Included(s) => n.key < *s,
+                Bound::Excluded(s) => n.key <= *s,
+                Bound::Unbounded => false,
+            },
+        };
+        let within = move |n: &ZSetNode<K>| match n.score.total_cmp(&score) {
+            Ordering::Less => true,
+            Ordering::Greater => false,
+            Ordering::Equal => match range.end_bound() {
+                Bound::Included(e) => n.key <= *e,
+                Bound::Excluded(e) => n.key < *e,
+                Bound::Unbounded => true,
+            },
+        };
+        (before, within)
+    }
+}
+
 impl<K> ZSet<K>
 where
     K: Eq + Hash + Clone,
@@ -163,7 +440,7 @@ where
         }
         let order = self.next_order;
         self.next_order += 1;
-        self.tree.insert(ZSetNode::new(key.clone(), score, order));
+        self.tree.insert(self.node(key.clone(), score, order));
         self.map.insert(key, (score, order));
         true
     }
@@ -172,26 +449,42 @@ where
         let Some((old_score, old_order)) = self.map.get(key).copied() else {
             return false;
         };
-        let old_node = ZSetNode::new(key.clone(), old_score, old_order);
+        let old_node = self.node(key.clone(), old_score, old_order);
         self.tree.remove(&old_node);
         let order = self.next_order;
         self.next_order += 1;
-        self.tree.insert(ZSetNode::new(key.clone(), score, order));
+        self.tree.insert(self.node(key.clone(), score, order));
         self.map.insert(key.clone(), (score, order));
         true
     }
 
+    /// Adds `delta` to the score of `key`, inserting it with score `delta`
+    /// if absent, and returns the new score (Redis `ZINCRBY`).
+    pub fn incr_by(&mut self, key: K, delta: f64) -> f64 {
+        match self.score(&key) {
+            Some(old) => {
+                let score = old + delta;
+                self.update_score(&key, score);
+                score
+            }
+            None => {
+                self.add(key, delta);
+                delta
+            }
+        }
+    }
+
     pub fn delete(&mut self, key: &K) -> bool {
         let Some((score, order)) = self.map.remove(key) else {
             return false;
         };
-        self.tree.remove(&ZSetNode::new(key.clone(), score, order));
+        self.tree.remove(&self.node(key.clone(), score, order));
         true
     }
 
     pub fn remove_score(&mut self, key: &K) -> Option<f64> {
         let (score, order) = self.map.remove(key)?;
-        self.tree.remove(&ZSetNode::new(key.clone(), score, order));
+        self.tree.remove(&self.node(key.clone(), score, order));
         Some(score)
     }
 
@@ -200,7 +493,7 @@ where
         let key = node.key.clone();
         let score = node.score;
         let order = node.order;
-        let node_to_remove = ZSetNode::new(key.clone(), score, order);
+        let node_to_remove = self.node(key.clone(), score, order);
         if self.tree.remove(&node_to_remove) {
             self.map.remove(&key);
             Some((key, score))
@@ -214,7 +507,7 @@ where
         let key = node.key.clone();
         let score = node.score;
         let order = node.order;
-        let node_to_remove = ZSetNode::new(key.clone(), score, order);
+        let node_to_remove = self.node(key.clone(), score, order);
         if self.tree.remove(&node_to_remove) {
             self.map.remove(&key);
             Some((key, score))
@@ -224,7 +517,7 @@ where
     }
 
     pub fn intersect(&self, another: &ZSet<K>) -> ZSet<K> {
-        let mut result = ZSet::new();
+        let mut result = self.empty_like();
         for entry in self.tree.iter() {
             if another.contains(&entry.key) {
                 let _ = result.add(entry.key.clone(), entry.score);
@@ -234,7 +527,7 @@ where
     }
 
     pub fn union(&self, another: &ZSet<K>) -> ZSet<K> {
-        let mut result = ZSet::new();
+        let mut result = self.empty_like();
         for entry in self.tree.iter() {
             let _ = result.add(entry.key.clone(), entry.score);
         }
@@ -244,6 +537,70 @@ where
         result
     }
 
+    /// Union of `inputs`, each score multiplied by its input's weight and
+    /// combined with `aggregate` (Redis `ZUNIONSTORE ... WEIGHTS ... AGGREGATE`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rust_tools::cw::{Aggregate, ZSet};
+    ///
+    /// let mut a = ZSet::new();
+    /// a.add("x", 1.0);
+    /// a.add("y", 2.0);
+    /// let mut b = ZSet::new();
+    /// b.add("y", 10.0);
+    ///
+    /// let u = ZSet::union_with_weights(&[(&a, 2.0), (&b, 1.0)], Aggregate::Sum);
+    /// assert_eq!(u.score(&"x"), Some(2.0));
+    /// assert_eq!(u.score(&"y"), Some(14.0));
+    /// let m = ZSet::union_with_weights(&[(&a, 1.0), (&b, 1.0)], Aggregate::Min);
+    /// assert_eq!(m.score(&"y"), Some(2.0));
+    /// ```
+    pub fn union_with_weights(inputs: &[(&ZSet<K>, f64)], aggregate: Aggregate) -> ZSet<K> {
+        let mut scores: FastMap<&K, f64> = FastMap::default();
+        let mut order: Vec<&K> = Vec::new();
+        for (set, weight) in inputs {
+            for node in set.tree.iter() {
+                let score = weighted(node.score, *weight);
+                match scores.get_mut(&node.key) {
+                    Some(acc) => *acc = aggregate.apply(*acc, score),
+                    None => {
+                        scores.insert(&node.key, score);
+                        order.push(&node.key);
+                    }
+                }
+            }
+        }
+        let mut result = inputs
+            .first()
+            .map_or_else(ZSet::with_insertion_order, |(set, _)| set.empty_like());
+        for key in order {
+            result.add(key.clone(), scores[key]);
+        }
+        result
+    }
+
+    /// Intersection of `inputs` with per-input weights and `aggregate`
+    /// (Redis `ZINTERSTORE ... WEIGHTS ... AGGREGATE`).
+    pub fn intersect_with_weights(inputs: &[(&ZSet<K>, f64)], aggregate: Aggregate) -> ZSet<K> {
+        let Some(((first, first_weight), rest)) = inputs.split_first() else {
+            return ZSet::with_insertion_order();
+        };
+        let mut result = first.empty_like();
+        'keys: for node in first.tree.iter() {
+            let mut acc = weighted(node.score, *first_weight);
+            for (set, weight) in rest {
+                let Some(score) = set.score(&node.key) else {
+                    continue 'keys;
+                };
+                acc = aggregate.apply(acc, weighted(score, *weight));
+            }
+            result.add(node.key.clone(), acc);
+        }
+        result
+    }
+
     pub fn subtract(&mut self, another: &ZSet<K>) {
         for entry in another.tree.iter() {
             let _ = self.delete(&entry.key);
@@ -253,7 +610,7 @@ where
 
 impl<K> Default for ZSet<K>
 where
-    K: Eq + Hash,
+    K: Eq + Hash + Ord,
 {
     fn default() -> Self {
         Self::new()
@@ -262,7 +619,13 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::ZSet;
+    use super::{Aggregate, ZSet, ZSetEntry};
+
+    fn keys<'a>(
+        entries: impl IntoIterator<Item = ZSetEntry<'a, &'static str>>,
+    ) -> Vec<&'static str> {
+        entries.into_iter().map(|e| *e.key()).collect()
+    }
 
     #[test]
     fn test_zset_basic_and_rank() {
@@ -341,7 +704,7 @@ mod tests {
             }
         }
 
-        let mut z = ZSet::new();
+        let mut z = ZSet::with_insertion_order();
         let k1 = NotOrd { id: 1 };
         let k2 = NotOrd { id: 2 };
         let k3 = NotOrd { id: 3 };
@@ -358,4 +721,154 @@ mod tests {
         assert_eq!(min.0.id, 2);
         assert_eq!(min.1, 1.0);
     }
+
+    #[test]
+    fn test_zset_range_by_rank_negative_indices() {
+        let mut z = ZSet::new();
+        for (i, k) in ["a", "b", "c", "d", "e"].into_iter().enumerate() {
+            z.add(k, i as f64);
+        }
+
+        assert_eq!(keys(z.range_by_rank(0, -1)), ["a", "b", "c", "d", "e"]);
+        assert_eq!(keys(z.range_by_rank(1, 2)), ["b", "c"]);
+        assert_eq!(keys(z.range_by_rank(-2, -1)), ["d", "e"]);
+        assert_eq!(keys(z.range_by_rank(-100, 1)), ["a", "b"]);
+        assert_eq!(keys(z.range_by_rank(3, 100)), ["d", "e"]);
+        assert!(keys(z.range_by_rank(3, 1)).is_empty());
+        assert!(keys(z.range_by_rank(5, 10)).is_empty());
+        assert!(keys(z.range_by_rank(0, -6)).is_empty());
+        assert_eq!(
+            keys(z.range_by_rank(0, -1).rev()),
+            ["e", "d", "c", "b", "a"]
+        );
+
+        assert_eq!(keys(z.rev_range_by_rank(0, 1)), ["e", "d"]);
+        assert_eq!(keys(z.rev_range_by_rank(-2, -1)), ["b", "a"]);
+        assert!(keys(z.rev_range_by_rank(2, 1)).is_empty());
+
+        assert!(ZSet::<&str>::new().range_by_rank(0, -1).next().is_none());
+    }
+
+    #[test]
+    fn test_zset_score_ranges_and_count() {
+        use std::ops::Bound::{Excluded, Included, Unbounded};
+
+        let mut z = ZSet::new();
+        for (k, s) in [("a", 1.0), ("b", 2.0), ("c", 2.0), ("d", 3.0), ("e", 5.0)] {
+            z.add(k, s);
+        }
+        let keys = |r: (std::ops::Bound<f64>, std::ops::Bound<f64>)| -> Vec<&str> {
+            z.range_by_score(r).map(|e| *e.key()).collect()
+        };
+        assert_eq!(keys((Included(2.0), Included(3.0))), ["b", "c", "d"]);
+        assert_eq!(keys((Excluded(2.0), Included(5.0))), ["d", "e"]);
+        assert_eq!(keys((Unbounded, Excluded(2.0))), ["a"]);
+        assert!(keys((Included(4.0), Included(1.0))).is_empty());
+        assert!(keys((Excluded(2.0), Excluded(3.0))).is_empty());
+
+        assert_eq!(z.count(..), 5);
+        assert_eq!(z.count(2.0..=3.0), 3);
+        assert_eq!(z.count(2.0..3.0), 2);
+        assert_eq!(z.count(10.0..), 0);
+        assert_eq!(z.count(4.0..=1.0), 0);
+
+        let rev: Vec<_> = z.range_by_score(..).rev().map(|e| *e.key()).collect();
+        assert_eq!(rev, ["e", "d", "c", "b", "a"]);
+        let all: Vec<_> = z.iter().map(|e| (*e.key(), e.score())).collect();
+        assert_eq!(all[0], ("a", 1.0));
+        assert_eq!(all.len(), 5);
+    }
+
+    #[test]
+    fn test_zset_rank_rev_rank_incr_by() {
+        let mut z = ZSet::new();
+        z.add("a", 1.0);
+        z.add("b", 2.0);
+        z.add("c", 3.0);
+        assert_eq!(z.rev_rank(&"c"), 1);
+        assert_eq!(z.rev_rank(&"a"), 3);
+        assert_eq!(z.rev_rank(&"x"), -1);
+
+        assert_eq!(z.incr_by("a", 5.0), 6.0);
+        assert_eq!(z.rank(&"a"), 3);
+        assert_eq!(z.rev_rank(&"a"), 1);
+        assert_eq!(z.incr_by("new", -1.0), -1.0);
+        assert_eq!(z.rank(&"new"), 1);
+        assert_eq!(z.len(), 4);
+    }
+
+    #[test]
+    fn test_zset_range_by_lex() {
+        let mut z = ZSet::new();
+        for k in ["d", "a", "c", "e", "b", "g", "f"] {
+            z.add(k, 0.0);
+        }
+        assert_eq!(keys(z.range_by_lex(.."c")), ["a", "b"]);
+        assert_eq!(keys(z.range_by_lex(..="c")), ["a", "b", "c"]);
+        assert_eq!(keys(z.range_by_lex("aaa".."g")), ["b", "c", "d", "e", "f"]);
+        assert_eq!(z.lex_count("b"..="d"), 3);
+        assert_eq!(z.lex_count(..), 7);
+    }
+
+    #[test]
+    fn test_zset_ties_ordered_by_member() {
+        let mut z = ZSet::new();
+        for k in ["c", "a", "b"] {
+            z.add(k, 1.0);
+        }
+        z.add("d", 0.0);
+        z.add("e", 2.0);
+        assert_eq!(keys(z.iter()), ["d", "a", "b", "c", "e"]);
+        assert_eq!(z.rank(&"b"), 3);
+        assert_eq!(keys(z.range_by_rank(1, 2)), ["a", "b"]);
+        z.update_score(&"a", 1.0);
+        assert_eq!(keys(z.range_by_score(1.0..=1.0)), ["a", "b", "c"]);
+
+        // 每段同分的元素各取一个字典序区间
+        assert_eq!(keys(z.range_by_lex("b"..="d")), ["d", "b", "c"]);
+        assert_eq!(z.lex_count("b"..="d"), 3);
+        assert_eq!(z.lex_count("f"..), 0);
+
+        let mut by_insertion = ZSet::with_insertion_order();
+        for k in ["c", "a", "b"] {
+            by_insertion.add(k, 1.0);
+        }
+        assert_eq!(keys(by_insertion.iter()), ["c", "a", "b"]);
+        assert_eq!(keys(by_insertion.range_by_lex("b"..)), ["b", "c"]);
+        assert_eq!(
+            keys(by_insertion.union(&z).iter()),
+            ["d", "c", "a", "b", "e"]
+        );
+    }
+
+    #[test]
+    fn test_zset_weighted_union_intersect() {
+        let mut a = ZSet::new();
+        a.add("x", 1.0);
+        a.add("y", 2.0);
+        a.add("z", f64::INFINITY);
+        let mut b = ZSet::new();
+        b.add("y", 10.0);
+        b.add("z", f64::NEG_INFINITY);
+        b.add("w", 3.0);
+
+        let sum = ZSet::union_with_weights(&[(&a, 1.0), (&b, 2.0)], Aggregate::Sum);
+        assert_eq!(sum.len(), 4);
+        assert_eq!(sum.score(&"x"), Some(1.0));
+        assert_eq!(sum.score(&"y"), Some(22.0));
+        assert_eq!(sum.score(&"z"), Some(0.0));
+        assert_eq!(sum.score(&"w"), Some(6.0));
+
+        let max = ZSet::union_with_weights(&[(&a, 1.0), (&b, 1.0)], Aggregate::Max);
+        assert_eq!(max.score(&"y"), Some(10.0));
+
+        let inter = ZSet::intersect_with_weights(&[(&a, 1.0), (&b, 0.5)], Aggregate::Min);
+        assert_eq!(inter.len(), 2);
+        assert_eq!(inter.score(&"y"), Some(2.0));
+        assert_eq!(inter.score(&"z"), Some(f64::NEG_INFINITY));
+
+        let inter = ZSet::intersect_with_weights(&[(&a, 1.0), (&b, 1.0)], Aggregate::default());
+        assert_eq!(inter.score(&"y"), Some(12.0));
+        assert!(ZSet::<&str>::intersect_with_weights(&[], Aggregate::Sum).is_empty());
+    }
 }
@@ -0,0 +1,986 @@
+//! JSON5 / JSONC / YAML / TOML 与 JSON 的互相转换
+//!
+//! - JSON5：尾逗号、单引号字符串、不加引号的键、十六进制和 `.5` / `+1` 形式的数字
+//! - JSONC：带注释的 JSON，[`format_jsonc`] 重新排版时保留注释和成段的空行
+//! - YAML / TOML：转换为 [`Value`]，或由 [`Value`] 生成
+//!
+//! # 示例
+//!
+//! ```rust
+//! use rust_tools::jsonw::convert::{format_jsonc, parse_as, to_string_as, Format};
+//! use serde_json::json;
+//!
+//! let text = "{\n  // 端口\n  port: 8080, hosts: ['a',],\n}";
+//! assert_eq!(
+//!     format_jsonc(text, "  ").unwrap(),
+//!     "{\n  // 端口\n  \"port\": 8080,\n  \"hosts\": [\n    \"a\"\n  ]\n}\n"
+//! );
+//!
+//! let value = parse_as(text, Format::Json5).unwrap();
+//! assert_eq!(value, json!({"port": 8080, "hosts": ["a"]}));
+//! assert_eq!(to_string_as(&value, Format::Toml).unwrap(), "port = 8080\nhosts = [\"a\"]\n");
+//! ```
+
+use std::{fmt, path::Path, str::FromStr};
+
+use serde_json::{Map, Number, Value};
+
+use crate::jsonw::{
+    patch::escape_pointer_token, sanitize::sanitize_json_input, types::ParseOptions,
+};
+
+/// 支持的文本格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Json5,
+    Jsonc,
+    Yaml,
+    Toml,
+}
+
+impl Format {
+    /// 按扩展名判断格式，无法识别时视为 JSON
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        path.as_ref()
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| ext.to_ascii_lowercase().parse().ok())
+            .unwrap_or(Self::Json)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Json5 => "json5",
+            Self::Jsonc => "jsonc",
+            Self::Yaml => "yaml",
+            Self::Toml => "toml",
+        }
+    }
+
+    /// JSON、JSON5 和 JSONC 共用同一套词法，可以保留注释重新排版
+    pub fn is_json_family(self) -> bool {
+        matches!(self, Self::Json | Self::Json5 | Self::Jsonc)
+    }
+}
+
+impl FromStr for Format {
+    type Err = ConvertError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "json5" => Ok(Self::Json5),
+            "jsonc" => Ok(Self::Jsonc),
+            "yaml" | "yml" => Ok(Self::Yaml),
+            "toml" => Ok(Self::Toml),
+            _ => Err(ConvertError::new(format!("unknown format: {s}"))),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// 解析或生成失败的原因，`line` 为 0 表示没有位置信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConvertError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ConvertError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            line: 0,
+            column: 0,
+            message: message.into(),
+        }
+    }
+
+    fn at(text: &str, offset: usize, message: impl Into<String>) -> Self {
+        let mut offset = offset.min(text.len());
+        while !text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let before = &text[..offset];
+        let line_begin = before.rfind('\n').map_or(0, |i| i + 1);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_begin..].chars().count() + 1,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            f.write_str(&self.message)
+        } else {
+            write!(
+                f,
+                "{} at line {} column {}",
+                self.message, self.line, self.column
+            )
+        }
+    }
+}
+
+impl std::error::Error for ConvertError {}
+
+/// 按格式解析文本；JSON 与 [`crate::jsonw::Json::from_str`] 一样先清理注释和控制字符
+pub fn parse_as(text: &str, format: Format) -> Result<Value, ConvertError> {
+    match format {
+        Format::Json => {
+            let sanitized = sanitize_json_input(text, ParseOptions::default());
+            serde_json::from_str(&sanitized).map_err(|e| ConvertError {
+                line: e.line(),
+                column: e.column(),
+                message: strip_location(&e.to_string(), e.line(), e.column()),
+            })
+        }
+        Format::Json5 | Format::Jsonc => parse_json5(text),
+        Format::Yaml => parse_yaml(text),
+        Format::Toml => parse_toml(text),
+    }
+}
+
+/// 按格式生成文本；JSON 系列输出两个空格缩进的标准 JSON
+pub fn to_string_as(value: &Value, format: Format) -> Result<String, ConvertError> {
+    match format {
+        Format::Json | Format::Json5 | Format::Jsonc => {
+            let mut out = serde_json::to_string_pretty(value)
+                .map_err(|e| ConvertError::new(e.to_string()))?;
+            out.push('\n');
+            Ok(out)
+        }
+        Format::Yaml => to_yaml(value),
+        Format::Toml => to_toml(value),
+    }
+}
+
+fn strip_location(message: &str, line: usize, column: usize) -> String {
+    let suffix = format!(" at line {line} column {column}");
+    message.strip_suffix(&suffix).unwrap_or(message).to_string()
+}
+
+// ---------------------------------------------------------------------------
+// JSON5 / JSONC
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Open(u8),
+    Close(u8),
+    Colon,
+    Comma,
+    Str(String),
+    /// 不加引号的键
+    Ident(String),
+    /// 数字和字面量，`raw` 为原文，`value` 为规范化后的值
+    Scalar {
+        raw: String,
+        value: Value,
+    },
+    Comment(String),
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    offset: usize,
+    /// 与前一个 token 之间的换行数
+    newlines: usize,
+}
+
+struct Lexer<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.pos += ch.len_utf8();
+        Some(ch)
+    }
+
+    fn error(&self, offset: usize, message: impl Into<String>) -> ConvertError {
+        ConvertError::at(self.text, offset, message)
+    }
+
+    fn tokenize(mut self) -> Result<Vec<Token>, ConvertError> {
+        let mut tokens = Vec::new();
+        loop {
+            let mut newlines = 0;
+            while let Some(ch) = self.peek() {
+                match ch {
+                    '\n' => newlines += 1,
+                    // JSON5 允许的其他空白，含 BOM 和不换行空格
+                    ' ' | '\t' | '\r' | '\u{feff}' | '\u{a0}' | '\u{2028}' | '\u{2029}' => {}
+                    _ => break,
+                }
+                self.bump();
+            }
+            let offset = self.pos;
+            let Some(ch) = self.bump() else {
+                return Ok(tokens);
+            };
+            let kind = match ch {
+                '{' | '[' => TokenKind::Open(ch as u8),
+                '}' | ']' => TokenKind::Close(ch as u8),
+                ':' => TokenKind::Colon,
+                ',' => TokenKind::Comma,
+                '"' | '\'' => TokenKind::Str(self.string(ch, offset)?),
+                '/' => TokenKind::Comment(self.comment(offset)?),
+                _ if ch == '-' || ch == '+' || ch == '.' || ch.is_ascii_digit() => {
+                    self.number(offset)?
+                }
+                _ if ch == '$' || ch == '_' || ch.is_alphabetic() => self.identifier(offset)?,
+                _ => return Err(self.error(offset, format!("unexpected character '{ch}'"))),
+            };
+            tokens.push(Token {
+                kind,
+                offset,
+                newlines,
+            });
+        }
+    }
+
+    fn comment(&mut self, start: usize) -> Result<String, ConvertError> {
+        match self.bump() {
+            Some('/') => {
+                let end = self.text[self.pos..]
+                    .find('\n')
+                    .map_or(self.text.len(), |i| self.pos + i);
+                self.pos = end;
+                Ok(self.text[start..end].trim_end().to_string())
+            }
+            Some('*') => {
+                let end = self.text[self.pos..]
+                    .find("*/")
+                    .ok_or_else(|| self.error(start, "unterminated block comment"))?;
+                self.pos += end + 2;
+                Ok(self.text[start..self.pos].to_string())
+            }
+            _ => Err(self.error(start, "unexpected character '/'")),
+        }
+    }
+
+    fn string(&mut self, quote: char, start: usize) -> Result<String, ConvertError> {
+        let mut out = String::new();
+        loop {
+            let at = self.pos;
+            match self.bump() {
+                None | Some('\n') => return Err(self.error(start, "unterminated string")),
+                Some(ch) if ch == quote => return Ok(out),
+                Some('\\') => match self.bump() {
+                    None => return Err(self.error(start, "unterminated string")),
+                    Some('b') => out.push('\u{8}'),
+                    Some('f') => out.push('\u{c}'),
+                    Some('n') => out.push('\n'),
+                    Some('r') => out.push('\r'),
+                    Some('t') => out.push('\t'),
+                    Some('v') => out.push('\u{b}'),
+                    Some('0') if !self.peek().is_some_and(|c| c.is_ascii_digit()) => out.push('\0'),
+                    Some('x') => {
+                        let code = self.hex(2, at)?;
+                        out.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+                    }
+                    Some('u') => out.push(self.unicode_escape(at)?),
+                    // 行尾的反斜杠表示续行
+                    Some('\r') => {
+                        if self.peek() == Some('\n') {
+                            self.bump();
+                        }
+                    }
+                    Some('\n' | '\u{2028}' | '\u{2029}') => {}
+                    Some(ch) if ch.is_ascii_digit() => {
+                        return Err(self.error(at, "invalid escape"));
+                    }
+                    Some(ch) => out.push(ch),
+                },
+                Some(ch) => out.push(ch),
+            }
+        }
+    }
+
+    fn hex(&mut self, digits: usize, at: usize) -> Result<u32, ConvertError> {
+        let end = self.pos + digits;
+        let code = self
+            .text
+            .get(self.pos..end)
+            .and_then(|s| u32::from_str_radix(s, 16).ok())
+            .ok_or_else(|| self.error(at, "invalid escape"))?;
+        self.pos = end;
+        Ok(code)
+    }
+
+    fn unicode_escape(&mut self, at: usize) -> Result<char, ConvertError> {
+        let high = self.hex(4, at)?;
+        if (0xd800..0xdc00).contains(&high) && self.text[self.pos..].starts_with("\\u") {
+            let save = self.pos;
+            self.pos += 2;
+            let low = self.hex(4, at)?;
+            if (0xdc00..0xe000).contains(&low) {
+                let code = 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00);
+                return Ok(char::from_u32(code).unwrap_or('\u{fffd}'));
+            }
+            self.pos = save;
+        }
+        Ok(char::from_u32(high).unwrap_or('\u{fffd}'))
+    }
+
+    fn number(&mut self, start: usize) -> Result<TokenKind, ConvertError> {
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '+' | '-'))
+        {
+            // 指数部分之外的 +/- 属于下一个 token
+            let prev = self.text.as_bytes()[self.pos - 1];
+            if matches!(self.peek(), Some('+' | '-')) && !matches!(prev, b'e' | b'E') {
+                break;
+            }
+            self.bump();
+        }
+        let raw = &self.text[start..self.pos];
+        let value = json5_number(raw)
+            .ok_or_else(|| self.error(start, format!("invalid number '{raw}'")))?;
+        Ok(TokenKind::Scalar {
+            raw: raw.to_string(),
+            value,
+        })
+    }
+
+    fn identifier(&mut self, start: usize) -> Result<TokenKind, ConvertError> {
+        while self
+            .peek()
+            .is_some_and(|c| c == '$' || c == '_' || c.is_alphanumeric())
+        {
+            self.bump();
+        }
+        let raw = &self.text[start..self.pos];
+        Ok(match raw {
+            "true" => TokenKind::Scalar {
+                raw: raw.to_string(),
+                value: Value::Bool(true),
+            },
+            "false" => TokenKind::Scalar {
+                raw: raw.to_string(),
+                value: Value::Bool(false),
+            },
+            "null" => TokenKind::Scalar {
+                raw: raw.to_string(),
+                value: Value::Null,
+            },
+            "Infinity" | "NaN" => {
+                return Err(self.error(start, format!("{raw} cannot be represented in JSON")));
+            }
+            _ => TokenKind::Ident(raw.to_string()),
+        })
+    }
+}
+
+/// 解析 JSON5 数字；`Infinity` / `NaN` 无法用 JSON 表示，返回 `None`
+fn json5_number(raw: &str) -> Option<Value> {
+    let (negative, body) = match raw.as_bytes().first()? {
+        b'-' => (true, &raw[1..]),
+        b'+' => (false, &raw[1..]),
+        _ => (false, raw),
+    };
+    if let Some(hex) = body.strip_prefix("0x").or_else(|| body.strip_prefix("0X")) {
+        let n = i64::from_str_radix(hex, 16).ok()?;
+        return Some(Value::from(if negative { -n } else { n }));
+    }
+    if !body.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    // 补全 `.5`、`5.` 这类写法后交给 serde_json，保持整数/浮点的区分
+    let mut normalized = String::with_capacity(raw.len() + 2);
+    if negative {
+        normalized.push('-');
+    }
+    if body.starts_with('.') {
+        normalized.push('0');
+    }
+    let (mantissa, exponent) = match body.find(['e', 'E']) {
+        Some(i) => body.split_at(i),
+        None => (body, ""),
+    };
+    normalized.push_str(mantissa);
+    if mantissa.ends_with('.') {
+        normalized.push('0');
+    }
+    normalized.push_str(exponent);
+    serde_json::from_str::<Number>(&normalized)
+        .ok()
+        .map(Value::Number)
+}
+
+/// 在 token 序列上做递归下降，跳过注释
+struct Json5Parser<'a> {
+    text: &'a str,
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl<'a> Json5Parser<'a> {
+    fn next(&mut self) -> Option<&'a Token> {
+        let tokens = self.tokens;
+        while let Some(token) = tokens.get(self.pos) {
+            self.pos += 1;
+            if !matches!(token.kind, TokenKind::Comment(_)) {
+                return Some(token);
+            }
+        }
+        None
+    }
+
+    fn peek(&self) -> Option<&'a Token> {
+        let tokens = self.tokens;
+        tokens[self.pos..]
+            .iter()
+            .find(|t| !matches!(t.kind, TokenKind::Comment(_)))
+    }
+
+    fn error_at(&self, token: Option<&Token>, message: &str) -> ConvertError {
+        let offset = token.map_or(self.text.len(), |t| t.offset);
+        ConvertError::at(self.text, offset, message)
+    }
+
+    fn document(&mut self) -> Result<Value, ConvertError> {
+        let value = self.value()?;
+        match self.next() {
+            None => Ok(value),
+            t => Err(self.error_at(t, "trailing characters")),
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, ConvertError> {
+        let Some(token) = self.next() else {
+            return Err(self.error_at(None, "unexpected end of input"));
+        };
+        match &token.kind {
+            TokenKind::Open(b'{') => {
+                let mut map = Map::new();
+                loop {
+                    let t = self.next();
+                    let key = match t.map(|t| &t.kind) {
+                        Some(TokenKind::Close(b'}')) => return Ok(Value::Object(map)),
+                        Some(TokenKind::Str(key) | TokenKind::Ident(key)) => key.clone(),
+                        None => return Err(self.error_at(None, "unclosed object")),
+                        _ => return Err(self.error_at(t, "expected key")),
+                    };
+                    let t = self.next();
+                    if !matches!(t.map(|t| &t.kind), Some(TokenKind::Colon)) {
+                        return Err(self.error_at(t, "expected ':'"));
+                    }
+                    let value = self.value()?;
+                    map.insert(key, value);
+                    if !self.separator(b'}')? {
+                        return Ok(Value::Object(map));
+                    }
+                }
+            }
+            TokenKind::Open(_) => {
+                let mut items = Vec::new();
+                loop {
+                    if matches!(self.peek().map(|t| &t.kind), Some(TokenKind::Close(b']'))) {
+                        self.next();
+                        return Ok(Value::Array(items));
+                    }
+                    items.push(self.value()?);
+                    if !self.separator(b']')? {
+                        return Ok(Value::Array(items));
+                    }
+                }
+            }
+            TokenKind::Str(s) => Ok(Value::String(s.clone())),
+            TokenKind::Scalar { value, .. } => Ok(value.clone()),
+            TokenKind::Ident(ident) => {
+                Err(self.error_at(Some(token), &format!("unexpected identifier '{ident}'")))
+            }
+            _ => Err(self.error_at(Some(token), "expected value")),
+        }
+    }
+
+    /// 成员之后：逗号返回 `true` 继续，闭合符返回 `false`
+    fn separator(&mut self, close: u8) -> Result<bool, ConvertError> {
+        let t = self.next();
+        match t.map(|t| &t.kind) {
+            Some(TokenKind::Comma) => Ok(true),
+            Some(TokenKind::Close(c)) if *c == close => Ok(false),
+            _ => Err(self.error_at(
+                t,
+                if close == b'}' {
+                    "expected ',' or '}'"
+                } else {
+                    "expected ',' or ']'"
+                },
+            )),
+        }
+    }
+}
+
+/// 解析 JSON5（JSONC 是它的子集），注释被忽略
+pub fn parse_json5(text: &str) -> Result<Value, ConvertError> {
+    let tokens = Lexer { text, pos: 0 }.tokenize()?;
+    Json5Parser {
+        text,
+        tokens: &tokens,
+        pos: 0,
+    }
+    .document()
+}
+
+/// 保留注释重新排版 JSONC / JSON5，输出为带注释的标准 JSON
+///
+/// 与值在同一行的注释仍跟在该值之后，单独成行的注释按所在层级缩进；成员之间的空行
+/// 保留一行。单引号字符串、不加引号的键会改写为双引号，尾逗号被移除。
+pub fn format_jsonc(text: &str, indent: &str) -> Result<String, ConvertError> {
+    let tokens = Lexer { text, pos: 0 }.tokenize()?;
+    Json5Parser {
+        text,
+        tokens: &tokens,
+        pos: 0,
+    }
+    .document()?;
+
+    let mut w = JsoncWriter {
+        out: String::new(),
+        indent,
+        depth: 0,
+        line_start: 0,
+        pending_break: false,
+        trailing_comment: None,
+    };
+    let significant = |from: usize| {
+        tokens[from..]
+            .iter()
+            .find(|t| !matches!(t.kind, TokenKind::Comment(_)))
+            .map(|t| &t.kind)
+    };
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        match &token.kind {
+            TokenKind::Comment(comment) => w.comment(comment, token.newlines),
+            TokenKind::Open(open) => {
+                w.item(token.newlines);
+                if matches!(
+                    tokens.get(i + 1).map(|t| &t.kind),
+                    Some(TokenKind::Close(_))
+                ) {
+                    w.push(if *open == b'{' { "{}" } else { "[]" });
+                    i += 2;
+                    continue;
+                }
+                w.push(if *open == b'{' { "{" } else { "[" });
+                w.depth += 1;
+                w.pending_break = true;
+            }
+            TokenKind::Close(close) => {
+                w.depth -= 1;
+                w.newline(0);
+                w.push(if *close == b'}' { "}" } else { "]" });
+            }
+            TokenKind::Colon => w.push(": "),
+            // 尾逗号直接丢弃
+            TokenKind::Comma => {
+                if !matches!(significant(i + 1), Some(TokenKind::Close(_))) {
+                    w.comma();
+                }
+            }
+            TokenKind::Str(s) | TokenKind::Ident(s) => {
+                w.item(token.newlines);
+                w.push(&serde_json::to_string(s).unwrap_or_default());
+            }
+            TokenKind::Scalar { raw, value } => {
+                w.item(token.newlines);
+                // 标准 JSON 数字保留原写法，JSON5 扩展写法改为规范形式
+                if serde_json::from_str::<Value>(raw).is_ok() {
+                    w.push(raw);
+                } else {
+                    w.push(&value.to_string());
+                }
+            }
+        }
+        i += 1;
+    }
+    w.out.push('\n');
+    Ok(w.out)
+}
+
+struct JsoncWriter<'a> {
+    out: String,
+    indent: &'a str,
+    depth: usize,
+    /// 当前行在 `out` 中的起始位置
+    line_start: usize,
+    /// 下一个值需要另起一行
+    pending_break: bool,
+    /// 当前行行尾注释（含前导空格）的起始位置
+    trailing_comment: Option<usize>,
+}
+
+impl JsoncWriter<'_> {
+    fn push(&mut self, s: &str) {
+        self.trailing_comment = None;
+        self.out.push_str(s);
+    }
+
+    fn at_line_start(&self) -> bool {
+        self.out.len() == self.line_start
+    }
+
+    /// 换行并缩进；`newlines` 大于 1 且不紧跟在开括号之后时保留一个空行
+    fn newline(&mut self, newlines: usize) {
+        if self.out.is_empty() {
+            return;
+        }
+        if newlines > 1 && !self.out.ends_with(['{', '[']) {
+            self.out.push('\n');
+        }
+        self.out.push('\n');
+        self.line_start = self.out.len();
+        self.trailing_comment = None;
+        self.out.push_str(&self.indent.repeat(self.depth));
+        self.line_start = self.out.len();
+        self.pending_break = false;
+    }
+
+    fn item(&mut self, newlines: usize) {
+        if self.pending_break {
+            self.newline(newlines);
+        }
+    }
+
+    fn comma(&mut self) {
+        // 逗号插到行尾注释之前
+        match self.trailing_comment {
+            Some(at) => self.out.insert(at, ','),
+            None => self.out.push(','),
+        }
+        self.pending_break = true;
+    }
+
+    fn comment(&mut self, comment: &str, newlines: usize) {
+        if newlines == 0 && !self.out.is_empty() && !self.at_line_start() {
+            if !self.out.ends_with(' ') {
+                self.trailing_comment = Some(self.out.len());
+                self.out.push(' ');
+            } else {
+                self.trailing_comment = Some(self.out.len() - 1);
+            }
+        } else {
+            if !self.out.is_empty() {
+                self.newline(newlines);
+            }
+            self.trailing_comment = None;
+        }
+        let mut lines = comment.lines();
+        if let Some(first) = lines.next() {
+            self.out.push_str(first);
+        }
+        for line in lines {
+            self.out.push('\n');
+            self.out.push_str(line.trim_end());
+        }
+        if comment.starts_with("//") || comment.contains('\n') {
+            self.pending_break = true;
+        } else if self.trailing_comment.is_none() {
+            // 单独成行的块注释之后另起一行
+            self.pending_break = true;
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// YAML / TOML
+// ---------------------------------------------------------------------------
+
+/// 解析 YAML，支持锚点、别名和 `<<` 合并键；非字符串的键转为其 YAML 文本
+pub fn parse_yaml(text: &str) -> Result<Value, ConvertError> {
+    let mut yaml: serde_yaml_ng::Value = serde_yaml_ng::from_str(text).map_err(|e| {
+        let message = e.to_string();
+        match e.location() {
+            Some(loc) => ConvertError {
+                line: loc.line(),
+                column: loc.column(),
+                message: strip_location(&message, loc.line(), loc.column()),
+            },
+            None => ConvertError::new(message),
+        }
+    })?;
+    yaml.apply_merge()
+        .map_err(|e| ConvertError::new(e.to_string()))?;
+    yaml_to_json(yaml)
+}
+
+fn yaml_to_json(yaml: serde_yaml_ng::Value) -> Result<Value, ConvertError> {
+    use serde_yaml_ng::Value as Yaml;
+    Ok(match yaml {
+        Yaml::Null => Value::Null,
+        Yaml::Bool(b) => Value::Bool(b),
+        Yaml::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                Value::from(i)
+            } else if let Some(u) = n.as_u64() {
+                Value::from(u)
+            } else {
+                let f = n.as_f64().unwrap_or(f64::NAN);
+                Number::from_f64(f).map(Value::Number).ok_or_else(|| {
+                    ConvertError::new(format!("{n} cannot be represented in JSON"))
+                })?
+            }
+        }
+        Yaml::String(s) => Value::String(s),
+        Yaml::Sequence(items) => Value::Array(
+            items
+                .into_iter()
+                .map(yaml_to_json)
+                .collect::<Result<_, _>>()?,
+        ),
+        Yaml::Mapping(mapping) => {
+            let mut map = Map::new();
+            for (k, v) in mapping {
+                let key = match k {
+                    Yaml::String(s) => s,
+                    other => serde_yaml_ng::to_string(&other)
+                        .map_err(|e| ConvertError::new(e.to_string()))?
+                        .trim_end()
+                        .to_string(),
+                };
+                map.insert(key, yaml_to_json(v)?);
+            }
+            Value::Object(map)
+        }
+        Yaml::Tagged(tagged) => yaml_to_json(tagged.value)?,
+    })
+}
+
+pub fn to_yaml(value: &Value) -> Result<String, ConvertError> {
+    serde_yaml_ng::to_string(value).map_err(|e| ConvertError::new(e.to_string()))
+}
+
+/// 解析 TOML，日期时间转为 RFC 3339 字符串
+pub fn parse_toml(text: &str) -> Result<Value, ConvertError> {
+    let table: toml::Table = text.parse().map_err(|e: toml::de::Error| {
+        let message = e.message().to_string();
+        match e.span() {
+            Some(span) => ConvertError::at(text, span.start, message),
+            None => ConvertError::new(message),
+        }
+    })?;
+    toml_to_json(toml::Value::Table(table))
+}
+
+fn toml_to_json(value: toml::Value) -> Result<Value, ConvertError> {
+    Ok(match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        toml::Value::Float(f) => Number::from_f64(f)
+            .map(Value::Number)
+            .ok_or_else(|| ConvertError::new(format!("{f} cannot be represented in JSON")))?,
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(d) => Value::String(d.to_string()),
+        toml::Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(toml_to_json)
+                .collect::<Result<_, _>>()?,
+        ),
+        toml::Value::Table(table) => Value::Object(
+            table
+                .into_iter()
+                .map(|(k, v)| Ok((k, toml_to_json(v)?)))
+                .collect::<Result<_, ConvertError>>()?,
+        ),
+    })
+}
+
+/// 生成 TOML；根必须是对象，TOML 没有 `null`，遇到时报告其 JSON Pointer
+pub fn to_toml(value: &Value) -> Result<String, ConvertError> {
+    let toml::Value::Table(table) = json_to_toml(value, "")? else {
+        return Err(ConvertError::new("TOML document must be an object"));
+    };
+    toml::to_string(&table).map_err(|e| ConvertError::new(e.to_string()))
+}
+
+fn json_to_toml(value: &Value, pointer: &str) -> Result<toml::Value, ConvertError> {
+    Ok(match value {
+        Value::Null => {
+            let at = if pointer.is_empty() { "/" } else { pointer };
+            return Err(ConvertError::new(format!(
+                "null at {at} cannot be represented in TOML"
+            )));
+        }
+        Value::Bool(b) => toml::Value::Boolean(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => toml::Value::Integer(i),
+            None => toml::Value::Float(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => toml::Value::String(s.clone()),
+        Value::Array(items) => toml::Value::Array(
+            items
+                .iter()
+                .enumerate()
+                .map(|(i, v)| json_to_toml(v, &format!("{pointer}/{i}")))
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Object(map) => toml::Value::Table(
+            map.iter()
+                .map(|(k, v)| {
+                    let token = escape_pointer_token(k);
+                    Ok((k.clone(), json_to_toml(v, &format!("{pointer}/{token}"))?))
+                })
+                .collect::<Result<_, ConvertError>>()?,
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_json5_syntax() {
+        let text = r#"
+            // 顶层注释
+            {
+              unquoted: 'single "quoted"',
+              $id_2: "esc\x41中\
+continued",
+              nums: [0x1F, -0x10, .5, 5., +1, 1e3, -2.5E-1,],
+              /* 块注释 */ nested: {a: [], b: {},},
+              lit: [true, false, null],
+            }
+        "#;
+        assert_eq!(
+            parse_json5(text).unwrap(),
+            json!({
+                "unquoted": "single \"quoted\"",
+                "$id_2": "escA中continued",
+                "nums": [31, -16, 0.5, 5.0, 1, 1000.0, -0.25],
+                "nested": {"a": [], "b": {}},
+                "lit": [true, false, null]
+            })
+        );
+        assert!(parse_json5("[1]").unwrap().is_array());
+    }
+
+    #[test]
+    fn test_json5_errors() {
+        let err = parse_json5("{\n  a: 1\n  b: 2\n}").unwrap_err();
+        assert_eq!(
+            (err.line, err.column, err.message.as_str()),
+            (3, 3, "expected ',' or '}'")
+        );
+        let err = parse_json5("[1, Infinity]").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 5));
+        assert!(parse_json5("{'a': 1} x").is_err());
+        assert!(parse_json5("[1,,2]").is_err());
+        assert!(parse_json5("/* open").is_err());
+        assert!(parse_json5("").is_err());
+        assert_eq!(
+            parse_json5("{a: b}").unwrap_err().message,
+            "unexpected identifier 'b'"
+        );
+    }
+
+    #[test]
+    fn test_format_jsonc_keeps_comments() {
+        let text = "// header\n{\"a\":1,   // one\n\n\n  /* own line */\n  'b': [1,2 /* two */,],\n  c: {}, d: { // empty-ish\n  },\n  \"e\": \"x\" // last\n}\n// footer\n";
+        let expected = r#"// header
+{
+  "a": 1, // one
+
+  /* own line */
+  "b": [
+    1,
+    2 /* two */
+  ],
+  "c": {},
+  "d": { // empty-ish
+  },
+  "e": "x" // last
+}
+// footer
+"#;
+        let formatted = format_jsonc(text, "  ").unwrap();
+        assert_eq!(formatted, expected);
+        // 排版结果仍可解析，且再次排版不变
+        assert_eq!(parse_json5(&formatted).unwrap(), parse_json5(text).unwrap());
+        assert_eq!(format_jsonc(&formatted, "  ").unwrap(), formatted);
+    }
+
+    #[test]
+    fn test_format_plain_json_matches_pretty_output() {
+        let value = json!({"a": [1, {"b": null}], "c": "中\n", "d": 1.5e300, "e": {}});
+        let compact = value.to_string();
+        assert_eq!(
+            format_jsonc(&compact, "  ").unwrap(),
+            to_string_as(&value, Format::Json).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_yaml_round_trip() {
+        let text = "base: &base\n  retries: 3\n  hosts: [a, b]\nprod:\n  <<: *base\n  debug: false\n  1: one\n  note: |\n    line1\n    line2\n";
+        let value = parse_yaml(text).unwrap();
+        assert_eq!(
+            value["prod"],
+            json!({"retries": 3, "hosts": ["a", "b"], "debug": false, "1": "one", "note": "line1\nline2\n"})
+        );
+        let yaml = to_yaml(&value).unwrap();
+        assert_eq!(parse_yaml(&yaml).unwrap(), value);
+
+        let err = parse_yaml("a: [1, 2\nb: 3").unwrap_err();
+        assert!(err.line > 0, "{err}");
+    }
+
+    #[test]
+    fn test_toml_round_trip() {
+        let text = "title = \"cfg\"\nwhen = 1979-05-27T07:32:00Z\n\n[server]\nport = 8080\nratio = 0.5\n\n[[users]]\nname = \"a\"\n\n[[users]]\nname = \"b\"\n";
+        let value = parse_toml(text).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "title": "cfg",
+                "when": "1979-05-27T07:32:00Z",
+                "server": {"port": 8080, "ratio": 0.5},
+                "users": [{"name": "a"}, {"name": "b"}]
+            })
+        );
+        assert_eq!(parse_toml(&to_toml(&value).unwrap()).unwrap(), value);
+
+        let err = parse_toml("a = 1\nb = \n").unwrap_err();
+        assert_eq!(err.line, 2);
+        let err = to_toml(&json!({"a": {"b/c": [1, null]}})).unwrap_err();
+        assert_eq!(
+            err.message,
+            "null at /a/b~1c/1 cannot be represented in TOML"
+        );
+        assert!(to_toml(&json!([1])).is_err());
+    }
+
+    #[test]
+    fn test_format_detection() {
+        assert_eq!(Format::from_path("a/b.YML"), Format::Yaml);
+        assert_eq!(Format::from_path("tsconfig.jsonc"), Format::Jsonc);
+        assert_eq!(Format::from_path("Cargo.toml"), Format::Toml);
+        assert_eq!(Format::from_path("noext"), Format::Json);
+        assert!("xml".parse::<Format>().is_err());
+    }
+}
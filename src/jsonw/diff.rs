@@ -1,95 +1,262 @@
+use std::collections::{HashMap, HashSet};
+
 use serde_json::Value;
 
 use crate::jsonw::sort;
 use crate::jsonw::types::DiffEntry;
 
 pub fn diff_json(old: &Value, new: &Value, sort_arrays: bool) -> Vec<DiffEntry> {
-    let mut out = Vec::new();
-    collect_diff_entries("", old, new, sort_arrays, &mut out);
-    out
+    diff_json_by_key(old, new, sort_arrays, &[])
+}
+
+/// 数组元素的身份路径
+///
+/// - `id`：所有对象数组都按元素的 `id` 字段配对
+/// - `items[].sku`：只有路径为 `items` 的数组按 `sku` 配对，优先于不带数组路径的规则
+/// - 字段可以是嵌套路径，如 `meta.id`；数组路径中的 `[]` 表示经过一层数组，如 `orders[].lines[].sku`
+///
+/// # 示例
+///
+/// ```rust
+/// use rust_tools::jsonw::ArrayKey;
+///
+/// assert!(ArrayKey::parse("items[].sku").is_some());
+/// assert!(ArrayKey::parse("items[].").is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArrayKey {
+    array: Option<String>,
+    field: Vec<String>,
 }
 
-fn collect_diff_entries(
-    path: &str,
+impl ArrayKey {
+    /// 解析身份路径，字段部分为空时返回 `None`
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (array, field) = match spec.rsplit_once("[].") {
+            Some((array, field)) => (Some(array.to_string()), field),
+            None => (None, spec),
+        };
+        let field: Vec<String> = field
+            .split('.')
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect();
+        (!field.is_empty()).then_some(Self { array, field })
+    }
+
+    fn identity<'v>(&self, v: &'v Value) -> Option<&'v Value> {
+        let id = self
+            .field
+            .iter()
+            .try_fold(v, |curr, k| curr.as_object()?.get(k))?;
+        (!id.is_null()).then_some(id)
+    }
+}
+
+/// 比较两个 JSON 值，数组中的对象按 `keys` 指定的身份配对
+///
+/// 配对后的路径形如 `items[sku="A1"].price`：元素只在一侧存在时输出整条新增/删除，
+/// 两侧都存在时继续比较字段，仅顺序变化不产生差异。某个数组中存在缺少身份字段或身份
+/// 重复的元素时，该数组退回按下标比较。
+///
+/// # 示例
+///
+/// ```rust
+/// use rust_tools::jsonw::{diff_json_by_key, ArrayKey, DiffKind};
+/// use serde_json::json;
+///
+/// let old = json!({"items": [{"sku": "A", "n": 1}, {"sku": "B", "n": 2}]});
+/// let new = json!({"items": [{"sku": "C", "n": 3}, {"sku": "B", "n": 5}]});
+///
+/// let keys = [ArrayKey::parse("items[].sku").unwrap()];
+/// let diff = diff_json_by_key(&old, &new, false, &keys);
+/// let summary: Vec<_> = diff.iter().map(|d| (d.key.as_str(), d.kind())).collect();
+/// assert_eq!(
+///     summary,
+///     vec![
+///         (r#"items[sku="A"]"#, DiffKind::Removed),
+///         (r#"items[sku="B"].n"#, DiffKind::Modified),
+///         (r#"items[sku="C"]"#, DiffKind::Added),
+///     ]
+/// );
+/// ```
+pub fn diff_json_by_key(
     old: &Value,
     new: &Value,
     sort_arrays: bool,
+    keys: &[ArrayKey],
+) -> Vec<DiffEntry> {
+    let ctx = DiffContext { sort_arrays, keys };
+    let mut out = Vec::new();
+    ctx.collect("", "", old, new, &mut out);
+    out
+}
+
+struct DiffContext<'a> {
+    sort_arrays: bool,
+    keys: &'a [ArrayKey],
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+fn push_or_collect(
+    ctx: &DiffContext<'_>,
+    path: String,
+    shape: &str,
+    v1: &Value,
+    v2: &Value,
     out: &mut Vec<DiffEntry>,
 ) {
-    if old.is_null() && new.is_null() {
+    if v1.is_null() ^ v2.is_null() {
+        out.push(DiffEntry {
+            key: path,
+            old: v1.clone(),
+            new: v2.clone(),
+        });
         return;
     }
+    ctx.collect(&path, shape, v1, v2, out);
+}
 
-    match (old, new) {
-        (Value::Object(o1), Value::Object(o2)) => {
-            let mut keys: Vec<&str> = o1.keys().chain(o2.keys()).map(|k| k.as_str()).collect();
-            keys.sort_unstable();
-            keys.dedup();
-
-            for k in keys {
-                let p = if path.is_empty() {
-                    k.to_string()
-                } else {
-                    format!("{path}.{k}")
-                };
-                let v1 = o1.get(k).unwrap_or(&Value::Null);
-                let v2 = o2.get(k).unwrap_or(&Value::Null);
-                if v1.is_null() ^ v2.is_null() {
-                    out.push(DiffEntry {
-                        key: p,
-                        old: v1.clone(),
-                        new: v2.clone(),
-                    });
-                    continue;
-                }
-                collect_diff_entries(&p, v1, v2, sort_arrays, out);
-            }
+impl DiffContext<'_> {
+    /// `shape` 是忽略具体下标的路径（数组记为 `[]`），用于匹配 [`ArrayKey`]
+    fn key_for(&self, shape: &str) -> Option<&ArrayKey> {
+        self.keys
+            .iter()
+            .find(|k| k.array.as_deref() == Some(shape))
+            .or_else(|| self.keys.iter().find(|k| k.array.is_none()))
+    }
+
+    fn collect(&self, path: &str, shape: &str, old: &Value, new: &Value, out: &mut Vec<DiffEntry>) {
+        if old.is_null() && new.is_null() {
+            return;
         }
-        (Value::Array(a1), Value::Array(a2)) => {
-            let mut left = a1.clone();
-            let mut right = a2.clone();
-            if sort_arrays {
-                left.sort_by_key(sort::json_scalar_string_key);
-                right.sort_by_key(sort::json_scalar_string_key);
+
+        match (old, new) {
+            (Value::Object(o1), Value::Object(o2)) => {
+                let mut keys: Vec<&str> = o1.keys().chain(o2.keys()).map(|k| k.as_str()).collect();
+                keys.sort_unstable();
+                keys.dedup();
+
+                for k in keys {
+                    let v1 = o1.get(k).unwrap_or(&Value::Null);
+                    let v2 = o2.get(k).unwrap_or(&Value::Null);
+                    push_or_collect(self, join_path(path, k), &join_path(shape, k), v1, v2, out);
+                }
             }
+            (Value::Array(a1), Value::Array(a2)) => {
+                let elem_shape = format!("{shape}[]");
+                if let Some(key) = self.key_for(shape)
+                    && self.collect_keyed(path, &elem_shape, key, a1, a2, out)
+                {
+                    return;
+                }
+
+                let mut left = a1.clone();
+                let mut right = a2.clone();
+                if self.sort_arrays {
+                    left.sort_by_key(sort::json_scalar_string_key);
+                    right.sort_by_key(sort::json_scalar_string_key);
+                }
 
-            let max_len = left.len().max(right.len());
-            for i in 0..max_len {
-                let p = if path.is_empty() {
-                    format!("{i}")
-                } else {
-                    format!("{path}.{i}")
-                };
-                let v1 = left.get(i).unwrap_or(&Value::Null);
-                let v2 = right.get(i).unwrap_or(&Value::Null);
-                if v1.is_null() ^ v2.is_null() {
+                let max_len = left.len().max(right.len());
+                for i in 0..max_len {
+                    let v1 = left.get(i).unwrap_or(&Value::Null);
+                    let v2 = right.get(i).unwrap_or(&Value::Null);
+                    push_or_collect(
+                        self,
+                        join_path(path, &i.to_string()),
+                        &elem_shape,
+                        v1,
+                        v2,
+                        out,
+                    );
+                }
+            }
+            _ => {
+                if old != new {
                     out.push(DiffEntry {
-                        key: p,
-                        old: v1.clone(),
-                        new: v2.clone(),
+                        key: path.to_string(),
+                        old: old.clone(),
+                        new: new.clone(),
                     });
-                    continue;
                 }
-                collect_diff_entries(&p, v1, v2, sort_arrays, out);
             }
         }
-        _ => {
-            if old != new {
+    }
+
+    /// 按身份配对比较两个数组；身份缺失或重复时返回 `false`，由调用方按下标比较
+    fn collect_keyed(
+        &self,
+        path: &str,
+        elem_shape: &str,
+        key: &ArrayKey,
+        a1: &[Value],
+        a2: &[Value],
+        out: &mut Vec<DiffEntry>,
+    ) -> bool {
+        let (Some(ids1), Some(ids2)) = (index_by_identity(key, a1), index_by_identity(key, a2))
+        else {
+            return false;
+        };
+        let lookup: HashMap<&str, usize> = ids2
+            .iter()
+            .enumerate()
+            .map(|(j, id)| (id.as_str(), j))
+            .collect();
+        let field = key.field.join(".");
+        let elem_path = |id: &str| format!("{path}[{field}={id}]");
+
+        for (i, id) in ids1.iter().enumerate() {
+            match lookup.get(id.as_str()) {
+                Some(&j) => self.collect(&elem_path(id), elem_shape, &a1[i], &a2[j], out),
+                None => out.push(DiffEntry {
+                    key: elem_path(id),
+                    old: a1[i].clone(),
+                    new: Value::Null,
+                }),
+            }
+        }
+        let known: HashSet<&str> = ids1.iter().map(String::as_str).collect();
+        for (j, id) in ids2.iter().enumerate() {
+            if !known.contains(id.as_str()) {
                 out.push(DiffEntry {
-                    key: path.to_string(),
-                    old: old.clone(),
-                    new: new.clone(),
+                    key: elem_path(id),
+                    old: Value::Null,
+                    new: a2[j].clone(),
                 });
             }
         }
+        true
     }
 }
 
+/// 每个元素身份的 JSON 文本；有元素缺少身份或身份重复时返回 `None`
+fn index_by_identity(key: &ArrayKey, items: &[Value]) -> Option<Vec<String>> {
+    let mut seen = HashSet::new();
+    let mut ids = Vec::with_capacity(items.len());
+    for item in items {
+        let id = serde_json::to_string(key.identity(item)?).ok()?;
+        if !seen.insert(id.clone()) {
+            return None;
+        }
+        ids.push(id);
+    }
+    Some(ids)
+}
+
 #[cfg(test)]
 mod tests {
-    use serde_json::Value;
+    use serde_json::{Value, json};
 
     use super::*;
+    use crate::jsonw::types::DiffKind;
 
     #[test]
     fn test_diff_basic() {
@@ -99,4 +266,87 @@ mod tests {
         assert!(diff.iter().any(|d| d.key == "a"));
         assert!(diff.iter().any(|d| d.key == "b.1"));
     }
+
+    fn summary(diff: &[DiffEntry]) -> Vec<(String, DiffKind)> {
+        diff.iter().map(|d| (d.key.clone(), d.kind())).collect()
+    }
+
+    #[test]
+    fn test_keyed_insert_and_reorder() {
+        let old = json!([{"id": 1, "v": "a"}, {"id": 2, "v": "b"}, {"id": 3, "v": "c"}]);
+        let new = json!([{"id": 0, "v": "z"}, {"id": 3, "v": "c"}, {"id": 1, "v": "a"}, {"id": 2, "v": "B"}]);
+        let keys = [ArrayKey::parse("id").unwrap()];
+
+        assert_eq!(
+            summary(&diff_json_by_key(&old, &new, false, &keys)),
+            vec![
+                ("[id=2].v".to_string(), DiffKind::Modified),
+                ("[id=0]".to_string(), DiffKind::Added),
+            ]
+        );
+        // 不指定身份时每个下标都算变化
+        assert_eq!(diff_json(&old, &new, false).len(), 7);
+    }
+
+    #[test]
+    fn test_keyed_by_array_path_and_nested_field() {
+        let old = json!({
+            "orders": [{"no": 1, "lines": [{"p": {"sku": "x"}, "q": 1}, {"p": {"sku": "y"}, "q": 1}]}],
+            "tags": [{"id": 1}, {"id": 2}]
+        });
+        let new = json!({
+            "orders": [{"no": 1, "lines": [{"p": {"sku": "y"}, "q": 2}]}],
+            "tags": [{"id": 2}, {"id": 1}]
+        });
+        let keys = [
+            ArrayKey::parse("orders[].no").unwrap(),
+            ArrayKey::parse("orders[].lines[].p.sku").unwrap(),
+        ];
+        assert_eq!(
+            summary(&diff_json_by_key(&old, &new, false, &keys)),
+            vec![
+                (
+                    r#"orders[no=1].lines[p.sku="x"]"#.to_string(),
+                    DiffKind::Removed
+                ),
+                (
+                    r#"orders[no=1].lines[p.sku="y"].q"#.to_string(),
+                    DiffKind::Modified
+                ),
+                // tags 没有对应规则，按下标比较
+                ("tags.0.id".to_string(), DiffKind::Modified),
+                ("tags.1.id".to_string(), DiffKind::Modified),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_keyed_falls_back_on_missing_or_duplicate_ids() {
+        let keys = [ArrayKey::parse("id").unwrap()];
+        let old = json!([{"id": 1}, {"id": 1}]);
+        let new = json!([{"id": 1}, {"id": 2}]);
+        assert_eq!(
+            summary(&diff_json_by_key(&old, &new, false, &keys)),
+            vec![("1.id".to_string(), DiffKind::Modified)]
+        );
+        let old = json!([1, 2]);
+        let new = json!([2, 3]);
+        assert_eq!(diff_json_by_key(&old, &new, true, &keys).len(), 2);
+    }
+
+    #[test]
+    fn test_array_key_parse() {
+        assert_eq!(
+            ArrayKey::parse("a[].b[].c.d"),
+            Some(ArrayKey {
+                array: Some("a[].b".to_string()),
+                field: vec!["c".to_string(), "d".to_string()],
+            })
+        );
+        assert_eq!(
+            ArrayKey::parse("[].id").and_then(|k| k.array),
+            Some(String::new())
+        );
+        assert_eq!(ArrayKey::parse(""), None);
+    }
 }
@@ -4,7 +4,10 @@ use serde::Serialize;
 use serde_json::Value;
 
 use crate::{
-    clipboardw::string_content, jsonw::sanitize::sanitize_json_input, jsonw::sort,
+    clipboardw::string_content,
+    jsonw::query::{Query, QueryError},
+    jsonw::sanitize::sanitize_json_input,
+    jsonw::sort,
     jsonw::types::ParseOptions,
 };
 
@@ -274,6 +277,11 @@ impl Json {
         extract_by_dot_path(self, key)
     }
 
+    /// 执行 JSONPath / jq 风格查询，语法见 [`crate::jsonw::query`]
+    pub fn query(&self, expr: &str) -> Result<Vec<Value>, QueryError> {
+        Ok(Query::parse(expr)?.evaluate(&self.value))
+    }
+
     pub fn raw_data(&self) -> Value {
         self.value.clone()
     }
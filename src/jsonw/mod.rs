@@ -5,7 +5,15 @@
 //! ## 功能概览
 //!
 //! - [`diff_json`] - 比较两个 JSON 值的差异
+//! - [`diff_json_by_key`] - 按 [`ArrayKey`] 指定的身份配对数组元素后再比较
+//! - [`diff_patch`] / [`apply_patch`] - RFC 6902 JSON Patch 的生成与应用（数组按 LCS 对齐并识别移动）
+//! - [`diff_merge_patch`] / [`apply_merge_patch`] - RFC 7386 JSON Merge Patch 的生成与应用
+//! - [`infer_schema`] / [`SchemaValidator`] - 从样本推断 JSON Schema，校验违规定位到原文行列号
+//! - [`convert`] - JSON5 / JSONC / YAML / TOML 转换，JSONC 重新排版时保留注释
+//! - [`query()`] / [`Query`] - JSONPath / jq 风格查询（递归下降、通配符、切片、过滤、投影）
 //! - [`sanitize_json_input`] - 清理和规范化 JSON 输入
+//! - [`stream`] - 流式事件解析、格式化、校验和搜索，内存占用与文件大小无关
+//! - [`ndjson`] - NDJSON 逐行解析、过滤和比较
 //! - [`Json`] - JSON 值包装器类型
 //! - [`DiffEntry`] - JSON 差异条目类型
 //! - [`ParseOptions`] - JSON 解析选项
@@ -27,6 +35,18 @@
 //! }
 //! ```
 //!
+//! ### 查询
+//!
+//! ```rust
+//! use rust_tools::jsonw::{Json, ParseOptions};
+//! use serde_json::json;
+//!
+//! let j = Json::from_str(r#"{"tasks": [{"id": 1, "status": "failed"}, {"id": 2, "status": "ok"}]}"#,
+//!     ParseOptions::default()).unwrap();
+//! let ids = j.query(r#"$.tasks[?(@.status == "failed")].id"#).unwrap();
+//! assert_eq!(ids, vec![json!(1)]);
+//! ```
+//!
 //! ### 清理 JSON 输入
 //!
 //! ```rust
@@ -47,16 +67,33 @@
 //! ## 类型
 //!
 //! - [`DiffEntry`] - 表示 JSON 差异的条目
+//! - [`DiffKind`] - 差异条目的类型（新增/删除/修改）
+//! - [`ArrayKey`] - 数组元素的身份路径
 //! - [`Json`] - JSON 值包装器
 //! - [`ParseOptions`] - 控制 JSON 解析行为的选项
+//! - [`PatchOp`] - RFC 6902 补丁操作
+//! - [`PatchError`] - 应用补丁失败的原因
+//! - [`QueryError`] - 查询表达式的语法错误
+//! - [`SchemaViolation`] - 一条 schema 校验违规
 
+pub mod convert;
 pub mod diff;
 pub mod json;
+pub mod ndjson;
+pub mod patch;
+pub mod query;
 pub mod sanitize;
+pub mod schema;
 pub mod sort;
+pub mod stream;
 pub mod types;
 
 // 重新导出常用类型和函数
-pub use diff::diff_json;
+pub use diff::{ArrayKey, diff_json, diff_json_by_key};
+pub use patch::{
+    PatchError, apply_merge_patch, apply_patch, diff_merge_patch, diff_patch, escape_pointer_token,
+};
+pub use query::{Query, QueryError, query};
 pub use sanitize::sanitize_json_input;
-pub use types::{DiffEntry, Json, ParseOptions};
+pub use schema::{SchemaError, SchemaValidator, SchemaViolation, infer_schema};
+pub use types::{DiffEntry, DiffKind, Json, ParseOptions, PatchOp};
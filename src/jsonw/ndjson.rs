@@ -0,0 +1,345 @@
+//! NDJSON（每行一个 JSON 值）逐行处理
+//!
+//! 每次只持有一行，适合处理远大于内存的日志文件。空白行会被跳过，出错时
+//! [`StreamError`] 给出行号、列号和字节偏移。
+//!
+//! # 示例
+//!
+//! ```rust
+//! use rust_tools::jsonw::ndjson::{filter_ndjson, NdjsonReader};
+//! use rust_tools::jsonw::Query;
+//!
+//! let input = "{\"level\":\"error\",\"id\":1}\n\n{\"level\":\"info\",\"id\":2}\n";
+//! let lines: Vec<usize> = NdjsonReader::new(input.as_bytes())
+//!     .map(|r| r.unwrap().line)
+//!     .collect();
+//! assert_eq!(lines, vec![1, 3]);
+//!
+//! let query = Query::parse(r#"select(.level == "error") | .id"#).unwrap();
+//! let mut out = Vec::new();
+//! assert_eq!(filter_ndjson(input.as_bytes(), &mut out, &query).unwrap(), 1);
+//! assert_eq!(out, b"1\n");
+//! ```
+
+use std::io::{BufRead, Write};
+
+use regex::Regex;
+use serde_json::Value;
+
+use crate::jsonw::{
+    diff::{ArrayKey, diff_json_by_key},
+    query::Query,
+    stream::{GrepMatch, StreamError, StreamStats, grep_stream},
+    types::DiffEntry,
+};
+
+/// NDJSON 中的一条记录
+#[derive(Debug, Clone, PartialEq)]
+pub struct NdjsonRecord {
+    /// 行号，从 1 开始
+    pub line: usize,
+    /// 该行起始的字节偏移
+    pub offset: u64,
+    pub value: Value,
+}
+
+/// 逐行读取 NDJSON
+pub struct NdjsonReader<R> {
+    reader: R,
+    buf: String,
+    line: usize,
+    offset: u64,
+    failed: bool,
+}
+
+impl<R: BufRead> NdjsonReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: String::new(),
+            line: 0,
+            offset: 0,
+            failed: false,
+        }
+    }
+
+    /// 已消费的字节数
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    fn read_record(&mut self) -> Result<Option<NdjsonRecord>, StreamError> {
+        loop {
+            self.buf.clear();
+            let start = self.offset;
+            let n = self
+                .reader
+                .read_line(&mut self.buf)
+                .map_err(|e| StreamError {
+                    line: self.line + 1,
+                    column: 1,
+                    offset: start,
+                    message: format!("read error: {e}"),
+                })?;
+            if n == 0 {
+                return Ok(None);
+            }
+            self.line += 1;
+            self.offset += n as u64;
+            let text = self.buf.trim();
+            if text.is_empty() {
+                continue;
+            }
+            return match serde_json::from_str(text) {
+                Ok(value) => Ok(Some(NdjsonRecord {
+                    line: self.line,
+                    offset: start,
+                    value,
+                })),
+                Err(e) => {
+                    // serde_json 的列号是去掉前导空白后文本中的字节位置，这里换算成字符列号
+                    let lead = self.buf.len() - self.buf.trim_start().len();
+                    let mut byte = e.column().saturating_sub(1).min(text.len());
+                    while !text.is_char_boundary(byte) {
+                        byte -= 1;
+                    }
+                    Err(StreamError {
+                        line: self.line,
+                        column: self.buf[..lead + byte].chars().count() + 1,
+                        offset: start + (lead + byte) as u64,
+                        message: serde_error_message(&e),
+                    })
+                }
+            };
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for NdjsonReader<R> {
+    type Item = Result<NdjsonRecord, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let result = self.read_record().transpose();
+        if matches!(result, Some(Err(_))) {
+            self.failed = true;
+        }
+        result
+    }
+}
+
+/// 去掉 serde_json 错误信息末尾自带的 "at line L column C"
+fn serde_error_message(e: &serde_json::Error) -> String {
+    let text = e.to_string();
+    let suffix = format!(" at line {} column {}", e.line(), e.column());
+    text.strip_suffix(&suffix).unwrap_or(&text).to_string()
+}
+
+fn write_error(record: &NdjsonRecord, e: std::io::Error) -> StreamError {
+    StreamError {
+        line: record.line,
+        column: 1,
+        offset: record.offset,
+        message: format!("write error: {e}"),
+    }
+}
+
+/// 对每一行执行查询，把结果压缩成一行一个写出；返回写出的结果个数
+pub fn filter_ndjson<R: BufRead, W: Write>(
+    reader: R,
+    mut out: W,
+    query: &Query,
+) -> Result<usize, StreamError> {
+    let mut written = 0;
+    for record in NdjsonReader::new(reader) {
+        let record = record?;
+        for value in query.evaluate(&record.value) {
+            serde_json::to_writer(&mut out, &value).map_err(|e| write_error(&record, e.into()))?;
+            out.write_all(b"\n").map_err(|e| write_error(&record, e))?;
+            written += 1;
+        }
+    }
+    Ok(written)
+}
+
+/// 逐行校验语法：每行必须恰好是一个完整的值，错误报告在所在行
+pub fn validate_ndjson<R: BufRead>(reader: R) -> Result<StreamStats, StreamError> {
+    let mut records = NdjsonReader::new(reader);
+    let mut stats = StreamStats::default();
+    for record in records.by_ref() {
+        let (events, depth) = shape(&record?.value);
+        stats.values += 1;
+        stats.events += events;
+        stats.max_depth = stats.max_depth.max(depth);
+    }
+    stats.bytes = records.offset();
+    Ok(stats)
+}
+
+/// `(事件数, 嵌套深度)`，与 [`crate::jsonw::stream::validate_stream`] 的统计口径一致
+fn shape(value: &Value) -> (usize, usize) {
+    let children: Box<dyn Iterator<Item = (usize, usize)>> = match value {
+        Value::Array(items) => Box::new(items.iter().map(shape)),
+        // 每个键本身也是一个事件
+        Value::Object(map) => Box::new(map.values().map(|v| {
+            let (events, depth) = shape(v);
+            (events + 1, depth)
+        })),
+        _ => return (1, 0),
+    };
+    children.fold((2, 1), |(events, depth), (e, d)| {
+        (events + e, depth.max(d + 1))
+    })
+}
+
+/// 逐行搜索标量，匹配的 `value_index` 是记录序号（从 0 开始，不计空白行），`line` 是记录所在行
+pub fn grep_ndjson<R: BufRead>(
+    reader: R,
+    pattern: &Regex,
+    mut on_match: impl FnMut(GrepMatch),
+) -> Result<usize, StreamError> {
+    let mut matches = 0;
+    for (index, record) in NdjsonReader::new(reader).enumerate() {
+        let record = record?;
+        let text = record.value.to_string();
+        matches += grep_stream(text.as_bytes(), pattern, |m| {
+            on_match(GrepMatch {
+                value_index: index,
+                line: record.line,
+                ..m
+            })
+        })?;
+    }
+    Ok(matches)
+}
+
+/// 两个 NDJSON 按记录顺序逐条比较
+///
+/// 每条有差异的记录回调一次 `(记录序号, 差异)`，序号从 1 开始（不计空白行）。
+/// 一侧多出的记录整条作为新增或删除报告，键为空字符串。返回有差异的记录数。
+pub fn diff_ndjson<R1: BufRead, R2: BufRead>(
+    old: R1,
+    new: R2,
+    sort_arrays: bool,
+    keys: &[ArrayKey],
+    mut on_diff: impl FnMut(usize, Vec<DiffEntry>),
+) -> Result<usize, StreamError> {
+    let mut old = NdjsonReader::new(old);
+    let mut new = NdjsonReader::new(new);
+    let mut changed = 0;
+    for index in 1.. {
+        let (a, b) = match (old.next().transpose()?, new.next().transpose()?) {
+            (None, None) => break,
+            (a, b) => (
+                a.map_or(Value::Null, |r| r.value),
+                b.map_or(Value::Null, |r| r.value),
+            ),
+        };
+        let entries = if a.is_null() ^ b.is_null() {
+            vec![DiffEntry {
+                key: String::new(),
+                old: a,
+                new: b,
+            }]
+        } else {
+            diff_json_by_key(&a, &b, sort_arrays, keys)
+        };
+        if !entries.is_empty() {
+            changed += 1;
+            on_diff(index, entries);
+        }
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_reader_skips_blank_lines_and_tracks_offsets() {
+        let input = "{\"a\":1}\r\n   \n  [2]\n3";
+        let records: Vec<NdjsonRecord> = NdjsonReader::new(input.as_bytes())
+            .map(Result::unwrap)
+            .collect();
+        let summary: Vec<(usize, u64, Value)> = records
+            .into_iter()
+            .map(|r| (r.line, r.offset, r.value))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, 0, json!({"a": 1})),
+                (3, 13, json!([2])),
+                (4, 19, json!(3))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reader_error_position() {
+        let input = "{\"a\":1}\n  {\"中\": tru}\n{\"never\":1}\n";
+        let mut reader = NdjsonReader::new(input.as_bytes());
+        assert!(reader.next().unwrap().is_ok());
+        let err = reader.next().unwrap().unwrap_err();
+        assert_eq!((err.line, err.column), (2, 12));
+        assert_eq!(err.message, "expected ident");
+        assert_eq!(
+            &input.as_bytes()[err.offset as usize..err.offset as usize + 1],
+            b"}"
+        );
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_filter_and_diff() {
+        let query = Query::parse("$[?(@.n > 1)].id").unwrap();
+        let mut out = Vec::new();
+        let n = filter_ndjson(
+            &b"[{\"id\":1,\"n\":1},{\"id\":2,\"n\":5}]\n[{\"id\":3,\"n\":9}]\n"[..],
+            &mut out,
+            &query,
+        )
+        .unwrap();
+        assert_eq!((n, out.as_slice()), (2, &b"2\n3\n"[..]));
+
+        let old = "{\"id\":1,\"v\":1}\n{\"id\":2,\"v\":2}\n";
+        let new = "{\"id\":1,\"v\":1}\n\n{\"id\":2,\"v\":3}\n{\"id\":3}\n";
+        let mut diffs = Vec::new();
+        let changed = diff_ndjson(old.as_bytes(), new.as_bytes(), false, &[], |i, d| {
+            diffs.push((i, d))
+        })
+        .unwrap();
+        assert_eq!(changed, 2);
+        assert_eq!(diffs[0].0, 2);
+        assert_eq!(diffs[0].1[0].key, "v");
+        assert_eq!(diffs[1].0, 3);
+        assert_eq!(diffs[1].1[0].new, json!({"id": 3}));
+    }
+
+    #[test]
+    fn test_validate_and_grep_by_line() {
+        let input = "{\"a\":[1,{\"b\":2}]}\n\n\"x\"\n";
+        let stats = validate_ndjson(input.as_bytes()).unwrap();
+        let expected = crate::jsonw::stream::validate_stream(input.as_bytes()).unwrap();
+        assert_eq!(stats, expected);
+
+        // 跨行的记录和同一行的两个值都不是合法的 NDJSON
+        let err = validate_ndjson(&b"{\"a\":\n1}\n"[..]).unwrap_err();
+        assert_eq!(err.line, 1);
+        let err = validate_ndjson(&b"{\"a\":1}\n{\"b\":2} {\"c\":3}\n"[..]).unwrap_err();
+        assert_eq!(err.line, 2);
+
+        let re = Regex::new("^2$").unwrap();
+        let mut found = Vec::new();
+        let n = grep_ndjson(&b"{\"n\":1}\n\n{\"x\":[0,2]}\n"[..], &re, |m| found.push(m)).unwrap();
+        assert_eq!(n, 1);
+        assert_eq!(
+            (found[0].value_index, found[0].line, found[0].path.as_str()),
+            (1, 3, "$.x[1]")
+        );
+    }
+}
@@ -0,0 +1,705 @@
+//! JSON Patch（RFC 6902）与 JSON Merge Patch（RFC 7386）
+//!
+//! - [`diff_patch`] 生成操作列表，路径为转义后的 JSON Pointer，不会因为键名中含 `.` 而产生歧义；
+//!   数组先去掉公共前后缀，再用 LCS 对齐，值相同但位置变化的元素输出为 `move`，
+//!   同一位置上被替换的元素继续向下细分
+//! - [`diff_merge_patch`] 生成合并补丁：对象逐键合并，`null` 表示删除，数组整体替换
+//! - [`apply_patch`] / [`apply_merge_patch`] 应用上面两种补丁
+//!
+//! # 示例
+//!
+//! ```rust
+//! use rust_tools::jsonw::{apply_patch, diff_patch, PatchOp};
+//! use serde_json::json;
+//!
+//! let old = json!({"a.b": 1, "list": [1, 2, 3, 4]});
+//! let new = json!({"a.b": 2, "list": [1, 3, 4, 2]});
+//!
+//! let ops = diff_patch(&old, &new);
+//! assert_eq!(
+//!     ops,
+//!     vec![
+//!         PatchOp::Replace { path: "/a.b".to_string(), value: json!(2) },
+//!         PatchOp::Move { from: "/list/1".to_string(), path: "/list/3".to_string() },
+//!     ]
+//! );
+//! assert_eq!(apply_patch(&old, &ops).unwrap(), new);
+//! ```
+
+use std::fmt;
+
+use serde_json::{Map, Value};
+
+use crate::jsonw::types::PatchOp;
+
+/// LCS 动态规划表的最大格数，超过后数组按"整段删除 + 整段插入"处理
+const LCS_LIMIT: usize = 1 << 22;
+
+/// 应用补丁失败：`index` 为出错操作在列表中的下标
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchError {
+    pub index: usize,
+    pub message: String,
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "patch operation #{}: {}", self.index, self.message)
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+/// 把单个键转义为 JSON Pointer 片段（`~` → `~0`，`/` → `~1`）
+pub fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape_pointer_token(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+fn child_path(path: &str, token: &str) -> String {
+    format!("{path}/{}", escape_pointer_token(token))
+}
+
+/// 生成把 `old` 变为 `new` 的 RFC 6902 操作列表
+pub fn diff_patch(old: &Value, new: &Value) -> Vec<PatchOp> {
+    let mut ops = Vec::new();
+    diff_into("", old, new, &mut ops);
+    ops
+}
+
+fn diff_into(path: &str, old: &Value, new: &Value, ops: &mut Vec<PatchOp>) {
+    match (old, new) {
+        (Value::Object(o1), Value::Object(o2)) => {
+            for (k, v1) in o1 {
+                match o2.get(k) {
+                    None => ops.push(PatchOp::Remove {
+                        path: child_path(path, k),
+                    }),
+                    Some(v2) if v1 != v2 => diff_into(&child_path(path, k), v1, v2, ops),
+                    Some(_) => {}
+                }
+            }
+            for (k, v2) in o2 {
+                if !o1.contains_key(k) {
+                    ops.push(PatchOp::Add {
+                        path: child_path(path, k),
+                        value: v2.clone(),
+                    });
+                }
+            }
+        }
+        (Value::Array(a1), Value::Array(a2)) => diff_array(path, a1, a2, ops),
+        _ => {
+            if old != new {
+                ops.push(PatchOp::Replace {
+                    path: path.to_string(),
+                    value: new.clone(),
+                });
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Edit {
+    Keep(usize, usize),
+    Del(usize),
+    Ins(usize),
+}
+
+/// 最长公共子序列对齐；中间段过大时退化为整段删除 + 整段插入
+fn edit_script(a: &[Value], b: &[Value]) -> Vec<Edit> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let am = &a[prefix..a.len() - suffix];
+    let bm = &b[prefix..b.len() - suffix];
+    let (n, m) = (am.len(), bm.len());
+
+    let mut script: Vec<Edit> = (0..prefix).map(|i| Edit::Keep(i, i)).collect();
+    let (mut i, mut j) = (0, 0);
+    if n.saturating_mul(m) <= LCS_LIMIT {
+        // dp[i][j] = am[i..] 与 bm[j..] 的 LCS 长度
+        let w = m + 1;
+        let mut dp = vec![0u32; (n + 1) * w];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                dp[i * w + j] = if am[i] == bm[j] {
+                    dp[(i + 1) * w + j + 1] + 1
+                } else {
+                    dp[(i + 1) * w + j].max(dp[i * w + j + 1])
+                };
+            }
+        }
+        while i < n && j < m {
+            if am[i] == bm[j] {
+                script.push(Edit::Keep(prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if dp[(i + 1) * w + j] >= dp[i * w + j + 1] {
+                script.push(Edit::Del(prefix + i));
+                i += 1;
+            } else {
+                script.push(Edit::Ins(prefix + j));
+                j += 1;
+            }
+        }
+    }
+    script.extend((i..n).map(|i| Edit::Del(prefix + i)));
+    script.extend((j..m).map(|j| Edit::Ins(prefix + j)));
+    script.extend((0..suffix).map(|k| Edit::Keep(a.len() - suffix + k, b.len() - suffix + k)));
+    script
+}
+
+fn diff_array(path: &str, a: &[Value], b: &[Value], ops: &mut Vec<PatchOp>) {
+    let index_path = |i: usize| format!("{path}/{i}");
+    let script = edit_script(a, b);
+
+    // target[i]：旧元素 i 最终落在新数组的哪个位置；source[j]：新位置 j 是否由旧元素提供
+    let mut target: Vec<Option<usize>> = vec![None; a.len()];
+    let mut source: Vec<bool> = vec![false; b.len()];
+    for edit in &script {
+        if let Edit::Keep(i, j) = *edit {
+            target[i] = Some(j);
+            source[j] = true;
+        }
+    }
+
+    // 被删除的元素在别处原样出现：记为 move
+    let deleted: Vec<usize> = script
+        .iter()
+        .filter_map(|e| if let Edit::Del(i) = *e { Some(i) } else { None })
+        .collect();
+    let inserted: Vec<usize> = script
+        .iter()
+        .filter_map(|e| if let Edit::Ins(j) = *e { Some(j) } else { None })
+        .collect();
+    let mut moved = vec![false; b.len()];
+    if deleted.len().saturating_mul(inserted.len()) <= LCS_LIMIT {
+        for &j in &inserted {
+            if let Some(&i) = deleted
+                .iter()
+                .find(|&&i| target[i].is_none() && a[i] == b[j])
+            {
+                target[i] = Some(j);
+                source[j] = true;
+                moved[j] = true;
+            }
+        }
+    }
+
+    // 同一段空隙里剩下的删除和插入按顺序配对，视为原地修改，继续向下比较
+    let mut modified = Vec::new();
+    let (mut dels, mut inss) = (Vec::new(), Vec::new());
+    for edit in script.iter().chain([&Edit::Keep(a.len(), b.len())]) {
+        match *edit {
+            Edit::Del(i) if target[i].is_none() => dels.push(i),
+            Edit::Ins(j) if !source[j] => inss.push(j),
+            Edit::Keep(..) => {
+                for (&i, &j) in dels.iter().zip(&inss) {
+                    target[i] = Some(j);
+                    source[j] = true;
+                    modified.push((i, j));
+                }
+                dels.clear();
+                inss.clear();
+            }
+            _ => {}
+        }
+    }
+
+    // 1. 原地修改使用旧下标，必须在结构变化之前输出
+    for &(i, j) in &modified {
+        diff_into(&index_path(i), &a[i], &b[j], ops);
+    }
+    // 2. 从后往前删除，保证前面的下标不变
+    for i in (0..a.len()).rev() {
+        if target[i].is_none() {
+            ops.push(PatchOp::Remove {
+                path: index_path(i),
+            });
+        }
+    }
+    // 3. 按新数组顺序逐个归位：已有的元素 move 过来，缺的元素 add。
+    //    挡在当前位置上的移动元素直接放到它最终的相对位置，避免把后面的元素逐个往前挪
+    let mut cur: Vec<usize> = target.iter().flatten().copied().collect();
+    let mut settled = vec![false; b.len()];
+    for (j, &from_old) in source.iter().enumerate() {
+        while let Some(&t) = cur.get(j)
+            && t != j
+            && moved[t]
+            && !settled[t]
+        {
+            settled[t] = true;
+            let q = cur.iter().filter(|&&x| x < t).count();
+            if q > j {
+                ops.push(PatchOp::Move {
+                    from: index_path(j),
+                    path: index_path(q),
+                });
+                cur.remove(j);
+                cur.insert(q, t);
+            }
+        }
+        if from_old {
+            let p = j + cur[j..].iter().position(|&t| t == j).unwrap_or(0);
+            if p != j {
+                ops.push(PatchOp::Move {
+                    from: index_path(p),
+                    path: index_path(j),
+                });
+                let t = cur.remove(p);
+                cur.insert(j, t);
+            }
+        } else {
+            ops.push(PatchOp::Add {
+                path: index_path(j),
+                value: b[j].clone(),
+            });
+            cur.insert(j, j);
+        }
+    }
+}
+
+/// 生成把 `old` 变为 `new` 的 RFC 7386 合并补丁
+///
+/// 合并补丁用 `null` 表示删除，因此 `new` 中值为 `null` 的字段无法表达，应用后会被删除。
+pub fn diff_merge_patch(old: &Value, new: &Value) -> Value {
+    let (Value::Object(o1), Value::Object(o2)) = (old, new) else {
+        return new.clone();
+    };
+    let mut patch = Map::new();
+    for k in o1.keys() {
+        if !o2.contains_key(k) {
+            patch.insert(k.clone(), Value::Null);
+        }
+    }
+    for (k, v2) in o2 {
+        match o1.get(k) {
+            Some(v1) if v1 == v2 => {}
+            Some(v1) if v1.is_object() && v2.is_object() => {
+                patch.insert(k.clone(), diff_merge_patch(v1, v2));
+            }
+            _ => {
+                patch.insert(k.clone(), v2.clone());
+            }
+        }
+    }
+    Value::Object(patch)
+}
+
+/// 应用 RFC 7386 合并补丁
+pub fn apply_merge_patch(target: &Value, patch: &Value) -> Value {
+    let Value::Object(p) = patch else {
+        return patch.clone();
+    };
+    let mut out = match target {
+        Value::Object(o) => o.clone(),
+        _ => Map::new(),
+    };
+    for (k, v) in p {
+        if v.is_null() {
+            out.shift_remove(k);
+        } else {
+            let merged = apply_merge_patch(out.get(k).unwrap_or(&Value::Null), v);
+            out.insert(k.clone(), merged);
+        }
+    }
+    Value::Object(out)
+}
+
+/// 依次应用 RFC 6902 操作，任何一步失败都返回错误且不修改输入
+pub fn apply_patch(doc: &Value, ops: &[PatchOp]) -> Result<Value, PatchError> {
+    let mut out = doc.clone();
+    for (index, op) in ops.iter().enumerate() {
+        apply_op(&mut out, op).map_err(|message| PatchError { index, message })?;
+    }
+    Ok(out)
+}
+
+fn apply_op(doc: &mut Value, op: &PatchOp) -> Result<(), String> {
+    match op {
+        PatchOp::Add { path, value } => add(doc, path, value.clone()),
+        PatchOp::Remove { path } => remove(doc, path).map(drop),
+        PatchOp::Replace { path, value } => {
+            *lookup_mut(doc, path)? = value.clone();
+            Ok(())
+        }
+        PatchOp::Move { from, path } => {
+            if path
+                .strip_prefix(from.as_str())
+                .is_some_and(|rest| rest.starts_with('/'))
+            {
+                return Err(format!("cannot move {from} into its own child {path}"));
+            }
+            let value = remove(doc, from)?;
+            add(doc, path, value)
+        }
+        PatchOp::Copy { from, path } => {
+            let value = lookup(doc, from)?.clone();
+            add(doc, path, value)
+        }
+        PatchOp::Test { path, value } => {
+            if lookup(doc, path)? == value {
+                Ok(())
+            } else {
+                Err(format!("test failed at {path}"))
+            }
+        }
+    }
+}
+
+fn lookup<'a>(doc: &'a Value, path: &str) -> Result<&'a Value, String> {
+    doc.pointer(path)
+        .ok_or_else(|| format!("path not found: {path}"))
+}
+
+fn lookup_mut<'a>(doc: &'a mut Value, path: &str) -> Result<&'a mut Value, String> {
+    doc.pointer_mut(path)
+        .ok_or_else(|| format!("path not found: {path}"))
+}
+
+/// 拆成父节点指针和最后一个（已反转义的）片段
+fn split_pointer(path: &str) -> Result<(&str, String), String> {
+    if !path.starts_with('/') {
+        return Err(format!("invalid JSON pointer: {path:?}"));
+    }
+    let idx = path.rfind('/').unwrap_or(0);
+    Ok((&path[..idx], unescape_pointer_token(&path[idx + 1..])))
+}
+
+/// RFC 6901 数组下标：十进制、无前导零
+fn parse_index(token: &str, path: &str) -> Result<usize, String> {
+    let valid = !token.is_empty()
+        && token.bytes().all(|b| b.is_ascii_digit())
+        && !(token.len() > 1 && token.starts_with('0'));
+    valid
+        .then(|| token.parse().ok())
+        .flatten()
+        .ok_or_else(|| format!("invalid array index in {path}"))
+}
+
+fn add(doc: &mut Value, path: &str, value: Value) -> Result<(), String> {
+    if path.is_empty() {
+        *doc = value;
+        return Ok(());
+    }
+    let (parent, last) = split_pointer(path)?;
+    match lookup_mut(doc, parent)? {
+        Value::Object(o) => {
+            o.insert(last, value);
+            Ok(())
+        }
+        Value::Array(a) => {
+            let idx = if last == "-" {
+                a.len()
+            } else {
+                parse_index(&last, path)?
+            };
+            if idx > a.len() {
+                return Err(format!("index out of bounds: {path}"));
+            }
+            a.insert(idx, value);
+            Ok(())
+        }
+        _ => Err(format!("parent of {path} is not a container")),
+    }
+}
+
+fn remove(doc: &mut Value, path: &str) -> Result<Value, String> {
+    if path.is_empty() {
+        return Err("cannot remove the whole document".to_string());
+    }
+    let (parent, last) = split_pointer(path)?;
+    match lookup_mut(doc, parent)? {
+        Value::Object(o) => o
+            .shift_remove(&last)
+            .ok_or_else(|| format!("path not found: {path}")),
+        Value::Array(a) => {
+            let idx = parse_index(&last, path)?;
+            if idx >= a.len() {
+                return Err(format!("index out of bounds: {path}"));
+            }
+            Ok(a.remove(idx))
+        }
+        _ => Err(format!("path not found: {path}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{RngExt, SeedableRng, rngs::StdRng};
+    use serde_json::json;
+
+    fn round_trip(old: &Value, new: &Value) -> Vec<PatchOp> {
+        let ops = diff_patch(old, new);
+        let patched = apply_patch(old, &ops).unwrap_or_else(|e| panic!("{e}: {ops:?}"));
+        assert_eq!(&patched, new, "ops: {ops:?}");
+        ops
+    }
+
+    #[test]
+    fn test_pointer_escaping() {
+        let old = json!({"a/b": {"c~d": 1}, "x.y": 1});
+        let new = json!({"a/b": {"c~d": 2}, "x.y": 1});
+        assert_eq!(
+            round_trip(&old, &new),
+            vec![PatchOp::Replace {
+                path: "/a~1b/c~0d".to_string(),
+                value: json!(2)
+            }]
+        );
+    }
+
+    #[test]
+    fn test_object_add_remove_replace_root() {
+        let ops = round_trip(&json!({"a": 1, "b": 2}), &json!({"b": 2, "c": 3}));
+        assert_eq!(
+            ops,
+            vec![
+                PatchOp::Remove {
+                    path: "/a".to_string()
+                },
+                PatchOp::Add {
+                    path: "/c".to_string(),
+                    value: json!(3)
+                },
+            ]
+        );
+        assert_eq!(
+            round_trip(&json!([1]), &json!({"a": 1})),
+            vec![PatchOp::Replace {
+                path: String::new(),
+                value: json!({"a": 1})
+            }]
+        );
+        assert!(
+            diff_patch(
+                &json!({"a": [1, {"b": null}]}),
+                &json!({"a": [1, {"b": null}]})
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_array_insert_is_not_index_noise() {
+        let old = json!([{"id": 1}, {"id": 2}, {"id": 3}]);
+        let new = json!([{"id": 0}, {"id": 1}, {"id": 2}, {"id": 3}]);
+        assert_eq!(
+            round_trip(&old, &new),
+            vec![PatchOp::Add {
+                path: "/0".to_string(),
+                value: json!({"id": 0})
+            }]
+        );
+
+        // 原地修改的元素继续细分到字段
+        let old = json!([{"id": 1, "v": "a"}, {"id": 2, "v": "b"}]);
+        let new = json!([{"id": 1, "v": "a"}, {"id": 2, "v": "c"}, 5]);
+        assert_eq!(
+            round_trip(&old, &new),
+            vec![
+                PatchOp::Replace {
+                    path: "/1/v".to_string(),
+                    value: json!("c")
+                },
+                PatchOp::Add {
+                    path: "/2".to_string(),
+                    value: json!(5)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_array_moves() {
+        let old = json!(["a", "b", "c", "d", "e"]);
+        let new = json!(["e", "a", "b", "c", "d"]);
+        assert_eq!(
+            round_trip(&old, &new),
+            vec![PatchOp::Move {
+                from: "/4".to_string(),
+                path: "/0".to_string()
+            }]
+        );
+        let ops = round_trip(&json!([1, 2, 3, 4, 5, 6]), &json!([6, 5, 4, 3, 2, 1]));
+        assert!(ops.iter().all(|op| matches!(op, PatchOp::Move { .. })));
+    }
+
+    #[test]
+    fn test_apply_patch_rfc_examples() {
+        let ops: Vec<PatchOp> = serde_json::from_value(json!([
+            {"op": "test", "path": "/baz", "value": "qux"},
+            {"op": "add", "path": "/foo/1", "value": "x"},
+            {"op": "add", "path": "/foo/-", "value": "end"},
+            {"op": "copy", "from": "/baz", "path": "/copied"},
+            {"op": "move", "from": "/foo/0", "path": "/moved"},
+            {"op": "replace", "path": "/baz", "value": "boo"},
+            {"op": "remove", "path": "/foo/0"}
+        ]))
+        .unwrap();
+        let doc = json!({"baz": "qux", "foo": ["bar", "baz"]});
+        assert_eq!(
+            apply_patch(&doc, &ops).unwrap(),
+            json!({"baz": "boo", "foo": ["baz", "end"], "copied": "qux", "moved": "bar"})
+        );
+    }
+
+    #[test]
+    fn test_apply_patch_errors() {
+        let doc = json!({"a": [1, 2], "b": {"c": 1}});
+        let cases = [
+            json!({"op": "remove", "path": "/missing"}),
+            json!({"op": "add", "path": "/a/3", "value": 0}),
+            json!({"op": "add", "path": "/a/01", "value": 0}),
+            json!({"op": "replace", "path": "/a/5", "value": 0}),
+            json!({"op": "test", "path": "/b/c", "value": 2}),
+            json!({"op": "move", "from": "/b", "path": "/b/c/d"}),
+            json!({"op": "add", "path": "a", "value": 0}),
+            json!({"op": "remove", "path": ""}),
+        ];
+        for case in cases {
+            let ops: Vec<PatchOp> = vec![
+                PatchOp::Test {
+                    path: "/a/0".to_string(),
+                    value: json!(1),
+                },
+                serde_json::from_value(case.clone()).unwrap(),
+            ];
+            let err = apply_patch(&doc, &ops).unwrap_err();
+            assert_eq!(err.index, 1, "{case}: {err}");
+        }
+    }
+
+    #[test]
+    fn test_merge_patch() {
+        let old = json!({"title": "Goodbye!", "author": {"givenName": "John", "familyName": "Doe"}, "tags": ["example", "sample"], "content": "text"});
+        let new = json!({"title": "Hello!", "author": {"givenName": "John"}, "tags": ["example"], "content": "text", "phoneNumber": "+01-123-456-7890"});
+        let patch = diff_merge_patch(&old, &new);
+        assert_eq!(
+            patch,
+            json!({"title": "Hello!", "author": {"familyName": null}, "tags": ["example"], "phoneNumber": "+01-123-456-7890"})
+        );
+        assert_eq!(apply_merge_patch(&old, &patch), new);
+        // 非对象补丁整体替换，对象补丁作用于非对象目标时从空对象开始
+        assert_eq!(apply_merge_patch(&json!({"a": 1}), &json!([1])), json!([1]));
+        assert_eq!(
+            apply_merge_patch(&json!([1]), &json!({"a": {"b": null}})),
+            json!({"a": {}})
+        );
+    }
+
+    fn random_value(rng: &mut StdRng, depth: u32) -> Value {
+        match rng.random_range(0..if depth == 0 { 3 } else { 5 }) {
+            0 => json!(rng.random_range(0..4)),
+            1 => json!(["x", "y", "z/~"][rng.random_range(0..3)]),
+            2 => Value::Bool(rng.random()),
+            3 => Value::Array(
+                (0..rng.random_range(0..6))
+                    .map(|_| random_value(rng, depth - 1))
+                    .collect(),
+            ),
+            _ => Value::Object(
+                (0..rng.random_range(0..4))
+                    .map(|_| {
+                        let key = ["a", "b", "c.d", "e/f"][rng.random_range(0..4)].to_string();
+                        (key, random_value(rng, depth - 1))
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// 随机修改：删除/插入/交换数组元素或改写字段，保证新旧文档有足够的相似度
+    fn mutate(rng: &mut StdRng, v: &Value) -> Value {
+        match v {
+            Value::Array(a) => {
+                let mut a: Vec<Value> = a
+                    .iter()
+                    .map(|x| {
+                        if rng.random_bool(0.3) {
+                            mutate(rng, x)
+                        } else {
+                            x.clone()
+                        }
+                    })
+                    .collect();
+                for _ in 0..rng.random_range(0..3) {
+                    match rng.random_range(0..3) {
+                        0 if !a.is_empty() => {
+                            a.remove(rng.random_range(0..a.len()));
+                        }
+                        1 => a.insert(rng.random_range(0..=a.len()), random_value(rng, 1)),
+                        _ if a.len() > 1 => {
+                            let (i, j) =
+                                (rng.random_range(0..a.len()), rng.random_range(0..a.len()));
+                            a.swap(i, j);
+                        }
+                        _ => {}
+                    }
+                }
+                Value::Array(a)
+            }
+            Value::Object(o) => {
+                let mut out = Map::new();
+                for (k, x) in o {
+                    if rng.random_bool(0.9) {
+                        let x = if rng.random_bool(0.3) {
+                            mutate(rng, x)
+                        } else {
+                            x.clone()
+                        };
+                        out.insert(k.clone(), x);
+                    }
+                }
+                if rng.random_bool(0.3) {
+                    out.insert("new".to_string(), random_value(rng, 1));
+                }
+                Value::Object(out)
+            }
+            _ if rng.random_bool(0.5) => random_value(rng, 1),
+            _ => v.clone(),
+        }
+    }
+
+    fn strip_nulls(v: &Value) -> Value {
+        match v {
+            Value::Object(o) => Value::Object(
+                o.iter()
+                    .filter(|(_, x)| !x.is_null())
+                    .map(|(k, x)| (k.clone(), strip_nulls(x)))
+                    .collect(),
+            ),
+            Value::Array(a) => Value::Array(a.iter().map(strip_nulls).collect()),
+            _ => v.clone(),
+        }
+    }
+
+    #[test]
+    fn test_random_round_trip() {
+        let mut rng = StdRng::seed_from_u64(12);
+        for _ in 0..2000 {
+            let old = random_value(&mut rng, 4);
+            let new = mutate(&mut rng, &old);
+            round_trip(&old, &new);
+
+            let patch = diff_merge_patch(&old, &new);
+            assert_eq!(apply_merge_patch(&old, &patch), strip_nulls(&new));
+
+            // 序列化后再反序列化，补丁仍然可用
+            let ops = diff_patch(&new, &old);
+            let text = serde_json::to_string(&ops).unwrap();
+            let ops: Vec<PatchOp> = serde_json::from_str(&text).unwrap();
+            assert_eq!(apply_patch(&new, &ops).unwrap(), old);
+        }
+    }
+}
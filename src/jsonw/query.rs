@@ -0,0 +1,1069 @@
+//! JSONPath / jq 风格查询
+//!
+//! 以 JSONPath（RFC 9535）为基础，兼容常用的 jq 写法：
+//!
+//! | 语法 | 含义 |
+//! |------|------|
+//! | `$` / `@` / `.` | 根节点 / 当前节点 / 当前节点（jq 风格） |
+//! | `.name`、`['name']`、`["a b"]` | 对象字段 |
+//! | `[0]`、`[-1]` | 数组下标，负数从尾部计数 |
+//! | `[start:end:step]` | 数组切片，各部分均可省略，`step` 可为负 |
+//! | `*`、`[*]`、`[]` | 所有子节点 |
+//! | `..name`、`..*`、`..[0]` | 递归下降：在当前节点及所有后代上应用选择器 |
+//! | `[a,'b',0:2]` | 多个选择器的并集 |
+//! | `[?(@.status == "failed")]` | 过滤子节点，支持 `== != < <= > >= =~ && \|\| !` 和 `length()` |
+//! | `{id, name, city: addr.city}` | 投影：用当前节点的若干字段构造新对象 |
+//! | `a \| b` | 管道：`b` 依次作用于 `a` 的每个结果 |
+//! | `length` / `keys` / `values` / `first` / `last` / `select(expr)` | 管道中可用的内置函数 |
+//!
+//! 过滤条件中 `@`（或 `.`）表示被测试的子节点，`$` 表示整个文档；只写路径表示
+//! "存在且不为 `null`/`false`"。`=~` 右侧可以是 `/pattern/i` 或字符串。
+//!
+//! # 示例
+//!
+//! ```rust
+//! use rust_tools::jsonw::query;
+//! use serde_json::json;
+//!
+//! let doc = json!({"jobs": [
+//!     {"id": 1, "status": "ok", "owner": {"name": "alice"}},
+//!     {"id": 2, "status": "failed", "owner": {"name": "bob"}},
+//! ]});
+//!
+//! let failed = query(&doc, r#"$.jobs[?(@.status == "failed")].id"#).unwrap();
+//! assert_eq!(failed, vec![json!(2)]);
+//!
+//! let rows = query(&doc, ".jobs[] | select(.id > 1) | {id, owner: owner.name}").unwrap();
+//! assert_eq!(rows, vec![json!({"id": 2, "owner": "bob"})]);
+//!
+//! assert_eq!(query(&doc, "$..name").unwrap(), vec![json!("alice"), json!("bob")]);
+//! ```
+
+use std::{borrow::Cow, cmp::Ordering, fmt};
+
+use regex::Regex;
+use serde_json::{Map, Value};
+
+/// 查询表达式的语法错误，`position` 为出错处的字符下标
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// 编译好的查询，可以对多个文档重复执行
+#[derive(Debug, Clone)]
+pub struct Query {
+    stages: Vec<Stage>,
+}
+
+#[derive(Debug, Clone)]
+enum Stage {
+    Path(Path),
+    Length,
+    Keys,
+    Values,
+    First,
+    Last,
+    Select(Expr),
+}
+
+#[derive(Debug, Clone)]
+struct Path {
+    absolute: bool,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Child(Vec<Selector>),
+    Descendant(Vec<Selector>),
+    Project(Vec<(String, Path)>),
+}
+
+#[derive(Debug, Clone)]
+enum Selector {
+    Name(String),
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, Option<i64>),
+    Wildcard,
+    Filter(Box<Expr>),
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Operand, CmpOp, Operand),
+    Matches(Operand, Regex),
+    Truthy(Operand),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone)]
+enum Operand {
+    Path(Path),
+    Literal(Value),
+    Length(Box<Operand>),
+}
+
+type Nodes<'a> = Vec<Cow<'a, Value>>;
+
+impl Query {
+    /// 解析查询表达式
+    pub fn parse(expr: &str) -> Result<Self, QueryError> {
+        let mut p = Parser {
+            chars: expr.chars().collect(),
+            pos: 0,
+        };
+        let mut stages = vec![p.parse_stage()?];
+        loop {
+            p.skip_ws();
+            if p.eat('|') {
+                stages.push(p.parse_stage()?);
+            } else {
+                break;
+            }
+        }
+        p.skip_ws();
+        if let Some(c) = p.peek() {
+            return Err(p.error(format!("unexpected character '{c}'")));
+        }
+        Ok(Self { stages })
+    }
+
+    /// 在 `root` 上执行查询，按文档顺序返回所有结果
+    pub fn evaluate(&self, root: &Value) -> Vec<Value> {
+        let mut nodes: Nodes<'_> = vec![Cow::Borrowed(root)];
+        for stage in &self.stages {
+            nodes = nodes
+                .into_iter()
+                .flat_map(|node| stage.apply(node, root))
+                .collect();
+        }
+        nodes.into_iter().map(Cow::into_owned).collect()
+    }
+}
+
+/// 解析并执行一次查询
+pub fn query(value: &Value, expr: &str) -> Result<Vec<Value>, QueryError> {
+    Ok(Query::parse(expr)?.evaluate(value))
+}
+
+// ---------------------------------------------------------------------------
+// 解析
+// ---------------------------------------------------------------------------
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+const BUILTINS: [&str; 5] = ["length", "keys", "values", "first", "last"];
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
+
+impl Parser {
+    fn error(&self, message: impl Into<String>) -> QueryError {
+        QueryError {
+            position: self.pos,
+            message: message.into(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_str(&mut self, s: &str) -> bool {
+        let n = s.chars().count();
+        if self.chars[self.pos..].iter().take(n).copied().eq(s.chars()) {
+            self.pos += n;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), QueryError> {
+        self.skip_ws();
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected '{c}'")))
+        }
+    }
+
+    fn skip_ws(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek_ident(&self) -> String {
+        self.chars[self.pos..]
+            .iter()
+            .take_while(|c| is_name_char(**c))
+            .collect()
+    }
+
+    fn parse_name(&mut self) -> Result<String, QueryError> {
+        let name = self.peek_ident();
+        if name.is_empty() {
+            return Err(self.error("expected a field name"));
+        }
+        self.pos += name.chars().count();
+        Ok(name)
+    }
+
+    fn parse_stage(&mut self) -> Result<Stage, QueryError> {
+        self.skip_ws();
+        let ident = self.peek_ident();
+        let len = ident.chars().count();
+        if ident == "select" && self.peek_at(len) == Some('(') {
+            self.pos += len + 1;
+            let expr = self.parse_or()?;
+            self.expect(')')?;
+            return Ok(Stage::Select(expr));
+        }
+        if BUILTINS.contains(&ident.as_str()) {
+            let mut after = self.pos + len;
+            while self.chars.get(after).is_some_and(|c| c.is_whitespace()) {
+                after += 1;
+            }
+            if matches!(self.chars.get(after), None | Some('|')) {
+                self.pos += len;
+                return Ok(match ident.as_str() {
+                    "length" => Stage::Length,
+                    "keys" => Stage::Keys,
+                    "values" => Stage::Values,
+                    "first" => Stage::First,
+                    _ => Stage::Last,
+                });
+            }
+        }
+        let path = self.parse_path()?;
+        Ok(Stage::Path(path))
+    }
+
+    /// 路径：可选的 `$`/`@` 开头，或者直接以字段名开头（`items[0].id`）
+    fn parse_path(&mut self) -> Result<Path, QueryError> {
+        let mut path = Path {
+            absolute: false,
+            segments: Vec::new(),
+        };
+        if self.eat('$') {
+            path.absolute = true;
+        } else if !self.eat('@') && self.peek().is_some_and(is_name_char) {
+            let name = self.parse_name()?;
+            path.segments
+                .push(Segment::Child(vec![Selector::Name(name)]));
+        }
+        loop {
+            match self.peek() {
+                Some('.') if self.peek_at(1) == Some('.') => {
+                    self.pos += 2;
+                    let selectors = match self.peek() {
+                        Some('[') => self.parse_bracket()?,
+                        Some('*') => {
+                            self.pos += 1;
+                            vec![Selector::Wildcard]
+                        }
+                        _ => vec![Selector::Name(self.parse_name()?)],
+                    };
+                    path.segments.push(Segment::Descendant(selectors));
+                }
+                Some('.') => {
+                    self.pos += 1;
+                    match self.peek() {
+                        Some('[') => {
+                            let selectors = self.parse_bracket()?;
+                            path.segments.push(Segment::Child(selectors));
+                        }
+                        Some('*') => {
+                            self.pos += 1;
+                            path.segments.push(Segment::Child(vec![Selector::Wildcard]));
+                        }
+                        Some('{') => path.segments.push(self.parse_projection()?),
+                        Some('"') | Some('\'') => {
+                            let name = self.parse_string()?;
+                            path.segments
+                                .push(Segment::Child(vec![Selector::Name(name)]));
+                        }
+                        Some(c) if is_name_char(c) => {
+                            let name = self.parse_name()?;
+                            path.segments
+                                .push(Segment::Child(vec![Selector::Name(name)]));
+                        }
+                        // jq 的恒等路径 `.`
+                        None | Some(' ' | '\t' | '\n' | '|' | ')' | ',' | '}' | ']') => {}
+                        Some(c) => return Err(self.error(format!("unexpected character '{c}'"))),
+                    }
+                }
+                Some('[') => {
+                    let selectors = self.parse_bracket()?;
+                    path.segments.push(Segment::Child(selectors));
+                }
+                Some('{') => path.segments.push(self.parse_projection()?),
+                _ => break,
+            }
+        }
+        Ok(path)
+    }
+
+    fn parse_bracket(&mut self) -> Result<Vec<Selector>, QueryError> {
+        self.pos += 1;
+        self.skip_ws();
+        if self.eat(']') {
+            return Ok(vec![Selector::Wildcard]);
+        }
+        let mut selectors = Vec::new();
+        loop {
+            selectors.push(self.parse_selector()?);
+            self.skip_ws();
+            if !self.eat(',') {
+                break;
+            }
+        }
+        self.expect(']')?;
+        Ok(selectors)
+    }
+
+    fn parse_selector(&mut self) -> Result<Selector, QueryError> {
+        self.skip_ws();
+        match self.peek() {
+            Some('*') => {
+                self.pos += 1;
+                Ok(Selector::Wildcard)
+            }
+            Some('?') => {
+                self.pos += 1;
+                Ok(Selector::Filter(Box::new(self.parse_or()?)))
+            }
+            Some('"' | '\'') => Ok(Selector::Name(self.parse_string()?)),
+            Some(c) if c.is_ascii_digit() || c == '-' || c == ':' => {
+                let start = self.parse_opt_int()?;
+                self.skip_ws();
+                if !self.eat(':') {
+                    return start
+                        .map(Selector::Index)
+                        .ok_or_else(|| self.error("expected an index"));
+                }
+                let end = self.parse_opt_int()?;
+                self.skip_ws();
+                let step = if self.eat(':') {
+                    self.parse_opt_int()?
+                } else {
+                    None
+                };
+                Ok(Selector::Slice(start, end, step))
+            }
+            Some(c) if is_name_char(c) => Ok(Selector::Name(self.parse_name()?)),
+            _ => Err(self.error("expected a selector")),
+        }
+    }
+
+    fn parse_opt_int(&mut self) -> Result<Option<i64>, QueryError> {
+        self.skip_ws();
+        let start = self.pos;
+        self.eat('-');
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return Ok(None);
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        text.parse().map(Some).map_err(|_| QueryError {
+            position: start,
+            message: format!("invalid integer '{text}'"),
+        })
+    }
+
+    fn parse_string(&mut self) -> Result<String, QueryError> {
+        let quote = self.peek().unwrap_or('"');
+        let start = self.pos;
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            match self.peek() {
+                None => {
+                    return Err(QueryError {
+                        position: start,
+                        message: "unterminated string".to_string(),
+                    });
+                }
+                Some(c) if c == quote => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                Some('\\') => {
+                    self.pos += 1;
+                    let escaped = self
+                        .peek()
+                        .ok_or_else(|| self.error("unterminated string"))?;
+                    self.pos += 1;
+                    out.push(match escaped {
+                        'n' => '\n',
+                        't' => '\t',
+                        'r' => '\r',
+                        other => other,
+                    });
+                }
+                Some(c) => {
+                    self.pos += 1;
+                    out.push(c);
+                }
+            }
+        }
+    }
+
+    fn parse_projection(&mut self) -> Result<Segment, QueryError> {
+        self.pos += 1;
+        let mut fields = Vec::new();
+        loop {
+            self.skip_ws();
+            let key = match self.peek() {
+                Some('"' | '\'') => self.parse_string()?,
+                _ => self.parse_name()?,
+            };
+            self.skip_ws();
+            let path = if self.eat(':') {
+                self.skip_ws();
+                self.parse_path()?
+            } else {
+                Path {
+                    absolute: false,
+                    segments: vec![Segment::Child(vec![Selector::Name(key.clone())])],
+                }
+            };
+            fields.push((key, path));
+            self.skip_ws();
+            if !self.eat(',') {
+                break;
+            }
+        }
+        self.expect('}')?;
+        Ok(Segment::Project(fields))
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, QueryError> {
+        let mut lhs = self.parse_and()?;
+        loop {
+            self.skip_ws();
+            if !self.eat_str("||") {
+                return Ok(lhs);
+            }
+            lhs = Expr::Or(Box::new(lhs), Box::new(self.parse_and()?));
+        }
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, QueryError> {
+        let mut lhs = self.parse_unary()?;
+        loop {
+            self.skip_ws();
+            if !self.eat_str("&&") {
+                return Ok(lhs);
+            }
+            lhs = Expr::And(Box::new(lhs), Box::new(self.parse_unary()?));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, QueryError> {
+        self.skip_ws();
+        if self.peek() == Some('!') && self.peek_at(1) != Some('=') {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+        if self.eat('(') {
+            let expr = self.parse_or()?;
+            self.expect(')')?;
+            return Ok(expr);
+        }
+        let lhs = self.parse_operand()?;
+        self.skip_ws();
+        if self.eat_str("=~") {
+            self.skip_ws();
+            return Ok(Expr::Matches(lhs, self.parse_regex()?));
+        }
+        let op = if self.eat_str("==") {
+            CmpOp::Eq
+        } else if self.eat_str("!=") {
+            CmpOp::Ne
+        } else if self.eat_str("<=") {
+            CmpOp::Le
+        } else if self.eat_str(">=") {
+            CmpOp::Ge
+        } else if self.eat('<') {
+            CmpOp::Lt
+        } else if self.eat('>') {
+            CmpOp::Gt
+        } else {
+            return Ok(Expr::Truthy(lhs));
+        };
+        Ok(Expr::Compare(lhs, op, self.parse_operand()?))
+    }
+
+    fn parse_regex(&mut self) -> Result<Regex, QueryError> {
+        let start = self.pos;
+        let (pattern, ignore_case) = if self.eat('/') {
+            let mut pattern = String::new();
+            loop {
+                match self.peek() {
+                    None => return Err(self.error("unterminated regex")),
+                    Some('/') => {
+                        self.pos += 1;
+                        break;
+                    }
+                    Some('\\') if self.peek_at(1) == Some('/') => {
+                        self.pos += 2;
+                        pattern.push('/');
+                    }
+                    Some(c) => {
+                        self.pos += 1;
+                        pattern.push(c);
+                    }
+                }
+            }
+            (pattern, self.eat('i'))
+        } else if matches!(self.peek(), Some('"' | '\'')) {
+            (self.parse_string()?, false)
+        } else {
+            return Err(self.error("expected a regex"));
+        };
+        let pattern = if ignore_case {
+            format!("(?i){pattern}")
+        } else {
+            pattern
+        };
+        Regex::new(&pattern).map_err(|e| QueryError {
+            position: start,
+            message: format!("invalid regex: {e}"),
+        })
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand, QueryError> {
+        self.skip_ws();
+        match self.peek() {
+            Some('@' | '$' | '.') => Ok(Operand::Path(self.parse_path()?)),
+            Some('"' | '\'') => Ok(Operand::Literal(Value::String(self.parse_string()?))),
+            Some(c) if c.is_ascii_digit() || c == '-' => self.parse_number(),
+            Some(c) if is_name_char(c) => {
+                let ident = self.peek_ident();
+                let len = ident.chars().count();
+                let literal = match ident.as_str() {
+                    "true" => Value::Bool(true),
+                    "false" => Value::Bool(false),
+                    "null" => Value::Null,
+                    "length" if self.peek_at(len) == Some('(') => {
+                        self.pos += len + 1;
+                        let inner = self.parse_operand()?;
+                        self.expect(')')?;
+                        return Ok(Operand::Length(Box::new(inner)));
+                    }
+                    _ => return Err(self.error(format!("unknown identifier '{ident}'"))),
+                };
+                self.pos += len;
+                Ok(Operand::Literal(literal))
+            }
+            _ => Err(self.error("expected a value or path")),
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<Operand, QueryError> {
+        let start = self.pos;
+        self.eat('-');
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+'))
+        {
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        match serde_json::from_str::<Value>(&text) {
+            Ok(v @ Value::Number(_)) => Ok(Operand::Literal(v)),
+            _ => Err(QueryError {
+                position: start,
+                message: format!("invalid number '{text}'"),
+            }),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// 执行
+// ---------------------------------------------------------------------------
+
+/// 在 `node` 上选出的子节点：借用的节点继续借用，临时构造的节点只能克隆
+fn map_refs<'a>(
+    node: Cow<'a, Value>,
+    f: impl for<'v> FnOnce(&'v Value, &mut Vec<&'v Value>),
+) -> Nodes<'a> {
+    match node {
+        Cow::Borrowed(v) => {
+            let mut out = Vec::new();
+            f(v, &mut out);
+            out.into_iter().map(Cow::Borrowed).collect()
+        }
+        Cow::Owned(v) => {
+            let mut out = Vec::new();
+            f(&v, &mut out);
+            out.into_iter().map(|x| Cow::Owned(x.clone())).collect()
+        }
+    }
+}
+
+fn children(v: &Value) -> Box<dyn Iterator<Item = &Value> + '_> {
+    match v {
+        Value::Array(a) => Box::new(a.iter()),
+        Value::Object(o) => Box::new(o.values()),
+        _ => Box::new(std::iter::empty()),
+    }
+}
+
+fn normalize_index(i: i64, len: usize) -> i64 {
+    if i < 0 { len as i64 + i } else { i }
+}
+
+fn slice_indices(
+    start: Option<i64>,
+    end: Option<i64>,
+    step: Option<i64>,
+    len: usize,
+) -> Vec<usize> {
+    let n = len as i64;
+    let step = step.unwrap_or(1);
+    let mut out = Vec::new();
+    if step > 0 {
+        let lower = normalize_index(start.unwrap_or(0), len).clamp(0, n);
+        let upper = normalize_index(end.unwrap_or(n), len).clamp(0, n);
+        let mut i = lower;
+        while i < upper {
+            out.push(i as usize);
+            i += step;
+        }
+    } else if step < 0 {
+        let upper = start.map_or(n - 1, |s| normalize_index(s, len).clamp(-1, n - 1));
+        let lower = end.map_or(-1, |e| normalize_index(e, len).clamp(-1, n - 1));
+        let mut i = upper;
+        while lower < i {
+            out.push(i as usize);
+            i += step;
+        }
+    }
+    out
+}
+
+fn select<'v>(selectors: &[Selector], v: &'v Value, root: &Value, out: &mut Vec<&'v Value>) {
+    for selector in selectors {
+        match selector {
+            Selector::Name(name) => {
+                if let Some(child) = v.as_object().and_then(|o| o.get(name)) {
+                    out.push(child);
+                }
+            }
+            Selector::Wildcard => out.extend(children(v)),
+            Selector::Index(i) => {
+                if let Some(a) = v.as_array() {
+                    let idx = normalize_index(*i, a.len());
+                    if idx >= 0
+                        && let Some(child) = a.get(idx as usize)
+                    {
+                        out.push(child);
+                    }
+                }
+            }
+            Selector::Slice(start, end, step) => {
+                if let Some(a) = v.as_array() {
+                    out.extend(
+                        slice_indices(*start, *end, *step, a.len())
+                            .into_iter()
+                            .map(|i| &a[i]),
+                    );
+                }
+            }
+            Selector::Filter(expr) => {
+                out.extend(children(v).filter(|child| expr.test(child, root)));
+            }
+        }
+    }
+}
+
+fn descend<'v>(selectors: &[Selector], v: &'v Value, root: &Value, out: &mut Vec<&'v Value>) {
+    select(selectors, v, root, out);
+    for child in children(v) {
+        descend(selectors, child, root, out);
+    }
+}
+
+impl Segment {
+    fn apply<'a>(&self, node: Cow<'a, Value>, root: &Value) -> Nodes<'a> {
+        match self {
+            Segment::Child(selectors) => map_refs(node, |v, out| select(selectors, v, root, out)),
+            Segment::Descendant(selectors) => {
+                map_refs(node, |v, out| descend(selectors, v, root, out))
+            }
+            Segment::Project(fields) => {
+                let mut obj = Map::new();
+                for (key, path) in fields {
+                    let value = path.first(&node, root).map_or(Value::Null, Cow::into_owned);
+                    obj.insert(key.clone(), value);
+                }
+                vec![Cow::Owned(Value::Object(obj))]
+            }
+        }
+    }
+}
+
+impl Path {
+    fn evaluate<'a>(&self, current: Cow<'a, Value>, root: &Value) -> Nodes<'a> {
+        let mut nodes = vec![current];
+        for segment in &self.segments {
+            nodes = nodes
+                .into_iter()
+                .flat_map(|node| segment.apply(node, root))
+                .collect();
+        }
+        nodes
+    }
+
+    /// 过滤条件和投影中的路径只取第一个结果
+    fn first<'a>(&self, current: &'a Value, root: &'a Value) -> Option<Cow<'a, Value>> {
+        let start = if self.absolute { root } else { current };
+        self.evaluate(Cow::Borrowed(start), root).into_iter().next()
+    }
+}
+
+impl Stage {
+    fn apply<'a>(&self, node: Cow<'a, Value>, root: &Value) -> Nodes<'a> {
+        match self {
+            // 管道中 `$` 指向本级输入
+            Stage::Path(path) => path.evaluate(node, root),
+            Stage::Length => value_length(&node)
+                .map(|n| Cow::Owned(Value::from(n)))
+                .into_iter()
+                .collect(),
+            Stage::Keys => {
+                let keys = match node.as_ref() {
+                    Value::Object(o) => o.keys().map(|k| Value::from(k.as_str())).collect(),
+                    Value::Array(a) => (0..a.len()).map(Value::from).collect(),
+                    _ => return Vec::new(),
+                };
+                vec![Cow::Owned(Value::Array(keys))]
+            }
+            Stage::Values => {
+                let values = match node.as_ref() {
+                    Value::Object(o) => o.values().cloned().collect(),
+                    Value::Array(_) => return vec![node],
+                    _ => return Vec::new(),
+                };
+                vec![Cow::Owned(Value::Array(values))]
+            }
+            Stage::First => map_refs(node, |v, out| {
+                out.extend(v.as_array().and_then(|a| a.first()));
+            }),
+            Stage::Last => map_refs(node, |v, out| {
+                out.extend(v.as_array().and_then(|a| a.last()));
+            }),
+            Stage::Select(expr) => {
+                if expr.test(&node, root) {
+                    vec![node]
+                } else {
+                    Vec::new()
+                }
+            }
+        }
+    }
+}
+
+fn value_length(v: &Value) -> Option<usize> {
+    match v {
+        Value::Array(a) => Some(a.len()),
+        Value::Object(o) => Some(o.len()),
+        Value::String(s) => Some(s.chars().count()),
+        Value::Null => Some(0),
+        _ => None,
+    }
+}
+
+impl Operand {
+    fn value<'a>(&'a self, current: &'a Value, root: &'a Value) -> Option<Cow<'a, Value>> {
+        match self {
+            Operand::Path(path) => path.first(current, root),
+            Operand::Literal(v) => Some(Cow::Borrowed(v)),
+            Operand::Length(inner) => inner
+                .value(current, root)
+                .and_then(|v| value_length(&v))
+                .map(|n| Cow::Owned(Value::from(n))),
+        }
+    }
+}
+
+/// 数字按数值比较（`1 == 1.0`），其余按结构比较
+fn json_eq(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64() == y.as_f64(),
+        (Value::Array(x), Value::Array(y)) => {
+            x.len() == y.len() && x.iter().zip(y).all(|(x, y)| json_eq(x, y))
+        }
+        (Value::Object(x), Value::Object(y)) => {
+            x.len() == y.len()
+                && x.iter()
+                    .all(|(k, v)| y.get(k).is_some_and(|other| json_eq(v, other)))
+        }
+        _ => a == b,
+    }
+}
+
+fn json_cmp(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64()?.partial_cmp(&y.as_f64()?),
+        (Value::String(x), Value::String(y)) => Some(x.cmp(y)),
+        _ => None,
+    }
+}
+
+impl Expr {
+    fn test(&self, current: &Value, root: &Value) -> bool {
+        match self {
+            Expr::Or(a, b) => a.test(current, root) || b.test(current, root),
+            Expr::And(a, b) => a.test(current, root) && b.test(current, root),
+            Expr::Not(e) => !e.test(current, root),
+            Expr::Truthy(operand) => operand
+                .value(current, root)
+                .is_some_and(|v| !matches!(v.as_ref(), Value::Null | Value::Bool(false))),
+            Expr::Matches(operand, re) => operand
+                .value(current, root)
+                .is_some_and(|v| v.as_str().is_some_and(|s| re.is_match(s))),
+            Expr::Compare(lhs, op, rhs) => {
+                let a = lhs.value(current, root);
+                let b = rhs.value(current, root);
+                let eq = match (&a, &b) {
+                    (None, None) => true,
+                    (Some(a), Some(b)) => json_eq(a, b),
+                    _ => false,
+                };
+                let ord = match (&a, &b) {
+                    (Some(a), Some(b)) => json_cmp(a, b),
+                    _ => None,
+                };
+                match op {
+                    CmpOp::Eq => eq,
+                    CmpOp::Ne => !eq,
+                    CmpOp::Lt => ord == Some(Ordering::Less),
+                    CmpOp::Gt => ord == Some(Ordering::Greater),
+                    CmpOp::Le => eq || ord == Some(Ordering::Less),
+                    CmpOp::Ge => eq || ord == Some(Ordering::Greater),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn store() -> Value {
+        json!({
+            "store": {
+                "book": [
+                    {"category": "reference", "author": "Nigel Rees", "title": "Sayings", "price": 8.95},
+                    {"category": "fiction", "author": "Evelyn Waugh", "title": "Sword", "price": 12.99},
+                    {"category": "fiction", "author": "Herman Melville", "title": "Moby Dick", "isbn": "0-553", "price": 8.99},
+                    {"category": "fiction", "author": "J. R. R. Tolkien", "title": "The Lord", "isbn": "0-395", "price": 22.99}
+                ],
+                "bicycle": {"color": "red", "price": 399}
+            }
+        })
+    }
+
+    fn q(doc: &Value, expr: &str) -> Vec<Value> {
+        query(doc, expr).unwrap_or_else(|e| panic!("{expr}: {e}"))
+    }
+
+    #[test]
+    fn test_child_index_and_wildcard() {
+        let doc = store();
+        assert_eq!(q(&doc, "$.store.bicycle.color"), vec![json!("red")]);
+        assert_eq!(q(&doc, "$['store']['bicycle']['price']"), vec![json!(399)]);
+        assert_eq!(q(&doc, "$.store.book[-1].title"), vec![json!("The Lord")]);
+        assert_eq!(q(&doc, "store.book[0].author"), vec![json!("Nigel Rees")]);
+        assert_eq!(q(&doc, "$.store.book[*].price").len(), 4);
+        assert_eq!(q(&doc, ".store.book[].price").len(), 4);
+        assert_eq!(q(&doc, "$.store.*").len(), 2);
+        assert!(q(&doc, "$.store.missing").is_empty());
+        assert!(q(&doc, "$.store.book[10]").is_empty());
+        assert_eq!(q(&doc, "."), vec![doc.clone()]);
+    }
+
+    #[test]
+    fn test_slices_and_unions() {
+        let doc = json!([0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(q(&doc, "$[1:3]"), vec![json!(1), json!(2)]);
+        assert_eq!(q(&doc, "$[:2]"), vec![json!(0), json!(1)]);
+        assert_eq!(q(&doc, "$[-2:]"), vec![json!(5), json!(6)]);
+        assert_eq!(q(&doc, "$[::3]"), vec![json!(0), json!(3), json!(6)]);
+        assert_eq!(q(&doc, "$[::-3]"), vec![json!(6), json!(3), json!(0)]);
+        assert_eq!(q(&doc, "$[5:1:-2]"), vec![json!(5), json!(3)]);
+        assert!(q(&doc, "$[::0]").is_empty());
+        assert_eq!(
+            q(&doc, "$[0, -1, 2:4]"),
+            vec![json!(0), json!(6), json!(2), json!(3)]
+        );
+
+        let doc = store();
+        assert_eq!(
+            q(&doc, "$.store.bicycle['color','price']"),
+            vec![json!("red"), json!(399)]
+        );
+    }
+
+    #[test]
+    fn test_recursive_descent() {
+        let doc = store();
+        assert_eq!(q(&doc, "$..author").len(), 4);
+        assert_eq!(q(&doc, "$..price").len(), 5);
+        assert_eq!(q(&doc, "$..book[2].title"), vec![json!("Moby Dick")]);
+        assert_eq!(
+            q(&doc, "$..[?(@.isbn)].title"),
+            vec![json!("Moby Dick"), json!("The Lord")]
+        );
+
+        let nested = json!({"a": {"a": {"a": 1}}});
+        assert_eq!(q(&nested, "$..a").len(), 3);
+        assert_eq!(q(&nested, "$..*").len(), 3);
+    }
+
+    #[test]
+    fn test_filters() {
+        let doc = store();
+        assert_eq!(
+            q(&doc, "$.store.book[?(@.price < 10)].title"),
+            vec![json!("Sayings"), json!("Moby Dick")]
+        );
+        assert_eq!(
+            q(
+                &doc,
+                r#"$.store.book[?(@.category == "fiction" && @.price >= 22.99)].title"#
+            ),
+            vec![json!("The Lord")]
+        );
+        assert_eq!(
+            q(&doc, "$.store.book[?(!@.isbn || @.price > 20)].title"),
+            vec![json!("Sayings"), json!("Sword"), json!("The Lord")]
+        );
+        assert_eq!(
+            q(&doc, "$.store.book[?@.author =~ /melville/i].title"),
+            vec![json!("Moby Dick")]
+        );
+        assert!(q(&doc, "$.store.book[?(@.price > $.store.bicycle.price)]").is_empty());
+        assert_eq!(
+            q(&doc, "$.store.book[?(length(@.title) == 5)].title"),
+            vec![json!("Sword")]
+        );
+        // 数字按数值比较
+        assert_eq!(q(&json!([1, 1.0, 2]), "$[?(@ == 1)]").len(), 2);
+        // 缺失字段只和缺失字段相等
+        assert_eq!(q(&doc, "$.store.book[?(@.isbn == @.missing)]").len(), 2);
+    }
+
+    #[test]
+    fn test_projection_and_pipes() {
+        let doc = store();
+        assert_eq!(
+            q(
+                &doc,
+                "$.store.book[?(@.price > 20)].{title, who: author, cost: @.price}"
+            ),
+            vec![json!({"title": "The Lord", "who": "J. R. R. Tolkien", "cost": 22.99})]
+        );
+        assert_eq!(
+            q(&doc, ".store.book[] | select(.isbn) | {isbn, missing}"),
+            vec![
+                json!({"isbn": "0-553", "missing": null}),
+                json!({"isbn": "0-395", "missing": null})
+            ]
+        );
+        assert_eq!(q(&doc, "$.store.book | length"), vec![json!(4)]);
+        assert_eq!(
+            q(&doc, "$.store.bicycle | keys"),
+            vec![json!(["color", "price"])]
+        );
+        assert_eq!(
+            q(&doc, "$.store.book | last | .title"),
+            vec![json!("The Lord")]
+        );
+        assert_eq!(q(&doc, "$.store.book[0] | values | length"), vec![json!(4)]);
+        // 字段名与内置函数同名时用 `.length`
+        assert_eq!(q(&json!({"length": 3}), ".length"), vec![json!(3)]);
+    }
+
+    #[test]
+    fn test_quoted_names_and_unicode() {
+        let doc = json!({"a b": {"名字": "张三"}, "it's": 1});
+        assert_eq!(q(&doc, "$['a b'].名字"), vec![json!("张三")]);
+        assert_eq!(q(&doc, r#"$["it's"]"#), vec![json!(1)]);
+        assert_eq!(q(&doc, r"$['it\'s']"), vec![json!(1)]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (expr, position) in [
+            ("$.a[", 4),
+            ("$.a[?(@.x == )]", 13),
+            ("$.a[?(@.x =~ /(/)]", 13),
+            ("$.a ^", 4),
+            ("$['a", 2),
+            ("$.a[?(@.x == foo)]", 13),
+        ] {
+            let err = Query::parse(expr).unwrap_err();
+            assert_eq!(err.position, position, "{expr}: {err}");
+        }
+    }
+
+    #[test]
+    fn test_compiled_query_is_reusable() {
+        let query = Query::parse("$[?(@.ok)].id").unwrap();
+        assert_eq!(
+            query.evaluate(&json!([{"id": 1, "ok": true}, {"id": 2, "ok": false}])),
+            vec![json!(1)]
+        );
+        assert_eq!(query.evaluate(&json!([{"id": 3, "ok": 1}])), vec![json!(3)]);
+        assert!(query.evaluate(&json!({"not": "array"})).is_empty());
+    }
+}
@@ -1,89 +1,105 @@
 use crate::jsonw::types::ParseOptions;
 
 pub fn sanitize_json_input(s: &str, options: ParseOptions) -> String {
-    let mut out = s.to_string();
-    if options.allow_comment {
-        out = strip_comments(&out);
-    }
-    if options.remove_special_chars {
-        // 移除控制字符，但保留换行和制表符
-        // 使用 chars() 而不是 bytes() 来正确处理 UTF-8 编码的多字节中文字符
-        out = out
-            .chars()
-            .filter(|c| !c.is_control() || *c == '\n' || *c == '\r' || *c == '\t')
-            .collect();
-    }
+    let mut out = String::with_capacity(s.len());
+    sanitize_into(s, options, |_, ch| out.push(ch));
     out
 }
 
-fn strip_comments(s: &str) -> String {
+/// 清理输入，同时返回清理后每个字节在原文中的字节偏移
+///
+/// 偏移表比清理结果多一项，对应原文末尾，便于把解析位置映射回原文的行列号。
+pub(crate) fn sanitize_json_input_with_offsets(
+    s: &str,
+    options: ParseOptions,
+) -> (String, Vec<usize>) {
     let mut out = String::with_capacity(s.len());
+    let mut offsets = Vec::with_capacity(s.len() + 1);
+    sanitize_into(s, options, |pos, ch| {
+        out.push(ch);
+        offsets.extend(pos..pos + ch.len_utf8());
+    });
+    offsets.push(s.len());
+    (out, offsets)
+}
+
+/// 逐字符输出清理结果，`emit` 收到字符在原文中的字节偏移
+fn sanitize_into(s: &str, options: ParseOptions, mut emit: impl FnMut(usize, char)) {
+    // 移除控制字符，但保留换行和制表符
+    // 使用 chars() 而不是 bytes() 来正确处理 UTF-8 编码的多字节中文字符
+    let mut emit = |pos: usize, ch: char| {
+        if !options.remove_special_chars || !ch.is_control() || matches!(ch, '\n' | '\r' | '\t') {
+            emit(pos, ch);
+        }
+    };
+    if options.allow_comment {
+        strip_comments(s, &mut emit);
+    } else {
+        s.char_indices().for_each(|(pos, ch)| emit(pos, ch));
+    }
+}
+
+fn strip_comments(s: &str, mut emit: impl FnMut(usize, char)) {
     let mut in_string = false;
     let mut escape = false;
-    let mut chars = s.chars().peekable();
+    let mut chars = s.char_indices().peekable();
 
-    while let Some(ch) = chars.next() {
+    while let Some((pos, ch)) = chars.next() {
         if escape {
-            out.push(ch);
+            emit(pos, ch);
             escape = false;
             continue;
         }
 
         if in_string {
             if ch == '\\' {
-                out.push(ch);
+                emit(pos, ch);
                 escape = true;
                 continue;
             }
             if ch == '"' {
                 in_string = false;
             }
-            out.push(ch);
+            emit(pos, ch);
             continue;
         }
 
         if ch == '"' {
             in_string = true;
-            out.push(ch);
+            emit(pos, ch);
             continue;
         }
 
         // Check for block comment /*
-        if ch == '/' && chars.peek().copied() == Some('*') {
+        if ch == '/' && chars.peek().map(|&(_, c)| c) == Some('*') {
             chars.next();
-            let mut newlines = 0;
-            while let Some(c) = chars.next() {
+            while let Some((p, c)) = chars.next() {
+                // Preserve newlines to maintain line numbers
                 if c == '\n' {
-                    newlines += 1;
+                    emit(p, c);
                 }
-                if c == '*' && chars.peek().copied() == Some('/') {
+                if c == '*' && chars.peek().map(|&(_, c)| c) == Some('/') {
                     chars.next();
                     break;
                 }
             }
-            // Preserve newlines to maintain line numbers
-            for _ in 0..newlines {
-                out.push('\n');
-            }
             continue;
         }
 
         // Check for line comment //
-        if ch == '/' && chars.peek().copied() == Some('/') {
+        if ch == '/' && chars.peek().map(|&(_, c)| c) == Some('/') {
             chars.next();
-            for c in chars.by_ref() {
+            for (p, c) in chars.by_ref() {
                 if c == '\n' {
-                    out.push('\n');
+                    emit(p, c);
                     break;
                 }
             }
             continue;
         }
 
-        out.push(ch);
+        emit(pos, ch);
     }
-
-    out
 }
 
 #[cfg(test)]
@@ -150,4 +166,17 @@ mod tests {
         // This is expected behavior - nested block comments are not standard
         assert!(serde_json::from_str::<Value>(&sanitized).is_err());
     }
+
+    #[test]
+    fn test_offsets_map_back_to_original() {
+        let s = "{ /* 注释 */ \"a\":\u{1}\"值\", // x\n\"b\": 2 }";
+        let (sanitized, offsets) = sanitize_json_input_with_offsets(s, ParseOptions::default());
+        assert_eq!(sanitized, sanitize_json_input(s, ParseOptions::default()));
+        assert_eq!(offsets.len(), sanitized.len() + 1);
+        for (i, b) in sanitized.bytes().enumerate() {
+            assert_eq!(s.as_bytes()[offsets[i]], b);
+        }
+        let b = sanitized.find("\"b\"").unwrap();
+        assert_eq!(&s[offsets[b]..], "\"b\": 2 }");
+    }
 }
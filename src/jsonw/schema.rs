@@ -0,0 +1,550 @@
+//! JSON Schema 推断与校验
+//!
+//! [`infer_schema`] 从若干样本合成 draft 2020-12 schema：同一位置出现的类型取并集，
+//! 所有样本都包含的字段记为 `required`，重复出现的少量字符串值收敛为 `enum`。
+//! [`SchemaValidator`] 基于 `jsonschema` 校验数据，并把每条违规的 JSON Pointer
+//! 映射回原文（清理注释和控制字符之前）的行号和列号。
+//!
+//! # 示例
+//!
+//! ```rust
+//! use rust_tools::jsonw::{infer_schema, ParseOptions, SchemaValidator};
+//! use serde_json::json;
+//!
+//! let schema = infer_schema(&[
+//!     json!({"id": 1, "status": "ok"}),
+//!     json!({"id": 2, "status": "ok", "note": null}),
+//!     json!({"id": 3, "status": "failed"}),
+//! ]);
+//! assert_eq!(schema["required"], json!(["id", "status"]));
+//! assert_eq!(schema["properties"]["status"]["enum"], json!(["failed", "ok"]));
+//!
+//! let validator = SchemaValidator::new(&schema).unwrap();
+//! let text = "{\n  // 注释\n  \"id\": \"x\",\n  \"status\": \"ok\"\n}";
+//! let violations = validator.validate_str(text, ParseOptions::default()).unwrap();
+//! assert_eq!(violations.len(), 1);
+//! assert_eq!(violations[0].pointer, "/id");
+//! assert_eq!((violations[0].line, violations[0].column), (3, 9));
+//! ```
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+};
+
+use serde_json::{Map, Value, json};
+
+use crate::jsonw::{sanitize::sanitize_json_input_with_offsets, types::ParseOptions};
+
+/// 推断结果声明的 schema 版本
+pub const SCHEMA_DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// 字符串取值不超过该数量且有重复时才推断为 `enum`
+const ENUM_LIMIT: usize = 10;
+
+/// 从样本推断 schema，样本为空时返回只含 `$schema` 的空 schema（接受任意值）
+pub fn infer_schema(samples: &[Value]) -> Value {
+    let mut shape = Shape::default();
+    for sample in samples {
+        shape.add(sample);
+    }
+    let mut schema = Map::new();
+    schema.insert("$schema".to_string(), json!(SCHEMA_DRAFT));
+    if let Value::Object(body) = shape.to_schema() {
+        schema.extend(body);
+    }
+    Value::Object(schema)
+}
+
+const NULL: u8 = 1;
+const BOOLEAN: u8 = 1 << 1;
+const INTEGER: u8 = 1 << 2;
+const NUMBER: u8 = 1 << 3;
+const STRING: u8 = 1 << 4;
+const ARRAY: u8 = 1 << 5;
+const OBJECT: u8 = 1 << 6;
+
+const TYPE_NAMES: [(u8, &str); 7] = [
+    (NULL, "null"),
+    (BOOLEAN, "boolean"),
+    (INTEGER, "integer"),
+    (NUMBER, "number"),
+    (STRING, "string"),
+    (ARRAY, "array"),
+    (OBJECT, "object"),
+];
+
+/// 同一位置上所有样本值的汇总
+#[derive(Default)]
+struct Shape {
+    types: u8,
+    /// 字符串出现次数和去重后的取值；取值超过 [`ENUM_LIMIT`] 后不再记录
+    strings: usize,
+    enum_values: Option<BTreeSet<String>>,
+    objects: usize,
+    /// 字段按首次出现的顺序排列，附带出现次数
+    properties: Vec<(String, usize, Shape)>,
+    index: HashMap<String, usize>,
+    items: Option<Box<Shape>>,
+}
+
+impl Shape {
+    fn add(&mut self, value: &Value) {
+        match value {
+            Value::Null => self.types |= NULL,
+            Value::Bool(_) => self.types |= BOOLEAN,
+            Value::Number(n) => {
+                self.types |= if n.is_f64() { NUMBER } else { INTEGER };
+            }
+            Value::String(s) => {
+                if self.types & STRING == 0 {
+                    self.enum_values = Some(BTreeSet::new());
+                }
+                self.types |= STRING;
+                self.strings += 1;
+                if let Some(values) = &mut self.enum_values {
+                    values.insert(s.clone());
+                    if values.len() > ENUM_LIMIT {
+                        self.enum_values = None;
+                    }
+                }
+            }
+            Value::Array(items) => {
+                self.types |= ARRAY;
+                for item in items {
+                    self.items.get_or_insert_default().add(item);
+                }
+            }
+            Value::Object(map) => {
+                self.types |= OBJECT;
+                self.objects += 1;
+                for (k, v) in map {
+                    let i = *self.index.entry(k.clone()).or_insert_with(|| {
+                        self.properties.push((k.clone(), 0, Shape::default()));
+                        self.properties.len() - 1
+                    });
+                    let (_, seen, shape) = &mut self.properties[i];
+                    *seen += 1;
+                    shape.add(v);
+                }
+            }
+        }
+    }
+
+    fn to_schema(&self) -> Value {
+        let mut schema = Map::new();
+        // integer 和 number 同时出现时合并为 number
+        let types = if self.types & NUMBER != 0 {
+            self.types & !INTEGER
+        } else {
+            self.types
+        };
+        let names: Vec<Value> = TYPE_NAMES
+            .iter()
+            .filter(|(bit, _)| types & bit != 0)
+            .map(|(_, name)| json!(name))
+            .collect();
+        match names.len() {
+            0 => {}
+            1 => {
+                schema.insert("type".to_string(), names[0].clone());
+            }
+            _ => {
+                schema.insert("type".to_string(), Value::Array(names));
+            }
+        }
+
+        if types == STRING
+            && let Some(values) = &self.enum_values
+            && values.len() < self.strings
+        {
+            schema.insert("enum".to_string(), json!(values));
+        }
+
+        if types & OBJECT != 0 {
+            let properties: Map<String, Value> = self
+                .properties
+                .iter()
+                .map(|(k, _, shape)| (k.clone(), shape.to_schema()))
+                .collect();
+            let required: Vec<Value> = self
+                .properties
+                .iter()
+                .filter(|(_, seen, _)| *seen == self.objects)
+                .map(|(k, _, _)| json!(k))
+                .collect();
+            schema.insert("properties".to_string(), Value::Object(properties));
+            if !required.is_empty() {
+                schema.insert("required".to_string(), Value::Array(required));
+            }
+        }
+
+        if let Some(items) = &self.items {
+            schema.insert("items".to_string(), items.to_schema());
+        }
+        Value::Object(schema)
+    }
+}
+
+/// schema 或数据无法使用的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaError {
+    /// schema 本身不合法
+    InvalidSchema(String),
+    /// 数据不是合法 JSON，行列号对应原文
+    InvalidJson {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSchema(message) => write!(f, "invalid schema: {message}"),
+            Self::InvalidJson {
+                line,
+                column,
+                message,
+            } => write!(f, "{message} at line {line} column {column}"),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// 一条校验违规
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+    /// 违规值的 JSON Pointer，根为空字符串
+    pub pointer: String,
+    pub message: String,
+    /// 违规值在原文中的起始行号，从 1 开始
+    pub line: usize,
+    /// 起始列号（按字符计），从 1 开始
+    pub column: usize,
+}
+
+/// 编译好的 schema 校验器
+pub struct SchemaValidator {
+    validator: jsonschema::Validator,
+}
+
+impl SchemaValidator {
+    pub fn new(schema: &Value) -> Result<Self, SchemaError> {
+        jsonschema::validator_for(schema)
+            .map(|validator| Self { validator })
+            .map_err(|e| SchemaError::InvalidSchema(e.to_string()))
+    }
+
+    /// 校验已解析的值，返回 `(JSON Pointer, 信息)`
+    pub fn validate(&self, value: &Value) -> Vec<(String, String)> {
+        self.validator
+            .iter_errors(value)
+            .map(|e| (e.instance_path().as_str().to_string(), e.to_string()))
+            .collect()
+    }
+
+    /// 按 [`ParseOptions`] 清理并解析 `text` 后校验，违规位置映射回 `text` 的行列号
+    pub fn validate_str(
+        &self,
+        text: &str,
+        options: ParseOptions,
+    ) -> Result<Vec<SchemaViolation>, SchemaError> {
+        let (sanitized, offsets) = sanitize_json_input_with_offsets(text, options);
+        let value: Value = serde_json::from_str(&sanitized).map_err(|e| {
+            // serde_json 的列号按字节计
+            let start = line_start(&sanitized, e.line());
+            let pos = (start + e.column().saturating_sub(1)).min(sanitized.len());
+            let (line, column) = line_column(text, offsets[pos]);
+            let text = e.to_string();
+            let suffix = format!(" at line {} column {}", e.line(), e.column());
+            SchemaError::InvalidJson {
+                line,
+                column,
+                message: text.strip_suffix(&suffix).unwrap_or(&text).to_string(),
+            }
+        })?;
+
+        Ok(self
+            .validate(&value)
+            .into_iter()
+            .map(|(pointer, message)| {
+                let pos = locate_pointer(&sanitized, &pointer).unwrap_or(0);
+                let (line, column) = line_column(text, offsets[pos]);
+                SchemaViolation {
+                    pointer,
+                    message,
+                    line,
+                    column,
+                }
+            })
+            .collect())
+    }
+}
+
+/// 第 `line` 行（从 1 开始）起始的字节偏移
+fn line_start(text: &str, line: usize) -> usize {
+    if line <= 1 {
+        return 0;
+    }
+    text.match_indices('\n')
+        .nth(line - 2)
+        .map_or(text.len(), |(i, _)| i + 1)
+}
+
+/// 字节偏移对应的行号和字符列号，均从 1 开始
+fn line_column(text: &str, mut pos: usize) -> (usize, usize) {
+    pos = pos.min(text.len());
+    while !text.is_char_boundary(pos) {
+        pos -= 1;
+    }
+    let before = &text[..pos];
+    let line = before.matches('\n').count() + 1;
+    let line_begin = before.rfind('\n').map_or(0, |i| i + 1);
+    (line, before[line_begin..].chars().count() + 1)
+}
+
+/// 在合法的 JSON 文本中找到 JSON Pointer 指向的值的起始字节偏移
+///
+/// 重复的键与 serde_json 一致取最后一个。
+fn locate_pointer(text: &str, pointer: &str) -> Option<usize> {
+    let mut scanner = Scanner {
+        bytes: text.as_bytes(),
+        pos: 0,
+    };
+    scanner.skip_ws();
+    if pointer.is_empty() {
+        return Some(scanner.pos);
+    }
+    for token in pointer.strip_prefix('/')?.split('/') {
+        let token = token.replace("~1", "/").replace("~0", "~");
+        match scanner.peek()? {
+            b'{' => {
+                scanner.pos += 1;
+                let mut found = None;
+                loop {
+                    scanner.skip_ws();
+                    if scanner.peek()? != b'"' {
+                        break;
+                    }
+                    let key = scanner.string()?;
+                    scanner.skip_ws();
+                    scanner.pos += 1; // ':'
+                    scanner.skip_ws();
+                    if key == token {
+                        found = Some(scanner.pos);
+                    }
+                    scanner.skip_value()?;
+                    scanner.skip_ws();
+                    if scanner.peek()? == b',' {
+                        scanner.pos += 1;
+                    }
+                }
+                scanner.pos = found?;
+            }
+            b'[' => {
+                let index: usize = token.parse().ok()?;
+                scanner.pos += 1;
+                for _ in 0..index {
+                    scanner.skip_ws();
+                    scanner.skip_value()?;
+                    scanner.skip_ws();
+                    if scanner.peek()? != b',' {
+                        return None;
+                    }
+                    scanner.pos += 1;
+                }
+                scanner.skip_ws();
+            }
+            _ => return None,
+        }
+    }
+    Some(scanner.pos)
+}
+
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Scanner<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    /// 读取一个字符串字面量并解码转义
+    fn string(&mut self) -> Option<String> {
+        let start = self.pos;
+        self.skip_string()?;
+        serde_json::from_slice(&self.bytes[start..self.pos]).ok()
+    }
+
+    fn skip_string(&mut self) -> Option<()> {
+        self.pos += 1;
+        loop {
+            match self.peek()? {
+                b'\\' => self.pos += 2,
+                b'"' => {
+                    self.pos += 1;
+                    return Some(());
+                }
+                _ => self.pos += 1,
+            }
+        }
+    }
+
+    fn skip_value(&mut self) -> Option<()> {
+        match self.peek()? {
+            b'"' => self.skip_string(),
+            b'{' | b'[' => {
+                let mut depth = 0usize;
+                loop {
+                    match self.peek()? {
+                        b'"' => {
+                            self.skip_string()?;
+                            continue;
+                        }
+                        b'{' | b'[' => depth += 1,
+                        b'}' | b']' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                self.pos += 1;
+                                return Some(());
+                            }
+                        }
+                        _ => {}
+                    }
+                    self.pos += 1;
+                }
+            }
+            _ => {
+                while !matches!(
+                    self.peek(),
+                    None | Some(b',' | b']' | b'}' | b' ' | b'\t' | b'\n' | b'\r')
+                ) {
+                    self.pos += 1;
+                }
+                Some(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_infer_merges_types_and_required() {
+        let schema = infer_schema(&[
+            json!({"a": 1, "b": "x", "c": [1, 2], "d": {"e": true}}),
+            json!({"a": 1.5, "b": null, "c": [], "d": {"e": false, "f": 1}}),
+        ]);
+        assert_eq!(
+            schema,
+            json!({
+                "$schema": SCHEMA_DRAFT,
+                "type": "object",
+                "properties": {
+                    "a": {"type": "number"},
+                    "b": {"type": ["null", "string"]},
+                    "c": {"type": "array", "items": {"type": "integer"}},
+                    "d": {
+                        "type": "object",
+                        "properties": {"e": {"type": "boolean"}, "f": {"type": "integer"}},
+                        "required": ["e"]
+                    }
+                },
+                "required": ["a", "b", "c", "d"]
+            })
+        );
+        assert_eq!(infer_schema(&[]), json!({"$schema": SCHEMA_DRAFT}));
+    }
+
+    #[test]
+    fn test_infer_enum_only_for_repeated_small_sets() {
+        let rows: Vec<Value> = (0..20)
+            .map(|i| {
+                let level = if i % 2 == 0 { "info" } else { "warn" };
+                json!({"level": level, "id": format!("u{i}")})
+            })
+            .collect();
+        let schema = infer_schema(&[Value::Array(rows)]);
+        let items = &schema["items"]["properties"];
+        assert_eq!(items["level"]["enum"], json!(["info", "warn"]));
+        assert_eq!(items["id"], json!({"type": "string"}));
+        // 单个样本不足以判断取值范围
+        assert_eq!(infer_schema(&[json!("a")])["enum"], Value::Null);
+    }
+
+    #[test]
+    fn test_inferred_schema_accepts_samples() {
+        let samples = [
+            json!([{"k": "a", "v": 1}, {"k": "b"}]),
+            json!([{"k": "a", "v": null}]),
+        ];
+        let validator = SchemaValidator::new(&infer_schema(&samples)).unwrap();
+        for sample in &samples {
+            assert!(validator.validate(sample).is_empty());
+        }
+        assert_eq!(validator.validate(&json!([{"k": "c"}])).len(), 1);
+    }
+
+    #[test]
+    fn test_violation_positions() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "a/b": {"type": "integer"},
+                "list": {"items": {"type": "string"}}
+            },
+            "required": ["need"]
+        });
+        let validator = SchemaValidator::new(&schema).unwrap();
+        let text = "{\n  /* 块注释\n  */ \"a/b\": \"x\", \"a/b\": 1.5,\n  \"list\": [\"ok\", {\"n\": [1]}, 2]\n}";
+        let mut found: Vec<(String, usize, usize)> = validator
+            .validate_str(text, ParseOptions::default())
+            .unwrap()
+            .into_iter()
+            .map(|v| (v.pointer, v.line, v.column))
+            .collect();
+        found.sort();
+        assert_eq!(
+            found,
+            vec![
+                (String::new(), 1, 1),
+                ("/a~1b".to_string(), 3, 25),
+                ("/list/1".to_string(), 4, 18),
+                ("/list/2".to_string(), 4, 30),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            SchemaValidator::new(&json!({"type": 1})),
+            Err(SchemaError::InvalidSchema(_))
+        ));
+        let validator = SchemaValidator::new(&json!({})).unwrap();
+        let err = validator
+            .validate_str("// 注释\n{\"中\": tru}", ParseOptions::default())
+            .unwrap_err();
+        assert_eq!(
+            err,
+            SchemaError::InvalidJson {
+                line: 2,
+                column: 10,
+                message: "expected ident".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,750 @@
+//! 流式 JSON 处理
+//!
+//! [`EventReader`] 是一个拉取式解析器：按字节读取输入，逐个产出 [`JsonEvent`]，
+//! 内存占用只与嵌套深度（和单个字符串的长度）有关，与文件大小无关。输入可以是
+//! 一个文档，也可以是多个首尾相接的顶层值（NDJSON 是其特例）。
+//!
+//! 在事件之上提供了几个常用操作：
+//!
+//! - [`format_stream`] - 流式格式化（缩进或压缩）
+//! - [`validate_stream`] - 只校验语法，统计值的个数和最大深度
+//! - [`grep_stream`] - 在所有标量中按正则搜索，返回 JSONPath 形式的路径
+//!
+//! 出错时 [`StreamError`] 给出行号、列号和字节偏移。
+//!
+//! # 示例
+//!
+//! ```rust
+//! use rust_tools::jsonw::stream::{format_stream, EventReader, JsonEvent};
+//!
+//! let input = br#"{"a": [1, true]} {"b": null}"#;
+//! let events: Vec<JsonEvent> = EventReader::new(&input[..]).map(Result::unwrap).collect();
+//! assert_eq!(events[0], JsonEvent::StartObject);
+//! assert_eq!(events[1], JsonEvent::Key("a".to_string()));
+//! assert_eq!(events.len(), 11);
+//!
+//! let mut out = Vec::new();
+//! let values = format_stream(&input[..], &mut out, None).unwrap();
+//! assert_eq!(values, 2);
+//! assert_eq!(String::from_utf8(out).unwrap(), "{\"a\":[1,true]}\n{\"b\":null}\n");
+//! ```
+
+use std::{
+    fmt,
+    io::{self, BufRead, Write},
+};
+
+use regex::Regex;
+use serde_json::{Number, Value};
+
+/// 解析事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonEvent {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    Key(String),
+    String(String),
+    Number(Number),
+    Bool(bool),
+    Null,
+}
+
+/// 流式解析错误，位置从 1 开始计数，`offset` 为字节偏移
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamError {
+    pub line: usize,
+    pub column: usize,
+    pub offset: u64,
+    pub message: String,
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {} column {} (byte {})",
+            self.message, self.line, self.column, self.offset
+        )
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// 顶层：下一个值或输入结束
+    TopLevel,
+    /// 冒号之后 / 数组逗号之后
+    Value,
+    /// `[` 之后：值或 `]`
+    ArrayFirst,
+    /// `{` 之后：键或 `}`
+    ObjectFirst,
+    /// 对象逗号之后
+    Key,
+    Colon,
+    /// 容器中的值之后：`,` 或结束符
+    AfterValue,
+}
+
+/// 拉取式 JSON 事件解析器
+pub struct EventReader<R> {
+    reader: R,
+    /// `true` 表示对象，`false` 表示数组
+    stack: Vec<bool>,
+    state: State,
+    line: usize,
+    column: usize,
+    offset: u64,
+    failed: bool,
+}
+
+impl<R: BufRead> EventReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            stack: Vec::new(),
+            state: State::TopLevel,
+            line: 1,
+            column: 1,
+            offset: 0,
+            failed: false,
+        }
+    }
+
+    /// 已消费的字节数
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// 当前行号（从 1 开始）
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// 当前嵌套深度
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    fn error(&self, message: impl Into<String>) -> StreamError {
+        StreamError {
+            line: self.line,
+            column: self.column,
+            offset: self.offset,
+            message: message.into(),
+        }
+    }
+
+    fn io_error(&self, e: io::Error) -> StreamError {
+        self.error(format!("read error: {e}"))
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, StreamError> {
+        match self.reader.fill_buf() {
+            Ok(buf) => Ok(buf.first().copied()),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => self.peek(),
+            Err(e) => Err(self.io_error(e)),
+        }
+    }
+
+    fn bump(&mut self, b: u8) {
+        self.reader.consume(1);
+        self.offset += 1;
+        if b == b'\n' {
+            self.line += 1;
+            self.column = 1;
+        } else if b & 0xC0 != 0x80 {
+            // UTF-8 续字节不计列
+            self.column += 1;
+        }
+    }
+
+    fn next_byte(&mut self) -> Result<Option<u8>, StreamError> {
+        let b = self.peek()?;
+        if let Some(b) = b {
+            self.bump(b);
+        }
+        Ok(b)
+    }
+
+    fn skip_ws(&mut self) -> Result<Option<u8>, StreamError> {
+        loop {
+            match self.peek()? {
+                Some(b @ (b' ' | b'\t' | b'\n' | b'\r')) => self.bump(b),
+                other => return Ok(other),
+            }
+        }
+    }
+
+    fn after_value(&mut self) {
+        self.state = if self.stack.is_empty() {
+            State::TopLevel
+        } else {
+            State::AfterValue
+        };
+    }
+
+    fn next_event(&mut self) -> Result<Option<JsonEvent>, StreamError> {
+        loop {
+            let b = self.skip_ws()?;
+            match self.state {
+                State::TopLevel => match b {
+                    None => return Ok(None),
+                    Some(b) => return self.parse_value(b).map(Some),
+                },
+                State::Value => match b {
+                    Some(b) => return self.parse_value(b).map(Some),
+                    None => return Err(self.error("unexpected end of input, expected a value")),
+                },
+                State::ArrayFirst => match b {
+                    Some(b']') => return Ok(Some(self.close(b']'))),
+                    Some(b) => return self.parse_value(b).map(Some),
+                    None => return Err(self.error("unexpected end of input in array")),
+                },
+                State::ObjectFirst | State::Key => match b {
+                    Some(b'"') => {
+                        let key = self.parse_string()?;
+                        self.state = State::Colon;
+                        return Ok(Some(JsonEvent::Key(key)));
+                    }
+                    Some(b'}') if self.state == State::ObjectFirst => {
+                        return Ok(Some(self.close(b'}')));
+                    }
+                    Some(_) => return Err(self.error("expected a string key")),
+                    None => return Err(self.error("unexpected end of input in object")),
+                },
+                State::Colon => match b {
+                    Some(b':') => {
+                        self.bump(b':');
+                        self.state = State::Value;
+                    }
+                    _ => return Err(self.error("expected ':'")),
+                },
+                State::AfterValue => {
+                    let in_object = self.stack.last() == Some(&true);
+                    match b {
+                        Some(b',') => {
+                            self.bump(b',');
+                            self.state = if in_object { State::Key } else { State::Value };
+                        }
+                        Some(b'}') if in_object => return Ok(Some(self.close(b'}'))),
+                        Some(b']') if !in_object => return Ok(Some(self.close(b']'))),
+                        Some(_) if in_object => return Err(self.error("expected ',' or '}'")),
+                        Some(_) => return Err(self.error("expected ',' or ']'")),
+                        None => return Err(self.error("unexpected end of input")),
+                    }
+                }
+            }
+        }
+    }
+
+    fn close(&mut self, b: u8) -> JsonEvent {
+        self.bump(b);
+        self.stack.pop();
+        self.after_value();
+        if b == b'}' {
+            JsonEvent::EndObject
+        } else {
+            JsonEvent::EndArray
+        }
+    }
+
+    fn parse_value(&mut self, b: u8) -> Result<JsonEvent, StreamError> {
+        let event = match b {
+            b'{' | b'[' => {
+                self.bump(b);
+                self.stack.push(b == b'{');
+                self.state = if b == b'{' {
+                    State::ObjectFirst
+                } else {
+                    State::ArrayFirst
+                };
+                return Ok(if b == b'{' {
+                    JsonEvent::StartObject
+                } else {
+                    JsonEvent::StartArray
+                });
+            }
+            b'"' => JsonEvent::String(self.parse_string()?),
+            b't' => {
+                self.expect_literal("true")?;
+                JsonEvent::Bool(true)
+            }
+            b'f' => {
+                self.expect_literal("false")?;
+                JsonEvent::Bool(false)
+            }
+            b'n' => {
+                self.expect_literal("null")?;
+                JsonEvent::Null
+            }
+            b'-' | b'0'..=b'9' => JsonEvent::Number(self.parse_number()?),
+            _ => return Err(self.error("expected a value")),
+        };
+        self.after_value();
+        Ok(event)
+    }
+
+    fn expect_literal(&mut self, word: &str) -> Result<(), StreamError> {
+        let start = self.error(format!("invalid literal, expected '{word}'"));
+        for expected in word.bytes() {
+            if self.next_byte()? != Some(expected) {
+                return Err(start);
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_number(&mut self) -> Result<Number, StreamError> {
+        let start = self.error("");
+        let mut text = String::new();
+        while let Some(b) = self.peek()? {
+            if !matches!(b, b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E') {
+                break;
+            }
+            self.bump(b);
+            text.push(b as char);
+        }
+        // serde_json 的数字解析比 str::parse 严格，能拒绝 `01`、`1.` 之类
+        match serde_json::from_str::<Value>(&text) {
+            Ok(Value::Number(n)) => Ok(n),
+            _ => Err(StreamError {
+                message: format!("invalid number '{text}'"),
+                ..start
+            }),
+        }
+    }
+
+    fn parse_hex4(&mut self) -> Result<u16, StreamError> {
+        let mut v = 0u16;
+        for _ in 0..4 {
+            let b = self
+                .next_byte()?
+                .ok_or_else(|| self.error("unexpected end of input in string"))?;
+            let d = (b as char)
+                .to_digit(16)
+                .ok_or_else(|| self.error("invalid \\u escape"))?;
+            v = (v << 4) | d as u16;
+        }
+        Ok(v)
+    }
+
+    fn parse_string(&mut self) -> Result<String, StreamError> {
+        let start = self.error("");
+        self.bump(b'"');
+        let mut buf: Vec<u8> = Vec::new();
+        loop {
+            let b = self
+                .peek()?
+                .ok_or_else(|| self.error("unexpected end of input in string"))?;
+            if b < 0x20 {
+                return Err(self.error("control character in string"));
+            }
+            self.bump(b);
+            match b {
+                b'"' => break,
+                b'\\' => {
+                    let e = self
+                        .next_byte()?
+                        .ok_or_else(|| self.error("unexpected end of input in string"))?;
+                    let ch = match e {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let hi = self.parse_hex4()?;
+                            let code = if (0xD800..0xDC00).contains(&hi) {
+                                if self.next_byte()? != Some(b'\\')
+                                    || self.next_byte()? != Some(b'u')
+                                {
+                                    return Err(self.error("unpaired surrogate in \\u escape"));
+                                }
+                                let lo = self.parse_hex4()?;
+                                if !(0xDC00..0xE000).contains(&lo) {
+                                    return Err(self.error("unpaired surrogate in \\u escape"));
+                                }
+                                0x10000 + (((hi as u32) - 0xD800) << 10) + ((lo as u32) - 0xDC00)
+                            } else {
+                                hi as u32
+                            };
+                            char::from_u32(code).ok_or_else(|| self.error("invalid \\u escape"))?
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    let mut tmp = [0u8; 4];
+                    buf.extend_from_slice(ch.encode_utf8(&mut tmp).as_bytes());
+                }
+                _ => buf.push(b),
+            }
+        }
+        String::from_utf8(buf).map_err(|_| StreamError {
+            message: "invalid UTF-8 in string".to_string(),
+            ..start
+        })
+    }
+}
+
+impl<R: BufRead> Iterator for EventReader<R> {
+    type Item = Result<JsonEvent, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let result = self.next_event().transpose();
+        if matches!(result, Some(Err(_))) {
+            self.failed = true;
+        }
+        result
+    }
+}
+
+/// [`validate_stream`] 的统计结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StreamStats {
+    /// 顶层值个数
+    pub values: usize,
+    /// 事件总数
+    pub events: usize,
+    pub max_depth: usize,
+    pub bytes: u64,
+}
+
+/// 只校验语法，不构造任何值
+pub fn validate_stream<R: BufRead>(reader: R) -> Result<StreamStats, StreamError> {
+    let mut events = EventReader::new(reader);
+    let mut stats = StreamStats::default();
+    while let Some(event) = events.next() {
+        event?;
+        stats.events += 1;
+        stats.max_depth = stats.max_depth.max(events.depth());
+        if events.depth() == 0 {
+            stats.values += 1;
+        }
+    }
+    stats.bytes = events.offset();
+    Ok(stats)
+}
+
+fn write_json_string<W: Write>(out: &mut W, s: &str) -> io::Result<()> {
+    serde_json::to_writer(&mut *out, s).map_err(io::Error::from)
+}
+
+/// 流式格式化：`indent` 为 `Some` 时缩进输出，否则压缩输出；每个顶层值单独一行
+///
+/// 返回顶层值个数。输出格式与 [`crate::jsonw::Json::to_pretty_string`] 一致。
+pub fn format_stream<R: BufRead, W: Write>(
+    reader: R,
+    mut out: W,
+    indent: Option<&str>,
+) -> Result<usize, StreamError> {
+    let mut events = EventReader::new(reader);
+    // 每层容器已写出的元素个数
+    let mut counts: Vec<usize> = Vec::new();
+    let mut after_key = false;
+    let mut values = 0;
+
+    while let Some(event) = events.next() {
+        let event = event?;
+        let io_err = |e: io::Error| StreamError {
+            line: events.line(),
+            column: 0,
+            offset: events.offset(),
+            message: format!("write error: {e}"),
+        };
+        let newline = |out: &mut W, depth: usize| -> io::Result<()> {
+            if let Some(indent) = indent {
+                out.write_all(b"\n")?;
+                for _ in 0..depth {
+                    out.write_all(indent.as_bytes())?;
+                }
+            }
+            Ok(())
+        };
+
+        let is_end = matches!(event, JsonEvent::EndObject | JsonEvent::EndArray);
+        let result = (|| -> io::Result<()> {
+            if is_end {
+                if counts.pop().unwrap_or(0) > 0 {
+                    newline(&mut out, counts.len())?;
+                }
+                out.write_all(if event == JsonEvent::EndObject {
+                    b"}"
+                } else {
+                    b"]"
+                })?;
+            } else {
+                // 新的元素（键或数组中的值）前写分隔符和换行
+                if !after_key && let Some(count) = counts.last_mut() {
+                    if *count > 0 {
+                        out.write_all(b",")?;
+                    }
+                    *count += 1;
+                    newline(&mut out, counts.len())?;
+                }
+                after_key = false;
+                match &event {
+                    JsonEvent::StartObject => {
+                        out.write_all(b"{")?;
+                        counts.push(0);
+                    }
+                    JsonEvent::StartArray => {
+                        out.write_all(b"[")?;
+                        counts.push(0);
+                    }
+                    JsonEvent::Key(k) => {
+                        write_json_string(&mut out, k)?;
+                        out.write_all(if indent.is_some() { b": " } else { b":" })?;
+                        after_key = true;
+                    }
+                    JsonEvent::String(s) => write_json_string(&mut out, s)?,
+                    JsonEvent::Number(n) => write!(out, "{n}")?,
+                    JsonEvent::Bool(b) => write!(out, "{b}")?,
+                    JsonEvent::Null => out.write_all(b"null")?,
+                    JsonEvent::EndObject | JsonEvent::EndArray => {}
+                }
+            }
+            if counts.is_empty() && !after_key {
+                values += 1;
+                out.write_all(b"\n")?;
+            }
+            Ok(())
+        })();
+        result.map_err(io_err)?;
+    }
+    out.flush()
+        .map_err(|e| events.error(format!("write error: {e}")))?;
+    Ok(values)
+}
+
+/// [`grep_stream`] 的一条匹配
+#[derive(Debug, Clone, PartialEq)]
+pub struct GrepMatch {
+    /// 所在顶层值的序号（从 0 开始），对 NDJSON 来说就是第几条记录
+    pub value_index: usize,
+    /// 标量所在的行号
+    pub line: usize,
+    /// JSONPath 形式的路径，可直接交给 [`crate::jsonw::query()`]
+    pub path: String,
+    pub value: Value,
+}
+
+enum Frame {
+    Array(usize),
+    Object(Option<String>),
+}
+
+fn render_path(frames: &[Frame]) -> String {
+    let mut path = String::from("$");
+    for frame in frames {
+        match frame {
+            Frame::Array(next) => path.push_str(&format!("[{}]", next.saturating_sub(1))),
+            Frame::Object(Some(k)) => {
+                let plain = !k.is_empty()
+                    && k.chars().all(|c| c.is_alphanumeric() || c == '_')
+                    && !k.starts_with(|c: char| c.is_ascii_digit());
+                if plain {
+                    path.push('.');
+                    path.push_str(k);
+                } else {
+                    path.push_str(&format!(
+                        "['{}']",
+                        k.replace('\\', "\\\\").replace('\'', "\\'")
+                    ));
+                }
+            }
+            Frame::Object(None) => {}
+        }
+    }
+    path
+}
+
+/// 在所有标量（字符串按原文，其余按 JSON 文本）中搜索 `pattern`，每个匹配回调一次
+///
+/// 返回匹配个数。只保留当前路径，内存占用与文件大小无关。
+pub fn grep_stream<R: BufRead>(
+    reader: R,
+    pattern: &Regex,
+    mut on_match: impl FnMut(GrepMatch),
+) -> Result<usize, StreamError> {
+    let mut events = EventReader::new(reader);
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut value_index = 0;
+    let mut matches = 0;
+
+    while let Some(event) = events.next() {
+        let line = events.line();
+        let event = event?;
+        if let JsonEvent::Key(k) = event {
+            if let Some(Frame::Object(key)) = frames.last_mut() {
+                *key = Some(k);
+            }
+            continue;
+        }
+        if matches!(event, JsonEvent::EndObject | JsonEvent::EndArray) {
+            frames.pop();
+        } else {
+            if let Some(Frame::Array(next)) = frames.last_mut() {
+                *next += 1;
+            }
+            let scalar = match event {
+                JsonEvent::StartObject => {
+                    frames.push(Frame::Object(None));
+                    None
+                }
+                JsonEvent::StartArray => {
+                    frames.push(Frame::Array(0));
+                    None
+                }
+                JsonEvent::String(s) => Some(Value::String(s)),
+                JsonEvent::Number(n) => Some(Value::Number(n)),
+                JsonEvent::Bool(b) => Some(Value::Bool(b)),
+                _ => Some(Value::Null),
+            };
+            if let Some(value) = scalar {
+                let text = match &value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                if pattern.is_match(&text) {
+                    matches += 1;
+                    on_match(GrepMatch {
+                        value_index,
+                        line,
+                        path: render_path(&frames),
+                        value,
+                    });
+                }
+            }
+        }
+        if frames.is_empty() {
+            value_index += 1;
+        }
+    }
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jsonw::{Json, query};
+    use serde_json::json;
+
+    fn events(input: &str) -> Result<Vec<JsonEvent>, StreamError> {
+        EventReader::new(input.as_bytes()).collect()
+    }
+
+    fn format(input: &str, indent: Option<&str>) -> Result<String, StreamError> {
+        let mut out = Vec::new();
+        format_stream(input.as_bytes(), &mut out, indent)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_events() {
+        assert_eq!(
+            events(r#" {"k": ["s\né😀", -1.5e3, false, null, {}, []]} "#).unwrap(),
+            vec![
+                JsonEvent::StartObject,
+                JsonEvent::Key("k".to_string()),
+                JsonEvent::StartArray,
+                JsonEvent::String("s\né😀".to_string()),
+                JsonEvent::Number(Number::from_f64(-1500.0).unwrap()),
+                JsonEvent::Bool(false),
+                JsonEvent::Null,
+                JsonEvent::StartObject,
+                JsonEvent::EndObject,
+                JsonEvent::StartArray,
+                JsonEvent::EndArray,
+                JsonEvent::EndArray,
+                JsonEvent::EndObject,
+            ]
+        );
+        assert!(events("").unwrap().is_empty());
+        assert_eq!(events("1 2\n3").unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_error_positions() {
+        for (input, line, column, offset) in [
+            ("{\"a\": 1,\n  }", 2, 3, 11),
+            ("[1, 2", 1, 6, 5),
+            ("{\"a\" 1}", 1, 6, 5),
+            ("[01]", 1, 2, 1),
+            ("[tru]", 1, 2, 1),
+            ("\n\n  \"中文\u{1}\"", 3, 6, 11),
+            ("[1 2]", 1, 4, 3),
+            ("{\"a\":1]", 1, 7, 6),
+        ] {
+            let err = events(input).unwrap_err();
+            assert_eq!(
+                (err.line, err.column, err.offset),
+                (line, column, offset),
+                "{input:?}: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_matches_pretty_string() {
+        let doc = json!({"a": [1, {"b": "x&<>", "c": []}, {}], "d": {"e": null}, "f": 1.5});
+        let text = doc.to_string();
+        assert_eq!(
+            format(&text, Some("  ")).unwrap(),
+            Json::new(doc.clone()).to_pretty_string()
+        );
+        assert_eq!(format(&text, None).unwrap(), format!("{text}\n"));
+        // 多个顶层值各占一行
+        assert_eq!(
+            format("1 [] {\"a\":{}}\n", None).unwrap(),
+            "1\n[]\n{\"a\":{}}\n"
+        );
+        assert!(format("[1,", None).is_err());
+    }
+
+    #[test]
+    fn test_validate_stats() {
+        let stats = validate_stream(&b"{\"a\":[[1]]}\n{\"b\":2}\n"[..]).unwrap();
+        assert_eq!(stats.values, 2);
+        assert_eq!(stats.max_depth, 3);
+        assert_eq!(stats.bytes, 20);
+        assert_eq!(validate_stream(&b"{\"a\":}"[..]).unwrap_err().column, 6);
+    }
+
+    #[test]
+    fn test_grep_paths_resolve() {
+        let input = "{\"users\": [{\"name\": \"bob\", \"a b\": \"bobby\"}, {\"name\": \"al\"}]}\n{\"x\": [\"bob\"]}";
+        let mut found = Vec::new();
+        let n = grep_stream(input.as_bytes(), &Regex::new("^bob").unwrap(), |m| {
+            found.push(m)
+        })
+        .unwrap();
+        assert_eq!(n, 3);
+        let paths: Vec<(usize, usize, &str)> = found
+            .iter()
+            .map(|m| (m.value_index, m.line, m.path.as_str()))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                (0, 1, "$.users[0].name"),
+                (0, 1, "$.users[0]['a b']"),
+                (1, 2, "$.x[0]"),
+            ]
+        );
+        // 路径可以交给 query 取回同一个值
+        let first: Value = serde_json::from_str(input.lines().next().unwrap()).unwrap();
+        assert_eq!(
+            query(&first, &found[1].path).unwrap(),
+            vec![found[1].value.clone()]
+        );
+    }
+}
@@ -102,3 +102,53 @@ pub struct DiffEntry {
     /// 新值
     pub new: Value,
 }
+
+/// 差异条目的类型，由新旧值是否为 `null` 决定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+impl DiffEntry {
+    /// 旧值为 `null` 视为新增，新值为 `null` 视为删除，其余为修改
+    pub fn kind(&self) -> DiffKind {
+        match (self.old.is_null(), self.new.is_null()) {
+            (true, false) => DiffKind::Added,
+            (false, true) => DiffKind::Removed,
+            _ => DiffKind::Modified,
+        }
+    }
+}
+
+/// RFC 6902 JSON Patch 操作
+///
+/// `path` / `from` 为 RFC 6901 JSON Pointer（`~` 转义为 `~0`，`/` 转义为 `~1`），
+/// 序列化后即标准的 `{"op": "add", "path": "/a/0", "value": ...}` 形式。
+///
+/// # 示例
+///
+/// ```rust
+/// use rust_tools::jsonw::PatchOp;
+/// use serde_json::json;
+///
+/// let op = PatchOp::Replace {
+///     path: "/a~1b".to_string(),
+///     value: json!(1),
+/// };
+/// assert_eq!(
+///     serde_json::to_value(&op).unwrap(),
+///     json!({"op": "replace", "path": "/a~1b", "value": 1})
+/// );
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOp {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: Value },
+}
@@ -2,6 +2,10 @@ use std::sync::LazyLock;
 
 use regex::Regex;
 
+pub mod precise;
+
+pub use precise::{acos, asin, atan, cos, e, exp, ln, log, log10, pi, pow, sin, sqrt, tan};
+
 static MULTI_MINUS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(--)+").unwrap());
 static POSITIVE_NUMBER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\+(\d+)").unwrap());
 
@@ -0,0 +1,758 @@
+//! 任意精度的小数运算和超越函数
+//!
+//! 数值以十进制字符串传入传出，内部用 [`BigInt`] 定点数计算，不受 `f64` 或
+//! `rust_decimal` 的位数限制。`digits` 是结果保留的小数位数，按四舍五入（远离零）
+//! 舍入并去掉末尾的 0。
+//!
+//! 超越函数先多算若干保护位；舍入位恰好落在 5 附近无法判定时加大保护位重算，
+//! 因此结果是正确舍入的。输入不是合法数字或超出定义域时返回 `None`。
+//!
+//! # 示例
+//!
+//! ```rust
+//! use rust_tools::strw::calc::precise;
+//!
+//! assert_eq!(
+//!     precise::sqrt("2", 30).unwrap(),
+//!     "1.41421356237309504880168872421"
+//! );
+//! assert_eq!(precise::pow("2", "0.5", 5).unwrap(), "1.41421");
+//! assert_eq!(precise::div("1", "8", 2).unwrap(), "0.13");
+//! assert_eq!(precise::ln("0", 10), None);
+//! ```
+
+use std::cmp::Ordering;
+
+use num_bigint::BigInt;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+/// 首次计算使用的保护位数
+const GUARD_DIGITS: u32 = 12;
+
+/// 保护位加倍重算的上限，超过后直接舍入（例如精确等于 ...5 的结果）
+const MAX_GUARD_DIGITS: u32 = 400;
+
+/// 结果允许的误差（以最后一个保护位为单位）
+const ERROR_ULPS: u32 = 16;
+
+/// 结果整数部分的位数上限，超过时返回 `None`（溢出）
+///
+/// 级数按 `整数位数 + 小数位数` 的精度计算，上万位时单次调用就要数十秒，
+/// 因此限制在几千位：`exp(9000)` 以内仍可计算，`exp(100000)` 立即返回。
+const MAX_MAGNITUDE_DIGITS: u32 = 4_000;
+
+/// 整数次幂结果的位数上限（二进制位）
+const MAX_POW_BITS: u64 = 1 << 24;
+
+/// 十进制定点数：值为 `m / 10^scale`
+#[derive(Debug, Clone)]
+struct Dec {
+    m: BigInt,
+    scale: u32,
+}
+
+impl Dec {
+    fn parse(s: &str) -> Option<Dec> {
+        let s = s.trim();
+        let (negative, body) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let (int, frac) = body.split_once('.').unwrap_or((body, ""));
+        if (int.is_empty() && frac.is_empty())
+            || !int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit())
+        {
+            return None;
+        }
+        let digits = format!("{int}{frac}");
+        let m: BigInt = digits.parse().ok()?;
+        Some(Dec {
+            m: if negative { -m } else { m },
+            scale: u32::try_from(frac.len()).ok()?,
+        })
+    }
+
+    fn from_int(n: i64) -> Dec {
+        Dec {
+            m: BigInt::from(n),
+            scale: 0,
+        }
+    }
+
+    /// 换算成 `w` 位小数的定点整数，多余的位向零截断
+    fn fixed(&self, w: u32) -> BigInt {
+        rescale(&self.m, self.scale, w)
+    }
+
+    fn is_integer(&self) -> bool {
+        (&self.m % pow10(self.scale)).is_zero()
+    }
+
+    /// 整数部分的位数，纯小数为 0
+    fn int_digits(&self) -> u32 {
+        let len = self.m.magnitude().to_string().len() as u32;
+        len.saturating_sub(self.scale)
+    }
+
+    fn to_f64(&self) -> f64 {
+        format_fixed(&self.m, self.scale)
+            .parse()
+            .unwrap_or(f64::NAN)
+    }
+
+    fn align(&self, other: &Dec) -> (BigInt, BigInt, u32) {
+        let scale = self.scale.max(other.scale);
+        (self.fixed(scale), other.fixed(scale), scale)
+    }
+}
+
+fn pow10(n: u32) -> BigInt {
+    BigInt::from(10u32).pow(n)
+}
+
+fn rescale(v: &BigInt, from: u32, to: u32) -> BigInt {
+    match from.cmp(&to) {
+        Ordering::Less => v * pow10(to - from),
+        Ordering::Equal => v.clone(),
+        Ordering::Greater => v / pow10(from - to),
+    }
+}
+
+/// 去掉 `drop` 位小数，四舍五入（远离零）
+fn round_off(v: &BigInt, drop: u32) -> BigInt {
+    if drop == 0 {
+        return v.clone();
+    }
+    let unit = pow10(drop);
+    let (q, r) = (
+        v.magnitude() / unit.magnitude(),
+        v.magnitude() % unit.magnitude(),
+    );
+    let q = BigInt::from(if r * 2u32 >= *unit.magnitude() {
+        q + 1u32
+    } else {
+        q
+    });
+    if v.is_negative() { -q } else { q }
+}
+
+/// 格式化定点数，去掉末尾的 0
+fn format_fixed(v: &BigInt, scale: u32) -> String {
+    let digits = v.magnitude().to_string();
+    let scale = scale as usize;
+    let (int, frac) = if digits.len() > scale {
+        digits.split_at(digits.len() - scale)
+    } else {
+        ("0", digits.as_str())
+    };
+    let frac = format!("{frac:0>scale$}");
+    let frac = frac.trim_end_matches('0');
+    let sign = if v.is_negative() { "-" } else { "" };
+    if frac.is_empty() {
+        format!("{sign}{int}")
+    } else {
+        format!("{sign}{int}.{frac}")
+    }
+}
+
+/// 正确舍入到 `digits` 位小数
+///
+/// `f(w)` 返回 `值 * 10^w` 的近似，误差不超过 [`ERROR_ULPS`] 个单位。
+fn correctly_rounded(digits: usize, f: impl Fn(u32) -> Option<BigInt>) -> Option<String> {
+    let digits = u32::try_from(digits).ok()?;
+    let mut guard = GUARD_DIGITS;
+    loop {
+        let v = f(digits + guard)?;
+        let unit = pow10(guard);
+        let half = &unit / 2u32;
+        let r = BigInt::from(v.magnitude() % unit.magnitude());
+        let ambiguous = (r - half).abs() <= BigInt::from(ERROR_ULPS);
+        if !ambiguous || guard >= MAX_GUARD_DIGITS {
+            return Some(format_fixed(&round_off(&v, guard), digits));
+        }
+        guard *= 2;
+    }
+}
+
+/// 定点数 `v`（`w` 位小数）整数部分的位数
+fn fixed_int_digits(v: &BigInt, w: u32) -> u32 {
+    (v.magnitude().to_string().len() as u32).saturating_sub(w)
+}
+
+// ─── 精确运算 ─────────────────────────────────────────────────────────────────
+
+/// 精确加法
+pub fn add(a: &str, b: &str) -> Option<String> {
+    let (a, b, scale) = Dec::parse(a)?.align(&Dec::parse(b)?);
+    Some(format_fixed(&(a + b), scale))
+}
+
+/// 精确减法
+pub fn sub(a: &str, b: &str) -> Option<String> {
+    let (a, b, scale) = Dec::parse(a)?.align(&Dec::parse(b)?);
+    Some(format_fixed(&(a - b), scale))
+}
+
+/// 精确乘法
+pub fn mul(a: &str, b: &str) -> Option<String> {
+    let (a, b) = (Dec::parse(a)?, Dec::parse(b)?);
+    Some(format_fixed(&(a.m * b.m), a.scale + b.scale))
+}
+
+/// 除法，舍入到 `digits` 位小数；除数为 0 时返回 `None`
+pub fn div(a: &str, b: &str, digits: usize) -> Option<String> {
+    let (a, b) = (Dec::parse(a)?, Dec::parse(b)?);
+    div_dec(&a, &b, u32::try_from(digits).ok()?)
+}
+
+fn div_dec(a: &Dec, b: &Dec, digits: u32) -> Option<String> {
+    if b.m.is_zero() {
+        return None;
+    }
+    // a/b * 10^digits = a.m * 10^(b.scale + digits) / (b.m * 10^a.scale)，按余数精确舍入
+    let num = &a.m * pow10(b.scale + digits);
+    let den = &b.m * pow10(a.scale);
+    let (n, d) = (num.magnitude(), den.magnitude());
+    let mut q = n / d;
+    if (n % d) * 2u32 >= *d {
+        q += 1u32;
+    }
+    let q = BigInt::from(q);
+    let q = if num.is_negative() != den.is_negative() {
+        -q
+    } else {
+        q
+    };
+    Some(format_fixed(&q, digits))
+}
+
+/// 截断除法的余数，符号与被除数相同；除数为 0 时返回 `None`
+pub fn rem(a: &str, b: &str) -> Option<String> {
+    let (a, b, scale) = Dec::parse(a)?.align(&Dec::parse(b)?);
+    if b.is_zero() {
+        return None;
+    }
+    Some(format_fixed(&(a % b), scale))
+}
+
+/// 舍入到 `digits` 位小数（四舍五入，远离零）
+pub fn round(a: &str, digits: usize) -> Option<String> {
+    let a = Dec::parse(a)?;
+    let digits = u32::try_from(digits).ok()?;
+    if a.scale <= digits {
+        return Some(format_fixed(&a.m, a.scale));
+    }
+    Some(format_fixed(&round_off(&a.m, a.scale - digits), digits))
+}
+
+/// 向下取整
+pub fn floor(a: &str) -> Option<String> {
+    let a = Dec::parse(a)?;
+    let unit = pow10(a.scale);
+    let mut q = &a.m / &unit;
+    if a.m.is_negative() && !(&a.m % &unit).is_zero() {
+        q -= 1;
+    }
+    Some(q.to_string())
+}
+
+/// 向上取整
+pub fn ceil(a: &str) -> Option<String> {
+    let a = Dec::parse(a)?;
+    let unit = pow10(a.scale);
+    let mut q = &a.m / &unit;
+    if a.m.is_positive() && !(&a.m % &unit).is_zero() {
+        q += 1;
+    }
+    Some(q.to_string())
+}
+
+/// 比较两个数的大小
+pub fn cmp(a: &str, b: &str) -> Option<Ordering> {
+    let (a, b, _) = Dec::parse(a)?.align(&Dec::parse(b)?);
+    Some(a.cmp(&b))
+}
+
+// ─── 常数 ─────────────────────────────────────────────────────────────────────
+
+/// `sum((-1)^k / ((2k+1) n^(2k+1)))`，即 `atan(1/n)`；`alternate` 为假时是 `atanh(1/n)`
+fn arctan_inverse(n: u32, w: u32, alternate: bool) -> BigInt {
+    let n2 = BigInt::from(n) * n;
+    let mut term = pow10(w) / n;
+    let mut sum = term.clone();
+    let mut k = 1u32;
+    loop {
+        term /= &n2;
+        if term.is_zero() {
+            return sum;
+        }
+        let t = &term / (2 * k + 1);
+        if alternate && k % 2 == 1 {
+            sum -= t;
+        } else {
+            sum += t;
+        }
+        k += 1;
+    }
+}
+
+/// Machin 公式：`pi = 16 atan(1/5) - 4 atan(1/239)`
+fn pi_fixed(w: u32) -> BigInt {
+    let w2 = w + 10;
+    let pi = arctan_inverse(5, w2, true) * 16 - arctan_inverse(239, w2, true) * 4;
+    rescale(&pi, w2, w)
+}
+
+/// `ln 2 = 2 atanh(1/3)`
+fn ln2_fixed(w: u32) -> BigInt {
+    let w2 = w + 10;
+    rescale(&(arctan_inverse(3, w2, false) * 2), w2, w)
+}
+
+/// 圆周率，保留 `digits` 位小数
+pub fn pi(digits: usize) -> String {
+    correctly_rounded(digits, |w| Some(pi_fixed(w))).unwrap_or_default()
+}
+
+/// 自然常数 e，保留 `digits` 位小数
+pub fn e(digits: usize) -> String {
+    exp("1", digits).unwrap_or_default()
+}
+
+// ─── 指数和对数 ───────────────────────────────────────────────────────────────
+
+/// `exp(x)`，`x` 是 `xs` 位小数的定点数，结果为 `w` 位小数
+fn exp_fixed(x: &BigInt, xs: u32, w: u32) -> Option<BigInt> {
+    let xf = format_fixed(x, xs).parse::<f64>().ok()?;
+    let magnitude = (xf * std::f64::consts::LOG10_E).max(0.0).ceil();
+    if magnitude > f64::from(MAX_MAGNITUDE_DIGITS) {
+        return None;
+    }
+    // 先把 x 缩小 2^k 倍让级数快速收敛，最后平方 k 次；每次平方损失约 0.3 位
+    let k = (xf.abs().max(1.0).log2().ceil() as u32) + 8;
+    let w2 = w + magnitude as u32 + 10 + k / 3;
+    let r = rescale(x, xs, w2) >> k as usize;
+    let one = pow10(w2);
+    let mut sum = one.clone();
+    let mut term = one.clone();
+    let mut n = 1u32;
+    loop {
+        term = term * &r / (&one * n);
+        if term.is_zero() {
+            break;
+        }
+        sum += &term;
+        n += 1;
+    }
+    for _ in 0..k {
+        sum = &sum * &sum / &one;
+    }
+    Some(rescale(&sum, w2, w))
+}
+
+/// `ln(a)`，`a > 0`：先按 2 的幂缩放到 `[1/√2, √2]`，再用 `2 atanh((y-1)/(y+1))`
+fn ln_fixed(a: &Dec, w: u32) -> Option<BigInt> {
+    if !a.m.is_positive() {
+        return None;
+    }
+    let num = a.m.clone();
+    let den = pow10(a.scale);
+    let mut e2 = num.bits() as i64 - den.bits() as i64;
+    let w2 = w + 10 + e2.unsigned_abs().to_string().len() as u32;
+    let one = pow10(w2);
+    let mut y = if e2 >= 0 {
+        num * &one / (den << e2 as usize)
+    } else {
+        (num << (-e2) as usize) * &one / den
+    };
+    let one_sq = &one * &one;
+    if &y * &y > &one_sq * 2u32 {
+        y >>= 1usize;
+        e2 += 1;
+    } else if &y * &y * 2u32 < one_sq {
+        y <<= 1usize;
+        e2 -= 1;
+    }
+    let z = (&y - &one) * &one / (&y + &one);
+    let z2 = &z * &z / &one;
+    let mut term = z.clone();
+    let mut sum = z;
+    let mut k = 1u32;
+    loop {
+        term = term * &z2 / &one;
+        if term.is_zero() {
+            break;
+        }
+        sum += &term / (2 * k + 1);
+        k += 1;
+    }
+    let ln = sum * 2u32 + ln2_fixed(w2) * e2;
+    Some(rescale(&ln, w2, w))
+}
+
+/// 平方根
+pub fn sqrt(a: &str, digits: usize) -> Option<String> {
+    let a = Dec::parse(a)?;
+    if a.m.is_negative() {
+        return None;
+    }
+    correctly_rounded(digits, |w| {
+        // sqrt(m / 10^s) * 10^w = sqrt(m * 10^(2w - s))，必要时多算几位保证 2w >= s
+        let w2 = w.max(a.scale.div_ceil(2));
+        let v = (&a.m * pow10(2 * w2 - a.scale)).sqrt();
+        Some(rescale(&v, w2, w))
+    })
+}
+
+/// 自然指数
+pub fn exp(a: &str, digits: usize) -> Option<String> {
+    let a = Dec::parse(a)?;
+    correctly_rounded(digits, |w| exp_fixed(&a.m, a.scale, w))
+}
+
+/// 自然对数，`a` 必须为正
+pub fn ln(a: &str, digits: usize) -> Option<String> {
+    let a = Dec::parse(a)?;
+    correctly_rounded(digits, |w| ln_fixed(&a, w))
+}
+
+/// 以 10 为底的对数
+pub fn log10(a: &str, digits: usize) -> Option<String> {
+    log(a, "10", digits)
+}
+
+/// 以 `base` 为底的对数；`base` 必须为正且不等于 1
+pub fn log(a: &str, base: &str, digits: usize) -> Option<String> {
+    let (a, base) = (Dec::parse(a)?, Dec::parse(base)?);
+    // 底数接近 1 时 ln(base) ≈ base - 1 很小，按它的前导零个数补足精度
+    let diff = &base.m - pow10(base.scale);
+    if diff.is_zero() {
+        return None;
+    }
+    let lead = base
+        .scale
+        .saturating_sub(diff.magnitude().to_string().len() as u32);
+    correctly_rounded(digits, |w| {
+        let w2 = w + 10 + 2 * lead + a.int_digits().to_string().len() as u32 + 1;
+        let lb = ln_fixed(&base, w2)?;
+        let la = ln_fixed(&a, w2)?;
+        Some(la * pow10(w) / lb)
+    })
+}
+
+/// 乘方：整数指数精确计算，否则按 `exp(b ln a)` 计算，此时底数不能为负
+pub fn pow(a: &str, b: &str, digits: usize) -> Option<String> {
+    let (a, b) = (Dec::parse(a)?, Dec::parse(b)?);
+    if b.is_integer() {
+        let n = &b.m / pow10(b.scale);
+        return int_pow(&a, &n, u32::try_from(digits).ok()?);
+    }
+    if a.m.is_negative() {
+        return None;
+    }
+    if a.m.is_zero() {
+        return b.m.is_positive().then(|| "0".to_string());
+    }
+    let magnitude = (b.to_f64() * a.to_f64().log10()).max(0.0);
+    if magnitude.is_nan() || magnitude > f64::from(MAX_MAGNITUDE_DIGITS) {
+        return None;
+    }
+    correctly_rounded(digits, |w| {
+        let w2 = w + magnitude.ceil() as u32 + 10 + b.int_digits();
+        let y = ln_fixed(&a, w2)? * &b.m / pow10(b.scale);
+        exp_fixed(&y, w2, w)
+    })
+}
+
+fn int_pow(a: &Dec, n: &BigInt, digits: u32) -> Option<String> {
+    if a.m.is_zero() {
+        return (!n.is_negative()).then(|| if n.is_zero() { "1" } else { "0" }.to_string());
+    }
+    let e = n.magnitude().to_u32()?;
+    if a.m.abs() > BigInt::one() && a.m.bits().saturating_mul(u64::from(e)) > MAX_POW_BITS {
+        return None;
+    }
+    let p = Dec {
+        m: a.m.pow(e),
+        scale: a.scale.checked_mul(e)?,
+    };
+    if n.is_negative() {
+        div_dec(&Dec::from_int(1), &p, digits)
+    } else if p.scale <= digits {
+        Some(format_fixed(&p.m, p.scale))
+    } else {
+        Some(format_fixed(&round_off(&p.m, p.scale - digits), digits))
+    }
+}
+
+// ─── 三角函数 ─────────────────────────────────────────────────────────────────
+
+/// `(sin x, cos x)`：先减去 2π 的整数倍，再用泰勒级数
+fn sin_cos_fixed(x: &Dec, w: u32) -> (BigInt, BigInt) {
+    let w2 = w + 10 + x.int_digits();
+    let one = pow10(w2);
+    let two_pi = pi_fixed(w2) * 2u32;
+    let xv = x.fixed(w2);
+    let turns = round_off(&(&xv * pow10(1) / &two_pi), 1);
+    let r = xv - turns * &two_pi;
+    let r2 = &r * &r / &one;
+
+    let mut sin = r.clone();
+    let mut term = r;
+    let mut k = 1u32;
+    loop {
+        term = -(term * &r2 / &one) / ((2 * k) * (2 * k + 1));
+        if term.is_zero() {
+            break;
+        }
+        sin += &term;
+        k += 1;
+    }
+
+    let mut cos = one.clone();
+    let mut term = one.clone();
+    let mut k = 1u32;
+    loop {
+        term = -(term * &r2 / &one) / ((2 * k - 1) * (2 * k));
+        if term.is_zero() {
+            break;
+        }
+        cos += &term;
+        k += 1;
+    }
+    (rescale(&sin, w2, w), rescale(&cos, w2, w))
+}
+
+pub fn sin(a: &str, digits: usize) -> Option<String> {
+    let a = Dec::parse(a)?;
+    correctly_rounded(digits, |w| Some(sin_cos_fixed(&a, w).0))
+}
+
+pub fn cos(a: &str, digits: usize) -> Option<String> {
+    let a = Dec::parse(a)?;
+    correctly_rounded(digits, |w| Some(sin_cos_fixed(&a, w).1))
+}
+
+/// 正切；余弦恰好为 0 时返回 `None`
+pub fn tan(a: &str, digits: usize) -> Option<String> {
+    let a = Dec::parse(a)?;
+    correctly_rounded(digits, |w| {
+        // 接近 π/2 时余弦很小，按结果的整数位数补足精度
+        let (s, c) = sin_cos_fixed(&a, w + 5);
+        if c.is_zero() {
+            return None;
+        }
+        let extra = 2 * fixed_int_digits(&(&s * pow10(w + 5) / &c), w + 5);
+        let w2 = w + 5 + extra;
+        let (s, c) = sin_cos_fixed(&a, w2);
+        if c.is_zero() {
+            return None;
+        }
+        Some(rescale(&(s * pow10(w2) / c), w2, w))
+    })
+}
+
+/// `atan(t)`，`|t| <= 1`：先用半角公式缩小 3 次，再用泰勒级数
+fn atan_small(t: BigInt, w: u32) -> BigInt {
+    let one = pow10(w);
+    let one_sq = &one * &one;
+    let mut t = t;
+    for _ in 0..3 {
+        t = &t * &one / (&one + (&one_sq + &t * &t).sqrt());
+    }
+    let t2 = &t * &t / &one;
+    let mut term = t.clone();
+    let mut sum = t;
+    let mut k = 1u32;
+    loop {
+        term = -(term * &t2 / &one);
+        if term.is_zero() {
+            break;
+        }
+        sum += &term / (2 * k + 1);
+        k += 1;
+    }
+    sum * 8u32
+}
+
+/// `|x| > 1` 时用 `atan x = ±π/2 - atan(1/x)`
+fn atan_fixed(x: &BigInt, w: u32) -> BigInt {
+    let w2 = w + 10;
+    let one = pow10(w2);
+    let x = rescale(x, w, w2);
+    let result = if x.magnitude() > one.magnitude() {
+        let half_pi = pi_fixed(w2) / 2u32;
+        let inner = atan_small(&one * &one / &x, w2);
+        if x.is_negative() {
+            -half_pi - inner
+        } else {
+            half_pi - inner
+        }
+    } else {
+        atan_small(x, w2)
+    };
+    rescale(&result, w2, w)
+}
+
+pub fn atan(a: &str, digits: usize) -> Option<String> {
+    let a = Dec::parse(a)?;
+    correctly_rounded(digits, |w| {
+        let w2 = w + 10 + a.int_digits();
+        Some(rescale(&atan_fixed(&a.fixed(w2), w2), w2, w))
+    })
+}
+
+/// `asin x = 2 atan(x / (1 + sqrt(1 - x²)))`，`|x| <= 1`
+fn asin_fixed(a: &Dec, w: u32) -> Option<BigInt> {
+    let w2 = w + 10;
+    let one = pow10(w2);
+    // 1 - x² 精确计算，避免 x 接近 ±1 时的抵消误差
+    let sq_scale = 2 * a.scale;
+    let rest = pow10(sq_scale) - &a.m * &a.m;
+    if rest.is_negative() {
+        return None;
+    }
+    let root = (rest * pow10(2 * w2)).sqrt() / pow10(a.scale);
+    let t = a.fixed(w2) * &one / (&one + root);
+    Some(rescale(&(atan_fixed(&t, w2) * 2u32), w2, w))
+}
+
+/// 反正弦，`|a| <= 1`
+pub fn asin(a: &str, digits: usize) -> Option<String> {
+    let a = Dec::parse(a)?;
+    correctly_rounded(digits, |w| asin_fixed(&a, w))
+}
+
+/// 反余弦，`|a| <= 1`
+pub fn acos(a: &str, digits: usize) -> Option<String> {
+    let a = Dec::parse(a)?;
+    correctly_rounded(digits, |w| {
+        let w2 = w + 5;
+        let v = pi_fixed(w2) / 2u32 - asin_fixed(&a, w2)?;
+        Some(rescale(&v, w2, w))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PI_60: &str = "3.141592653589793238462643383279502884197169399375105820974945";
+    const E_60: &str = "2.718281828459045235360287471352662497757247093699959574966968";
+
+    #[test]
+    fn exact_arithmetic() {
+        assert_eq!(add("0.0003", "0").unwrap(), "0.0003");
+        assert_eq!(sub("1", "1.25").unwrap(), "-0.25");
+        assert_eq!(mul("-0.5", "0.02").unwrap(), "-0.01");
+        assert_eq!(div("2", "3", 5).unwrap(), "0.66667");
+        assert_eq!(div("-1", "8", 2).unwrap(), "-0.13");
+        assert_eq!(div("7", "0.0003", 3).unwrap(), "23333.333");
+        assert_eq!(div("1", "0", 3), None);
+        assert_eq!(rem("-7.5", "2").unwrap(), "-1.5");
+        assert_eq!(round("2.4999", 0).unwrap(), "2");
+        assert_eq!(floor("-1.5").unwrap(), "-2");
+        assert_eq!(ceil("1.01").unwrap(), "2");
+        assert_eq!(cmp("0.10", "0.1"), Some(Ordering::Equal));
+        assert_eq!(add("1e5", "1"), None);
+    }
+
+    #[test]
+    fn constants_at_high_precision() {
+        assert_eq!(pi(60), PI_60);
+        assert_eq!(e(60), E_60);
+        assert_eq!(pi(0), "3");
+    }
+
+    #[test]
+    fn roots_exponentials_and_logarithms() {
+        assert_eq!(
+            sqrt("2", 50).unwrap(),
+            "1.41421356237309504880168872420969807856967187537695"
+        );
+        assert_eq!(sqrt("0.0001", 10).unwrap(), "0.01");
+        assert_eq!(sqrt("-1", 10), None);
+        assert_eq!(
+            ln("2", 50).unwrap(),
+            "0.69314718055994530941723212145817656807550013436026"
+        );
+        assert_eq!(
+            ln("10", 40).unwrap(),
+            "2.3025850929940456840179914546843642076011"
+        );
+        assert_eq!(
+            ln("0.001", 30).unwrap(),
+            "-6.907755278982137052053974364053"
+        );
+        assert_eq!(log10("1000", 30).unwrap(), "3");
+        assert_eq!(log("8", "2", 20).unwrap(), "3");
+        assert_eq!(log("5", "1", 5), None);
+        assert_eq!(
+            exp("-1", 40).unwrap(),
+            "0.3678794411714423215955237701614608674458"
+        );
+        assert_eq!(
+            exp("100", 5).unwrap(),
+            "26881171418161354484126255515800135873611118.77374"
+        );
+        assert_eq!(
+            pow("2", "0.5", 40).unwrap(),
+            "1.4142135623730950488016887242096980785697"
+        );
+        assert_eq!(pow("10", "-3", 5).unwrap(), "0.001");
+        assert_eq!(pow("1.5", "2", 1).unwrap(), "2.3");
+        assert_eq!(pow("-8", "3", 0).unwrap(), "-512");
+        assert_eq!(pow("-8", "0.5", 5), None);
+        assert_eq!(pow("0", "-1", 5), None);
+    }
+
+    #[test]
+    fn huge_exponentials_overflow_quickly() {
+        let start = std::time::Instant::now();
+        assert_eq!(exp("100000", 16), None);
+        assert_eq!(pow("10", "100000.5", 16), None);
+        assert!(exp("9000", 0).is_some());
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    }
+
+    #[test]
+    fn trigonometry() {
+        assert_eq!(
+            sin("1", 40).unwrap(),
+            "0.8414709848078965066525023216302989996226"
+        );
+        assert_eq!(
+            cos("1", 40).unwrap(),
+            "0.5403023058681397174009366074429766037323"
+        );
+        assert_eq!(
+            tan("1", 40).unwrap(),
+            "1.5574077246549022305069748074583601730873"
+        );
+        assert_eq!(sin("0", 10).unwrap(), "0");
+        assert_eq!(sin(PI_60, 30).unwrap(), "0");
+        assert_eq!(
+            sin("1000000", 30).unwrap(),
+            "-0.349993502171292952117652486781"
+        );
+        assert_eq!(
+            atan("1", 60).unwrap(),
+            "0.785398163397448309615660845819875721049292349843776455243736"
+        );
+        assert_eq!(
+            atan("-123.5", 30).unwrap(),
+            "-1.562699337757158510491360633468"
+        );
+        assert_eq!(
+            asin("0.5", 40).unwrap(),
+            "0.5235987755982988730771072305465838140329"
+        );
+        assert_eq!(asin("-1", 20).unwrap(), "-1.57079632679489661923");
+        assert_eq!(acos("1", 20).unwrap(), "0");
+        assert_eq!(
+            acos("-0.25", 30).unwrap(),
+            "1.823476581936975272716979128633"
+        );
+        assert_eq!(asin("1.0001", 5), None);
+    }
+}
@@ -4,7 +4,7 @@
 //!
 //! ## 子模块概览
 //!
-//! - [`calc`] - 字符串计算（长度、字节数等）
+//! - [`calc`] - 字符串计算（长度、字节数等），[`calc::precise`] 提供任意精度的超越函数
 //! - [`check`] - 字符串检查（是否为空、空白字符等）
 //! - [`find`] - 字符串查找（查找子串、字符等）
 //! - [`mod@format`] - 字符串格式化（文本换行、缩进等）
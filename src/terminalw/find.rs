@@ -2,11 +2,12 @@ use std::{
     path::Path,
     sync::{
         Arc, Condvar, Mutex,
-        atomic::{AtomicBool, AtomicI32, AtomicI64, Ordering},
+        atomic::{AtomicBool, AtomicI32, AtomicI64, AtomicUsize, Ordering},
     },
 };
 
 use crate::commonw::types::FastSet;
+use crate::cw::work_stealing;
 
 pub struct SyncSet {
     inner: Mutex<FastSet<String>>,
@@ -51,48 +52,18 @@ pub static NUM_PRINT: AtomicI64 = AtomicI64::new(5);
 pub static COUNT: AtomicI64 = AtomicI64::new(0);
 pub static MAX_LEVEL: AtomicI32 = AtomicI32::new(i32::MAX);
 
-struct Semaphore {
-    cap: usize,
-    state: Mutex<usize>,
-    cv: Condvar,
-}
-
-impl Semaphore {
-    fn new(cap: usize) -> Self {
-        Self {
-            cap,
-            state: Mutex::new(0),
-            cv: Condvar::new(),
-        }
-    }
-
-    fn acquire(&self) {
-        let mut used = self.state.lock().unwrap();
-        while *used >= self.cap {
-            used = self.cv.wait(used).unwrap();
-        }
-        *used += 1;
-    }
-
-    fn release(&self) {
-        let mut used = self.state.lock().unwrap();
-        *used = used.saturating_sub(1);
-        self.cv.notify_one();
-    }
-}
-
-static MAX_THREADS: std::sync::LazyLock<Mutex<Arc<Semaphore>>> =
-    std::sync::LazyLock::new(|| Mutex::new(Arc::new(Semaphore::new(4))));
+static THREADS: AtomicUsize = AtomicUsize::new(4);
 
 pub fn change_threads(num: usize) {
-    let mut lock = MAX_THREADS.lock().unwrap();
-    *lock = Arc::new(Semaphore::new(num.max(1)));
+    THREADS.store(num.max(1), Ordering::Relaxed);
 }
 
+#[deprecated(note = "find_blocking returns when the search is done; no WaitGroup is needed")]
 pub struct WaitGroup {
     inner: Arc<(Mutex<usize>, Condvar)>,
 }
 
+#[allow(deprecated)]
 impl WaitGroup {
     pub fn new() -> Self {
         Self {
@@ -124,6 +95,7 @@ impl WaitGroup {
     }
 }
 
+#[allow(deprecated)]
 impl Default for WaitGroup {
     fn default() -> Self {
         Self::new()
@@ -141,6 +113,9 @@ fn is_probably_text_file(path: &Path) -> bool {
     n == 0 || (!sample.contains(&0) && std::str::from_utf8(sample).is_ok())
 }
 
+/// 在后台线程里执行 [`find_blocking`]，完成后调用 `wg.done()`；调用方用 `wg.wait()` 等待。
+#[deprecated(note = "use find_blocking, which returns when the search is done")]
+#[allow(deprecated)]
 pub fn find<F>(root_dir: &str, task: Arc<F>, wg: Arc<WaitGroup>, level: i32)
 where
     F: Fn(String) + Send + Sync + 'static,
@@ -148,23 +123,38 @@ where
     wg.add(1);
     let root = root_dir.to_string();
     std::thread::spawn(move || {
-        find_impl(&root, &task, &wg, level);
+        find_blocking(&root, |path| task(path), level);
         wg.done();
     });
 }
 
-fn find_impl<F>(root_dir: &str, task: &Arc<F>, wg: &Arc<WaitGroup>, level: i32)
+/// 从 `root_dir` 开始并发遍历目录，对每个通过过滤的文本文件调用 `task`，全部完成后返回。
+///
+/// 子目录作为任务交给 [`work_stealing::run`]，线程数由 [`change_threads`] 设置；
+/// `COUNT` 达到 `NUM_PRINT` 后所有线程尽快停止。
+pub fn find_blocking<F>(root_dir: &str, task: F, level: i32)
 where
-    F: Fn(String) + Send + Sync + 'static,
+    F: Fn(String) + Sync,
 {
-    if level > MAX_LEVEL.load(Ordering::Relaxed) {
-        return;
-    }
-    let sem = { Arc::clone(&*MAX_THREADS.lock().unwrap()) };
-    sem.acquire();
-    let _guard = ScopeGuard::new(move || sem.release());
+    let threads = THREADS.load(Ordering::Relaxed);
+    work_stealing::run(
+        threads,
+        [(root_dir.to_string(), level)],
+        |(dir, level), ctx| {
+            if COUNT.load(Ordering::Relaxed) >= NUM_PRINT.load(Ordering::Relaxed) {
+                ctx.stop();
+                return;
+            }
+            visit_dir(&dir, level, &task, |sub| ctx.spawn((sub, level + 1)));
+        },
+    );
+}
 
-    if COUNT.load(Ordering::Relaxed) >= NUM_PRINT.load(Ordering::Relaxed) {
+fn visit_dir<F>(root_dir: &str, level: i32, task: &F, mut spawn: impl FnMut(String))
+where
+    F: Fn(String),
+{
+    if level > MAX_LEVEL.load(Ordering::Relaxed) {
         return;
     }
 
@@ -187,10 +177,7 @@ where
         }
 
         if path.is_dir() {
-            let next = path.to_string_lossy().to_string();
-            let task = Arc::clone(task);
-            let wg2 = Arc::clone(wg);
-            find(&next, task, wg2, level + 1);
+            spawn(path.to_string_lossy().to_string());
             continue;
         }
 
@@ -208,21 +195,3 @@ where
         }
     }
 }
-
-struct ScopeGuard<F: FnOnce()> {
-    f: Option<F>,
-}
-
-impl<F: FnOnce()> ScopeGuard<F> {
-    fn new(f: F) -> Self {
-        Self { f: Some(f) }
-    }
-}
-
-impl<F: FnOnce()> Drop for ScopeGuard<F> {
-    fn drop(&mut self) {
-        if let Some(f) = self.f.take() {
-            f();
-        }
-    }
-}
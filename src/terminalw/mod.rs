@@ -20,10 +20,10 @@
 //! - 深度限制
 //!
 //! ```rust,ignore
-//! use rust_tools::terminalw::find;
+//! use rust_tools::terminalw::find_blocking;
 //!
-//! // find 函数需要 Arc<Task> 和 Arc<WaitGroup> 参数
-//! // 详细用法请参考 terminalw::find 模块文档
+//! // 基于 cw::work_stealing 并发遍历，所有文件处理完后返回
+//! find_blocking(".", |path: String| println!("{path}"), 0);
 //! ```
 //!
 //! ### Glob 匹配
@@ -71,8 +71,10 @@ pub mod utils;
 pub use filepath::{glob_case_insensitive, glob_paths};
 pub use find::{
     CHECK_EXTENSION, COUNT, EXCLUDE, EXTENSIONS, FILE_NAMES_NOT_CHECK, FILE_NAMES_TO_CHECK,
-    MAX_LEVEL, NUM_PRINT, SyncSet, VERBOSE, WaitGroup, change_threads, find,
+    MAX_LEVEL, NUM_PRINT, SyncSet, VERBOSE, change_threads, find_blocking,
 };
+#[allow(deprecated)]
+pub use find::{WaitGroup, find};
 pub use internal::actiontype::ActionList;
 pub use parser::{Parser, ParserOption, disable_parser_number, new_parser};
 pub use utils::{add_quote, format_file_extensions, map_to_string};
@@ -89,4 +89,28 @@ mod tests {
         // Call first args again - should recompute
         assert_eq!(cached_triple(1, 2, 3), 6);
     }
+
+    // Test the sharded concurrent cache backend
+    #[lru_cache(cap = 64, ttl_ms = 1000, concurrent)]
+    fn cached_concurrent_len(s: String) -> usize {
+        println!("Computing cached_concurrent_len(\"{s}\")...");
+        s.len()
+    }
+
+    #[test]
+    fn test_lru_cache_macro_concurrent() {
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                std::thread::spawn(|| {
+                    for i in 0..50 {
+                        let s = format!("key-{}", i % 10);
+                        assert_eq!(cached_concurrent_len(s.clone()), s.len());
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+    }
 }